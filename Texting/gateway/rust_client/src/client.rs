@@ -171,6 +171,33 @@ pub fn emit_response(response: &Response, raw: bool, pretty: bool) -> String {
     }
 }
 
+/// Emit a `batch` response as one line per item, in request order, each
+/// shaped like `emit_response` output. A rejected batch is emitted whole.
+pub fn emit_batch(response: &Response, pretty: bool) -> String {
+    let items = response
+        .result
+        .clone()
+        .and_then(|result| serde_json::from_value::<Vec<Response>>(result).ok());
+    match items {
+        Some(items) if response.ok => items
+            .iter()
+            .map(|item| emit_response(item, false, pretty))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => emit_response(response, false, pretty),
+    }
+}
+
+/// Whether a `batch` response succeeded and so did every item in it.
+pub fn batch_ok(response: &Response) -> bool {
+    response.ok
+        && response
+            .result
+            .as_ref()
+            .and_then(|result| result.as_array())
+            .is_some_and(|items| items.iter().all(|item| item["ok"] == true))
+}
+
 // Custom serialization for Response (needed for raw output)
 impl serde::Serialize for Response {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -3,6 +3,9 @@
 //! This client speaks the NDJSON protocol over a Unix domain socket,
 //! providing a significant speedup over the Python client by eliminating
 //! the Python interpreter startup overhead.
+//!
+//! `batch --file` sends several requests in one exchange and prints one
+//! output line per request, in file order.
 
 mod client;
 mod protocol;
@@ -10,6 +13,7 @@ mod protocol;
 use clap::{Parser, Subcommand};
 use protocol::{OutputControls, Request};
 use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 /// Fast Rust client for the Wolfies iMessage daemon.
@@ -142,6 +146,38 @@ enum Command {
         #[arg(long)]
         since: Option<String>,
     },
+
+    /// Run several requests in one exchange (one output line per request)
+    Batch {
+        /// NDJSON file with one {"method": ..., "params": {...}} per line
+        #[arg(long)]
+        file: PathBuf,
+    },
+}
+
+/// Batch items from an NDJSON file, blank lines skipped, with the output
+/// controls added to each item's params.
+fn read_batch_file(path: &Path, controls: &OutputControls) -> Result<Vec<Value>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut items = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let item: Value = serde_json::from_str(line).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        let method = item
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("line {}: missing \"method\"", index + 1))?;
+        let mut params = match item.get("params") {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(params)) => params.clone(),
+            Some(_) => return Err(format!("line {}: \"params\" must be an object", index + 1)),
+        };
+        controls.apply_to(&mut params);
+        items.push(json!({ "method": method, "params": params }));
+    }
+    Ok(items)
 }
 
 fn main() -> ExitCode {
@@ -229,17 +265,35 @@ fn main() -> ExitCode {
             controls.apply_to(&mut params);
             Request::new("bundle", Value::Object(params))
         }
+
+        Command::Batch { file } => match read_batch_file(file, &controls) {
+            Ok(items) => Request::batch(items),
+            Err(message) => {
+                let error_json = json!({
+                    "ok": false,
+                    "error": { "code": "INVALID_BATCH_FILE", "message": message, "details": null }
+                });
+                eprintln!("{}", error_json);
+                return ExitCode::from(2);
+            }
+        },
     };
+    let is_batch = matches!(cli.command, Command::Batch { .. });
 
     // Create client and send request
     let daemon_client = client::DaemonClient::new(&cli.socket, cli.timeout);
 
     match daemon_client.call(&request) {
         Ok(response) => {
-            let output = client::emit_response(&response, cli.raw_response, cli.pretty);
+            let output = if is_batch && !cli.raw_response {
+                client::emit_batch(&response, cli.pretty)
+            } else {
+                client::emit_response(&response, cli.raw_response, cli.pretty)
+            };
             println!("{}", output);
 
-            if response.ok {
+            let ok = if is_batch { client::batch_ok(&response) } else { response.ok };
+            if ok {
                 ExitCode::from(0)
             } else {
                 ExitCode::from(1)
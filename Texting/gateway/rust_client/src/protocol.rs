@@ -39,6 +39,12 @@ impl Request {
     pub fn no_params(method: impl Into<String>) -> Self {
         Self::new(method, Value::Object(serde_json::Map::new()))
     }
+
+    /// Create a `batch` request running `items` (`{method, params}` objects)
+    /// in order; the daemon answers with one response per item.
+    pub fn batch(items: Vec<Value>) -> Self {
+        Self::new("batch", serde_json::json!({ "requests": items }))
+    }
 }
 
 /// A response from the daemon.
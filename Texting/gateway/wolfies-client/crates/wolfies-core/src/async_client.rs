@@ -0,0 +1,477 @@
+//! Tokio Unix socket and TCP client for the Wolfies daemon (`async` feature).
+//!
+//! Same NDJSON framing, request building, transports (TCP adds the `auth`
+//! token), and version downgrade as `DaemonClient`, for consumers already
+//! running a Tokio runtime. The whole exchange (connect, write, read one
+//! line) is bounded by the client timeout. The CLI binaries keep using the
+//! sync client, so they don't pull in Tokio.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpStream, UnixStream};
+
+use crate::client::{batch_results, downgrade_version, ClientError, Transport};
+use crate::protocol::{
+    ErrorPayload, FollowupOptions, OutputControls, Request, Response, BATCH_METHOD, PROTOCOL_V,
+};
+
+/// An async client for the Wolfies daemon.
+pub struct AsyncDaemonClient {
+    transport: Transport,
+    timeout: Duration,
+    protocol_v: u8,
+}
+
+impl AsyncDaemonClient {
+    /// Create a new client with the given socket path and timeout.
+    pub fn new(socket_path: impl Into<String>, timeout_secs: f64) -> Self {
+        Self {
+            transport: Transport::Unix {
+                socket_path: socket_path.into(),
+            },
+            timeout: Duration::from_secs_f64(timeout_secs),
+            protocol_v: PROTOCOL_V,
+        }
+    }
+
+    /// Create a TCP client (`host:port`) that sends `token` as `auth` on every request.
+    pub fn tcp(addr: impl Into<String>, token: impl Into<String>, timeout_secs: f64) -> Self {
+        Self {
+            transport: Transport::Tcp {
+                addr: addr.into(),
+                token: token.into(),
+            },
+            timeout: Duration::from_secs_f64(timeout_secs),
+            protocol_v: PROTOCOL_V,
+        }
+    }
+
+    /// Speak protocol version `v` (e.g. 1 for daemons that predate v2).
+    pub fn with_protocol_version(mut self, v: u8) -> Self {
+        self.protocol_v = v;
+        self
+    }
+
+    /// Protocol version this client's requests carry.
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol_v
+    }
+
+    /// Build a request at the client's protocol version (see `DaemonClient::request`).
+    pub fn request(&self, method: impl Into<String>, mut params: Value) -> Request {
+        if self.protocol_v >= 2 {
+            if let Value::Object(ref mut obj) = params {
+                obj.entry("timeout_ms")
+                    .or_insert_with(|| Value::from(self.timeout.as_millis() as u64));
+            }
+        }
+        Request::with_version(self.protocol_v, method, params)
+    }
+
+    /// Build a request with no parameters at the client's protocol version.
+    pub fn no_params(&self, method: impl Into<String>) -> Request {
+        self.request(method, Value::Object(Map::new()))
+    }
+
+    /// Send a request, downgrading once if the daemon rejects its version.
+    pub async fn call(&self, request: &Request) -> Result<Response, ClientError> {
+        let response = self.send(request).await?;
+        match downgrade_version(request, &response) {
+            Some(v) => {
+                let mut retry = request.clone();
+                retry.v = v;
+                self.send(&retry).await
+            }
+            None => Ok(response),
+        }
+    }
+
+    /// Run `requests` in one `batch` exchange (see `DaemonClient::call_batch`).
+    pub async fn call_batch(
+        &self,
+        requests: &[Request],
+    ) -> Result<Vec<Result<Value, ErrorPayload>>, ClientError> {
+        let request = self.request(BATCH_METHOD, Request::batch_params(requests));
+        batch_results(self.call(&request).await?)
+    }
+
+    /// Send one request to the daemon and receive its response.
+    async fn send(&self, request: &Request) -> Result<Response, ClientError> {
+        let exchange = async {
+            match self.transport {
+                Transport::Unix { ref socket_path } => {
+                    if !Path::new(socket_path).exists() {
+                        return Err(ClientError::SocketNotFound(socket_path.clone()));
+                    }
+                    let json =
+                        serde_json::to_string(request).map_err(ClientError::SerializeError)?;
+                    exchange(UnixStream::connect(socket_path).await?, &json).await
+                }
+                Transport::Tcp {
+                    ref addr,
+                    ref token,
+                } => {
+                    // Same request line, plus the shared-secret token
+                    let mut value =
+                        serde_json::to_value(request).map_err(ClientError::SerializeError)?;
+                    value["auth"] = Value::String(token.clone());
+                    let json =
+                        serde_json::to_string(&value).map_err(ClientError::SerializeError)?;
+                    exchange(TcpStream::connect(addr.as_str()).await?, &json).await
+                }
+            }
+        };
+        tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| ClientError::Timeout)?
+    }
+
+    /// Build and send `method` with `params` plus the output controls.
+    async fn call_with(
+        &self,
+        method: &str,
+        mut params: Map<String, Value>,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        controls.apply_to(&mut params);
+        self.call(&self.request(method, Value::Object(params)))
+            .await
+    }
+
+    /// `health`
+    pub async fn health(&self) -> Result<Response, ClientError> {
+        self.call(&self.no_params("health")).await
+    }
+
+    /// `unread_count`
+    pub async fn unread_count(&self) -> Result<Response, ClientError> {
+        self.call(&self.no_params("unread_count")).await
+    }
+
+    /// `unread_messages`, newest first.
+    pub async fn unread_messages(
+        &self,
+        limit: u32,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("limit".to_string(), json!(limit));
+        self.call_with("unread_messages", params, controls).await
+    }
+
+    /// `recent`, newest first.
+    pub async fn recent(
+        &self,
+        limit: u32,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("limit".to_string(), json!(limit));
+        self.call_with("recent", params, controls).await
+    }
+
+    /// `text_search`, optionally only messages after `since`.
+    pub async fn text_search(
+        &self,
+        query: &str,
+        limit: u32,
+        since: Option<&str>,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("query".to_string(), json!(query));
+        params.insert("limit".to_string(), json!(limit));
+        if let Some(s) = since {
+            params.insert("since".to_string(), json!(s));
+        }
+        self.call_with("text_search", params, controls).await
+    }
+
+    /// `messages_by_phone`
+    pub async fn messages_by_phone(
+        &self,
+        phone: &str,
+        limit: u32,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("phone".to_string(), json!(phone));
+        params.insert("limit".to_string(), json!(limit));
+        self.call_with("messages_by_phone", params, controls).await
+    }
+
+    /// `quick`, for the given pins (the daemon's configured pins if empty).
+    pub async fn quick(
+        &self,
+        pins: &[String],
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        if !pins.is_empty() {
+            params.insert("pins".to_string(), json!(pins));
+        }
+        self.call_with("quick", params, controls).await
+    }
+
+    /// `followup` (see `DaemonClient::followup`).
+    pub async fn followup(
+        &self,
+        opts: &FollowupOptions,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        opts.apply_to(&mut params);
+        self.call_with("followup", params, controls).await
+    }
+
+    /// `followup_snooze`: hide `contact`'s follow-ups until `until`.
+    pub async fn followup_snooze(
+        &self,
+        contact: &str,
+        until: Option<&str>,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("contact".to_string(), json!(contact));
+        if let Some(u) = until {
+            params.insert("until".to_string(), json!(u));
+        }
+        self.call(&self.request("followup_snooze", Value::Object(params)))
+            .await
+    }
+
+    /// `followup_unsnooze`: remove `contact`'s snooze.
+    pub async fn followup_unsnooze(&self, contact: &str) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("contact".to_string(), json!(contact));
+        self.call(&self.request("followup_unsnooze", Value::Object(params)))
+            .await
+    }
+}
+
+/// Write one NDJSON request line and read one response line.
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    json: &str,
+) -> Result<Response, ClientError> {
+    stream.write_all(json.as_bytes()).await?;
+    stream.write_all(b"\n").await?;
+    stream.flush().await?;
+
+    let mut line = String::new();
+    if BufReader::new(stream).read_line(&mut line).await? == 0 {
+        return Err(ClientError::EmptyResponse);
+    }
+    serde_json::from_str(&line).map_err(ClientError::ParseError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    /// Echo daemon: answers each request line with the request as its result.
+    async fn echo_daemon(listener: UnixListener, connections: usize) {
+        for _ in 0..connections {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let reply = json!({
+                "id": request["id"], "ok": true, "result": request, "error": null,
+                "meta": {"server_ms": 0.1, "protocol_v": 2},
+            });
+            write
+                .write_all(format!("{}\n", reply).as_bytes())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_round_trips_ndjson() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let server = tokio::spawn(echo_daemon(UnixListener::bind(&socket).unwrap(), 2));
+
+        let client = AsyncDaemonClient::new(socket.to_string_lossy(), 2.0);
+        let request = client.no_params("health");
+        let response = client.call(&request).await.unwrap();
+        assert!(response.ok);
+        assert_eq!(response.id, request.id);
+        let echoed = response.result.unwrap();
+        assert_eq!(echoed["method"], "health");
+        assert_eq!(echoed["v"], 2);
+        assert_eq!(echoed["params"]["timeout_ms"], 2000);
+
+        let controls = OutputControls {
+            minimal: true,
+            ..Default::default()
+        };
+        let response = client
+            .text_search("dinner", 5, Some("2026-10-01"), &controls)
+            .await
+            .unwrap();
+        let params = &response.result.unwrap()["params"];
+        assert_eq!(params["query"], "dinner");
+        assert_eq!(params["limit"], 5);
+        assert_eq!(params["since"], "2026-10-01");
+        assert_eq!(params["minimal"], true);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_followup_methods_send_their_params() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let server = tokio::spawn(echo_daemon(UnixListener::bind(&socket).unwrap(), 3));
+
+        let client = AsyncDaemonClient::new(socket.to_string_lossy(), 2.0);
+        let opts = FollowupOptions {
+            days: 7,
+            min_question_score: Some(0.5),
+            ..Default::default()
+        };
+        let controls = OutputControls {
+            compact: true,
+            ..Default::default()
+        };
+        let sent = client
+            .followup(&opts, &controls)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(sent["method"], "followup");
+        assert_eq!(sent["params"]["days"], 7);
+        assert_eq!(sent["params"]["min_question_score"], 0.5);
+        assert_eq!(sent["params"]["compact"], true);
+
+        let sent = client
+            .followup_snooze("Alice", Some("7d"))
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(sent["method"], "followup_snooze");
+        assert_eq!(sent["params"]["contact"], "Alice");
+        assert_eq!(sent["params"]["until"], "7d");
+
+        let sent = client
+            .followup_unsnooze("Alice")
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(sent["method"], "followup_unsnooze");
+        assert!(sent["params"].get("until").is_none());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_call_batch_returns_results_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        // Batch daemon: `nope` fails, other items echo back
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let items: Vec<Value> = request["params"]["requests"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| match item["method"] == "nope" {
+                    true => json!({
+                        "id": "batch.1", "ok": false, "result": null, "meta": null,
+                        "error": {"code": "ERROR", "message": "Unknown method: nope", "details": null},
+                    }),
+                    false => json!({"id": "batch.0", "ok": true, "result": item, "error": null, "meta": null}),
+                })
+                .collect();
+            let reply = json!({
+                "id": request["id"], "ok": true, "result": items, "error": null,
+                "meta": {"server_ms": 0.1, "protocol_v": 2},
+            });
+            write
+                .write_all(format!("{}\n", reply).as_bytes())
+                .await
+                .unwrap();
+            request
+        });
+
+        let client = AsyncDaemonClient::new(socket.to_string_lossy(), 2.0);
+        let requests = [client.no_params("health"), client.no_params("nope")];
+        let results = client.call_batch(&requests).await.unwrap();
+        let sent = server.await.unwrap();
+        assert_eq!(sent["method"], "batch");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap()["method"], "health");
+        assert_eq!(
+            results[1].as_ref().unwrap_err().message,
+            "Unknown method: nope"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tcp_call_injects_auth() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let reply = json!({
+                "id": request["id"], "ok": request["auth"] == "secret", "result": request["method"],
+                "error": null, "meta": {"server_ms": 0.1, "protocol_v": 2},
+            });
+            write
+                .write_all(format!("{}\n", reply).as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let client = AsyncDaemonClient::tcp(addr, "secret", 2.0);
+        let response = client.health().await.unwrap();
+        server.await.unwrap();
+        assert!(response.ok);
+        assert_eq!(response.result, Some(json!("health")));
+    }
+
+    #[tokio::test]
+    async fn test_silent_daemon_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        // Accept, read the request, never answer
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let client = AsyncDaemonClient::new(socket.to_string_lossy(), 0.1);
+        let err = client.health().await.unwrap_err();
+        assert!(matches!(err, ClientError::Timeout));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_missing_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("missing.sock");
+        let client = AsyncDaemonClient::new(socket.to_string_lossy(), 1.0);
+        let err = client.unread_count().await.unwrap_err();
+        assert!(matches!(err, ClientError::SocketNotFound(_)));
+    }
+}
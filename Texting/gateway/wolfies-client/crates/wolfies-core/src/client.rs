@@ -1,11 +1,32 @@
-//! Unix socket client for the Wolfies daemon.
+//! Unix socket and TCP clients for the Wolfies daemon.
+//!
+//! Both transports speak the same NDJSON protocol; the TCP transport adds
+//! the shared-secret `auth` field to every request.
+//!
+//! Each client has a protocol version (default `PROTOCOL_V`). If the daemon
+//! rejects it as `UNSUPPORTED_PROTOCOL` and advertises an older version we
+//! still speak, `call` retries once at that version.
+//!
+//! `call_batch` sends several requests in one exchange (the daemon's `batch`
+//! method) and returns one result per request, in order. `followup`,
+//! `followup_snooze`, and `followup_unsnooze` build and send those methods.
+//!
+//! Error output (daemon errors from `emit_response`, transport errors from
+//! `format_client_error`) carries the request id, which the daemon logs with
+//! every request.
 
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
 use std::os::unix::net::UnixStream;
 use std::path::Path;
 use std::time::Duration;
 
-use crate::protocol::{Meta, Profile, Request, Response};
+use crate::emit::ResponseEmitter;
+use crate::protocol::{
+    ErrorPayload, FollowupOptions, Meta, OutputControls, Profile, Request, Response, BATCH_METHOD,
+    MIN_SUPPORTED_V, PROTOCOL_V, UNSUPPORTED_PROTOCOL,
+};
+use serde_json::{json, Map, Value};
 use thiserror::Error;
 
 /// Errors that can occur when communicating with the daemon.
@@ -28,61 +49,197 @@ pub enum ClientError {
 
     #[error("Timeout waiting for response")]
     Timeout,
+
+    #[error("Batch rejected: {}: {}", .0.code, .0.message)]
+    BatchRejected(ErrorPayload),
+}
+
+/// How the client reaches the daemon.
+#[derive(Debug, Clone)]
+pub(crate) enum Transport {
+    Unix { socket_path: String },
+    Tcp { addr: String, token: String },
 }
 
 /// A client for the Wolfies daemon.
 pub struct DaemonClient {
-    socket_path: String,
+    transport: Transport,
     timeout: Duration,
+    protocol_v: u8,
 }
 
 impl DaemonClient {
     /// Create a new client with the given socket path and timeout.
     pub fn new(socket_path: impl Into<String>, timeout_secs: f64) -> Self {
         Self {
-            socket_path: socket_path.into(),
+            transport: Transport::Unix {
+                socket_path: socket_path.into(),
+            },
             timeout: Duration::from_secs_f64(timeout_secs),
+            protocol_v: PROTOCOL_V,
         }
     }
 
-    /// Send a request to the daemon and receive a response.
-    pub fn call(&self, request: &Request) -> Result<Response, ClientError> {
-        let path = Path::new(&self.socket_path);
+    /// Create a TCP client (`host:port`) that sends `token` as `auth` on every request.
+    pub fn tcp(addr: impl Into<String>, token: impl Into<String>, timeout_secs: f64) -> Self {
+        Self {
+            transport: Transport::Tcp {
+                addr: addr.into(),
+                token: token.into(),
+            },
+            timeout: Duration::from_secs_f64(timeout_secs),
+            protocol_v: PROTOCOL_V,
+        }
+    }
 
-        // Check if socket exists
-        if !path.exists() {
-            return Err(ClientError::SocketNotFound(self.socket_path.clone()));
+    /// Speak protocol version `v` (e.g. 1 for daemons that predate v2).
+    pub fn with_protocol_version(mut self, v: u8) -> Self {
+        self.protocol_v = v;
+        self
+    }
+
+    /// Protocol version this client's requests carry.
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol_v
+    }
+
+    /// Build a request at the client's protocol version.
+    ///
+    /// v2 requests carry the client timeout as `timeout_ms` (unless `params`
+    /// sets one), so the daemon gives up no later than we do.
+    pub fn request(&self, method: impl Into<String>, mut params: Value) -> Request {
+        if self.protocol_v >= 2 {
+            if let Value::Object(ref mut obj) = params {
+                obj.entry("timeout_ms")
+                    .or_insert_with(|| Value::from(self.timeout.as_millis() as u64));
+            }
         }
+        Request::with_version(self.protocol_v, method, params)
+    }
+
+    /// Build a request with no parameters at the client's protocol version.
+    pub fn no_params(&self, method: impl Into<String>) -> Request {
+        self.request(method, Value::Object(serde_json::Map::new()))
+    }
 
-        // Connect to socket
-        let stream = UnixStream::connect(path)?;
-        stream.set_read_timeout(Some(self.timeout))?;
-        stream.set_write_timeout(Some(self.timeout))?;
-
-        // Send request as NDJSON (compact JSON + newline)
-        let mut writer = &stream;
-        let json = serde_json::to_string(request).map_err(ClientError::SerializeError)?;
-        writer.write_all(json.as_bytes())?;
-        writer.write_all(b"\n")?;
-        writer.flush()?;
-
-        // Read one NDJSON line
-        let mut reader = BufReader::new(&stream);
-        let mut line = String::new();
-        let bytes_read = reader.read_line(&mut line)?;
-
-        if bytes_read == 0 {
-            return Err(ClientError::EmptyResponse);
+    /// Send a request, downgrading once if the daemon rejects its version.
+    pub fn call(&self, request: &Request) -> Result<Response, ClientError> {
+        let response = self.send(request)?;
+        match downgrade_version(request, &response) {
+            Some(v) => {
+                let mut retry = request.clone();
+                retry.v = v;
+                self.send(&retry)
+            }
+            None => Ok(response),
         }
+    }
 
-        // Parse response
-        let response: Response = serde_json::from_str(&line).map_err(ClientError::ParseError)?;
+    /// Run `requests` in order in one `batch` exchange, returning each one's
+    /// result or error. A failing item doesn't stop the rest; only a batch
+    /// the daemon refuses as a whole (e.g. a daemon without `batch`) is an
+    /// error here, `BatchRejected`.
+    pub fn call_batch(
+        &self,
+        requests: &[Request],
+    ) -> Result<Vec<Result<Value, ErrorPayload>>, ClientError> {
+        let response = self.call(&self.request(BATCH_METHOD, Request::batch_params(requests)))?;
+        batch_results(response)
+    }
+
+    /// `followup`: unanswered questions and stale conversations, snoozed
+    /// contacts left out.
+    pub fn followup(
+        &self,
+        opts: &FollowupOptions,
+        controls: &OutputControls,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        opts.apply_to(&mut params);
+        controls.apply_to(&mut params);
+        self.call(&self.request("followup", Value::Object(params)))
+    }
 
-        Ok(response)
+    /// `followup_snooze`: hide `contact`'s follow-ups until `until` (a
+    /// duration like 8h or 7d, a date, or a local time; daemon default 1d).
+    pub fn followup_snooze(
+        &self,
+        contact: &str,
+        until: Option<&str>,
+    ) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("contact".to_string(), json!(contact));
+        if let Some(u) = until {
+            params.insert("until".to_string(), json!(u));
+        }
+        self.call(&self.request("followup_snooze", Value::Object(params)))
     }
 
-    /// Format a client-side error as a daemon-style error response.
-    pub fn format_client_error(err: &ClientError) -> serde_json::Value {
+    /// `followup_unsnooze`: remove `contact`'s snooze.
+    pub fn followup_unsnooze(&self, contact: &str) -> Result<Response, ClientError> {
+        let mut params = Map::new();
+        params.insert("contact".to_string(), json!(contact));
+        self.call(&self.request("followup_unsnooze", Value::Object(params)))
+    }
+
+    /// Send one request to the daemon and receive its response.
+    fn send(&self, request: &Request) -> Result<Response, ClientError> {
+        match self.transport {
+            Transport::Unix { ref socket_path } => {
+                let path = Path::new(socket_path);
+
+                // Check if socket exists
+                if !path.exists() {
+                    return Err(ClientError::SocketNotFound(socket_path.clone()));
+                }
+
+                // Connect to socket
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+
+                let json = serde_json::to_string(request).map_err(ClientError::SerializeError)?;
+                exchange(&stream, &json)
+            }
+            Transport::Tcp {
+                ref addr,
+                ref token,
+            } => {
+                let stream = self.connect_tcp(addr)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+
+                // Same request line, plus the shared-secret token
+                let mut value =
+                    serde_json::to_value(request).map_err(ClientError::SerializeError)?;
+                value["auth"] = serde_json::Value::String(token.clone());
+                let json = serde_json::to_string(&value).map_err(ClientError::SerializeError)?;
+                exchange(&stream, &json)
+            }
+        }
+    }
+
+    /// Connect to the first reachable address for `addr`, bounded by the timeout.
+    fn connect_tcp(&self, addr: &str) -> Result<TcpStream, ClientError> {
+        let mut last_err = None;
+        for sock_addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&sock_addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(ClientError::ConnectionFailed(last_err.unwrap_or_else(
+            || {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("No address for {}", addr),
+                )
+            },
+        )))
+    }
+
+    /// Format a client-side error for `request_id` as a daemon-style error response.
+    pub fn format_client_error(err: &ClientError, request_id: &str) -> serde_json::Value {
         let (code, message, details) = match err {
             ClientError::SocketNotFound(path) => (
                 "DAEMON_NOT_RUNNING",
@@ -97,19 +254,25 @@ impl DaemonClient {
                 })),
             ),
             ClientError::Timeout => ("TIMEOUT", "Timeout waiting for response".to_string(), None),
-            ClientError::EmptyResponse => {
-                ("EMPTY_RESPONSE", "Empty response from daemon".to_string(), None)
-            }
-            ClientError::SerializeError(e) => {
-                ("SERIALIZE_ERROR", format!("JSON serialization error: {}", e), None)
-            }
-            ClientError::ParseError(e) => {
-                ("PARSE_ERROR", format!("JSON parse error: {}", e), None)
+            ClientError::EmptyResponse => (
+                "EMPTY_RESPONSE",
+                "Empty response from daemon".to_string(),
+                None,
+            ),
+            ClientError::SerializeError(e) => (
+                "SERIALIZE_ERROR",
+                format!("JSON serialization error: {}", e),
+                None,
+            ),
+            ClientError::ParseError(e) => ("PARSE_ERROR", format!("JSON parse error: {}", e), None),
+            ClientError::BatchRejected(e) => {
+                (e.code.as_str(), e.message.clone(), e.details.clone())
             }
         };
 
         serde_json::json!({
             "ok": false,
+            "id": request_id,
             "error": {
                 "code": code,
                 "message": message,
@@ -119,53 +282,77 @@ impl DaemonClient {
     }
 }
 
+/// The version to retry at when `response` rejects `request`'s protocol
+/// version and the daemon advertises an older one we can speak.
+pub(crate) fn downgrade_version(request: &Request, response: &Response) -> Option<u8> {
+    let rejected = response
+        .error
+        .as_ref()
+        .is_some_and(|e| e.code == UNSUPPORTED_PROTOCOL);
+    let server_v = response.meta.as_ref().and_then(|m| m.protocol_v)?;
+    (rejected && (MIN_SUPPORTED_V..request.v).contains(&server_v)).then_some(server_v)
+}
+
+/// Per-item results from a `batch` response.
+pub(crate) fn batch_results(
+    response: Response,
+) -> Result<Vec<Result<Value, ErrorPayload>>, ClientError> {
+    if !response.ok {
+        return Err(ClientError::BatchRejected(
+            response.error.unwrap_or_else(unknown_error),
+        ));
+    }
+    let items: Vec<Response> = serde_json::from_value(response.result.unwrap_or_default())
+        .map_err(ClientError::ParseError)?;
+    Ok(items
+        .into_iter()
+        .map(|item| match item.ok {
+            true => Ok(item.result.unwrap_or_default()),
+            false => Err(item.error.unwrap_or_else(unknown_error)),
+        })
+        .collect())
+}
+
+fn unknown_error() -> ErrorPayload {
+    ErrorPayload {
+        code: "ERROR".to_string(),
+        message: "unknown error".to_string(),
+        details: None,
+    }
+}
+
+/// Write one NDJSON request line and read one response line.
+fn exchange<S: Read + Write + Copy>(stream: S, json: &str) -> Result<Response, ClientError> {
+    // Send request as NDJSON (compact JSON + newline)
+    let mut writer = stream;
+    writer.write_all(json.as_bytes())?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+
+    // Read one NDJSON line
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    let bytes_read = reader.read_line(&mut line)?;
+
+    if bytes_read == 0 {
+        return Err(ClientError::EmptyResponse);
+    }
+
+    // Parse response
+    let response: Response = serde_json::from_str(&line).map_err(ClientError::ParseError)?;
+
+    Ok(response)
+}
+
 /// Emit the response to stdout according to output mode.
 ///
 /// - Default: print `result` only (or error wrapper if failed)
 /// - `raw`: print full response wrapper
 /// - `pretty`: pretty-print JSON
+///
+/// Streams of responses, and their exit codes, go through `ResponseEmitter`.
 pub fn emit_response(response: &Response, raw: bool, pretty: bool) -> String {
-    if raw {
-        // Print full response wrapper
-        if pretty {
-            serde_json::to_string_pretty(response).unwrap_or_else(|_| "{}".to_string())
-        } else {
-            serde_json::to_string(response).unwrap_or_else(|_| "{}".to_string())
-        }
-    } else if !response.ok {
-        // Error: print stable error shape
-        let err = response
-            .error
-            .as_ref()
-            .map(|e| {
-                serde_json::json!({
-                    "code": e.code,
-                    "message": e.message,
-                    "details": e.details
-                })
-            })
-            .unwrap_or_else(|| {
-                serde_json::json!({
-                    "code": "ERROR",
-                    "message": "unknown error",
-                    "details": null
-                })
-            });
-        let out = serde_json::json!({ "ok": false, "error": err });
-        if pretty {
-            serde_json::to_string_pretty(&out).unwrap_or_else(|_| "{}".to_string())
-        } else {
-            serde_json::to_string(&out).unwrap_or_else(|_| "{}".to_string())
-        }
-    } else {
-        // Success: print result only
-        let result = response.result.as_ref().unwrap_or(&serde_json::Value::Null);
-        if pretty {
-            serde_json::to_string_pretty(result).unwrap_or_else(|_| "null".to_string())
-        } else {
-            serde_json::to_string(result).unwrap_or_else(|_| "null".to_string())
-        }
-    }
+    ResponseEmitter::new(raw, pretty).render(response)
 }
 
 // Custom serialization for Response (needed for raw output)
@@ -191,15 +378,24 @@ impl serde::Serialize for Meta {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("Meta", 4)?;
+        let mut state = serializer.serialize_struct("Meta", 7)?;
         state.serialize_field("server_ms", &self.server_ms)?;
         state.serialize_field("protocol_v", &self.protocol_v)?;
+        if self.min_supported.is_some() {
+            state.serialize_field("min_supported", &self.min_supported)?;
+        }
         if self.serialize_ms.is_some() {
             state.serialize_field("serialize_ms", &self.serialize_ms)?;
         }
         if self.profile.is_some() {
             state.serialize_field("profile", &self.profile)?;
         }
+        if self.trace.is_some() {
+            state.serialize_field("trace", &self.trace)?;
+        }
+        if !self.warnings.is_empty() {
+            state.serialize_field("warnings", &self.warnings)?;
+        }
         state.end()
     }
 }
@@ -217,3 +413,287 @@ impl serde::Serialize for Profile {
         state.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_tcp_call_injects_auth() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let reply = serde_json::json!({
+                "id": request["id"],
+                "ok": request["auth"] == "secret",
+                "result": request["method"],
+                "error": null,
+                "meta": {"server_ms": 0.1, "protocol_v": 1},
+            });
+            (&stream)
+                .write_all(format!("{}\n", reply).as_bytes())
+                .unwrap();
+        });
+
+        let client = DaemonClient::tcp(addr, "secret", 2.0);
+        let request = Request::no_params("health");
+        let response = client.call(&request).unwrap();
+        server.join().unwrap();
+
+        assert!(response.ok);
+        assert_eq!(response.id, request.id);
+        assert_eq!(response.result, Some(serde_json::json!("health")));
+    }
+
+    /// Fake v1-only daemon: rejects newer versions the way a v2 daemon
+    /// configured for v1 would, answers v1 requests with their version.
+    fn v1_daemon(listener: TcpListener, connections: usize) -> std::thread::JoinHandle<Vec<u64>> {
+        std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let v = request["v"].as_u64().unwrap();
+                seen.push(v);
+                let reply = if v > 1 {
+                    serde_json::json!({
+                        "id": request["id"], "ok": false, "result": null,
+                        "error": {"code": "UNSUPPORTED_PROTOCOL", "message": "v1 only", "details": null},
+                        "meta": {"server_ms": 0.1, "protocol_v": 1, "min_supported": 1},
+                    })
+                } else {
+                    serde_json::json!({
+                        "id": request["id"], "ok": true, "result": {"v": v}, "error": null,
+                        "meta": {"server_ms": 0.1, "protocol_v": 1, "min_supported": 1},
+                    })
+                };
+                (&stream)
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .unwrap();
+            }
+            seen
+        })
+    }
+
+    #[test]
+    fn test_followup_methods_send_their_params() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        // Echo daemon: each request comes back as its result
+        let server = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let (stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let reply = serde_json::json!({
+                    "id": request["id"], "ok": true, "result": request, "error": null,
+                    "meta": {"server_ms": 0.1, "protocol_v": 2},
+                });
+                (&stream)
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .unwrap();
+            }
+        });
+
+        let client = DaemonClient::tcp(addr, "secret", 2.0);
+        let opts = FollowupOptions {
+            days: 7,
+            min_question_score: Some(0.5),
+            ..Default::default()
+        };
+        let controls = OutputControls {
+            compact: true,
+            ..Default::default()
+        };
+        let sent = client.followup(&opts, &controls).unwrap().result.unwrap();
+        assert_eq!(sent["method"], "followup");
+        assert_eq!(
+            (
+                &sent["params"]["days"],
+                &sent["params"]["stale"],
+                &sent["params"]["min_question_score"]
+            ),
+            (&json!(7), &json!(3), &json!(0.5))
+        );
+        assert_eq!(sent["params"]["compact"], true);
+
+        let sent = client
+            .followup_snooze("Alice", Some("7d"))
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(sent["method"], "followup_snooze");
+        assert_eq!(
+            (&sent["params"]["contact"], &sent["params"]["until"]),
+            (&json!("Alice"), &json!("7d"))
+        );
+
+        let sent = client.followup_unsnooze("Alice").unwrap().result.unwrap();
+        assert_eq!(sent["method"], "followup_unsnooze");
+        assert!(sent["params"].get("until").is_none());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_v2_client_downgrades_for_v1_daemon() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = v1_daemon(listener, 2);
+
+        let client = DaemonClient::tcp(addr, "secret", 2.0);
+        let request = client.no_params("health");
+        assert_eq!(request.v, PROTOCOL_V);
+        assert_eq!(request.params["timeout_ms"], 2000);
+
+        let response = client.call(&request).unwrap();
+        assert_eq!(server.join().unwrap(), vec![2, 1]);
+        assert!(response.ok);
+        assert_eq!(response.result, Some(serde_json::json!({"v": 1})));
+        assert_eq!(response.meta.unwrap().min_supported, Some(1));
+    }
+
+    #[test]
+    fn test_error_output_carries_request_id() {
+        let response: Response = serde_json::from_value(serde_json::json!({
+            "id": "req-7f3a", "ok": false, "result": null,
+            "error": {"code": "ERROR", "message": "Unknown method: nope", "details": null},
+            "meta": {
+                "server_ms": 0.1, "protocol_v": 2, "trace": ["WARN request failed id=req-7f3a"],
+                "warnings": ["skipped a malformed row"],
+            },
+        }))
+        .unwrap();
+
+        let plain: serde_json::Value =
+            serde_json::from_str(&emit_response(&response, false, false)).unwrap();
+        assert_eq!(plain["id"], "req-7f3a");
+        assert_eq!(plain["error"]["code"], "ERROR");
+
+        let raw: serde_json::Value =
+            serde_json::from_str(&emit_response(&response, true, false)).unwrap();
+        assert_eq!(raw["id"], "req-7f3a");
+        assert_eq!(raw["meta"]["trace"][0], "WARN request failed id=req-7f3a");
+        assert_eq!(raw["meta"]["warnings"][0], "skipped a malformed row");
+
+        let transport = DaemonClient::format_client_error(&ClientError::Timeout, "req-7f3a");
+        assert_eq!(transport["id"], "req-7f3a");
+        assert_eq!(transport["error"]["code"], "TIMEOUT");
+    }
+
+    /// Fake daemon answering one request: batches item by item (`nope` fails,
+    /// anything else echoes its method and params), other methods rejected.
+    fn batch_daemon(listener: TcpListener) -> std::thread::JoinHandle<serde_json::Value> {
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let meta = serde_json::json!({"server_ms": 0.1, "protocol_v": 2});
+            let reply = if request["method"] == "batch" {
+                let items: Vec<serde_json::Value> = request["params"]["requests"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let id = format!("{}.{}", request["id"].as_str().unwrap(), i);
+                        if item["method"] == "nope" {
+                            serde_json::json!({
+                                "id": id, "ok": false, "result": null, "meta": meta,
+                                "error": {"code": "ERROR", "message": "Unknown method: nope", "details": null},
+                            })
+                        } else {
+                            serde_json::json!({"id": id, "ok": true, "result": item, "error": null, "meta": meta})
+                        }
+                    })
+                    .collect();
+                serde_json::json!({"id": request["id"], "ok": true, "result": items, "error": null, "meta": meta})
+            } else {
+                serde_json::json!({
+                    "id": request["id"], "ok": false, "result": null, "meta": meta,
+                    "error": {"code": "ERROR", "message": "Unknown method: batch", "details": null},
+                })
+            };
+            (&stream)
+                .write_all(format!("{}\n", reply).as_bytes())
+                .unwrap();
+            request
+        })
+    }
+
+    #[test]
+    fn test_call_batch_keeps_order_and_item_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = batch_daemon(listener);
+
+        let client = DaemonClient::tcp(addr, "secret", 2.0);
+        let requests = [
+            client.no_params("health"),
+            client.no_params("nope"),
+            client.request("unread", serde_json::json!({"limit": 3})),
+        ];
+        let results = client.call_batch(&requests).unwrap();
+        let sent = server.join().unwrap();
+
+        assert_eq!(sent["method"], "batch");
+        assert_eq!(sent["params"]["requests"].as_array().unwrap().len(), 3);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()["method"], "health");
+        assert_eq!(
+            results[1].as_ref().unwrap_err().message,
+            "Unknown method: nope"
+        );
+        assert_eq!(results[2].as_ref().unwrap()["params"]["limit"], 3);
+    }
+
+    #[test]
+    fn test_call_batch_rejected_as_a_whole() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = batch_daemon(listener);
+
+        // A daemon that doesn't know `batch` fails the whole call
+        let rejected = batch_results(
+            serde_json::from_value(serde_json::json!({
+                "id": "x", "ok": false, "result": null, "meta": null,
+                "error": {"code": "ERROR", "message": "Unknown method: batch", "details": null},
+            }))
+            .unwrap(),
+        );
+        let Err(ClientError::BatchRejected(error)) = rejected else {
+            panic!("expected BatchRejected");
+        };
+        assert_eq!(error.message, "Unknown method: batch");
+        let formatted = DaemonClient::format_client_error(&ClientError::BatchRejected(error), "x");
+        assert_eq!(formatted["error"]["code"], "ERROR");
+
+        // An empty batch is still one exchange, with no results
+        let client = DaemonClient::tcp(addr, "secret", 2.0);
+        assert!(client.call_batch(&[]).unwrap().is_empty());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_v1_client_setting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = v1_daemon(listener, 1);
+
+        let client = DaemonClient::tcp(addr, "secret", 2.0).with_protocol_version(1);
+        let request = client.no_params("health");
+        // v1 has no official timeout_ms
+        assert!(request.params.get("timeout_ms").is_none());
+
+        assert!(client.call(&request).unwrap().ok);
+        assert_eq!(server.join().unwrap(), vec![1]);
+    }
+}
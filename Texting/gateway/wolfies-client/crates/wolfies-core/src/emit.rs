@@ -0,0 +1,291 @@
+//! Rendering daemon responses for the CLI binaries, and their exit codes.
+//!
+//! A `ResponseEmitter` renders each response it's given as it arrives: the
+//! result alone on success, the stable `{ok: false, id, error}` shape on
+//! failure, or (with `raw`) the full wrapper. By default every response is
+//! one compact JSON line, so a stream of them is NDJSON; `pretty` prints
+//! each one indented instead. The emitter tallies the outcomes and turns
+//! them into an exit code through its `ExitPolicy`.
+//!
+//! A single response is the trivial stream: `emit_response` is one `render`.
+
+use crate::client::{ClientError, DaemonClient};
+use crate::protocol::Response;
+use serde_json::Value;
+
+/// Exit codes for a stream of responses. Which one applies:
+///
+/// - a transport error (no response at all): `transport_error`
+/// - every response failed: `all_failed`
+/// - some but not all failed: `any_failed`
+/// - none failed (or there were none): 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitPolicy {
+    pub any_failed: u8,
+    pub all_failed: u8,
+    pub transport_error: u8,
+}
+
+impl Default for ExitPolicy {
+    fn default() -> Self {
+        Self {
+            any_failed: 1,
+            all_failed: 2,
+            transport_error: 3,
+        }
+    }
+}
+
+impl ExitPolicy {
+    /// One code for any failed response, whether or not all of them failed.
+    pub fn uniform(failed: u8, transport_error: u8) -> Self {
+        Self {
+            any_failed: failed,
+            all_failed: failed,
+            transport_error,
+        }
+    }
+
+    /// The exit code after `failed` of `total` responses failed.
+    pub fn code(&self, total: usize, failed: usize, transport_error: bool) -> u8 {
+        if transport_error {
+            self.transport_error
+        } else if failed == 0 {
+            0
+        } else if failed == total {
+            self.all_failed
+        } else {
+            self.any_failed
+        }
+    }
+}
+
+/// Renders responses and tracks the exit code they add up to.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseEmitter {
+    raw: bool,
+    pretty: bool,
+    policy: ExitPolicy,
+    total: usize,
+    failed: usize,
+    transport_error: bool,
+}
+
+impl ResponseEmitter {
+    /// `raw`: print full response wrappers. `pretty`: indent instead of one
+    /// line per response.
+    pub fn new(raw: bool, pretty: bool) -> Self {
+        Self {
+            raw,
+            pretty,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_policy(mut self, policy: ExitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Render `response` without recording its outcome.
+    pub fn render(&self, response: &Response) -> String {
+        if self.raw {
+            return self.to_json(response, "{}");
+        }
+        match response.ok {
+            true => self.to_json(response.result.as_ref().unwrap_or(&Value::Null), "null"),
+            false => self.to_json(&error_item(response), "{}"),
+        }
+    }
+
+    /// Render one response of the stream and record whether it failed.
+    pub fn emit(&mut self, response: &Response) -> String {
+        self.total += 1;
+        if !response.ok {
+            self.failed += 1;
+        }
+        self.render(response)
+    }
+
+    /// Render every response, in order.
+    pub fn emit_all<'a>(
+        &mut self,
+        responses: impl IntoIterator<Item = &'a Response>,
+    ) -> Vec<String> {
+        responses
+            .into_iter()
+            .map(|response| self.emit(response))
+            .collect()
+    }
+
+    /// Render a `batch` response item by item. A rejected batch, or one whose
+    /// items don't parse, is a single failed response; `raw` prints the
+    /// wrapper once but still counts the items.
+    pub fn emit_batch(&mut self, response: &Response) -> Vec<String> {
+        let items = response
+            .result
+            .clone()
+            .filter(|_| response.ok)
+            .and_then(|result| serde_json::from_value::<Vec<Response>>(result).ok());
+        match items {
+            Some(items) if self.raw => {
+                self.total += items.len();
+                self.failed += items.iter().filter(|item| !item.ok).count();
+                vec![self.render(response)]
+            }
+            Some(items) => self.emit_all(&items),
+            None => vec![self.emit(response)],
+        }
+    }
+
+    /// Render a client-side error for `request_id` in the daemon's error shape.
+    pub fn emit_transport_error(&mut self, err: &ClientError, request_id: &str) -> String {
+        self.transport_error = true;
+        self.to_json(&DaemonClient::format_client_error(err, request_id), "{}")
+    }
+
+    /// The exit code for everything emitted so far.
+    pub fn exit_code(&self) -> u8 {
+        self.policy
+            .code(self.total, self.failed, self.transport_error)
+    }
+
+    fn to_json<T: serde::Serialize>(&self, value: &T, fallback: &str) -> String {
+        let json = match self.pretty {
+            true => serde_json::to_string_pretty(value),
+            false => serde_json::to_string(value),
+        };
+        json.unwrap_or_else(|_| fallback.to_string())
+    }
+}
+
+/// The stable error shape of a failed response.
+fn error_item(response: &Response) -> Value {
+    let err = response
+        .error
+        .as_ref()
+        .map(|e| {
+            serde_json::json!({
+                "code": e.code,
+                "message": e.message,
+                "details": e.details
+            })
+        })
+        .unwrap_or_else(|| {
+            serde_json::json!({
+                "code": "ERROR",
+                "message": "unknown error",
+                "details": null
+            })
+        });
+    serde_json::json!({ "ok": false, "id": response.id, "error": err })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ok(id: &str, result: Value) -> Response {
+        serde_json::from_value(
+            json!({ "id": id, "ok": true, "result": result, "error": null, "meta": null }),
+        )
+        .unwrap()
+    }
+
+    fn failed(id: &str, code: &str) -> Response {
+        serde_json::from_value(json!({
+            "id": id, "ok": false, "result": null,
+            "error": { "code": code, "message": "boom", "details": null }, "meta": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stream_lines_and_exit_codes() {
+        let responses = [
+            ok("a", json!({"count": 3})),
+            failed("b", "NOT_FOUND"),
+            ok("c", json!([1, 2])),
+        ];
+        let mut emitter = ResponseEmitter::new(false, false);
+        let lines = emitter.emit_all(&responses);
+        assert_eq!(
+            lines,
+            [
+                r#"{"count":3}"#,
+                r#"{"error":{"code":"NOT_FOUND","details":null,"message":"boom"},"id":"b","ok":false}"#,
+                "[1,2]",
+            ]
+        );
+        assert_eq!(emitter.exit_code(), 1);
+
+        let mut all_failed = ResponseEmitter::new(false, false);
+        all_failed.emit_all(&[failed("a", "ERROR"), failed("b", "ERROR")]);
+        assert_eq!(all_failed.exit_code(), 2);
+
+        let mut none_failed = ResponseEmitter::new(false, false);
+        assert_eq!(none_failed.exit_code(), 0);
+        none_failed.emit(&ok("a", json!(null)));
+        assert_eq!(none_failed.exit_code(), 0);
+
+        let mut transport = ResponseEmitter::new(false, false);
+        transport.emit(&ok("a", json!(1)));
+        let line = transport.emit_transport_error(&ClientError::Timeout, "b");
+        assert_eq!(
+            serde_json::from_str::<Value>(&line).unwrap()["error"]["code"],
+            "TIMEOUT"
+        );
+        assert_eq!(transport.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_single_response_with_uniform_policy() {
+        let policy = ExitPolicy::uniform(1, 2);
+        let mut emitter = ResponseEmitter::new(false, false).with_policy(policy);
+        emitter.emit(&failed("a", "ERROR"));
+        assert_eq!(emitter.exit_code(), 1);
+        assert_eq!(policy.code(2, 1, false), 1);
+        assert_eq!(policy.code(1, 0, true), 2);
+    }
+
+    #[test]
+    fn test_pretty_and_raw_rendering() {
+        let response = ok("a", json!({"n": 1}));
+        assert_eq!(
+            ResponseEmitter::new(false, true).render(&response),
+            "{\n  \"n\": 1\n}"
+        );
+        let raw: Value =
+            serde_json::from_str(&ResponseEmitter::new(true, false).render(&response)).unwrap();
+        assert_eq!(raw["id"], "a");
+        assert_eq!(raw["result"]["n"], 1);
+    }
+
+    #[test]
+    fn test_batch_items_emitted_one_per_line() {
+        let items = json!([
+            { "id": "x.0", "ok": true, "result": {"n": 1}, "error": null, "meta": null },
+            { "id": "x.1", "ok": false, "result": null,
+              "error": { "code": "UNKNOWN_METHOD", "message": "nope", "details": null }, "meta": null },
+        ]);
+        let batch = ok("x", items);
+        let mut emitter = ResponseEmitter::new(false, false);
+        let lines = emitter.emit_batch(&batch);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"n":1}"#);
+        assert_eq!(
+            serde_json::from_str::<Value>(&lines[1]).unwrap()["id"],
+            "x.1"
+        );
+        assert_eq!(emitter.exit_code(), 1);
+
+        let mut raw = ResponseEmitter::new(true, false);
+        assert_eq!(raw.emit_batch(&batch).len(), 1);
+        assert_eq!(raw.exit_code(), 1);
+
+        let mut rejected = ResponseEmitter::new(false, false);
+        assert_eq!(rejected.emit_batch(&failed("x", "INVALID_PARAMS")).len(), 1);
+        assert_eq!(rejected.exit_code(), 2);
+    }
+}
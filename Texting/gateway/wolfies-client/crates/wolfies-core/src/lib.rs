@@ -1,12 +1,22 @@
 //! Shared core library for Wolfies daemon clients.
 //!
-//! This crate provides the NDJSON protocol types and Unix socket client
+//! This crate provides the NDJSON protocol types and the Unix socket / TCP client
 //! that are shared across all Wolfies service clients (iMessage, Gmail,
-//! Calendar, Reminders, etc.).
+//! Calendar, Reminders, etc.), and the `ResponseEmitter` their binaries print
+//! responses and pick exit codes with. With the `async` feature, `AsyncDaemonClient`
+//! offers the same protocol over Tokio.
 
+#[cfg(feature = "async")]
+pub mod async_client;
 pub mod client;
+pub mod emit;
 pub mod protocol;
 
 // Re-export commonly used types
+#[cfg(feature = "async")]
+pub use async_client::AsyncDaemonClient;
 pub use client::{emit_response, ClientError, DaemonClient};
-pub use protocol::{ErrorPayload, Meta, OutputControls, Profile, Request, Response};
+pub use emit::{ExitPolicy, ResponseEmitter};
+pub use protocol::{
+    ErrorPayload, FollowupOptions, Meta, OutputControls, Profile, Request, Response,
+};
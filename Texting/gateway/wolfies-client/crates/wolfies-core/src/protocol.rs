@@ -2,21 +2,41 @@
 //!
 //! The daemon uses newline-delimited JSON (NDJSON) over a Unix domain socket.
 //! Each request and response is a single JSON object followed by a newline.
+//!
+//! Protocol v2 makes the output controls and `timeout_ms` in `params`
+//! official; v1 daemons treat them as ordinary (mostly ignored) params.
+//! A daemon rejects versions it doesn't speak with `UNSUPPORTED_PROTOCOL`
+//! and reports its newest version in `meta.protocol_v`.
+//!
+//! A `batch` request carries other requests' `{method, params}` and gets back
+//! an array of full responses, one per item, in order.
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Newest protocol version this client speaks (the default for requests).
+pub const PROTOCOL_V: u8 = 2;
+
+/// Oldest protocol version a client may fall back to.
+pub const MIN_SUPPORTED_V: u8 = 1;
+
+/// Error code a daemon returns for a request version it doesn't speak.
+pub const UNSUPPORTED_PROTOCOL: &str = "UNSUPPORTED_PROTOCOL";
+
+/// Method that runs several requests, in order, in one exchange.
+pub const BATCH_METHOD: &str = "batch";
+
 /// A request to the daemon.
 ///
 /// Request format:
 /// ```json
-/// {"id": "uuid", "v": 1, "method": "...", "params": {...}}
+/// {"id": "uuid", "v": 2, "method": "...", "params": {...}}
 /// ```
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Request {
     /// Unique request identifier (echoed in response)
     pub id: String,
-    /// Protocol version (always 1)
+    /// Protocol version (see `DaemonClient::with_protocol_version`)
     pub v: u8,
     /// Method name (e.g., "health", "unread_count", "bundle")
     pub method: String,
@@ -25,11 +45,18 @@ pub struct Request {
 }
 
 impl Request {
-    /// Create a new request with the given method and parameters.
+    /// Create a new request with the given method and parameters, at `PROTOCOL_V`.
+    ///
+    /// Prefer `DaemonClient::request`, which uses the client's protocol version.
     pub fn new(method: impl Into<String>, params: Value) -> Self {
+        Self::with_version(PROTOCOL_V, method, params)
+    }
+
+    /// Create a request for a specific protocol version.
+    pub fn with_version(v: u8, method: impl Into<String>, params: Value) -> Self {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
-            v: 1,
+            v,
             method: method.into(),
             params,
         }
@@ -39,6 +66,16 @@ impl Request {
     pub fn no_params(method: impl Into<String>) -> Self {
         Self::new(method, Value::Object(serde_json::Map::new()))
     }
+
+    /// Params for a `batch` request running `requests` in order. Their ids
+    /// aren't sent: the daemon numbers items `<batch id>.<index>`.
+    pub fn batch_params(requests: &[Request]) -> Value {
+        let items: Vec<Value> = requests
+            .iter()
+            .map(|r| serde_json::json!({ "method": r.method, "params": r.params }))
+            .collect();
+        serde_json::json!({ "requests": items })
+    }
 }
 
 /// A response from the daemon.
@@ -62,7 +99,7 @@ pub struct Response {
 }
 
 /// Error payload returned by the daemon.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ErrorPayload {
     /// Error code (e.g., "INVALID_JSON", "UNKNOWN_METHOD", "ERROR")
     pub code: String,
@@ -77,12 +114,19 @@ pub struct ErrorPayload {
 pub struct Meta {
     /// Time spent processing in daemon (milliseconds)
     pub server_ms: Option<f64>,
-    /// Protocol version
+    /// Newest protocol version the daemon supports
     pub protocol_v: Option<u8>,
+    /// Oldest protocol version the daemon accepts (v2 daemons only)
+    pub min_supported: Option<u8>,
     /// Serialization time (milliseconds, only when profiling enabled)
     pub serialize_ms: Option<f64>,
     /// Profiling data (only when WOLFIES_PROFILE=1)
     pub profile: Option<Profile>,
+    /// Daemon log lines for this request (only when params had `trace: true`)
+    pub trace: Option<Vec<String>>,
+    /// Non-fatal problems the daemon worked around for this request
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
 
 /// Profiling data from daemon (optional).
@@ -98,7 +142,8 @@ pub struct Profile {
 
 /// Output control parameters for daemon requests.
 ///
-/// These are passed in `params` to control output format and size.
+/// These are passed in `params` to control output format and size; from
+/// protocol v2 the daemon applies them to every method's result.
 #[derive(Debug, Default, Clone)]
 pub struct OutputControls {
     /// Use minimal JSON preset (lowest token cost)
@@ -133,3 +178,49 @@ impl OutputControls {
         }
     }
 }
+
+/// `followup` filters; `Default` matches the daemon's defaults.
+#[derive(Debug, Clone)]
+pub struct FollowupOptions {
+    /// Days to look back
+    pub days: u32,
+    /// Days without a reply before a conversation counts as stale
+    pub stale: u32,
+    /// Include questions asked in group chats
+    pub include_groups: bool,
+    /// Minimum question score (0.0-1.0); the daemon's default when None
+    pub min_question_score: Option<f64>,
+    /// Leave out automated senders (short codes, 2FA, marketing)
+    pub exclude_automated: bool,
+}
+
+impl Default for FollowupOptions {
+    fn default() -> Self {
+        Self {
+            days: 30,
+            stale: 3,
+            include_groups: false,
+            min_question_score: None,
+            exclude_automated: false,
+        }
+    }
+}
+
+impl FollowupOptions {
+    /// Add the filters to a JSON object.
+    pub fn apply_to(&self, obj: &mut serde_json::Map<String, Value>) {
+        obj.insert("days".to_string(), Value::from(self.days));
+        obj.insert("stale".to_string(), Value::from(self.stale));
+        obj.insert(
+            "include_groups".to_string(),
+            Value::Bool(self.include_groups),
+        );
+        obj.insert(
+            "exclude_automated".to_string(),
+            Value::Bool(self.exclude_automated),
+        );
+        if let Some(score) = self.min_question_score {
+            obj.insert("min_question_score".to_string(), Value::from(score));
+        }
+    }
+}
@@ -1,13 +1,14 @@
 //! Fast Rust client for the Wolfies iMessage daemon.
 //!
-//! This client speaks the NDJSON protocol over a Unix domain socket,
+//! This client speaks the NDJSON protocol over a Unix domain socket (or TCP
+//! with `--tcp`),
 //! providing a significant speedup over the Python client by eliminating
 //! the Python interpreter startup overhead.
 
 use clap::{Parser, Subcommand};
 use serde_json::{json, Map, Value};
 use std::process::ExitCode;
-use wolfies_core::{emit_response, DaemonClient, OutputControls, Request};
+use wolfies_core::{DaemonClient, ExitPolicy, FollowupOptions, OutputControls, ResponseEmitter};
 
 /// Fast Rust client for the Wolfies iMessage daemon.
 #[derive(Parser, Debug)]
@@ -18,10 +19,22 @@ struct Cli {
     #[arg(long, default_value_t = default_socket_path())]
     socket: String,
 
+    /// Connect over TCP (host:port) instead of the Unix socket
+    #[arg(long)]
+    tcp: Option<String>,
+
+    /// Token file for --tcp (copy of the daemon's ~/.wolfies-imessage/daemon.token)
+    #[arg(long, default_value_t = default_token_path(), requires = "tcp")]
+    token_file: String,
+
     /// Socket timeout in seconds
     #[arg(long, default_value_t = 2.0)]
     timeout: f64,
 
+    /// Protocol version to request (falls back automatically if the daemon is older)
+    #[arg(long, default_value_t = wolfies_core::protocol::PROTOCOL_V)]
+    protocol_v: u8,
+
     /// Print full response wrapper (for debugging)
     #[arg(long)]
     raw_response: bool,
@@ -50,6 +63,11 @@ struct Cli {
     #[arg(long)]
     text_only_search: bool,
 
+    /// Ask the daemon for this request's log lines (meta.trace; printed to
+    /// stderr unless --raw-response)
+    #[arg(long)]
+    trace: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -60,6 +78,12 @@ fn default_socket_path() -> String {
     format!("{}/.wolfies-imessage/daemon.sock", home)
 }
 
+/// Default token file for TCP connections.
+fn default_token_path() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    format!("{}/.wolfies-imessage/daemon.token", home)
+}
+
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Check daemon health
@@ -106,6 +130,52 @@ enum Command {
         limit: u32,
     },
 
+    /// Unanswered questions and stale conversations (snoozed contacts left out)
+    Followup {
+        /// Days to look back
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+
+        /// Days without a reply before a conversation counts as stale
+        #[arg(long, default_value_t = 3)]
+        stale: u32,
+
+        /// Include questions asked in group chats
+        #[arg(long)]
+        include_groups: bool,
+
+        /// Minimum question score (0.0-1.0)
+        #[arg(long)]
+        min_question_score: Option<f64>,
+
+        /// Leave out automated senders (short codes, 2FA, marketing)
+        #[arg(long)]
+        exclude_automated: bool,
+    },
+
+    /// Snooze a contact's follow-ups
+    FollowupSnooze {
+        /// Contact name, phone, or email
+        contact: String,
+
+        /// Duration (8h, 7d), date, or local time (daemon default: 1d)
+        #[arg(long)]
+        until: Option<String>,
+    },
+
+    /// Remove a contact's follow-up snooze
+    FollowupUnsnooze {
+        /// Contact name, phone, or email
+        contact: String,
+    },
+
+    /// Latest message and unread count for each pinned contact or group
+    Quick {
+        /// Pins to show instead of the daemon's configured `pinned` (repeatable)
+        #[arg(long = "pin")]
+        pins: Vec<String>,
+    },
+
     /// Bundled multi-operation request
     Bundle {
         /// Comma-separated sections to include (meta,unread_count,unread_messages,recent,search,contact_messages)
@@ -154,27 +224,54 @@ fn main() -> ExitCode {
         text_only: cli.text_only_search,
     };
 
+    // Create client (its protocol version stamps every request)
+    let daemon_client = match cli.tcp {
+        Some(ref addr) => match std::fs::read_to_string(&cli.token_file) {
+            Ok(token) => DaemonClient::tcp(addr, token.trim(), cli.timeout),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    json!({
+                        "ok": false,
+                        "error": {
+                            "code": "TOKEN_NOT_FOUND",
+                            "message": format!("Failed to read token file {}: {}", cli.token_file, e),
+                            "details": null
+                        }
+                    })
+                );
+                return ExitCode::from(2);
+            }
+        },
+        None => DaemonClient::new(&cli.socket, cli.timeout),
+    }
+    .with_protocol_version(cli.protocol_v);
+
     // Build the request based on subcommand
-    let request = match &cli.command {
-        Command::Health => Request::no_params("health"),
+    let mut request = match &cli.command {
+        Command::Health => daemon_client.no_params("health"),
 
-        Command::UnreadCount => Request::no_params("unread_count"),
+        Command::UnreadCount => daemon_client.no_params("unread_count"),
 
         Command::Unread { limit } => {
             let mut params = Map::new();
             params.insert("limit".to_string(), json!(limit));
             controls.apply_to(&mut params);
-            Request::new("unread_messages", Value::Object(params))
+            daemon_client.request("unread_messages", Value::Object(params))
         }
 
         Command::Recent { limit } => {
             let mut params = Map::new();
             params.insert("limit".to_string(), json!(limit));
             controls.apply_to(&mut params);
-            Request::new("recent", Value::Object(params))
+            daemon_client.request("recent", Value::Object(params))
         }
 
-        Command::TextSearch { query, limit, since } => {
+        Command::TextSearch {
+            query,
+            limit,
+            since,
+        } => {
             let mut params = Map::new();
             params.insert("query".to_string(), json!(query));
             params.insert("limit".to_string(), json!(limit));
@@ -182,7 +279,7 @@ fn main() -> ExitCode {
                 params.insert("since".to_string(), json!(s));
             }
             controls.apply_to(&mut params);
-            Request::new("text_search", Value::Object(params))
+            daemon_client.request("text_search", Value::Object(params))
         }
 
         Command::MessagesByPhone { phone, limit } => {
@@ -190,7 +287,51 @@ fn main() -> ExitCode {
             params.insert("phone".to_string(), json!(phone));
             params.insert("limit".to_string(), json!(limit));
             controls.apply_to(&mut params);
-            Request::new("messages_by_phone", Value::Object(params))
+            daemon_client.request("messages_by_phone", Value::Object(params))
+        }
+
+        Command::Followup {
+            days,
+            stale,
+            include_groups,
+            min_question_score,
+            exclude_automated,
+        } => {
+            let opts = FollowupOptions {
+                days: *days,
+                stale: *stale,
+                include_groups: *include_groups,
+                min_question_score: *min_question_score,
+                exclude_automated: *exclude_automated,
+            };
+            let mut params = Map::new();
+            opts.apply_to(&mut params);
+            controls.apply_to(&mut params);
+            daemon_client.request("followup", Value::Object(params))
+        }
+
+        Command::FollowupSnooze { contact, until } => {
+            let mut params = Map::new();
+            params.insert("contact".to_string(), json!(contact));
+            if let Some(ref u) = until {
+                params.insert("until".to_string(), json!(u));
+            }
+            daemon_client.request("followup_snooze", Value::Object(params))
+        }
+
+        Command::FollowupUnsnooze { contact } => {
+            let mut params = Map::new();
+            params.insert("contact".to_string(), json!(contact));
+            daemon_client.request("followup_unsnooze", Value::Object(params))
+        }
+
+        Command::Quick { pins } => {
+            let mut params = Map::new();
+            if !pins.is_empty() {
+                params.insert("pins".to_string(), json!(pins));
+            }
+            controls.apply_to(&mut params);
+            daemon_client.request("quick", Value::Object(params))
         }
 
         Command::Bundle {
@@ -225,34 +366,38 @@ fn main() -> ExitCode {
             }
 
             controls.apply_to(&mut params);
-            Request::new("bundle", Value::Object(params))
+            daemon_client.request("bundle", Value::Object(params))
         }
     };
 
-    // Create client and send request
-    let daemon_client = DaemonClient::new(&cli.socket, cli.timeout);
+    if cli.trace {
+        request.params["trace"] = json!(true);
+    }
 
+    // Send request: exit 1 if the daemon reports an error, 2 if it can't be reached
+    let mut emitter =
+        ResponseEmitter::new(cli.raw_response, cli.pretty).with_policy(ExitPolicy::uniform(1, 2));
     match daemon_client.call(&request) {
         Ok(response) => {
-            let output = emit_response(&response, cli.raw_response, cli.pretty);
-            println!("{}", output);
-
-            if response.ok {
-                ExitCode::from(0)
-            } else {
-                ExitCode::from(1)
+            if cli.trace && !cli.raw_response {
+                let lines = response.meta.as_ref().and_then(|m| m.trace.as_ref());
+                for line in lines.into_iter().flatten() {
+                    eprintln!("[trace] {}", line);
+                }
+            }
+            // --raw-response carries them in meta; otherwise they'd be lost with it
+            if !cli.raw_response {
+                for warning in response.meta.iter().flat_map(|m| &m.warnings) {
+                    eprintln!("Warning: {}", warning);
+                }
             }
+            println!("{}", emitter.emit(&response));
+            ExitCode::from(emitter.exit_code())
         }
         Err(e) => {
             // Format client-side error as daemon-style response
-            let error_json = DaemonClient::format_client_error(&e);
-            let output = if cli.pretty {
-                serde_json::to_string_pretty(&error_json).unwrap_or_else(|_| "{}".to_string())
-            } else {
-                serde_json::to_string(&error_json).unwrap_or_else(|_| "{}".to_string())
-            };
-            eprintln!("{}", output);
-            ExitCode::from(2)
+            eprintln!("{}", emitter.emit_transport_error(&e, &request.id));
+            ExitCode::from(emitter.exit_code())
         }
     }
 }
@@ -0,0 +1,125 @@
+//! Hot-query benchmarks over a synthetic chat.db.
+//!
+//! Every workload runs twice: in-process through the api layer (what a CLI
+//! call pays once the database is open), and as a daemon round-trip over a
+//! temp Unix socket (connect, NDJSON request, dispatch, response). Neither
+//! side installs the blob text cache, so blob decoding is measured too.
+//!
+//! The fixture is `SyntheticConfig::default()` (50k messages); set
+//! BENCH_MESSAGES to scale it. Aliases in .cargo/config.toml:
+//!
+//! - `cargo bench-hot`: run the suite
+//! - `cargo bench-baseline`: run and save the numbers as baseline `main`
+//! - `cargo bench-compare`: run and report the change against `main`
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial suite (recent, unread, text_search, analytics, bundle; direct + daemon)
+
+#[path = "../tests/support/mod.rs"]
+mod support;
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+
+use support::synthetic_db::{SyntheticConfig, SyntheticDb, COMMON_TERM};
+use wolfies_imessage::api;
+use wolfies_imessage::contacts::manager::ContactsManager;
+use wolfies_imessage::daemon::server::DaemonServer;
+use wolfies_imessage::daemon::service::DaemonService;
+use wolfies_imessage::daemon_client::DaemonClient;
+use wolfies_imessage::db::connection;
+
+/// Sections in the bundle workload.
+const BUNDLE_INCLUDE: &str = "unread_count,recent,analytics,followup_count";
+
+/// Daemon method and params for each workload, keyed by bench name.
+fn workloads() -> Vec<(&'static str, &'static str, Value)> {
+    vec![
+        ("recent_20", "recent", json!({"limit": 20, "days": 7})),
+        ("unread_50", "unread", json!({"limit": 50})),
+        ("text_search", "text_search", json!({"query": COMMON_TERM, "limit": 50})),
+        ("analytics", "analytics", json!({"days": 30})),
+        ("bundle_4", "bundle", json!({"include": BUNDLE_INCLUDE})),
+    ]
+}
+
+fn fixture_config() -> SyntheticConfig {
+    let mut config = SyntheticConfig::default();
+    if let Some(messages) = std::env::var("BENCH_MESSAGES").ok().and_then(|v| v.parse().ok()) {
+        config.messages = messages;
+    }
+    config
+}
+
+/// Serve `db` from a daemon on a background thread; returns a client for it.
+fn start_daemon(db: &SyntheticDb) -> DaemonClient {
+    // Keep the daemon's state (scheduled-send store) out of the real data dir
+    std::env::set_var("WOLFIES_IMESSAGE_HOME", db.dir.path());
+
+    let socket = db.dir.path().join("daemon.sock");
+    let conn = connection::open_read_only(&db.path).expect("open fixture");
+    let server = DaemonServer::with_service(&socket, DaemonService::from_parts(conn, ContactsManager::empty()));
+    std::thread::spawn(move || server.serve());
+
+    wait_for(&socket);
+    DaemonClient::new(socket)
+}
+
+fn wait_for(socket: &Path) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !socket.exists() {
+        assert!(Instant::now() < deadline, "daemon did not start");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn hot_queries(c: &mut Criterion) {
+    let started = Instant::now();
+    let db = SyntheticDb::create(fixture_config());
+    eprintln!(
+        "fixture: {} messages, {} contacts, {} groups over {} days (generated in {:.1?})",
+        db.config.messages,
+        db.config.contacts,
+        db.config.groups,
+        db.config.days,
+        started.elapsed()
+    );
+
+    let conn = connection::open_read_only(&db.path).expect("open fixture");
+    let contacts = ContactsManager::empty();
+    // No api-level bundle: the direct bundle goes through dispatch, minus the socket
+    let service = DaemonService::from_parts(connection::open_read_only(&db.path).expect("open fixture"), ContactsManager::empty());
+
+    let recent = api::RecentOptions { limit: 20, days: Some(7), ..Default::default() };
+    let unread = api::UnreadOptions { limit: 50, ..Default::default() };
+    let search = api::SearchOptions { query: Some(COMMON_TERM.to_string()), limit: 50, ..Default::default() };
+    let analytics = api::AnalyticsOptions { days: 30, ..Default::default() };
+    let bundle: HashMap<String, Value> = HashMap::from([("include".to_string(), json!(BUNDLE_INCLUDE))]);
+
+    let mut direct = c.benchmark_group("direct");
+    direct.bench_function("recent_20", |b| b.iter(|| black_box(api::recent(&conn, &contacts, &recent).unwrap())));
+    direct.bench_function("unread_50", |b| b.iter(|| black_box(api::unread(&conn, &contacts, &unread).unwrap())));
+    direct.bench_function("text_search", |b| {
+        b.iter(|| black_box(api::text_search(&conn, &contacts, &search).unwrap()))
+    });
+    direct.bench_function("analytics", |b| {
+        b.iter(|| black_box(api::analytics(&conn, &contacts, &analytics).unwrap()))
+    });
+    direct.bench_function("bundle_4", |b| b.iter(|| black_box(service.dispatch("bundle", bundle.clone()).unwrap())));
+    direct.finish();
+
+    let client = start_daemon(&db);
+    let mut daemon = c.benchmark_group("daemon");
+    for (name, method, params) in workloads() {
+        daemon.bench_function(name, |b| b.iter(|| black_box(client.call(method, params.clone()).unwrap())));
+    }
+    daemon.finish();
+}
+
+criterion_group!(benches, hot_queries);
+criterion_main!(benches);
@@ -0,0 +1,1010 @@
+//! Library API: typed, data-returning functions over a Messages database.
+//!
+//! This is the layer the CLI formats and prints and the daemon serializes;
+//! nothing here writes to stdout. Every function takes the connection (and
+//! contacts, for name resolution) explicitly, so callers control where the
+//! database lives and how long it stays open.
+//!
+//! ```no_run
+//! use wolfies_imessage::api::{self, SearchOptions};
+//! use wolfies_imessage::contacts::manager::ContactsManager;
+//! use wolfies_imessage::db::connection::open_db;
+//!
+//! let conn = open_db()?;
+//! let contacts = ContactsManager::load_default_or_empty();
+//!
+//! let opts = SearchOptions { query: Some("dinner".to_string()), ..Default::default() };
+//! let hits = api::text_search(&conn, &contacts, &opts)?;
+//! for hit in &hits {
+//!     let who = hit.message.contact_name.as_deref().unwrap_or(&hit.message.phone);
+//!     println!("{}: {}", who, hit.message.text);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - recent and unread with a handle filter bind its ROWIDs in chunks again (read_messages_for_handles over helpers::query_chunked)
+//! - 10/16/2026 - message_rows_sql selects and limits ROWIDs in a message_page CTE; ServiceFilter uses queries::message_service_is
+//! - 10/16/2026 - recent and unread restrict handles with HandleFilter::sql_condition in one query (dropped read_messages_for_handles)
+//! - 10/16/2026 - muted_conversation_count (muted chats among the most recent that conversations left out)
+//! - 10/16/2026 - AnalyticsOptions.streaks/streaks_all_time: Analytics.streaks (daily streaks, first message, lifetime count)
+//! - 10/16/2026 - unread uses helpers::unread_conditions (past the chat read position, as unread_count and bundle); added unread_total
+//! - 10/16/2026 - AnalyticsOptions.start_cocoa/end_cocoa: analytics over an explicit window (bounded statements); comparison windows match its length
+//! - 10/16/2026 - AnalyticsOptions.compare: Analytics.comparison against the preceding window (upper-bounded combined query)
+//! - 10/16/2026 - FindOptions.with_reactions attaches standing tapbacks to each message (one query for the page)
+//! - 10/16/2026 - Messages carry item_kind (system item placeholders); FindOptions.include_system (default on) and SearchOptions.include_system (default off) keep or drop system items in SQL
+//! - 10/16/2026 - find merges the contact's 1:1 chats on every service (SMS and iMessage) with their messages elsewhere; FindOptions.service (ServiceFilter) keeps one service, before the limit
+//! - 10/16/2026 - unread_by_chat (unread count and oldest unread per conversation) and muted_unread_chat_count
+//! - 10/16/2026 - Messages carry is_known_sender; RecentOptions/UnreadOptions.sender keeps known or unknown senders (in SQL, before the limit)
+//! - 10/16/2026 - recent/unread with handle or relationship filters bind handle ROWIDs in chunks (helpers::query_chunked), merged newest first
+//! - 10/16/2026 - find matches email handles directly; send_to_phone renamed send_to_handle (emails pass through)
+//! - 10/16/2026 - unread_count (single COUNT query)
+//! - 10/16/2026 - Messages carry display_name (ContactsManager::display_name, with the 1:1 chat's name)
+//! - 10/16/2026 - SearchOptions.group scopes text_search to one chat
+//! - 10/16/2026 - Message rows read the attachment hint (placeholders for attachment-only messages)
+//! - 10/16/2026 - unread and conversations leave out muted conversations; added muted_unread_count
+//! - 10/16/2026 - Example loads contacts with load_default_or_empty
+//! - 10/16/2026 - Initial api layer: recent, conversations, unread, find, text_search, analytics, send
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::Connection;
+use std::collections::HashSet;
+
+use crate::commands::{analytics, messaging, reading};
+use crate::config;
+use crate::contacts::manager::{looks_like_email, ContactsManager};
+use crate::db::item_kind::{self, ItemCodes};
+use crate::db::{helpers, queries};
+use crate::mutes::MuteFilter;
+use crate::senders::{SenderFilter, SenderMode};
+
+pub use crate::commands::analytics::{Analytics, Comparison};
+pub use crate::commands::messaging::SendResult;
+pub use crate::commands::reading::{ConversationRow, Message, SearchMatch, UnreadChat};
+
+/// Options for [`recent`].
+#[derive(Debug, Clone)]
+pub struct RecentOptions {
+    pub limit: u32,
+    /// Only messages from the last N days (all history when None).
+    pub days: Option<u32>,
+    /// Only contacts with this relationship type (e.g. "family").
+    pub relationship: Option<String>,
+    /// Only these raw handles; takes precedence over `relationship`.
+    pub handles: Vec<String>,
+    /// Parse mentions, links and subject into `Message::entities`.
+    pub with_entities: bool,
+    /// Only known (or only unknown) senders; applied before the limit.
+    pub sender: SenderMode,
+}
+
+impl Default for RecentOptions {
+    fn default() -> Self {
+        Self {
+            limit: 20,
+            days: None,
+            relationship: None,
+            handles: Vec::new(),
+            with_entities: false,
+            sender: SenderMode::All,
+        }
+    }
+}
+
+/// Options for [`unread`].
+#[derive(Debug, Clone)]
+pub struct UnreadOptions {
+    pub limit: u32,
+    pub relationship: Option<String>,
+    pub with_entities: bool,
+    /// Conversations to leave out (see `MuteFilter::load`).
+    pub mutes: Option<MuteFilter>,
+    /// Only known (or only unknown) senders; applied before the limit.
+    pub sender: SenderMode,
+}
+
+impl Default for UnreadOptions {
+    fn default() -> Self {
+        Self { limit: 50, relationship: None, with_entities: false, mutes: None, sender: SenderMode::All }
+    }
+}
+
+/// Options for [`find`].
+#[derive(Debug, Clone)]
+pub struct FindOptions {
+    /// Contact name, phone, email, or `self` (notes to self).
+    pub contact: String,
+    /// Case-insensitive substring the message text must contain.
+    pub query: Option<String>,
+    pub limit: u32,
+    pub with_entities: bool,
+    /// Only messages sent over this service; applied before the limit.
+    pub service: ServiceFilter,
+    /// Keep system items (renames, FaceTime calls, ...; see `db::item_kind`).
+    pub include_system: bool,
+    /// Attach each message's tapbacks (one more query).
+    pub with_reactions: bool,
+}
+
+impl FindOptions {
+    /// The latest 20 messages with `contact`, system items included.
+    pub fn contact(contact: impl Into<String>) -> Self {
+        Self {
+            contact: contact.into(),
+            query: None,
+            limit: 20,
+            with_entities: false,
+            service: ServiceFilter::All,
+            include_system: true,
+            with_reactions: false,
+        }
+    }
+}
+
+/// Which service's messages [`find`] keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ServiceFilter {
+    #[default]
+    All,
+    IMessage,
+    Sms,
+}
+
+impl ServiceFilter {
+    /// From `--service`: "imessage", "sms", or "all".
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "all" => Ok(ServiceFilter::All),
+            "imessage" => Ok(ServiceFilter::IMessage),
+            "sms" => Ok(ServiceFilter::Sms),
+            other => Err(anyhow!("Unknown service: {} (expected imessage|sms|all)", other)),
+        }
+    }
+
+    /// Condition on the message's service; None keeps every service.
+    fn sql_condition(self) -> Option<String> {
+        let service = match self {
+            ServiceFilter::All => return None,
+            ServiceFilter::IMessage => "imessage",
+            ServiceFilter::Sms => "sms",
+        };
+        Some(queries::message_service_is(service))
+    }
+}
+
+/// Options for [`text_search`]: exactly one of `query`, `any`, or `all`.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub query: Option<String>,
+    /// Match any of these terms.
+    pub any: Vec<String>,
+    /// Match all of these terms.
+    pub all: Vec<String>,
+    /// Only search this group chat (chat identifier or display name).
+    pub group: Option<String>,
+    pub limit: u32,
+    /// Also match system items (off by default; see `db::item_kind`).
+    pub include_system: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self { query: None, any: Vec::new(), all: Vec::new(), group: None, limit: 50, include_system: false }
+    }
+}
+
+/// Options for [`analytics`].
+#[derive(Debug, Clone)]
+pub struct AnalyticsOptions {
+    /// Restrict to one contact (by name).
+    pub contact: Option<String>,
+    pub relationship: Option<String>,
+    pub days: u32,
+    /// Window start (Cocoa ns) instead of `days` back from now.
+    pub start_cocoa: Option<i64>,
+    /// Window end (Cocoa ns, exclusive); None counts up to now.
+    pub end_cocoa: Option<i64>,
+    /// Count notes-to-self in top contacts.
+    pub include_self: bool,
+    /// Compare against the preceding window of the same length (Analytics.comparison).
+    pub compare: bool,
+    /// Daily streaks with the contact (Analytics.streaks); needs `contact`.
+    pub streaks: bool,
+    /// Look for streaks in all history instead of the last `days`.
+    pub streaks_all_time: bool,
+}
+
+impl Default for AnalyticsOptions {
+    fn default() -> Self {
+        Self {
+            contact: None,
+            relationship: None,
+            days: 30,
+            start_cocoa: None,
+            end_cocoa: None,
+            include_self: false,
+            compare: false,
+            streaks: false,
+            streaks_all_time: false,
+        }
+    }
+}
+
+/// Latest messages across all conversations, newest first.
+pub fn recent(conn: &Connection, contacts: &ContactsManager, opts: &RecentOptions) -> Result<Vec<Message>> {
+    let filter = if opts.handles.is_empty() {
+        relationship_filter(conn, opts.relationship.as_deref(), contacts)?
+    } else {
+        Some(helpers::HandleFilter::resolve(conn, &opts.handles)?)
+    };
+
+    let senders = SenderFilter::load(conn, contacts, opts.sender)?;
+
+    let mut conditions = Vec::new();
+    if let Some(days) = opts.days {
+        conditions.push(format!("message.date >= {}", queries::days_ago_cocoa(days)));
+    }
+    conditions.extend(senders.sql_condition("message.ROWID", "message.handle_id"));
+    if let Some(filter) = filter {
+        return read_messages_for_handles(conn, &conditions, &filter, opts.limit, opts.with_entities, contacts, &senders);
+    }
+    let sql = message_rows_sql(&conditions, "?1");
+    read_messages(conn, &sql, [opts.limit], opts.with_entities, contacts, &senders)
+}
+
+/// Latest message per conversation, most recently active first.
+pub fn conversations(
+    conn: &Connection,
+    contacts: &ContactsManager,
+    limit: u32,
+    relationship: Option<&str>,
+    mutes: Option<&MuteFilter>,
+) -> Result<Vec<ConversationRow>> {
+    let filter = relationship_filter(conn, relationship, contacts)?;
+    reading::conversation_rows(conn, limit, filter.as_ref(), mutes, contacts)
+}
+
+/// Conversations among the `limit` most recent that `conversations` left
+/// out because they're muted.
+pub fn muted_conversation_count(
+    conn: &Connection,
+    contacts: &ContactsManager,
+    limit: u32,
+    relationship: Option<&str>,
+    mutes: Option<&MuteFilter>,
+) -> Result<usize> {
+    let Some(mutes) = mutes else {
+        return Ok(0);
+    };
+    let filter = relationship_filter(conn, relationship, contacts)?;
+    let kept: HashSet<String> = helpers::query_recent_conversations(conn, limit, filter.as_ref(), Some(mutes))?
+        .into_iter()
+        .map(|c| c.chat_identifier)
+        .collect();
+    Ok(helpers::query_recent_conversations(conn, limit, filter.as_ref(), None)?
+        .iter()
+        .filter(|c| !kept.contains(&c.chat_identifier))
+        .count())
+}
+
+/// Unread incoming messages, newest first.
+pub fn unread(conn: &Connection, contacts: &ContactsManager, opts: &UnreadOptions) -> Result<Vec<Message>> {
+    let filter = relationship_filter(conn, opts.relationship.as_deref(), contacts)?;
+    let senders = SenderFilter::load(conn, contacts, opts.sender)?;
+
+    let mut conditions = helpers::unread_conditions(conn, "message", opts.mutes.as_ref());
+    conditions.extend(senders.sql_condition("message.ROWID", "message.handle_id"));
+    if let Some(filter) = filter {
+        return read_messages_for_handles(conn, &conditions, &filter, opts.limit, opts.with_entities, contacts, &senders);
+    }
+    let sql = message_rows_sql(&conditions, "?1");
+    read_messages(conn, &sql, [opts.limit], opts.with_entities, contacts, &senders)
+}
+
+/// Number of messages `unread` would return without a limit (no contacts needed).
+pub fn unread_count(conn: &Connection, mutes: Option<&MuteFilter>) -> Result<usize> {
+    helpers::query_unread_count(conn, None, mutes, None)
+}
+
+/// Number of messages `unread` would return for `opts` without its limit
+/// (relationship and sender filters applied).
+pub fn unread_total(conn: &Connection, contacts: &ContactsManager, opts: &UnreadOptions) -> Result<usize> {
+    let filter = relationship_filter(conn, opts.relationship.as_deref(), contacts)?;
+    let senders = SenderFilter::load(conn, contacts, opts.sender)?;
+    helpers::query_unread_count(conn, filter.as_ref(), opts.mutes.as_ref(), Some(&senders))
+}
+
+/// Unread messages `unread` left out because their conversation is muted.
+pub fn muted_unread_count(conn: &Connection, contacts: &ContactsManager, opts: &UnreadOptions) -> Result<usize> {
+    let Some(ref mutes) = opts.mutes else {
+        return Ok(0);
+    };
+    let filter = relationship_filter(conn, opts.relationship.as_deref(), contacts)?;
+    helpers::query_muted_unread_count(conn, filter.as_ref(), mutes)
+}
+
+/// Unread count and oldest unread date per conversation, most recently
+/// active first (past each chat's read position where it has one).
+pub fn unread_by_chat(conn: &Connection, contacts: &ContactsManager, mutes: Option<&MuteFilter>) -> Result<Vec<UnreadChat>> {
+    reading::unread_chat_rows(conn, mutes, contacts)
+}
+
+/// Conversations with unread messages `unread_by_chat` left out because they're muted.
+pub fn muted_unread_chat_count(conn: &Connection, mutes: Option<&MuteFilter>) -> Result<usize> {
+    let Some(mutes) = mutes else {
+        return Ok(0);
+    };
+    let all = helpers::query_unread_by_chat(conn, None)?.len();
+    Ok(all.saturating_sub(helpers::query_unread_by_chat(conn, Some(mutes))?.len()))
+}
+
+/// Messages with one contact, optionally containing `query`, newest first.
+///
+/// A contact's SMS and iMessage histories are separate chats; every 1:1 chat
+/// whose participant normalizes to one of their handles is merged (with the
+/// contact's messages in other chats) in one query, so the date order and
+/// the limit hold across services.
+pub fn find(conn: &Connection, contacts: &ContactsManager, opts: &FindOptions) -> Result<Vec<Message>> {
+    // No contact match: use the input directly as a phone pattern
+    let phone = contacts
+        .resolve_to_phone(&opts.contact)
+        .unwrap_or_else(|| opts.contact.clone());
+
+    // `self` is the notes-to-self conversation, matched by chat rather than handle
+    let scope = if opts.contact.eq_ignore_ascii_case(helpers::SELF_CONTACT) {
+        let mine = helpers::resolve_my_handles(conn, &config::active().my_handles.value)?;
+        let chats = helpers::query_self_chats(conn, &mine)?;
+        if chats.is_empty() {
+            return Err(anyhow!(
+                "No notes-to-self conversation found (set my_handles in config.toml)"
+            ));
+        }
+        let ids: Vec<String> = chats.iter().map(|id| id.to_string()).collect();
+        format!(
+            "message.ROWID IN (SELECT message_id FROM chat_message_join WHERE chat_id IN ({}))",
+            ids.join(",")
+        )
+    } else {
+        let (_, handles) = reading::contact_filter(conn, &opts.contact, contacts)?;
+        let chats = helpers::query_direct_chats_for(conn, &handles)?;
+        format!(
+            r"(message.ROWID IN (SELECT message_id FROM chat_message_join WHERE {}) OR {} OR handle.id LIKE ?1 ESCAPE '\')",
+            helpers::rowids_condition("chat_id", &chats),
+            handles.sql_condition("message.handle_id"),
+        )
+    };
+
+    // Blob-only messages can't be matched in SQL; they're filtered after decoding
+    let mut conditions = vec![scope];
+    conditions.extend(opts.service.sql_condition());
+    if !opts.include_system {
+        conditions.push(item_kind::sql_exclude_system("message"));
+    }
+    if opts.query.is_some() {
+        conditions.push(r"(message.text LIKE ?2 ESCAPE '\' OR message.attributedBody IS NOT NULL)".to_string());
+    }
+    let sql = message_rows_sql(&conditions, "?3");
+
+    // Emails match as written; phones by their digits, whatever the formatting
+    let phone_pattern = if looks_like_email(&phone) {
+        queries::like_contains(&phone)
+    } else {
+        let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+        queries::like_contains(&digits)
+    };
+    let query_pattern = opts.query.as_deref().map(queries::like_contains).unwrap_or_default();
+    let senders = SenderFilter::load(conn, contacts, SenderMode::All)?;
+    let mut messages = read_messages(
+        conn,
+        &sql,
+        rusqlite::params![phone_pattern, query_pattern, opts.limit],
+        opts.with_entities,
+        contacts,
+        &senders,
+    )?;
+
+    if let Some(ref q) = opts.query {
+        let q = q.to_lowercase();
+        messages.retain(|m| m.text.to_lowercase().contains(&q));
+    }
+    if opts.with_reactions {
+        let rowids: Vec<i64> = messages.iter().map(|m| m.rowid).collect();
+        let mut reactions = reading::named_reactions(conn, contacts, &rowids)?;
+        for m in &mut messages {
+            m.reactions = reactions.remove(&m.guid).unwrap_or_default();
+        }
+    }
+    Ok(messages)
+}
+
+/// Messages matching a phrase, or any/all of several terms.
+pub fn text_search(conn: &Connection, contacts: &ContactsManager, opts: &SearchOptions) -> Result<Vec<SearchMatch>> {
+    let (terms, mode) = helpers::resolve_search_terms(opts.query.as_deref(), &opts.any, &opts.all)?;
+    let chat = opts
+        .group
+        .as_deref()
+        .map(|group| {
+            helpers::resolve_group(conn, group)?.ok_or_else(|| anyhow!("Group '{}' not found", group))
+        })
+        .transpose()?;
+    let hits = helpers::query_text_search(
+        conn,
+        &terms,
+        mode,
+        chat.as_ref().map(|c| c.rowid),
+        opts.include_system,
+        opts.limit,
+    )
+        .context("Failed to execute query")?;
+
+    let senders = SenderFilter::load(conn, contacts, SenderMode::All)?;
+    let mut matches = reading::search_matches(hits);
+    for m in &mut matches {
+        m.message.contact_name = contact_name(contacts, &m.message.phone);
+        m.message.display_name = contacts.display_name(&m.message.phone, None);
+        // A 1:1 chat's identifier is the other party's handle
+        let identifier = m.message.group_id.as_deref().unwrap_or(&m.message.phone);
+        m.message.is_known_sender = senders.is_known(Some(&m.message.phone), Some(identifier));
+        // cache_roomnames can be empty on older rows; the scope is authoritative
+        if let Some(chat) = &chat {
+            m.message.is_group_chat = true;
+            m.message.group_id = Some(chat.chat_identifier.clone());
+        }
+    }
+    Ok(matches)
+}
+
+/// Message totals, busiest times, and top contacts over the last `days`, or
+/// the window from `start_cocoa` to `end_cocoa`.
+pub fn analytics(conn: &Connection, contacts: &ContactsManager, opts: &AnalyticsOptions) -> Result<Analytics> {
+    let cutoff_cocoa = opts.start_cocoa.unwrap_or_else(|| queries::days_ago_cocoa(opts.days));
+    let end_cocoa = opts.end_cocoa;
+    let filter = relationship_filter(conn, opts.relationship.as_deref(), contacts)?;
+    let phone = opts
+        .contact
+        .as_deref()
+        .map(|name| {
+            contacts
+                .find_by_name(name)
+                .map(|c| c.phone.clone())
+                .ok_or_else(|| anyhow!("Contact '{}' not found", name))
+        })
+        .transpose()?;
+
+    let combined = |start: i64, end: Option<i64>| match (&filter, phone.as_deref()) {
+        (Some(f), None) => helpers::query_analytics_for_handles(conn, start, end, f),
+        (_, phone) => helpers::query_analytics_combined(conn, start, end, phone),
+    };
+    let stats = combined(cutoff_cocoa, end_cocoa)?;
+
+    // Top contacts only make sense across several people
+    let top_contacts = match phone {
+        Some(_) => Vec::new(),
+        None => {
+            let mine = helpers::self_exclusion(conn, opts.include_self)?;
+            let mut top = helpers::query_top_contacts(conn, cutoff_cocoa, end_cocoa, filter.as_ref(), mine.as_ref())?;
+            for tc in &mut top {
+                tc.contact_name = contact_name(contacts, &tc.phone);
+            }
+            top
+        }
+    };
+
+    // The previous window ends where this one starts
+    let span = end_cocoa.unwrap_or_else(|| queries::days_ago_cocoa(0)) - cutoff_cocoa;
+    let comparison = if opts.compare {
+        let previous_start = cutoff_cocoa - span;
+        let previous = combined(previous_start, Some(cutoff_cocoa))?;
+        Some(Comparison::new(&stats, &previous, previous_start, cutoff_cocoa))
+    } else {
+        None
+    };
+
+    // Whole days, so a DST change inside the window doesn't lose one
+    let days = match opts.start_cocoa {
+        Some(_) => ((span + queries::DAY_NS / 2) / queries::DAY_NS).max(1) as u32,
+        None => opts.days,
+    };
+    let streaks = match (opts.streaks, phone.as_deref()) {
+        (false, _) => None,
+        (true, None) => return Err(anyhow!("streaks needs a contact")),
+        (true, Some(phone)) => {
+            let window = (!opts.streaks_all_time).then_some((cutoff_cocoa, opts.days));
+            Some(analytics::build_streaks(
+                conn,
+                phone,
+                contact_name(contacts, phone).as_deref().unwrap_or(phone),
+                window.map(|(cutoff, _)| cutoff),
+                window.map(|(_, days)| days),
+                chrono::Local::now().date_naive(),
+            )?)
+        }
+    };
+    Ok(Analytics {
+        comparison,
+        streaks,
+        period_start: opts.start_cocoa.map(helpers::cocoa_to_iso),
+        period_end: end_cocoa.map(helpers::cocoa_to_iso),
+        ..Analytics::from_stats(stats, top_contacts, days)
+    })
+}
+
+/// Send `message` to a contact by name (fuzzy) or phone.
+///
+/// Honors the configured dry run and message length limit.
+pub fn send(contacts: &ContactsManager, contact: &str, message: &str) -> Result<SendResult> {
+    let phone = contacts
+        .resolve_to_phone(contact)
+        .ok_or_else(|| anyhow!("Contact '{}' not found", contact))?;
+    messaging::deliver(Some(contact), &phone, message)
+}
+
+/// Send `message` to a phone number (normalized with the configured country
+/// code) or an email handle (as given).
+pub fn send_to_handle(phone: &str, message: &str) -> Result<SendResult> {
+    messaging::deliver(None, &messaging::normalize_phone(phone), message)
+}
+
+/// Resolve an optional relationship type to a handle filter.
+fn relationship_filter(
+    conn: &Connection,
+    relationship: Option<&str>,
+    contacts: &ContactsManager,
+) -> Result<Option<helpers::HandleFilter>> {
+    relationship
+        .map(|r| helpers::HandleFilter::resolve(conn, &contacts.handles_for_relationship(r)?))
+        .transpose()
+}
+
+fn contact_name(contacts: &ContactsManager, handle: &str) -> Option<String> {
+    contacts.find_by_phone(handle).map(|c| c.name.clone())
+}
+
+/// `queries::MESSAGE_ROWS` with `conditions` ANDed, newest first, limited by
+/// `limit_param`. The conditions pick the page's ROWIDs (over `message` and
+/// its sender's `handle`) before any of the row columns are computed.
+fn message_rows_sql(conditions: &[String], limit_param: &str) -> String {
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join("\n  AND "))
+    };
+    format!(
+        "WITH message_page AS (\nSELECT message.ROWID AS rowid\nFROM message\nLEFT JOIN handle ON message.handle_id = handle.ROWID\n{}\nORDER BY message.date DESC\nLIMIT {}\n){}\nORDER BY message.date DESC",
+        where_clause,
+        limit_param,
+        queries::MESSAGE_ROWS,
+    )
+}
+
+/// Run a `MESSAGE_ROWS` query and build messages from its rows.
+fn read_messages(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+    with_entities: bool,
+    contacts: &ContactsManager,
+    senders: &SenderFilter,
+) -> Result<Vec<Message>> {
+    let rows = read_message_rows(conn, sql, params)?;
+    Ok(rows.into_iter().map(|row| build_message(row, with_entities, contacts, senders)).collect())
+}
+
+/// `MESSAGE_ROWS` with `conditions` and the filter's handles, newest first,
+/// at most `limit`. Handle ROWIDs are bound in chunks under the parameter
+/// limit (helpers::query_chunked); the merged rows are re-sorted and cut.
+fn read_messages_for_handles(
+    conn: &Connection,
+    conditions: &[String],
+    filter: &helpers::HandleFilter,
+    limit: u32,
+    with_entities: bool,
+    contacts: &ContactsManager,
+    senders: &SenderFilter,
+) -> Result<Vec<Message>> {
+    let limit_param = limit as i64;
+    let mut rows = helpers::query_chunked(filter.rowids(), 1, |chunk| {
+        let mut chunk_conditions = conditions.to_vec();
+        chunk_conditions.push(queries::in_params("message.handle_id", 2, chunk.len()));
+        let sql = message_rows_sql(&chunk_conditions, "?1");
+        read_message_rows(conn, &sql, rusqlite::params_from_iter(std::iter::once(&limit_param).chain(chunk)))
+    })?;
+    rows.sort_by(|a, b| b.date_cocoa.cmp(&a.date_cocoa).then(b.rowid.cmp(&a.rowid)));
+    rows.truncate(limit as usize);
+    Ok(rows.into_iter().map(|row| build_message(row, with_entities, contacts, senders)).collect())
+}
+
+fn read_message_rows(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> Result<Vec<reading::MessageRow>> {
+    let mut stmt = conn.prepare(sql).context("Failed to prepare query")?;
+    let rows = stmt
+        .query_map(params, |row| {
+            Ok(reading::MessageRow {
+                text: row.get(0)?,
+                attributed_body: row.get(1)?,
+                date_cocoa: row.get(2)?,
+                is_from_me: row.get::<_, i32>(3)? != 0,
+                handle: row.get(4)?,
+                cache_roomnames: row.get(5)?,
+                subject: row.get(6)?,
+                guid: row.get(7)?,
+                rowid: row.get(8)?,
+                attachment_hint: row.get(9)?,
+                chat_hint: row.get(10)?,
+                service: row.get(11)?,
+                item: ItemCodes {
+                    item_type: row.get(12)?,
+                    group_action_type: row.get(13)?,
+                    associated_message_type: row.get(14)?,
+                    balloon_bundle_id: row.get(15)?,
+                    group_title: row.get(16)?,
+                    other_handle: row.get(17)?,
+                },
+            })
+        })
+        .context("Failed to execute query")?;
+
+    rows.map(|row| row.context("Failed to read row")).collect()
+}
+
+/// A message with its contact and display names and known-sender flag filled in.
+fn build_message(
+    row: reading::MessageRow,
+    with_entities: bool,
+    contacts: &ContactsManager,
+    senders: &SenderFilter,
+) -> Message {
+    let chat_name = row.direct_chat_name().map(str::to_string);
+    let is_known_sender = senders.is_known(row.handle.as_deref(), row.chat_identifier());
+    let mut message = row.into_message(with_entities);
+    message.is_known_sender = is_known_sender;
+    message.contact_name = contact_name(contacts, &message.phone);
+    message.display_name = contacts.display_name(&message.phone, chat_name.as_deref());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contacts::manager::Contact;
+    use crate::db::fixtures::{empty_db, insert_attachment, insert_chat, insert_handle, insert_message, set_chat_service};
+
+    fn contacts() -> ContactsManager {
+        ContactsManager::from_contacts(vec![Contact {
+            name: "Alice".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }])
+    }
+
+    #[test]
+    fn test_sender_filter_applies_before_the_limit() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let stranger = insert_handle(&conn, "+14155550009");
+        let chat_alice = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let chat_stranger = insert_chat(&conn, "+14155550009", None, &[stranger]);
+        let now = queries::days_ago_cocoa(0);
+        // The newest unread rows are all from the stranger
+        for i in 0..3 {
+            insert_message(&conn, chat_alice, alice, Some("from alice"), now - 100 + i, false, false);
+        }
+        for i in 0..5 {
+            insert_message(&conn, chat_stranger, stranger, Some("from stranger"), now - 10 + i, false, false);
+        }
+        let contacts = contacts();
+
+        let known = UnreadOptions { limit: 2, sender: SenderMode::Known, ..Default::default() };
+        let messages = unread(&conn, &contacts, &known).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages.iter().all(|m| m.is_known_sender && m.text == "from alice"));
+
+        let unknown = RecentOptions { limit: 10, sender: SenderMode::Unknown, ..Default::default() };
+        let messages = recent(&conn, &contacts, &unknown).unwrap();
+        assert_eq!(messages.len(), 5);
+        assert!(messages.iter().all(|m| !m.is_known_sender));
+
+        let all = unread(&conn, &contacts, &UnreadOptions::default()).unwrap();
+        assert_eq!(all.iter().filter(|m| m.is_known_sender).count(), 3);
+    }
+
+    #[test]
+    fn test_find_merges_sms_and_imessage_history() {
+        let conn = empty_db();
+        // Messages keeps one handle and one chat per service for the same number
+        let imessage = insert_handle(&conn, "+14155550001");
+        let sms = insert_handle(&conn, "4155550001");
+        let stranger = insert_handle(&conn, "+14155550009");
+        let chat_imessage = insert_chat(&conn, "+14155550001", None, &[imessage]);
+        let chat_sms = insert_chat(&conn, "4155550001", None, &[sms]);
+        let chat_stranger = insert_chat(&conn, "+14155550009", None, &[stranger]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, chat_sms, sms, Some("sms 1"), now - 60, false, true);
+        insert_message(&conn, chat_imessage, imessage, Some("imessage 1"), now - 50, false, true);
+        // Sent SMS rows can have no handle at all; only the chat ties them to Alice
+        insert_message(&conn, chat_sms, 0, Some("sms 2"), now - 40, true, true);
+        insert_message(&conn, chat_stranger, stranger, Some("not alice"), now - 35, false, true);
+        insert_message(&conn, chat_imessage, imessage, Some("imessage 2"), now - 30, true, true);
+        insert_message(&conn, chat_sms, sms, Some("sms 3"), now - 20, false, true);
+        set_chat_service(&conn, chat_sms, "SMS");
+        // No service on the row: the chat's applies
+        conn.execute("UPDATE message SET service = NULL WHERE text = 'sms 3'", []).unwrap();
+        let contacts = contacts();
+        let found = |service, limit| {
+            let opts = FindOptions { service, limit, ..FindOptions::contact("Alice") };
+            find(&conn, &contacts, &opts)
+                .unwrap()
+                .into_iter()
+                .map(|m| (m.text, m.service.unwrap_or_default()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            found(ServiceFilter::All, 10),
+            [
+                ("sms 3".to_string(), "SMS".to_string()),
+                ("imessage 2".to_string(), "iMessage".to_string()),
+                ("sms 2".to_string(), "SMS".to_string()),
+                ("imessage 1".to_string(), "iMessage".to_string()),
+                ("sms 1".to_string(), "SMS".to_string()),
+            ]
+        );
+        // The limit cuts the merged history, not each chat
+        let latest: Vec<_> = found(ServiceFilter::All, 3).into_iter().map(|(text, _)| text).collect();
+        assert_eq!(latest, ["sms 3", "imessage 2", "sms 2"]);
+        let sms_only: Vec<_> = found(ServiceFilter::Sms, 2).into_iter().map(|(text, _)| text).collect();
+        assert_eq!(sms_only, ["sms 3", "sms 2"]);
+        let imessage_only: Vec<_> = found(ServiceFilter::IMessage, 10).into_iter().map(|(text, _)| text).collect();
+        assert_eq!(imessage_only, ["imessage 2", "imessage 1"]);
+
+        assert_eq!(ServiceFilter::parse(" SMS ").unwrap(), ServiceFilter::Sms);
+        assert!(ServiceFilter::parse("rcs").is_err());
+    }
+
+    #[test]
+    fn test_display_name_precedence_in_messages() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let acme = insert_handle(&conn, "+18005550100");
+        let stranger = insert_handle(&conn, "+14155550003");
+        // Alice's chat has a nickname, but the contact name wins
+        let chat_alice = insert_chat(&conn, "+14155550001", Some("Ali"), &[alice]);
+        let chat_acme = insert_chat(&conn, "+18005550100", Some("Acme Support"), &[acme]);
+        let chat_stranger = insert_chat(&conn, "+14155550003", None, &[stranger]);
+        let group = insert_chat(&conn, "chat123456", Some("Hikers"), &[stranger, alice]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, chat_alice, alice, Some("hi"), now - 4, false, false);
+        insert_message(&conn, chat_acme, acme, Some("Your order shipped"), now - 3, false, false);
+        insert_message(&conn, chat_stranger, stranger, Some("who dis"), now - 2, false, false);
+        insert_message(&conn, group, stranger, Some("trail at 9"), now - 1, false, false);
+        let contacts = contacts();
+
+        let names = |messages: Vec<Message>| messages.into_iter().map(|m| m.display_name).collect::<Vec<_>>();
+        // A group's name isn't its members' identity
+        let expected = ["+14155550003", "+14155550003", "Acme Support", "Alice"];
+        assert_eq!(names(recent(&conn, &contacts, &RecentOptions::default()).unwrap()), expected);
+        assert_eq!(names(unread(&conn, &contacts, &UnreadOptions::default()).unwrap()), expected);
+        let acme_messages = find(&conn, &contacts, &FindOptions::contact("+18005550100")).unwrap();
+        assert_eq!(names(acme_messages), ["Acme Support"]);
+    }
+
+    #[test]
+    fn test_recent_for_thousands_of_handles() {
+        let conn = empty_db();
+        let chat = insert_chat(&conn, "chat-many", None, &[]);
+        let phones: Vec<String> = (0..2500).map(|i| format!("+1415{:07}", i)).collect();
+        for (i, phone) in phones.iter().enumerate() {
+            let handle = insert_handle(&conn, phone);
+            insert_message(&conn, chat, handle, Some(phone), 1_000 + i as i64, false, false);
+        }
+
+        // No handles means no handle filter
+        for count in [0, 1, 999, 2500] {
+            let opts = RecentOptions { limit: 3, handles: phones[..count].to_vec(), ..Default::default() };
+            let texts: Vec<String> = recent(&conn, &contacts(), &opts).unwrap().into_iter().map(|m| m.text).collect();
+            let scope = if count == 0 { &phones[..] } else { &phones[..count] };
+            let expected: Vec<String> = scope.iter().rev().take(3).cloned().collect();
+            assert_eq!(texts, expected, "{} handles", count);
+        }
+    }
+
+    #[test]
+    fn test_find_by_email_handle() {
+        let conn = empty_db();
+        let sarah = insert_handle(&conn, "sarah@icloud.com");
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat_sarah = insert_chat(&conn, "sarah@icloud.com", None, &[sarah]);
+        let chat_alice = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, chat_sarah, sarah, Some("from sarah"), now - 2, false, true);
+        insert_message(&conn, chat_alice, alice, Some("from alice"), now - 1, false, true);
+
+        // Not fuzzy-matched to a contact, and not reduced to (no) digits
+        let found = find(&conn, &contacts(), &FindOptions::contact("Sarah@iCloud.com")).unwrap();
+        let texts: Vec<&str> = found.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["from sarah"]);
+    }
+
+    #[test]
+    fn test_typed_results_without_stdout() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let chat_a = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let chat_b = insert_chat(&conn, "+14155550002", None, &[bob]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, chat_a, alice, Some("Dinner at 7?"), now - 3, false, false);
+        insert_message(&conn, chat_a, alice, Some("yes"), now - 2, true, true);
+        insert_message(&conn, chat_b, bob, Some("dinner was great"), now - 1, false, true);
+        let contacts = contacts();
+
+        let latest = recent(&conn, &contacts, &RecentOptions::default()).unwrap();
+        let texts: Vec<&str> = latest.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["dinner was great", "yes", "Dinner at 7?"]);
+        assert_eq!(latest[1].contact_name.as_deref(), Some("Alice"));
+        assert_eq!(latest[0].contact_name, None);
+
+        let friends = RecentOptions { relationship: Some("friend".to_string()), ..Default::default() };
+        assert_eq!(recent(&conn, &contacts, &friends).unwrap().len(), 2);
+
+        let pending = unread(&conn, &contacts, &UnreadOptions::default()).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].text, "Dinner at 7?");
+
+        let opts = FindOptions { query: Some("DINNER".to_string()), ..FindOptions::contact("Alice") };
+        assert_eq!(find(&conn, &contacts, &opts).unwrap().len(), 1);
+
+        let opts = SearchOptions { query: Some("dinner".to_string()), ..Default::default() };
+        let hits = text_search(&conn, &contacts, &opts).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].matched_terms, ["dinner"]);
+
+        let stats = analytics(&conn, &contacts, &AnalyticsOptions::default()).unwrap();
+        assert_eq!((stats.total_messages, stats.sent_count, stats.received_count), (3, 1, 2));
+        let alice_row = stats.top_contacts.iter().find(|tc| tc.phone == "+14155550001").unwrap();
+        assert_eq!(alice_row.contact_name.as_deref(), Some("Alice"));
+    }
+
+    #[test]
+    fn test_text_search_scoped_to_group() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let direct = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let group = insert_chat(&conn, "chat900", Some("Ski Trip"), &[alice, bob]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, direct, alice, Some("cabin_50% deposit due"), now - 3, false, true);
+        insert_message(&conn, group, alice, Some("who has the cabin_50% code?"), now - 2, false, true);
+        insert_message(&conn, group, bob, Some("no idea"), now - 1, false, true);
+        let contacts = contacts();
+
+        let everywhere = SearchOptions { query: Some("cabin_50%".to_string()), ..Default::default() };
+        assert_eq!(text_search(&conn, &contacts, &everywhere).unwrap().len(), 2);
+
+        for group in ["chat900", "ski trip"] {
+            let scoped = SearchOptions { group: Some(group.to_string()), ..everywhere.clone() };
+            let hits = text_search(&conn, &contacts, &scoped).unwrap();
+            assert_eq!(hits.len(), 1, "{}", group);
+            assert_eq!(hits[0].message.text, "who has the cabin_50% code?");
+            assert_eq!(hits[0].message.contact_name.as_deref(), Some("Alice"));
+            assert_eq!(hits[0].message.group_id.as_deref(), Some("chat900"));
+        }
+
+        let missing = SearchOptions { group: Some("nope".to_string()), ..everywhere };
+        assert!(text_search(&conn, &contacts, &missing).unwrap_err().to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_unread_by_chat_names_and_mutes() {
+        use crate::db::fixtures::set_last_read;
+        use crate::mutes::{Mute, MuteKind};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let stranger = insert_handle(&conn, "+14155550009");
+        let direct = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let shop = insert_chat(&conn, "+14155550009", Some("Bike Shop"), &[stranger]);
+        let group = insert_chat(&conn, "chat77", None, &[alice, stranger]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, direct, alice, Some("read already"), now - 30, false, false);
+        insert_message(&conn, direct, alice, Some("new"), now - 20, false, false);
+        set_last_read(&conn, direct, now - 25);
+        insert_message(&conn, shop, stranger, Some("bike's ready"), now - 10, false, false);
+        insert_message(&conn, group, stranger, Some("hello all"), now - 5, false, false);
+        insert_message(&conn, group, alice, Some("hi!"), now - 4, false, false);
+
+        let chats = unread_by_chat(&conn, &contacts(), None).unwrap();
+        let rows: Vec<(&str, &str, i64, bool)> = chats
+            .iter()
+            .map(|c| (c.chat_identifier.as_str(), c.name.as_str(), c.unread_count, c.is_group))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("chat77", "chat77", 2, true),
+                ("+14155550009", "Bike Shop", 1, false),
+                ("+14155550001", "Alice", 1, false),
+            ]
+        );
+        assert_eq!(chats[0].oldest_unread_date, helpers::cocoa_to_iso(now - 5));
+        assert_eq!(muted_unread_chat_count(&conn, None).unwrap(), 0);
+
+        let mute = Mute {
+            kind: MuteKind::Group,
+            key: "chat77".to_string(),
+            handles: Vec::new(),
+            target: "chat77".to_string(),
+            name: None,
+            until: None,
+            created_at: chrono::Local::now(),
+        };
+        let mutes = MuteFilter::resolve(&conn, &[mute]).unwrap();
+        let kept = unread_by_chat(&conn, &contacts(), Some(&mutes)).unwrap();
+        assert_eq!(kept.iter().map(|c| c.chat_identifier.as_str()).collect::<Vec<_>>(), ["+14155550009", "+14155550001"]);
+        assert_eq!(muted_unread_chat_count(&conn, Some(&mutes)).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_attachment_only_messages_keep_text_empty() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let now = queries::days_ago_cocoa(0);
+        let photo = insert_message(&conn, chat, alice, Some("\u{FFFC}"), now - 2, false, true);
+        insert_attachment(&conn, photo, Some("image/png"), "screenshot.png");
+        let pdf = insert_message(&conn, chat, alice, None, now - 1, false, true);
+        insert_attachment(&conn, pdf, None, "lease.pdf");
+
+        let latest = recent(&conn, &contacts(), &RecentOptions::default()).unwrap();
+        let bodies: Vec<(&str, Option<&str>)> =
+            latest.iter().map(|m| (m.text.as_str(), m.placeholder.as_deref())).collect();
+        assert_eq!(bodies, [("", Some("[file: lease.pdf]")), ("", Some("[photo]"))]);
+    }
+
+    #[test]
+    fn test_analytics_compare_with_previous_window() {
+        use crate::commands::analytics::{Direction, MetricDelta};
+        use crate::db::fixtures::{insert_reaction, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let now = queries::days_ago_cocoa(0);
+        // This week: 2 sent, 2 received, one photo, one tapback
+        for i in 0..4 {
+            insert_message(&conn, chat, alice, Some("this week"), now - DAY_NS + i, i % 2 == 0, true);
+        }
+        let photo = insert_message(&conn, chat, 0, None, now - DAY_NS + 10, true, true);
+        insert_attachment(&conn, photo, Some("image/jpeg"), "IMG_1.jpg");
+        insert_reaction(&conn, chat, alice, &format!("msg-{}", photo), 2000, now - DAY_NS + 11, false);
+        // Last week: 1 sent, 3 received, no attachments or tapbacks
+        for i in 0..4 {
+            insert_message(&conn, chat, alice, Some("last week"), now - 10 * DAY_NS + i, i == 0, true);
+        }
+        // Before both windows
+        insert_message(&conn, chat, alice, Some("old"), now - 30 * DAY_NS, false, true);
+
+        let opts = AnalyticsOptions { days: 7, ..Default::default() };
+        let plain = analytics(&conn, &contacts(), &opts).unwrap();
+        assert!(plain.comparison.is_none());
+        assert!(serde_json::to_value(&plain).unwrap().get("comparison").is_none());
+
+        let report = analytics(&conn, &contacts(), &AnalyticsOptions { compare: true, ..opts }).unwrap();
+        let c = report.comparison.unwrap();
+        assert_eq!(c.previous_end, helpers::cocoa_to_iso(queries::days_ago_cocoa(7)));
+        assert_eq!(
+            c.total_messages,
+            MetricDelta { current: 5, previous: 4, delta: 1, delta_pct: Some(25.0), direction: Direction::Up }
+        );
+        assert_eq!((c.sent_count.delta, c.sent_count.delta_pct), (2, Some(200.0)));
+        assert_eq!((c.received_count.delta, c.received_count.direction), (-1, Direction::Down));
+        // Nothing to compare against: no percentage
+        assert_eq!(c.attachment_count.delta_pct, None);
+        assert_eq!((c.reaction_count.current, c.reaction_count.previous), (1, 0));
+        let json = serde_json::to_value(&c).unwrap();
+        assert!(json["reaction_count"]["delta_pct"].is_null());
+        assert_eq!(json["received_count"]["direction"], "down");
+        assert_eq!(MetricDelta::new(3, 3).direction, Direction::Flat);
+    }
+}
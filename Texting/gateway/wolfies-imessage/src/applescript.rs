@@ -3,22 +3,98 @@
 //! Uses osascript to communicate with Messages.app.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - mark_chat_read looks the chat up by its id (chat.guid, groups included) and opens it by participants; script values pass as argv
+//! - 10/16/2026 - send_imessage passes phone and message as argv to a fixed script; escaping also encodes newlines/tabs and drops NUL
+//! - 10/16/2026 - Automation denials (-1743) surface as CliError::AutomationDenied; added probe_automation
+//! - 10/16/2026 - Added messages_accounts (enabled services, for `account`)
+//! - 10/16/2026 - Added display_notification
+//! - 10/16/2026 - Added mark_chat_read
 //! - 01/10/2026 - Initial implementation (Claude)
 
 use anyhow::{anyhow, Result};
 use std::process::Command;
 use std::time::Duration;
 
+use crate::error::CliError;
+
 /// Escape a string for safe inclusion in AppleScript.
 ///
 /// CRITICAL: Order matters!
 /// 1. Escape backslashes FIRST
 /// 2. Then escape quotes
+/// 3. Encode line breaks and tabs (\n, \r, \t) so the literal stays on one line
 ///
-/// This prevents injection attacks where user data breaks the string context.
+/// NUL bytes are dropped (osascript can't take them). This prevents injection
+/// attacks where user data breaks the string context.
 pub fn escape_applescript_string(s: &str) -> String {
-    s.replace('\\', "\\\\") // Backslashes FIRST
+    strip_nul(s)
+        .replace('\\', "\\\\") // Backslashes FIRST
         .replace('"', "\\\"") // Then quotes
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// Drop NUL bytes (process arguments can't carry them).
+pub fn strip_nul(s: &str) -> String {
+    s.replace('\0', "")
+}
+
+/// Whether osascript stderr means macOS refused Apple events to the target app.
+///
+/// -1743 is errAEEventNotPermitted ("Not authorized to send Apple events");
+/// -1744 is the consent-required variant seen before the prompt is answered.
+pub fn is_automation_denied(stderr: &str) -> bool {
+    stderr.contains("(-1743)")
+        || stderr.contains("(-1744)")
+        || stderr.to_lowercase().contains("not authorized to send apple events")
+}
+
+/// Map failed osascript stderr to an error, singling out Automation denials.
+fn osascript_error(stderr: &[u8]) -> anyhow::Error {
+    let stderr = String::from_utf8_lossy(stderr).trim().to_string();
+    if is_automation_denied(&stderr) {
+        CliError::AutomationDenied { detail: stderr }.into()
+    } else {
+        anyhow!("AppleScript failed: {}", stderr)
+    }
+}
+
+/// Harmless script that needs Automation access to Messages.
+const AUTOMATION_PROBE_SCRIPT: &str = r#"tell application "Messages" to get name"#;
+
+/// Check Automation access to Messages without sending anything.
+///
+/// Fails with `CliError::AutomationDenied` when access is denied; may show
+/// the macOS consent prompt on first run.
+pub fn probe_automation() -> Result<()> {
+    let output = Command::new("osascript").arg("-e").arg(AUTOMATION_PROBE_SCRIPT).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(osascript_error(&output.stderr))
+    }
+}
+
+/// Send script for `send_imessage`. Phone and message arrive as `argv`, so
+/// user text is never spliced into AppleScript source.
+const SEND_SCRIPT: &str = r#"
+on run argv
+    tell application "Messages"
+        set targetService to 1st account whose service type = iMessage
+        set targetBuddy to participant (item 1 of argv) of targetService
+        send (item 2 of argv) to targetBuddy
+    end tell
+end run
+"#;
+
+/// osascript invocation for a send: the fixed script, then phone and message
+/// as arguments (NUL bytes dropped; newlines and quotes pass through as-is).
+fn send_command(phone: &str, message: &str) -> Command {
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(SEND_SCRIPT).arg(strip_nul(phone)).arg(strip_nul(message));
+    command
 }
 
 /// Send an iMessage via Messages.app.
@@ -26,40 +102,162 @@ pub fn escape_applescript_string(s: &str) -> String {
 /// Uses AppleScript to target the iMessage service and send to a participant.
 ///
 /// # Arguments
-/// * `phone` - Phone number or email (will be escaped)
-/// * `message` - Message text (will be escaped)
+/// * `phone` - Phone number or email (passed as argv)
+/// * `message` - Message text (passed as argv)
 ///
 /// # Returns
 /// * `Ok(())` on success
 /// * `Err` with AppleScript error on failure
 pub fn send_imessage(phone: &str, message: &str) -> Result<()> {
-    let safe_phone = escape_applescript_string(phone);
-    let safe_msg = escape_applescript_string(message);
+    let output = send_command(phone, message).output()?;
 
-    let script = format!(
-        r#"
-tell application "Messages"
-    set targetService to 1st account whose service type = iMessage
-    set targetBuddy to participant "{}" of targetService
-    send "{}" to targetBuddy
-end tell
-"#,
-        safe_phone, safe_msg
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(osascript_error(&output.stderr))
+    }
+}
+
+/// Check that a chat identifier is safe to embed in a script and URL.
+///
+/// Chat identifiers are phone numbers, emails, or `chatNNN` group ids; anything
+/// else is rejected rather than escaped, since it also ends up in a URL.
+pub fn is_safe_chat_identifier(chat_identifier: &str) -> bool {
+    !chat_identifier.is_empty()
+        && chat_identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '@' | '.' | '_' | '-'))
+}
+
+/// Check that a chat guid (`service;style;identifier`, Messages' chat id) is
+/// safe to pass to a script: an identifier's characters plus `;`.
+pub fn is_safe_chat_guid(chat_guid: &str) -> bool {
+    chat_guid.split(';').count() == 3 && chat_guid.split(';').all(is_safe_chat_identifier)
+}
+
+/// Mark-read script for `mark_chat_read`: the chat id and the URL that
+/// displays it arrive as `argv`. Errors if Messages has no chat with that id.
+const MARK_READ_SCRIPT: &str = r#"
+on run argv
+    tell application "Messages"
+        if not (exists chat id (item 1 of argv)) then error "Chat not found: " & (item 1 of argv)
+    end tell
+    open location (item 2 of argv)
+end run
+"#;
+
+/// URL that opens the conversation with exactly `participants` (for a group,
+/// the existing group chat with them).
+fn mark_read_url(participants: &[String]) -> String {
+    format!("imessage://open?addresses={}", participants.join(","))
+}
+
+/// Display a conversation in Messages.app, which is what marks it read.
+///
+/// Messages exposes no scriptable read flag, and chat.db is opened read-only.
+/// The chat is looked up by its id (chat.guid, so group chats work too) and
+/// opened by its `participants`. Fails if a value is unsafe, the chat doesn't
+/// exist in Messages, or osascript errors; whether it ended up read is the
+/// caller's to check in chat.db.
+pub fn mark_chat_read(chat_guid: &str, participants: &[String]) -> Result<()> {
+    if !is_safe_chat_guid(chat_guid) {
+        return Err(anyhow!("Invalid chat id: {}", chat_guid));
+    }
+    if participants.is_empty() {
+        return Err(anyhow!("Chat {} has no participants to open it by", chat_guid));
+    }
+    if let Some(bad) = participants.iter().find(|p| !is_safe_chat_identifier(p)) {
+        return Err(anyhow!("Invalid participant handle: {}", bad));
+    }
+
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(MARK_READ_SCRIPT)
+        .arg(chat_guid)
+        .arg(mark_read_url(participants))
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(osascript_error(&output.stderr))
+    }
+}
+
+/// Build a `display notification` script with every field escaped.
+fn notification_script(title: &str, subtitle: Option<&str>, body: &str) -> String {
+    let mut script = format!(
+        r#"display notification "{}" with title "{}""#,
+        escape_applescript_string(body),
+        escape_applescript_string(title)
     );
+    if let Some(subtitle) = subtitle {
+        script.push_str(&format!(r#" subtitle "{}""#, escape_applescript_string(subtitle)));
+    }
+    script
+}
 
+/// Post a macOS user notification.
+pub fn display_notification(title: &str, subtitle: Option<&str>, body: &str) -> Result<()> {
     let output = Command::new("osascript")
         .arg("-e")
-        .arg(&script)
+        .arg(notification_script(title, subtitle, body))
         .output()?;
 
     if output.status.success() {
         Ok(())
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(anyhow!(
-            "AppleScript failed: {}",
-            stderr.trim().to_string()
-        ))
+        Err(osascript_error(&output.stderr))
+    }
+}
+
+/// An account configured in Messages.app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessagesAccount {
+    /// "iMessage", "SMS", ...
+    pub service: String,
+    /// Account description (the Apple ID or phone number)
+    pub handle: String,
+    pub enabled: bool,
+}
+
+/// One tab-separated line per account: service type, description, enabled.
+const ACCOUNTS_SCRIPT: &str = r#"
+tell application "Messages"
+    set output to ""
+    repeat with acct in every account
+        set output to output & (service type of acct as text) & tab & (description of acct) & tab & (enabled of acct as text) & linefeed
+    end repeat
+    return output
+end tell
+"#;
+
+/// Parse `ACCOUNTS_SCRIPT` output; malformed lines are skipped.
+fn parse_accounts(stdout: &str) -> Vec<MessagesAccount> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let service = fields.next().filter(|s| !s.is_empty())?;
+            let handle = fields.next()?;
+            let enabled = fields.next()?;
+            Some(MessagesAccount {
+                service: service.to_string(),
+                handle: handle.to_string(),
+                enabled: enabled.eq_ignore_ascii_case("true"),
+            })
+        })
+        .collect()
+}
+
+/// Accounts configured in Messages.app.
+pub fn messages_accounts() -> Result<Vec<MessagesAccount>> {
+    let output = Command::new("osascript").arg("-e").arg(ACCOUNTS_SCRIPT).output()?;
+
+    if output.status.success() {
+        Ok(parse_accounts(&String::from_utf8_lossy(&output.stdout)))
+    } else {
+        Err(osascript_error(&output.stderr))
     }
 }
 
@@ -101,6 +299,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_safe_chat_identifier() {
+        assert!(is_safe_chat_identifier("+14155551234"));
+        assert!(is_safe_chat_identifier("someone@example.com"));
+        assert!(is_safe_chat_identifier("chat123456789"));
+        assert!(!is_safe_chat_identifier(""));
+        assert!(!is_safe_chat_identifier(r#"x" & do shell script "rm"#));
+        assert!(!is_safe_chat_identifier("a b"));
+    }
+
+    #[test]
+    fn test_safe_chat_guid() {
+        assert!(is_safe_chat_guid("iMessage;-;+14155551234"));
+        assert!(is_safe_chat_guid("iMessage;+;chat123456789"));
+        assert!(is_safe_chat_guid("SMS;-;someone@example.com"));
+        assert!(!is_safe_chat_guid("chat123456789"));
+        assert!(!is_safe_chat_guid("iMessage;+;chat1;x"));
+        assert!(!is_safe_chat_guid(r#"iMessage;+;x" & do shell script "rm"#));
+    }
+
+    #[test]
+    fn test_mark_read_url_opens_by_participants() {
+        assert_eq!(mark_read_url(&["+14155551234".to_string()]), "imessage://open?addresses=+14155551234");
+        let group = ["+14155551234".to_string(), "someone@example.com".to_string()];
+        assert_eq!(mark_read_url(&group), "imessage://open?addresses=+14155551234,someone@example.com");
+        assert!(MARK_READ_SCRIPT.contains("exists chat id (item 1 of argv)"));
+    }
+
+    #[test]
+    fn test_notification_script_escapes_fields() {
+        let script = notification_script(r#"Al "the" Pal"#, Some("Family"), r"a\b");
+        assert_eq!(
+            script,
+            r#"display notification "a\\b" with title "Al \"the\" Pal" subtitle "Family""#
+        );
+        assert!(!notification_script("t", None, "b").contains("subtitle"));
+    }
+
+    /// Where the AppleScript string literal opening `script[0]` ends, or None
+    /// if it never closes (backslash escapes skipped, as AppleScript reads them).
+    fn literal_end(script: &str) -> Option<usize> {
+        let chars: Vec<char> = script.chars().collect();
+        assert_eq!(chars.first(), Some(&'"'));
+        let mut i = 1;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 2,
+                '"' => return Some(i),
+                _ => i += 1,
+            }
+        }
+        None
+    }
+
+    const HOSTILE: &str = "ok\" & (do shell script \"rm -rf ~\") & \"\n\
+        end tell\r\n\
+        tell application \"Finder\" to delete every file\t\\\" \\\\\0\n\
+        set x to \"";
+
+    #[test]
+    fn test_escape_keeps_hostile_text_one_literal() {
+        let escaped = escape_applescript_string(HOSTILE);
+        assert!(!escaped.contains(['\n', '\r', '\t', '\0']));
+        let literal = format!("\"{}\"", escaped);
+        assert_eq!(literal_end(&literal), Some(literal.chars().count() - 1));
+        assert_eq!(escape_applescript_string("a\nb\r\tc\0"), r"a\nb\r\tc");
+
+        let script = notification_script(HOSTILE, None, HOSTILE);
+        assert_eq!(script.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_send_passes_message_as_argv() {
+        let command = send_command("+14155551234", HOSTILE);
+        let args: Vec<_> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(command.get_program(), "osascript");
+        assert_eq!(args.len(), 4);
+        assert_eq!((args[0].as_str(), args[1].as_str()), ("-e", SEND_SCRIPT));
+        assert_eq!(args[2], "+14155551234");
+        // Verbatim apart from NUL; the script itself never changes
+        assert_eq!(args[3], HOSTILE.replace('\0', ""));
+        assert!(!SEND_SCRIPT.contains("do shell script"));
+    }
+
     #[test]
     fn test_escape_order_matters() {
         // Input: "hi" with backslash before quote
@@ -109,4 +391,43 @@ mod tests {
         let expected = r#"\\\"test\\\""#;
         assert_eq!(escape_applescript_string(input), expected);
     }
+
+    #[test]
+    fn test_automation_denied_stderr_samples() {
+        let denied = [
+            "36:158: execution error: Not authorized to send Apple events to Messages. (-1743)",
+            "execution error: Messages got an error: Not authorized to send Apple events to Messages. (-1743)",
+            "0:45: execution error: Not authorized to send Apple events to Messages.",
+            "execution error: Messages got an error: Sending Apple events to Messages requires user consent. (-1744)",
+        ];
+        for stderr in denied {
+            assert!(is_automation_denied(stderr), "{}", stderr);
+            let err = osascript_error(stderr.as_bytes());
+            assert!(matches!(err.downcast_ref::<CliError>(), Some(CliError::AutomationDenied { .. })));
+        }
+
+        let other = [
+            "36:120: execution error: Messages got an error: Can’t get participant \"+14155550001\". (-1728)",
+            "0:12: syntax error: Expected end of line but found identifier. (-2741)",
+            "execution error: Error: -17430",
+        ];
+        for stderr in other {
+            assert!(!is_automation_denied(stderr), "{}", stderr);
+            let err = osascript_error(stderr.as_bytes());
+            assert!(err.downcast_ref::<CliError>().is_none());
+            assert!(err.to_string().starts_with("AppleScript failed: "));
+        }
+    }
+
+    #[test]
+    fn test_parse_accounts() {
+        let stdout = "iMessage\tme@icloud.com\ttrue\nSMS\t+14155550000\tfalse\ngarbage\n\n";
+        assert_eq!(
+            parse_accounts(stdout),
+            vec![
+                MessagesAccount { service: "iMessage".into(), handle: "me@icloud.com".into(), enabled: true },
+                MessagesAccount { service: "SMS".into(), handle: "+14155550000".into(), enabled: false },
+            ]
+        );
+    }
 }
@@ -1,11 +1,23 @@
 //! wolfies-imessage-daemon - Persistent daemon with hot resources.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - start rotates daemon.log past launchd::LOG_ROTATE_BYTES
+//! - 10/16/2026 - --idle-exit help: the daemon stays up while scheduled sends wait
+//! - 10/16/2026 - Path comes from the data file registry (data_files::DAEMON_LOG)
+//! - 10/16/2026 - Log to stderr via tracing (RUST_LOG, default info) so per-request lines reach daemon.log
+//! - 10/16/2026 - install / uninstall (per-user LaunchAgent); status reports whether the agent is loaded
+//! - 10/16/2026 - start gained --idle-exit SECS (exit after that long without requests)
+//! - 10/16/2026 - start gained --tcp / --tcp-allow-remote (token-authenticated TCP listener)
 //! - 01/10/2026 - Initial implementation (Phase 4C, Claude)
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use std::path::Path;
+use std::time::Duration;
+use wolfies_imessage::daemon::auth;
+use wolfies_imessage::daemon::launchd::{self, SystemLaunchctl};
+use wolfies_imessage::daemon::server::{self, DaemonServer, TcpOptions};
+use wolfies_imessage::data_files;
 
 #[derive(Parser)]
 #[command(name = "wolfies-imessage-daemon")]
@@ -26,6 +38,21 @@ enum Commands {
         /// Run in foreground (don't daemonize)
         #[arg(long)]
         foreground: bool,
+
+        /// Also listen on TCP (e.g. 127.0.0.1:8765, or a bare port for loopback);
+        /// clients must send the token from ~/.wolfies-imessage/daemon.token
+        #[arg(long)]
+        tcp: Option<String>,
+
+        /// Allow --tcp to bind a non-loopback address
+        #[arg(long, requires = "tcp")]
+        tcp_allow_remote: bool,
+
+        /// Exit after this many seconds without a request, once no scheduled
+        /// sends are waiting (removes the socket and pid file; pair with the
+        /// CLI's --auto-start-daemon or launchd)
+        #[arg(long, value_name = "SECS")]
+        idle_exit: Option<u64>,
     },
 
     /// Stop the daemon
@@ -41,36 +68,98 @@ enum Commands {
         #[arg(long, default_value = "~/.wolfies-imessage/daemon.sock")]
         socket: String,
     },
+
+    /// Start the daemon at login via a LaunchAgent (~/Library/LaunchAgents)
+    Install {
+        /// Socket path
+        #[arg(long, default_value = "~/.wolfies-imessage/daemon.sock")]
+        socket: String,
+
+        /// Passed to `start --idle-exit`
+        #[arg(long, value_name = "SECS")]
+        idle_exit: Option<u64>,
+    },
+
+    /// Unload and remove the LaunchAgent
+    Uninstall,
 }
 
 fn main() -> Result<()> {
+    // One line per request (id, method, duration, outcome); launchd sends stderr to daemon.log
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Start { socket, foreground } => cmd_start(socket, foreground),
+        Commands::Start { socket, foreground, tcp, tcp_allow_remote, idle_exit } => {
+            cmd_start(socket, foreground, tcp.as_deref(), tcp_allow_remote, idle_exit.map(Duration::from_secs))
+        }
         Commands::Stop { socket } => cmd_stop(socket),
         Commands::Status { socket } => cmd_status(socket),
+        Commands::Install { socket, idle_exit } => cmd_install(socket, idle_exit),
+        Commands::Uninstall => cmd_uninstall(),
     }
 }
 
-fn cmd_start(socket: String, foreground: bool) -> Result<()> {
+fn cmd_start(
+    socket: String,
+    foreground: bool,
+    tcp: Option<&str>,
+    tcp_allow_remote: bool,
+    idle_exit: Option<Duration>,
+) -> Result<()> {
     let socket_path = shellexpand::tilde(&socket).to_string();
 
+    // launchd appends to daemon.log for good; start a fresh one past the size limit
+    match launchd::rotate_log(&data_files::DAEMON_LOG.path(), launchd::LOG_ROTATE_BYTES, chrono::Local::now()) {
+        Ok(Some(rotated)) => tracing::info!("daemon.log rotated to {}", rotated.display()),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("daemon.log not rotated: {:#}", e),
+    }
+
     // Create parent directory if needed
     if let Some(parent) = Path::new(&socket_path).parent() {
         std::fs::create_dir_all(parent)?;
     }
 
+    // Validate the TCP address and create the token before daemonizing, so errors reach the terminal
+    let tcp = tcp
+        .map(|value| -> Result<TcpOptions> {
+            let addr = auth::parse_tcp_addr(value)?;
+            auth::check_bind(&addr, tcp_allow_remote)?;
+            let token_path = auth::default_token_path();
+            let token = auth::load_or_create_token(&token_path)?;
+            eprintln!("[daemon] tcp clients authenticate with the token in {}", token_path.display());
+            Ok(TcpOptions { addr, token })
+        })
+        .transpose()?;
+    let start_server = |tcp: Option<TcpOptions>| -> Result<()> {
+        let mut server = DaemonServer::new(&socket_path)?;
+        if let Some(tcp) = tcp {
+            server = server.with_tcp(tcp);
+        }
+        if let Some(idle) = idle_exit {
+            server = server.with_idle_exit(idle);
+        }
+        server.serve()
+    };
+
     if foreground {
         // Foreground mode (for development/debugging)
         eprintln!("[daemon] starting in foreground");
-        let server = wolfies_imessage::daemon::server::DaemonServer::new(&socket_path)?;
-        server.serve()?;
+        start_server(tcp)?;
     } else {
         // Background mode (fork into daemon process)
         use daemonize::Daemonize;
 
-        let pid_file = format!("{}.pid", socket_path);
+        let pid_file = server::pid_file_path(&socket_path);
 
         let daemonize = Daemonize::new()
             .pid_file(&pid_file)
@@ -79,8 +168,7 @@ fn cmd_start(socket: String, foreground: bool) -> Result<()> {
         match daemonize.start() {
             Ok(_) => {
                 // Child process: run server
-                let server = wolfies_imessage::daemon::server::DaemonServer::new(&socket_path)?;
-                server.serve()?;
+                start_server(tcp)?;
             }
             Err(e) => {
                 eprintln!("Failed to daemonize: {}", e);
@@ -94,7 +182,7 @@ fn cmd_start(socket: String, foreground: bool) -> Result<()> {
 
 fn cmd_stop(socket: String) -> Result<()> {
     let socket_path = shellexpand::tilde(&socket).to_string();
-    let pid_file = format!("{}.pid", socket_path);
+    let pid_file = server::pid_file_path(&socket_path);
 
     // Read PID file
     let pid_str = std::fs::read_to_string(&pid_file)?;
@@ -117,6 +205,14 @@ fn cmd_stop(socket: String) -> Result<()> {
 fn cmd_status(socket: String) -> Result<()> {
     let socket_path = shellexpand::tilde(&socket).to_string();
 
+    let plist_path = launchd::default_plist_path();
+    match launchd::is_loaded(&SystemLaunchctl, &launchd::user_domain()) {
+        Ok(true) => println!("LaunchAgent {} loaded", launchd::LABEL),
+        Ok(false) if plist_path.exists() => println!("LaunchAgent installed but not loaded ({})", plist_path.display()),
+        Ok(false) => println!("LaunchAgent not installed"),
+        Err(e) => println!("LaunchAgent status unknown: {}", e),
+    }
+
     // Try to connect to socket
     match std::os::unix::net::UnixStream::connect(&socket_path) {
         Ok(_) => {
@@ -129,3 +225,26 @@ fn cmd_status(socket: String) -> Result<()> {
         }
     }
 }
+
+fn cmd_install(socket: String, idle_exit: Option<u64>) -> Result<()> {
+    let spec = launchd::AgentSpec {
+        program: std::env::current_exe()?.canonicalize()?,
+        socket: shellexpand::tilde(&socket).to_string(),
+        idle_exit,
+        log_path: data_files::DAEMON_LOG.path(),
+    };
+    let plist_path = launchd::default_plist_path();
+    launchd::install(&SystemLaunchctl, &launchd::user_domain(), &plist_path, &spec)?;
+    println!("LaunchAgent {} loaded ({})", launchd::LABEL, plist_path.display());
+    Ok(())
+}
+
+fn cmd_uninstall() -> Result<()> {
+    let plist_path = launchd::default_plist_path();
+    if launchd::uninstall(&SystemLaunchctl, &launchd::user_domain(), &plist_path)? {
+        println!("LaunchAgent {} removed", launchd::LABEL);
+    } else {
+        println!("LaunchAgent not installed");
+    }
+    Ok(())
+}
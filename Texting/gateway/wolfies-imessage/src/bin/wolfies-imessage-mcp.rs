@@ -0,0 +1,28 @@
+//! wolfies-imessage-mcp - MCP (Model Context Protocol) server over stdio.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial implementation
+
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "wolfies-imessage-mcp")]
+#[command(about = "MCP stdio server exposing wolfies-imessage read tools")]
+struct Cli {
+    /// Expose the send tool (off by default)
+    #[arg(long)]
+    allow_send: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // stdout carries the protocol; diagnostics go to stderr
+    let server = wolfies_imessage::daemon::mcp::McpServer::new(cli.allow_send)?;
+    eprintln!("[mcp] ready (send {})", if cli.allow_send { "enabled" } else { "disabled" });
+
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    server.serve(stdin.lock(), stdout.lock())
+}
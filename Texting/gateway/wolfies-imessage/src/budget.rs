@@ -0,0 +1,410 @@
+//! Token budgets for LLM consumers (`bundle --max-tokens`).
+//!
+//! `trim_to_budget` works on an assembled bundle (a JSON object of
+//! sections) and never touches the database, so the CLI and the daemon trim
+//! alike. Until the estimate fits, it:
+//!
+//! 1. drops the oldest message of the largest list section, down to one
+//!    message per section;
+//! 2. truncates text fields (`output::TEXT_FIELDS`) in every section, to
+//!    each of `TEXT_STEPS` characters in turn;
+//! 3. drops whole sections, least important first (`--section-priority`).
+//!
+//! What it did goes in the bundle's `meta.trimmed` (`{"recent":
+//! "12_messages", "search": "truncated_text"}`), which counts toward the
+//! budget itself. Ties break by priority, then section name, so the same
+//! bundle always trims the same way. Estimates are approximate:
+//! `CharsPerToken` (4 characters of compact JSON per token) unless the
+//! caller passes its own `TokenEstimator`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Message trimming estimates each item once and subtracts it (whole-bundle estimate only to confirm), not a re-serialization per removal
+//! - 10/16/2026 - Initial bundle trimming (oldest messages, text, whole sections) with a pluggable estimator
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Map, Value};
+
+use crate::output::{truncate_chars, TEXT_FIELDS};
+
+/// Text field lengths tried in turn (characters).
+const TEXT_STEPS: &[usize] = &[500, 200, 100, 50, 20];
+
+/// Section holding the trim report; never trimmed itself.
+const META: &str = "meta";
+
+/// Sections most worth keeping, first to last (the `--section-priority`
+/// default). Sections not listed are dropped before any listed one.
+pub const DEFAULT_PRIORITY: &[&str] = &[
+    "unread_count",
+    "muted_count",
+    "unread_messages",
+    "contact_messages",
+    "search",
+    "recent",
+    "followup_count",
+    "analytics",
+    "heatmap",
+];
+
+/// Tokens a piece of text costs.
+pub trait TokenEstimator {
+    fn estimate(&self, text: &str) -> usize;
+}
+
+/// One token per `n` characters, rounded up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharsPerToken(pub usize);
+
+impl Default for CharsPerToken {
+    fn default() -> Self {
+        CharsPerToken(4)
+    }
+}
+
+impl TokenEstimator for CharsPerToken {
+    fn estimate(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(self.0.max(1))
+    }
+}
+
+/// Estimated tokens of `value` as compact JSON.
+pub fn estimate_tokens(value: &Value, estimator: &dyn TokenEstimator) -> usize {
+    estimator.estimate(&value.to_string())
+}
+
+/// Section priority, most important first: `--section-priority`
+/// ("unread_messages,recent"), else `DEFAULT_PRIORITY`.
+pub fn parse_priority(value: Option<&str>) -> Vec<String> {
+    match value {
+        Some(list) => list.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect(),
+        None => DEFAULT_PRIORITY.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+/// What `trim_to_budget` did, by section: "12_messages", "truncated_text",
+/// both ("12_messages,truncated_text"), or "dropped".
+pub type Trimmed = BTreeMap<String, String>;
+
+#[derive(Default)]
+struct Report {
+    messages: BTreeMap<String, usize>,
+    truncated: BTreeSet<String>,
+    dropped: BTreeSet<String>,
+}
+
+impl Report {
+    fn trimmed(&self) -> Trimmed {
+        let sections = self.messages.keys().chain(&self.truncated).chain(&self.dropped);
+        sections
+            .map(|section| {
+                let what = if self.dropped.contains(section) {
+                    "dropped".to_string()
+                } else {
+                    let messages = self.messages.get(section).map(|n| format!("{}_messages", n));
+                    let text = self.truncated.contains(section).then(|| "truncated_text".to_string());
+                    messages.into_iter().chain(text).collect::<Vec<_>>().join(",")
+                };
+                (section.clone(), what)
+            })
+            .collect()
+    }
+}
+
+/// Trim `bundle` to about `max_tokens` (see the module docs) and return
+/// what was trimmed (empty when it already fit). Bundles that aren't JSON
+/// objects are left alone.
+pub fn trim_to_budget(
+    bundle: &mut Value,
+    max_tokens: usize,
+    priority: &[String],
+    estimator: &dyn TokenEstimator,
+) -> Trimmed {
+    let Value::Object(sections) = bundle else {
+        return Trimmed::new();
+    };
+    let mut report = Report::default();
+    let total = |sections: &Map<String, Value>| estimator.estimate(&serde_json::to_string(sections).unwrap_or_default());
+    let fits = |sections: &Map<String, Value>| total(sections) <= max_tokens;
+    // Lower is more important; unlisted sections rank last
+    let rank = |name: &str| priority.iter().position(|p| p == name).unwrap_or(priority.len());
+
+    // 1. Oldest messages of the largest list section. Each item is estimated
+    // once and subtracted when it goes; per-item estimates round up, so the
+    // running total can undershoot and is checked against the whole bundle.
+    let mut remaining = total(sections);
+    while remaining > max_tokens {
+        // Per list section: item estimates and their sum
+        let mut sizes: BTreeMap<String, (Vec<usize>, usize)> = sections
+            .iter()
+            .filter(|(name, _)| *name != META)
+            .filter_map(|(name, value)| {
+                let items: Vec<usize> = value.as_array()?.iter().map(|item| estimate_tokens(item, estimator)).collect();
+                let sum = items.iter().sum();
+                Some((name.clone(), (items, sum)))
+            })
+            .collect();
+        let mut removed = false;
+        while remaining > max_tokens {
+            let largest = sizes
+                .iter()
+                .filter(|(_, (items, _))| items.len() > 1)
+                .max_by_key(|(name, (_, sum))| (*sum, rank(name), std::cmp::Reverse(*name)))
+                .map(|(name, _)| name.clone());
+            let Some(name) = largest else { break };
+            let (Some(Value::Array(items)), Some((item_sizes, sum))) = (sections.get_mut(&name), sizes.get_mut(&name))
+            else {
+                break;
+            };
+            let index = oldest_index(items);
+            items.remove(index);
+            let size = item_sizes.remove(index);
+            *sum -= size;
+            *report.messages.entry(name).or_default() += 1;
+            let meta_before = sections.get(META).map_or(0, |meta| estimate_tokens(meta, estimator));
+            record(sections, &report);
+            let meta_after = sections.get(META).map_or(0, |meta| estimate_tokens(meta, estimator));
+            remaining = (remaining + meta_after).saturating_sub(size + meta_before);
+            removed = true;
+        }
+        if !removed {
+            break;
+        }
+        remaining = total(sections);
+    }
+
+    // 2. Text fields, shorter each step
+    for &max_chars in TEXT_STEPS {
+        if fits(sections) {
+            break;
+        }
+        for (name, value) in sections.iter_mut().filter(|(name, _)| *name != META) {
+            if truncate_text(value, max_chars) {
+                report.truncated.insert(name.clone());
+            }
+        }
+        record(sections, &report);
+    }
+
+    // 3. Whole sections, least important first
+    let mut droppable: Vec<String> = sections.keys().filter(|name| *name != META).cloned().collect();
+    droppable.sort_by_key(|name| (std::cmp::Reverse(rank(name)), name.clone()));
+    for name in droppable {
+        if fits(sections) {
+            break;
+        }
+        sections.remove(&name);
+        report.dropped.insert(name);
+        record(sections, &report);
+    }
+
+    report.trimmed()
+}
+
+/// Write the report so far into `meta.trimmed` (a `meta` that isn't an
+/// object is left alone).
+fn record(sections: &mut Map<String, Value>, report: &Report) {
+    let meta = sections.entry(META).or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(meta) = meta {
+        let trimmed = report.trimmed().into_iter().map(|(k, v)| (k, Value::String(v))).collect();
+        meta.insert("trimmed".to_string(), Value::Object(trimmed));
+    }
+}
+
+/// The item with the earliest `date`; the last one when any item has no
+/// date (listings are newest first).
+fn oldest_index(items: &[Value]) -> usize {
+    let dates: Option<Vec<&str>> = items.iter().map(|item| item.get("date").and_then(Value::as_str)).collect();
+    match dates {
+        // The last of equally old items, like the undated case
+        Some(dates) => (0..dates.len()).rev().min_by_key(|&i| dates[i]).unwrap_or_default(),
+        None => items.len().saturating_sub(1),
+    }
+}
+
+/// Truncate text fields longer than `max_chars` anywhere in `value`;
+/// whether any changed.
+fn truncate_text(value: &mut Value, max_chars: usize) -> bool {
+    match value {
+        Value::Array(items) => items.iter_mut().fold(false, |changed, item| truncate_text(item, max_chars) | changed),
+        Value::Object(map) => map.iter_mut().fold(false, |changed, (key, field)| {
+            let shortened = match field {
+                Value::String(text) if TEXT_FIELDS.contains(&key.as_str()) && text.chars().count() > max_chars => {
+                    *text = truncate_chars(text, max_chars);
+                    true
+                }
+                _ => truncate_text(field, max_chars),
+            };
+            shortened | changed
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// `count` (at most 60) messages of `text_len` characters, newest first.
+    fn messages(count: usize, text_len: usize) -> Value {
+        let items = (0..count)
+            .map(|i| json!({"date": format!("2026-10-16T12:{:02}:00Z", 59 - i), "text": "x".repeat(text_len)}))
+            .collect();
+        Value::Array(items)
+    }
+
+    fn priority() -> Vec<String> {
+        parse_priority(None)
+    }
+
+    fn tokens(value: &Value) -> usize {
+        estimate_tokens(value, &CharsPerToken::default())
+    }
+
+    #[test]
+    fn test_chars_per_token_rounds_up() {
+        assert_eq!(CharsPerToken(4).estimate(""), 0);
+        assert_eq!(CharsPerToken(4).estimate("abcde"), 2);
+        assert_eq!(CharsPerToken(1).estimate("héllo"), 5);
+        assert_eq!(tokens(&json!({"a": 1})), 2);
+    }
+
+    #[test]
+    fn test_fitting_bundle_is_untouched() {
+        let mut bundle = json!({"unread_count": 3, "recent": messages(5, 20)});
+        let before = bundle.clone();
+        assert!(trim_to_budget(&mut bundle, tokens(&before), &priority(), &CharsPerToken::default()).is_empty());
+        assert_eq!(bundle, before);
+    }
+
+    #[test]
+    fn test_drops_oldest_messages_from_largest_section_first() {
+        let mut bundle = json!({"unread_count": 3, "recent": messages(30, 40), "search": messages(10, 40)});
+        let trimmed = trim_to_budget(&mut bundle, 500, &priority(), &CharsPerToken::default());
+
+        assert!(tokens(&bundle) <= 500, "{}", tokens(&bundle));
+        let recent = bundle["recent"].as_array().unwrap();
+        let search = bundle["search"].as_array().unwrap();
+        // Only the larger section lost messages, oldest first
+        assert_eq!(search.len(), 10);
+        assert!(recent.len() < 30 && recent.len() >= search.len(), "{}", recent.len());
+        assert_eq!(recent[0]["date"], "2026-10-16T12:59:00Z");
+        assert_eq!(recent.last().unwrap()["date"], format!("2026-10-16T12:{:02}:00Z", 60 - recent.len()));
+        assert_eq!(trimmed, Trimmed::from([("recent".to_string(), format!("{}_messages", 30 - recent.len()))]));
+        assert_eq!(bundle["meta"]["trimmed"], json!(trimmed));
+        assert_eq!(bundle["unread_count"], 3);
+
+        // A tighter budget trims both, keeping them level
+        let mut bundle = json!({"recent": messages(30, 40), "search": messages(10, 40)});
+        trim_to_budget(&mut bundle, 200, &priority(), &CharsPerToken::default());
+        let (recent, search) = (bundle["recent"].as_array().unwrap(), bundle["search"].as_array().unwrap());
+        assert!(search.len() < 10 && recent.len().abs_diff(search.len()) <= 1, "{} vs {}", recent.len(), search.len());
+    }
+
+    #[test]
+    fn test_oldest_by_date_even_out_of_order() {
+        let items = vec![json!({"date": "2026-10-02"}), json!({"date": "2026-10-01"}), json!({"date": "2026-10-03"})];
+        assert_eq!(oldest_index(&items), 1);
+        assert_eq!(oldest_index(&[json!({"text": "a"}), json!({"text": "b"})]), 1);
+    }
+
+    #[test]
+    fn test_truncates_text_once_sections_are_down_to_one_message() {
+        let mut bundle = json!({"recent": messages(3, 2_000), "unread_messages": messages(1, 2_000)});
+        let trimmed = trim_to_budget(&mut bundle, 200, &priority(), &CharsPerToken::default());
+
+        assert!(tokens(&bundle) <= 200, "{}", tokens(&bundle));
+        assert_eq!(trimmed["recent"], "2_messages,truncated_text");
+        assert_eq!(trimmed["unread_messages"], "truncated_text");
+        let text = bundle["recent"][0]["text"].as_str().unwrap();
+        assert!(text.ends_with("...") && text.len() < 2_000);
+        // Only text fields are cut
+        assert_eq!(bundle["recent"][0]["date"], "2026-10-16T12:59:00Z");
+    }
+
+    #[test]
+    fn test_drops_least_important_sections_last_resort() {
+        let mut bundle = json!({
+            "unread_count": 3,
+            "recent": messages(1, 20),
+            "heatmap": {"cells": "y".repeat(400)},
+            "custom": "z".repeat(400),
+        });
+        let trimmed = trim_to_budget(&mut bundle, 60, &priority(), &CharsPerToken::default());
+
+        assert!(tokens(&bundle) <= 60, "{}", tokens(&bundle));
+        // Unlisted first, then the tail of the priority list
+        assert_eq!(trimmed["custom"], "dropped");
+        assert_eq!(trimmed["heatmap"], "dropped");
+        assert_eq!(bundle["unread_count"], 3);
+        assert!(bundle.get("recent").is_some());
+
+        // A custom priority changes what goes first
+        let cells = "y".repeat(400);
+        let mut bundle = json!({"analytics": {"series": cells}, "heatmap": {"cells": cells}});
+        let expected = json!({"heatmap": {"cells": cells}, "meta": {"trimmed": {"analytics": "dropped"}}});
+        let custom = parse_priority(Some("heatmap, analytics"));
+        let trimmed = trim_to_budget(&mut bundle, tokens(&expected), &custom, &CharsPerToken::default());
+        assert_eq!(trimmed, Trimmed::from([("analytics".to_string(), "dropped".to_string())]));
+        assert_eq!(bundle, expected);
+    }
+
+    #[test]
+    fn test_trimming_is_deterministic_and_keeps_meta() {
+        let build = || {
+            json!({
+                "meta": {"version": "1.0"},
+                "recent": messages(20, 100),
+                "search": messages(20, 100),
+                "unread_messages": messages(20, 100),
+            })
+        };
+        let (mut a, mut b) = (build(), build());
+        let trimmed_a = trim_to_budget(&mut a, 300, &priority(), &CharsPerToken::default());
+        let trimmed_b = trim_to_budget(&mut b, 300, &priority(), &CharsPerToken::default());
+        assert_eq!((a.clone(), trimmed_a), (b, trimmed_b));
+        assert_eq!(a["meta"]["version"], "1.0");
+    }
+
+    #[test]
+    fn test_pluggable_estimator() {
+        struct Words;
+        impl TokenEstimator for Words {
+            fn estimate(&self, text: &str) -> usize {
+                text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).count()
+            }
+        }
+        // 19 words; dropping one message (and reporting it) leaves 15
+        let mut bundle = json!({"recent": [{"text": "a b c d e f g h"}, {"text": "i j k l m n o p"}]});
+        let trimmed = trim_to_budget(&mut bundle, 16, &priority(), &Words);
+        assert_eq!(trimmed["recent"], "1_messages");
+        assert_eq!(bundle["recent"], json!([{"text": "a b c d e f g h"}]));
+    }
+
+    #[test]
+    fn test_message_trimming_estimates_whole_bundle_rarely() {
+        use std::cell::Cell;
+        /// `CharsPerToken`, counting estimates of more than one message.
+        struct Counting(Cell<usize>);
+        impl TokenEstimator for Counting {
+            fn estimate(&self, text: &str) -> usize {
+                if text.len() > 1_000 {
+                    self.0.set(self.0.get() + 1);
+                }
+                CharsPerToken::default().estimate(text)
+            }
+        }
+        let mut bundle = json!({"recent": messages(60, 40), "search": messages(60, 40)});
+        let estimator = Counting(Cell::new(0));
+        let trimmed = trim_to_budget(&mut bundle, 1_000, &priority(), &estimator);
+
+        assert!(tokens(&bundle) <= 1_000, "{}", tokens(&bundle));
+        assert!(trimmed.contains_key("recent") && trimmed.contains_key("search"));
+        // A few confirmations plus one check each for steps 2 and 3, not
+        // one per dropped message
+        let dropped: usize = ["recent", "search"].iter().map(|s| 60 - bundle[s].as_array().unwrap().len()).sum();
+        assert!(dropped > 50, "{}", dropped);
+        assert!(estimator.0.get() <= 6, "{} whole-bundle estimates", estimator.0.get());
+    }
+}
@@ -0,0 +1,214 @@
+//! `account`: the Apple ID and phone numbers this Mac's Messages sends from.
+//!
+//! chat.db records the local handle on each chat (`account_login`,
+//! `last_addressed_handle`). When that shows no iMessage handle or no SMS
+//! use, Messages.app is asked for its accounts over AppleScript. A source
+//! that fails adds a warning and the other's findings are still returned.
+//! The chat.db handles are also what `my_handles` defaults to.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial account command (chat.db accounts, AppleScript fallback)
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::applescript::{self, MessagesAccount};
+use crate::db::connection;
+use crate::db::helpers::{self, handle_key, ChatAccount};
+use crate::output::{self, OutputControls};
+
+/// A handle Messages sends from, on one service.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Account {
+    pub handle: String,
+    /// "iMessage", "SMS", ...; None on schemas without chat.service_name
+    pub service: Option<String>,
+    /// Chats in chat.db sent from this handle (0 if only Messages.app knows it)
+    pub chat_count: i64,
+    /// Whether Messages.app has the account enabled; None if it wasn't asked
+    pub enabled: Option<bool>,
+}
+
+/// `account` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AccountInfo {
+    pub imessage_handles: Vec<String>,
+    pub sms_enabled: bool,
+    pub accounts: Vec<Account>,
+    pub warnings: Vec<String>,
+}
+
+/// Account list from Messages.app (`applescript::messages_accounts` outside tests).
+type MessagesAccounts<'a> = &'a dyn Fn() -> Result<Vec<MessagesAccount>>;
+
+fn is_service(account: &Account, names: &[&str]) -> bool {
+    account
+        .service
+        .as_deref()
+        .is_some_and(|s| names.iter().any(|n| s.eq_ignore_ascii_case(n)))
+}
+
+const IMESSAGE: &[&str] = &["iMessage"];
+const CARRIER: &[&str] = &["SMS", "RCS"];
+
+/// Combine chat.db accounts with Messages.app's, asking Messages.app only
+/// when chat.db lacks an iMessage handle or carrier (SMS/RCS) use.
+pub(crate) fn build(chat_accounts: Result<Vec<ChatAccount>>, messages: MessagesAccounts) -> AccountInfo {
+    let mut warnings = Vec::new();
+    let mut accounts: Vec<Account> = match chat_accounts {
+        Ok(found) => found
+            .into_iter()
+            .map(|a| Account {
+                handle: a.handle,
+                service: a.service,
+                chat_count: a.chat_count,
+                enabled: None,
+            })
+            .collect(),
+        Err(e) => {
+            warnings.push(format!("chat.db accounts unavailable: {:#}", e));
+            Vec::new()
+        }
+    };
+
+    let sufficient = accounts.iter().any(|a| is_service(a, IMESSAGE)) && accounts.iter().any(|a| is_service(a, CARRIER));
+    let mut asked = false;
+    if !sufficient {
+        match messages() {
+            Ok(found) => {
+                asked = true;
+                for m in found {
+                    let handle = match m.handle.split_once(':') {
+                        Some((prefix, handle)) if prefix.len() == 1 => handle.to_string(),
+                        _ => m.handle,
+                    };
+                    let existing = accounts.iter_mut().find(|a| {
+                        handle_key(&a.handle) == handle_key(&handle)
+                            && a.service.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(&m.service))
+                    });
+                    match existing {
+                        Some(a) => a.enabled = Some(m.enabled),
+                        None => accounts.push(Account {
+                            handle,
+                            service: Some(m.service),
+                            chat_count: 0,
+                            enabled: Some(m.enabled),
+                        }),
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("Messages.app accounts unavailable: {:#}", e)),
+        }
+    }
+
+    let mut imessage_handles: Vec<String> = Vec::new();
+    for a in accounts.iter().filter(|a| is_service(a, IMESSAGE) && a.enabled != Some(false)) {
+        if !imessage_handles.iter().any(|h| handle_key(h) == handle_key(&a.handle)) {
+            imessage_handles.push(a.handle.clone());
+        }
+    }
+    // Messages.app's enabled flag is authoritative when it reported a carrier account
+    let carrier: Vec<&Account> = accounts.iter().filter(|a| is_service(a, CARRIER)).collect();
+    let sms_enabled = if asked && carrier.iter().any(|a| a.enabled.is_some()) {
+        carrier.iter().any(|a| a.enabled == Some(true))
+    } else {
+        !carrier.is_empty()
+    };
+
+    AccountInfo {
+        imessage_handles,
+        sms_enabled,
+        accounts,
+        warnings,
+    }
+}
+
+/// Show the handles and services Messages sends from.
+pub fn account(output: &OutputControls) -> Result<()> {
+    let chat_accounts = connection::open_db().and_then(|conn| helpers::query_chat_accounts(&conn));
+    let info = build(chat_accounts, &applescript::messages_accounts);
+    for warning in &info.warnings {
+        output::warn(warning.clone());
+    }
+
+    if output.json {
+        output.print(&info)?;
+        return Ok(());
+    }
+    let handles = if info.imessage_handles.is_empty() {
+        "(none found)".to_string()
+    } else {
+        info.imessage_handles.join(", ")
+    };
+    println!("iMessage: {}", handles);
+    println!("SMS: {}", if info.sms_enabled { "enabled" } else { "not enabled" });
+    if !info.accounts.is_empty() {
+        println!("{:-<60}", "");
+        for a in &info.accounts {
+            let state = match a.enabled {
+                Some(false) => ", disabled",
+                _ => "",
+            };
+            println!(
+                "[{}] {} ({} chats{})",
+                a.service.as_deref().unwrap_or("?"),
+                a.handle,
+                a.chat_count,
+                state
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::cell::Cell;
+
+    fn chat(handle: &str, service: &str, chat_count: i64) -> ChatAccount {
+        ChatAccount { handle: handle.to_string(), service: Some(service.to_string()), chat_count }
+    }
+
+    fn messages(service: &str, handle: &str, enabled: bool) -> MessagesAccount {
+        MessagesAccount { service: service.to_string(), handle: handle.to_string(), enabled }
+    }
+
+    #[test]
+    fn test_build_merges_sources_and_degrades() {
+        // chat.db has both services: Messages.app isn't asked
+        let asked = Cell::new(false);
+        let never = || {
+            asked.set(true);
+            Ok(Vec::new())
+        };
+        let info = build(Ok(vec![chat("me@icloud.com", "iMessage", 4), chat("+14155550000", "SMS", 2)]), &never);
+        assert!(!asked.get());
+        assert_eq!(info.imessage_handles, ["me@icloud.com"]);
+        assert!(info.sms_enabled);
+
+        // No SMS history: Messages.app fills in, and its enabled flag wins
+        let apple = || {
+            Ok(vec![
+                messages("iMessage", "E:me@icloud.com", true),
+                messages("iMessage", "+14155550000", true),
+                messages("SMS", "+14155550000", false),
+            ])
+        };
+        let info = build(Ok(vec![chat("me@icloud.com", "iMessage", 4)]), &apple);
+        assert_eq!(info.imessage_handles, ["me@icloud.com", "+14155550000"]);
+        assert!(!info.sms_enabled);
+        assert_eq!(info.accounts.len(), 3);
+        assert_eq!((info.accounts[0].chat_count, info.accounts[0].enabled), (4, Some(true)));
+        assert!(info.warnings.is_empty());
+
+        // Both sources failing still yields a (empty) result with warnings
+        let broken = || Err(anyhow!("osascript not found"));
+        let info = build(Err(anyhow!("no chat.db")), &broken);
+        assert!(info.imessage_handles.is_empty() && !info.sms_enabled);
+        assert_eq!(info.warnings.len(), 2);
+        assert!(info.warnings[1].contains("osascript not found"));
+    }
+}
@@ -1,6 +1,24 @@
-//! Analytics commands: analytics, followup.
+//! Analytics commands: analytics, series, top terms.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Heatmap reports analysis_period_days like the other analytics outputs
+//! - 10/16/2026 - Added analytics <contact> --streaks [--all-time] (current/longest daily streaks, first message, lifetime count); Analytics.streaks
+//! - 10/16/2026 - analytics --start/--end: totals over a fixed window (period_start/period_end; days from the window)
+//! - 10/16/2026 - analytics --compare: comparison block (absolute and percent deltas and direction against the preceding window)
+//! - 10/16/2026 - Added analytics --heatmap (weekday x hour counts, local time; build_heatmap shared with the daemon bundle)
+//! - 10/16/2026 - --envelope: days and contact_resolved recorded for meta.applied; --series/--top-terms JSON through print_json
+//! - 10/16/2026 - --series and --top-terms: --csv output (top terms as sender/term/count rows)
+//! - 10/16/2026 - Added analytics <contact> --gaps (longest silences, who broke them)
+//! - 10/16/2026 - Added analytics <contact> --emoji (per-sender emoji and tapback tallies)
+//! - 10/16/2026 - Moved followup to commands::followup
+//! - 10/16/2026 - analytics prints api::analytics (one connection, combined query); Analytics is public
+//! - 10/16/2026 - analytics text mode renders compact tables (TextRenderer)
+//! - 10/16/2026 - followup previews are char-safe (output::preview)
+//! - 10/16/2026 - Added analytics <contact> --top-terms (per-sender term counts)
+//! - 10/16/2026 - Added analytics --series (hourly/daily/weekly sent/received buckets)
+//! - 10/16/2026 - Notes-to-self excluded from top_contacts and followup (--include-self)
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - Added relationship filter to analytics and followup
 //! - 01/10/2026 - Refactored to use shared db::helpers (Phase 5) (Claude)
 //! - 01/10/2026 - Added parallel query execution (Phase 4B) with rayon (Claude)
 //! - 01/10/2026 - Added contact caching (Phase 4A) - accepts Arc<ContactsManager> (Claude)
@@ -9,326 +27,1153 @@
 //! - 01/10/2026 - Implemented follow-up detection command (Claude)
 
 use anyhow::Result;
-use rayon::prelude::*;
+use chrono::{Datelike, Days, Local, NaiveDate};
+use schemars::JsonSchema;
 use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
+use crate::api;
+use crate::commands::reading;
 use crate::contacts::manager::ContactsManager;
-use crate::db::{connection::open_db, helpers, queries};
+use crate::db::queries::{self, DAY_NS};
+use crate::db::{connection::open_db, helpers};
+use crate::emoji::{self, EmojiCount, EmojiCounter};
+use crate::terms::{self, TermCount, TermCounter};
+use crate::output::{self, OutputControls, Table, TextRenderer, TextStyle, Tone};
 
-#[derive(Debug, Serialize)]
-struct Analytics {
-    total_messages: i64,
-    sent_count: i64,
-    received_count: i64,
-    avg_daily_messages: f64,
-    busiest_hour: Option<i64>,
-    busiest_day: Option<String>,
-    top_contacts: Vec<helpers::TopContact>,
-    attachment_count: i64,
-    reaction_count: i64,
-    analysis_period_days: u32,
+/// Silences reported by `--gaps`.
+pub const GAPS_LIMIT: usize = 10;
+
+/// Default `--min-gap-days`.
+pub const DEFAULT_MIN_GAP_DAYS: u32 = 3;
+
+/// Characters kept from the message that broke a silence.
+const GAP_PREVIEW_CHARS: usize = 80;
+
+/// Message totals, busiest times, and top contacts over a period.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Analytics {
+    pub total_messages: i64,
+    pub sent_count: i64,
+    pub received_count: i64,
+    pub avg_daily_messages: f64,
+    pub busiest_hour: Option<i64>,
+    pub busiest_day: Option<String>,
+    pub top_contacts: Vec<helpers::TopContact>,
+    pub attachment_count: i64,
+    pub reaction_count: i64,
+    pub analysis_period_days: u32,
+    /// Window start, with --start (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_start: Option<String>,
+    /// Window end, exclusive, with --end (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_end: Option<String>,
+    /// Against the preceding window of the same length (only with --compare).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    /// Texting streaks with the contact (only when requested).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaks: Option<Streaks>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct UnansweredQuestion {
-    phone: String,
-    contact_name: Option<String>,
-    text: String,
-    date: String,
-    days_ago: i64,
+/// This period against the one right before it (`--compare`).
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Comparison {
+    /// Start of the previous window (ISO 8601)
+    pub previous_start: String,
+    /// End of the previous window, exclusive: where this period starts
+    pub previous_end: String,
+    pub total_messages: MetricDelta,
+    pub sent_count: MetricDelta,
+    pub received_count: MetricDelta,
+    pub attachment_count: MetricDelta,
+    pub reaction_count: MetricDelta,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct StaleConversation {
-    phone: String,
-    contact_name: Option<String>,
-    last_text: Option<String>,
-    last_date: String,
-    days_ago: i64,
+impl Comparison {
+    pub(crate) fn new(
+        current: &helpers::CombinedAnalytics,
+        previous: &helpers::CombinedAnalytics,
+        previous_start_cocoa: i64,
+        previous_end_cocoa: i64,
+    ) -> Self {
+        Comparison {
+            previous_start: helpers::cocoa_to_iso(previous_start_cocoa),
+            previous_end: helpers::cocoa_to_iso(previous_end_cocoa),
+            total_messages: MetricDelta::new(current.total, previous.total),
+            sent_count: MetricDelta::new(current.sent, previous.sent),
+            received_count: MetricDelta::new(current.received, previous.received),
+            attachment_count: MetricDelta::new(current.attachments, previous.attachments),
+            reaction_count: MetricDelta::new(current.reactions, previous.reactions),
+        }
+    }
+}
+
+/// One metric, now and in the previous window.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct MetricDelta {
+    pub current: i64,
+    pub previous: i64,
+    /// current - previous
+    pub delta: i64,
+    /// Change relative to the previous window, in percent (one decimal);
+    /// null when the previous window had none
+    pub delta_pct: Option<f64>,
+    pub direction: Direction,
+}
+
+impl MetricDelta {
+    pub fn new(current: i64, previous: i64) -> Self {
+        let delta = current - previous;
+        MetricDelta {
+            current,
+            previous,
+            delta,
+            delta_pct: (previous != 0).then(|| percentage(delta, previous)),
+            direction: match delta.signum() {
+                1 => Direction::Up,
+                -1 => Direction::Down,
+                _ => Direction::Flat,
+            },
+        }
+    }
 }
 
+/// Which way a metric moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Up,
+    Down,
+    Flat,
+}
+
+impl Analytics {
+    pub(crate) fn from_stats(stats: helpers::CombinedAnalytics, top_contacts: Vec<helpers::TopContact>, days: u32) -> Self {
+        Analytics {
+            total_messages: stats.total,
+            sent_count: stats.sent,
+            received_count: stats.received,
+            avg_daily_messages: average_daily(stats.total, days),
+            busiest_hour: stats.busiest_hour,
+            busiest_day: stats
+                .busiest_day
+                .and_then(|d| helpers::day_number_to_name(d).map(|s| s.to_string())),
+            top_contacts,
+            attachment_count: stats.attachments,
+            reaction_count: stats.reactions,
+            analysis_period_days: days,
+            period_start: None,
+            period_end: None,
+            comparison: None,
+            streaks: None,
+        }
+    }
+}
+
+/// What a conversation is about: top terms on each side.
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct TopTerms {
+    my_terms: Vec<TermCount>,
+    their_terms: Vec<TermCount>,
+    messages_analyzed: usize,
+}
+
+/// One `--top-terms --csv` row.
 #[derive(Debug, Serialize)]
-struct FollowUpReport {
-    unanswered_questions: Vec<UnansweredQuestion>,
-    stale_conversations: Vec<StaleConversation>,
-    total_items: usize,
+struct TermRow<'a> {
+    sender: &'static str,
+    term: &'a str,
+    count: usize,
 }
 
-// ============================================================================
-// Main analytics command with parallel execution
-// ============================================================================
+impl TopTerms {
+    /// Both sides as flat rows, mine first.
+    fn rows(&self) -> Vec<TermRow<'_>> {
+        [("me", &self.my_terms), ("them", &self.their_terms)]
+            .into_iter()
+            .flat_map(|(sender, terms)| terms.iter().map(move |t| TermRow { sender, term: &t.term, count: t.count }))
+            .collect()
+    }
+}
+
+/// Emoji habits on each side of a conversation.
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct EmojiStats {
+    contact_name: String,
+    me: SenderEmoji,
+    them: SenderEmoji,
+    analysis_period_days: u32,
+}
+
+/// One sender's emoji tallies.
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub(crate) struct SenderEmoji {
+    messages: usize,
+    total_emoji: usize,
+    emoji_per_100_messages: f64,
+    top_emoji: Vec<EmojiCount>,
+    /// Tapbacks sent, by kind (love, laugh, emoji, ...)
+    tapbacks: BTreeMap<String, usize>,
+}
+
+impl SenderEmoji {
+    fn from_counter(counter: &EmojiCounter, tapbacks: BTreeMap<String, usize>) -> Self {
+        SenderEmoji {
+            messages: counter.messages(),
+            total_emoji: counter.total(),
+            emoji_per_100_messages: counter.per_100_messages(),
+            top_emoji: counter.top(emoji::TOP_EMOJI),
+            tapbacks,
+        }
+    }
+}
 
 /// Get conversation analytics.
-pub fn analytics(contact: Option<&str>, days: u32, json: bool, contacts: &Arc<ContactsManager>) -> Result<()> {
-    let cutoff_cocoa = queries::days_ago_cocoa(days);
-
-    // Resolve contact to phone if provided
-    let phone = if let Some(contact_name) = contact {
-        let contact = contacts.find_by_name(contact_name)
-            .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", contact_name))?;
-        Some(contact.phone.clone())
-    } else {
-        None
+///
+/// `start`/`end` (YYYY-MM-DD, local, end inclusive) replace `days` with a
+/// fixed window, parsed like summary's (reading::date_range).
+#[allow(clippy::too_many_arguments)]
+pub fn analytics(
+    contact: Option<&str>,
+    relationship: Option<&str>,
+    days: u32,
+    start: Option<&str>,
+    end: Option<&str>,
+    include_self: bool,
+    compare: bool,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    contact_phone(contact, contacts)?;
+    let (start_cocoa, end_cocoa) = date_window(start, end)?;
+    match start {
+        Some(start) => {
+            output::applied("start", start);
+            if let Some(end) = end {
+                output::applied("end", end);
+            }
+        }
+        None => output::applied("days", days),
+    }
+    let conn = open_db()?;
+    let opts = api::AnalyticsOptions {
+        contact: contact.map(str::to_string),
+        relationship: relationship.map(str::to_string),
+        days,
+        start_cocoa,
+        end_cocoa,
+        include_self,
+        compare,
+        ..Default::default()
     };
+    output.show(&api::analytics(&conn, contacts, &opts)?)?;
+    Ok(())
+}
 
-    // Execute 6 queries in parallel using rayon
-    // Each query opens its own connection (simple approach)
-    let phone_ref = phone.as_deref();
-
-    let ((total, sent, received), ((busiest_hour, busiest_day), (top_contacts, (attachment_count, reaction_count)))) = rayon::join(
-        || {
-            // Query 1: Message counts
-            let conn = open_db().expect("Failed to open DB");
-            helpers::query_message_counts(&conn, cutoff_cocoa, phone_ref).expect("Query failed")
-        },
-        || rayon::join(
-            || rayon::join(
-                || {
-                    // Query 2: Busiest hour
-                    let conn = open_db().expect("Failed to open DB");
-                    helpers::query_busiest_hour(&conn, cutoff_cocoa, phone_ref).expect("Query failed")
-                },
-                || {
-                    // Query 3: Busiest day
-                    let conn = open_db().expect("Failed to open DB");
-                    helpers::query_busiest_day(&conn, cutoff_cocoa, phone_ref).expect("Query failed")
-                }
-            ),
-            || rayon::join(
-                || {
-                    // Query 4: Top contacts (only if no phone filter)
-                    if phone_ref.is_none() {
-                        let conn = open_db().expect("Failed to open DB");
-                        helpers::query_top_contacts(&conn, cutoff_cocoa).expect("Query failed")
-                    } else {
-                        Vec::new()
-                    }
-                },
-                || rayon::join(
-                    || {
-                        // Query 5: Attachments
-                        let conn = open_db().expect("Failed to open DB");
-                        helpers::query_attachments(&conn, cutoff_cocoa, phone_ref).expect("Query failed")
-                    },
-                    || {
-                        // Query 6: Reactions
-                        let conn = open_db().expect("Failed to open DB");
-                        helpers::query_reactions(&conn, cutoff_cocoa, phone_ref).expect("Query failed")
-                    }
-                )
-            )
-        )
-    );
+/// --start/--end as an analytics window in Cocoa ns: (None, None) without
+/// --start; the end is None when the window runs to now.
+pub fn date_window(start: Option<&str>, end: Option<&str>) -> Result<(Option<i64>, Option<i64>)> {
+    let Some(start) = start else {
+        return Ok((None, None));
+    };
+    let (start_cocoa, end_cocoa) = reading::date_range(None, Some(start), end)?;
+    Ok((Some(start_cocoa), (end_cocoa < i64::MAX).then_some(end_cocoa)))
+}
 
-    // Convert busiest day number to name
-    let busiest_day_name = busiest_day.and_then(|d| {
-        helpers::day_number_to_name(d).map(|s| s.to_string())
-    });
+/// Message volume per time bucket over the last `days`, for charting.
+pub fn series(
+    contact: Option<&str>,
+    bucket: helpers::SeriesBucket,
+    days: u32,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let phone = contact_phone(contact, contacts)?;
+    output::applied("days", days);
+    let conn = open_db()?;
+    let series = helpers::query_volume_series(&conn, queries::days_ago_cocoa(days), bucket, phone.as_deref())?;
 
-    // Build analytics struct
-    let avg_daily = if days > 0 {
-        (total as f64) / (days as f64)
+    if output.csv {
+        output.print_csv(&series)?;
+    } else if output.json {
+        output.print_json(&series, true)?;
     } else {
-        0.0
-    };
+        println!("Message Volume ({}, {} days):", bucket.name(), days);
+        println!("{:-<40}", "");
+        for point in &series {
+            println!("{}  sent {:>4}  received {:>4}", point.bucket_start, point.sent, point.received);
+        }
+    }
+    Ok(())
+}
 
-    let analytics = Analytics {
-        total_messages: total,
-        sent_count: sent,
-        received_count: received,
-        avg_daily_messages: (avg_daily * 10.0).round() / 10.0, // Round to 1 decimal
-        busiest_hour,
-        busiest_day: busiest_day_name,
-        top_contacts,
-        attachment_count,
-        reaction_count,
-        analysis_period_days: days,
-    };
+/// Most frequent terms in a conversation, split by sender.
+///
+/// Both parties' names (the contact's and `me`, when given) are excluded.
+pub fn top_terms(
+    contact: &str,
+    days: u32,
+    me: Option<&str>,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let contact = contacts
+        .find_by_name(contact)
+        .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", contact))?;
+    output::applied("contact_resolved", &contact.phone);
+    output::applied("days", days);
+    let names: Vec<&str> = std::iter::once(contact.name.as_str()).chain(me).collect();
+
+    let conn = open_db()?;
+    let report = build_top_terms(&conn, queries::days_ago_cocoa(days), &contact.phone, &names)?;
 
-    // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&analytics)?);
+    if output.csv {
+        output.print_csv(&report.rows())?;
+    } else if output.json {
+        output.print_json(&report, true)?;
     } else {
-        println!("Conversation Analytics:");
+        println!("Top Terms with {} ({} messages, {} days):", contact.name, report.messages_analyzed, days);
         println!("{:-<40}", "");
-        println!("total_messages: {}", analytics.total_messages);
-        println!("sent_count: {}", analytics.sent_count);
-        println!("received_count: {}", analytics.received_count);
-        println!("avg_daily_messages: {:.1}", analytics.avg_daily_messages);
-        if let Some(hour) = analytics.busiest_hour {
-            println!("busiest_hour: {}", hour);
+        for (label, terms) in [("Me", &report.my_terms), (contact.name.as_str(), &report.their_terms)] {
+            println!("{}:", label);
+            for t in terms {
+                println!("  {}: {}", t.term, t.count);
+            }
         }
-        if let Some(ref day) = analytics.busiest_day {
-            println!("busiest_day: {}", day);
+    }
+    Ok(())
+}
+
+/// Count terms per sender over a conversation since `cutoff_cocoa`.
+pub(crate) fn build_top_terms(
+    conn: &rusqlite::Connection,
+    cutoff_cocoa: i64,
+    phone: &str,
+    names: &[&str],
+) -> Result<TopTerms> {
+    let mut mine = TermCounter::new(names);
+    let mut theirs = TermCounter::new(names);
+    let messages_analyzed = helpers::for_each_conversation_text(conn, cutoff_cocoa, phone, |is_from_me, text| {
+        if is_from_me {
+            mine.add(text);
+        } else {
+            theirs.add(text);
         }
-        if !analytics.top_contacts.is_empty() {
-            println!("top_contacts:");
-            for tc in &analytics.top_contacts {
-                println!("  {}: {} messages", tc.phone, tc.message_count);
-            }
+    })?;
+    Ok(TopTerms {
+        my_terms: mine.top(terms::TOP_TERMS),
+        their_terms: theirs.top(terms::TOP_TERMS),
+        messages_analyzed,
+    })
+}
+
+/// Emoji and tapback tallies in the conversation with a contact, split by sender.
+pub fn emoji_stats(
+    contact: &str,
+    days: u32,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let contact = contacts
+        .find_by_name(contact)
+        .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", contact))?;
+    output::applied("contact_resolved", &contact.phone);
+    output::applied("days", days);
+
+    let conn = open_db()?;
+    let stats = build_emoji_stats(&conn, queries::days_ago_cocoa(days), &contact.phone, &contact.name, days)?;
+    output.show(&stats)?;
+    Ok(())
+}
+
+/// Count emoji (message text, blob text included) and tapbacks per sender.
+pub(crate) fn build_emoji_stats(
+    conn: &rusqlite::Connection,
+    cutoff_cocoa: i64,
+    phone: &str,
+    contact_name: &str,
+    days: u32,
+) -> Result<EmojiStats> {
+    let mut mine = EmojiCounter::new();
+    let mut theirs = EmojiCounter::new();
+    helpers::for_each_conversation_text(conn, cutoff_cocoa, phone, |is_from_me, text| {
+        if is_from_me {
+            mine.add(text);
+        } else {
+            theirs.add(text);
         }
-        println!("attachment_count: {}", analytics.attachment_count);
-        println!("reaction_count: {}", analytics.reaction_count);
-        println!("analysis_period_days: {}", analytics.analysis_period_days);
+    })?;
+
+    let mut my_tapbacks = BTreeMap::new();
+    let mut their_tapbacks = BTreeMap::new();
+    helpers::for_each_conversation_tapback(conn, cutoff_cocoa, phone, |is_from_me, reaction_type| {
+        let kind = helpers::reaction_kind(reaction_type, None).kind.to_string();
+        let side = if is_from_me { &mut my_tapbacks } else { &mut their_tapbacks };
+        *side.entry(kind).or_insert(0) += 1;
+    })?;
+
+    Ok(EmojiStats {
+        contact_name: contact_name.to_string(),
+        me: SenderEmoji::from_counter(&mine, my_tapbacks),
+        them: SenderEmoji::from_counter(&theirs, their_tapbacks),
+        analysis_period_days: days,
+    })
+}
+
+/// The longest silences in the conversation with a contact.
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct SilenceGaps {
+    contact_name: String,
+    min_gap_days: u32,
+    analysis_period_days: u32,
+    gaps: Vec<SilenceGap>,
+}
+
+/// One period with no messages either way.
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct SilenceGap {
+    start: String,
+    /// Now, when the silence is ongoing
+    end: String,
+    duration_days: f64,
+    ongoing: bool,
+    /// "me" or "them"; absent while ongoing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    broken_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<String>,
+}
+
+/// A silence between two timeline entries, or from the last one to now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Gap {
+    pub start_cocoa: i64,
+    pub end_cocoa: i64,
+    /// Timeline index of the message that ended the silence (None: ongoing)
+    pub broken_at: Option<usize>,
+}
+
+impl Gap {
+    fn duration_ns(&self) -> i64 {
+        self.end_cocoa - self.start_cocoa
     }
+}
+
+/// Silences of at least `min_gap_ns` in a chronological `(date, is_from_me)`
+/// timeline, longest first (ties: earliest first), at most `limit`. The time
+/// from the last message to `now_cocoa` counts as an ongoing silence; an
+/// empty timeline has none.
+pub(crate) fn find_gaps(timeline: &[(i64, bool)], now_cocoa: i64, min_gap_ns: i64, limit: usize) -> Vec<Gap> {
+    let mut gaps: Vec<Gap> = timeline
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| Gap { start_cocoa: pair[0].0, end_cocoa: pair[1].0, broken_at: Some(i + 1) })
+        .chain(timeline.last().map(|&(date, _)| Gap { start_cocoa: date, end_cocoa: now_cocoa, broken_at: None }))
+        .filter(|gap| gap.duration_ns() >= min_gap_ns)
+        .collect();
+    gaps.sort_by(|a, b| b.duration_ns().cmp(&a.duration_ns()).then_with(|| a.start_cocoa.cmp(&b.start_cocoa)));
+    gaps.truncate(limit);
+    gaps
+}
+
+/// Longest silences in the conversation with a contact.
+pub fn gaps(
+    contact: &str,
+    days: u32,
+    min_gap_days: u32,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let contact = contacts
+        .find_by_name(contact)
+        .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", contact))?;
+    output::applied("contact_resolved", &contact.phone);
+    output::applied("days", days);
 
+    let conn = open_db()?;
+    let now_cocoa = queries::unix_to_cocoa(chrono::Utc::now().timestamp());
+    let report = build_silence_gaps(
+        &conn,
+        queries::days_ago_cocoa(days),
+        now_cocoa,
+        &contact.phone,
+        &contact.name,
+        days,
+        min_gap_days,
+    )?;
+    output.show(&report)?;
     Ok(())
 }
 
-/// Detect messages needing follow-up.
-pub fn followup(days: u32, stale: u32, json: bool, contacts: &Arc<ContactsManager>) -> Result<()> {
-    let cutoff_cocoa = queries::days_ago_cocoa(days);
-    let stale_threshold_ns = (stale as i64) * 24 * 3600 * 1_000_000_000; // Convert days to nanoseconds
-
-    // Helper to calculate days ago from Cocoa timestamp
-    let days_ago_from_cocoa = |cocoa_ns: i64| -> i64 {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs() as i64;
-        let msg_unix = queries::cocoa_to_unix(cocoa_ns);
-        (now - msg_unix) / 86400
-    };
+/// Find silences in the conversation timeline and describe who broke each.
+pub(crate) fn build_silence_gaps(
+    conn: &rusqlite::Connection,
+    cutoff_cocoa: i64,
+    now_cocoa: i64,
+    phone: &str,
+    contact_name: &str,
+    days: u32,
+    min_gap_days: u32,
+) -> Result<SilenceGaps> {
+    let timeline = helpers::query_conversation_timeline(conn, cutoff_cocoa, phone)?;
+    let points: Vec<(i64, bool)> = timeline.iter().map(|m| (m.date_cocoa, m.is_from_me)).collect();
+    let gaps = find_gaps(&points, now_cocoa, min_gap_days as i64 * DAY_NS, GAPS_LIMIT)
+        .into_iter()
+        .map(|gap| {
+            let breaker = gap.broken_at.map(|i| &timeline[i]);
+            SilenceGap {
+                start: helpers::cocoa_to_iso(gap.start_cocoa),
+                end: helpers::cocoa_to_iso(gap.end_cocoa),
+                duration_days: (gap.duration_ns() as f64 / DAY_NS as f64 * 10.0).round() / 10.0,
+                ongoing: breaker.is_none(),
+                broken_by: breaker.map(|m| if m.is_from_me { "me" } else { "them" }.to_string()),
+                preview: breaker.and_then(|m| m.text.as_deref()).map(|t| output::preview(t, GAP_PREVIEW_CHARS)),
+            }
+        })
+        .collect();
+    Ok(SilenceGaps {
+        contact_name: contact_name.to_string(),
+        min_gap_days,
+        analysis_period_days: days,
+        gaps,
+    })
+}
 
-    // Clone contacts for parallel execution
-    let contacts_clone = Arc::clone(contacts);
-
-    // Execute 2 queries in parallel using rayon
-    let (unanswered_questions, stale_conversations) = rayon::join(
-        || {
-            // Query 1: Unanswered questions
-            let conn = open_db().expect("Failed to open DB");
-            let mut stmt = conn.prepare(queries::FOLLOWUP_UNANSWERED_QUESTIONS)
-                .expect("Failed to prepare query");
-            let question_rows = stmt.query_map([cutoff_cocoa, stale_threshold_ns], |row: &rusqlite::Row| {
-                let _rowid: i64 = row.get(0)?;
-                let text: Option<String> = row.get(1)?;
-                let date_cocoa: i64 = row.get(2)?;
-                let phone: Option<String> = row.get(3)?;
-
-                // Convert Cocoa timestamp to ISO string
-                let unix_ts = queries::cocoa_to_unix(date_cocoa);
-                use std::time::{UNIX_EPOCH, Duration};
-                let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-                let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-
-                Ok((
-                    phone.unwrap_or_else(|| "Unknown".to_string()),
-                    text.unwrap_or_else(|| "[no text]".to_string()),
-                    datetime.to_rfc3339(),
-                    days_ago_from_cocoa(date_cocoa),
-                ))
-            }).expect("Query failed");
-
-            question_rows
-                .filter_map(|r: rusqlite::Result<(String, String, String, i64)>| r.ok())
-                .map(|(phone, text, date, days_ago)| {
-                    let contact_name = contacts.find_by_phone(&phone).map(|c| c.name.clone());
-                    UnansweredQuestion {
-                        phone,
-                        contact_name,
-                        text,
-                        date,
-                        days_ago,
-                    }
-                })
-                .collect::<Vec<_>>()
-        },
-        || {
-            // Query 2: Stale conversations
-            let conn = open_db().expect("Failed to open DB");
-            let mut stmt = conn.prepare(queries::FOLLOWUP_STALE_CONVERSATIONS)
-                .expect("Failed to prepare query");
-            let stale_rows = stmt.query_map([cutoff_cocoa, stale_threshold_ns], |row: &rusqlite::Row| {
-                let phone: Option<String> = row.get(0)?;
-                let last_date_cocoa: i64 = row.get(1)?;
-                let last_text: Option<String> = row.get(2)?;
-                let _last_from_me: bool = row.get(3)?;
-
-                // Convert Cocoa timestamp to ISO string
-                let unix_ts = queries::cocoa_to_unix(last_date_cocoa);
-                use std::time::{UNIX_EPOCH, Duration};
-                let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-                let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-
-                Ok((
-                    phone.unwrap_or_else(|| "Unknown".to_string()),
-                    last_text,
-                    datetime.to_rfc3339(),
-                    days_ago_from_cocoa(last_date_cocoa),
-                ))
-            }).expect("Query failed");
-
-            stale_rows
-                .filter_map(|r: rusqlite::Result<(String, Option<String>, String, i64)>| r.ok())
-                .map(|(phone, last_text, last_date, days_ago)| {
-                    let contact_name = contacts_clone.find_by_phone(&phone).map(|c| c.name.clone());
-                    StaleConversation {
-                        phone,
-                        contact_name,
-                        last_text,
-                        last_date,
-                        days_ago,
-                    }
-                })
-                .collect::<Vec<_>>()
+/// Consecutive days texting with a contact, and when it all started (`--streaks`).
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Streaks {
+    pub contact_name: String,
+    /// Consecutive days with a message either way, ending today (or
+    /// yesterday, while today has none yet); 0 when broken
+    pub current_streak_days: u32,
+    /// First day of the current streak (YYYY-MM-DD)
+    pub current_streak_start: Option<String>,
+    pub texted_today: bool,
+    /// Longest streak in the period (the earliest, on a tie)
+    pub longest_streak_days: u32,
+    pub longest_streak_start: Option<String>,
+    pub longest_streak_end: Option<String>,
+    /// Days the streaks were looked for in; None with --all-time
+    pub analysis_period_days: Option<u32>,
+    /// First message ever exchanged (ISO 8601), whatever the period
+    pub first_message: Option<String>,
+    /// Next anniversary of the first message (YYYY-MM-DD)
+    pub next_anniversary: Option<String>,
+    /// Messages ever exchanged, whatever the period
+    pub lifetime_messages: i64,
+}
+
+/// Runs of consecutive days found by `find_streaks`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct StreakRuns {
+    /// Length and first day of the run ending today or yesterday
+    pub current: u32,
+    pub current_start: Option<NaiveDate>,
+    /// Length and first day of the longest run (earliest on a tie)
+    pub longest: u32,
+    pub longest_start: Option<NaiveDate>,
+}
+
+/// Streaks in a set of days with messages, as of `today`. The current
+/// streak survives a day without messages until that day is over, so a run
+/// ending yesterday still counts; days after `today` are ignored.
+pub(crate) fn find_streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> StreakRuns {
+    let mut runs = StreakRuns::default();
+    let mut run: Option<(NaiveDate, NaiveDate, u32)> = None;
+    for &day in days.range(..=today) {
+        let (start, len) = match run {
+            Some((start, last, len)) if last.succ_opt() == Some(day) => (start, len + 1),
+            _ => (day, 1),
+        };
+        if len > runs.longest {
+            runs.longest = len;
+            runs.longest_start = Some(start);
+        }
+        run = Some((start, day, len));
+    }
+    if let Some((start, last, len)) = run {
+        if last == today || last.succ_opt() == Some(today) {
+            runs.current = len;
+            runs.current_start = Some(start);
         }
-    );
+    }
+    runs
+}
 
-    let report = FollowUpReport {
-        unanswered_questions: unanswered_questions.clone(),
-        stale_conversations: stale_conversations.clone(),
-        total_items: unanswered_questions.len() + stale_conversations.len(),
-    };
+/// The first anniversary of `first` after it and on or after `today` (Feb 29
+/// falls back to Feb 28 outside leap years).
+fn next_anniversary(first: NaiveDate, today: NaiveDate) -> Option<NaiveDate> {
+    (first.year() + 1..=today.year().max(first.year()) + 1)
+        .filter_map(|year| first.with_year(year).or_else(|| NaiveDate::from_ymd_opt(year, 2, 28)))
+        .find(|&date| date >= today)
+}
 
-    // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&report)?);
-    } else {
-        println!("Follow-Up Report:");
-        println!("{:-<60}", "");
-        println!("Total items needing attention: {}", report.total_items);
-        println!();
-
-        if !unanswered_questions.is_empty() {
-            println!("Unanswered Questions ({}):", unanswered_questions.len());
-            println!("{:-<60}", "");
-            for q in &unanswered_questions {
-                let contact = q.contact_name.as_deref().unwrap_or(&q.phone);
-                println!("[{} days ago] {}", q.days_ago, contact);
-                let preview = if q.text.len() > 80 {
-                    format!("{}...", &q.text[..80])
-                } else {
-                    q.text.clone()
-                };
-                println!("  Q: {}", preview);
+/// The local calendar day of a message date.
+fn local_day(cocoa: i64) -> NaiveDate {
+    let unix = queries::decode_cocoa(cocoa).unix_secs;
+    chrono::DateTime::from_timestamp(unix, 0).unwrap_or_default().with_timezone(&Local).date_naive()
+}
+
+/// Current and longest streaks with a contact, plus the lifetime figures.
+pub fn streaks(
+    contact: &str,
+    days: u32,
+    all_time: bool,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let contact = contacts
+        .find_by_name(contact)
+        .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", contact))?;
+    output::applied("contact_resolved", &contact.phone);
+    let days = (!all_time).then_some(days);
+    if let Some(days) = days {
+        output::applied("days", days);
+    }
+
+    let conn = open_db()?;
+    let cutoff_cocoa = days.map(queries::days_ago_cocoa);
+    let today = Local::now().date_naive();
+    output.show(&build_streaks(&conn, &contact.phone, &contact.name, cutoff_cocoa, days, today)?)?;
+    Ok(())
+}
+
+/// Streaks in the conversation timeline since `cutoff_cocoa` (all history
+/// when None), bucketed by local day.
+pub(crate) fn build_streaks(
+    conn: &rusqlite::Connection,
+    phone: &str,
+    contact_name: &str,
+    cutoff_cocoa: Option<i64>,
+    days: Option<u32>,
+    today: NaiveDate,
+) -> Result<Streaks> {
+    let timeline = helpers::query_conversation_timeline(conn, cutoff_cocoa.unwrap_or(0), phone)?;
+    let active: BTreeSet<NaiveDate> = timeline.iter().map(|m| local_day(m.date_cocoa)).collect();
+    let runs = find_streaks(&active, today);
+    let (lifetime_messages, first_cocoa) = helpers::query_conversation_lifetime(conn, phone)?;
+
+    let day = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+    let longest_end = runs.longest_start.and_then(|start| start.checked_add_days(Days::new(runs.longest as u64 - 1)));
+    Ok(Streaks {
+        contact_name: contact_name.to_string(),
+        current_streak_days: runs.current,
+        current_streak_start: runs.current_start.map(day),
+        texted_today: active.contains(&today),
+        longest_streak_days: runs.longest,
+        longest_streak_start: runs.longest_start.map(day),
+        longest_streak_end: longest_end.map(day),
+        analysis_period_days: days,
+        first_message: first_cocoa.map(helpers::cocoa_to_iso),
+        next_anniversary: first_cocoa.and_then(|cocoa| next_anniversary(local_day(cocoa), today)).map(day),
+        lifetime_messages,
+    })
+}
+
+/// When messages happen: counts by local weekday (rows, Sunday first) and hour.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Heatmap {
+    /// Phone of the contact, when limited to one conversation
+    pub contact: Option<String>,
+    pub analysis_period_days: u32,
+    #[serde(flatten)]
+    pub counts: helpers::HeatmapCounts,
+}
+
+/// Weekday-by-hour message counts over the last `days`.
+pub fn heatmap(
+    contact: Option<&str>,
+    days: u32,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let phone = contact_phone(contact, contacts)?;
+    output::applied("days", days);
+    let conn = open_db()?;
+    output.show(&build_heatmap(&conn, days, phone)?)?;
+    Ok(())
+}
+
+/// Heatmap for everyone, or for the conversation with `phone`.
+pub fn build_heatmap(conn: &rusqlite::Connection, days: u32, phone: Option<String>) -> Result<Heatmap> {
+    let counts = helpers::query_heatmap(conn, queries::days_ago_cocoa(days), phone.as_deref())?;
+    Ok(Heatmap { contact: phone, analysis_period_days: days, counts })
+}
+
+/// Resolve an optional contact name to its phone (recorded as `contact_resolved`).
+fn contact_phone(contact: Option<&str>, contacts: &ContactsManager) -> Result<Option<String>> {
+    let phone = contact
+        .map(|name| {
+            contacts
+                .find_by_name(name)
+                .map(|c| c.phone.clone())
+                .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", name))
+        })
+        .transpose()?;
+    if let Some(phone) = &phone {
+        output::applied("contact_resolved", phone);
+    }
+    Ok(phone)
+}
+
+/// Average messages per day, rounded to 1 decimal.
+/// `part` as a percentage of `whole`, one decimal.
+fn percentage(part: i64, whole: i64) -> f64 {
+    ((part as f64) * 1000.0 / (whole as f64)).round() / 10.0
+}
+
+/// "+12 (+25.0%)", or "+12" when there is no percentage.
+fn delta_cell(metric: &MetricDelta) -> String {
+    match metric.delta_pct {
+        Some(pct) => format!("{:+} ({:+.1}%)", metric.delta, pct),
+        None => format!("{:+}", metric.delta),
+    }
+}
+
+fn average_daily(total: i64, days: u32) -> f64 {
+    if days == 0 {
+        return 0.0;
+    }
+    ((total as f64) / (days as f64) * 10.0).round() / 10.0
+}
+
+impl TextRenderer for Analytics {
+    fn render(&self, style: &TextStyle) -> String {
+        let mut stats = Table::new(&["messages", "sent", "received", "avg/day", "busiest", "attachments", "reactions"]);
+        let busiest = [
+            self.busiest_hour.map(|h| format!("{:02}:00", h)),
+            self.busiest_day.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+        stats.row(vec![
+            self.total_messages.to_string(),
+            self.sent_count.to_string(),
+            self.received_count.to_string(),
+            format!("{:.1}", self.avg_daily_messages),
+            if busiest.is_empty() { "-".to_string() } else { busiest },
+            self.attachment_count.to_string(),
+            self.reaction_count.to_string(),
+        ]);
+
+        let mut out = vec![
+            style.paint(Tone::Bold, &format!("Conversation Analytics ({} days)", self.analysis_period_days)),
+            stats.render(style),
+        ];
+        if let Some(ref c) = self.comparison {
+            let mut change = Table::new(&["vs previous", "messages", "sent", "received", "attachments", "reactions"]);
+            change.row(vec![
+                format!("{} days", self.analysis_period_days),
+                delta_cell(&c.total_messages),
+                delta_cell(&c.sent_count),
+                delta_cell(&c.received_count),
+                delta_cell(&c.attachment_count),
+                delta_cell(&c.reaction_count),
+            ]);
+            out.push(String::new());
+            out.push(change.render(style));
+        }
+        if !self.top_contacts.is_empty() {
+            let mut top = Table::new(&["top contact", "messages"]);
+            for tc in &self.top_contacts {
+                let who = tc.contact_name.as_ref().unwrap_or(&tc.phone);
+                top.row(vec![who.clone(), tc.message_count.to_string()]);
             }
-            println!();
+            out.push(String::new());
+            out.push(top.render(style));
+        }
+        if let Some(ref streaks) = self.streaks {
+            out.push(String::new());
+            out.push(streaks.render(style));
         }
+        out.join("\n")
+    }
+}
+
+/// Widest bar in the emoji text rendering.
+const EMOJI_BAR_WIDTH: usize = 10;
 
-        if !stale_conversations.is_empty() {
-            println!("Stale Conversations ({}):", stale_conversations.len());
-            println!("{:-<60}", "");
-            for s in &stale_conversations {
-                let contact = s.contact_name.as_deref().unwrap_or(&s.phone);
-                println!("[{} days ago] {}", s.days_ago, contact);
-                if let Some(ref text) = s.last_text {
-                    let preview = if text.len() > 80 {
-                        format!("{}...", &text[..80])
-                    } else {
-                        text.clone()
-                    };
-                    println!("  Last: {}", preview);
-                }
+impl TextRenderer for EmojiStats {
+    fn render(&self, style: &TextStyle) -> String {
+        let mut out = vec![style.paint(
+            Tone::Bold,
+            &format!("Emoji with {} ({} days)", self.contact_name, self.analysis_period_days),
+        )];
+        for (label, tone, side) in [("Me", Tone::Me, &self.me), (self.contact_name.as_str(), Tone::Other, &self.them)] {
+            out.push(String::new());
+            out.push(format!(
+                "{}  {}",
+                style.paint(tone, label),
+                style.paint(
+                    Tone::Dim,
+                    &format!(
+                        "{} emoji in {} messages ({:.1} per 100)",
+                        side.total_emoji, side.messages, side.emoji_per_100_messages
+                    ),
+                ),
+            ));
+            let max = side.top_emoji.first().map(|e| e.count).unwrap_or(0);
+            for e in &side.top_emoji {
+                let width = (e.count * EMOJI_BAR_WIDTH).div_ceil(max.max(1)).max(1);
+                out.push(format!("  {}  {} {}", e.emoji, "▇".repeat(width), e.count));
+            }
+            if side.top_emoji.is_empty() {
+                out.push("  (no emoji)".to_string());
             }
+            if !side.tapbacks.is_empty() {
+                let tapbacks: Vec<String> = side.tapbacks.iter().map(|(kind, n)| format!("{} {}", kind, n)).collect();
+                out.push(format!("  tapbacks: {}", tapbacks.join(", ")));
+            }
+        }
+        out.join("\n")
+    }
+}
+
+impl TextRenderer for SilenceGaps {
+    fn render(&self, style: &TextStyle) -> String {
+        let title = style.paint(
+            Tone::Bold,
+            &format!(
+                "Longest silences with {} ({} days, {}+ days)",
+                self.contact_name, self.analysis_period_days, self.min_gap_days
+            ),
+        );
+        if self.gaps.is_empty() {
+            return format!("{}\n{}", title, style.paint(Tone::Dim, "No silences that long."));
         }
+        let day = |iso: &str| iso.get(..10).unwrap_or(iso).to_string();
+        let mut table = Table::new(&["days", "from", "to", "broken by", "message"]);
+        for gap in &self.gaps {
+            let (to, broken_by) = if gap.ongoing {
+                ("now".to_string(), "(ongoing)".to_string())
+            } else {
+                let by = match gap.broken_by.as_deref() {
+                    Some("me") => "Me",
+                    _ => self.contact_name.as_str(),
+                };
+                (day(&gap.end), by.to_string())
+            };
+            table.row(vec![
+                format!("{:.1}", gap.duration_days),
+                day(&gap.start),
+                to,
+                broken_by,
+                gap.preview.clone().unwrap_or_default(),
+            ]);
+        }
+        format!("{}\n{}", title, table.render(style))
+    }
+}
 
-        if report.total_items == 0 {
-            println!("No follow-ups needed. Great job staying on top of messages!");
+impl TextRenderer for Streaks {
+    fn render(&self, style: &TextStyle) -> String {
+        let period = match self.analysis_period_days {
+            Some(days) => format!("{} days", days),
+            None => "all time".to_string(),
+        };
+        let title = style.paint(Tone::Bold, &format!("Streaks with {} ({})", self.contact_name, period));
+        let since = |days: u32, start: &Option<String>| match (days, start) {
+            (0, _) | (_, None) => "-".to_string(),
+            (days, Some(start)) => format!("{} days (from {})", days, start),
+        };
+        let mut table = Table::new(&["current", "longest", "first message", "next anniversary", "messages"]);
+        table.row(vec![
+            since(self.current_streak_days, &self.current_streak_start),
+            since(self.longest_streak_days, &self.longest_streak_start),
+            self.first_message.as_deref().and_then(|iso| iso.get(..10)).unwrap_or("-").to_string(),
+            self.next_anniversary.clone().unwrap_or_else(|| "-".to_string()),
+            self.lifetime_messages.to_string(),
+        ]);
+        let mut out = vec![title, table.render(style)];
+        if self.current_streak_days > 0 && !self.texted_today {
+            out.push(style.paint(Tone::Dim, "No message yet today: the streak ends at midnight."));
         }
+        out.join("\n")
     }
+}
 
-    Ok(())
+/// Heatmap shades, lightest to darkest.
+const HEAT_SHADES: [char; 5] = [' ', '.', ':', '*', '#'];
+
+impl TextRenderer for Heatmap {
+    fn render(&self, style: &TextStyle) -> String {
+        let who = self.contact.as_deref().map(|c| format!(" with {}", c)).unwrap_or_default();
+        let mut lines = vec![style.paint(
+            Tone::Bold,
+            &format!("Messages by weekday and hour{} ({} days, local time)", who, self.analysis_period_days),
+        )];
+        let totals: Vec<Vec<i64>> = (0..7)
+            .map(|day| (0..24).map(|hour| self.counts.sent[day][hour] + self.counts.received[day][hour]).collect())
+            .collect();
+        let max = totals.iter().flatten().copied().max().unwrap_or(0);
+        if max == 0 {
+            lines.push(style.paint(Tone::Dim, "No messages."));
+            return lines.join("\n");
+        }
+        lines.push(style.paint(Tone::Dim, "     0     6     12    18"));
+        for (day, row) in totals.iter().enumerate() {
+            let cells: String = row
+                .iter()
+                .map(|&n| match n {
+                    0 => HEAT_SHADES[0],
+                    // Any activity is at least the lightest visible shade
+                    n => HEAT_SHADES[((n * 4 + max - 1) / max).clamp(1, 4) as usize],
+                })
+                .collect();
+            let name = helpers::day_number_to_name(day as i64).unwrap_or_default();
+            lines.push(format!("{:<4} {}", name.get(..3).unwrap_or(name), cells));
+        }
+        let sent: i64 = self.counts.sent.iter().flatten().sum();
+        let received: i64 = self.counts.received.iter().flatten().sum();
+        lines.push(style.paint(Tone::Dim, &format!("sent {}, received {}, busiest slot {}", sent, received, max)));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+    #[test]
+    fn test_analytics_plain_rendering() {
+        let analytics = Analytics {
+            total_messages: 1234,
+            sent_count: 600,
+            received_count: 634,
+            avg_daily_messages: 41.13,
+            busiest_hour: Some(9),
+            busiest_day: Some("Tuesday".to_string()),
+            top_contacts: vec![
+                helpers::TopContact { phone: "+14155550001".to_string(), contact_name: None, message_count: 120, variants: Vec::new() },
+                helpers::TopContact { phone: "me@icloud.com".to_string(), contact_name: None, message_count: 7, variants: Vec::new() },
+            ],
+            attachment_count: 12,
+            reaction_count: 40,
+            analysis_period_days: 30,
+            period_start: None,
+            period_end: None,
+            comparison: None,
+            streaks: None,
+        };
+        let expected = "\
+Conversation Analytics (30 days)
+messages  sent  received  avg/day  busiest        attachments  reactions
+1234      600   634       41.1     09:00 Tuesday  12           40
+
+top contact    messages
++14155550001   120
+me@icloud.com  7";
+        assert_eq!(analytics.render(&TextStyle::default()), expected);
+    }
+
+    #[test]
+    fn test_top_terms_split_by_sender_with_blob_text() {
+        let conn = empty_db();
+        let sam = insert_handle(&conn, "+14155550001");
+        let other = insert_handle(&conn, "+14155550002");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        let day = 700 * DAY_NS;
+        insert_message(&conn, chat, sam, Some("Hiking Saturday, Sam here"), day, false, true);
+        insert_message(&conn, chat, sam, Some("hiking boots!"), day + 1, true, true);
+        insert_message(&conn, chat, other, Some("hiking elsewhere"), day + 2, false, true);
+
+        // Text only in attributedBody
+        let blob_msg = insert_message(&conn, chat, sam, None, day + 3, false, true);
+        let mut blob: Vec<u8> = b"streamtypedNSString".to_vec();
+        blob.extend_from_slice(&[0x01, 0x94, 0x84, 0x01, b'+', 0x0b]);
+        blob.extend_from_slice(b"more hiking");
+        blob.extend_from_slice(&[0x86, 0x84]);
+        conn.execute("UPDATE message SET attributedBody = ?1 WHERE ROWID = ?2", rusqlite::params![blob, blob_msg])
+            .unwrap();
+
+        let report = build_top_terms(&conn, 0, "4155550001", &["Sam Jones", "Alex"]).unwrap();
+        assert_eq!(report.messages_analyzed, 3);
+        let terms = |t: &[TermCount]| t.iter().map(|t| (t.term.clone(), t.count)).collect::<Vec<_>>();
+        assert_eq!(terms(&report.my_terms), vec![("boots".into(), 1), ("hiking".into(), 1)]);
+        assert_eq!(terms(&report.their_terms), vec![("hiking".into(), 2), ("saturday".into(), 1)]);
+    }
+
+    #[test]
+    fn test_emoji_stats_split_by_sender_with_tapbacks() {
+        let conn = empty_db();
+        let sam = insert_handle(&conn, "+14155550001");
+        let other = insert_handle(&conn, "+14155550002");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        let day = 700 * DAY_NS;
+        insert_message(&conn, chat, sam, Some("see you 👋🏽👋🏽"), day, false, true);
+        insert_message(&conn, chat, sam, Some("ok"), day + 1, false, true);
+        insert_message(&conn, chat, sam, Some("yay 🎉"), day + 2, true, true);
+        insert_message(&conn, chat, other, Some("🎉🎉🎉"), day + 3, false, true);
+
+        let tapback = |rowid: i64, kind: i64| {
+            conn.execute(
+                "UPDATE message SET associated_message_type = ?1 WHERE ROWID = ?2",
+                rusqlite::params![kind, rowid],
+            )
+            .unwrap();
+        };
+        tapback(insert_message(&conn, chat, sam, Some("Loved “ok”"), day + 4, true, true), 2000);
+        tapback(insert_message(&conn, chat, sam, Some("Laughed at “yay”"), day + 5, false, true), 2003);
+        // Removals don't count
+        tapback(insert_message(&conn, chat, sam, Some("Removed a heart"), day + 6, true, true), 3000);
+
+        let stats = build_emoji_stats(&conn, 0, "4155550001", "Sam", 30).unwrap();
+        assert_eq!((stats.me.messages, stats.me.total_emoji), (1, 1));
+        assert_eq!(stats.me.emoji_per_100_messages, 100.0);
+        assert_eq!(stats.me.tapbacks, BTreeMap::from([("love".to_string(), 1)]));
+        assert_eq!((stats.them.messages, stats.them.total_emoji), (2, 2));
+        assert_eq!(stats.them.top_emoji, vec![EmojiCount { emoji: "👋🏽".into(), count: 2 }]);
+        assert_eq!(stats.them.tapbacks, BTreeMap::from([("laugh".to_string(), 1)]));
+
+        let expected = "\
+Emoji with Sam (30 days)
+
+Me  1 emoji in 1 messages (100.0 per 100)
+  🎉  ▇▇▇▇▇▇▇▇▇▇ 1
+  tapbacks: love 1
+
+Sam  2 emoji in 2 messages (100.0 per 100)
+  👋🏽  ▇▇▇▇▇▇▇▇▇▇ 2
+  tapbacks: laugh 1";
+        assert_eq!(stats.render(&TextStyle::default()), expected);
+    }
+
+    #[test]
+    fn test_find_gaps_longest_first_with_threshold() {
+        const D: i64 = DAY_NS;
+        // 1d, 5d (broken by them), 2d, 10d (broken by me), then 4d to now
+        let timeline = [(0, true), (D, false), (6 * D, false), (8 * D, true), (18 * D, true)];
+        let gaps = find_gaps(&timeline, 22 * D, 3 * D, GAPS_LIMIT);
+        assert_eq!(
+            gaps,
+            vec![
+                Gap { start_cocoa: 8 * D, end_cocoa: 18 * D, broken_at: Some(4) },
+                Gap { start_cocoa: D, end_cocoa: 6 * D, broken_at: Some(2) },
+                Gap { start_cocoa: 18 * D, end_cocoa: 22 * D, broken_at: None },
+            ]
+        );
+        assert_eq!(find_gaps(&timeline, 22 * D, 3 * D, 1).len(), 1);
+        // Recent last message: no ongoing gap
+        assert!(find_gaps(&timeline, 18 * D + 1, 3 * D, GAPS_LIMIT).iter().all(|g| g.broken_at.is_some()));
+    }
+
+    #[test]
+    fn test_find_gaps_edge_cases() {
+        const D: i64 = DAY_NS;
+        assert!(find_gaps(&[], 100 * D, 3 * D, GAPS_LIMIT).is_empty());
+        // A single old message is one ongoing silence
+        assert_eq!(
+            find_gaps(&[(D, false)], 30 * D, 3 * D, GAPS_LIMIT),
+            vec![Gap { start_cocoa: D, end_cocoa: 30 * D, broken_at: None }]
+        );
+        // Exactly the threshold counts; equal durations keep the earlier first
+        let timeline = [(0, true), (3 * D, false), (6 * D, true)];
+        let gaps = find_gaps(&timeline, 6 * D, 3 * D, GAPS_LIMIT);
+        assert_eq!(gaps.iter().map(|g| g.start_cocoa).collect::<Vec<_>>(), vec![0, 3 * D]);
+    }
+
+    #[test]
+    fn test_silence_gaps_report() {
+        let conn = empty_db();
+        let sam = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        let day = 700 * DAY_NS;
+        insert_message(&conn, chat, sam, Some("see you"), day, true, true);
+        insert_message(&conn, chat, sam, Some("hey stranger,\nlong time"), day + 6 * DAY_NS, false, true);
+        insert_message(&conn, chat, sam, Some("ha yes"), day + 7 * DAY_NS, true, true);
+
+        let report = build_silence_gaps(&conn, 0, day + 11 * DAY_NS, "4155550001", "Sam", 30, 3).unwrap();
+        assert_eq!(report.gaps.len(), 2);
+        let first = &report.gaps[0];
+        assert_eq!((first.duration_days, first.ongoing), (6.0, false));
+        assert_eq!(first.broken_by.as_deref(), Some("them"));
+        assert_eq!(first.preview.as_deref(), Some("hey stranger, long time"));
+        let ongoing = &report.gaps[1];
+        assert!(ongoing.ongoing && ongoing.broken_by.is_none() && ongoing.preview.is_none());
+        assert_eq!(ongoing.duration_days, 4.0);
+
+        let rendered = report.render(&TextStyle::default());
+        assert!(rendered.starts_with("Longest silences with Sam (30 days, 3+ days)"), "{}", rendered);
+        assert!(rendered.contains("hey stranger, long time"), "{}", rendered);
+        assert!(rendered.contains("(ongoing)"), "{}", rendered);
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn day_set(days: &[(u32, u32)]) -> BTreeSet<NaiveDate> {
+        days.iter().map(|&(m, d)| date(2026, m, d)).collect()
+    }
+
+    #[test]
+    fn test_find_streaks_gap_patterns() {
+        let today = date(2026, 10, 16);
+        // Alive today: 14-16 after a gap, longest 3 earlier
+        let runs = find_streaks(&day_set(&[(10, 1), (10, 2), (10, 3), (10, 5), (10, 14), (10, 15), (10, 16)]), today);
+        assert_eq!((runs.current, runs.current_start), (3, Some(date(2026, 10, 14))));
+        assert_eq!((runs.longest, runs.longest_start), (3, Some(date(2026, 10, 1))));
+
+        // Alive through yesterday, nothing yet today
+        let runs = find_streaks(&day_set(&[(10, 13), (10, 14), (10, 15)]), today);
+        assert_eq!((runs.current, runs.current_start), (3, Some(date(2026, 10, 13))));
+
+        // Broken: last message two days ago
+        let runs = find_streaks(&day_set(&[(10, 10), (10, 11), (10, 12), (10, 13), (10, 14)]), today);
+        assert_eq!((runs.current, runs.current_start), (0, None));
+        assert_eq!((runs.longest, runs.longest_start), (5, Some(date(2026, 10, 10))));
+
+        // Across a month end; days after today don't count
+        let runs = find_streaks(&day_set(&[(9, 30), (10, 1), (10, 16), (10, 17), (10, 18), (10, 19)]), today);
+        assert_eq!((runs.current, runs.longest), (1, 2));
+        assert_eq!(runs.longest_start, Some(date(2026, 9, 30)));
+
+        assert_eq!(find_streaks(&BTreeSet::new(), today), StreakRuns::default());
+    }
+
+    #[test]
+    fn test_next_anniversary() {
+        let today = date(2026, 10, 16);
+        assert_eq!(next_anniversary(date(2019, 12, 1), today), Some(date(2026, 12, 1)));
+        assert_eq!(next_anniversary(date(2019, 3, 1), today), Some(date(2027, 3, 1)));
+        assert_eq!(next_anniversary(date(2019, 10, 16), today), Some(date(2026, 10, 16)));
+        assert_eq!(next_anniversary(date(2026, 10, 16), today), Some(date(2027, 10, 16)));
+        assert_eq!(next_anniversary(date(2024, 2, 29), today), Some(date(2027, 2, 28)));
+        assert_eq!(next_anniversary(date(2024, 2, 29), date(2027, 3, 1)), Some(date(2028, 2, 29)));
+    }
+
+    #[test]
+    fn test_streaks_report() {
+        let conn = empty_db();
+        let sam = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        let noon = DAY_NS / 2;
+        for (day, from_me) in [(600, true), (700, false), (701, true), (701, false), (702, true), (705, false), (706, true)] {
+            insert_message(&conn, chat, sam, Some("hi"), day * DAY_NS + noon, from_me, true);
+        }
+        let today = local_day(706 * DAY_NS + noon);
+
+        let all_time = build_streaks(&conn, "4155550001", "Sam", None, None, today).unwrap();
+        assert_eq!((all_time.current_streak_days, all_time.longest_streak_days), (2, 3));
+        assert!(all_time.texted_today);
+        assert_eq!(all_time.longest_streak_end, Some(local_day(702 * DAY_NS + noon).format("%Y-%m-%d").to_string()));
+        assert_eq!(all_time.lifetime_messages, 7);
+        assert_eq!(all_time.first_message, Some(helpers::cocoa_to_iso(600 * DAY_NS + noon)));
+
+        // The window bounds the streaks but not the lifetime figures
+        let recent = build_streaks(&conn, "4155550001", "Sam", Some(704 * DAY_NS), Some(3), today).unwrap();
+        assert_eq!((recent.current_streak_days, recent.longest_streak_days), (2, 2));
+        assert_eq!(recent.lifetime_messages, 7);
+        assert_eq!(recent.first_message, all_time.first_message);
+
+        let rendered = recent.render(&TextStyle::default());
+        assert!(rendered.starts_with("Streaks with Sam (3 days)"), "{}", rendered);
+        assert!(!rendered.contains("No message yet today"), "{}", rendered);
+    }
 }
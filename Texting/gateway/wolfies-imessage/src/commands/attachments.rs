@@ -0,0 +1,1467 @@
+//! Attachment commands: list attachments and copy them out with readable names.
+//!
+//! Attachment files live under ~/Library/Messages/Attachments in GUID-named
+//! directories, often as HEIC. `--copy-to` copies matched files into a target
+//! directory as `YYYYMMDD_<sender>_<original-name>`. `--image-meta` reads the
+//! head of each image file for dimensions, capture date, and camera model.
+//! `--dedupe` hashes each file (blake3) and collapses identical ones, in the
+//! listing and in the copy-out.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - find_mp4_box stops on a box size that overflows (checked_add) instead of panicking
+//! - 10/16/2026 - --max-bytes stops copying at the first file over budget (later smaller files were still copied, contrary to the docs); sort_by_key
+//! - 10/16/2026 - Progress on stderr while hashing (--dedupe) and copying (--copy-to); copy_attachments, copy_deduped and dedupe_keys take a Progress
+//! - 10/16/2026 - Added --dedupe (blake3 content hashes, duplicate_of, bytes saved) and --max-hash-bytes
+//! - 10/16/2026 - Attachment rows that fail to decode are skipped with a warning (helpers::ok_row)
+//! - 10/16/2026 - JSON goes through OutputControls::print_json (--envelope)
+//! - 10/16/2026 - --csv output (attachment rows and copy results)
+//! - 10/16/2026 - Added --image-meta (dimensions and EXIF for image attachments)
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - Contact and MIME filters escape LIKE wildcards
+//! - 10/16/2026 - Added audio enrichment (duration, transcript, sender name)
+//! - 10/16/2026 - Moved attachments command from reading.rs; added copy-out with HEIC hook
+
+use anyhow::{Context, Result};
+use chrono::TimeZone;
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::{blob_parser, connection, helpers, queries};
+use crate::output::OutputControls;
+use crate::progress::{self, Progress};
+
+/// Bytes read from the head of each image for `--image-meta`.
+const IMAGE_META_MAX_BYTES: u64 = 256 * 1024;
+/// Worker threads for `--image-meta` (disk IO dominates).
+const IMAGE_META_THREADS: usize = 4;
+/// Worker threads for `--dedupe` hashing.
+const HASH_THREADS: usize = 4;
+
+/// Attachment row for serialization.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Attachment {
+    pub filename: Option<String>,
+    pub mime_type: Option<String>,
+    pub total_bytes: Option<i64>,
+    pub transfer_name: Option<String>,
+    pub date: String,
+    pub is_from_me: bool,
+    pub sender_handle: Option<String>,
+    /// Present for audio attachments only
+    #[serde(flatten)]
+    pub audio: Option<AudioInfo>,
+    /// Present for image attachments with `--image-meta` only
+    #[serde(flatten)]
+    pub image: Option<ImageMeta>,
+    /// blake3 of the file (or its first `--max-hash-bytes`); `--dedupe` only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Filename of the earliest identical attachment; `--dedupe` only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    #[serde(skip)]
+    pub date_cocoa: i64,
+}
+
+/// Enrichment for audio (voice message) attachments.
+///
+/// Every field is nullable: an unreadable file or missing transcript never
+/// drops the row.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+pub struct AudioInfo {
+    pub duration_secs: Option<f64>,
+    pub transcript: Option<String>,
+    pub sender_name: Option<String>,
+}
+
+/// Metadata read from an image attachment's file.
+///
+/// Every field is nullable; a missing or unreadable file sets `meta_error`
+/// instead of dropping the row. Images without EXIF keep their dimensions.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+pub struct ImageMeta {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// EXIF DateTimeOriginal as camera-local time (YYYY-MM-DDTHH:MM:SS)
+    pub captured_at: Option<String>,
+    pub camera_model: Option<String>,
+    pub meta_error: Option<String>,
+}
+
+/// Content-hash options from `--dedupe` / `--max-hash-bytes`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DedupeOptions {
+    /// Hash only the first this-many bytes (plus the file length)
+    pub max_hash_bytes: Option<u64>,
+}
+
+/// What `--dedupe` compares: the content when the file is readable,
+/// otherwise what the database recorded about it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DedupeKey {
+    /// File length and blake3 hex of (the first `max_hash_bytes` of) the file
+    Content { len: u64, hash: String },
+    /// Missing file: `(total_bytes, transfer_name)` from the attachment row
+    Recorded { total_bytes: i64, transfer_name: String },
+}
+
+impl DedupeKey {
+    /// Size of one copy, for `bytes_saved`.
+    fn bytes(&self) -> u64 {
+        match self {
+            DedupeKey::Content { len, .. } => *len,
+            DedupeKey::Recorded { total_bytes, .. } => (*total_bytes).max(0) as u64,
+        }
+    }
+}
+
+/// Totals for a `--dedupe` run.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+pub struct DedupeSummary {
+    /// Rows kept (including ones with nothing to compare)
+    pub unique: usize,
+    /// Rows collapsed into an earlier identical one
+    pub duplicates: usize,
+    /// Bytes the duplicates take up
+    pub bytes_saved: u64,
+}
+
+/// `attachments --dedupe` listing: one row per distinct file.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DedupedAttachments {
+    pub attachments: Vec<Attachment>,
+    /// Collapsed rows, each naming its original in `duplicate_of`
+    pub duplicates: Vec<Attachment>,
+    pub dedupe: DedupeSummary,
+}
+
+/// `attachments --copy-to --dedupe`: per-file status plus totals.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DedupedCopy {
+    pub results: Vec<CopyResult>,
+    pub dedupe: DedupeSummary,
+}
+
+/// Copy-out options from CLI flags.
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// Target directory (created if missing)
+    pub dest_dir: PathBuf,
+    /// Copy newest attachments first (default: chronological)
+    pub newest_first: bool,
+    /// Stop copying once this many bytes have been written
+    pub max_bytes: Option<u64>,
+    /// Convert HEIC images to JPEG after copying (macOS `sips`)
+    pub convert_heic: bool,
+}
+
+/// A single file to copy.
+#[derive(Debug, Clone)]
+pub struct CopyItem {
+    pub source: PathBuf,
+    /// Local date as YYYYMMDD
+    pub date: String,
+    pub sender: String,
+    pub original_name: String,
+}
+
+/// Per-file copy status.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+pub struct CopyResult {
+    pub source: String,
+    pub dest: Option<String>,
+    pub bytes: u64,
+    pub skipped_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub converted: Option<String>,
+    /// Source of the copied file this one duplicates (`--dedupe`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+}
+
+/// Get attachments (photos, videos, files), optionally copying them out.
+#[allow(clippy::too_many_arguments)]
+pub fn attachments(
+    contact: Option<&str>,
+    mime_type: Option<&str>,
+    limit: u32,
+    copy: Option<&CopyOptions>,
+    image_meta: bool,
+    dedupe: Option<DedupeOptions>,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = connection::open_db()?;
+    let progress = progress::for_output(output);
+    let mut rows = query_attachments(&conn, contact, mime_type, limit, contacts)?;
+    if image_meta {
+        fill_image_meta(&mut rows)?;
+    }
+    let keys = match dedupe {
+        Some(opts) => Some(dedupe_keys(&mut rows, opts.max_hash_bytes, progress.as_ref())?),
+        None => None,
+    };
+
+    if let Some(opts) = copy {
+        let mut items: Vec<(i64, Option<DedupeKey>, CopyItem)> = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, a)| {
+                let key = keys.as_ref().and_then(|keys| keys[i].clone());
+                to_copy_item(a, contacts).map(|item| (a.date_cocoa, key, item))
+            })
+            .collect();
+        // Chronological by default; newest first when asked (max_bytes keeps the head)
+        if opts.newest_first {
+            items.sort_by_key(|item| std::cmp::Reverse(item.0));
+        } else {
+            items.sort_by_key(|item| item.0);
+        }
+        let (item_keys, items): (Vec<Option<DedupeKey>>, Vec<CopyItem>) =
+            items.into_iter().map(|(_, key, item)| (key, item)).unzip();
+
+        let (mut results, summary) = if keys.is_some() {
+            let (results, summary) =
+                copy_deduped(&items, &item_keys, &opts.dest_dir, opts.max_bytes, progress.as_ref())?;
+            (results, Some(summary))
+        } else {
+            (copy_attachments(&items, &opts.dest_dir, opts.max_bytes, progress.as_ref())?, None)
+        };
+        if opts.convert_heic {
+            for result in results.iter_mut() {
+                apply_heic_hook(result, &SipsConverter);
+            }
+        }
+        if output.csv {
+            output.print_csv(&results)?;
+            return Ok(());
+        }
+        return print_copy_results(&results, summary.as_ref(), output);
+    }
+
+    if let Some(keys) = keys {
+        return print_deduped(rows, &keys, output);
+    }
+
+    if output.csv {
+        output.print_csv(&rows)?;
+    } else if output.json {
+        output.print_json(&rows, false)?;
+    } else {
+        if rows.is_empty() {
+            println!("No attachments found.");
+            return Ok(());
+        }
+
+        println!("Attachments ({}):", rows.len());
+        println!("{}", "-".repeat(60));
+        for a in &rows {
+            let name = a
+                .filename
+                .as_deref()
+                .or(a.transfer_name.as_deref())
+                .unwrap_or("Unknown");
+            let mime = a.mime_type.as_deref().unwrap_or("unknown");
+            let size = a.total_bytes.unwrap_or(0);
+            let size_str = if size > 0 {
+                format!("{:.1}KB", size as f64 / 1024.0)
+            } else {
+                "N/A".to_string()
+            };
+            match &a.image {
+                Some(meta) => println!("{} ({}, {}) {}", name, mime, size_str, describe_image_meta(meta)),
+                None => println!("{} ({}, {})", name, mime, size_str),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Query attachment rows with optional contact and MIME prefix filters.
+pub fn query_attachments(
+    conn: &rusqlite::Connection,
+    contact: Option<&str>,
+    mime_type: Option<&str>,
+    limit: u32,
+    contacts: &ContactsManager,
+) -> Result<Vec<Attachment>> {
+    // Resolve contact to a digits pattern (same approach as find)
+    let phone_pattern = contact.map(|c| {
+        let phone = contacts.resolve_to_phone(c).unwrap_or_else(|| c.to_string());
+        crate::output::applied("contact_resolved", &phone);
+        let digits: String = phone.chars().filter(|ch| ch.is_ascii_digit()).collect();
+        queries::like_contains(&digits)
+    });
+    let mime_pattern = mime_type.map(|m| format!("{}%", queries::escape_like(m)));
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            attachment.filename,
+            attachment.mime_type,
+            attachment.total_bytes,
+            attachment.transfer_name,
+            message.date,
+            message.is_from_me,
+            handle.id,
+            message.text,
+            message.attributedBody
+        FROM attachment
+        JOIN message_attachment_join ON attachment.ROWID = message_attachment_join.attachment_id
+        JOIN message ON message_attachment_join.message_id = message.ROWID
+        LEFT JOIN handle ON message.handle_id = handle.ROWID
+        WHERE (?1 IS NULL OR handle.id LIKE ?1 ESCAPE '\')
+          AND (?2 IS NULL OR attachment.mime_type LIKE ?2 ESCAPE '\')
+        ORDER BY message.date DESC
+        LIMIT ?3
+        "#,
+    )?;
+
+    let rows = stmt.query_map(
+        rusqlite::params![phone_pattern, mime_pattern, limit],
+        |row| {
+            let date_cocoa: i64 = row.get(4)?;
+            let text: Option<String> = row.get(7)?;
+            let blob: Option<Vec<u8>> = row.get(8)?;
+            Ok((
+                Attachment {
+                    filename: row.get(0)?,
+                    mime_type: row.get(1)?,
+                    total_bytes: row.get(2)?,
+                    transfer_name: row.get(3)?,
+                    date: helpers::cocoa_to_iso(date_cocoa),
+                    is_from_me: row.get::<_, i32>(5)? != 0,
+                    sender_handle: row.get(6)?,
+                    audio: None,
+                    image: None,
+                    content_hash: None,
+                    duplicate_of: None,
+                    date_cocoa,
+                },
+                text,
+                blob,
+            ))
+        },
+    )?;
+
+    Ok(rows
+        .filter_map(helpers::ok_row)
+        .map(|(mut a, text, blob)| {
+            let is_audio = a
+                .mime_type
+                .as_deref()
+                .map(|m| m.starts_with("audio/"))
+                .unwrap_or(false);
+            if is_audio {
+                a.audio = Some(audio_info(&a, text, blob.as_deref(), contacts));
+            }
+            a
+        })
+        .collect())
+}
+
+/// Build audio enrichment for an attachment row.
+fn audio_info(
+    a: &Attachment,
+    text: Option<String>,
+    blob: Option<&[u8]>,
+    contacts: &ContactsManager,
+) -> AudioInfo {
+    let duration_secs = a
+        .filename
+        .as_deref()
+        .and_then(|f| audio_duration_secs(Path::new(&shellexpand::tilde(f).to_string())));
+
+    // Transcripts ride along in the message text/attributedBody; the bare
+    // attachment placeholder (U+FFFC) is not a transcript.
+    let raw = text.or_else(|| blob.and_then(|b| blob_parser::parse_blob(b).ok().flatten().map(|p| p.text)));
+    let transcript = raw
+        .map(|t| t.replace('\u{FFFC}', "").trim().to_string())
+        .filter(|t| !t.is_empty());
+
+    let sender_name = if a.is_from_me {
+        None
+    } else {
+        a.sender_handle
+            .as_deref()
+            .and_then(|h| contacts.find_by_phone(h))
+            .map(|c| c.name.clone())
+    };
+
+    AudioInfo {
+        duration_secs,
+        transcript,
+        sender_name,
+    }
+}
+
+/// Read an audio file's duration from its header (m4a/mp4 or CAF).
+///
+/// Returns None for missing files and unknown or malformed formats.
+pub fn audio_duration_secs(path: &Path) -> Option<f64> {
+    let data = std::fs::read(path).ok()?;
+    if data.len() >= 4 && &data[0..4] == b"caff" {
+        caf_duration_secs(&data)
+    } else {
+        mp4_duration_secs(&data)
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    let b = data.get(at..at + 8)?;
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(b);
+    Some(u64::from_be_bytes(buf))
+}
+
+/// Find a child box by type within `data`, returning its payload. A box
+/// size that runs past the end (or overflows) ends the search.
+fn find_mp4_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    let mut pos = 0;
+    while pos + 8 <= data.len() {
+        let size32 = read_u32(data, pos)? as usize;
+        let box_type = &data[pos + 4..pos + 8];
+        let (header, size) = match size32 {
+            1 => (16, usize::try_from(read_u64(data, pos + 8)?).ok()?),
+            0 => (8, data.len() - pos),
+            n => (8, n),
+        };
+        let end = pos.checked_add(size)?;
+        if size < header || end > data.len() {
+            return None;
+        }
+        if box_type == kind {
+            return Some(&data[pos + header..end]);
+        }
+        pos = end;
+    }
+    None
+}
+
+/// Duration from the moov/mvhd box of an MPEG-4 (m4a) file.
+fn mp4_duration_secs(data: &[u8]) -> Option<f64> {
+    let moov = find_mp4_box(data, b"moov")?;
+    let mvhd = find_mp4_box(moov, b"mvhd")?;
+    let (timescale, duration) = match *mvhd.first()? {
+        // version(1) + flags(3) + creation(4) + modification(4)
+        0 => (read_u32(mvhd, 12)? as u64, read_u32(mvhd, 16)? as u64),
+        // version(1) + flags(3) + creation(8) + modification(8)
+        1 => (read_u32(mvhd, 20)? as u64, read_u64(mvhd, 24)?),
+        _ => return None,
+    };
+    if timescale == 0 {
+        return None;
+    }
+    Some(duration as f64 / timescale as f64)
+}
+
+/// Duration from the desc/pakt (or data) chunks of a Core Audio Format file.
+fn caf_duration_secs(data: &[u8]) -> Option<f64> {
+    // "caff" + version(2) + flags(2)
+    let mut pos = 8;
+    let mut sample_rate: Option<f64> = None;
+    let mut bytes_per_packet = 0u32;
+    let mut frames_per_packet = 0u32;
+    let mut valid_frames: Option<u64> = None;
+    let mut data_bytes: Option<u64> = None;
+
+    while pos + 12 <= data.len() {
+        let chunk_type = &data[pos..pos + 4];
+        let size = read_u64(data, pos + 4)? as i64;
+        let body = pos + 12;
+        match chunk_type {
+            b"desc" => {
+                sample_rate = Some(f64::from_bits(read_u64(data, body)?));
+                bytes_per_packet = read_u32(data, body + 16)?;
+                frames_per_packet = read_u32(data, body + 20)?;
+            }
+            b"pakt" => {
+                valid_frames = Some(read_u64(data, body + 8)?);
+            }
+            b"data" => {
+                // -1 means "runs to end of file"; the first 4 bytes are an edit count
+                let len = if size < 0 { (data.len() - body) as i64 } else { size };
+                data_bytes = Some(len.saturating_sub(4).max(0) as u64);
+                if size < 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        if size < 0 {
+            break;
+        }
+        pos = body.checked_add(size as usize)?;
+    }
+
+    let rate = sample_rate.filter(|r| *r > 0.0)?;
+    if let Some(frames) = valid_frames {
+        return Some(frames as f64 / rate);
+    }
+    match data_bytes {
+        Some(bytes) if bytes_per_packet > 0 && frames_per_packet > 0 => {
+            let packets = bytes / bytes_per_packet as u64;
+            Some((packets * frames_per_packet as u64) as f64 / rate)
+        }
+        _ => None,
+    }
+}
+
+fn is_image(a: &Attachment) -> bool {
+    a.mime_type
+        .as_deref()
+        .map(|m| m.starts_with("image/"))
+        .unwrap_or(false)
+}
+
+/// Fill `image` for image rows, `IMAGE_META_THREADS` files at a time.
+pub fn fill_image_meta(rows: &mut [Attachment]) -> Result<()> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(IMAGE_META_THREADS)
+        .build()
+        .context("Failed to start image metadata workers")?;
+    pool.install(|| {
+        rows.par_iter_mut().filter(|a| is_image(a)).for_each(|a| {
+            a.image = Some(match a.filename.as_deref() {
+                Some(f) => image_meta(Path::new(&shellexpand::tilde(f).to_string())),
+                None => ImageMeta {
+                    meta_error: Some("no_file_path".to_string()),
+                    ..ImageMeta::default()
+                },
+            });
+        });
+    });
+    Ok(())
+}
+
+/// Read dimensions and EXIF fields from the first `IMAGE_META_MAX_BYTES` of
+/// an image file.
+///
+/// HEIC files whose metadata sits past the read window come back with nulls
+/// (and a `meta_error` when even the dimensions are out of reach).
+pub fn image_meta(path: &Path) -> ImageMeta {
+    let mut head = Vec::new();
+    let read = std::fs::File::open(path)
+        .and_then(|f| f.take(IMAGE_META_MAX_BYTES).read_to_end(&mut head));
+    if let Err(e) = read {
+        let reason = if e.kind() == std::io::ErrorKind::NotFound {
+            "missing".to_string()
+        } else {
+            format!("unreadable: {}", e)
+        };
+        return ImageMeta {
+            meta_error: Some(reason),
+            ..ImageMeta::default()
+        };
+    }
+
+    let mut meta = ImageMeta::default();
+    match imagesize::blob_size(&head) {
+        Ok(size) => {
+            meta.width = u32::try_from(size.width).ok();
+            meta.height = u32::try_from(size.height).ok();
+        }
+        Err(e) => meta.meta_error = Some(format!("dimensions: {}", e)),
+    }
+
+    // No EXIF is normal (screenshots, stripped uploads), so it isn't an error
+    if let Ok(exif) = exif::Reader::new().read_from_container(&mut Cursor::new(&head)) {
+        meta.captured_at = exif_ascii(&exif, exif::Tag::DateTimeOriginal)
+            .or_else(|| exif_ascii(&exif, exif::Tag::DateTime))
+            .and_then(|raw| exif::DateTime::from_ascii(&raw).ok())
+            .map(|dt| {
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+                )
+            });
+        meta.camera_model = exif_ascii(&exif, exif::Tag::Model)
+            .map(|raw| String::from_utf8_lossy(&raw).trim().to_string())
+            .filter(|m| !m.is_empty());
+    }
+
+    meta
+}
+
+/// First ASCII value of a primary-image EXIF tag.
+fn exif_ascii(exif: &exif::Exif, tag: exif::Tag) -> Option<Vec<u8>> {
+    match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Ascii(parts) => parts.first().cloned(),
+        _ => None,
+    }
+}
+
+/// One-line text summary of image metadata.
+fn describe_image_meta(meta: &ImageMeta) -> String {
+    let mut parts = Vec::new();
+    if let (Some(w), Some(h)) = (meta.width, meta.height) {
+        parts.push(format!("{}x{}", w, h));
+    }
+    if let Some(model) = &meta.camera_model {
+        parts.push(model.clone());
+    }
+    if let Some(captured) = &meta.captured_at {
+        parts.push(captured.clone());
+    }
+    if let Some(err) = &meta.meta_error {
+        parts.push(format!("meta_error: {}", err));
+    }
+    format!("[{}]", parts.join(", "))
+}
+
+/// blake3 hex of a file (or of its first `max_bytes`) and the file's length.
+///
+/// None when the file is missing or unreadable.
+pub fn content_hash(path: &Path, max_bytes: Option<u64>) -> Option<(u64, String)> {
+    let file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok().filter(|m| m.is_file())?.len();
+    let mut hasher = blake3::Hasher::new();
+    match max_bytes {
+        Some(max) => hasher.update_reader(file.take(max)).ok()?,
+        None => hasher.update_reader(file).ok()?,
+    };
+    Some((len, hasher.finalize().to_hex().to_string()))
+}
+
+/// Dedupe key for one row: hash the file when it's there, otherwise fall
+/// back to `(total_bytes, transfer_name)`; None when neither is known.
+fn dedupe_key(a: &Attachment, max_hash_bytes: Option<u64>) -> Option<DedupeKey> {
+    let hashed = a
+        .filename
+        .as_deref()
+        .and_then(|f| content_hash(Path::new(&shellexpand::tilde(f).to_string()), max_hash_bytes));
+    if let Some((len, hash)) = hashed {
+        return Some(DedupeKey::Content { len, hash });
+    }
+    match (a.total_bytes, &a.transfer_name) {
+        (Some(total_bytes), Some(name)) if total_bytes > 0 => Some(DedupeKey::Recorded {
+            total_bytes,
+            transfer_name: name.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// Key every row, `HASH_THREADS` files at a time, filling `content_hash`
+/// for the rows whose file was hashed.
+pub fn dedupe_keys(
+    rows: &mut [Attachment],
+    max_hash_bytes: Option<u64>,
+    progress: &dyn Progress,
+) -> Result<Vec<Option<DedupeKey>>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(HASH_THREADS)
+        .build()
+        .context("Failed to start hashing workers")?;
+    progress.start("Hashing", Some(rows.len() as u64));
+    let keys: Vec<Option<DedupeKey>> = pool.install(|| {
+        rows.par_iter()
+            .map(|a| {
+                let key = dedupe_key(a, max_hash_bytes);
+                progress.advance(1);
+                key
+            })
+            .collect()
+    });
+    progress.finish();
+    for (a, key) in rows.iter_mut().zip(&keys) {
+        if let Some(DedupeKey::Content { hash, .. }) = key {
+            a.content_hash = Some(hash.clone());
+        }
+    }
+    Ok(keys)
+}
+
+/// For each key, the index of the first earlier entry with the same key.
+///
+/// Entries without a key are never duplicates.
+pub fn duplicate_indices(keys: &[Option<DedupeKey>]) -> Vec<Option<usize>> {
+    let mut first: HashMap<&DedupeKey, usize> = HashMap::new();
+    keys.iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let key = key.as_ref()?;
+            match first.get(key) {
+                Some(&original) => Some(original),
+                None => {
+                    first.insert(key, i);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Totals for duplicate indices over `keys`.
+fn dedupe_summary(keys: &[Option<DedupeKey>], dupes: &[Option<usize>]) -> DedupeSummary {
+    let mut summary = DedupeSummary::default();
+    for (key, dupe) in keys.iter().zip(dupes) {
+        match (key, dupe) {
+            (Some(key), Some(_)) => {
+                summary.duplicates += 1;
+                summary.bytes_saved += key.bytes();
+            }
+            _ => summary.unique += 1,
+        }
+    }
+    summary
+}
+
+/// Split rows (newest first, as queried) into originals and duplicates.
+///
+/// The earliest copy of each file is the original; later ones name its
+/// filename (or transfer name) in `duplicate_of`.
+pub fn collapse_duplicates(rows: Vec<Attachment>, keys: &[Option<DedupeKey>]) -> DedupedAttachments {
+    // Oldest first, so the first occurrence is the earliest one
+    let keys: Vec<Option<DedupeKey>> = keys.iter().rev().cloned().collect();
+    let dupes = duplicate_indices(&keys);
+    let dedupe = dedupe_summary(&keys, &dupes);
+
+    let mut rows: Vec<Attachment> = rows.into_iter().rev().collect();
+    let names: Vec<Option<String>> = rows
+        .iter()
+        .map(|a| a.filename.clone().or_else(|| a.transfer_name.clone()))
+        .collect();
+    let mut attachments = Vec::new();
+    let mut duplicates = Vec::new();
+    for (mut a, dupe) in rows.drain(..).zip(&dupes) {
+        match dupe {
+            Some(original) => {
+                a.duplicate_of = names[*original].clone();
+                duplicates.push(a);
+            }
+            None => attachments.push(a),
+        }
+    }
+    attachments.reverse();
+    duplicates.reverse();
+    DedupedAttachments { attachments, duplicates, dedupe }
+}
+
+/// Print a `--dedupe` listing in CSV, JSON, or text mode.
+fn print_deduped(rows: Vec<Attachment>, keys: &[Option<DedupeKey>], output: &OutputControls) -> Result<()> {
+    let deduped = collapse_duplicates(rows, keys);
+    if output.csv {
+        let all: Vec<&Attachment> = deduped.attachments.iter().chain(&deduped.duplicates).collect();
+        output.print_csv(&all)?;
+        return Ok(());
+    }
+    if output.json {
+        output.print_json(&deduped, false)?;
+        return Ok(());
+    }
+
+    if deduped.attachments.is_empty() {
+        println!("No attachments found.");
+        return Ok(());
+    }
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for d in &deduped.duplicates {
+        *counts.entry(d.duplicate_of.as_deref().unwrap_or("")).or_default() += 1;
+    }
+    println!(
+        "Attachments ({} unique, {} duplicates, {:.1}MB saved):",
+        deduped.dedupe.unique,
+        deduped.dedupe.duplicates,
+        deduped.dedupe.bytes_saved as f64 / (1024.0 * 1024.0)
+    );
+    println!("{}", "-".repeat(60));
+    for a in &deduped.attachments {
+        let name = a.filename.as_deref().or(a.transfer_name.as_deref()).unwrap_or("Unknown");
+        let mime = a.mime_type.as_deref().unwrap_or("unknown");
+        match counts.get(name) {
+            Some(n) => println!("{} ({}) +{} duplicate(s)", name, mime, n),
+            None => println!("{} ({})", name, mime),
+        }
+    }
+    Ok(())
+}
+
+/// `copy_attachments` for keyed items: later copies of a file are skipped
+/// as "duplicate" and name the copied source in `duplicate_of`.
+pub fn copy_deduped(
+    items: &[CopyItem],
+    keys: &[Option<DedupeKey>],
+    dest_dir: &Path,
+    max_bytes: Option<u64>,
+    progress: &dyn Progress,
+) -> Result<(Vec<CopyResult>, DedupeSummary)> {
+    let dupes = duplicate_indices(keys);
+    let originals: Vec<CopyItem> = items
+        .iter()
+        .zip(&dupes)
+        .filter(|(_, dupe)| dupe.is_none())
+        .map(|(item, _)| item.clone())
+        .collect();
+    let mut copied = copy_attachments(&originals, dest_dir, max_bytes, progress)?.into_iter();
+
+    let results = items
+        .iter()
+        .zip(&dupes)
+        .map(|(item, dupe)| match dupe {
+            Some(original) => CopyResult {
+                source: item.source.to_string_lossy().to_string(),
+                dest: None,
+                bytes: 0,
+                skipped_reason: Some("duplicate".to_string()),
+                converted: None,
+                duplicate_of: Some(items[*original].source.to_string_lossy().to_string()),
+            },
+            None => copied.next().expect("one result per original"),
+        })
+        .collect();
+    Ok((results, dedupe_summary(keys, &dupes)))
+}
+
+/// Build a copy item from an attachment row (None when there's no file path).
+fn to_copy_item(a: &Attachment, contacts: &ContactsManager) -> Option<CopyItem> {
+    let filename = a.filename.as_deref()?;
+    let source = PathBuf::from(shellexpand::tilde(filename).to_string());
+
+    let original_name = a
+        .transfer_name
+        .clone()
+        .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "attachment".to_string());
+
+    let sender = if a.is_from_me {
+        "me".to_string()
+    } else {
+        a.sender_handle
+            .as_deref()
+            .map(|h| contacts.find_by_phone(h).map(|c| c.name.clone()).unwrap_or_else(|| h.to_string()))
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    let date = chrono::Local
+        .timestamp_opt(queries::cocoa_to_unix(a.date_cocoa), 0)
+        .single()
+        .map(|dt| dt.format("%Y%m%d").to_string())
+        .unwrap_or_else(|| "00000000".to_string());
+
+    Some(CopyItem {
+        source,
+        date,
+        sender,
+        original_name,
+    })
+}
+
+/// Make a string safe for use in a filename.
+fn sanitize_component(s: &str) -> String {
+    let cleaned: String = s
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '+' | '-' | '.' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let trimmed = cleaned.trim_matches(|c| c == '_' || c == '.');
+    if trimmed.is_empty() {
+        "unknown".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Readable destination name: `YYYYMMDD_<sender>_<original-name>`.
+pub fn readable_name(date: &str, sender: &str, original_name: &str) -> String {
+    format!(
+        "{}_{}_{}",
+        date,
+        sanitize_component(sender),
+        sanitize_component(original_name)
+    )
+}
+
+/// Append a counter before the extension until the name is unused.
+fn unique_dest(dest_dir: &Path, name: &str, taken: &HashSet<PathBuf>) -> PathBuf {
+    let candidate = dest_dir.join(name);
+    if !candidate.exists() && !taken.contains(&candidate) {
+        return candidate;
+    }
+
+    let path = Path::new(name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string());
+
+    let mut counter = 1;
+    loop {
+        let numbered = match ext {
+            Some(ref e) => format!("{}_{}.{}", stem, counter, e),
+            None => format!("{}_{}", stem, counter),
+        };
+        let candidate = dest_dir.join(numbered);
+        if !candidate.exists() && !taken.contains(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// Copy items into `dest_dir`, returning per-file status.
+///
+/// Missing sources are skipped. Copying stops at the first file that would
+/// take the total past `max_bytes`: it and every file after it are skipped
+/// (even smaller ones), so the copy is a prefix of `items` and a broad query
+/// can't fill the disk.
+pub fn copy_attachments(
+    items: &[CopyItem],
+    dest_dir: &Path,
+    max_bytes: Option<u64>,
+    progress: &dyn Progress,
+) -> Result<Vec<CopyResult>> {
+    std::fs::create_dir_all(dest_dir)
+        .with_context(|| format!("Failed to create directory: {:?}", dest_dir))?;
+
+    let mut results = Vec::with_capacity(items.len());
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    let mut copied_bytes: u64 = 0;
+    let mut over_budget = false;
+
+    progress.start("Copying", Some(items.len() as u64));
+    for item in items {
+        progress.advance(1);
+        let source = item.source.to_string_lossy().to_string();
+        let skipped = |reason: &str| CopyResult {
+            source: source.clone(),
+            dest: None,
+            bytes: 0,
+            skipped_reason: Some(reason.to_string()),
+            converted: None,
+            duplicate_of: None,
+        };
+
+        if over_budget {
+            results.push(skipped("max_bytes_exceeded"));
+            continue;
+        }
+        let size = match std::fs::metadata(&item.source) {
+            Ok(meta) if meta.is_file() => meta.len(),
+            Ok(_) => {
+                results.push(skipped("not_a_file"));
+                continue;
+            }
+            Err(_) => {
+                results.push(skipped("missing"));
+                continue;
+            }
+        };
+
+        if max_bytes.is_some_and(|max| copied_bytes + size > max) {
+            over_budget = true;
+            results.push(skipped("max_bytes_exceeded"));
+            continue;
+        }
+
+        let name = readable_name(&item.date, &item.sender, &item.original_name);
+        let dest = unique_dest(dest_dir, &name, &taken);
+
+        match std::fs::copy(&item.source, &dest) {
+            Ok(bytes) => {
+                copied_bytes += bytes;
+                taken.insert(dest.clone());
+                results.push(CopyResult {
+                    source: source.clone(),
+                    dest: Some(dest.to_string_lossy().to_string()),
+                    bytes,
+                    skipped_reason: None,
+                    converted: None,
+                    duplicate_of: None,
+                });
+            }
+            Err(e) => results.push(skipped(&format!("copy_failed: {}", e))),
+        }
+    }
+    progress.finish();
+
+    Ok(results)
+}
+
+/// Conversion hook applied to copied files (e.g. HEIC → JPEG).
+pub trait FileConverter {
+    /// Convert `path` if applicable, returning the new file's path.
+    fn convert(&self, path: &Path) -> Result<Option<PathBuf>>;
+}
+
+/// HEIC → JPEG conversion via macOS `sips`.
+pub struct SipsConverter;
+
+impl FileConverter for SipsConverter {
+    fn convert(&self, path: &Path) -> Result<Option<PathBuf>> {
+        let is_heic = path
+            .extension()
+            .map(|e| e.eq_ignore_ascii_case("heic"))
+            .unwrap_or(false);
+        if !is_heic {
+            return Ok(None);
+        }
+
+        let out = path.with_extension("jpg");
+        let output = Command::new("sips")
+            .args(["-s", "format", "jpeg"])
+            .arg(path)
+            .arg("--out")
+            .arg(&out)
+            .output()
+            .context("Failed to run sips")?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "sips failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(Some(out))
+    }
+}
+
+/// Run the conversion hook on a copied file, recording the outcome.
+fn apply_heic_hook(result: &mut CopyResult, converter: &dyn FileConverter) {
+    let Some(dest) = result.dest.clone() else {
+        return;
+    };
+    match converter.convert(Path::new(&dest)) {
+        Ok(Some(converted)) => result.converted = Some(converted.to_string_lossy().to_string()),
+        Ok(None) => {}
+        Err(e) => result.converted = Some(format!("error: {}", e)),
+    }
+}
+
+/// Print copy results (and `--dedupe` totals) in JSON or text mode.
+fn print_copy_results(results: &[CopyResult], dedupe: Option<&DedupeSummary>, output: &OutputControls) -> Result<()> {
+    if output.json {
+        match dedupe {
+            Some(summary) => output.print_json(
+                &DedupedCopy {
+                    results: results.to_vec(),
+                    dedupe: summary.clone(),
+                },
+                false,
+            )?,
+            None => output.print_json(&results, false)?,
+        }
+        return Ok(());
+    }
+
+    let copied: Vec<&CopyResult> = results.iter().filter(|r| r.dest.is_some()).collect();
+    let total_bytes: u64 = copied.iter().map(|r| r.bytes).sum();
+    println!(
+        "Copied {} of {} attachments ({:.1}MB)",
+        copied.len(),
+        results.len(),
+        total_bytes as f64 / (1024.0 * 1024.0)
+    );
+    if let Some(summary) = dedupe {
+        println!(
+            "Skipped {} duplicates ({:.1}MB saved)",
+            summary.duplicates,
+            summary.bytes_saved as f64 / (1024.0 * 1024.0)
+        );
+    }
+    println!("{}", "-".repeat(60));
+    for r in results {
+        match (&r.dest, &r.skipped_reason) {
+            (Some(dest), _) => println!("{} -> {}", r.source, dest),
+            (None, Some(reason)) => println!("SKIPPED ({}): {}", reason, r.source),
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(source: &Path, date: &str, sender: &str, name: &str) -> CopyItem {
+        CopyItem {
+            source: source.to_path_buf(),
+            date: date.to_string(),
+            sender: sender.to_string(),
+            original_name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_readable_name() {
+        assert_eq!(
+            readable_name("20260110", "+14155551234", "IMG_0001.HEIC"),
+            "20260110_+14155551234_IMG_0001.HEIC"
+        );
+        assert_eq!(
+            readable_name("20260110", "Jane Doe", "my photo.jpg"),
+            "20260110_Jane_Doe_my_photo.jpg"
+        );
+        assert_eq!(readable_name("20260110", "", "../x"), "20260110_unknown_x");
+    }
+
+    #[test]
+    fn test_copy_with_collision_and_missing() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let a = src.path().join("a.jpg");
+        let b = src.path().join("b.jpg");
+        std::fs::write(&a, b"aaaa").unwrap();
+        std::fs::write(&b, b"bbbbbb").unwrap();
+
+        let items = vec![
+            item(&a, "20260110", "me", "photo.jpg"),
+            item(&b, "20260110", "me", "photo.jpg"),
+            item(&src.path().join("gone.jpg"), "20260110", "me", "gone.jpg"),
+        ];
+        let results = copy_attachments(&items, dest.path(), None, &progress::Silent).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].dest.as_deref().unwrap().ends_with("20260110_me_photo.jpg"));
+        assert!(results[1].dest.as_deref().unwrap().ends_with("20260110_me_photo_1.jpg"));
+        assert_eq!(results[1].bytes, 6);
+        assert_eq!(results[2].skipped_reason.as_deref(), Some("missing"));
+        assert!(dest.path().join("20260110_me_photo_1.jpg").exists());
+    }
+
+    #[test]
+    fn test_copy_respects_max_bytes() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let a = src.path().join("a.bin");
+        let b = src.path().join("b.bin");
+        let c = src.path().join("c.bin");
+        std::fs::write(&a, vec![0u8; 10]).unwrap();
+        std::fs::write(&b, vec![0u8; 10]).unwrap();
+        std::fs::write(&c, vec![0u8; 2]).unwrap();
+
+        let items = vec![
+            item(&a, "20260110", "x", "a.bin"),
+            item(&b, "20260111", "x", "b.bin"),
+            item(&c, "20260112", "x", "c.bin"),
+        ];
+        let results = copy_attachments(&items, dest.path(), Some(15), &progress::Silent).unwrap();
+
+        assert!(results[0].dest.is_some());
+        assert_eq!(results[1].skipped_reason.as_deref(), Some("max_bytes_exceeded"));
+        // c would fit, but copying stopped at b
+        assert_eq!(results[2].skipped_reason.as_deref(), Some("max_bytes_exceeded"));
+    }
+
+    fn mp4_box(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut out = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[test]
+    fn test_mp4_duration() {
+        let mut mvhd = vec![0u8; 4]; // version 0 + flags
+        mvhd.extend_from_slice(&[0u8; 8]); // creation + modification
+        mvhd.extend_from_slice(&1000u32.to_be_bytes()); // timescale
+        mvhd.extend_from_slice(&4500u32.to_be_bytes()); // duration
+        let mut file = mp4_box(b"ftyp", b"M4A \0\0\0\0");
+        file.extend(mp4_box(b"moov", &mp4_box(b"mvhd", &mvhd)));
+
+        assert_eq!(mp4_duration_secs(&file), Some(4.5));
+        assert_eq!(mp4_duration_secs(b"garbage"), None);
+
+        // A 64-bit box size past usize::MAX - pos ends the search instead of overflowing
+        let mut file = mp4_box(b"ftyp", b"M4A \0\0\0\0");
+        file.extend_from_slice(&1u32.to_be_bytes());
+        file.extend_from_slice(b"free");
+        file.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(find_mp4_box(&file, b"moov"), None);
+    }
+
+    fn caf_chunk(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut out = kind.to_vec();
+        out.extend_from_slice(&(payload.len() as i64).to_be_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[test]
+    fn test_caf_duration_from_pakt() {
+        let mut desc = 24000f64.to_bits().to_be_bytes().to_vec();
+        desc.extend_from_slice(b"opus");
+        desc.extend_from_slice(&0u32.to_be_bytes()); // flags
+        desc.extend_from_slice(&0u32.to_be_bytes()); // bytes per packet (variable)
+        desc.extend_from_slice(&480u32.to_be_bytes()); // frames per packet
+        desc.extend_from_slice(&1u32.to_be_bytes()); // channels
+        desc.extend_from_slice(&0u32.to_be_bytes()); // bits
+        let mut pakt = 10i64.to_be_bytes().to_vec(); // packets
+        pakt.extend_from_slice(&48000i64.to_be_bytes()); // valid frames
+
+        let mut file = b"caff\0\x01\0\0".to_vec();
+        file.extend(caf_chunk(b"desc", &desc));
+        file.extend(caf_chunk(b"pakt", &pakt));
+
+        assert_eq!(caf_duration_secs(&file), Some(2.0));
+    }
+
+    #[test]
+    fn test_audio_duration_missing_file_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(audio_duration_secs(&dir.path().join("nope.caf")), None);
+        let junk = dir.path().join("junk.m4a");
+        std::fs::write(&junk, b"not audio").unwrap();
+        assert_eq!(audio_duration_secs(&junk), None);
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/images")
+            .join(name)
+    }
+
+    #[test]
+    fn test_image_meta_reads_exif() {
+        let meta = image_meta(&fixture("exif_4x3.jpg"));
+        assert_eq!(
+            meta,
+            ImageMeta {
+                width: Some(4),
+                height: Some(3),
+                captured_at: Some("2026-01-10T09:30:00".to_string()),
+                camera_model: Some("FixtureCam 1".to_string()),
+                meta_error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_image_meta_without_exif_keeps_dimensions() {
+        let meta = image_meta(&fixture("plain_2x2.jpg"));
+        assert_eq!((meta.width, meta.height), (Some(2), Some(2)));
+        assert_eq!(meta.captured_at, None);
+        assert_eq!(meta.camera_model, None);
+        assert_eq!(meta.meta_error, None);
+    }
+
+    #[test]
+    fn test_image_meta_missing_and_garbage_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = image_meta(&dir.path().join("gone.heic"));
+        assert_eq!(missing.width, None);
+        assert_eq!(missing.meta_error.as_deref(), Some("missing"));
+
+        let junk = dir.path().join("junk.heic");
+        std::fs::write(&junk, b"not an image").unwrap();
+        let meta = image_meta(&junk);
+        assert_eq!(meta.width, None);
+        assert!(meta.meta_error.unwrap().starts_with("dimensions:"));
+    }
+
+    #[test]
+    fn test_fill_image_meta_only_touches_images() {
+        let row = |filename: Option<String>, mime: &str| Attachment {
+            filename,
+            mime_type: Some(mime.to_string()),
+            total_bytes: None,
+            transfer_name: None,
+            date: String::new(),
+            is_from_me: true,
+            sender_handle: None,
+            audio: None,
+            image: None,
+            content_hash: None,
+            duplicate_of: None,
+            date_cocoa: 0,
+        };
+        let path = fixture("exif_4x3.jpg").to_string_lossy().to_string();
+        let mut rows = vec![
+            row(Some(path.clone()), "image/jpeg"),
+            row(Some(path), "application/pdf"),
+            row(None, "image/heic"),
+        ];
+        fill_image_meta(&mut rows).unwrap();
+
+        assert_eq!(rows[0].image.as_ref().unwrap().width, Some(4));
+        assert_eq!(rows[1].image, None);
+        assert_eq!(rows[2].image.as_ref().unwrap().meta_error.as_deref(), Some("no_file_path"));
+    }
+
+    fn file_row(path: &Path, total_bytes: i64, name: &str, date_cocoa: i64) -> Attachment {
+        Attachment {
+            filename: Some(path.to_string_lossy().to_string()),
+            mime_type: Some("image/jpeg".to_string()),
+            total_bytes: Some(total_bytes),
+            transfer_name: Some(name.to_string()),
+            date: String::new(),
+            is_from_me: false,
+            sender_handle: None,
+            audio: None,
+            image: None,
+            content_hash: None,
+            duplicate_of: None,
+            date_cocoa,
+        }
+    }
+
+    #[test]
+    fn test_content_hash_whole_file_and_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.bin");
+        let b = dir.path().join("b.bin");
+        let c = dir.path().join("c.bin");
+        std::fs::write(&a, b"same bytes, same hash").unwrap();
+        std::fs::write(&b, b"same bytes, same hash").unwrap();
+        std::fs::write(&c, b"same bytes, other tail").unwrap();
+
+        let (len, hash) = content_hash(&a, None).unwrap();
+        assert_eq!(len, 21);
+        assert_eq!(hash, blake3::hash(b"same bytes, same hash").to_hex().to_string());
+        assert_eq!(content_hash(&b, None).unwrap().1, hash);
+        assert_ne!(content_hash(&c, None).unwrap().1, hash);
+
+        // Prefix mode: a shared head hashes alike, the length still differs
+        let (a_len, a_head) = content_hash(&a, Some(10)).unwrap();
+        let (c_len, c_head) = content_hash(&c, Some(10)).unwrap();
+        assert_eq!(a_head, c_head);
+        assert_ne!(a_len, c_len);
+        assert_eq!(content_hash(&dir.path().join("gone.bin"), None), None);
+        assert_eq!(content_hash(dir.path(), None), None);
+    }
+
+    #[test]
+    fn test_dedupe_groups_identical_files_and_falls_back_when_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.jpg");
+        let again = dir.path().join("again.jpg");
+        let other = dir.path().join("other.jpg");
+        std::fs::write(&first, vec![7u8; 1000]).unwrap();
+        std::fs::write(&again, vec![7u8; 1000]).unwrap();
+        std::fs::write(&other, vec![8u8; 1000]).unwrap();
+        let gone = dir.path().join("gone.jpg");
+
+        // Newest first, as queried
+        let mut rows = vec![
+            file_row(&gone, 500, "IMG_9.jpg", 6),
+            file_row(&again, 1000, "forwarded.jpg", 5),
+            file_row(&gone, 500, "IMG_9.jpg", 4),
+            file_row(&other, 1000, "other.jpg", 3),
+            file_row(&gone, 500, "IMG_8.jpg", 2),
+            file_row(&first, 1000, "first.jpg", 1),
+        ];
+        rows.push(Attachment { filename: None, total_bytes: None, ..file_row(&gone, 0, "x", 0) });
+        let hashing = progress::Recorder::default();
+        let keys = dedupe_keys(&mut rows, None, &hashing).unwrap();
+        let events = hashing.events();
+        assert_eq!(events.first(), Some(&progress::Event::Start("Hashing".to_string(), Some(7))));
+        assert_eq!(events.last(), Some(&progress::Event::Finish));
+        assert_eq!(hashing.processed(), 7);
+
+        assert!(matches!(keys[1], Some(DedupeKey::Content { len: 1000, .. })));
+        assert_eq!(keys[1], keys[5]);
+        assert_ne!(keys[3], keys[5]);
+        assert_eq!(
+            keys[0],
+            Some(DedupeKey::Recorded { total_bytes: 500, transfer_name: "IMG_9.jpg".to_string() })
+        );
+        assert_ne!(keys[0], keys[4]);
+        assert_eq!(keys[6], None);
+        assert_eq!(rows[1].content_hash, rows[5].content_hash);
+        assert_eq!(rows[0].content_hash, None);
+
+        let deduped = collapse_duplicates(rows, &keys);
+        let names = |rows: &[Attachment]| {
+            rows.iter().map(|a| a.transfer_name.clone().unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&deduped.attachments), ["IMG_9.jpg", "other.jpg", "IMG_8.jpg", "first.jpg", "x"]);
+        assert_eq!(names(&deduped.duplicates), ["IMG_9.jpg", "forwarded.jpg"]);
+        // The earliest copy is the original
+        assert_eq!(deduped.attachments[0].date_cocoa, 4);
+        assert_eq!(deduped.duplicates[1].duplicate_of.as_deref(), first.to_str());
+        assert_eq!(
+            deduped.dedupe,
+            DedupeSummary { unique: 5, duplicates: 2, bytes_saved: 1500 }
+        );
+    }
+
+    #[test]
+    fn test_copy_deduped_skips_later_copies() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let a = src.path().join("a.jpg");
+        let b = src.path().join("b.jpg");
+        let c = src.path().join("c.jpg");
+        std::fs::write(&a, b"identical").unwrap();
+        std::fs::write(&b, b"different").unwrap();
+        std::fs::write(&c, b"identical").unwrap();
+
+        let items = vec![
+            item(&a, "20260110", "me", "a.jpg"),
+            item(&b, "20260111", "me", "b.jpg"),
+            item(&c, "20260112", "me", "c.jpg"),
+        ];
+        let keys: Vec<Option<DedupeKey>> = [&a, &b, &c]
+            .iter()
+            .map(|p| content_hash(p, None).map(|(len, hash)| DedupeKey::Content { len, hash }))
+            .collect();
+        let copying = progress::Recorder::default();
+        let (results, summary) = copy_deduped(&items, &keys, dest.path(), None, &copying).unwrap();
+        // Only the originals are copied, so only they count
+        assert_eq!(
+            copying.events(),
+            [
+                progress::Event::Start("Copying".to_string(), Some(2)),
+                progress::Event::Advance(1),
+                progress::Event::Advance(1),
+                progress::Event::Finish,
+            ]
+        );
+
+        assert!(results[0].dest.is_some() && results[1].dest.is_some());
+        assert_eq!(results[2].dest, None);
+        assert_eq!(results[2].skipped_reason.as_deref(), Some("duplicate"));
+        assert_eq!(results[2].duplicate_of.as_deref(), a.to_str());
+        assert_eq!(summary, DedupeSummary { unique: 2, duplicates: 1, bytes_saved: 9 });
+        assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 2);
+    }
+
+    struct FakeConverter;
+
+    impl FileConverter for FakeConverter {
+        fn convert(&self, path: &Path) -> Result<Option<PathBuf>> {
+            Ok(Some(path.with_extension("jpg")))
+        }
+    }
+
+    #[test]
+    fn test_heic_hook_only_runs_on_copied_files() {
+        let mut copied = CopyResult {
+            source: "s".to_string(),
+            dest: Some("/tmp/x.HEIC".to_string()),
+            bytes: 1,
+            skipped_reason: None,
+            converted: None,
+            duplicate_of: None,
+        };
+        apply_heic_hook(&mut copied, &FakeConverter);
+        assert_eq!(copied.converted.as_deref(), Some("/tmp/x.jpg"));
+
+        let mut skipped = CopyResult {
+            dest: None,
+            skipped_reason: Some("missing".to_string()),
+            converted: None,
+            ..copied.clone()
+        };
+        apply_heic_hook(&mut skipped, &FakeConverter);
+        assert_eq!(skipped.converted, None);
+    }
+}
@@ -0,0 +1,36 @@
+//! Cache maintenance: `cache clear` for the extracted-text cache.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Typed CacheClear output for `schema`
+//! - 10/16/2026 - Initial cache clear
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::db::text_cache;
+use crate::output::OutputControls;
+
+/// `cache clear` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CacheClear {
+    pub cleared: bool,
+    pub path: String,
+}
+
+/// Delete the text cache (use after corruption or a parser change).
+pub fn clear(output: &OutputControls) -> Result<()> {
+    let path = text_cache::default_path();
+    let existed = text_cache::clear(&path)?;
+    if output.json {
+        output.print(&CacheClear {
+            cleared: existed,
+            path: path.display().to_string(),
+        })?;
+    } else if existed {
+        println!("Cleared text cache at {}", path.display());
+    } else {
+        println!("No text cache at {}", path.display());
+    }
+    Ok(())
+}
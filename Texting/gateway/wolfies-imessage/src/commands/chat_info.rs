@@ -0,0 +1,239 @@
+//! `chat-info`: who is in a conversation and what we know about it.
+//!
+//! The target resolves like a `quick` pin: an exact contact name (every chat
+//! the contact is in, one-to-one and groups), then a group by identifier or
+//! display name, then a fuzzy contact name or a literal phone/email. Each
+//! chat reports its service, participants with contact names, message span
+//! and count, attachment count, and pinned state where the schema has it.
+//! The daemon's `chat_info` method shares `build_report`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial chat-info command and daemon method
+
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::contacts::manager::{Contact, ContactsManager};
+use crate::db::connection::open_db;
+use crate::db::helpers::{self, ChatStats, HandleFilter};
+use crate::output::{self, OutputControls, TextRenderer, TextStyle, Tone};
+
+/// One participant handle and the contact it belongs to.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ChatParticipant {
+    pub handle: String,
+    pub name: Option<String>,
+}
+
+/// One conversation's header.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ChatInfo {
+    pub chat_identifier: String,
+    pub display_name: Option<String>,
+    /// iMessage, SMS, ...
+    pub service: Option<String>,
+    pub is_group: bool,
+    pub participants: Vec<ChatParticipant>,
+    pub message_count: i64,
+    pub first_message_date: Option<String>,
+    pub last_message_date: Option<String>,
+    pub attachment_count: i64,
+    /// Only on schemas with chat.is_pinned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<bool>,
+}
+
+/// `chat-info` output: the chats the target resolved to, most recent first.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ChatInfoReport {
+    /// The target as given
+    pub query: String,
+    /// Contact or group name it resolved to
+    pub name: Option<String>,
+    pub chats: Vec<ChatInfo>,
+}
+
+fn looks_like_handle(target: &str) -> bool {
+    target.contains('@') || target.chars().filter(|c| c.is_ascii_digit()).count() >= 10
+}
+
+/// Chats with any of a contact's handles.
+fn contact_chats(conn: &Connection, contact: &Contact) -> Result<Vec<i64>> {
+    let handles: Vec<String> = contact.all_handles().map(str::to_string).collect();
+    helpers::query_chats_with_handles(conn, &HandleFilter::resolve(conn, &handles)?)
+}
+
+/// Resolve `target` to a name and chat ROWIDs (see the module docs for the order).
+fn resolve(conn: &Connection, contacts: &ContactsManager, target: &str) -> Result<(Option<String>, Vec<i64>)> {
+    if let Some(contact) = contacts.find_by_name(target) {
+        return Ok((Some(contact.name.clone()), contact_chats(conn, contact)?));
+    }
+    if let Some(group) = helpers::resolve_group(conn, target)? {
+        return Ok((group.display_name, vec![group.rowid]));
+    }
+    if let Some(contact) = contacts.find_fuzzy(target) {
+        return Ok((Some(contact.name.clone()), contact_chats(conn, contact)?));
+    }
+    if looks_like_handle(target) {
+        let chats = helpers::query_chats_with_handles(conn, &HandleFilter::resolve(conn, &[target.to_string()])?)?;
+        return Ok((contacts.find_by_phone(target).map(|c| c.name.clone()), chats));
+    }
+    Err(anyhow!("'{}' matches no contact or group", target))
+}
+
+fn describe(stats: ChatStats, participants: Vec<String>, contacts: &ContactsManager) -> ChatInfo {
+    // One-to-one chats without participant rows: the identifier is the other party
+    let is_group = helpers::is_group_chat_identifier(Some(&stats.chat_identifier)) || participants.len() > 1;
+    let handles = if participants.is_empty() && !is_group { vec![stats.chat_identifier.clone()] } else { participants };
+    ChatInfo {
+        participants: handles
+            .into_iter()
+            .map(|handle| ChatParticipant { name: contacts.find_by_phone(&handle).map(|c| c.name.clone()), handle })
+            .collect(),
+        chat_identifier: stats.chat_identifier,
+        display_name: stats.display_name,
+        service: stats.service,
+        is_group,
+        message_count: stats.message_count,
+        first_message_date: stats.first_date,
+        last_message_date: stats.last_date,
+        attachment_count: stats.attachment_count,
+        pinned: stats.pinned,
+    }
+}
+
+/// Resolve `target` and describe each of its chats.
+pub fn build_report(conn: &Connection, contacts: &ContactsManager, target: &str) -> Result<ChatInfoReport> {
+    let (name, chat_ids) = resolve(conn, contacts, target)?;
+    let mut participants = helpers::query_chat_participants(conn, &chat_ids)?;
+    let chats = helpers::query_chat_stats(conn, &chat_ids)?
+        .into_iter()
+        .map(|stats| {
+            let handles = participants.remove(&stats.rowid).unwrap_or_default();
+            describe(stats, handles, contacts)
+        })
+        .collect();
+    Ok(ChatInfoReport { query: target.to_string(), name, chats })
+}
+
+impl TextRenderer for ChatInfoReport {
+    fn render(&self, style: &TextStyle) -> String {
+        let label = self.name.as_deref().unwrap_or(&self.query);
+        if self.chats.is_empty() {
+            return format!("No conversations with '{}'.", label);
+        }
+        let mut lines = vec![style.paint(Tone::Bold, &format!("{} ({} conversations)", label, self.chats.len()))];
+        for chat in &self.chats {
+            let title = chat.display_name.as_deref().unwrap_or(&chat.chat_identifier);
+            let kind = if chat.is_group { "group" } else { "1:1" };
+            let service = chat.service.as_deref().unwrap_or("unknown service");
+            let pinned = if chat.pinned == Some(true) { ", pinned" } else { "" };
+            lines.push(String::new());
+            lines.push(format!("{} ({}, {}{})", style.paint(Tone::Bold, title), kind, service, pinned));
+            let people: Vec<String> = chat
+                .participants
+                .iter()
+                .map(|p| match &p.name {
+                    Some(name) => format!("{} ({})", name, p.handle),
+                    None => p.handle.clone(),
+                })
+                .collect();
+            lines.push(format!("  participants: {}", people.join(", ")));
+            let span = match (&chat.first_message_date, &chat.last_message_date) {
+                (Some(first), Some(last)) => format!(", {} to {}", output::short_time(first), output::short_time(last)),
+                _ => String::new(),
+            };
+            lines.push(format!(
+                "  {} messages{}, {} attachments",
+                chat.message_count, span, chat.attachment_count
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Show the chats for a group or contact.
+pub fn chat_info(target: &str, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let conn = open_db()?;
+    output.show(&build_report(&conn, contacts, target)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_attachment, insert_chat, insert_handle, insert_message, DAY_NS};
+
+    fn contacts() -> ContactsManager {
+        ContactsManager::from_contacts(vec![Contact {
+            name: "Alice".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: vec!["alice@example.com".to_string()],
+        }])
+    }
+
+    #[test]
+    fn test_contact_lists_direct_and_group_chats() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let alice_email = insert_handle(&conn, "alice@example.com");
+        let bob = insert_handle(&conn, "+14155550002");
+        let direct = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_chat(&conn, "alice@example.com", None, &[]);
+        let group = insert_chat(&conn, "chat100200", Some("Trip"), &[alice_email, bob]);
+        insert_chat(&conn, "+14155550002", None, &[bob]);
+
+        insert_message(&conn, direct, alice, Some("hi"), DAY_NS, false, true);
+        let photo = insert_message(&conn, direct, 0, None, 2 * DAY_NS, true, true);
+        insert_attachment(&conn, photo, Some("image/jpeg"), "IMG_1.jpg");
+        insert_message(&conn, group, bob, Some("flights?"), 5 * DAY_NS, false, true);
+
+        let report = build_report(&conn, &contacts(), "alice").unwrap();
+        assert_eq!(report.name.as_deref(), Some("Alice"));
+        let ids: Vec<&str> = report.chats.iter().map(|c| c.chat_identifier.as_str()).collect();
+        // Most recent first; the message-less email chat last
+        assert_eq!(ids, ["chat100200", "+14155550001", "alice@example.com"]);
+
+        let trip = &report.chats[0];
+        assert!(trip.is_group);
+        assert_eq!(trip.display_name.as_deref(), Some("Trip"));
+        assert_eq!(trip.service.as_deref(), Some("iMessage"));
+        let names: Vec<_> = trip.participants.iter().map(|p| (p.handle.as_str(), p.name.as_deref())).collect();
+        // Sorted by handle id
+        assert_eq!(names, [("+14155550002", None), ("alice@example.com", Some("Alice"))]);
+
+        let one_to_one = &report.chats[1];
+        assert!(!one_to_one.is_group);
+        assert_eq!((one_to_one.message_count, one_to_one.attachment_count), (2, 1));
+        assert!(one_to_one.first_message_date < one_to_one.last_message_date);
+        assert!(one_to_one.pinned.is_none());
+
+        // No participant rows: the identifier stands in
+        assert_eq!(report.chats[2].participants[0].handle, "alice@example.com");
+        assert_eq!(report.chats[2].message_count, 0);
+        assert!(report.chats[2].first_message_date.is_none());
+    }
+
+    #[test]
+    fn test_group_and_unknown_targets() {
+        let conn = empty_db();
+        let bob = insert_handle(&conn, "+14155550002");
+        insert_chat(&conn, "chat100200", Some("Trip"), &[bob]);
+        conn.execute_batch("ALTER TABLE chat ADD COLUMN is_pinned INTEGER DEFAULT 1").unwrap();
+
+        let report = build_report(&conn, &contacts(), "trip").unwrap();
+        assert_eq!(report.name.as_deref(), Some("Trip"));
+        assert_eq!(report.chats.len(), 1);
+        assert_eq!(report.chats[0].pinned, Some(true));
+
+        let by_phone = build_report(&conn, &contacts(), "(415) 555-0002").unwrap();
+        assert_eq!(by_phone.chats.len(), 1);
+
+        let err = build_report(&conn, &contacts(), "Nobody").unwrap_err();
+        assert!(err.to_string().contains("matches no contact or group"));
+    }
+}
@@ -0,0 +1,295 @@
+//! `commitments`: candidate calendar items found in recent messages.
+//!
+//! A message is a candidate when it has a date/time expression
+//! (`date_expr`) and either commitment phrasing ("let's meet", "due",
+//! "call you at", "see you") or a time of day. Only items at or after the
+//! start of today are reported, soonest first. `--emit-ics` writes the
+//! high-confidence items as a VCALENDAR for import.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - JSON goes through OutputControls::print_json (--envelope)
+//! - 10/16/2026 - Skips attachment-only messages (placeholder, no text)
+//! - 10/16/2026 - Text-mode previews use output::preview (char-safe, one line)
+//! - 10/16/2026 - Initial commitments scan and --emit-ics export
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use crate::contacts::manager::ContactsManager;
+use crate::date_expr::{self, DateMatch};
+use crate::db::{connection::open_db, helpers, queries};
+use crate::output::{self, OutputControls};
+
+/// Recent messages scanned per run.
+pub const SCAN_LIMIT: u32 = 5000;
+
+/// Items at or above this confidence go into `--emit-ics`.
+pub const HIGH_CONFIDENCE: f64 = 0.7;
+
+/// Characters of message text used for an event SUMMARY.
+const SUMMARY_CHARS: usize = 60;
+
+/// One candidate commitment.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub(crate) struct Commitment {
+    /// Contact name, or the handle when unknown.
+    contact: String,
+    text: String,
+    /// Local time ("2026-10-17T15:00:00"), or a bare date when no time was given.
+    inferred_datetime: String,
+    confidence: f64,
+    guid: String,
+    #[serde(skip)]
+    when: DateMatch,
+}
+
+fn commitment_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"\b(let'?s meet|meet (?:up|you)|see (?:you|ya)|call (?:you|me)|talk (?:at|then)|due|deadline|appointment|reservation|pick (?:you )?up|dinner|lunch|breakfast|coffee|drinks|meeting|remind me|don'?t forget)\b",
+        )
+        .expect("valid commitment regex")
+    })
+}
+
+/// Confidence for a message with a recognized date, or None if it isn't a
+/// candidate: phrasing plus a time 0.9, phrasing alone 0.7, a time alone 0.5.
+pub fn confidence(text: &str, when: &DateMatch) -> Option<f64> {
+    let phrased = commitment_re().is_match(&text.to_lowercase().replace('’', "'"));
+    match (phrased, when.has_time) {
+        (true, true) => Some(0.9),
+        (true, false) => Some(0.7),
+        (false, true) => Some(0.5),
+        (false, false) => None,
+    }
+}
+
+/// Scan recent messages and print candidate commitments.
+pub fn commitments(
+    days: u32,
+    emit_ics: Option<&Path>,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    let conn = open_db()?;
+    output::applied("days", days);
+    let messages = helpers::query_recent_messages(&conn, queries::days_ago_cocoa(days), SCAN_LIMIT, None)?;
+    let today = Local::now().date_naive().and_time(chrono::NaiveTime::MIN);
+    let items = find_commitments(messages, contacts, today);
+
+    if let Some(path) = emit_ics {
+        let high: Vec<&Commitment> = items.iter().filter(|c| c.confidence >= HIGH_CONFIDENCE).collect();
+        std::fs::write(path, to_ics(&high, Utc::now()))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        eprintln!("Wrote {} event(s) to {}", high.len(), path.display());
+    }
+
+    if output.json {
+        output.print_json(&items, true)?;
+    } else {
+        if items.is_empty() {
+            println!("No commitments found.");
+            return Ok(());
+        }
+        println!("Commitments ({}):", items.len());
+        println!("{:-<60}", "");
+        for c in &items {
+            let preview = output::preview(&c.text, 80);
+            println!("[{}] {} ({:.1}): {}", c.inferred_datetime, c.contact, c.confidence, preview);
+        }
+    }
+    Ok(())
+}
+
+/// Candidates from `messages` dated at or after `not_before`, soonest first.
+pub(crate) fn find_commitments(
+    messages: Vec<helpers::RecentMessage>,
+    contacts: &ContactsManager,
+    not_before: NaiveDateTime,
+) -> Vec<Commitment> {
+    let mut items: Vec<Commitment> = messages
+        .into_iter()
+        .filter_map(|msg| {
+            let text = msg.text?;
+            let sent = local_time(msg.date_cocoa)?;
+            let when = date_expr::recognize(&text, sent)?;
+            let confidence = confidence(&text, &when)?;
+            if when.datetime < not_before {
+                return None;
+            }
+            let contact = contacts
+                .find_by_phone(&msg.phone)
+                .map(|c| c.name.clone())
+                .unwrap_or(msg.phone);
+            Some(Commitment {
+                contact,
+                text,
+                inferred_datetime: format_when(&when),
+                confidence,
+                guid: msg.guid,
+                when,
+            })
+        })
+        .collect();
+    items.sort_by_key(|c| c.when.datetime);
+    items
+}
+
+/// A message's Cocoa timestamp as local wall-clock time.
+fn local_time(date_cocoa: i64) -> Option<NaiveDateTime> {
+    Local
+        .timestamp_opt(queries::cocoa_to_unix(date_cocoa), 0)
+        .single()
+        .map(|dt| dt.naive_local())
+}
+
+fn format_when(when: &DateMatch) -> String {
+    if when.has_time {
+        when.datetime.format("%Y-%m-%dT%H:%M:%S").to_string()
+    } else {
+        when.date().format("%Y-%m-%d").to_string()
+    }
+}
+
+// ============================================================================
+// iCalendar export
+// ============================================================================
+
+/// A VCALENDAR with one VEVENT per item. Timed events are one hour in
+/// floating (local) time; date-only items are all-day events.
+pub(crate) fn to_ics(items: &[&Commitment], stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//wolfies-imessage//commitments//EN".to_string(),
+    ];
+    for item in items {
+        let when = item.when;
+        let summary: String = item.text.chars().take(SUMMARY_CHARS).collect();
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@wolfies-imessage", item.guid));
+        lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        if when.has_time {
+            lines.push(format!("DTSTART:{}", when.datetime.format("%Y%m%dT%H%M%S")));
+            lines.push("DURATION:PT1H".to_string());
+        } else {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", when.date().format("%Y%m%d")));
+        }
+        lines.push(format!("SUMMARY:{}", ics_escape(&format!("{}: {}", item.contact, summary))));
+        lines.push(format!("DESCRIPTION:{}", ics_escape(&item.text)));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold_line(l)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// Escape a TEXT value (RFC 5545 3.3.11).
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets without splitting a UTF-8 character.
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn message(text: &str, sent: NaiveDateTime, guid: &str) -> helpers::RecentMessage {
+        let unix = Local.from_local_datetime(&sent).single().unwrap().timestamp();
+        helpers::RecentMessage {
+            rowid: 1,
+            guid: guid.to_string(),
+            text: Some(text.to_string()),
+            date: String::new(),
+            date_cocoa: queries::unix_to_cocoa(unix),
+            is_from_me: false,
+            phone: "+14155550001".to_string(),
+            placeholder: None,
+        }
+    }
+
+    #[test]
+    fn test_find_commitments_scores_and_filters() {
+        let sent = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let messages = vec![
+            message("Let's meet tomorrow at 3", sent, "a"),
+            message("report due friday", sent, "b"),
+            message("ping me at 4:30", sent, "c"),
+            message("tomorrow is my birthday", sent, "d"),
+            message("see you yesterday", sent, "e"),
+            message("lunch on 10/1?", sent, "f"),
+        ];
+        let items = find_commitments(messages, &ContactsManager::empty(), sent.date().and_hms_opt(0, 0, 0).unwrap());
+        // "10/1" is two weeks before the message, so it's past (not next October)
+        let got: Vec<(&str, &str, f64)> =
+            items.iter().map(|c| (c.guid.as_str(), c.inferred_datetime.as_str(), c.confidence)).collect();
+        assert_eq!(
+            got,
+            vec![
+                ("b", "2026-10-16", 0.7),
+                ("c", "2026-10-16T16:30:00", 0.5),
+                ("a", "2026-10-17T15:00:00", 0.9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ics_output() {
+        let sent = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let items = find_commitments(
+            vec![
+                message("Dinner tomorrow at 7, bring wine; ok?", sent, "g1"),
+                message("rent due 10/20", sent, "g2"),
+            ],
+            &ContactsManager::empty(),
+            sent,
+        );
+        let refs: Vec<&Commitment> = items.iter().collect();
+        let stamp = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let ics = to_ics(&refs, stamp);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("UID:g1@wolfies-imessage\r\n"));
+        assert!(ics.contains("DTSTAMP:20261016T120000Z\r\n"));
+        assert!(ics.contains("DTSTART:20261017T190000\r\nDURATION:PT1H\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261020\r\n"));
+        assert!(ics.contains("DESCRIPTION:Dinner tomorrow at 7\\, bring wine\\; ok?\r\n"));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("SUMMARY:{}", "é".repeat(50));
+        let folded = fold_line(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold_line("short"), "short");
+    }
+}
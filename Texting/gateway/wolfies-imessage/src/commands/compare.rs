@@ -0,0 +1,389 @@
+//! Compare command: messages present in one chat.db but not another.
+//!
+//! The other database (e.g. a Time Machine copy) is attached read-only as
+//! `other` and messages are matched by guid. Per-conversation counts are
+//! aggregated inside SQLite. `--show-messages` rows are collected in the
+//! same read snapshot and written once it has ended.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - --show-messages rows are collected inside the snapshot and written after it ends (no output while it is open)
+//! - 10/16/2026 - Listed messages take text and attachment placeholders from db::message_body (no local "not available" string)
+//! - 10/16/2026 - Counts and listed rows come from one snapshot of both databases (connection::read_snapshot)
+//! - 10/16/2026 - Progress on stderr: one step per direction while counting, then each listed message with --show-messages (stdout redirected)
+//! - 10/16/2026 - Initial compare (guid set difference per conversation, streamed rows)
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::message_body::{display_text, message_body};
+use crate::db::{connection, helpers, queries};
+use crate::output::{self, OutputControls};
+use crate::progress::{self, Progress};
+
+/// Schema name the other database is attached under.
+pub const OTHER_SCHEMA: &str = "other";
+
+/// Which side of the difference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// In the primary (--db-path) database, missing from the other.
+    OnlyInPrimary,
+    /// In the other database, missing from the primary.
+    OnlyInOther,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 2] = [Direction::OnlyInPrimary, Direction::OnlyInOther];
+
+    /// JSON key for this side.
+    pub fn key(self) -> &'static str {
+        match self {
+            Direction::OnlyInPrimary => "only_in_primary",
+            Direction::OnlyInOther => "only_in_other",
+        }
+    }
+
+    /// Fill a COMPARE_* query's `{a}` (source) and `{b}` (missing from) schemas.
+    fn sql(self, template: &str) -> String {
+        let (a, b) = match self {
+            Direction::OnlyInPrimary => ("main", OTHER_SCHEMA),
+            Direction::OnlyInOther => (OTHER_SCHEMA, "main"),
+        };
+        template.replace("{a}", a).replace("{b}", b)
+    }
+}
+
+/// Messages missing from the other side, for one conversation.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConversationDiff {
+    /// None for messages not joined to any chat.
+    pub chat_id: Option<String>,
+    pub display_name: Option<String>,
+    pub contact_name: Option<String>,
+    pub message_count: i64,
+    pub first_date: String,
+    pub last_date: String,
+}
+
+/// A message present on only one side.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiffMessage {
+    pub rowid: i64,
+    pub guid: String,
+    pub text: String,
+    /// Attachment placeholder ("[photo]", "[file: name.pdf]", ...); `text` is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    pub date: String,
+    pub is_from_me: bool,
+    pub handle: Option<String>,
+    pub chat_id: Option<String>,
+}
+
+/// One direction of the comparison.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DirectionDiff {
+    pub message_count: i64,
+    /// Busiest conversation first.
+    pub conversations: Vec<ConversationDiff>,
+    /// Oldest first (only with --show-messages).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub messages: Option<Vec<DiffMessage>>,
+}
+
+/// `compare` output, grouped by direction.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CompareReport {
+    pub primary: String,
+    pub other: String,
+    pub days: Option<u32>,
+    pub only_in_primary: DirectionDiff,
+    pub only_in_other: DirectionDiff,
+}
+
+/// Per-conversation counts of messages on `direction`'s side only.
+pub fn conversation_diffs(
+    conn: &rusqlite::Connection,
+    direction: Direction,
+    cutoff_cocoa: i64,
+    contacts: &ContactsManager,
+) -> Result<Vec<ConversationDiff>> {
+    let mut stmt = conn.prepare(&direction.sql(queries::COMPARE_CONVERSATIONS))?;
+    let rows = stmt.query_map([cutoff_cocoa], |row| {
+        let chat_id: Option<String> = row.get(0)?;
+        Ok(ConversationDiff {
+            contact_name: chat_id
+                .as_deref()
+                .filter(|id| !helpers::is_group_chat_identifier(Some(id)))
+                .and_then(|id| contacts.find_by_phone(id))
+                .map(|c| c.name.clone()),
+            chat_id,
+            display_name: row.get::<_, Option<String>>(1)?.filter(|n| !n.is_empty()),
+            message_count: row.get(2)?,
+            first_date: helpers::cocoa_to_iso(row.get(3)?),
+            last_date: helpers::cocoa_to_iso(row.get(4)?),
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Stream the messages on `direction`'s side only, oldest first.
+pub fn for_each_diff_message(
+    conn: &rusqlite::Connection,
+    direction: Direction,
+    cutoff_cocoa: i64,
+    mut f: impl FnMut(DiffMessage) -> Result<()>,
+) -> Result<()> {
+    let mut stmt = conn.prepare(&direction.sql(queries::COMPARE_MESSAGES))?;
+    let mut rows = stmt.query([cutoff_cocoa])?;
+    while let Some(row) = rows.next()? {
+        let guid: Option<String> = row.get(1)?;
+        let text: Option<String> = row.get(2)?;
+        let blob: Option<Vec<u8>> = row.get(3)?;
+        let hint: Option<String> = row.get(8)?;
+        let (text, placeholder) = message_body(guid.as_deref(), text, blob.as_deref(), hint.as_deref()).into_parts();
+        f(DiffMessage {
+            rowid: row.get(0)?,
+            text,
+            placeholder,
+            guid: guid.unwrap_or_default(),
+            date: helpers::cocoa_to_iso(row.get(4)?),
+            is_from_me: row.get(5)?,
+            handle: row.get(6)?,
+            chat_id: row.get(7)?,
+        })?;
+    }
+    Ok(())
+}
+
+/// Both directions' per-conversation counts (no message rows).
+///
+/// Each direction is one aggregate query with no row count until it's done,
+/// so progress is a step per direction.
+pub fn build_report(
+    conn: &rusqlite::Connection,
+    primary: &Path,
+    other: &Path,
+    days: Option<u32>,
+    contacts: &ContactsManager,
+    progress: &dyn Progress,
+) -> Result<CompareReport> {
+    let cutoff = days.map(queries::days_ago_cocoa).unwrap_or(0);
+    let side = |direction| -> Result<DirectionDiff> {
+        let conversations = conversation_diffs(conn, direction, cutoff, contacts)?;
+        progress.advance(1);
+        Ok(DirectionDiff {
+            message_count: conversations.iter().map(|c| c.message_count).sum(),
+            conversations,
+            messages: None,
+        })
+    };
+    progress.start("Comparing", Some(Direction::ALL.len() as u64));
+    let report = CompareReport {
+        primary: primary.to_string_lossy().into_owned(),
+        other: other.to_string_lossy().into_owned(),
+        days,
+        only_in_primary: side(Direction::OnlyInPrimary)?,
+        only_in_other: side(Direction::OnlyInOther)?,
+    };
+    progress.finish();
+    Ok(report)
+}
+
+/// Compare the primary database with `other` by message guid.
+pub fn compare(
+    other: &Path,
+    days: Option<u32>,
+    show_messages: bool,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let primary = crate::config::active().db_path.value.clone();
+    let conn = connection::open_db()?;
+    connection::attach_read_only(&conn, other, OTHER_SCHEMA)?;
+    // Both databases from one snapshot, so the counts match the listed rows.
+    // The rows are collected inside it and written after it ends, so a slow
+    // reader of stdout can't hold back chat.db's WAL checkpoints.
+    let progress = progress::for_output(output);
+    let report = connection::read_snapshot(&conn, |conn| {
+        let mut report = build_report(conn, &primary, other, days, contacts, progress.as_ref())?;
+        if show_messages {
+            let cutoff = days.map(queries::days_ago_cocoa).unwrap_or(0);
+            let total = report.only_in_primary.message_count + report.only_in_other.message_count;
+            progress.start("Listing", Some(total.max(0) as u64));
+            for direction in Direction::ALL {
+                let mut messages = Vec::new();
+                for_each_diff_message(conn, direction, cutoff, |m| {
+                    messages.push(m);
+                    progress.advance(1);
+                    Ok(())
+                })?;
+                side_of_mut(&mut report, direction).messages = Some(messages);
+            }
+            progress.finish();
+        }
+        Ok(report)
+    })?;
+
+    if output.json {
+        output.print(&report)?;
+        return Ok(());
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    writeln!(out, "Comparing {} with {}", report.primary, report.other)?;
+    for direction in Direction::ALL {
+        let side = side_of(&report, direction);
+        let label = match direction {
+            Direction::OnlyInPrimary => "Only in primary",
+            Direction::OnlyInOther => "Only in other",
+        };
+        writeln!(out)?;
+        writeln!(out, "{} ({} messages):", label, side.message_count)?;
+        writeln!(out, "{}", "-".repeat(60))?;
+        for c in &side.conversations {
+            let name = c
+                .contact_name
+                .as_deref()
+                .or(c.display_name.as_deref())
+                .or(c.chat_id.as_deref())
+                .unwrap_or("(no conversation)");
+            writeln!(out, "  {}: {} ({} .. {})", name, c.message_count, c.first_date, c.last_date)?;
+        }
+        for m in side.messages.iter().flatten() {
+            let sender = if m.is_from_me { "Me" } else { m.handle.as_deref().unwrap_or("Unknown") };
+            let preview = output::preview(display_text(&m.text, m.placeholder.as_deref()), 80);
+            writeln!(out, "    [{}] {}: {}", m.date, sender, preview)?;
+        }
+    }
+    out.flush().context("Failed to write output")?;
+
+    Ok(())
+}
+
+fn side_of(report: &CompareReport, direction: Direction) -> &DirectionDiff {
+    match direction {
+        Direction::OnlyInPrimary => &report.only_in_primary,
+        Direction::OnlyInOther => &report.only_in_other,
+    }
+}
+
+fn side_of_mut(report: &mut CompareReport, direction: Direction) -> &mut DirectionDiff {
+    match direction {
+        Direction::OnlyInPrimary => &mut report.only_in_primary,
+        Direction::OnlyInOther => &mut report.only_in_other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{insert_attachment, insert_chat, insert_handle, insert_message, SCHEMA, DAY_NS};
+    use rusqlite::Connection;
+
+    fn fixture_file(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_compare_by_guid_both_directions() {
+        let dir = tempfile::tempdir().unwrap();
+        let live_path = dir.path().join("chat.db");
+        let backup_path = dir.path().join("backup.db");
+        let base = 700 * DAY_NS;
+
+        // Backup: msg-1..msg-3 in one chat
+        let backup = fixture_file(&backup_path);
+        let alice = insert_handle(&backup, "+14155550001");
+        let chat = insert_chat(&backup, "+14155550001", None, &[alice]);
+        for i in 0..3 {
+            insert_message(&backup, chat, alice, Some("old"), base + i, false, true);
+        }
+
+        // Live: msg-1 and msg-2 survive, msg-3 was deleted, msg-4/msg-5 are new in a group
+        let live = fixture_file(&live_path);
+        let alice = insert_handle(&live, "+14155550001");
+        let chat = insert_chat(&live, "+14155550001", None, &[alice]);
+        let group = insert_chat(&live, "chat42", Some("Ski Trip"), &[alice]);
+        for i in 0..3 {
+            insert_message(&live, chat, alice, Some("old"), base + i, false, true);
+        }
+        live.execute("DELETE FROM message WHERE guid = 'msg-3'", []).unwrap();
+        insert_message(&live, group, alice, Some("new one"), base + 10, false, true);
+        insert_message(&live, group, 0, Some("new two"), base + 11, true, true);
+        drop(live);
+        drop(backup);
+
+        let conn = connection::open_read_only(&live_path).unwrap();
+        connection::attach_read_only(&conn, &backup_path, OTHER_SCHEMA).unwrap();
+        let contacts = ContactsManager::empty();
+        let steps = progress::Recorder::default();
+        let report = build_report(&conn, &live_path, &backup_path, None, &contacts, &steps).unwrap();
+        assert_eq!(steps.events().first(), Some(&progress::Event::Start("Comparing".to_string(), Some(2))));
+        assert_eq!(steps.processed(), 2);
+
+        assert_eq!(report.only_in_primary.message_count, 2);
+        assert_eq!(report.only_in_primary.conversations.len(), 1);
+        assert_eq!(report.only_in_primary.conversations[0].display_name.as_deref(), Some("Ski Trip"));
+        assert_eq!(report.only_in_other.message_count, 1);
+        assert_eq!(report.only_in_other.conversations[0].chat_id.as_deref(), Some("+14155550001"));
+
+        let mut guids = Vec::new();
+        for_each_diff_message(&conn, Direction::OnlyInOther, 0, |m| {
+            guids.push(m.guid);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(guids, ["msg-3"]);
+
+        let mut texts = Vec::new();
+        for_each_diff_message(&conn, Direction::OnlyInPrimary, 0, |m| {
+            texts.push(m.text);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(texts, ["new one", "new two"]);
+
+        // A cutoff after the deleted message leaves only the new ones
+        let recent = conversation_diffs(&conn, Direction::OnlyInOther, base + 5, &contacts).unwrap();
+        assert!(recent.is_empty());
+    }
+
+    #[test]
+    fn test_diff_messages_use_shared_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let live_path = dir.path().join("chat.db");
+        let backup_path = dir.path().join("backup.db");
+        drop(fixture_file(&backup_path));
+        let live = fixture_file(&live_path);
+        let alice = insert_handle(&live, "+14155550001");
+        let chat = insert_chat(&live, "+14155550001", None, &[alice]);
+        let photo = insert_message(&live, chat, alice, None, 700 * DAY_NS, false, true);
+        insert_attachment(&live, photo, Some("image/heic"), "IMG_0001.HEIC");
+        insert_message(&live, chat, alice, None, 700 * DAY_NS + 1, false, true);
+        drop(live);
+
+        let conn = connection::open_read_only(&live_path).unwrap();
+        connection::attach_read_only(&conn, &backup_path, OTHER_SCHEMA).unwrap();
+        let mut bodies = Vec::new();
+        for_each_diff_message(&conn, Direction::OnlyInPrimary, 0, |m| {
+            bodies.push((m.text, m.placeholder));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            bodies,
+            [
+                (String::new(), Some("[photo]".to_string())),
+                (crate::db::message_body::UNAVAILABLE.to_string(), None),
+            ]
+        );
+    }
+}
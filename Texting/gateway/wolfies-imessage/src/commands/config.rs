@@ -0,0 +1,66 @@
+//! `config show`: the effective configuration and where each value came from.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Typed ConfigShow output for `schema`
+//! - 10/16/2026 - Initial config show
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::config::{Settings, Source};
+use crate::output::OutputControls;
+
+/// `config show` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfigShow {
+    pub config_path: String,
+    pub config_found: bool,
+    pub settings: BTreeMap<String, ConfigEntry>,
+}
+
+/// One effective setting and its provenance.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfigEntry {
+    pub value: Value,
+    pub source: Source,
+}
+
+/// Print every setting with its provenance (default/config/env/flag).
+pub fn show(settings: &Settings, output: &OutputControls) -> Result<()> {
+    let entries = settings.entries();
+
+    if output.json {
+        let values = entries
+            .iter()
+            .map(|(key, value, source)| {
+                let entry = ConfigEntry {
+                    value: value.clone(),
+                    source: *source,
+                };
+                (key.to_string(), entry)
+            })
+            .collect();
+        output.print(&ConfigShow {
+            config_path: settings.config_path.display().to_string(),
+            config_found: settings.config_found,
+            settings: values,
+        })?;
+        return Ok(());
+    }
+
+    let status = if settings.config_found { "" } else { " (not found)" };
+    println!("Config file: {}{}", settings.config_path.display(), status);
+    println!("{}", "-".repeat(60));
+    for (key, value, source) in &entries {
+        let shown = match value {
+            Value::String(s) => s.clone(),
+            Value::Null => "(unset)".to_string(),
+            other => other.to_string(),
+        };
+        println!("{:<24} {} ({})", key, shown, source.as_str());
+    }
+    Ok(())
+}
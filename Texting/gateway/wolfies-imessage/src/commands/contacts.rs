@@ -1,23 +1,35 @@
-//! Contact commands: contacts, add-contact.
+//! Contact commands: contacts, add-contact, contacts history/diff/restore/dedupe.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - stats uses the shared ContactsManager and counts unread like the unread commands (read position, mutes)
+//! - 10/16/2026 - add-contact refuses colliding names (--merge adds the phone to the existing contact); added dedupe
+//! - 10/16/2026 - list/stats/diff load contacts via load_default_or_empty (warn on corrupt file)
+//! - 10/16/2026 - add-contact saves atomically with a backup; added history, diff, restore
+//! - 10/16/2026 - Added contacts --stats (per-contact message stats, --days, --sort)
 //! - 01/10/2026 - Implemented list and add with JSON file I/O (Claude)
 //! - 01/10/2026 - Initial stub implementation (Claude)
 
+use crate::contacts::backup::{self, ContactsDiff};
+use crate::contacts::merge::{self, DuplicateGroup};
 use crate::contacts::manager::{default_contacts_path, Contact, ContactsManager};
+use crate::db::{connection::open_db, helpers::{self, HandleStats}, queries};
+use crate::mutes::MuteFilter;
 use crate::output::OutputControls;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 
 /// List all contacts.
 pub fn list(output: &OutputControls) -> Result<()> {
-    let contacts = ContactsManager::load_default().unwrap_or_else(|_| ContactsManager::empty());
+    let contacts = ContactsManager::load_default_or_empty();
 
     let all = contacts.all();
 
     if output.json {
         // Convert slice to Vec for serialization
         let contacts_vec: Vec<&Contact> = all.iter().collect();
-        output.print(&contacts_vec);
+        output.print(&contacts_vec)?;
     } else {
         if all.is_empty() {
             println!("No contacts found.");
@@ -40,8 +52,251 @@ pub fn list(output: &OutputControls) -> Result<()> {
     Ok(())
 }
 
+/// List contacts.json backups, newest first.
+pub fn history(output: &OutputControls) -> Result<()> {
+    let dir = backup::backup_dir();
+    let backups = backup::list_backups(&dir)?;
+
+    if output.json {
+        output.print(&backups)?;
+    } else {
+        if backups.is_empty() {
+            println!("No backups in {}.", dir.display());
+            return Ok(());
+        }
+        println!("Backups ({}):", backups.len());
+        println!("{}", "-".repeat(50));
+        for b in &backups {
+            let count = b.contact_count.map(|n| format!("{} contacts", n)).unwrap_or_else(|| "unreadable".to_string());
+            println!("{}  ({})", b.name, count);
+        }
+    }
+    Ok(())
+}
+
+/// Show what changed between a backup and the current contacts.json.
+pub fn diff(backup_name: &str, output: &OutputControls) -> Result<()> {
+    let source = backup::resolve_backup(&backup::backup_dir(), backup_name)?;
+    let old = ContactsManager::load(&source)?;
+    let current = ContactsManager::load_default_or_empty();
+    let diff = backup::diff_contacts(old.all(), current.all());
+
+    if output.json {
+        output.print(&diff)?;
+    } else {
+        print_diff(&diff);
+    }
+    Ok(())
+}
+
+fn print_diff(diff: &ContactsDiff) {
+    if diff.is_empty() {
+        println!("No differences.");
+        return;
+    }
+    for c in &diff.added {
+        println!("+ {}: {}", c.name, c.phone);
+    }
+    for c in &diff.removed {
+        println!("- {}: {}", c.name, c.phone);
+    }
+    for change in &diff.changed {
+        println!("~ {}: {} ({})", change.after.name, change.after.phone, change.fields.join(", "));
+    }
+}
+
+/// Replace contacts.json with a backup (the current file is backed up first).
+pub fn restore(backup_name: &str, output: &OutputControls) -> Result<()> {
+    let dir = backup::backup_dir();
+    let source = backup::resolve_backup(&dir, backup_name)?;
+    let result = backup::restore(&default_contacts_path(), &source, &dir, backup::BACKUP_KEEP)?;
+
+    if output.json {
+        output.print(&result)?;
+    } else {
+        println!("Restored {} contacts from {}", result.contact_count, result.restored_from);
+        if let Some(ref previous) = result.previous_backup {
+            println!("Previous file saved as {}", previous);
+        }
+    }
+    Ok(())
+}
+
+/// Sort order for `contacts --stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactSort {
+    /// Most recently messaged first; contacts with no messages last.
+    LastMessage,
+    /// Most messages first.
+    Count,
+    /// Alphabetical by name.
+    Name,
+}
+
+impl ContactSort {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "last_message" => Ok(Self::LastMessage),
+            "count" => Ok(Self::Count),
+            "name" => Ok(Self::Name),
+            other => Err(anyhow!("Unknown sort '{}' (expected last_message, count, or name)", other)),
+        }
+    }
+}
+
+/// A contacts.json entry with its message stats over the window.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ContactStats {
+    #[serde(flatten)]
+    pub contact: Contact,
+    pub message_count: i64,
+    pub sent: i64,
+    pub received: i64,
+    pub last_message_date: Option<String>,
+    pub has_unread: bool,
+    #[serde(skip)]
+    last_date_cocoa: Option<i64>,
+}
+
+/// List contacts with message stats from one grouped query over the window.
+/// `has_unread` agrees with `unread`: muted conversations don't count.
+pub fn stats(days: u32, sort: ContactSort, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let conn = open_db()?;
+    let mutes = MuteFilter::load(&conn, false)?;
+    let by_handle = helpers::query_handle_stats(&conn, queries::days_ago_cocoa(days), mutes.as_ref())?;
+    let rows = contact_stats(contacts.all(), &by_handle, sort);
+
+    if output.json {
+        output.print(&rows)?;
+    } else {
+        if rows.is_empty() {
+            println!("No contacts found.");
+            return Ok(());
+        }
+
+        println!("Contacts ({}, last {} days):", rows.len(), days);
+        println!("{}", "-".repeat(70));
+        for row in &rows {
+            let last = row
+                .last_message_date
+                .as_deref()
+                .map(|d| d.get(..10).unwrap_or(d))
+                .unwrap_or("never");
+            let unread = if row.has_unread { " [unread]" } else { "" };
+            println!(
+                "{}: {} msgs ({} sent / {} received), last {}{}",
+                row.contact.name, row.message_count, row.sent, row.received, last, unread
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Join contacts to the per-handle stats map. Every handle of a contact
+/// counts once; contacts without messages get zeros.
+pub fn contact_stats(
+    contacts: &[Contact],
+    by_handle: &HashMap<String, HandleStats>,
+    sort: ContactSort,
+) -> Vec<ContactStats> {
+    let mut rows: Vec<ContactStats> = contacts
+        .iter()
+        .map(|contact| {
+            let keys: HashSet<String> = contact.all_handles().map(helpers::handle_key).collect();
+            let mut total = HandleStats::default();
+            for stats in keys.iter().filter_map(|k| by_handle.get(k)) {
+                total.absorb(stats);
+            }
+            ContactStats {
+                contact: contact.clone(),
+                message_count: total.message_count,
+                sent: total.sent,
+                received: total.received,
+                last_message_date: total.last_date_cocoa.map(helpers::cocoa_to_iso),
+                has_unread: total.unread > 0,
+                last_date_cocoa: total.last_date_cocoa,
+            }
+        })
+        .collect();
+
+    match sort {
+        ContactSort::LastMessage => rows.sort_by_key(|r| std::cmp::Reverse(r.last_date_cocoa)),
+        ContactSort::Count => rows.sort_by_key(|r| std::cmp::Reverse(r.message_count)),
+        ContactSort::Name => rows.sort_by_key(|r| r.contact.name.to_lowercase()),
+    }
+    rows
+}
+
+/// `contacts dedupe` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DedupeReport {
+    pub groups: Vec<DuplicateGroup>,
+    /// Whether the merges were written to contacts.json
+    pub applied: bool,
+    pub contacts_before: usize,
+    pub contacts_after: usize,
+}
+
+/// Find duplicate contacts and, with `apply`, merge them in contacts.json.
+pub fn dedupe(apply: bool, output: &OutputControls) -> Result<()> {
+    let path = default_contacts_path();
+    let (manager, warnings) = ContactsManager::load_with_warnings(&path)?;
+    if apply && !warnings.is_empty() {
+        // Saving would silently drop the entries that failed to parse
+        return Err(anyhow!(
+            "contacts.json has {} malformed entries ({}); fix them before --apply",
+            warnings.len(),
+            warnings.join("; ")
+        ));
+    }
+    let contacts = manager.all();
+    let groups = merge::find_duplicates(contacts);
+    let merged = merge::apply_merges(contacts, &groups);
+    let applied = apply && !groups.is_empty();
+    if applied {
+        backup::save_contacts(&path, &merged, &backup::backup_dir(), backup::BACKUP_KEEP)?;
+    }
+    let report = DedupeReport {
+        applied,
+        contacts_before: contacts.len(),
+        contacts_after: merged.len(),
+        groups,
+    };
+
+    if output.json {
+        output.print(&report)?;
+        return Ok(());
+    }
+    if report.groups.is_empty() {
+        println!("No duplicate contacts.");
+        return Ok(());
+    }
+    println!("Duplicate contacts ({} groups):", report.groups.len());
+    println!("{:-<60}", "");
+    for group in &report.groups {
+        println!("{} ({})", group.keep.name, group.keep.phone);
+        for d in &group.duplicates {
+            println!("  + {} ({})", d.name, d.phone);
+        }
+        println!("  {}", group.reasons.join("; "));
+    }
+    if report.applied {
+        println!(
+            "Merged: {} -> {} contacts (previous file backed up)",
+            report.contacts_before, report.contacts_after
+        );
+    } else {
+        println!("Run `contacts dedupe --apply` to merge them.");
+    }
+    Ok(())
+}
+
 /// Add a new contact.
-pub fn add(name: &str, phone: &str, relationship: &str, notes: Option<&str>) -> Result<()> {
+///
+/// A name that collides with an existing contact (exactly or fuzzily) is an
+/// error; with `merge`, the phone is added to that contact's handles instead.
+pub fn add(name: &str, phone: &str, relationship: &str, notes: Option<&str>, merge: bool) -> Result<()> {
     let path = default_contacts_path();
 
     // Load existing contacts or start with empty list
@@ -50,23 +305,13 @@ pub fn add(name: &str, phone: &str, relationship: &str, notes: Option<&str>) ->
             .with_context(|| format!("Failed to read contacts file: {:?}", path))?;
         serde_json::from_str(&content).with_context(|| "Failed to parse contacts JSON")?
     } else {
-        // Create parent directory if needed
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
-        }
         Vec::new()
     };
 
-    // Check for duplicate phone
-    let normalized: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    // Check for duplicate phone (including merged-in handles)
+    let key = helpers::handle_key(phone);
     for existing in &contacts {
-        let existing_normalized: String = existing
-            .phone
-            .chars()
-            .filter(|c| c.is_ascii_digit())
-            .collect();
-        if existing_normalized == normalized {
+        if existing.all_handles().any(|h| helpers::handle_key(h) == key) {
             println!(
                 "Contact with phone {} already exists: {}",
                 phone, existing.name
@@ -75,26 +320,160 @@ pub fn add(name: &str, phone: &str, relationship: &str, notes: Option<&str>) ->
         }
     }
 
-    // Add new contact
-    let new_contact = Contact {
-        name: name.to_string(),
-        phone: phone.to_string(),
-        relationship_type: relationship.to_string(),
-        notes: notes.map(String::from),
-    };
-
-    contacts.push(new_contact);
-
-    // Write back to file
-    let json = serde_json::to_string_pretty(&contacts)?;
-    std::fs::write(&path, json).with_context(|| format!("Failed to write contacts file: {:?}", path))?;
+    match add_or_merge(&mut contacts, name, phone, relationship, notes, merge)? {
+        Some(merged_into) => println!("Added {} to existing contact {}", phone, merged_into),
+        None => println!("Added contact: {} ({})", name, phone),
+    }
 
-    println!("Added contact: {} ({})", name, phone);
+    // Back up the old file, then replace it atomically
+    backup::save_contacts(&path, &contacts, &backup::backup_dir(), backup::BACKUP_KEEP)?;
 
     Ok(())
 }
 
+/// Append a new contact, or with `merge` add the phone to the one contact
+/// whose name collides. Returns the name merged into, if any.
+fn add_or_merge(
+    contacts: &mut Vec<Contact>,
+    name: &str,
+    phone: &str,
+    relationship: &str,
+    notes: Option<&str>,
+    merge: bool,
+) -> Result<Option<String>> {
+    let collisions = merge::name_collisions(contacts, name);
+    let describe = || {
+        collisions
+            .iter()
+            .map(|c| format!("{} ({})", c.name, c.phone))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match (collisions.as_slice(), merge) {
+        ([], _) => {
+            contacts.push(Contact {
+                name: name.to_string(),
+                phone: phone.to_string(),
+                relationship_type: relationship.to_string(),
+                notes: notes.map(String::from),
+                handles: Vec::new(),
+            });
+            Ok(None)
+        }
+        ([only], true) => {
+            let existing = &mut contacts[only.index];
+            merge::add_handle(existing, phone);
+            Ok(Some(existing.name.clone()))
+        }
+        (_, true) => Err(anyhow!(
+            "'{}' matches several contacts: {}; rename or run `contacts dedupe` first",
+            name,
+            describe()
+        )),
+        (_, false) => Err(anyhow!(
+            "'{}' collides with existing contact {}; pass --merge to add {} to it, or use a distinct name",
+            name,
+            describe(),
+            phone
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    // Tests would go here but require mocking file I/O
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, set_last_read, DAY_NS};
+
+    fn contact(name: &str, phone: &str, handles: &[&str]) -> Contact {
+        Contact {
+            name: name.to_string(),
+            phone: phone.to_string(),
+            relationship_type: String::new(),
+            notes: None,
+            handles: handles.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_contact_stats_joins_handles_and_keeps_zeros() {
+        let conn = empty_db();
+        let phone = insert_handle(&conn, "+14155551234");
+        let email = insert_handle(&conn, "Alice@Example.com");
+        let bob = insert_handle(&conn, "+14155550002");
+        let a_chat = insert_chat(&conn, "+14155551234", None, &[phone]);
+        let a_mail = insert_chat(&conn, "alice@example.com", None, &[email]);
+        let b_chat = insert_chat(&conn, "+14155550002", None, &[bob]);
+        insert_message(&conn, a_chat, phone, Some("hi"), 700 * DAY_NS, true, true);
+        insert_message(&conn, a_mail, email, Some("yo"), 703 * DAY_NS, false, false);
+        insert_message(&conn, b_chat, bob, Some("hey"), 701 * DAY_NS, false, true);
+        insert_message(&conn, b_chat, bob, Some("again"), 702 * DAY_NS, false, true);
+        insert_message(&conn, b_chat, bob, Some("ok"), 702 * DAY_NS + 1, true, true);
+
+        let contacts = vec![
+            // SMS-style number without +1 still matches; email matches case-insensitively
+            contact("Alice", "4155551234", &["alice@example.com"]),
+            contact("Bob", "+1 (415) 555-0002", &[]),
+            contact("Carol", "+14155550003", &[]),
+        ];
+        let by_handle = helpers::query_handle_stats(&conn, 0, None).unwrap();
+
+        let rows = contact_stats(&contacts, &by_handle, ContactSort::LastMessage);
+        let got: Vec<(&str, i64, i64, i64, bool)> = rows
+            .iter()
+            .map(|r| (r.contact.name.as_str(), r.message_count, r.sent, r.received, r.has_unread))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("Alice", 2, 1, 1, true),
+                ("Bob", 3, 1, 2, false),
+                ("Carol", 0, 0, 0, false),
+            ]
+        );
+        assert_eq!(rows[0].last_message_date, Some(helpers::cocoa_to_iso(703 * DAY_NS)));
+        assert_eq!(rows[2].last_message_date, None);
+
+        let by_count = contact_stats(&contacts, &by_handle, ContactSort::Count);
+        assert_eq!(by_count[0].contact.name, "Bob");
+
+        // Window excludes Alice's phone message
+        let recent = helpers::query_handle_stats(&conn, 701 * DAY_NS, None).unwrap();
+        let rows = contact_stats(&contacts, &recent, ContactSort::Name);
+        assert_eq!((rows[0].contact.name.as_str(), rows[0].message_count), ("Alice", 1));
+
+        // Read past the chat's read position: no longer unread, despite its flags
+        set_last_read(&conn, a_mail, 704 * DAY_NS);
+        let by_handle = helpers::query_handle_stats(&conn, 0, None).unwrap();
+        let rows = contact_stats(&contacts, &by_handle, ContactSort::Name);
+        assert!(!rows[0].has_unread);
+    }
+
+    #[test]
+    fn test_contact_sort_parse() {
+        assert_eq!(ContactSort::parse("last_message").unwrap(), ContactSort::LastMessage);
+        assert_eq!(ContactSort::parse("count").unwrap(), ContactSort::Count);
+        assert!(ContactSort::parse("recent").is_err());
+    }
+
+    #[test]
+    fn test_add_or_merge_name_collisions() {
+        let mut contacts = vec![contact("Mom", "+14155550001", &[]), contact("Bob", "+14155550002", &[])];
+
+        let err = add_or_merge(&mut contacts, "mom", "+14155550009", "family", None, false).unwrap_err();
+        assert!(err.to_string().contains("Mom (+14155550001)") && err.to_string().contains("--merge"));
+        assert_eq!(contacts.len(), 2);
+
+        let merged = add_or_merge(&mut contacts, "mom", "+14155550009", "family", None, true).unwrap();
+        assert_eq!(merged.as_deref(), Some("Mom"));
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[0].handles, ["+14155550009"]);
+
+        // No collision: --merge just adds
+        assert_eq!(add_or_merge(&mut contacts, "Carol", "+14155550003", "friend", None, true).unwrap(), None);
+        assert_eq!(contacts.len(), 3);
+
+        // Ambiguous merge target
+        contacts.push(contact("MOM", "+14155550004", &[]));
+        assert!(add_or_merge(&mut contacts, "Mom", "+14155550005", "", None, true).is_err());
+    }
 }
@@ -0,0 +1,246 @@
+//! Deleted command: messages in "Recently Deleted" that can still be recovered.
+//!
+//! macOS 13+ keeps deleted messages referenced from
+//! chat_recoverable_message_join for about 30 days; older databases have no
+//! such table and simply report nothing.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Text and attachment placeholders come from db::message_body (no local "not available" string)
+//! - 10/16/2026 - Initial deleted command (recoverable messages, days remaining)
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::commands::reading;
+use crate::contacts::manager::ContactsManager;
+use crate::db::message_body::display_text;
+use crate::db::{connection, helpers, queries};
+use crate::output::{self, OutputControls};
+
+/// Days Messages keeps a deleted message recoverable.
+pub const RECOVERABLE_DAYS: i64 = 30;
+
+/// A recently deleted message.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DeletedMessage {
+    pub rowid: i64,
+    pub guid: String,
+    pub text: String,
+    /// Attachment placeholder ("[photo]", "[file: name.pdf]", ...); `text` is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    /// When the message was originally sent or received.
+    pub date: String,
+    pub is_from_me: bool,
+    pub sender_handle: Option<String>,
+    pub contact_name: Option<String>,
+    /// Conversation the message was deleted from.
+    pub chat_id: Option<String>,
+    pub chat_name: Option<String>,
+    pub deleted_date: String,
+    /// Deletion date plus `RECOVERABLE_DAYS`; permanently removed after this.
+    pub recoverable_until: String,
+    /// Whole days left before permanent removal (0 once overdue).
+    pub days_remaining: i64,
+}
+
+/// Enrich deleted rows with contact names and the recovery deadline relative to `now_cocoa`.
+pub fn build_deleted(
+    rows: Vec<helpers::DeletedRow>,
+    contacts: &ContactsManager,
+    now_cocoa: i64,
+) -> Vec<DeletedMessage> {
+    use queries::DAY_NS;
+
+    rows.into_iter()
+        .map(|row| {
+            let until = row.delete_cocoa.saturating_add(RECOVERABLE_DAYS * DAY_NS);
+            let contact_name = row
+                .handle
+                .as_deref()
+                .and_then(|h| contacts.find_by_phone(h))
+                .map(|c| c.name.clone());
+            let (text, placeholder) = row.body.into_parts();
+            DeletedMessage {
+                rowid: row.rowid,
+                guid: row.guid,
+                text,
+                placeholder,
+                date: helpers::cocoa_to_iso(row.date_cocoa),
+                is_from_me: row.is_from_me,
+                chat_name: row.chat_name.or_else(|| contact_name.clone()),
+                sender_handle: row.handle,
+                contact_name,
+                chat_id: row.chat_identifier,
+                deleted_date: helpers::cocoa_to_iso(row.delete_cocoa),
+                recoverable_until: helpers::cocoa_to_iso(until),
+                // Round up: anything left of a day still counts as a day
+                days_remaining: ((until - now_cocoa).max(0) + DAY_NS - 1) / DAY_NS,
+            }
+        })
+        .collect()
+}
+
+/// List recoverable deleted messages, optionally for one contact.
+pub fn deleted(
+    days: u32,
+    contact: Option<&str>,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = connection::open_db().context("Failed to open Messages database")?;
+    let supported = helpers::has_recoverable_messages(&conn);
+
+    let filter = contact
+        .map(|c| reading::contact_filter(&conn, c, contacts).map(|(_, filter)| filter))
+        .transpose()?;
+    let rows = helpers::query_deleted_messages(&conn, queries::days_ago_cocoa(days), filter.as_ref())?;
+    let messages = build_deleted(rows, contacts, queries::days_ago_cocoa(0));
+
+    if output.json {
+        output.print(&messages)?;
+        return Ok(());
+    }
+
+    if !supported {
+        println!("This Messages database has no Recently Deleted table (macOS 13 or later).");
+        return Ok(());
+    }
+    if messages.is_empty() {
+        println!("No recoverable deleted messages in the last {} days.", days);
+        return Ok(());
+    }
+
+    println!("Recently Deleted ({}):", messages.len());
+    println!("{}", "-".repeat(60));
+    for m in &messages {
+        let sender = if m.is_from_me {
+            "Me"
+        } else {
+            m.contact_name.as_deref().or(m.sender_handle.as_deref()).unwrap_or("Unknown")
+        };
+        let chat = m.chat_name.as_deref().or(m.chat_id.as_deref()).unwrap_or("unknown chat");
+        let preview = output::preview(display_text(&m.text, m.placeholder.as_deref()), 80);
+        println!("[{}] {} in {}: {}", m.date, sender, chat, preview);
+        println!("  deleted {}, {} days left", m.deleted_date, m.days_remaining);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::summary;
+    use crate::db::fixtures::{
+        empty_db, insert_attachment, insert_chat, insert_handle, insert_message, mark_deleted, DAY_NS,
+    };
+
+    #[test]
+    fn test_deleted_messages_with_days_remaining() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let chat_a = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let chat_b = insert_chat(&conn, "+14155550002", None, &[bob]);
+
+        let base = 700 * DAY_NS;
+        insert_message(&conn, chat_a, alice, Some("kept"), base, false, true);
+        let oops = insert_message(&conn, chat_a, alice, Some("oops"), base + 1, false, true);
+        let old = insert_message(&conn, chat_b, bob, Some("old news"), base + 2, false, true);
+        mark_deleted(&conn, chat_a, oops, base + 10 * DAY_NS);
+        // delete_date in seconds, as some macOS versions store it
+        mark_deleted(&conn, chat_b, old, (base + 2 * DAY_NS) / 1_000_000_000);
+
+        let contacts = ContactsManager::from_contacts(vec![crate::contacts::manager::Contact {
+            name: "Alice".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }]);
+        let now = base + 12 * DAY_NS;
+        let rows = helpers::query_deleted_messages(&conn, 0, None).unwrap();
+        let deleted = build_deleted(rows, &contacts, now);
+
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(deleted[0].text, "oops");
+        assert_eq!(deleted[0].contact_name.as_deref(), Some("Alice"));
+        assert_eq!(deleted[0].chat_name.as_deref(), Some("Alice"));
+        assert_eq!(deleted[0].days_remaining, 28);
+        assert_eq!(deleted[0].recoverable_until, helpers::cocoa_to_iso(base + 40 * DAY_NS));
+        assert_eq!(deleted[1].text, "old news");
+        assert_eq!(deleted[1].days_remaining, 20);
+
+        // Delete-date cutoff and contact filter
+        let recent = helpers::query_deleted_messages(&conn, base + 5 * DAY_NS, None).unwrap();
+        assert_eq!(recent.len(), 1);
+        let filter = helpers::HandleFilter::resolve(&conn, &["+14155550002".to_string()]).unwrap();
+        let bobs = helpers::query_deleted_messages(&conn, 0, Some(&filter)).unwrap();
+        assert_eq!(bobs.iter().map(|r| r.rowid).collect::<Vec<_>>(), vec![old]);
+
+        // No text: the shared attachment placeholder, else the shared "not available"
+        let photo = insert_message(&conn, chat_a, alice, None, base + 3, false, true);
+        insert_attachment(&conn, photo, Some("image/jpeg"), "IMG_1.JPG");
+        let empty = insert_message(&conn, chat_a, alice, None, base + 4, false, true);
+        mark_deleted(&conn, chat_a, photo, base + 11 * DAY_NS);
+        mark_deleted(&conn, chat_a, empty, base + 11 * DAY_NS);
+        let rows = helpers::query_deleted_messages(&conn, base + 11 * DAY_NS, None).unwrap();
+        let bodies: Vec<(String, Option<String>)> =
+            build_deleted(rows, &contacts, now).into_iter().map(|m| (m.text, m.placeholder)).collect();
+        assert!(bodies.contains(&(String::new(), Some("[photo]".to_string()))));
+        assert!(bodies.contains(&(crate::db::message_body::UNAVAILABLE.to_string(), None)));
+    }
+
+    #[test]
+    fn test_summary_marks_or_drops_deleted() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let base = 700 * DAY_NS;
+        insert_message(&conn, chat, alice, Some("hi"), base, false, true);
+        // In 1:1 chats my messages carry the other person's handle too
+        let oops = insert_message(&conn, chat, alice, Some("oops"), base + 1, true, true);
+        insert_message(&conn, chat, alice, Some("what was that?"), base + 2, false, true);
+        mark_deleted(&conn, chat, oops, base + DAY_NS);
+
+        let mut window = summary::SummaryWindow {
+            start_cocoa: 0,
+            end_cocoa: i64::MAX,
+            limit: 10,
+            offset: 0,
+            cursor: None,
+            ascending: true,
+            include_deleted: false,
+            detect_language: false,
+            with_reactions: false,
+        };
+        let contacts = ContactsManager::empty();
+        let texts = |s: &summary::Summary| s.messages.iter().map(|m| m.text.clone()).collect::<Vec<_>>();
+
+        let plain = summary::build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        assert_eq!(texts(&plain), ["hi", "what was that?"]);
+        assert_eq!(plain.deleted_count, None);
+
+        window.include_deleted = true;
+        let marked = summary::build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        assert_eq!(texts(&marked), ["hi", "oops", "what was that?"]);
+        assert!(marked.messages[1].deleted && marked.messages[1].sender == "Me");
+        assert_eq!(marked.deleted_count, Some(1));
+
+        // Without the table there is nothing to mark
+        conn.execute_batch("DROP TABLE chat_recoverable_message_join").unwrap();
+        let old_schema = summary::build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        assert_eq!(old_schema.message_count, 3);
+        assert_eq!(old_schema.deleted_count, Some(0));
+    }
+
+    #[test]
+    fn test_deleted_without_recoverable_table() {
+        let conn = empty_db();
+        conn.execute_batch("DROP TABLE chat_recoverable_message_join").unwrap();
+        assert!(!helpers::has_recoverable_messages(&conn));
+        assert!(helpers::query_deleted_messages(&conn, 0, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,396 @@
+//! Digest command: a daily briefing combining unread, followups, and deltas.
+//!
+//! Compares the requested period against the prior equal-length period to
+//! surface people who've gone quiet and overall volume changes. The digest
+//! is built from a `&Connection` so the daemon can serve it from its hot
+//! connection.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Unanswered questions leave out system items (helpers include_system)
+//! - 10/16/2026 - Unread section can keep only known or unknown senders (--known-only / --unknown-only); groups carry is_known_sender
+//! - 10/16/2026 - JSON goes through OutputControls::print_json (--envelope)
+//! - 10/16/2026 - Unanswered questions capped at helpers::FOLLOWUP_LIMIT
+//! - 10/16/2026 - Unread previews fall back to the attachment placeholder
+//! - 10/16/2026 - Unread section leaves out muted conversations (--include-muted); muted_count
+//! - 10/16/2026 - Text-mode previews use output::preview (char-safe, one line)
+//! - 10/16/2026 - Typed unanswered questions; JsonSchema derives for `schema`
+//! - 10/16/2026 - Optional relationship handle filter
+//! - 10/16/2026 - Initial digest (unread by contact, unanswered, gone quiet, stats)
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::helpers::{self, HandleFilter, TopContact, UnreadMessage};
+use crate::db::connection;
+use crate::db::queries::{self, DAY_NS};
+use crate::mutes::MuteFilter;
+use crate::output::{self, OutputControls};
+use crate::senders::{SenderFilter, SenderMode};
+
+/// Max unread messages pulled into the digest.
+const UNREAD_LIMIT: u32 = 200;
+/// Min messages in the prior period for someone to count as "usually talk to".
+const QUIET_MIN_PREVIOUS: i64 = 5;
+/// Someone is "quiet" when this period's count is at most 1/QUIET_RATIO of the prior.
+const QUIET_RATIO: i64 = 4;
+/// Max quiet contacts listed.
+const QUIET_LIMIT: usize = 10;
+/// Max messages shown per unread contact.
+const UNREAD_PREVIEW: usize = 5;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Digest {
+    pub period: Period,
+    pub unread: Vec<UnreadGroup>,
+    /// Unread messages left out because their conversation is muted
+    pub muted_count: usize,
+    pub unanswered_questions: Vec<DigestQuestion>,
+    pub gone_quiet: Vec<QuietContact>,
+    pub stats: Stats,
+}
+
+/// A question from someone that hasn't had a reply.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DigestQuestion {
+    pub text: String,
+    pub date: String,
+    pub phone: String,
+    pub contact_name: Option<String>,
+    pub days_ago: i64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Period {
+    pub start: String,
+    pub end: String,
+    pub previous_start: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnreadGroup {
+    pub phone: String,
+    pub contact_name: Option<String>,
+    pub count: usize,
+    pub latest_date: String,
+    pub is_known_sender: bool,
+    pub messages: Vec<UnreadMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+pub struct QuietContact {
+    pub phone: String,
+    pub contact_name: Option<String>,
+    pub previous_count: i64,
+    pub current_count: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, JsonSchema)]
+pub struct Counts {
+    pub total: i64,
+    pub sent: i64,
+    pub received: i64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Stats {
+    pub current: Counts,
+    pub previous: Counts,
+    pub delta: Counts,
+}
+
+/// Parse `--since`: "today", "yesterday", or YYYY-MM-DD (local midnight).
+pub fn parse_since(since: Option<&str>) -> Result<chrono::DateTime<Local>> {
+    let today = Local::now().date_naive();
+    let date = match since.map(str::trim) {
+        None | Some("yesterday") => today - Duration::days(1),
+        Some("today") => today,
+        Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|_| anyhow!("Invalid --since '{}' (use yesterday, today, or YYYY-MM-DD)", s))?,
+    };
+    Local
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .ok_or_else(|| anyhow!("Invalid local midnight for {}", date))
+}
+
+/// Group unread messages by sender, busiest first.
+pub fn group_unread(messages: Vec<UnreadMessage>, contacts: &ContactsManager) -> Vec<UnreadGroup> {
+    let mut groups: HashMap<String, Vec<UnreadMessage>> = HashMap::new();
+    for m in messages {
+        groups.entry(m.phone.clone()).or_default().push(m);
+    }
+
+    let mut out: Vec<UnreadGroup> = groups
+        .into_iter()
+        .map(|(phone, messages)| {
+            // Query order is newest first
+            let latest_date = messages.first().map(|m| m.date.clone()).unwrap_or_default();
+            UnreadGroup {
+                contact_name: contacts.find_by_phone(&phone).map(|c| c.name.clone()),
+                count: messages.len(),
+                latest_date,
+                is_known_sender: messages.iter().any(|m| m.is_known_sender),
+                messages: messages.into_iter().take(UNREAD_PREVIEW).collect(),
+                phone,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| b.latest_date.cmp(&a.latest_date)));
+    out
+}
+
+/// People with real volume last period whose volume collapsed this period.
+pub fn find_gone_quiet(
+    current: &[TopContact],
+    previous: &[TopContact],
+    contacts: &ContactsManager,
+) -> Vec<QuietContact> {
+    let current_counts: HashMap<&str, i64> = current
+        .iter()
+        .map(|c| (c.phone.as_str(), c.message_count))
+        .collect();
+
+    let mut quiet: Vec<QuietContact> = previous
+        .iter()
+        .filter(|p| p.message_count >= QUIET_MIN_PREVIOUS)
+        .filter_map(|p| {
+            let now = current_counts.get(p.phone.as_str()).copied().unwrap_or(0);
+            (now * QUIET_RATIO <= p.message_count).then(|| QuietContact {
+                phone: p.phone.clone(),
+                contact_name: contacts.find_by_phone(&p.phone).map(|c| c.name.clone()),
+                previous_count: p.message_count,
+                current_count: now,
+            })
+        })
+        .collect();
+    quiet.sort_by_key(|q| std::cmp::Reverse(q.previous_count));
+    quiet.truncate(QUIET_LIMIT);
+    quiet
+}
+
+fn counts(tuple: (i64, i64, i64)) -> Counts {
+    Counts {
+        total: tuple.0,
+        sent: tuple.1,
+        received: tuple.2,
+    }
+}
+
+/// Build the digest for [start, end) compared against the prior equal-length period.
+/// `senders` applies to the unread section only.
+pub fn build_digest(
+    conn: &Connection,
+    contacts: &ContactsManager,
+    start_cocoa: i64,
+    end_cocoa: i64,
+    filter: Option<&HandleFilter>,
+    mutes: Option<&MuteFilter>,
+    senders: &SenderFilter,
+) -> Result<Digest> {
+    let span = (end_cocoa - start_cocoa).max(0);
+    let previous_start = start_cocoa - span;
+
+    let unread = helpers::query_unread_messages(conn, UNREAD_LIMIT, filter, mutes, Some(senders))?;
+    let unread = group_unread(unread, contacts);
+    let muted_count = mutes
+        .map(|m| helpers::query_muted_unread_count(conn, filter, m))
+        .transpose()?
+        .unwrap_or(0);
+
+    // Any reply after the question (up to now) counts as answered
+    let reply_window = span + DAY_NS;
+    let unanswered_questions = helpers::query_unanswered_questions(conn, start_cocoa, reply_window, helpers::FOLLOWUP_LIMIT, false)?
+        .into_iter()
+        .filter(|q| filter.is_none_or(|f| f.matches(&q.phone)))
+        .map(|q| {
+            let contact_name = contacts.find_by_phone(&q.phone).map(|c| c.name.clone());
+            DigestQuestion {
+                text: q.text,
+                date: q.date,
+                phone: q.phone,
+                contact_name,
+                days_ago: q.days_ago,
+            }
+        })
+        .collect();
+
+    let current_contacts = helpers::query_contact_counts_between(conn, start_cocoa, end_cocoa, filter)?;
+    let previous_contacts =
+        helpers::query_contact_counts_between(conn, previous_start, start_cocoa, filter)?;
+    let gone_quiet = find_gone_quiet(&current_contacts, &previous_contacts, contacts);
+
+    let current = counts(helpers::query_message_counts_between(conn, start_cocoa, end_cocoa, filter)?);
+    let previous = counts(helpers::query_message_counts_between(conn, previous_start, start_cocoa, filter)?);
+    let delta = Counts {
+        total: current.total - previous.total,
+        sent: current.sent - previous.sent,
+        received: current.received - previous.received,
+    };
+
+    Ok(Digest {
+        period: Period {
+            start: helpers::cocoa_to_iso(start_cocoa),
+            end: helpers::cocoa_to_iso(end_cocoa),
+            previous_start: helpers::cocoa_to_iso(previous_start),
+        },
+        unread,
+        muted_count,
+        unanswered_questions,
+        gone_quiet,
+        stats: Stats {
+            current,
+            previous,
+            delta,
+        },
+    })
+}
+
+/// Print the digest (JSON nested by section, or a readable briefing).
+pub fn digest(
+    since: Option<&str>,
+    relationship: Option<&str>,
+    include_muted: bool,
+    sender: SenderMode,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = connection::open_db()?;
+    let filter = relationship
+        .map(|r| HandleFilter::resolve(&conn, &contacts.handles_for_relationship(r)?))
+        .transpose()?;
+    let mutes = MuteFilter::load(&conn, include_muted)?;
+    let senders = SenderFilter::load(&conn, contacts, sender)?;
+    let start = parse_since(since)?;
+    let start_cocoa = queries::unix_to_cocoa(start.timestamp());
+    let end_cocoa = queries::unix_to_cocoa(Local::now().timestamp());
+
+    let digest = build_digest(&conn, contacts, start_cocoa, end_cocoa, filter.as_ref(), mutes.as_ref(), &senders)?;
+
+    if output.json {
+        output.print_json(&digest, false)?;
+        return Ok(());
+    }
+
+    println!("Digest since {}", start.format("%Y-%m-%d %H:%M"));
+    println!("{}", "=".repeat(60));
+
+    let s = &digest.stats;
+    println!(
+        "Messages: {} ({:+}) | sent {} ({:+}) | received {} ({:+})",
+        s.current.total, s.delta.total, s.current.sent, s.delta.sent, s.current.received, s.delta.received
+    );
+
+    println!();
+    println!("Unread ({} people):", digest.unread.len());
+    if digest.muted_count > 0 {
+        println!("  ({} in muted conversations hidden; --include-muted to show)", digest.muted_count);
+    }
+    if digest.unread.is_empty() {
+        println!("  All caught up.");
+    }
+    for g in &digest.unread {
+        println!("  {} - {} unread", g.contact_name.as_deref().unwrap_or(&g.phone), g.count);
+        if let Some(text) = g.messages.first().and_then(|m| m.text.as_deref().or(m.placeholder.as_deref())) {
+            let preview = output::preview(text, 80);
+            println!("    latest: {}", preview);
+        }
+    }
+
+    println!();
+    println!("Unanswered questions ({}):", digest.unanswered_questions.len());
+    for q in &digest.unanswered_questions {
+        let who = q.contact_name.as_deref().unwrap_or(&q.phone);
+        let text = output::preview(&q.text, 80);
+        println!("  {}: {}", who, text);
+    }
+
+    println!();
+    println!("Gone quiet ({}):", digest.gone_quiet.len());
+    for c in &digest.gone_quiet {
+        println!(
+            "  {} - {} messages, down from {}",
+            c.contact_name.as_deref().unwrap_or(&c.phone),
+            c.current_count,
+            c.previous_count
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+    fn top(phone: &str, count: i64) -> TopContact {
+        TopContact {
+            phone: phone.to_string(),
+            contact_name: None,
+            message_count: count,
+            variants: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_gone_quiet() {
+        let contacts = ContactsManager::empty();
+        let previous = vec![top("+1", 20), top("+2", 10), top("+3", 3), top("+4", 8)];
+        let current = vec![top("+2", 9), top("+4", 2)];
+
+        let quiet = find_gone_quiet(&current, &previous, &contacts);
+        let phones: Vec<(&str, i64)> = quiet.iter().map(|q| (q.phone.as_str(), q.current_count)).collect();
+        // +2 still active, +3 below the "usually talk to" floor
+        assert_eq!(phones, vec![("+1", 0), ("+4", 2)]);
+    }
+
+    #[test]
+    fn test_group_unread_by_sender() {
+        let contacts = ContactsManager::empty();
+        let msg = |phone: &str, date: &str| UnreadMessage {
+            rowid: 0,
+            guid: String::new(),
+            text: Some("hi".to_string()),
+            date: date.to_string(),
+            phone: phone.to_string(),
+            placeholder: None,
+            is_known_sender: false,
+        };
+        let groups = group_unread(
+            vec![msg("+1", "2026-01-03"), msg("+2", "2026-01-02"), msg("+1", "2026-01-01")],
+            &contacts,
+        );
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].phone, "+1");
+        assert_eq!(groups[0].count, 2);
+        assert_eq!(groups[0].latest_date, "2026-01-03");
+    }
+
+    #[test]
+    fn test_build_digest_compares_periods() {
+        let conn = empty_db();
+        let contacts = ContactsManager::empty();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+
+        // Previous period (days 690-700): 6 received; current (700-710): 1 sent
+        for i in 0..6 {
+            insert_message(&conn, chat, alice, Some("hey"), 691 * DAY_NS + i, false, true);
+        }
+        insert_message(&conn, chat, alice, Some("sorry, busy"), 705 * DAY_NS, true, true);
+
+        let senders = SenderFilter::load(&conn, &contacts, SenderMode::All).unwrap();
+        let digest = build_digest(&conn, &contacts, 700 * DAY_NS, 710 * DAY_NS, None, None, &senders).unwrap();
+        assert_eq!(digest.stats.current, Counts { total: 1, sent: 1, received: 0 });
+        assert_eq!(digest.stats.previous.total, 6);
+        assert_eq!(digest.stats.delta.total, -5);
+        assert_eq!(digest.gone_quiet.len(), 1);
+        assert_eq!(digest.gone_quiet[0].previous_count, 6);
+        assert!(digest.unread.is_empty());
+    }
+}
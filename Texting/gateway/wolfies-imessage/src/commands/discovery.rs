@@ -1,6 +1,19 @@
-//! Discovery commands: handles, unknown, discover, scheduled.
+//! Discovery commands: handles, unknown, discover.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - discover --interactive reports a skipped sender through output::warn
+//! - 10/16/2026 - Handle and UnknownSender variants are serde(default): not required by the schema
+//! - 10/16/2026 - handles, unknown, discover print JSON through OutputControls::print_json (--envelope)
+//! - 10/16/2026 - handles: --csv output
+//! - 10/16/2026 - discover --interactive reports a name collision and moves on instead of aborting
+//! - 10/16/2026 - Sample previews are char-safe (output::preview)
+//! - 10/16/2026 - Phone format variants merged (canonical handle + variants); unknown/discover use helpers::query_unknown_senders
+//! - 10/16/2026 - unknown/discover skip my own handles (--include-self)
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - unknown/discover rows carry kind + looks_automated; --exclude-automated
+//! - 10/16/2026 - discover adds name_hints (chat names, email local-part, signatures) and --interactive
+//! - 10/16/2026 - handles --merge-contacts groups phone/email handles per person
+//! - 10/16/2026 - Moved scheduled to commands::scheduled (local send queue)
 //! - 01/10/2026 - Added contact caching (Phase 4A) - accepts Arc<ContactsManager> (Claude)
 //! - 01/10/2026 - Initial stub implementation (Claude)
 //! - 01/10/2026 - Implemented handles discovery command (Claude)
@@ -8,61 +21,267 @@
 //! - 01/10/2026 - Implemented discover frequent texters command (Claude)
 //! - 01/10/2026 - Implemented scheduled messages stub (not supported by Messages.db) (Claude)
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use regex::Regex;
 use rusqlite;
+use schemars::JsonSchema;
 use serde::Serialize;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, OnceLock};
 
+use crate::contacts::classify::{self, Classification};
 use crate::contacts::manager::ContactsManager;
-use crate::db::{connection::open_db, queries};
+use crate::db::{connection::open_db, helpers, queries};
+use crate::output::{self, OutputControls};
 
-#[derive(Debug, Serialize)]
-struct Handle {
+/// Handles fetched before merging, so merged rows still fill `limit`.
+pub const MERGE_FETCH_LIMIT: u32 = 5000;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Handle {
     handle: String,
     message_count: i64,
     last_message_date: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variants: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct UnknownSender {
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct UnknownSender {
     handle: String,
     message_count: i64,
     last_message_date: String,
     sample_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variants: Vec<String>,
+    #[serde(flatten)]
+    classification: Classification,
+}
+
+/// A discover candidate with advisory name hints.
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct DiscoveryCandidate {
+    #[serde(flatten)]
+    sender: UnknownSender,
+    name_hints: Vec<String>,
+}
+
+/// Recent incoming texts scanned for a signature line.
+const SIGNATURE_SCAN_LIMIT: u32 = 20;
+
+/// A signature must recur at least this often to become a hint.
+const SIGNATURE_MIN_HITS: usize = 2;
+
+/// Email local-parts that name a role, not a person.
+const GENERIC_LOCAL_PARTS: &[&str] = &[
+    "admin", "contact", "hello", "help", "info", "mail", "me", "noreply", "no-reply", "support",
+];
+
+fn signature_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // "- John" / "~ John Smith" on its own line, or after a finished sentence
+        Regex::new(r"(?:^|\n|[.!?]\s+)[-–—~]\s*([A-Z][A-Za-z'’]+(?: [A-Z][A-Za-z'’.]*)?)\s*$")
+            .expect("valid signature regex")
+    })
+}
+
+/// Prettified local-part of an email handle ("john.smith@x.com" -> "John Smith").
+pub fn email_name_hint(handle: &str) -> Option<String> {
+    let (local, _) = handle.split_once('@')?;
+    let local = local.split('+').next().unwrap_or(local);
+    if GENERIC_LOCAL_PARTS.contains(&local.to_lowercase().as_str()) {
+        return None;
+    }
+
+    let words: Vec<String> = local
+        .split(['.', '_', '-'])
+        .map(|w| w.trim_matches(|c: char| c.is_ascii_digit()))
+        .filter(|w| w.len() >= 2 && w.chars().all(|c| c.is_alphabetic()))
+        .map(|w| {
+            let mut chars = w.chars();
+            let first = chars.next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default();
+            first + &chars.as_str().to_lowercase()
+        })
+        .collect();
+
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+/// Signature-like trailing line of a message ("See you then!\n- John" -> "John").
+pub fn signature_line(text: &str) -> Option<String> {
+    signature_re()
+        .captures(text.trim_end())
+        .map(|c| c[1].trim_end_matches('.').to_string())
+}
+
+/// Most common signature across texts; ties go to the most recent.
+pub fn signature_hint(texts: &[String]) -> Option<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for sig in texts.iter().filter_map(|t| signature_line(t)) {
+        match counts.iter_mut().find(|(s, _)| *s == sig) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((sig, 1)),
+        }
+    }
+
+    // max_by_key keeps the last max, so scan oldest-first to prefer recent ties
+    counts
+        .into_iter()
+        .rev()
+        .filter(|(_, n)| *n >= SIGNATURE_MIN_HITS)
+        .max_by_key(|(_, n)| *n)
+        .map(|(s, _)| s)
+}
+
+/// Best-effort name hints for a handle: signature, email local-part, chat names.
+pub fn name_hints(conn: &rusqlite::Connection, handle: &str) -> Result<Vec<String>> {
+    let texts = helpers::query_handle_recent_texts(conn, handle, SIGNATURE_SCAN_LIMIT)?;
+
+    let mut hints: Vec<String> = Vec::new();
+    let candidates = signature_hint(&texts)
+        .into_iter()
+        .chain(email_name_hint(handle))
+        .chain(helpers::query_handle_chat_names(conn, handle)?);
+    for hint in candidates {
+        if !hints.iter().any(|h| h.eq_ignore_ascii_case(&hint)) {
+            hints.push(hint);
+        }
+    }
+    Ok(hints)
+}
+
+/// Classify a sender from its handle and recent incoming texts.
+pub fn classify_sender(conn: &rusqlite::Connection, handle: &str) -> Classification {
+    let texts = helpers::query_handle_recent_texts(conn, handle, classify::SAMPLE_TEXTS)
+        .unwrap_or_default();
+    classify::classify(handle, &texts)
+}
+
+/// Handles grouped into one row per person.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MergedHandle {
+    /// Contact name when the handles resolve to a contact.
+    pub contact_name: Option<String>,
+    pub handles: Vec<String>,
+    pub message_count: i64,
+    pub last_message_date: String,
+    #[serde(skip)]
+    last_date_cocoa: i64,
+}
+
+/// Group handles by resolved contact, then person_centric_id, then raw handle.
+///
+/// Counts are summed and the latest date kept. Sorted by last date, ties
+/// broken by message count.
+pub fn merge_handles(
+    handles: Vec<helpers::HandleInfo>,
+    contacts: &ContactsManager,
+) -> Vec<MergedHandle> {
+    let mut groups: HashMap<String, MergedHandle> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+
+    for h in handles {
+        let contact = contacts.find_by_phone(&h.handle);
+        let key = match (contact, h.person_centric_id.as_deref()) {
+            (Some(c), _) => format!("contact:{}", c.name),
+            (None, Some(pci)) => format!("person:{}", pci),
+            (None, None) => format!("handle:{}", h.handle),
+        };
+
+        let entry = groups.entry(key.clone()).or_insert_with(|| {
+            order.push(key);
+            MergedHandle {
+                contact_name: contact.map(|c| c.name.clone()),
+                handles: Vec::new(),
+                message_count: 0,
+                last_message_date: String::new(),
+                last_date_cocoa: i64::MIN,
+            }
+        });
+        entry.message_count += h.message_count;
+        if h.last_date_cocoa > entry.last_date_cocoa {
+            entry.last_date_cocoa = h.last_date_cocoa;
+            entry.last_message_date = h.last_date;
+        }
+        let raw = if h.variants.is_empty() { vec![h.handle] } else { h.variants };
+        for handle in raw {
+            if !entry.handles.contains(&handle) {
+                entry.handles.push(handle);
+            }
+        }
+    }
+
+    let mut merged: Vec<MergedHandle> =
+        order.into_iter().filter_map(|k| groups.remove(&k)).collect();
+    merged.sort_by(|a, b| {
+        b.last_date_cocoa
+            .cmp(&a.last_date_cocoa)
+            .then(b.message_count.cmp(&a.message_count))
+    });
+    merged
 }
 
 /// List all phone/email handles from recent messages.
-pub fn handles(days: u32, limit: u32, json: bool) -> Result<()> {
+///
+/// With `merge_contacts`, handles belonging to the same person are grouped.
+pub fn handles(
+    days: u32,
+    limit: u32,
+    merge_contacts: bool,
+    output: &output::OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
     let conn = open_db()?;
     let cutoff_cocoa = queries::days_ago_cocoa(days);
+    output::applied("days", days);
 
-    // Query handles
-    let mut stmt = conn.prepare(queries::DISCOVERY_HANDLES)?;
-    let handle_rows = stmt.query_map([cutoff_cocoa, limit as i64], |row: &rusqlite::Row| {
-        let handle: String = row.get(0)?;
-        let message_count: i64 = row.get(1)?;
-        let last_message_cocoa: i64 = row.get(2)?;
-
-        // Convert Cocoa timestamp to ISO string
-        let unix_ts = queries::cocoa_to_unix(last_message_cocoa);
-        use std::time::{UNIX_EPOCH, Duration};
-        let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-        let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-
-        Ok(Handle {
-            handle,
-            message_count,
-            last_message_date: datetime.to_rfc3339(),
-        })
-    })?;
+    if merge_contacts {
+        let all = helpers::query_handles(&conn, cutoff_cocoa, MERGE_FETCH_LIMIT)?;
+        let mut merged = merge_handles(all, contacts);
+        merged.truncate(limit as usize);
+
+        if output.csv {
+            output.print_csv(&merged)?;
+        } else if output.json {
+            output.print_json(&merged, true)?;
+        } else {
+            if merged.is_empty() {
+                println!("No handles found.");
+                return Ok(());
+            }
+
+            println!("People ({}):", merged.len());
+            println!("{:-<60}", "");
+            for m in &merged {
+                let label = m.contact_name.as_deref().unwrap_or(&m.handles[0]);
+                println!("{}: {} messages (last: {})", label, m.message_count, m.last_message_date);
+                println!("  {}", m.handles.join(", "));
+            }
+        }
+        return Ok(());
+    }
 
-    let handles: Vec<Handle> = handle_rows
-        .filter_map(|r: rusqlite::Result<Handle>| r.ok())
+    let handles: Vec<Handle> = helpers::query_handles(&conn, cutoff_cocoa, limit)?
+        .into_iter()
+        .map(|h| Handle {
+            handle: h.handle,
+            message_count: h.message_count,
+            last_message_date: h.last_date,
+            variants: h.variants,
+        })
         .collect();
 
     // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&handles)?);
+    if output.csv {
+        output.print_csv(&handles)?;
+    } else if output.json {
+        output.print_json(&handles, true)?;
     } else {
         if handles.is_empty() {
             println!("No handles found.");
@@ -80,42 +299,48 @@ pub fn handles(days: u32, limit: u32, json: bool) -> Result<()> {
 }
 
 /// Find messages from senders not in contacts.
-pub fn unknown(days: u32, limit: u32, json: bool, contacts: &Arc<ContactsManager>) -> Result<()> {
+pub fn unknown(
+    days: u32,
+    limit: u32,
+    exclude_automated: bool,
+    include_self: bool,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
     let conn = open_db()?;
     let cutoff_cocoa = queries::days_ago_cocoa(days);
+    output::applied("days", days);
+    let mine = helpers::self_exclusion(&conn, include_self)?;
 
     // Query all handles with recent messages
-    let mut stmt = conn.prepare(queries::DISCOVERY_UNKNOWN)?;
-    let unknown_rows = stmt.query_map([cutoff_cocoa], |row: &rusqlite::Row| {
-        let handle: String = row.get(0)?;
-        let message_count: i64 = row.get(1)?;
-        let last_message_cocoa: i64 = row.get(2)?;
-        let sample_text: Option<String> = row.get(3)?;
-
-        // Convert Cocoa timestamp to ISO string
-        let unix_ts = queries::cocoa_to_unix(last_message_cocoa);
-        use std::time::{UNIX_EPOCH, Duration};
-        let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-        let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-
-        Ok(UnknownSender {
-            handle,
-            message_count,
-            last_message_date: datetime.to_rfc3339(),
-            sample_text,
-        })
-    })?;
+    let unknown_rows = helpers::query_unknown_senders(&conn, cutoff_cocoa)?
+        .into_iter()
+        .map(|s| UnknownSender {
+            classification: classify::classify(&s.handle, &[]),
+            handle: s.handle,
+            message_count: s.message_count,
+            last_message_date: s.last_date,
+            sample_text: s.sample_text,
+            variants: s.variants,
+        });
 
-    // Filter out known contacts
+    // Filter out known contacts and my own handles (and bots, if asked)
     let unknown_senders: Vec<UnknownSender> = unknown_rows
-        .filter_map(|r: rusqlite::Result<UnknownSender>| r.ok())
-        .filter(|sender| contacts.find_by_phone(&sender.handle).is_none())
+        .filter(|sender| {
+            contacts.find_by_phone(&sender.handle).is_none()
+                && !helpers::is_mine(mine.as_ref(), &sender.handle)
+        })
+        .map(|mut sender| {
+            sender.classification = classify_sender(&conn, &sender.handle);
+            sender
+        })
+        .filter(|sender| !(exclude_automated && sender.classification.is_automated()))
         .take(limit as usize)
         .collect();
 
     // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&unknown_senders)?);
+    if output.json {
+        output.print_json(&unknown_senders, true)?;
     } else {
         if unknown_senders.is_empty() {
             println!("No unknown senders found.");
@@ -125,14 +350,15 @@ pub fn unknown(days: u32, limit: u32, json: bool, contacts: &Arc<ContactsManager
         println!("Unknown Senders ({}):", unknown_senders.len());
         println!("{:-<60}", "");
         for sender in &unknown_senders {
-            println!("{}: {} messages (last: {})", sender.handle, sender.message_count, sender.last_message_date);
+            println!(
+                "{}: {} messages (last: {}){}",
+                sender.handle,
+                sender.message_count,
+                sender.last_message_date,
+                automation_tag(&sender.classification)
+            );
             if let Some(ref text) = sender.sample_text {
-                let preview = if text.len() > 60 {
-                    format!("{}...", &text[..60])
-                } else {
-                    text.clone()
-                };
-                println!("  Sample: {}", preview);
+                println!("  Sample: {}", output::preview(text, 60));
             }
         }
     }
@@ -141,89 +367,281 @@ pub fn unknown(days: u32, limit: u32, json: bool, contacts: &Arc<ContactsManager
 }
 
 /// Discover frequent texters not in contacts.
-pub fn discover(days: u32, limit: u32, min_messages: u32, json: bool, contacts: &Arc<ContactsManager>) -> Result<()> {
+///
+/// With `interactive`, walks the candidates and offers to add each one.
+#[allow(clippy::too_many_arguments)]
+pub fn discover(
+    days: u32,
+    limit: u32,
+    min_messages: u32,
+    exclude_automated: bool,
+    interactive: bool,
+    include_self: bool,
+    output: &OutputControls,
+    contacts: &Arc<ContactsManager>,
+) -> Result<()> {
+    if interactive && output.json {
+        bail!("--interactive cannot be combined with --json");
+    }
+
     let conn = open_db()?;
     let cutoff_cocoa = queries::days_ago_cocoa(days);
+    output::applied("days", days);
+    let mine = helpers::self_exclusion(&conn, include_self)?;
 
     // Query all handles with recent messages
-    let mut stmt = conn.prepare(queries::DISCOVERY_UNKNOWN)?;
-    let unknown_rows = stmt.query_map([cutoff_cocoa], |row: &rusqlite::Row| {
-        let handle: String = row.get(0)?;
-        let message_count: i64 = row.get(1)?;
-        let last_message_cocoa: i64 = row.get(2)?;
-        let sample_text: Option<String> = row.get(3)?;
-
-        // Convert Cocoa timestamp to ISO string
-        let unix_ts = queries::cocoa_to_unix(last_message_cocoa);
-        use std::time::{UNIX_EPOCH, Duration};
-        let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-        let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-
-        Ok(UnknownSender {
-            handle,
-            message_count,
-            last_message_date: datetime.to_rfc3339(),
-            sample_text,
-        })
-    })?;
+    let unknown_rows = helpers::query_unknown_senders(&conn, cutoff_cocoa)?
+        .into_iter()
+        .map(|s| UnknownSender {
+            classification: classify::classify(&s.handle, &[]),
+            handle: s.handle,
+            message_count: s.message_count,
+            last_message_date: s.last_date,
+            sample_text: s.sample_text,
+            variants: s.variants,
+        });
 
     // Filter out known contacts and apply min_messages threshold
     let mut frequent_texters: Vec<UnknownSender> = unknown_rows
-        .filter_map(|r: rusqlite::Result<UnknownSender>| r.ok())
         .filter(|sender| {
             contacts.find_by_phone(&sender.handle).is_none()
+                && !helpers::is_mine(mine.as_ref(), &sender.handle)
                 && sender.message_count >= min_messages as i64
         })
         .collect();
 
     // Sort by message count descending (most active first)
-    frequent_texters.sort_by(|a, b| b.message_count.cmp(&a.message_count));
-    frequent_texters.truncate(limit as usize);
+    frequent_texters.sort_by_key(|sender| std::cmp::Reverse(sender.message_count));
+
+    let candidates: Vec<DiscoveryCandidate> = frequent_texters
+        .into_iter()
+        .map(|mut sender| {
+            sender.classification = classify_sender(&conn, &sender.handle);
+            sender
+        })
+        .filter(|sender| !(exclude_automated && sender.classification.is_automated()))
+        .take(limit as usize)
+        .map(|sender| {
+            let name_hints = name_hints(&conn, &sender.handle).unwrap_or_default();
+            DiscoveryCandidate { sender, name_hints }
+        })
+        .collect();
+
+    if interactive {
+        return review_candidates(&candidates);
+    }
 
     // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&frequent_texters)?);
+    if output.json {
+        output.print_json(&candidates, true)?;
     } else {
-        if frequent_texters.is_empty() {
+        if candidates.is_empty() {
             println!("No frequent texters found (min {} messages).", min_messages);
             return Ok(());
         }
 
-        println!("Frequent Texters Not in Contacts ({}):", frequent_texters.len());
+        println!("Frequent Texters Not in Contacts ({}):", candidates.len());
         println!("{:-<60}", "");
         println!("Suggestion: Consider adding these contacts");
         println!();
-        for sender in &frequent_texters {
-            println!("{}: {} messages (last: {})", sender.handle, sender.message_count, sender.last_message_date);
-            if let Some(ref text) = sender.sample_text {
-                let preview = if text.len() > 60 {
-                    format!("{}...", &text[..60])
-                } else {
-                    text.clone()
-                };
-                println!("  Sample: {}", preview);
-            }
+        for candidate in &candidates {
+            print_candidate(candidate);
         }
     }
 
     Ok(())
 }
 
-/// Get scheduled messages (pending sends).
-///
-/// Note: Scheduled messages are not available in the Messages.db schema.
-/// macOS Messages.app stores scheduled messages in memory or a separate
-/// system location not accessible via the chat.db database.
-pub fn scheduled(json: bool) -> Result<()> {
-    if json {
-        println!("{{\"scheduled_messages\": [], \"note\": \"Scheduled messages are not available in Messages.db schema\"}}");
-    } else {
-        println!("Scheduled Messages:");
-        println!("{:-<60}", "");
-        println!("No scheduled messages available.");
+/// " [short_code]" / " [personal, automated]" for bot-like senders, else empty.
+fn automation_tag(classification: &Classification) -> String {
+    match (classification.kind, classification.looks_automated) {
+        (classify::SenderKind::Personal, false) => String::new(),
+        (kind, false) => format!(" [{}]", kind.as_str()),
+        (kind, true) => format!(" [{}, automated]", kind.as_str()),
+    }
+}
+
+fn print_candidate(candidate: &DiscoveryCandidate) {
+    let sender = &candidate.sender;
+    println!(
+        "{}: {} messages (last: {}){}",
+        sender.handle,
+        sender.message_count,
+        sender.last_message_date,
+        automation_tag(&sender.classification)
+    );
+    if !candidate.name_hints.is_empty() {
+        println!("  Maybe: {}", candidate.name_hints.join(", "));
+    }
+    if let Some(ref text) = sender.sample_text {
+        println!("  Sample: {}", output::preview(text, 60));
+    }
+}
+
+/// Prompt for each candidate and add confirmed ones via the add-contact path.
+fn review_candidates(candidates: &[DiscoveryCandidate]) -> Result<()> {
+    if candidates.is_empty() {
+        println!("No frequent texters found.");
+        return Ok(());
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    for (i, candidate) in candidates.iter().enumerate() {
         println!();
-        println!("Note: Scheduled messages are not stored in the Messages.db");
-        println!("database and cannot be queried through this CLI.");
+        println!("[{}/{}]", i + 1, candidates.len());
+        print_candidate(candidate);
+
+        let suggested = candidate.name_hints.first();
+        match suggested {
+            Some(hint) => print!("Name (Enter = \"{}\", s = skip, q = quit): ", hint),
+            None => print!("Name (Enter = skip, q = quit): "),
+        }
+        io::stdout().flush()?;
+
+        let Some(line) = lines.next() else { break };
+        let answer = line?.trim().to_string();
+        let name = match (answer.as_str(), suggested) {
+            ("q", _) => break,
+            ("s", _) | ("", None) => continue,
+            ("", Some(hint)) => hint.clone(),
+            (typed, _) => typed.to_string(),
+        };
+
+        // A name collision (or any other failure) skips this sender only
+        if let Err(e) = super::contacts::add(&name, &candidate.sender.handle, "other", None, false) {
+            output::warn(format!("skipped {}: {:#}", candidate.sender.handle, e));
+            // Shown before the next prompt, not after the session
+            output::flush_warnings();
+        }
     }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contacts::manager::Contact;
+
+    fn info(handle: &str, count: i64, date: i64, pci: Option<&str>) -> helpers::HandleInfo {
+        helpers::HandleInfo {
+            handle: handle.to_string(),
+            message_count: count,
+            last_date: format!("d{}", date),
+            last_date_cocoa: date,
+            person_centric_id: pci.map(str::to_string),
+            variants: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_email_name_hint() {
+        let cases = [
+            ("john.smith@gmail.com", Some("John Smith")),
+            ("mary_jane-watson@x.org", Some("Mary Jane Watson")),
+            ("jdoe83+spam@icloud.com", Some("Jdoe")),
+            ("noreply@service.com", None),
+            ("12345@txt.att.net", None),
+            ("+14087771234", None),
+        ];
+        for (handle, expected) in cases {
+            assert_eq!(email_name_hint(handle).as_deref(), expected, "{}", handle);
+        }
+    }
+
+    #[test]
+    fn test_signature_line() {
+        let cases = [
+            ("See you at 5\n- John", Some("John")),
+            ("Running late, sorry! - John Smith", Some("John Smith")),
+            ("Thanks for dinner.\n~ Mary  ", Some("Mary")),
+            ("-Bob", Some("Bob")),
+            ("Meet at 5 - Tuesday works", None),
+            ("ok - see you", None),
+            ("no signature here", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(signature_line(text).as_deref(), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_signature_hint_needs_repeats() {
+        let texts: Vec<String> = [
+            "Call me back\n- Jim",
+            "Great, thanks. - Anna",
+            "lol",
+            "On my way\n- Jim",
+            "Sounds good. - Anna",
+            "- Anna",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(signature_hint(&texts).as_deref(), Some("Anna"));
+
+        let once = vec!["See you\n- Jim".to_string(), "ok".to_string()];
+        assert_eq!(signature_hint(&once), None);
+    }
+
+    #[test]
+    fn test_signature_hint_tie_prefers_recent() {
+        // Newest first, as returned by query_handle_recent_texts
+        let texts: Vec<String> = ["- Jim", "- Anna", "- Anna", "- Jim"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(signature_hint(&texts).as_deref(), Some("Jim"));
+    }
+
+    #[test]
+    fn test_name_hints_from_db() {
+        use crate::db::fixtures::*;
+
+        let conn = empty_db();
+        let h = insert_handle(&conn, "john.smith@gmail.com");
+        let other = insert_handle(&conn, "+14155550000");
+        let dm = insert_chat(&conn, "john.smith@gmail.com", None, &[h]);
+        insert_chat(&conn, "chat123", Some("Soccer Dads"), &[h, other]);
+        insert_message(&conn, dm, h, Some("Running late\n- John"), DAY_NS, false, true);
+        insert_message(&conn, dm, h, Some("Here now\n- John"), 2 * DAY_NS, false, true);
+        insert_message(&conn, dm, 0, Some("- Me"), 3 * DAY_NS, true, true);
+
+        let hints = name_hints(&conn, "john.smith@gmail.com").unwrap();
+        assert_eq!(hints, vec!["John", "John Smith", "Soccer Dads"]);
+    }
+
+    #[test]
+    fn test_merge_handles_by_contact_then_person_id() {
+        let contacts = ContactsManager::from_contacts(vec![Contact {
+            name: "Sarah".to_string(),
+            phone: "+14155551234".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: vec!["sarah@icloud.com".to_string()],
+        }]);
+
+        let merged = merge_handles(
+            vec![
+                info("+14155551234", 10, 50, None),
+                info("sarah@icloud.com", 5, 80, None),
+                info("+14085550000", 3, 60, Some("PCI-1")),
+                info("x@example.com", 4, 40, Some("PCI-1")),
+                info("+12125550000", 7, 60, None),
+            ],
+            &contacts,
+        );
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].contact_name.as_deref(), Some("Sarah"));
+        assert_eq!(merged[0].handles, vec!["+14155551234", "sarah@icloud.com"]);
+        assert_eq!(merged[0].message_count, 15);
+        assert_eq!(merged[0].last_message_date, "d80");
+
+        // Tie on last date (60): higher count first
+        assert_eq!(merged[1].handles, vec!["+14085550000", "x@example.com"]);
+        assert_eq!(merged[1].message_count, 7);
+        assert_eq!(merged[2].handles, vec!["+12125550000"]);
+    }
+}
@@ -0,0 +1,223 @@
+//! `doctor`: environment checks, and the text-mode `--explain` query plan printout.
+//!
+//! `doctor` checks that Messages.db opens, contacts load, and Messages
+//! accepts Apple events from this app (the Automation permission sends
+//! need). With
+//! `--performance` it also runs EXPLAIN QUERY PLAN for the main
+//! date-bounded queries (`queries::DATE_BOUNDED_QUERIES`) against the live
+//! database and flags full scans of `message`. It only suggests fixes
+//! (bounding by ROWID range); chat.db belongs to Messages.app, so no index is
+//! ever created.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - --explain with JSON output puts the plan in meta.query_plan instead of printing JSON on stderr
+//! - 10/16/2026 - automation check (osascript probe, AUTOMATION_DENIED guidance)
+//! - 10/16/2026 - Initial doctor [--performance] and --explain plan output
+
+use anyhow::Result;
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::applescript;
+use crate::contacts::manager::ContactsManager;
+use crate::db::connection::{self, PlanRow, QueryPlan};
+use crate::db::queries;
+use crate::error::{CliError, AUTOMATION_GUIDANCE};
+use crate::output::{self, OutputControls};
+
+/// One `doctor` check.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    /// Suggested fix (never applied)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// EXPLAIN QUERY PLAN rows (performance checks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<Vec<PlanRow>>,
+}
+
+/// `doctor` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DoctorReport {
+    pub ok: bool,
+    pub checks: Vec<Check>,
+}
+
+fn check(name: &str, ok: bool, detail: String) -> Check {
+    Check { name: name.to_string(), ok, detail, suggestion: None, plan: None }
+}
+
+/// Plan check for one query: flags full scans of message.
+fn plan_check(conn: &Connection, name: &str, sql: &str) -> Check {
+    let plan = match connection::explain_query_plan(conn, sql) {
+        Ok(plan) => plan,
+        Err(e) => return check(name, false, format!("EXPLAIN failed: {:#}", e)),
+    };
+    let scans = queries::message_scans(sql, &plan);
+    let mut result = match scans.as_slice() {
+        [] => check(name, true, "no full scan of message".to_string()),
+        rows => check(
+            name,
+            false,
+            format!(
+                "full scan of message: {}",
+                rows.iter().map(|r| r.detail.as_str()).collect::<Vec<_>>().join("; ")
+            ),
+        ),
+    };
+    let bounded: Vec<String> = scans
+        .iter()
+        .filter_map(|r| queries::scanned_table(&r.detail))
+        .filter(|alias| queries::rowid_range_applies(sql, alias))
+        .map(str::to_string)
+        .collect();
+    if let Some(alias) = bounded.first() {
+        result.suggestion = Some(format!(
+            "bound {alias}.ROWID from the first ROWID at the date cutoff ({alias}.ROWID >= ?) so SQLite \
+             searches the primary key; ROWIDs follow arrival order in chat.db"
+        ));
+    }
+    result.plan = Some(plan);
+    result
+}
+
+/// Automation check from the result of `applescript::probe_automation`.
+fn automation_check(probe: Result<()>) -> Check {
+    match probe {
+        Ok(()) => check("automation", true, "Messages accepts Apple events".to_string()),
+        Err(e) => match e.downcast_ref::<CliError>() {
+            Some(CliError::AutomationDenied { detail }) => {
+                let mut c = check("automation", false, format!("Apple events to Messages denied ({})", detail));
+                c.suggestion = Some(AUTOMATION_GUIDANCE.to_string());
+                c
+            }
+            _ => check("automation", false, format!("probe failed: {:#}", e)),
+        },
+    }
+}
+
+fn build(performance: bool, contacts: &ContactsManager) -> DoctorReport {
+    let mut checks = Vec::new();
+    let path = crate::config::active().db_path.value.clone();
+    let conn = connection::open_db();
+    checks.push(match &conn {
+        Ok(conn) => {
+            let max_rowid: Result<Option<i64>, _> = conn.query_row("SELECT MAX(ROWID) FROM message", [], |r| r.get(0));
+            match max_rowid {
+                Ok(max) => check("database", true, format!("{} (latest message ROWID {})", path.display(), max.unwrap_or(0))),
+                Err(e) => check("database", false, format!("{}: {}", path.display(), e)),
+            }
+        }
+        Err(e) => check("database", false, format!("{:#}", e)),
+    });
+    checks.push(check("contacts", true, format!("{} contacts loaded", contacts.all().len())));
+    checks.push(automation_check(applescript::probe_automation()));
+
+    if performance {
+        match &conn {
+            Ok(conn) => {
+                for (name, sql) in queries::DATE_BOUNDED_QUERIES {
+                    checks.push(plan_check(conn, name, sql));
+                }
+            }
+            Err(_) => checks.push(check("performance", false, "skipped: database unavailable".to_string())),
+        }
+    }
+
+    DoctorReport { ok: checks.iter().all(|c| c.ok), checks }
+}
+
+/// Run environment (and with `performance`, query plan) checks.
+pub fn doctor(performance: bool, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let report = build(performance, contacts);
+    if output.json {
+        output.print(&report)?;
+        return Ok(());
+    }
+    for c in &report.checks {
+        println!("[{}] {}: {}", if c.ok { "ok" } else { "!!" }, c.name, c.detail);
+        if let Some(ref suggestion) = c.suggestion {
+            println!("     suggestion: {}", suggestion);
+        }
+    }
+    Ok(())
+}
+
+/// Plan rows as an indented tree, one row per line.
+fn render_plan(plan: &[PlanRow]) -> String {
+    let depth = |row: &PlanRow| {
+        let mut depth = 0;
+        let mut parent = row.parent;
+        while let Some(p) = plan.iter().find(|r| r.id == parent) {
+            depth += 1;
+            parent = p.parent;
+        }
+        depth
+    };
+    plan.iter()
+        .map(|row| format!("{}{}", "  ".repeat(depth(row) + 1), row.detail))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `--explain` in text mode: print the primary query's plan on stderr,
+/// keeping stdout to the command's own output. JSON output carries it as
+/// `meta.query_plan` instead (`OutputControls::envelope_meta`).
+pub fn print_explain() {
+    let plan: Option<QueryPlan> = match connection::primary_query_plan() {
+        Ok(plan) => plan,
+        Err(e) => {
+            output::warn(format!("--explain: {:#}", e));
+            return;
+        }
+    };
+    match plan {
+        Some(plan) => eprintln!("Query plan ({:.1} ms):\n{}", plan.elapsed_ms, render_plan(&plan.plan)),
+        None => eprintln!("Query plan: no query ran locally (daemon-backed command?)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::empty_db;
+
+    #[test]
+    fn test_plan_check_flags_scan_and_suggests_rowid_range() {
+        let conn = empty_db();
+        let c = plan_check(&conn, "RECENT_MESSAGES", queries::RECENT_MESSAGES);
+        assert!(!c.ok);
+        assert!(c.detail.contains("SCAN m"), "{}", c.detail);
+        assert!(c.suggestion.as_deref().is_some_and(|s| s.starts_with("bound m.ROWID")));
+
+        let plan = c.plan.unwrap();
+        let rendered = render_plan(&plan);
+        assert!(rendered.lines().any(|l| l.trim() == "SCAN m"), "{}", rendered);
+
+        conn.execute_batch("CREATE INDEX message_idx_date ON message(date)").unwrap();
+        let c = plan_check(&conn, "MESSAGE_COUNTS_BETWEEN", queries::MESSAGE_COUNTS_BETWEEN);
+        assert!(c.ok && c.suggestion.is_none(), "{:?}", c);
+    }
+
+    #[test]
+    fn test_automation_check() {
+        let c = automation_check(Ok(()));
+        assert!(c.ok && c.suggestion.is_none());
+
+        let denied = anyhow::Error::new(CliError::AutomationDenied {
+            detail: "Not authorized to send Apple events to Messages. (-1743)".to_string(),
+        });
+        let c = automation_check(Err(denied));
+        assert!(!c.ok);
+        assert!(c.detail.contains("-1743"), "{}", c.detail);
+        assert_eq!(c.suggestion.as_deref(), Some(AUTOMATION_GUIDANCE));
+
+        let c = automation_check(Err(anyhow::anyhow!("osascript not found")));
+        assert!(!c.ok && c.suggestion.is_none());
+        assert!(c.detail.starts_with("probe failed:"), "{}", c.detail);
+    }
+}
@@ -0,0 +1,316 @@
+//! Drafts: `draft add/list/send/delete` over the review-first queue.
+//!
+//! Drafts store the phone the contact resolved to when written, but `send`
+//! resolves the contact again: contacts.json may have changed while the
+//! draft waited. A changed resolution is sent to the new phone, with a
+//! warning naming both.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - DraftSendResult.dry_run is serde(default), so the schema doesn't require it when omitted
+//! - 10/16/2026 - Initial draft add/list/send/delete
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::commands::messaging::{self, check_message_length, SendResult};
+use crate::contacts::manager::ContactsManager;
+use crate::drafts::{Draft, DraftStore, SentDraft};
+use crate::output::{self, OutputControls};
+
+/// Outcome of sending one draft.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DraftSendResult {
+    pub id: String,
+    pub contact: String,
+    /// Phone resolved when the draft was written
+    pub drafted_phone: String,
+    /// Phone resolved now (sent to); None if the contact no longer resolves
+    pub phone: Option<String>,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `draft send` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DraftSendReport {
+    pub results: Vec<DraftSendResult>,
+    pub sent: usize,
+    pub failed: usize,
+}
+
+/// `draft delete` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DraftDeleteResult {
+    pub deleted: bool,
+    pub draft: Draft,
+}
+
+/// Send path for one message (`messaging::deliver` outside tests).
+type Deliver<'a> = &'a dyn Fn(Option<&str>, &str, &str) -> Result<SendResult>;
+
+/// Phone and contact name for a name or phone, as `send` would resolve it.
+fn resolve(contacts: &ContactsManager, contact: &str) -> Option<(String, Option<String>)> {
+    let phone = contacts.resolve_to_phone(contact)?;
+    let name = contacts.find_by_phone(&phone).map(|c| c.name.clone());
+    Some((phone, name))
+}
+
+/// Queue a draft for a contact (name or phone).
+pub fn add(contact: &str, message: &str, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let (phone, name) = resolve(contacts, contact).ok_or_else(|| anyhow!("Contact '{}' not found", contact))?;
+    check_message_length(message)?;
+    let draft = DraftStore::open_default().add(contact, &phone, name.as_deref(), message)?;
+
+    if output.json {
+        output.print(&draft)?;
+    } else {
+        println!("Drafted {} for {} (id {})", describe(&draft), draft.phone, draft.id);
+        println!("Review with `draft list`, send with `draft send {}`.", draft.id);
+    }
+    Ok(())
+}
+
+/// Show pending drafts, or the sent log with `sent`.
+pub fn list(sent: bool, output: &OutputControls) -> Result<()> {
+    let store = DraftStore::open_default();
+    if sent {
+        return print_sent(&store.sent()?, output);
+    }
+
+    let drafts = store.list()?;
+    if output.json {
+        output.print(&drafts)?;
+        return Ok(());
+    }
+    if drafts.is_empty() {
+        println!("No drafts.");
+        return Ok(());
+    }
+    println!("Drafts ({}):", drafts.len());
+    println!("{:-<60}", "");
+    for draft in &drafts {
+        println!(
+            "[{}] {} to {} ({}): {}",
+            draft.id,
+            draft.created_at.format("%Y-%m-%d %H:%M"),
+            describe(draft),
+            draft.phone,
+            output::preview(&draft.message, 80)
+        );
+    }
+    Ok(())
+}
+
+fn print_sent(sent: &[SentDraft], output: &OutputControls) -> Result<()> {
+    if output.json {
+        output.print(&sent)?;
+        return Ok(());
+    }
+    if sent.is_empty() {
+        println!("No sent drafts.");
+        return Ok(());
+    }
+    println!("Sent drafts ({}):", sent.len());
+    println!("{:-<60}", "");
+    for entry in sent {
+        println!(
+            "[{}] {} to {} ({}): {}",
+            entry.draft.id,
+            entry.sent_at.format("%Y-%m-%d %H:%M"),
+            describe(&entry.draft),
+            entry.sent_to,
+            output::preview(&entry.draft.message, 80)
+        );
+    }
+    Ok(())
+}
+
+/// "Alice" for a name that resolved, "Alice → Alicia" for a fuzzy match, or the phone.
+fn describe(draft: &Draft) -> String {
+    match draft.contact_name {
+        Some(ref name) if name.eq_ignore_ascii_case(&draft.contact) => name.clone(),
+        Some(ref name) => format!("{} → {}", draft.contact, name),
+        None => draft.contact.clone(),
+    }
+}
+
+/// Send one draft (`id`) or all of them.
+pub fn send(id: Option<&str>, all: bool, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let ids = match (id, all) {
+        (Some(id), false) => Some(vec![id.to_string()]),
+        (None, true) => None,
+        _ => return Err(anyhow!("Give a draft id or --all")),
+    };
+    let report = send_drafts(&DraftStore::open_default(), ids.as_deref(), contacts, &messaging::deliver)?;
+
+    if output.json {
+        output.print(&report)?;
+    } else if report.results.is_empty() {
+        println!("No drafts to send.");
+    } else {
+        for r in &report.results {
+            let to = r.phone.as_deref().unwrap_or(&r.drafted_phone);
+            match (&r.error, r.dry_run) {
+                (Some(error), _) => println!("[{}] {} failed: {}", r.id, r.contact, error),
+                (None, true) => println!("[{}] Dry run: would send to {} ({})", r.id, r.contact, to),
+                (None, false) => println!("[{}] Sent to {} ({})", r.id, r.contact, to),
+            }
+        }
+    }
+
+    if report.failed > 0 {
+        return Err(anyhow!("{} of {} drafts failed to send", report.failed, report.results.len()));
+    }
+    Ok(())
+}
+
+/// Claim, re-resolve, and deliver drafts; failed ones go back in the queue.
+///
+/// Dry runs leave the queue untouched.
+pub(crate) fn send_drafts(
+    store: &DraftStore,
+    ids: Option<&[String]>,
+    contacts: &ContactsManager,
+    deliver: Deliver,
+) -> Result<DraftSendReport> {
+    let dry_run = messaging::dry_run();
+    let drafts = if dry_run {
+        let pending = store.list()?;
+        match ids {
+            None => pending,
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    pending
+                        .iter()
+                        .find(|d| &d.id == id)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("No draft with id '{}'", id))
+                })
+                .collect::<Result<_>>()?,
+        }
+    } else {
+        store.claim(ids)?
+    };
+
+    let mut results = Vec::with_capacity(drafts.len());
+    for draft in drafts {
+        let mut result = DraftSendResult {
+            id: draft.id.clone(),
+            contact: draft.contact.clone(),
+            drafted_phone: draft.phone.clone(),
+            phone: None,
+            success: false,
+            dry_run,
+            warning: None,
+            error: None,
+        };
+
+        let outcome = match resolve(contacts, &draft.contact) {
+            None => Err(anyhow!("Contact '{}' no longer resolves", draft.contact)),
+            Some((phone, _)) => {
+                if phone != draft.phone {
+                    let warning = format!(
+                        "draft {}: '{}' now resolves to {} (drafted for {}); sending to {}",
+                        draft.id, draft.contact, phone, draft.phone, phone
+                    );
+                    output::warn(warning.clone());
+                    result.warning = Some(warning);
+                }
+                result.phone = Some(phone.clone());
+                deliver(Some(&draft.contact), &phone, &draft.message).map(|_| phone)
+            }
+        };
+
+        match outcome {
+            Ok(phone) => {
+                result.success = true;
+                if !dry_run {
+                    store.record_sent(draft, &phone, Local::now())?;
+                }
+            }
+            Err(e) => {
+                result.error = Some(format!("{:#}", e));
+                if !dry_run {
+                    store.restore(draft)?;
+                }
+            }
+        }
+        results.push(result);
+    }
+
+    let sent = results.iter().filter(|r| r.success).count();
+    Ok(DraftSendReport { failed: results.len() - sent, sent, results })
+}
+
+/// Discard a pending draft.
+pub fn delete(id: &str, output: &OutputControls) -> Result<()> {
+    let draft = DraftStore::open_default().delete(id)?;
+    if output.json {
+        output.print(&DraftDeleteResult { deleted: true, draft })?;
+    } else {
+        println!("Deleted draft {}", draft.id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contacts::manager::Contact;
+    use std::cell::RefCell;
+
+    fn contacts(alice_phone: &str) -> ContactsManager {
+        ContactsManager::from_contacts(vec![Contact {
+            name: "Alice".to_string(),
+            phone: alice_phone.to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }])
+    }
+
+    #[test]
+    fn test_send_re_resolves_and_restores_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DraftStore::new(dir.path().join("drafts.json"));
+        let alice = store.add("Alice", "+14155550001", Some("Alice"), "hi alice").unwrap();
+        let ghost = store.add("Ghost", "+14155550009", None, "hi ghost").unwrap();
+
+        // Alice changed numbers since drafting; Ghost was removed from contacts
+        let sent = RefCell::new(Vec::new());
+        let deliver = |contact: Option<&str>, phone: &str, message: &str| {
+            sent.borrow_mut().push((phone.to_string(), message.to_string()));
+            Ok(SendResult {
+                success: true,
+                dry_run: false,
+                contact: contact.map(str::to_string),
+                phone: phone.to_string(),
+                message: Some(message.to_string()),
+                message_bytes: Some(message.len()),
+                message_chars: Some(message.chars().count()),
+            })
+        };
+        let report = send_drafts(&store, None, &contacts("+14155550002"), &deliver).unwrap();
+
+        assert_eq!((report.sent, report.failed), (1, 1));
+        assert_eq!(sent.borrow().as_slice(), [("+14155550002".to_string(), "hi alice".to_string())]);
+        let a = &report.results[0];
+        assert_eq!(a.id, alice.id);
+        assert_eq!(a.phone.as_deref(), Some("+14155550002"));
+        assert!(a.warning.as_deref().unwrap().contains("+14155550001"));
+        assert!(report.results[1].error.as_deref().unwrap().contains("no longer resolves"));
+
+        // Sent draft moved to the log; failed one is still queued under its id
+        assert_eq!(store.list().unwrap().iter().map(|d| d.id.clone()).collect::<Vec<_>>(), [ghost.id]);
+        let log = store.sent().unwrap();
+        assert_eq!((log[0].draft.id.as_str(), log[0].sent_to.as_str()), (alice.id.as_str(), "+14155550002"));
+    }
+}
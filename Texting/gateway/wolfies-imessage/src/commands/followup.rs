@@ -0,0 +1,455 @@
+//! Follow-up commands: `followup`, `followup snooze`, `followup unsnooze`.
+//!
+//! Unanswered questions are candidates from FOLLOWUP_UNANSWERED_QUESTIONS
+//! (a loose LIKE pre-filter) scored by `question_score`; only those at or
+//! above `min_question_score` are reported. Group chats, automated senders
+//! (contacts::classify) and snoozed contacts (snoozes.json) are filtered
+//! here so the CLI and the daemon's `followup` report the same items.
+//! System items (`db::item_kind`) are left out in SQL unless include_system.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Snoozes record the contact's other handles (ContactsManager::other_handle_keys)
+//! - 10/16/2026 - FollowupOptions.include_system (system items ignored by default)
+//! - 10/16/2026 - Moved followup out of analytics; question scoring, group/automated filters, snoozes
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::contacts::classify::classify;
+use crate::contacts::manager::ContactsManager;
+use crate::db::connection::open_db;
+use crate::db::helpers::{self, handle_key, HandleFilter};
+use crate::db::queries;
+use crate::output::{self, OutputControls};
+use crate::snoozes::{Snooze, SnoozeStore};
+
+/// Question candidates fetched before scoring and filtering.
+const CANDIDATE_LIMIT: u32 = helpers::FOLLOWUP_LIMIT * 4;
+
+/// Default `--min-question-score`.
+pub const DEFAULT_MIN_QUESTION_SCORE: f64 = 0.5;
+
+/// Words that open a question.
+const INTERROGATIVES: &[&str] = &[
+    "who", "what", "when", "where", "why", "how", "which", "can", "could", "would", "will", "should", "do",
+    "does", "did", "is", "are", "am",
+];
+
+/// Requests that want an answer even without a question mark.
+const REQUEST_PHRASES: &[&str] = &["can you", "could you", "would you", "let me know", "lmk", "are you free", "wdyt"];
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnansweredQuestion {
+    pub phone: String,
+    pub contact_name: Option<String>,
+    pub text: String,
+    pub date: String,
+    pub days_ago: i64,
+    pub chat_identifier: Option<String>,
+    pub is_group: bool,
+    /// 0.0-1.0, see `question_score`
+    pub question_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StaleConversation {
+    pub phone: String,
+    pub contact_name: Option<String>,
+    pub last_text: Option<String>,
+    pub last_date: String,
+    pub days_ago: i64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FollowUpReport {
+    pub unanswered_questions: Vec<UnansweredQuestion>,
+    pub stale_conversations: Vec<StaleConversation>,
+    pub total_items: usize,
+    /// Items left out because their contact is snoozed
+    pub snoozed_count: usize,
+}
+
+/// `followup` filters.
+#[derive(Debug, Clone)]
+pub struct FollowupOptions {
+    pub days: u32,
+    pub stale: u32,
+    pub relationship: Option<String>,
+    pub include_self: bool,
+    pub include_groups: bool,
+    pub min_question_score: f64,
+    pub exclude_automated: bool,
+    /// Count system items (FaceTime calls, renames, ...) as messages and replies
+    pub include_system: bool,
+}
+
+impl Default for FollowupOptions {
+    fn default() -> Self {
+        Self {
+            days: 30,
+            stale: 3,
+            relationship: None,
+            include_self: false,
+            include_groups: false,
+            min_question_score: DEFAULT_MIN_QUESTION_SCORE,
+            exclude_automated: false,
+            include_system: false,
+        }
+    }
+}
+
+/// `followup unsnooze` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnsnoozeResult {
+    pub unsnoozed: bool,
+    pub snooze: Snooze,
+}
+
+/// Lowercased words of `text` with URLs dropped, punctuation kept.
+fn words_without_urls(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter(|w| !w.contains("://") && !w.to_ascii_lowercase().starts_with("www."))
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// How much a message reads like a question that wants an answer (0.0-1.0).
+///
+/// A '?' (outside URLs) counts 0.6, 0.7 when it ends the message. A sentence
+/// opening with an interrogative ("when", "can", ...) or a request phrase
+/// ("let me know", "lmk") adds 0.3 next to a '?', 0.5 without one. A '?'
+/// on a single word with neither ("huh?", "??") stays at 0.3.
+pub fn question_score(text: &str) -> f64 {
+    let words = words_without_urls(text);
+    let joined = words.join(" ");
+    let has_mark = joined.contains('?');
+    let mut score: f64 = if joined.ends_with('?') {
+        0.7
+    } else if has_mark {
+        0.6
+    } else {
+        0.0
+    };
+    let bonus = if has_mark { 0.3 } else { 0.5 };
+
+    let interrogative = joined.split(['.', '!', '?', '\n']).any(|sentence| {
+        sentence
+            .split_whitespace()
+            .next()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+            .is_some_and(|w| INTERROGATIVES.contains(&w))
+    });
+    if interrogative {
+        score += bonus;
+    }
+
+    let spaced: String = joined
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '\'' { c } else { ' ' })
+        .collect();
+    let spaced = format!(" {} ", spaced.split_whitespace().collect::<Vec<_>>().join(" "));
+    let request = REQUEST_PHRASES.iter().any(|p| spaced.contains(&format!(" {} ", p)));
+    if request {
+        score += bonus;
+    }
+
+    let word_count = spaced.split_whitespace().count();
+    if !interrogative && !request && word_count <= 1 {
+        score = score.min(0.3);
+    }
+    (score.min(1.0) * 100.0).round() / 100.0
+}
+
+fn is_snoozed(snoozes: &[Snooze], handle: &str) -> bool {
+    snoozes.iter().any(|s| s.matches(handle))
+}
+
+/// Unanswered questions and stale conversations after all `followup` filters.
+///
+/// `snoozes` are the snoozes in effect (see `SnoozeStore::active_or_warn`).
+pub fn build_report(
+    conn: &Connection,
+    contacts: &ContactsManager,
+    opts: &FollowupOptions,
+    snoozes: &[Snooze],
+) -> Result<FollowUpReport> {
+    let filter = match opts.relationship.as_deref() {
+        Some(rel) => Some(HandleFilter::resolve(conn, &contacts.handles_for_relationship(rel)?)?),
+        None => None,
+    };
+    let mine = helpers::self_exclusion(conn, opts.include_self)?;
+    // Both queries carry their own LIMIT, so everything here is a post-filter
+    let keep = |phone: &str, text: Option<&str>| {
+        filter.as_ref().is_none_or(|f| f.matches(phone))
+            && !helpers::is_mine(mine.as_ref(), phone)
+            && !(opts.exclude_automated
+                && classify(phone, &text.map(str::to_string).into_iter().collect::<Vec<_>>()).is_automated())
+    };
+
+    let cutoff_cocoa = queries::days_ago_cocoa(opts.days);
+    let stale_threshold_ns = (opts.stale as i64) * 24 * 3600 * 1_000_000_000;
+    let mut snoozed_count = 0;
+
+    let mut unanswered_questions = Vec::new();
+    for q in helpers::query_unanswered_questions(
+        conn,
+        cutoff_cocoa,
+        stale_threshold_ns,
+        CANDIDATE_LIMIT,
+        opts.include_system,
+    )? {
+        if unanswered_questions.len() == helpers::FOLLOWUP_LIMIT as usize {
+            break;
+        }
+        let question_score = question_score(&q.text);
+        if (q.is_group && !opts.include_groups)
+            || question_score < opts.min_question_score
+            || !keep(&q.phone, Some(&q.text))
+        {
+            continue;
+        }
+        if is_snoozed(snoozes, &q.phone) {
+            snoozed_count += 1;
+            continue;
+        }
+        unanswered_questions.push(UnansweredQuestion {
+            contact_name: contacts.find_by_phone(&q.phone).map(|c| c.name.clone()),
+            phone: q.phone,
+            text: q.text,
+            date: q.date,
+            days_ago: q.days_ago,
+            chat_identifier: q.chat_identifier,
+            is_group: q.is_group,
+            question_score,
+        });
+    }
+
+    let mut stale_conversations = Vec::new();
+    for s in helpers::query_stale_conversations(conn, cutoff_cocoa, stale_threshold_ns, opts.include_system)? {
+        if !keep(&s.phone, s.last_text.as_deref()) {
+            continue;
+        }
+        if is_snoozed(snoozes, &s.phone) {
+            snoozed_count += 1;
+            continue;
+        }
+        stale_conversations.push(StaleConversation {
+            contact_name: contacts.find_by_phone(&s.phone).map(|c| c.name.clone()),
+            phone: s.phone,
+            last_text: s.last_text,
+            last_date: s.last_date,
+            days_ago: s.days_ago,
+        });
+    }
+
+    Ok(FollowUpReport {
+        total_items: unanswered_questions.len() + stale_conversations.len(),
+        unanswered_questions,
+        stale_conversations,
+        snoozed_count,
+    })
+}
+
+/// Resolve a snooze target (contact name, phone, or email) to its handle.
+fn resolve_handle(contact: &str, contacts: &ContactsManager) -> Result<String> {
+    if contact.contains('@') {
+        return Ok(contact.trim().to_string());
+    }
+    contacts
+        .resolve_to_phone(contact)
+        .ok_or_else(|| anyhow!("Contact '{}' not found", contact))
+}
+
+/// Snooze a contact's follow-ups until `until` (see `mutes::parse_until`).
+pub fn snooze_contact(
+    store: &SnoozeStore,
+    contact: &str,
+    until: &str,
+    contacts: &ContactsManager,
+    now: DateTime<Local>,
+) -> Result<Snooze> {
+    let handle = resolve_handle(contact, contacts)?;
+    let snooze = Snooze {
+        key: handle_key(&handle),
+        handles: contacts.other_handle_keys(&handle),
+        target: contact.to_string(),
+        name: contacts.find_by_phone(&handle).map(|c| c.name.clone()),
+        until: super::mutes::parse_until(until, now)?,
+        created_at: now,
+    };
+    store.add(snooze, now)
+}
+
+/// Remove a contact's snooze.
+pub fn unsnooze_contact(
+    store: &SnoozeStore,
+    contact: &str,
+    contacts: &ContactsManager,
+    now: DateTime<Local>,
+) -> Result<Snooze> {
+    let handle = resolve_handle(contact, contacts)?;
+    store
+        .remove_snooze(&handle_key(&handle), now)
+        .map_err(|_| anyhow!("'{}' is not snoozed", contact))
+}
+
+fn describe(snooze: &Snooze) -> String {
+    match snooze.name {
+        Some(ref name) => format!("{} ({})", name, snooze.target),
+        None => snooze.target.clone(),
+    }
+}
+
+/// Detect messages needing follow-up.
+pub fn followup(opts: &FollowupOptions, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let conn = open_db()?;
+    let snoozes = SnoozeStore::open_default().active_or_warn(Local::now());
+    let report = build_report(&conn, contacts, opts, &snoozes)?;
+
+    if output.json {
+        output.print(&report)?;
+        return Ok(());
+    }
+    println!("Follow-Up Report:");
+    println!("{:-<60}", "");
+    println!("Total items needing attention: {}", report.total_items);
+    if report.snoozed_count > 0 {
+        println!("Snoozed: {}", report.snoozed_count);
+    }
+    println!();
+
+    if !report.unanswered_questions.is_empty() {
+        println!("Unanswered Questions ({}):", report.unanswered_questions.len());
+        println!("{:-<60}", "");
+        for q in &report.unanswered_questions {
+            let contact = q.contact_name.as_deref().unwrap_or(&q.phone);
+            let group = if q.is_group { " (group)" } else { "" };
+            println!("[{} days ago] {}{}", q.days_ago, contact, group);
+            println!("  Q: {}", output::preview(&q.text, 80));
+        }
+        println!();
+    }
+
+    if !report.stale_conversations.is_empty() {
+        println!("Stale Conversations ({}):", report.stale_conversations.len());
+        println!("{:-<60}", "");
+        for s in &report.stale_conversations {
+            let contact = s.contact_name.as_deref().unwrap_or(&s.phone);
+            println!("[{} days ago] {}", s.days_ago, contact);
+            if let Some(ref text) = s.last_text {
+                println!("  Last: {}", output::preview(text, 80));
+            }
+        }
+    }
+
+    if report.total_items == 0 {
+        println!("No follow-ups needed. Great job staying on top of messages!");
+    }
+    Ok(())
+}
+
+/// Leave a contact out of `followup` until `until`.
+pub fn snooze(contact: &str, until: &str, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let snooze = snooze_contact(&SnoozeStore::open_default(), contact, until, contacts, Local::now())?;
+    if output.json {
+        output.print(&snooze)?;
+    } else {
+        println!("Snoozed {} until {}", describe(&snooze), snooze.until.format("%Y-%m-%d %H:%M"));
+    }
+    Ok(())
+}
+
+/// Bring a snoozed contact back into `followup`.
+pub fn unsnooze(contact: &str, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let snooze = unsnooze_contact(&SnoozeStore::open_default(), contact, contacts, Local::now())?;
+    if output.json {
+        output.print(&UnsnoozeResult { unsnoozed: true, snooze })?;
+    } else {
+        println!("Unsnoozed {}", describe(&snooze));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, set_item, DAY_NS};
+
+    #[test]
+    fn test_question_score() {
+        assert_eq!(question_score("When are you back?"), 1.0);
+        assert_eq!(question_score("dinner at 7 works?"), 0.7);
+        assert_eq!(question_score("lmk when you land"), 0.5);
+        assert_eq!(question_score("Where should we meet"), 0.5);
+        // Interrogative words mid-sentence and URLs don't count
+        assert_eq!(question_score("I know how it went"), 0.0);
+        assert_eq!(question_score("see https://example.com/?q=1"), 0.0);
+        assert_eq!(question_score("huh?"), 0.3);
+        assert_eq!(question_score("??"), 0.3);
+    }
+
+    #[test]
+    fn test_build_report_filters_and_snoozes() {
+        let conn = empty_db();
+        let now = queries::unix_to_cocoa(Local::now().timestamp());
+        let sam = insert_handle(&conn, "+14155550001");
+        let alex = insert_handle(&conn, "+14155550002");
+        let bank = insert_handle(&conn, "32665");
+        let sam_chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        let alex_chat = insert_chat(&conn, "+14155550002", None, &[alex]);
+        let bank_chat = insert_chat(&conn, "32665", None, &[bank]);
+        let group = insert_chat(&conn, "chat123456789", Some("Trip"), &[sam, alex]);
+        insert_message(&conn, sam_chat, sam, Some("Can you send the lease?"), now - 5 * DAY_NS, false, true);
+        insert_message(&conn, alex_chat, alex, Some("I know how it went"), now - 5 * DAY_NS, false, true);
+        insert_message(&conn, bank_chat, bank, Some("What is your PIN? Reply STOP to opt out"), now - 4 * DAY_NS, false, true);
+        insert_message(&conn, group, alex, Some("Who is driving?"), now - 3 * DAY_NS, false, true);
+
+        let opts = FollowupOptions { include_self: true, ..FollowupOptions::default() };
+        let report = build_report(&conn, &ContactsManager::empty(), &opts, &[]).unwrap();
+        let phones = |r: &FollowUpReport| r.unanswered_questions.iter().map(|q| q.phone.clone()).collect::<Vec<_>>();
+        assert_eq!(phones(&report), ["32665", "+14155550001"]);
+
+        let opts = FollowupOptions { include_groups: true, exclude_automated: true, ..opts };
+        let report = build_report(&conn, &ContactsManager::empty(), &opts, &[]).unwrap();
+        assert_eq!(phones(&report), ["+14155550002", "+14155550001"]);
+        assert!(report.unanswered_questions[0].is_group);
+
+        let snooze = Snooze {
+            key: handle_key("4155550001"),
+            handles: Vec::new(),
+            target: "4155550001".to_string(),
+            name: None,
+            until: Local::now() + chrono::Duration::days(1),
+            created_at: Local::now(),
+        };
+        let report = build_report(&conn, &ContactsManager::empty(), &opts, &[snooze]).unwrap();
+        assert_eq!(phones(&report), ["+14155550002"]);
+        assert!(report.snoozed_count >= 1);
+    }
+
+    #[test]
+    fn test_system_items_only_count_when_included() {
+        let conn = empty_db();
+        let now = queries::unix_to_cocoa(Local::now().timestamp());
+        let sam = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        insert_message(&conn, chat, sam, Some("Can you send the lease?"), now - 5 * DAY_NS, false, true);
+        // I FaceTimed Sam an hour later
+        let call = insert_message(&conn, chat, sam, None, now - 5 * DAY_NS + DAY_NS / 24, true, true);
+        set_item(&conn, call, 6, 0, None);
+
+        let opts = FollowupOptions { include_self: true, ..FollowupOptions::default() };
+        let report = build_report(&conn, &ContactsManager::empty(), &opts, &[]).unwrap();
+        assert_eq!(report.unanswered_questions.len(), 1);
+        assert_eq!(report.stale_conversations.len(), 1);
+
+        // Counted, the call answers the question and is the last word
+        let opts = FollowupOptions { include_system: true, ..opts };
+        let report = build_report(&conn, &ContactsManager::empty(), &opts, &[]).unwrap();
+        assert!(report.unanswered_questions.is_empty());
+        assert!(report.stale_conversations.is_empty());
+    }
+}
@@ -1,18 +1,36 @@
-//! Group commands: groups, group-messages.
+//! Group commands: groups, group-messages, group-analytics, group-history.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - group-messages --with-reactions (standing tapbacks per message, one query per page)
+//! - 10/16/2026 - groups and group-messages print JSON through OutputControls::print_json (--envelope)
+//! - 10/16/2026 - group-messages reads text through db::message_body (text cache, attachment placeholders)
+//! - 10/16/2026 - groups list fetches all participants in one query (was one query per chat)
+//! - 10/16/2026 - Text-mode previews use output::preview (char-safe, one line)
+//! - 10/16/2026 - Added group-history (participant add/remove/leave and rename events)
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - Participant filter escapes LIKE wildcards
+//! - 10/16/2026 - Added group-analytics (per-participant stats, reactions, mention response gap)
 //! - 01/10/2026 - Initial stub implementation (Claude)
 //! - 01/10/2026 - Implemented list groups command (Claude)
 //! - 01/10/2026 - Implemented group messages command (Claude)
 
-use anyhow::Result;
-use rusqlite;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use rusqlite::{self, Connection};
+use schemars::JsonSchema;
 use serde::Serialize;
 
-use crate::db::{blob_parser, connection::open_db, queries};
+use crate::commands::reading;
+use crate::contacts::manager::ContactsManager;
+use crate::db::message_body::{display_text, message_body};
+use crate::db::{connection::open_db, helpers, queries};
+use crate::output::{self, OutputControls};
 
-#[derive(Debug, Serialize)]
-struct GroupChat {
+/// How many busiest hours group-analytics reports.
+const BUSIEST_HOURS: u32 = 3;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct GroupChat {
     group_id: String,
     display_name: Option<String>,
     participants: Vec<String>,
@@ -21,78 +39,74 @@ struct GroupChat {
     message_count: i64,
 }
 
-#[derive(Debug, Serialize)]
-struct GroupMessage {
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct GroupMessage {
     message_id: i64,
     guid: String,
     text: String,
+    /// Attachment placeholder ("[photo]", ...); `text` is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    placeholder: Option<String>,
     is_from_me: bool,
     date: String,
     sender_handle: Option<String>,
     group_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     group_id: Option<String>,
+    /// Standing tapbacks (only with --with-reactions; absent when there are none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reactions: Vec<helpers::MessageReaction>,
 }
 
-/// List all group chats.
-pub fn list(limit: u32, json: bool) -> Result<()> {
-    let conn = open_db()?;
-
-    // Query group chats
+/// Group chats (two or more participants), most recently active first.
+///
+/// Participants for every listed chat come from one query, not one per chat.
+pub(crate) fn list_groups(conn: &Connection, limit: u32) -> Result<Vec<GroupChat>> {
     let mut stmt = conn.prepare(queries::LIST_GROUPS)?;
-    let chat_rows = stmt.query_map([limit as i64], |row: &rusqlite::Row| {
-        Ok((
-            row.get::<_, i64>(0)?,        // ROWID
-            row.get::<_, String>(1)?,     // chat_identifier
-            row.get::<_, Option<String>>(2)?, // display_name
-            row.get::<_, Option<i64>>(3)?,    // last_date
-            row.get::<_, i64>(4)?,        // msg_count
-        ))
-    })?;
+    let chat_rows = stmt
+        .query_map([limit as i64], |row: &rusqlite::Row| {
+            Ok((
+                row.get::<_, i64>(0)?,            // ROWID
+                row.get::<_, String>(1)?,         // chat_identifier
+                row.get::<_, Option<String>>(2)?, // display_name
+                row.get::<_, Option<i64>>(3)?,    // last_date
+                row.get::<_, i64>(4)?,            // msg_count
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
 
-    let mut groups = Vec::new();
-
-    for row_result in chat_rows {
-        let (chat_rowid, chat_identifier, display_name, last_date_cocoa, msg_count): (i64, String, Option<String>, Option<i64>, i64) = row_result?;
-
-        // Get participants for this chat
-        let mut participants_stmt = conn.prepare(queries::GROUP_PARTICIPANTS)?;
-        let participant_rows = participants_stmt.query_map([chat_rowid], |row: &rusqlite::Row| {
-            row.get::<_, String>(0)
-        })?;
+    let chat_ids: Vec<i64> = chat_rows.iter().map(|row| row.0).collect();
+    let mut participants_by_chat = helpers::query_chat_participants(conn, &chat_ids)?;
 
-        let participants: Vec<String> = participant_rows
-            .filter_map(|r: rusqlite::Result<String>| r.ok())
-            .collect();
+    let mut groups = Vec::new();
+    for (chat_rowid, chat_identifier, display_name, last_date_cocoa, msg_count) in chat_rows {
+        let participants = participants_by_chat.remove(&chat_rowid).unwrap_or_default();
 
         // Only include if it has multiple participants (group chat)
         if participants.len() < 2 {
             continue;
         }
 
-        // Convert Cocoa timestamp to ISO string
-        let last_message_date = last_date_cocoa.map(|cocoa_ns| {
-            let unix_ts = queries::cocoa_to_unix(cocoa_ns);
-            // Convert to ISO 8601 string
-            use std::time::{UNIX_EPOCH, Duration};
-            let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-            let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-            datetime.to_rfc3339()
-        });
-
         groups.push(GroupChat {
             group_id: chat_identifier,
             display_name,
-            participants: participants.clone(),
             participant_count: participants.len(),
-            last_message_date,
+            participants,
+            last_message_date: last_date_cocoa.map(helpers::cocoa_to_iso),
             message_count: msg_count,
         });
     }
+    Ok(groups)
+}
+
+/// List all group chats.
+pub fn list(limit: u32, output: &OutputControls) -> Result<()> {
+    let conn = open_db()?;
+    let groups = list_groups(&conn, limit)?;
 
     // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&groups)?);
+    if output.json {
+        output.print_json(&groups, true)?;
     } else {
         if groups.is_empty() {
             println!("No group chats found.");
@@ -116,10 +130,19 @@ pub fn list(limit: u32, json: bool) -> Result<()> {
 }
 
 /// Get messages from a group chat.
-pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, json: bool) -> Result<()> {
+///
+/// With `with_reactions`, each message carries its standing tapbacks (one
+/// more query for the page; reactors by handle).
+pub fn messages(
+    group_id: Option<&str>,
+    participant: Option<&str>,
+    limit: u32,
+    with_reactions: bool,
+    output: &OutputControls,
+) -> Result<()> {
     let conn = open_db()?;
 
-    let messages: Vec<GroupMessage> = if let Some(gid) = group_id {
+    let mut messages: Vec<GroupMessage> = if let Some(gid) = group_id {
         // Query by group_id
         let mut stmt = conn.prepare(queries::GROUP_MESSAGES)?;
         let msg_rows = stmt.query_map([gid, limit.to_string().as_str()], |row: &rusqlite::Row| {
@@ -131,17 +154,10 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
             let date_cocoa: i64 = row.get(5)?;
             let sender_handle: Option<String> = row.get(6)?;
             let group_name: Option<String> = row.get(7)?;
+            let hint: Option<String> = row.get(8)?;
 
-            // Extract text from blob or use text column
-            let text = if let Some(blob) = blob_col {
-                blob_parser::extract_text_from_blob(&blob)
-                    .ok()
-                    .flatten()
-                    .or(text_col)
-                    .unwrap_or_else(|| "[message content not available]".to_string())
-            } else {
-                text_col.unwrap_or_else(|| "[message content not available]".to_string())
-            };
+            let (text, placeholder) =
+                message_body(Some(&guid), text_col, blob_col.as_deref(), hint.as_deref()).into_parts();
 
             // Convert Cocoa timestamp to ISO string
             let unix_ts = queries::cocoa_to_unix(date_cocoa);
@@ -153,11 +169,13 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
                 message_id,
                 guid,
                 text,
+                placeholder,
                 is_from_me,
                 date: datetime.to_rfc3339(),
                 sender_handle,
                 group_name,
                 group_id: None,
+                reactions: Vec::new(),
             })
         })?;
 
@@ -165,7 +183,8 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
     } else if let Some(participant) = participant {
         // Query by participant
         let mut stmt = conn.prepare(queries::GROUP_MESSAGES_BY_PARTICIPANT)?;
-        let msg_rows = stmt.query_map([participant, limit.to_string().as_str()], |row: &rusqlite::Row| {
+        let pattern = queries::like_contains(participant);
+        let msg_rows = stmt.query_map([pattern.as_str(), limit.to_string().as_str()], |row: &rusqlite::Row| {
             let message_id: i64 = row.get(0)?;
             let guid: String = row.get(1)?;
             let text_col: Option<String> = row.get(2)?;
@@ -175,17 +194,10 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
             let sender_handle: Option<String> = row.get(6)?;
             let group_name: Option<String> = row.get(7)?;
             let group_id: String = row.get(8)?;
+            let hint: Option<String> = row.get(9)?;
 
-            // Extract text from blob or use text column
-            let text = if let Some(blob) = blob_col {
-                blob_parser::extract_text_from_blob(&blob)
-                    .ok()
-                    .flatten()
-                    .or(text_col)
-                    .unwrap_or_else(|| "[message content not available]".to_string())
-            } else {
-                text_col.unwrap_or_else(|| "[message content not available]".to_string())
-            };
+            let (text, placeholder) =
+                message_body(Some(&guid), text_col, blob_col.as_deref(), hint.as_deref()).into_parts();
 
             // Convert Cocoa timestamp to ISO string
             let unix_ts = queries::cocoa_to_unix(date_cocoa);
@@ -197,11 +209,13 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
                 message_id,
                 guid,
                 text,
+                placeholder,
                 is_from_me,
                 date: datetime.to_rfc3339(),
                 sender_handle,
                 group_name,
                 group_id: Some(group_id),
+                reactions: Vec::new(),
             })
         })?;
 
@@ -209,10 +223,17 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
     } else {
         return Err(anyhow::anyhow!("Either group_id or participant must be specified"));
     };
+    if with_reactions {
+        let rowids: Vec<i64> = messages.iter().map(|m| m.message_id).collect();
+        let mut reactions = helpers::query_message_reactions(&conn, &rowids)?;
+        for m in &mut messages {
+            m.reactions = reactions.remove(&m.guid).unwrap_or_default();
+        }
+    }
 
     // Output
-    if json {
-        println!("{}", serde_json::to_string_pretty(&messages)?);
+    if output.json {
+        output.print_json(&messages, true)?;
     } else {
         if messages.is_empty() {
             println!("No group messages found.");
@@ -227,7 +248,13 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
             } else {
                 msg.sender_handle.as_deref().unwrap_or("Unknown").to_string()
             };
-            println!("[{}] {}: {}", msg.date, sender, msg.text);
+            println!(
+                "[{}] {}: {}{}",
+                msg.date,
+                sender,
+                display_text(&msg.text, msg.placeholder.as_deref()),
+                reading::reaction_suffix(&msg.reactions)
+            );
             if let Some(ref gid) = msg.group_id {
                 println!("  Group: {} ({})", msg.group_name.as_deref().unwrap_or(""), gid);
             }
@@ -236,3 +263,497 @@ pub fn messages(group_id: Option<&str>, participant: Option<&str>, limit: u32, j
 
     Ok(())
 }
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ParticipantStats {
+    pub handle: String,
+    pub contact_name: Option<String>,
+    pub message_count: i64,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct HourCount {
+    pub hour: i64,
+    pub message_count: i64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GroupAnalytics {
+    pub group_id: String,
+    pub display_name: Option<String>,
+    pub analysis_period_days: u32,
+    pub total_messages: i64,
+    pub participants: Vec<ParticipantStats>,
+    pub my_message_count: i64,
+    pub my_participation_pct: f64,
+    pub most_reacted: Option<helpers::MostReacted>,
+    pub busiest_hours: Vec<HourCount>,
+    pub mentions_of_me: usize,
+    pub median_response_gap_secs: Option<i64>,
+}
+
+fn percentage(part: i64, total: i64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    ((part as f64) * 1000.0 / (total as f64)).round() / 10.0
+}
+
+/// Detects messages that address me: "you", or my name / @first-name.
+struct MentionMatcher {
+    pattern: Regex,
+}
+
+impl MentionMatcher {
+    fn new(my_name: Option<&str>) -> Self {
+        let mut alternatives = vec![r"\byou\b".to_string()];
+        if let Some(name) = my_name.map(str::trim).filter(|n| !n.is_empty()) {
+            alternatives.push(format!(r"\b{}\b", regex::escape(name)));
+            if let Some(first) = name.split_whitespace().next() {
+                alternatives.push(format!(r"@{}\b", regex::escape(first)));
+            }
+        }
+        let pattern = Regex::new(&format!("(?i){}", alternatives.join("|")))
+            .expect("escaped mention pattern is valid");
+        Self { pattern }
+    }
+
+    fn is_mention(&self, text: &str) -> bool {
+        self.pattern.is_match(text)
+    }
+}
+
+/// Median gap (seconds) between someone mentioning me and my next message.
+///
+/// Consecutive mentions before I reply count once, from the first of them.
+/// Returns (mention count, median gap).
+fn mention_response_gaps(
+    timeline: &[helpers::TimelineMessage],
+    matcher: &MentionMatcher,
+) -> (usize, Option<i64>) {
+    let mut mentions = 0;
+    let mut pending: Option<i64> = None;
+    let mut gaps: Vec<i64> = Vec::new();
+
+    for msg in timeline {
+        if msg.is_from_me {
+            if let Some(mentioned_at) = pending.take() {
+                gaps.push((msg.date_cocoa - mentioned_at) / 1_000_000_000);
+            }
+        } else if msg.text.as_deref().is_some_and(|t| matcher.is_mention(t)) {
+            mentions += 1;
+            pending.get_or_insert(msg.date_cocoa);
+        }
+    }
+
+    (mentions, median(&mut gaps))
+}
+
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2
+    } else {
+        values[mid]
+    })
+}
+
+/// Build group analytics for a chat (by chat_identifier or display name).
+pub fn build_group_analytics(
+    conn: &Connection,
+    contacts: &ContactsManager,
+    group: &str,
+    days: u32,
+    my_name: Option<&str>,
+) -> Result<GroupAnalytics> {
+    let chat = helpers::resolve_group(conn, group)?
+        .ok_or_else(|| anyhow!("Group '{}' not found", group))?;
+    let cutoff_cocoa = queries::days_ago_cocoa(days);
+
+    let senders = helpers::query_group_sender_counts(conn, chat.rowid, cutoff_cocoa)?;
+    let total: i64 = senders.iter().map(|s| s.message_count).sum();
+    let my_count: i64 = senders.iter().filter(|s| s.is_from_me).map(|s| s.message_count).sum();
+
+    let participants = senders
+        .into_iter()
+        .filter(|s| !s.is_from_me)
+        .map(|s| {
+            let handle = s.handle.unwrap_or_else(|| "Unknown".to_string());
+            ParticipantStats {
+                contact_name: contacts.find_by_phone(&handle).map(|c| c.name.clone()),
+                handle,
+                message_count: s.message_count,
+                percentage: percentage(s.message_count, total),
+            }
+        })
+        .collect();
+
+    let busiest_hours = helpers::query_group_busiest_hours(conn, chat.rowid, cutoff_cocoa, BUSIEST_HOURS)?
+        .into_iter()
+        .map(|(hour, message_count)| HourCount { hour, message_count })
+        .collect();
+
+    let timeline = helpers::query_group_timeline(conn, chat.rowid, cutoff_cocoa)?;
+    let (mentions_of_me, median_response_gap_secs) =
+        mention_response_gaps(&timeline, &MentionMatcher::new(my_name));
+
+    Ok(GroupAnalytics {
+        group_id: chat.chat_identifier,
+        display_name: chat.display_name,
+        analysis_period_days: days,
+        total_messages: total,
+        participants,
+        my_message_count: my_count,
+        my_participation_pct: percentage(my_count, total),
+        most_reacted: helpers::query_group_most_reacted(conn, chat.rowid, cutoff_cocoa)?,
+        busiest_hours,
+        mentions_of_me,
+        median_response_gap_secs,
+    })
+}
+
+/// Message statistics for one group chat.
+pub fn group_analytics(
+    group: &str,
+    days: u32,
+    my_name: Option<&str>,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = open_db()?;
+    let stats = build_group_analytics(&conn, contacts, group, days, my_name)?;
+
+    if output.json {
+        output.print(&stats)?;
+        return Ok(());
+    }
+
+    println!(
+        "Group Analytics: {} ({} days)",
+        stats.display_name.as_deref().unwrap_or(&stats.group_id),
+        stats.analysis_period_days
+    );
+    println!("{:-<60}", "");
+    println!("total_messages: {}", stats.total_messages);
+    println!(
+        "my_participation: {} messages ({:.1}%)",
+        stats.my_message_count, stats.my_participation_pct
+    );
+    if !stats.participants.is_empty() {
+        println!("participants:");
+        for p in &stats.participants {
+            let name = p.contact_name.as_deref().unwrap_or(&p.handle);
+            println!("  {}: {} ({:.1}%)", name, p.message_count, p.percentage);
+        }
+    }
+    if !stats.busiest_hours.is_empty() {
+        let hours: Vec<String> = stats
+            .busiest_hours
+            .iter()
+            .map(|h| format!("{:02}:00 ({})", h.hour, h.message_count))
+            .collect();
+        println!("busiest_hours: {}", hours.join(", "));
+    }
+    if let Some(ref m) = stats.most_reacted {
+        let preview = output::preview(m.text.as_deref().unwrap_or("[no text]"), 80);
+        println!("most_reacted ({} reactions): {}", m.reaction_count, preview);
+    }
+    println!("mentions_of_me: {}", stats.mentions_of_me);
+    if let Some(gap) = stats.median_response_gap_secs {
+        println!("median_response_gap: {}m", gap / 60);
+    }
+
+    Ok(())
+}
+
+/// Kind of group membership or name change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupAction {
+    Added,
+    Removed,
+    Left,
+    Renamed,
+}
+
+impl GroupAction {
+    /// Map message item_type/group_action_type to an action.
+    fn from_codes(item_type: i64, group_action_type: i64) -> Option<Self> {
+        match (item_type, group_action_type) {
+            (1, 0) => Some(Self::Added),
+            (1, 1) => Some(Self::Removed),
+            (2, _) => Some(Self::Renamed),
+            (3, 0) => Some(Self::Left),
+            _ => None,
+        }
+    }
+}
+
+/// One participant or name change in a group chat.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GroupEvent {
+    pub date: String,
+    pub action: GroupAction,
+    /// Contact name or handle of whoever made the change ("me" for my changes).
+    pub actor: String,
+    /// Participant added, removed, or who left (None for renames).
+    pub affected: Option<String>,
+    /// Group name after a rename.
+    pub new_name: Option<String>,
+}
+
+/// Participant and name-change history for one group chat.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GroupHistory {
+    pub group_id: String,
+    pub display_name: Option<String>,
+    /// Oldest first.
+    pub events: Vec<GroupEvent>,
+}
+
+/// Build the participant/rename history of a chat (by chat_identifier or display name).
+pub fn build_group_history(conn: &Connection, contacts: &ContactsManager, group: &str) -> Result<GroupHistory> {
+    let chat = helpers::resolve_group(conn, group)?
+        .ok_or_else(|| anyhow!("Group '{}' not found", group))?;
+    let name_of = |handle: &str| {
+        contacts
+            .find_by_phone(handle)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| handle.to_string())
+    };
+
+    let events = helpers::query_group_events(conn, chat.rowid)?
+        .into_iter()
+        .filter_map(|row| {
+            let action = GroupAction::from_codes(row.item_type, row.group_action_type)?;
+            let actor = match row.actor_handle.as_deref() {
+                _ if row.is_from_me => "me".to_string(),
+                Some(handle) => name_of(handle),
+                None => "Unknown".to_string(),
+            };
+            let affected = match action {
+                GroupAction::Renamed => None,
+                // Whoever left is the sender of the system message
+                GroupAction::Left => Some(actor.clone()),
+                GroupAction::Added | GroupAction::Removed => row.affected_handle.as_deref().map(name_of),
+            };
+            Some(GroupEvent {
+                date: helpers::cocoa_to_iso(row.date_cocoa),
+                action,
+                actor,
+                affected,
+                new_name: if action == GroupAction::Renamed { row.group_title } else { None },
+            })
+        })
+        .collect();
+
+    Ok(GroupHistory {
+        group_id: chat.chat_identifier,
+        display_name: chat.display_name,
+        events,
+    })
+}
+
+/// Participant and name-change history for one group chat.
+pub fn group_history(group: &str, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let conn = open_db()?;
+    let history = build_group_history(&conn, contacts, group)?;
+
+    if output.json {
+        output.print(&history)?;
+        return Ok(());
+    }
+
+    println!(
+        "Group History: {} ({} events)",
+        history.display_name.as_deref().unwrap_or(&history.group_id),
+        history.events.len()
+    );
+    println!("{:-<60}", "");
+    for e in &history.events {
+        let what = match e.action {
+            GroupAction::Added => format!("added {}", e.affected.as_deref().unwrap_or("someone")),
+            GroupAction::Removed => format!("removed {}", e.affected.as_deref().unwrap_or("someone")),
+            GroupAction::Left => "left the group".to_string(),
+            GroupAction::Renamed => match e.new_name.as_deref() {
+                Some(name) => format!("renamed the group to \"{}\"", name),
+                None => "removed the group name".to_string(),
+            },
+        };
+        println!("[{}] {} {}", e.date, e.actor, what);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::DAY_NS;
+
+    fn msg(is_from_me: bool, text: &str, secs: i64) -> helpers::TimelineMessage {
+        helpers::TimelineMessage {
+            is_from_me,
+            text: Some(text.to_string()),
+            date_cocoa: 700 * DAY_NS + secs * 1_000_000_000,
+        }
+    }
+
+    thread_local! {
+        static STATEMENTS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    fn count_statement(_sql: &str) {
+        STATEMENTS.with(|n| n.set(n.get() + 1));
+    }
+
+    #[test]
+    fn test_list_groups_single_participant_query() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+        let mut conn = empty_db();
+        let handles: Vec<i64> = (0..6).map(|i| insert_handle(&conn, &format!("+1415555000{}", i))).collect();
+        for g in 0..40 {
+            let members = [handles[g % 6], handles[(g + 1) % 6], handles[(g + 2) % 6]];
+            let chat = insert_chat(&conn, &format!("chat{}", 100 + g), None, &members);
+            insert_message(&conn, chat, members[0], Some("hi"), 700 * DAY_NS + g as i64, false, true);
+        }
+        // Named 1:1 chat: listed by LIST_GROUPS, dropped by the participant filter
+        let solo = insert_chat(&conn, "+14155550000", Some("Mom"), &[handles[0]]);
+        insert_message(&conn, solo, handles[0], Some("call me"), 800 * DAY_NS, false, true);
+
+        conn.trace(Some(count_statement));
+        STATEMENTS.with(|n| n.set(0));
+        let groups = list_groups(&conn, 100).unwrap();
+        conn.trace(None);
+
+        assert_eq!(groups.len(), 40);
+        // LIST_GROUPS plus one participants query, however many chats
+        assert_eq!(STATEMENTS.with(|n| n.get()), 2);
+        assert!(groups.iter().all(|g| g.participant_count == 3 && g.participants.len() == 3));
+        assert_eq!(groups[0].group_id, "chat139");
+        assert_eq!(groups[0].participants, ["+14155550003", "+14155550004", "+14155550005"]);
+    }
+
+    #[test]
+    fn test_mention_matcher() {
+        let matcher = MentionMatcher::new(Some("Wolfgang Schoenberger"));
+        assert!(matcher.is_mention("@Wolfgang are you in?"));
+        assert!(matcher.is_mention("ask wolfgang schoenberger"));
+        assert!(matcher.is_mention("what do YOU think"));
+        assert!(!matcher.is_mention("youth soccer at 5"));
+        assert!(!MentionMatcher::new(None).is_mention("@Wolfgang"));
+    }
+
+    #[test]
+    fn test_mention_response_gaps_median() {
+        let matcher = MentionMatcher::new(Some("Wolf"));
+        let timeline = vec![
+            msg(false, "@Wolf thoughts?", 0),
+            msg(false, "wolf??", 30), // still pending from the first mention
+            msg(true, "sure", 60),
+            msg(false, "can you grab ice?", 100),
+            msg(true, "yep", 400),
+            msg(false, "unrelated", 500),
+            msg(true, "also", 600),
+            msg(false, "you there?", 700),
+            msg(true, "here", 720),
+        ];
+        let (mentions, median_gap) = mention_response_gaps(&timeline, &matcher);
+        assert_eq!(mentions, 4);
+        // gaps: 60, 300, 20 -> median 60
+        assert_eq!(median_gap, Some(60));
+        assert_eq!(mention_response_gaps(&[], &matcher), (0, None));
+    }
+
+    #[test]
+    fn test_group_history_events() {
+        use crate::contacts::manager::Contact;
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let group = insert_chat(&conn, "chat42", Some("Ski Trip"), &[alice, bob]);
+        let base = 700 * DAY_NS;
+
+        insert_message(&conn, group, alice, Some("hi all"), base, false, true);
+        let add = insert_message(&conn, group, alice, None, base + 1, false, true);
+        let rename = insert_message(&conn, group, 0, None, base + 2, true, true);
+        let left = insert_message(&conn, group, bob, None, base + 3, false, true);
+        // Group icon change: not a participant or name event
+        let icon = insert_message(&conn, group, alice, None, base + 4, false, true);
+        for (rowid, item_type, action, other, title) in [
+            (add, 1, 0, bob, None),
+            (rename, 2, 0, 0, Some("Ski Trip 2027")),
+            (left, 3, 0, 0, None),
+            (icon, 3, 1, 0, None),
+        ] {
+            conn.execute(
+                "UPDATE message SET item_type = ?1, group_action_type = ?2, other_handle = ?3, group_title = ?4 WHERE ROWID = ?5",
+                rusqlite::params![item_type, action, other, title, rowid],
+            )
+            .unwrap();
+        }
+
+        let contacts = ContactsManager::from_contacts(vec![Contact {
+            name: "Alice".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }]);
+        let history = build_group_history(&conn, &contacts, "ski").unwrap();
+        assert_eq!(history.group_id, "chat42");
+
+        let summary: Vec<(GroupAction, &str, Option<&str>, Option<&str>)> = history
+            .events
+            .iter()
+            .map(|e| (e.action, e.actor.as_str(), e.affected.as_deref(), e.new_name.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (GroupAction::Added, "Alice", Some("+14155550002"), None),
+                (GroupAction::Renamed, "me", None, Some("Ski Trip 2027")),
+                (GroupAction::Left, "+14155550002", Some("+14155550002"), None),
+            ]
+        );
+        assert!(build_group_history(&conn, &contacts, "nope").is_err());
+    }
+
+    #[test]
+    fn test_group_history_without_event_columns() {
+        use crate::db::fixtures::{insert_chat, insert_handle};
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            &crate::db::fixtures::SCHEMA
+                .replace("    item_type INTEGER DEFAULT 0,\n", "")
+                .replace("    other_handle INTEGER DEFAULT 0,\n", ""),
+        )
+        .unwrap();
+        let alice = insert_handle(&conn, "+14155550001");
+        insert_chat(&conn, "chat42", Some("Ski Trip"), &[alice]);
+
+        let history = build_group_history(&conn, &ContactsManager::empty(), "chat42").unwrap();
+        assert!(history.events.is_empty());
+    }
+
+    #[test]
+    fn test_median_even() {
+        assert_eq!(median(&mut [40, 10, 30, 20]), Some(25));
+        assert_eq!(median(&mut []), None);
+    }
+
+    #[test]
+    fn test_percentage_rounds_to_one_decimal() {
+        assert_eq!(percentage(1, 3), 33.3);
+        assert_eq!(percentage(0, 0), 0.0);
+    }
+}
@@ -0,0 +1,486 @@
+//! Links command: shared URLs, deduplicated by normalized form.
+//!
+//! Tracking parameters (utm_*, fbclid) are stripped and hosts lowercased so
+//! the same link shared five times shows up once with a count. Title fetching
+//! touches the network, so it's opt-in (`--fetch-titles`) and bounded.
+//!
+//! Candidates are messages whose text column mentions http, plus messages
+//! with no text column but an attributedBody (their URLs are only known
+//! after decoding); `limit` counts messages that turned out to share a link.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - attributedBody-only messages are candidates too (filtered after decoding); limit counts messages with links
+//! - 10/16/2026 - LinkSort derives clap::ValueEnum (one source for --sort values; dropped LinkSort::parse)
+//! - 10/16/2026 - JSON goes through OutputControls::print_json (--envelope)
+//! - 10/16/2026 - --csv output
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - Prefer link entities from attributedBody over regex extraction
+//! - 10/16/2026 - Moved from reading.rs; added dedupe, counts, sorting, --fetch-titles
+
+use anyhow::Result;
+use regex::Regex;
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::{blob_parser, connection};
+use crate::db::helpers::cocoa_to_iso;
+use crate::output::OutputControls;
+
+/// Max title fetches per invocation.
+pub const MAX_TITLE_FETCHES: usize = 20;
+/// Concurrent title fetches.
+const FETCH_CONCURRENCY: usize = 4;
+/// Per-request timeout for title fetches.
+const FETCH_TIMEOUT_SECS: u32 = 5;
+/// Bytes of the page read when looking for <title>.
+const FETCH_MAX_BYTES: u32 = 64 * 1024;
+
+/// Sort order for the links listing (`--sort recent|frequency`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkSort {
+    /// Most recently shared first.
+    Recent,
+    /// Most often shared first (ties broken by recency).
+    Frequency,
+}
+
+/// One URL occurrence in a message.
+#[derive(Debug, Clone)]
+pub struct LinkOccurrence {
+    pub url: String,
+    pub date_cocoa: i64,
+    /// "me", a contact name, or the sender handle.
+    pub shared_by: String,
+}
+
+/// A deduplicated link with who shared it and how often.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SharedLink {
+    pub url: String,
+    pub count: usize,
+    pub shared_by: Vec<String>,
+    pub first_shared: String,
+    pub last_shared: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip)]
+    last_cocoa: i64,
+}
+
+/// Normalize a URL for deduplication.
+///
+/// Lowercases scheme and host, drops utm_* / fbclid query params, an empty
+/// query or fragment, and trailing punctuation picked up from prose.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim_end_matches(['.', ',', ')', '!', '?', ';', ':', '\'', '"']);
+
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((r, f)) => (r, Some(f).filter(|f| !f.is_empty())),
+        None => (rest, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((r, q)) => (r, Some(q)),
+        None => (rest, None),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+
+    let mut out = format!("{}://{}{}", scheme.to_lowercase(), host.to_lowercase(), path);
+
+    let kept: Vec<&str> = query
+        .unwrap_or("")
+        .split('&')
+        .filter(|param| {
+            let key = param.split('=').next().unwrap_or("").to_lowercase();
+            !param.is_empty() && !key.starts_with("utm_") && key != "fbclid"
+        })
+        .collect();
+    if !kept.is_empty() {
+        out.push('?');
+        out.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        out.push('#');
+        out.push_str(fragment);
+    }
+    out
+}
+
+/// Group occurrences by normalized URL, counting shares and sharers.
+pub fn dedupe_links(occurrences: Vec<LinkOccurrence>, sort: LinkSort) -> Vec<SharedLink> {
+    let mut by_url: HashMap<String, SharedLink> = HashMap::new();
+    let mut first_cocoa: HashMap<String, i64> = HashMap::new();
+
+    for occ in occurrences {
+        let url = normalize_url(&occ.url);
+        let link = by_url.entry(url.clone()).or_insert_with(|| SharedLink {
+            url: url.clone(),
+            count: 0,
+            shared_by: Vec::new(),
+            first_shared: String::new(),
+            last_shared: String::new(),
+            title: None,
+            last_cocoa: i64::MIN,
+        });
+        link.count += 1;
+        if !link.shared_by.contains(&occ.shared_by) {
+            link.shared_by.push(occ.shared_by);
+        }
+        link.last_cocoa = link.last_cocoa.max(occ.date_cocoa);
+        let first = first_cocoa.entry(url).or_insert(i64::MAX);
+        *first = (*first).min(occ.date_cocoa);
+    }
+
+    let mut links: Vec<SharedLink> = by_url
+        .into_values()
+        .map(|mut link| {
+            link.first_shared = cocoa_to_iso(first_cocoa[&link.url]);
+            link.last_shared = cocoa_to_iso(link.last_cocoa);
+            link
+        })
+        .collect();
+
+    match sort {
+        LinkSort::Recent => links.sort_by_key(|link| std::cmp::Reverse(link.last_cocoa)),
+        LinkSort::Frequency => links.sort_by(|a, b| {
+            b.count.cmp(&a.count).then(b.last_cocoa.cmp(&a.last_cocoa))
+        }),
+    }
+    links
+}
+
+/// Fetches a page title for a URL.
+pub trait TitleFetcher: Sync {
+    fn fetch_title(&self, url: &str) -> Option<String>;
+}
+
+/// Title fetcher backed by `curl` (bounded time and bytes).
+pub struct CurlFetcher;
+
+impl TitleFetcher for CurlFetcher {
+    fn fetch_title(&self, url: &str) -> Option<String> {
+        let output = Command::new("curl")
+            .args(["--silent", "--location", "--max-redirs", "3"])
+            .args(["--max-time", &FETCH_TIMEOUT_SECS.to_string()])
+            .args(["--range", &format!("0-{}", FETCH_MAX_BYTES - 1)])
+            .args(["--proto", "=http,https"])
+            .arg("--")
+            .arg(url)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let body = &output.stdout[..output.stdout.len().min(FETCH_MAX_BYTES as usize)];
+        extract_title(&String::from_utf8_lossy(body))
+    }
+}
+
+/// URLs in a message: link entities from the blob when present, else a
+/// regex over the text column (or the blob's text when the column is empty).
+pub fn message_urls(text: Option<&str>, blob: Option<&[u8]>, url_regex: &Regex) -> Vec<String> {
+    let parsed = blob.and_then(|b| blob_parser::parse_blob(b).ok().flatten());
+    if let Some(parsed) = parsed.as_ref().filter(|p| !p.entities.links.is_empty()) {
+        return parsed.entities.links.clone();
+    }
+    let text = text.or(parsed.as_ref().map(|p| p.text.as_str())).unwrap_or("");
+    url_regex
+        .find_iter(text)
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// URL occurrences in the `limit` most recent messages that share a link.
+///
+/// Messages with an attributedBody but no text column can't be matched in
+/// SQL, so they're decoded and kept only if they yield URLs.
+pub fn link_occurrences(conn: &Connection, limit: u32, contacts: &ContactsManager) -> Result<Vec<LinkOccurrence>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            message.text,
+            message.attributedBody,
+            message.date,
+            message.is_from_me,
+            handle.id
+        FROM message
+        LEFT JOIN handle ON message.handle_id = handle.ROWID
+        WHERE message.text LIKE '%http%'
+           OR (message.text IS NULL AND message.attributedBody IS NOT NULL)
+        ORDER BY message.date DESC
+        "#,
+    )?;
+
+    let url_regex = Regex::new(r#"https?://[^\s<>"]+"#)?;
+    let mut occurrences = Vec::new();
+    let mut messages = 0;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<Vec<u8>>>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i32>(3)?,
+            row.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+
+    for row_result in rows {
+        if messages >= limit {
+            break;
+        }
+        let (text, blob, date, is_from_me, handle_id) = row_result?;
+        let urls = message_urls(text.as_deref(), blob.as_deref(), &url_regex);
+        if urls.is_empty() {
+            continue;
+        }
+        messages += 1;
+        let shared_by = if is_from_me != 0 {
+            "me".to_string()
+        } else {
+            let handle = handle_id.unwrap_or_else(|| "unknown".to_string());
+            contacts.find_by_phone(&handle).map(|c| c.name.clone()).unwrap_or(handle)
+        };
+        for url in urls {
+            occurrences.push(LinkOccurrence {
+                url,
+                date_cocoa: date,
+                shared_by: shared_by.clone(),
+            });
+        }
+    }
+    Ok(occurrences)
+}
+
+/// Pull the <title> (or og:title) out of an HTML page.
+pub fn extract_title(html: &str) -> Option<String> {
+    let title_re = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").ok()?;
+    let og_re = Regex::new(r#"(?is)<meta[^>]+property=["']og:title["'][^>]+content=["']([^"']*)["']"#).ok()?;
+
+    let raw = title_re
+        .captures(html)
+        .or_else(|| og_re.captures(html))
+        .map(|c| c[1].to_string())?;
+    let title = raw
+        .replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// Fill `title` for up to `max` links, `FETCH_CONCURRENCY` at a time.
+pub fn fill_titles(links: &mut [SharedLink], fetcher: &dyn TitleFetcher, max: usize) {
+    let count = links.len().min(max);
+    for batch in links[..count].chunks_mut(FETCH_CONCURRENCY) {
+        std::thread::scope(|scope| {
+            for link in batch.iter_mut() {
+                scope.spawn(move || link.title = fetcher.fetch_title(&link.url));
+            }
+        });
+    }
+}
+
+/// Extract URLs shared in conversations.
+#[allow(clippy::too_many_arguments)]
+pub fn links(
+    _contact: Option<&str>,
+    _days: Option<u32>,
+    _all_time: bool,
+    limit: u32,
+    sort: LinkSort,
+    fetch_titles: bool,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = connection::open_db()?;
+    let occurrences = link_occurrences(&conn, limit, contacts)?;
+
+    let mut links = dedupe_links(occurrences, sort);
+    if fetch_titles {
+        fill_titles(&mut links, &CurlFetcher, MAX_TITLE_FETCHES);
+    }
+
+    if output.csv {
+        output.print_csv(&links)?;
+    } else if output.json {
+        output.print_json(&links, false)?;
+    } else {
+        if links.is_empty() {
+            println!("No links found.");
+            return Ok(());
+        }
+
+        println!("Shared Links ({}):", links.len());
+        println!("{}", "-".repeat(60));
+        for link in &links {
+            println!("{} (x{}, {})", link.url, link.count, link.shared_by.join(", "));
+            if let Some(ref title) = link.title {
+                println!("  {}", title);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn occ(url: &str, date: i64, by: &str) -> LinkOccurrence {
+        LinkOccurrence {
+            url: url.to_string(),
+            date_cocoa: date,
+            shared_by: by.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_message_urls_prefers_entities() {
+        let url_regex = Regex::new(r#"https?://[^\s<>"]+"#).unwrap();
+        let blob = include_bytes!("../db/testdata/attributed_body/link.bin");
+
+        // Text column with a truncated URL: the link entity wins
+        let urls = message_urls(Some("Dinner spot: https://example.com/me"), Some(blob), &url_regex);
+        assert_eq!(urls, vec!["https://example.com/menu"]);
+
+        // No blob: regex over the text
+        let urls = message_urls(Some("see https://a.example/x and http://b.example"), None, &url_regex);
+        assert_eq!(urls, vec!["https://a.example/x", "http://b.example"]);
+    }
+
+    #[test]
+    fn test_link_occurrences_include_attributed_body_only_messages() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+        let conn = empty_db();
+        let sam = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        insert_message(&conn, chat, sam, Some("see https://a.example/x"), 1, false, true);
+        // Text only in attributedBody: a link entity, and plain text with no link
+        let link = insert_message(&conn, chat, sam, None, 2, false, true);
+        let blob = include_bytes!("../db/testdata/attributed_body/link.bin").to_vec();
+        conn.execute("UPDATE message SET attributedBody = ?1 WHERE ROWID = ?2", rusqlite::params![blob, link])
+            .unwrap();
+        let plain = insert_message(&conn, chat, sam, None, 3, false, true);
+        let blob = include_bytes!("../db/testdata/attributed_body/plain.bin").to_vec();
+        conn.execute("UPDATE message SET attributedBody = ?1 WHERE ROWID = ?2", rusqlite::params![blob, plain])
+            .unwrap();
+
+        let contacts = ContactsManager::empty();
+        let urls = |limit| {
+            link_occurrences(&conn, limit, &contacts)
+                .unwrap()
+                .into_iter()
+                .map(|o| o.url)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(urls(10), vec!["https://example.com/menu", "https://a.example/x"]);
+        // The linkless blob doesn't use up the limit
+        assert_eq!(urls(1), vec!["https://example.com/menu"]);
+    }
+
+    #[test]
+    fn test_normalize_strips_tracking_and_lowercases_host() {
+        assert_eq!(
+            normalize_url("HTTPS://WWW.YouTube.com/watch?v=AbC&utm_source=x&fbclid=123"),
+            "https://www.youtube.com/watch?v=AbC"
+        );
+        assert_eq!(
+            normalize_url("https://example.com/Path?utm_medium=a&UTM_campaign=b"),
+            "https://example.com/Path"
+        );
+        assert_eq!(normalize_url("https://example.com/a#"), "https://example.com/a");
+        assert_eq!(normalize_url("https://example.com/a#frag"), "https://example.com/a#frag");
+        assert_eq!(normalize_url("https://example.com/a)."), "https://example.com/a");
+        assert_eq!(normalize_url("https://Example.com"), "https://example.com");
+    }
+
+    #[test]
+    fn test_dedupe_counts_and_sharers() {
+        let links = dedupe_links(
+            vec![
+                occ("https://youtube.com/watch?v=1&utm_source=ig", 50, "Alice"),
+                occ("https://YOUTUBE.com/watch?v=1", 40, "me"),
+                occ("https://youtube.com/watch?v=1", 30, "Alice"),
+                occ("https://other.com", 60, "Bob"),
+            ],
+            LinkSort::Frequency,
+        );
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].url, "https://youtube.com/watch?v=1");
+        assert_eq!(links[0].count, 3);
+        assert_eq!(links[0].shared_by, vec!["Alice", "me"]);
+        assert_eq!(links[0].last_cocoa, 50);
+        assert_eq!(links[1].url, "https://other.com");
+    }
+
+    #[test]
+    fn test_sort_recent() {
+        let links = dedupe_links(
+            vec![
+                occ("https://a.com", 10, "me"),
+                occ("https://a.com", 20, "me"),
+                occ("https://b.com", 30, "me"),
+            ],
+            LinkSort::Recent,
+        );
+        assert_eq!(links[0].url, "https://b.com");
+        use clap::ValueEnum;
+        assert_eq!(LinkSort::from_str("frequency", false).unwrap(), LinkSort::Frequency);
+        assert!(LinkSort::from_str("alpha", false).is_err());
+    }
+
+    struct StubFetcher {
+        calls: AtomicUsize,
+    }
+
+    impl TitleFetcher for StubFetcher {
+        fn fetch_title(&self, url: &str) -> Option<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Some(format!("Title of {}", url))
+        }
+    }
+
+    #[test]
+    fn test_fill_titles_is_bounded() {
+        let occurrences = (0..30).map(|i| occ(&format!("https://site{}.com", i), i, "me")).collect();
+        let mut links = dedupe_links(occurrences, LinkSort::Recent);
+        let fetcher = StubFetcher { calls: AtomicUsize::new(0) };
+
+        fill_titles(&mut links, &fetcher, MAX_TITLE_FETCHES);
+
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), MAX_TITLE_FETCHES);
+        assert_eq!(links[0].title.as_deref(), Some("Title of https://site29.com"));
+        assert!(links[MAX_TITLE_FETCHES].title.is_none());
+    }
+
+    #[test]
+    fn test_extract_title() {
+        assert_eq!(
+            extract_title("<html><head><title>\n  Rust &amp; Cargo\n</title></head>"),
+            Some("Rust & Cargo".to_string())
+        );
+        assert_eq!(
+            extract_title(r#"<meta property="og:title" content="OG Title">"#),
+            Some("OG Title".to_string())
+        );
+        assert_eq!(extract_title("<p>no title</p>"), None);
+    }
+}
@@ -0,0 +1,370 @@
+//! `maintenance status` / `maintenance prune`: inventory and clean up the
+//! data directory (~/.wolfies-imessage).
+//!
+//! Every file is described by its `data_files` registry entry. `prune`
+//! deletes only what can be rebuilt or is left over: `--caches` (the text
+//! cache and its sidecars, kept while a daemon is running since it holds
+//! the cache open), `--logs` (rotated daemon logs), and with `--all` also
+//! sockets and pid files whose daemon is gone. Contacts backups and pending
+//! drafts/scheduled sends are only deleted by `--all --force`; config, user
+//! state, the live daemon log, lock files, and unknown files never are.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - --logs restored (the daemon rotates daemon.log at start)
+//! - 10/16/2026 - Caches are kept while a daemon is running
+//! - 10/16/2026 - Initial maintenance status and prune
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::data_files::{self, DataClass};
+use crate::output::OutputControls;
+use crate::storage;
+
+/// One file or directory in the data directory.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DataEntry {
+    pub name: String,
+    pub path: String,
+    pub class: DataClass,
+    /// What the registry says it is (None for unknown files)
+    pub about: Option<String>,
+    pub is_dir: bool,
+    /// Size, including everything under a directory
+    pub bytes: u64,
+    /// Last modified (RFC 3339, local time)
+    pub modified: Option<String>,
+    /// Sockets and pid files: whether the daemon that owns them is gone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale: Option<bool>,
+    #[serde(skip)]
+    modified_at: Option<SystemTime>,
+}
+
+/// `maintenance status` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DataStatus {
+    pub dir: String,
+    pub exists: bool,
+    pub total_bytes: u64,
+    pub entries: Vec<DataEntry>,
+}
+
+/// What `maintenance prune` selects.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneOptions {
+    pub caches: bool,
+    pub logs: bool,
+    /// Everything deletable: caches, rotated logs, stale sockets and pid files
+    pub all: bool,
+    /// With `all`, also contacts backups and pending drafts/scheduled sends
+    pub force: bool,
+    /// Only entries last modified longer ago than this
+    pub older_than: Option<Duration>,
+}
+
+/// An entry `prune` selected but left alone.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SkippedEntry {
+    pub name: String,
+    pub class: DataClass,
+    pub reason: String,
+}
+
+/// `maintenance prune` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PruneReport {
+    pub dir: String,
+    pub removed: Vec<DataEntry>,
+    pub freed_bytes: u64,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+fn size_of(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| size_of(&e.path())).sum())
+        .unwrap_or_default()
+}
+
+fn pid_alive(pid: i32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists
+    let rc = unsafe { libc::kill(pid, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+fn pid_in(pid_file: &Path) -> Option<i32> {
+    std::fs::read_to_string(pid_file).ok()?.trim().parse().ok().filter(|&pid| pid > 0)
+}
+
+/// Whether the daemon behind a socket or pid file is gone: the pid file's
+/// process is dead, or (without a pid file) nothing accepts on the socket.
+fn is_stale(path: &Path, name: &str) -> bool {
+    if name.ends_with(".pid") {
+        return !pid_in(path).is_some_and(pid_alive);
+    }
+    let pid_file = PathBuf::from(format!("{}.pid", path.display()));
+    match pid_in(&pid_file) {
+        Some(pid) => !pid_alive(pid),
+        None => UnixStream::connect(path).is_err(),
+    }
+}
+
+/// Inventory of `dir`, sorted by name.
+pub fn status_of(dir: &Path) -> Result<DataStatus> {
+    let mut entries = Vec::new();
+    let listing = match std::fs::read_dir(dir) {
+        Ok(listing) => Some(listing),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", dir)),
+    };
+    for item in listing.into_iter().flatten() {
+        let item = item?;
+        let name = item.file_name().to_string_lossy().into_owned();
+        let path = item.path();
+        let meta = std::fs::symlink_metadata(&path)?;
+        let registered = data_files::classify(&name);
+        let class = registered.map_or(DataClass::Unknown, |entry| entry.class);
+        let stale = (class == DataClass::Runtime).then(|| is_stale(&path, &name));
+        let modified_at = meta.modified().ok();
+        entries.push(DataEntry {
+            about: registered.map(|entry| entry.about.to_string()),
+            path: path.display().to_string(),
+            class,
+            is_dir: meta.is_dir(),
+            bytes: size_of(&path),
+            modified: modified_at.map(|t| DateTime::<Local>::from(t).to_rfc3339()),
+            stale,
+            modified_at,
+            name,
+        });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(DataStatus {
+        dir: dir.display().to_string(),
+        exists: dir.is_dir(),
+        total_bytes: entries.iter().map(|e| e.bytes).sum(),
+        entries,
+    })
+}
+
+/// Whether `options` selects entries of `class`.
+fn selects(options: &PruneOptions, class: DataClass) -> bool {
+    match class {
+        DataClass::Cache => options.caches || options.all,
+        DataClass::RotatedLog => options.logs || options.all,
+        DataClass::Runtime | DataClass::Backup | DataClass::Pending => options.all,
+        _ => false,
+    }
+}
+
+/// Why a selected entry is kept, if it is. `daemon_running`: some daemon's
+/// socket or pid file in the directory isn't stale (it may have the text
+/// cache open).
+fn keep_reason(
+    entry: &DataEntry,
+    options: &PruneOptions,
+    cutoff: Option<SystemTime>,
+    daemon_running: bool,
+) -> Option<&'static str> {
+    match entry.class {
+        DataClass::Backup | DataClass::Pending if !options.force => Some("needs --force"),
+        DataClass::Runtime if entry.stale != Some(true) => Some("daemon is running"),
+        DataClass::Cache if daemon_running => Some("daemon is running"),
+        _ => match (cutoff, entry.modified_at) {
+            (Some(cutoff), Some(modified)) if modified > cutoff => Some("newer than --older-than"),
+            _ => None,
+        },
+    }
+}
+
+/// Delete what `options` selects from `dir`.
+pub fn prune_dir(dir: &Path, options: &PruneOptions, now: SystemTime) -> Result<PruneReport> {
+    if !(options.caches || options.logs || options.all) {
+        bail!("Nothing to prune: pass --caches, --logs, or --all");
+    }
+    let cutoff = options.older_than.and_then(|age| age.to_std().ok()).and_then(|age| now.checked_sub(age));
+    let entries = status_of(dir)?.entries;
+    let daemon_running = entries.iter().any(|e| e.class == DataClass::Runtime && e.stale == Some(false));
+    let mut removed = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in entries.into_iter().filter(|e| selects(options, e.class)) {
+        if let Some(reason) = keep_reason(&entry, options, cutoff, daemon_running) {
+            skipped.push(SkippedEntry { name: entry.name, class: entry.class, reason: reason.to_string() });
+            continue;
+        }
+        let path = Path::new(&entry.path);
+        let result = match entry.is_dir {
+            true => std::fs::remove_dir_all(path),
+            false => std::fs::remove_file(path),
+        };
+        match result {
+            Ok(()) => removed.push(entry),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to remove {:?}", path)),
+        }
+    }
+    Ok(PruneReport {
+        dir: dir.display().to_string(),
+        freed_bytes: removed.iter().map(|e| e.bytes).sum(),
+        removed,
+        skipped,
+    })
+}
+
+fn human_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{}B", bytes),
+        1024..=1_048_575 => format!("{:.1}KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1}MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn class_name(class: DataClass) -> String {
+    serde_json::to_value(class).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+/// List everything in the data directory.
+pub fn status(output: &OutputControls) -> Result<()> {
+    let status = status_of(&storage::data_dir())?;
+    if output.json {
+        output.print(&status)?;
+        return Ok(());
+    }
+    if !status.exists {
+        println!("No data directory at {}", status.dir);
+        return Ok(());
+    }
+    println!("{} ({})", status.dir, human_bytes(status.total_bytes));
+    for e in &status.entries {
+        let modified = e.modified_at.map(|t| DateTime::<Local>::from(t).format("%Y-%m-%d %H:%M").to_string());
+        let stale = if e.stale == Some(true) { " [stale]" } else { "" };
+        println!(
+            "  {:<24} {:>9}  {:<16}  {:<11} {}{}",
+            e.name,
+            human_bytes(e.bytes),
+            modified.unwrap_or_default(),
+            class_name(e.class),
+            e.about.as_deref().unwrap_or("-"),
+            stale
+        );
+    }
+    Ok(())
+}
+
+/// Delete the selected subsets of the data directory.
+pub fn prune(options: &PruneOptions, output: &OutputControls) -> Result<()> {
+    let report = prune_dir(&storage::data_dir(), options, SystemTime::now())?;
+    if output.json {
+        output.print(&report)?;
+        return Ok(());
+    }
+    for e in &report.removed {
+        println!("Removed {} ({})", e.name, human_bytes(e.bytes));
+    }
+    for s in &report.skipped {
+        println!("Kept {} ({})", s.name, s.reason);
+    }
+    println!("Freed {} in {}", human_bytes(report.freed_bytes), report.dir);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration as StdDuration;
+
+    fn write(dir: &Path, name: &str) {
+        std::fs::write(dir.join(name), name).unwrap();
+    }
+
+    fn names(entries: &[DataEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_status_classifies_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["mutes.json", "textcache.db", "daemon.log", "notes.txt"] {
+            write(dir.path(), name);
+        }
+        std::fs::create_dir(dir.path().join("contacts-backups")).unwrap();
+        write(&dir.path().join("contacts-backups"), "contacts-1.json");
+
+        let status = status_of(dir.path()).unwrap();
+        assert_eq!(names(&status.entries), ["contacts-backups", "daemon.log", "mutes.json", "notes.txt", "textcache.db"]);
+        let classes: Vec<DataClass> = status.entries.iter().map(|e| e.class).collect();
+        assert_eq!(
+            classes,
+            [DataClass::Backup, DataClass::Log, DataClass::State, DataClass::Unknown, DataClass::Cache]
+        );
+        assert_eq!(status.entries[0].bytes, "contacts-1.json".len() as u64);
+        assert!(status.entries[0].is_dir);
+        assert_eq!(status.total_bytes, status.entries.iter().map(|e| e.bytes).sum::<u64>());
+
+        let missing = status_of(&dir.path().join("missing")).unwrap();
+        assert!(!missing.exists);
+        assert!(missing.entries.is_empty());
+    }
+
+    #[test]
+    fn test_stale_runtime_files() {
+        let dir = tempfile::tempdir().unwrap();
+        // Not a socket, nothing listening: stale
+        write(dir.path(), "old.sock");
+        std::fs::write(dir.path().join("live.sock.pid"), format!("{}\n", std::process::id())).unwrap();
+        std::fs::write(dir.path().join("dead.sock.pid"), "999999999\n").unwrap();
+
+        let status = status_of(dir.path()).unwrap();
+        let stale: Vec<(&str, Option<bool>)> = status.entries.iter().map(|e| (e.name.as_str(), e.stale)).collect();
+        assert_eq!(stale, [("dead.sock.pid", Some(true)), ("live.sock.pid", Some(false)), ("old.sock", Some(true))]);
+    }
+
+    #[test]
+    fn test_prune_older_than_keeps_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "daemon.log.1");
+        write(dir.path(), "daemon.log");
+        let options = PruneOptions { logs: true, older_than: Some(Duration::days(30)), ..Default::default() };
+
+        let report = prune_dir(dir.path(), &options, SystemTime::now()).unwrap();
+        assert!(report.removed.is_empty());
+        assert_eq!(report.skipped[0].reason, "newer than --older-than");
+
+        let later = SystemTime::now() + StdDuration::from_secs(31 * 86_400);
+        let report = prune_dir(dir.path(), &options, later).unwrap();
+        assert_eq!(names(&report.removed), ["daemon.log.1"]);
+        assert!(dir.path().join("daemon.log").exists());
+
+        assert!(prune_dir(dir.path(), &PruneOptions::default(), later).is_err());
+    }
+
+    #[test]
+    fn test_prune_keeps_caches_while_a_daemon_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "textcache.db");
+        write(dir.path(), "textcache.db-wal");
+        std::fs::write(dir.path().join("daemon.sock.pid"), format!("{}\n", std::process::id())).unwrap();
+        let options = PruneOptions { caches: true, ..Default::default() };
+
+        let report = prune_dir(dir.path(), &options, SystemTime::now()).unwrap();
+        assert!(report.removed.is_empty());
+        let kept: Vec<(&str, &str)> = report.skipped.iter().map(|s| (s.name.as_str(), s.reason.as_str())).collect();
+        assert_eq!(kept, [("textcache.db", "daemon is running"), ("textcache.db-wal", "daemon is running")]);
+
+        // Once the daemon is gone they go
+        std::fs::write(dir.path().join("daemon.sock.pid"), "999999999\n").unwrap();
+        let report = prune_dir(dir.path(), &options, SystemTime::now()).unwrap();
+        assert_eq!(names(&report.removed), ["textcache.db", "textcache.db-wal"]);
+    }
+}
@@ -1,19 +1,53 @@
-//! Messaging commands: send, send-by-phone.
+//! Messaging commands: send, send-by-phone, mark-read, send-log.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - mark-read addresses each chat by its chat.guid (group chats included) and checks chat.db that it ended up read
+//! - 10/16/2026 - A failed send-by-phone or mark-read returns only the error (printed once by main) instead of printing a result document too; SendResult drops error/code/retry_after_secs
+//! - 10/16/2026 - SendResult.dry_run is serde(default) (optional in the schema, like in the output)
+//! - 10/16/2026 - Rate limit and send log step of deliver split out as send_logged (shared with the scheduler worker)
+//! - 10/16/2026 - message_body: send/send-by-phone text from --message-file or --stdin (exact, one trailing newline dropped); option-like words refused; SendResult.message_bytes/message_chars
+//! - 10/16/2026 - send_by_phone renamed send_by_handle; email handles skip phone normalization
+//! - 10/16/2026 - Length check on by default (send.max_message_chars, 10000; 0 = no limit)
+//! - 10/16/2026 - SendResult.code (RATE_LIMITED, AUTOMATION_DENIED) on failed sends
+//! - 10/16/2026 - deliver enforces the send rate limit and writes the send log; added send-log
+//! - 10/16/2026 - send and send-by-phone go through api::send / api::send_to_phone
+//! - 10/16/2026 - Added deliver (send without printing); send and the MCP server use it
+//! - 10/16/2026 - Typed SendResult/MarkReadReport outputs for `schema`
+//! - 10/16/2026 - Send safety from config (dry run, max message length); configurable country code
+//! - 10/16/2026 - Added mark-read (AppleScript, per-conversation status)
 //! - 01/10/2026 - Implemented send and send_by_phone with AppleScript (Claude)
 //! - 01/10/2026 - Initial stub implementation (Claude)
 
+use crate::api;
 use crate::applescript;
-use crate::contacts::manager::ContactsManager;
-use crate::output::OutputControls;
+use crate::config;
+use crate::contacts::manager::{looks_like_email, ContactsManager};
+use crate::db::{connection, helpers};
+use crate::error::CliError;
+use crate::output::{self, OutputControls};
+use crate::send_log::{self, RateLimit, RecipientVolume, SendLog, SendLogEntry};
 use anyhow::{anyhow, Context, Result};
-use serde_json::json;
+use chrono::{Duration, Local};
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
 
 /// Normalize a phone number for sending.
 ///
-/// Strips non-digit characters and ensures + prefix for international format.
-fn normalize_phone(phone: &str) -> String {
+/// Strips non-digit characters and ensures + prefix for international format,
+/// using the configured country code for national numbers. Email handles are
+/// returned as given (trimmed).
+pub(crate) fn normalize_phone(phone: &str) -> String {
+    normalize_phone_in(phone, config::active().country_code.value.as_deref())
+}
+
+/// `normalize_phone` with an explicit country code.
+fn normalize_phone_in(phone: &str, country_code: Option<&str>) -> String {
+    if looks_like_email(phone) {
+        return phone.trim().to_string();
+    }
     let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
 
     // If already has + prefix, keep it
@@ -21,12 +55,164 @@ fn normalize_phone(phone: &str) -> String {
         return phone.to_string();
     }
 
-    // Add + prefix for 10+ digit numbers
-    if digits.len() >= 10 {
-        format!("+{}", digits)
+    // National numbers (trunk 0 dropped, 7-10 digits) get the country code;
+    // short codes are left alone
+    let national = digits.trim_start_matches('0');
+    match country_code {
+        Some(code) if (7..=10).contains(&national.len()) => format!("+{}{}", code, national),
+        _ if digits.len() >= 10 => format!("+{}", digits),
+        _ => digits,
+    }
+}
+
+/// Refuse messages longer than `send.max_message_chars` (0 = no limit).
+pub(crate) fn check_message_length(message: &str) -> Result<()> {
+    check_length(message, config::active().send_max_message_chars.value)
+}
+
+fn check_length(message: &str, max: u32) -> Result<()> {
+    let len = message.chars().count();
+    if max > 0 && len > max as usize {
+        return Err(anyhow!(
+            "Message is {} characters, over send.max_message_chars ({}); split it or raise the limit in config.toml",
+            len,
+            max
+        ));
+    }
+    Ok(())
+}
+
+/// The message for `send` / `send-by-phone`: the exact contents of `file` or
+/// of stdin, or the positional words joined with spaces.
+pub fn message_body(words: &[String], file: Option<&Path>, stdin: bool) -> Result<String> {
+    let body = match (file, stdin) {
+        (Some(path), _) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read message file {}", path.display()))?,
+        (None, true) => {
+            let mut body = String::new();
+            std::io::stdin().read_to_string(&mut body).context("Failed to read message from stdin")?;
+            body
+        }
+        (None, false) => return join_words(words),
+    };
+    let body = without_final_newline(&body);
+    if body.trim().is_empty() {
+        return Err(anyhow!("Message is empty"));
+    }
+    Ok(body.to_string())
+}
+
+/// Drop the one line ending editors and `echo` add; other newlines
+/// (blank lines included) are part of the message.
+fn without_final_newline(body: &str) -> &str {
+    body.strip_suffix("\r\n").or_else(|| body.strip_suffix('\n')).unwrap_or(body)
+}
+
+/// Positional words joined with spaces. A word that looks like an option
+/// can only get here after `--`, and is more likely a misplaced flag than
+/// text, so it's refused rather than sent.
+fn join_words(words: &[String]) -> Result<String> {
+    let looks_like_option = |word: &&String| {
+        word.as_str() == "--" || word.strip_prefix("--").is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_alphabetic()))
+    };
+    if let Some(word) = words.iter().find(looks_like_option) {
+        return Err(anyhow!(
+            "'{}' in the message looks like an option; put options before the message, or pass text like this with --message-file or --stdin",
+            word
+        ));
+    }
+    Ok(words.join(" "))
+}
+
+/// True when `send.dry_run` (or --dry-run) is in effect.
+pub(crate) fn dry_run() -> bool {
+    config::active().send_dry_run.value
+}
+
+/// Outcome of `send` / `send-by-phone`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SendResult {
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    pub phone: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Size of the message sent, in UTF-8 bytes and in characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_bytes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_chars: Option<usize>,
+}
+
+impl SendResult {
+    fn sent(contact: Option<&str>, phone: &str, message: &str) -> Self {
+        Self {
+            success: true,
+            dry_run: false,
+            contact: contact.map(str::to_string),
+            phone: phone.to_string(),
+            message: Some(message.to_string()),
+            message_bytes: Some(message.len()),
+            message_chars: Some(message.chars().count()),
+        }
+    }
+}
+
+/// Send (or dry-run) to an already-resolved phone and return the outcome
+/// instead of printing it.
+///
+/// Real sends are checked against the send rate limit (unless --force-send)
+/// and recorded in the send log, both under the log's lock.
+pub fn deliver(contact: Option<&str>, phone: &str, message: &str) -> Result<SendResult> {
+    check_message_length(message)?;
+    if dry_run() {
+        return Ok(SendResult {
+            dry_run: true,
+            ..SendResult::sent(contact, phone, message)
+        });
+    }
+
+    let settings = config::active();
+    let limit = (!settings.send_force.value).then(|| RateLimit::from_settings(settings));
+    send_logged(&SendLog::open_default(), limit, phone, message, &applescript::send_imessage)?;
+    Ok(SendResult::sent(contact, phone, message))
+}
+
+/// `send` under the send log's lock: refused if `limit` (None: no limit)
+/// says so, and recorded in `log` whether or not it succeeds.
+pub(crate) fn send_logged(
+    log: &SendLog,
+    limit: Option<RateLimit>,
+    phone: &str,
+    message: &str,
+    send: &dyn Fn(&str, &str) -> Result<()>,
+) -> Result<()> {
+    let _lock = log.lock()?;
+    if let Some(limit) = limit {
+        let now = Local::now();
+        limit.check(&log.read_since(now - limit.lookback())?, phone, now)?;
+    }
+
+    let outcome = send(phone, message);
+    if let Err(e) = log.append(&SendLogEntry::new(phone, message, outcome.is_ok(), Local::now())) {
+        output::warn(format!("send not recorded in send log: {:#}", e));
+    }
+    outcome.context("Failed to send message")
+}
+
+fn print_dry_run(contact: Option<&str>, phone: &str, message: &str, output: &OutputControls) -> Result<()> {
+    if output.json {
+        output.print(&SendResult {
+            dry_run: true,
+            ..SendResult::sent(contact, phone, message)
+        })?;
     } else {
-        digits
+        println!("Dry run: would send to {} ({} chars)", phone, message.chars().count());
     }
+    Ok(())
 }
 
 /// Send a message to a contact by name.
@@ -38,68 +224,259 @@ pub fn send(contact: &str, message: &str, output: &OutputControls) -> Result<()>
     let contacts = ContactsManager::load_default()
         .context("Failed to load contacts. Run 'python3 scripts/sync_contacts.py' first.")?;
 
-    // Resolve contact to phone number
-    let phone = contacts
-        .resolve_to_phone(contact)
-        .ok_or_else(|| anyhow!("Contact '{}' not found", contact))?;
-
-    // Send via AppleScript
-    applescript::send_imessage(&phone, message).context("Failed to send message")?;
+    // Resolve and send via AppleScript (unless dry run)
+    let result = api::send(&contacts, contact, message)?;
 
     // Output result
-    if output.json {
-        output.print(&json!({
-            "success": true,
-            "contact": contact,
-            "phone": phone,
-            "message": message
-        }));
+    if result.dry_run {
+        print_dry_run(Some(contact), &result.phone, message, output)?;
+    } else if output.json {
+        output.print(&result)?;
     } else {
-        println!("Message sent to {} ({})", contact, phone);
+        println!("Message sent to {} ({})", contact, result.phone);
     }
 
     Ok(())
 }
 
-/// Send message directly to a phone number.
+/// Send message directly to a phone number or email handle.
 ///
-/// Normalizes the phone number and sends via AppleScript.
-pub fn send_by_phone(phone: &str, message: &str, output: &OutputControls) -> Result<()> {
-    let normalized = normalize_phone(phone);
-
-    // Send via AppleScript
-    match applescript::send_imessage(&normalized, message) {
-        Ok(()) => {
-            if output.json {
-                output.print(&json!({
-                    "success": true,
-                    "phone": normalized,
-                    "message": message
-                }));
-            } else {
-                println!("Message sent to {}", normalized);
-            }
-            Ok(())
+/// Normalizes phone numbers (emails pass through) and sends via AppleScript.
+pub fn send_by_handle(phone: &str, message: &str, output: &OutputControls) -> Result<()> {
+    check_message_length(message)?;
+
+    // A failure is main's to report (the error envelope under --envelope)
+    let result = api::send_to_handle(phone, message)?;
+
+    if result.dry_run {
+        print_dry_run(None, &result.phone, message, output)?;
+    } else if output.json {
+        output.print(&result)?;
+    } else {
+        println!("Message sent to {}", result.phone);
+    }
+
+    Ok(())
+}
+
+/// Per-conversation mark-read outcome.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MarkReadResult {
+    pub chat_identifier: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `mark-read` summary across every attempted conversation.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MarkReadReport {
+    pub attempted: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<MarkReadResult>,
+}
+
+/// How long `mark_read` waits for Messages to record a displayed chat as read.
+const MARK_READ_WAIT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Messages in `chat_identifier` still unread.
+fn unread_in(conn: &Connection, chat_identifier: &str) -> Result<i64> {
+    Ok(helpers::query_unread_chats(conn)?
+        .into_iter()
+        .find(|(chat, _)| chat == chat_identifier)
+        .map_or(0, |(_, count)| count))
+}
+
+/// Display one conversation in Messages (by its chat id, so groups work
+/// too), then wait up to `wait` for chat.db to show it read.
+fn mark_chat_read(conn: &Connection, chat_identifier: &str, wait: std::time::Duration) -> Result<()> {
+    let (rowid, guid) = helpers::query_chat_by_identifier(conn, chat_identifier)?
+        .ok_or_else(|| anyhow!("no chat with this identifier in chat.db"))?;
+    let participants = helpers::query_chat_participants(conn, &[rowid])?.remove(&rowid).unwrap_or_default();
+    applescript::mark_chat_read(&guid, &participants)?;
+
+    let deadline = std::time::Instant::now() + wait;
+    loop {
+        let unread = unread_in(conn, chat_identifier)?;
+        if unread == 0 {
+            return Ok(());
         }
-        Err(e) => {
-            if output.json {
-                output.print(&json!({
-                    "success": false,
-                    "phone": normalized,
-                    "error": e.to_string()
-                }));
-            } else {
-                eprintln!("Failed to send message: {}", e);
+        if std::time::Instant::now() >= deadline {
+            return Err(anyhow!("opened in Messages, but {} message(s) are still unread", unread));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    }
+}
+
+/// Mark conversations read in Messages.app.
+///
+/// Targets a contact, a group chat id, or (with `all`) every conversation the
+/// unread query reports. Every conversation is attempted; if any fail, the
+/// command returns only a partial-failure error naming them (main prints it,
+/// as the error envelope under `--envelope`) instead of the report.
+pub fn mark_read(
+    contact: Option<&str>,
+    group_id: Option<&str>,
+    all: bool,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = connection::open_db()?;
+    let targets: Vec<(String, Option<i64>)> = if all {
+        helpers::query_unread_chats(&conn)?
+            .into_iter()
+            .map(|(chat, count)| (chat, Some(count)))
+            .collect()
+    } else if let Some(group) = group_id {
+        vec![(group.to_string(), None)]
+    } else if let Some(name) = contact {
+        let phone = contacts
+            .resolve_to_phone(name)
+            .ok_or_else(|| anyhow!("Contact '{}' not found", name))?;
+        vec![(normalize_phone(&phone), None)]
+    } else {
+        return Err(anyhow!("Specify a contact, --group-id, or --all"));
+    };
+
+    let results: Vec<MarkReadResult> = targets
+        .into_iter()
+        .map(|(chat_identifier, unread_count)| {
+            match mark_chat_read(&conn, &chat_identifier, MARK_READ_WAIT) {
+                Ok(()) => MarkReadResult {
+                    chat_identifier,
+                    success: true,
+                    unread_count,
+                    error: None,
+                },
+                Err(e) => MarkReadResult {
+                    chat_identifier,
+                    success: false,
+                    unread_count,
+                    error: Some(e.to_string()),
+                },
             }
-            Err(e)
+        })
+        .collect();
+
+    let failures: Vec<String> = results
+        .iter()
+        .filter_map(|r| r.error.as_ref().map(|e| format!("{} ({})", r.chat_identifier, e)))
+        .collect();
+    if !failures.is_empty() {
+        let err = anyhow::Error::new(CliError::PartialFailure {
+            failed: failures.len(),
+            total: results.len(),
+        });
+        return Err(err.context(format!(
+            "{} of {} conversations not marked read: {}",
+            failures.len(),
+            results.len(),
+            failures.join("; ")
+        )));
+    }
+
+    let report = MarkReadReport {
+        attempted: results.len(),
+        succeeded: results.len(),
+        failed: 0,
+        results,
+    };
+    if output.json {
+        output.print(&report)?;
+    } else if report.results.is_empty() {
+        println!("No unread conversations.");
+    } else {
+        for r in &report.results {
+            println!("Marked read: {}", r.chat_identifier);
         }
     }
+    Ok(())
+}
+
+/// `send-log` output: outbound volume per recipient.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SendLogSummary {
+    pub days: u32,
+    pub sent: usize,
+    pub failed: usize,
+    pub recipients: Vec<RecipientVolume>,
+}
+
+/// Summarize the send log over the last `days` days.
+pub fn send_log(days: u32, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let entries = SendLog::open_default().read_since(Local::now() - Duration::days(days as i64))?;
+    let mut recipients = send_log::summarize(&entries);
+    for r in &mut recipients {
+        r.contact_name = contacts.find_by_phone(&r.phone).map(|c| c.name.clone());
+    }
+    let summary = SendLogSummary {
+        days,
+        sent: recipients.iter().map(|r| r.sent).sum(),
+        failed: recipients.iter().map(|r| r.failed).sum(),
+        recipients,
+    };
+
+    if output.json {
+        output.print(&summary)?;
+        return Ok(());
+    }
+    println!(
+        "Sends in the last {} days: {} sent, {} failed",
+        summary.days, summary.sent, summary.failed
+    );
+    if summary.recipients.is_empty() {
+        return Ok(());
+    }
+    println!("{:-<60}", "");
+    for r in &summary.recipients {
+        let who = match r.contact_name {
+            Some(ref name) => format!("{} ({})", name, r.phone),
+            None => r.phone.clone(),
+        };
+        let last = r
+            .last_sent
+            .map(|t| format!(", last {}", t.format("%Y-%m-%d %H:%M")))
+            .unwrap_or_default();
+        let failed = if r.failed > 0 { format!(", {} failed", r.failed) } else { String::new() };
+        println!("{}: {} sent{}{}", who, r.sent, failed, last);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_message_length_limit() {
+        assert!(check_length(&"a".repeat(10), 10).is_ok());
+        let err = check_length(&"é".repeat(11), 10).unwrap_err().to_string();
+        assert!(err.contains("11 characters") && err.contains("send.max_message_chars (10)"));
+        assert!(check_length(&"a".repeat(20_000), 0).is_ok());
+    }
+
+    #[test]
+    fn test_message_body_from_words() {
+        let words = |ws: &[&str]| ws.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(message_body(&words(&["see", "you", "-5", "mins"]), None, false).unwrap(), "see you -5 mins");
+        for flag in ["--dry-run", "--", "--in=2h"] {
+            let err = message_body(&words(&["hi", flag]), None, false).unwrap_err().to_string();
+            assert!(err.contains(flag) && err.contains("--message-file"), "{}", err);
+        }
+        assert_eq!(message_body(&words(&["a", "---", "b"]), None, false).unwrap(), "a --- b");
+    }
+
+    #[test]
+    fn test_without_final_newline() {
+        assert_eq!(without_final_newline("hi\n"), "hi");
+        assert_eq!(without_final_newline("hi\r\n"), "hi");
+        assert_eq!(without_final_newline("line 1\n\nline 2\n\n"), "line 1\n\nline 2\n");
+        assert_eq!(without_final_newline("  two  spaces"), "  two  spaces");
+    }
+
     #[test]
     fn test_normalize_phone_with_plus() {
         assert_eq!(normalize_phone("+14155551234"), "+14155551234");
@@ -119,4 +496,21 @@ mod tests {
     fn test_normalize_phone_with_country() {
         assert_eq!(normalize_phone("1-415-555-1234"), "+14155551234");
     }
+
+    #[test]
+    fn test_normalize_phone_configured_country_code() {
+        assert_eq!(normalize_phone_in("4155551234", Some("1")), "+14155551234");
+        assert_eq!(normalize_phone_in("14155551234", Some("1")), "+14155551234");
+        assert_eq!(normalize_phone_in("07700 900123", Some("44")), "+447700900123");
+        assert_eq!(normalize_phone_in("+33612345678", Some("1")), "+33612345678");
+        assert_eq!(normalize_phone_in("4155551234", None), "+4155551234");
+        assert_eq!(normalize_phone_in("72345", Some("1")), "72345");
+    }
+
+    #[test]
+    fn test_normalize_phone_leaves_emails_alone() {
+        assert_eq!(normalize_phone_in("sarah@icloud.com", Some("1")), "sarah@icloud.com");
+        assert_eq!(normalize_phone_in("4155551234@vtext.com", None), "4155551234@vtext.com");
+        assert_eq!(normalize_phone_in(" Sarah@iCloud.com", Some("44")), "Sarah@iCloud.com");
+    }
 }
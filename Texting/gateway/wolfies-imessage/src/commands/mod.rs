@@ -1,13 +1,57 @@
 //! Command implementations.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Added summary module (split out of reading)
+//! - 10/16/2026 - Added maintenance module
+//! - 10/16/2026 - Added raw_message module
+//! - 10/16/2026 - Added presets module
+//! - 10/16/2026 - Added chat_info module
+//! - 10/16/2026 - Added quick module
+//! - 10/16/2026 - Added doctor module
+//! - 10/16/2026 - Added followup module
+//! - 10/16/2026 - Added account module
+//! - 10/16/2026 - Added mutes module
+//! - 10/16/2026 - Added draft module
+//! - 10/16/2026 - Added compare module
+//! - 10/16/2026 - Added deleted module
+//! - 10/16/2026 - Added commitments module
+//! - 10/16/2026 - Added schema module
+//! - 10/16/2026 - Added config module
+//! - 10/16/2026 - Added cache module
+//! - 10/16/2026 - Added links module
+//! - 10/16/2026 - Added digest module
+//! - 10/16/2026 - Added watch module
+//! - 10/16/2026 - Added scheduled module
+//! - 10/16/2026 - Added attachments module
 //! - 01/10/2026 - Initial module structure (Claude)
 
+pub mod account;
 pub mod analytics;
+pub mod attachments;
+pub mod cache;
+pub mod chat_info;
+pub mod commitments;
+pub mod compare;
+pub mod config;
 pub mod contacts;
+pub mod deleted;
+pub mod digest;
+pub mod draft;
 pub mod discovery;
+pub mod doctor;
+pub mod followup;
 pub mod groups;
+pub mod links;
+pub mod maintenance;
 pub mod messaging;
+pub mod mutes;
+pub mod presets;
+pub mod quick;
 pub mod rag;
+pub mod raw_message;
 pub mod reading;
+pub mod scheduled;
+pub mod schema;
 pub mod setup;
+pub mod summary;
+pub mod watch;
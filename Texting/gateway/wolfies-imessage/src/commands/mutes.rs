@@ -0,0 +1,200 @@
+//! Mute commands: `mute`, `unmute`, and `mutes list`.
+//!
+//! A target is a group id (chat identifier, as `groups` shows it) or a
+//! contact name, phone, or email. Contacts are stored by normalized handle
+//! so any spelling of the number unmutes the same conversation, along with
+//! the contact's other handles so all of their 1:1 chats are muted.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Contact mutes record the contact's other handles (Mute.handles)
+//! - 10/16/2026 - Initial mute/unmute/mutes list with --until
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use rusqlite::OptionalExtension;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::connection;
+use crate::db::helpers::{handle_key, is_group_chat_identifier};
+use crate::mutes::{Mute, MuteKind, MuteStore};
+use crate::output::OutputControls;
+use crate::scheduler;
+
+/// `unmute` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnmuteResult {
+    pub unmuted: bool,
+    pub mute: Mute,
+}
+
+/// The conversation a `mute`/`unmute` target refers to.
+struct Target {
+    kind: MuteKind,
+    key: String,
+    /// A contact's other handles, normalized
+    handles: Vec<String>,
+    name: Option<String>,
+}
+
+fn resolve_target(target: &str, contacts: &ContactsManager) -> Result<Target> {
+    if is_group_chat_identifier(Some(target)) {
+        return Ok(Target { kind: MuteKind::Group, key: target.to_string(), handles: Vec::new(), name: None });
+    }
+    let handle = if target.contains('@') {
+        target.trim().to_string()
+    } else {
+        contacts
+            .resolve_to_phone(target)
+            .ok_or_else(|| anyhow!("Contact '{}' not found", target))?
+    };
+    Ok(Target {
+        kind: MuteKind::Contact,
+        key: handle_key(&handle),
+        handles: contacts.other_handle_keys(&handle),
+        name: contacts.find_by_phone(&handle).map(|c| c.name.clone()),
+    })
+}
+
+/// Display name of a group chat; errors if Messages.db has no such chat.
+/// None when the database can't be opened (the mute is still recorded).
+fn group_name(chat_identifier: &str) -> Result<Option<String>> {
+    let Ok(conn) = connection::open_db() else {
+        return Ok(None);
+    };
+    let name: Option<Option<String>> = conn
+        .query_row(
+            "SELECT display_name FROM chat WHERE chat_identifier = ?1",
+            [chat_identifier],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match name {
+        Some(name) => Ok(name.filter(|n| !n.is_empty())),
+        None => Err(anyhow!("No group chat '{}' (see `groups` for ids)", chat_identifier)),
+    }
+}
+
+/// Parse `--until`: a duration from now (8h, 7d), a local time, or a date
+/// (muted until that day starts).
+pub(crate) fn parse_until(input: &str, now: DateTime<Local>) -> Result<DateTime<Local>> {
+    let until = if let Ok(delay) = scheduler::parse_delay(input) {
+        now + delay
+    } else if let Ok(date) = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d") {
+        Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .earliest()
+            .ok_or_else(|| anyhow!("'{}' does not exist in the local timezone", input))?
+    } else {
+        scheduler::parse_send_at(input)
+            .map_err(|_| anyhow!("Invalid --until '{}' (expected e.g. 8h, 7d, 2026-01-12, 2026-01-12T09:00)", input))?
+    };
+    if until <= now {
+        return Err(anyhow!("--until {} is in the past", until.to_rfc3339()));
+    }
+    Ok(until)
+}
+
+fn describe(mute: &Mute) -> String {
+    match mute.name {
+        Some(ref name) => format!("{} ({})", name, mute.target),
+        None => mute.target.clone(),
+    }
+}
+
+fn describe_until(mute: &Mute) -> String {
+    match mute.until {
+        Some(until) => format!("until {}", until.format("%Y-%m-%d %H:%M")),
+        None => "indefinitely".to_string(),
+    }
+}
+
+/// Mute a group or contact conversation.
+pub fn mute(target: &str, until: Option<&str>, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let now = Local::now();
+    let resolved = resolve_target(target, contacts)?;
+    let name = match resolved.kind {
+        MuteKind::Group => group_name(&resolved.key)?,
+        MuteKind::Contact => resolved.name,
+    };
+    let mute = Mute {
+        kind: resolved.kind,
+        key: resolved.key,
+        handles: resolved.handles,
+        target: target.to_string(),
+        name,
+        until: until.map(|u| parse_until(u, now)).transpose()?,
+        created_at: now,
+    };
+    let mute = MuteStore::open_default().add(mute, now)?;
+
+    if output.json {
+        output.print(&mute)?;
+    } else {
+        println!("Muted {} {}", describe(&mute), describe_until(&mute));
+    }
+    Ok(())
+}
+
+/// Remove a mute.
+pub fn unmute(target: &str, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let resolved = resolve_target(target, contacts)?;
+    let mute = MuteStore::open_default()
+        .remove_mute(resolved.kind, &resolved.key, Local::now())
+        .map_err(|_| anyhow!("'{}' is not muted", target))?;
+
+    if output.json {
+        output.print(&UnmuteResult { unmuted: true, mute })?;
+    } else {
+        println!("Unmuted {}", describe(&mute));
+    }
+    Ok(())
+}
+
+/// List mutes still in effect.
+pub fn list(output: &OutputControls) -> Result<()> {
+    let mutes = MuteStore::open_default().active(Local::now())?;
+
+    if output.json {
+        output.print(&mutes)?;
+        return Ok(());
+    }
+    if mutes.is_empty() {
+        println!("No muted conversations.");
+        return Ok(());
+    }
+    println!("Muted conversations ({}):", mutes.len());
+    println!("{:-<60}", "");
+    for mute in &mutes {
+        let kind = match mute.kind {
+            MuteKind::Group => "group",
+            MuteKind::Contact => "contact",
+        };
+        println!("[{}] {} {}", kind, describe(mute), describe_until(mute));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_until() {
+        let now = Local.with_ymd_and_hms(2026, 1, 10, 12, 0, 0).unwrap();
+        assert_eq!(parse_until("8h", now).unwrap(), now + Duration::hours(8));
+        assert_eq!(parse_until("7d", now).unwrap(), now + Duration::days(7));
+        assert_eq!(
+            parse_until("2026-01-12", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 1, 12, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_until("2026-01-12T09:30", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 1, 12, 9, 30, 0).unwrap()
+        );
+        assert!(parse_until("2026-01-09", now).is_err());
+        assert!(parse_until("next week", now).is_err());
+    }
+}
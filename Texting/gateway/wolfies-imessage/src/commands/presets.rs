@@ -0,0 +1,76 @@
+//! Preset commands: `presets list` and `presets delete`.
+//!
+//! Presets are saved with the global `--save-as <name>` and replayed with
+//! `run <name>` (see crate::presets).
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial presets list/delete
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::output::OutputControls;
+use crate::presets::{Preset, PresetStore};
+
+/// `presets delete` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PresetDelete {
+    pub deleted: bool,
+    pub preset: Preset,
+}
+
+/// A subcommand and the parameters that say something: set options, enabled
+/// flags, and nested subcommands (`{"subcommand", "params"}` objects).
+fn describe(subcommand: &str, params: &Map<String, Value>, parts: &mut Vec<String>) {
+    parts.push(subcommand.to_string());
+    for (key, value) in params {
+        let flag = key.replace('_', "-");
+        match value {
+            Value::Null | Value::Bool(false) => {}
+            Value::Array(items) if items.is_empty() => {}
+            Value::Bool(true) => parts.push(format!("--{}", flag)),
+            Value::String(s) => parts.push(format!("{}={}", flag, s)),
+            Value::Object(nested) if nested.contains_key("subcommand") => {
+                let params = nested.get("params").and_then(Value::as_object).cloned().unwrap_or_default();
+                describe(nested["subcommand"].as_str().unwrap_or_default(), &params, parts);
+            }
+            other => parts.push(format!("{}={}", flag, other)),
+        }
+    }
+}
+
+/// List saved presets.
+pub fn list(output: &OutputControls) -> Result<()> {
+    let presets = PresetStore::open_default().list()?;
+
+    if output.json {
+        output.print(&presets)?;
+        return Ok(());
+    }
+    if presets.is_empty() {
+        println!("No presets. Save one with --save-as <name>.");
+        return Ok(());
+    }
+    println!("Presets ({}):", presets.len());
+    println!("{:-<60}", "");
+    for preset in &presets {
+        let mut parts = Vec::new();
+        describe(&preset.subcommand, &preset.params, &mut parts);
+        println!("{}: {}", preset.name, parts.join(" "));
+    }
+    Ok(())
+}
+
+/// Delete a preset.
+pub fn delete(name: &str, output: &OutputControls) -> Result<()> {
+    let preset = PresetStore::open_default().remove(name)?;
+
+    if output.json {
+        output.print(&PresetDelete { deleted: true, preset })?;
+    } else {
+        println!("Deleted preset '{}'", preset.name);
+    }
+    Ok(())
+}
@@ -0,0 +1,239 @@
+//! `quick`: one screen of pinned people and groups (config `pinned`).
+//!
+//! Each pin resolves to its chats (an exact contact name, then a group by
+//! identifier or display name, then a fuzzy contact name or a literal
+//! phone/email), and every pinned chat's latest message and unread count
+//! come from one query (`helpers::query_latest_in_chats`). A person with
+//! several one-to-one chats (iMessage and SMS) shows the newest message and
+//! the unread count across them. Unknown pins become warning rows, so one
+//! stale pin never hides the rest. The daemon's `quick` method shares
+//! `build_report`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial quick command and daemon method
+
+use anyhow::Result;
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::contacts::manager::{Contact, ContactsManager};
+use crate::db::connection::open_db;
+use crate::db::helpers::{self, handle_key, ConversationSummary};
+use crate::output::{self, OutputControls, Table, TextRenderer, TextStyle, Tone};
+
+/// Preview length in the text table.
+const PREVIEW_CHARS: usize = 48;
+
+/// One pinned contact or group.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QuickRow {
+    /// The pin as configured
+    pub pin: String,
+    /// Contact or group name it resolved to
+    pub name: Option<String>,
+    pub is_group: bool,
+    pub unread_count: i64,
+    /// Latest message across the pin's chats (null when there is none)
+    pub last: Option<ConversationSummary>,
+    /// Set when the pin matched no contact or group
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// `quick` output: pinned rows in configured order.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QuickReport {
+    pub pins: Vec<QuickRow>,
+    /// Unread messages across all pins
+    pub unread_total: i64,
+}
+
+/// What a pin resolved to.
+struct Resolved {
+    name: Option<String>,
+    is_group: bool,
+    chats: Vec<i64>,
+    warning: Option<String>,
+}
+
+fn looks_like_handle(pin: &str) -> bool {
+    pin.contains('@') || pin.chars().filter(|c| c.is_ascii_digit()).count() >= 10
+}
+
+/// One-to-one chats with any of `handles`.
+fn direct_chats_with<'a>(direct: &[(i64, String)], handles: impl IntoIterator<Item = &'a str>) -> Vec<i64> {
+    let keys: HashSet<String> = handles.into_iter().map(handle_key).filter(|k| !k.is_empty()).collect();
+    direct
+        .iter()
+        .filter(|(_, handle)| keys.contains(&handle_key(handle)))
+        .map(|(rowid, _)| *rowid)
+        .collect()
+}
+
+fn resolve_pin(
+    conn: &Connection,
+    contacts: &ContactsManager,
+    direct: &[(i64, String)],
+    pin: &str,
+) -> Result<Resolved> {
+    let person = |contact: &Contact| Resolved {
+        name: Some(contact.name.clone()),
+        is_group: false,
+        chats: direct_chats_with(direct, contact.all_handles()),
+        warning: None,
+    };
+    if let Some(contact) = contacts.find_by_name(pin) {
+        return Ok(person(contact));
+    }
+    if let Some(group) = helpers::resolve_group(conn, pin)? {
+        return Ok(Resolved {
+            is_group: helpers::is_group_chat_identifier(Some(&group.chat_identifier)),
+            name: group.display_name.or(Some(group.chat_identifier)),
+            chats: vec![group.rowid],
+            warning: None,
+        });
+    }
+    if let Some(contact) = contacts.find_fuzzy(pin) {
+        return Ok(person(contact));
+    }
+    if looks_like_handle(pin) {
+        return Ok(Resolved {
+            name: contacts.find_by_phone(pin).map(|c| c.name.clone()),
+            is_group: false,
+            chats: direct_chats_with(direct, [pin]),
+            warning: None,
+        });
+    }
+    Ok(Resolved {
+        name: None,
+        is_group: false,
+        chats: Vec::new(),
+        warning: Some(format!("'{}' matches no contact or group", pin)),
+    })
+}
+
+/// Latest message and unread count for each pin, in `pins` order.
+pub fn build_report(conn: &Connection, contacts: &ContactsManager, pins: &[String]) -> Result<QuickReport> {
+    let direct = helpers::query_direct_chats(conn)?;
+    let resolved = pins
+        .iter()
+        .map(|pin| resolve_pin(conn, contacts, &direct, pin))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut chat_ids: Vec<i64> = resolved.iter().flat_map(|r| r.chats.iter().copied()).collect();
+    chat_ids.sort_unstable();
+    chat_ids.dedup();
+    let latest = helpers::query_latest_in_chats(conn, &chat_ids)?;
+
+    let rows: Vec<QuickRow> = pins
+        .iter()
+        .zip(resolved)
+        .map(|(pin, r)| {
+            let summaries: Vec<&ConversationSummary> = r.chats.iter().filter_map(|id| latest.get(id)).collect();
+            QuickRow {
+                pin: pin.clone(),
+                name: r.name,
+                is_group: r.is_group,
+                unread_count: summaries.iter().map(|s| s.unread_count).sum(),
+                // RFC 3339 UTC dates compare in time order
+                last: summaries.iter().max_by(|a, b| a.last_date.cmp(&b.last_date)).map(|s| (*s).clone()),
+                warning: r.warning,
+            }
+        })
+        .collect();
+    Ok(QuickReport { unread_total: rows.iter().map(|r| r.unread_count).sum(), pins: rows })
+}
+
+impl TextRenderer for QuickReport {
+    fn render(&self, style: &TextStyle) -> String {
+        if self.pins.is_empty() {
+            return "No pins (set pinned = [\"Name\", \"Group\"] in config.toml)".to_string();
+        }
+        let mut table = Table::new(&["pin", "unread", "when", "last"]);
+        for row in &self.pins {
+            let label = row.name.clone().unwrap_or_else(|| row.pin.clone());
+            let (when, last) = match (&row.warning, &row.last) {
+                (Some(warning), _) => (String::new(), style.paint(Tone::Dim, &format!("! {}", warning))),
+                (None, None) => (String::new(), style.paint(Tone::Dim, "(no messages)")),
+                (None, Some(s)) => {
+                    let text = s.last_message_text.as_deref().or(s.placeholder.as_deref()).unwrap_or("");
+                    let sender = if s.last_is_from_me { "me: " } else { "" };
+                    (output::short_time(&s.last_date), format!("{}{}", sender, output::preview(text, PREVIEW_CHARS)))
+                }
+            };
+            let unread = if row.unread_count > 0 { row.unread_count.to_string() } else { "-".to_string() };
+            table.row(vec![label, unread, when, last]);
+        }
+        table.render(style)
+    }
+}
+
+/// Show the configured pins.
+pub fn quick(output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let conn = open_db()?;
+    let report = build_report(&conn, contacts, &crate::config::active().pinned.value)?;
+    for row in &report.pins {
+        if let Some(ref warning) = row.warning {
+            output::warn(warning.clone());
+        }
+    }
+    output.show(&report)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+    #[test]
+    fn test_quick_rows_follow_pins_and_merge_chats() {
+        let conn = empty_db();
+        let mom_imessage = insert_handle(&conn, "+14155550001");
+        let mom_email = insert_handle(&conn, "mom@example.com");
+        let sarah = insert_handle(&conn, "+14155550002");
+        let mom_chat = insert_chat(&conn, "+14155550001", None, &[mom_imessage]);
+        let mom_email_chat = insert_chat(&conn, "mom@example.com", None, &[mom_email]);
+        let group = insert_chat(&conn, "chat123456", Some("Work Group"), &[mom_imessage, sarah]);
+        insert_chat(&conn, "+14155550002", None, &[sarah]);
+
+        insert_message(&conn, mom_chat, mom_imessage, Some("call me"), DAY_NS, false, false);
+        insert_message(&conn, mom_email_chat, mom_email, Some("dinner sunday?"), 2 * DAY_NS, false, false);
+        insert_message(&conn, group, sarah, Some("standup moved"), 3 * DAY_NS, false, true);
+        insert_message(&conn, group, 0, Some("thanks"), 4 * DAY_NS, true, true);
+
+        let contacts = ContactsManager::from_contacts(vec![Contact {
+            name: "Mom".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "family".to_string(),
+            notes: None,
+            handles: vec!["mom@example.com".to_string()],
+        }]);
+        let pins: Vec<String> = ["work group", "Nobody", "Mom", "(415) 555-0002"].map(String::from).to_vec();
+        let report = build_report(&conn, &contacts, &pins).unwrap();
+
+        let names: Vec<_> = report.pins.iter().map(|r| r.name.as_deref()).collect();
+        assert_eq!(names, [Some("Work Group"), None, Some("Mom"), None]);
+
+        let work = &report.pins[0];
+        assert!(work.is_group);
+        assert_eq!(work.unread_count, 0);
+        assert_eq!(work.last.as_ref().unwrap().last_message_text.as_deref(), Some("thanks"));
+
+        assert!(report.pins[1].warning.as_deref().unwrap().contains("Nobody"));
+
+        // Both of Mom's chats count; the newest message wins
+        let mom = &report.pins[2];
+        assert_eq!(mom.unread_count, 2);
+        assert_eq!(mom.last.as_ref().unwrap().last_message_text.as_deref(), Some("dinner sunday?"));
+
+        // A literal handle with no messages is a row, not a warning
+        assert!(report.pins[3].last.is_none() && report.pins[3].warning.is_none());
+        assert_eq!(report.unread_total, 2);
+
+        let text = report.render(&TextStyle::default());
+        assert!(text.lines().nth(2).unwrap().contains("! 'Nobody' matches no contact or group"), "{}", text);
+    }
+}
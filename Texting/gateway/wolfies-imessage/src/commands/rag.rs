@@ -1,102 +1,647 @@
 //! RAG commands - delegate to Python daemon via Unix socket.
 //!
+//! Each command sends one request (method = command name) and renders the
+//! result: JSON through OutputControls, or a readable summary matching the
+//! Python CLI.
+//!
+//! `ask --local-fallback` answers from chat.db with a keyword search when
+//! the daemon is unreachable, in the same context format.
+//!
 //! CHANGELOG:
+//! - 10/16/2026 - Local-fallback notice is an output::warn (JSON warnings, stderr after text)
+//! - 10/16/2026 - Typed AskResult/ClearResult outputs for `schema`
+//! - 10/16/2026 - Socket and auto-start resolved by crate::config (flag, env, config.toml)
+//! - 10/16/2026 - ask --local-fallback: local keyword context when the daemon is unavailable
+//! - 10/16/2026 - Implemented index/search/ask/stats/clear/sources over the daemon socket
+//! - 10/16/2026 - Commands check the daemon socket first (optional auto-start)
 //! - 01/10/2026 - Initial stub implementation (Claude)
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::TimeZone;
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::daemon_client::{self, AutoStart, ClientError, DaemonClient};
+use crate::db::helpers::{self, ContextMessage, SearchHit};
+use crate::db::{connection, queries};
+use crate::error::CliError;
+use crate::output::{self, OutputControls};
+
+/// Env var overriding the RAG daemon socket (below --socket, above config.toml).
+pub const SOCKET_ENV: &str = "WOLFIES_RAG_SOCKET";
+
+/// Socket timeout for RAG requests (indexing can take minutes).
+const RAG_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// `engine` reported by `ask` when answered from chat.db instead of the daemon.
+pub const LOCAL_ENGINE: &str = "local-keyword";
+
+/// Messages shown on each side of a local hit.
+const LOCAL_CONTEXT_WINDOW: u32 = 2;
+
+/// Keywords searched per question.
+const MAX_KEYWORDS: usize = 8;
+
+/// `ask` output; `engine` and `keywords` are set only for the local fallback.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AskResult {
+    pub question: String,
+    pub context: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+/// `clear` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ClearResult {
+    pub deleted_chunks: u64,
+    pub source: String,
+}
+
+/// Words that carry no search signal in a question.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "all", "and", "any", "are", "been", "before", "but", "can", "could",
+    "did", "does", "doing", "don't", "for", "from", "get", "got", "had", "has", "have",
+    "her", "him", "his", "how", "i'm", "into", "its", "just", "last", "let", "like",
+    "me", "my", "not", "now", "our", "out", "said", "say", "she", "should", "some",
+    "tell", "than", "that", "the", "their", "them", "then", "there", "these", "they",
+    "this", "was", "were", "what", "when", "where", "which", "who", "whom",
+    "why", "will", "with", "would", "you", "your",
+];
+
+/// Client for the daemon socket, auto-starting it if asked to.
+pub fn client(socket: &Path, auto_start: bool) -> Result<DaemonClient> {
+    let client = DaemonClient::new(socket).with_timeout(RAG_TIMEOUT);
+    if !auto_start {
+        return Ok(client);
+    }
+    let daemon_bin = daemon_client::daemon_binary().ok_or_else(|| {
+        anyhow!(
+            "Cannot auto-start daemon: {} not found next to this binary (set {})",
+            daemon_client::DAEMON_BIN_NAME,
+            daemon_client::DAEMON_BIN_ENV
+        )
+    })?;
+    Ok(client.with_auto_start(AutoStart {
+        daemon_bin,
+        wait: daemon_client::DEFAULT_START_WAIT,
+    }))
+}
+
+/// Call the daemon, mapping "can't reach it" to CliError::DaemonUnavailable.
+fn call(client: &DaemonClient, method: &str, params: Map<String, Value>) -> Result<Value> {
+    client.call(method, Value::Object(params)).map_err(|e| match e {
+        e if e.is_unavailable() => CliError::DaemonUnavailable {
+            reason: e.to_string(),
+        }
+        .into(),
+        ClientError::Daemon { code, message } => anyhow!("{} ({}): {}", method, code, message),
+        e => anyhow!("{}: {}", method, e),
+    })
+}
+
+/// Params object with the `None` entries left out.
+fn params(entries: &[(&str, Option<Value>)]) -> Map<String, Value> {
+    entries
+        .iter()
+        .filter_map(|(k, v)| v.clone().map(|v| (k.to_string(), v)))
+        .collect()
+}
+
+/// "a, b" → ["a", "b"] (the daemon takes a list).
+fn source_list(sources: Option<&str>) -> Option<Value> {
+    sources.map(|s| {
+        json!(s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>())
+    })
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or("")
+}
+
+fn date_prefix(value: &Value, key: &str) -> String {
+    value[key].as_str().map(|s| s.chars().take(10).collect()).unwrap_or_else(|| "N/A".to_string())
+}
 
 /// Index content for semantic search (via daemon).
 pub fn index(
+    client: &DaemonClient,
     source: &str,
     days: u32,
     limit: Option<u32>,
     contact: Option<&str>,
     full: bool,
-    json: bool,
+    output: &OutputControls,
 ) -> Result<()> {
-    // [*INCOMPLETE*] Implement daemon IPC
-    // Status: Stub only
-    // Remaining: Port daemon_client.rs from wolfies-client
-    eprintln!(
-        "[TODO] index: source={}, days={}, limit={:?}, contact={:?}, full={}",
-        source, days, limit, contact, full
-    );
-    if json {
-        println!(r#"{{"error": "RAG commands delegate to Python daemon - not yet implemented"}}"#);
-    } else {
-        println!("RAG index command not yet implemented.");
-        println!("Use Python CLI: python3 gateway/imessage_client.py index --source={}", source);
+    let result = call(
+        client,
+        "index",
+        params(&[
+            ("source", Some(json!(source.to_lowercase()))),
+            ("days", Some(json!(days))),
+            ("limit", limit.map(|l| json!(l))),
+            ("contact", contact.map(|c| json!(c))),
+            ("full", Some(json!(full))),
+        ]),
+    )?;
+
+    if output.json {
+        output.print(&result)?;
+        return Ok(());
+    }
+
+    let indexed = result["chunks_indexed"].as_u64().unwrap_or(0);
+    println!("✓ Indexed {}", source);
+    println!("  Chunks found: {}", result["chunks_found"].as_u64().unwrap_or(indexed));
+    println!("  Chunks indexed: {}", indexed);
+    if let Some(secs) = result["elapsed_seconds"].as_f64() {
+        println!("  Duration: {:.1}s", secs);
+    }
+    if let Some(by_source) = result["by_source"].as_object() {
+        for (src, info) in by_source {
+            println!("  - {}: {} chunks", src, info["chunks_indexed"].as_u64().unwrap_or(0));
+        }
     }
     Ok(())
 }
 
 /// Semantic search across indexed content (via daemon).
-pub fn search(query: &str, sources: Option<&str>, days: Option<u32>, limit: u32, json: bool) -> Result<()> {
-    // [*INCOMPLETE*] Implement daemon IPC
-    eprintln!(
-        "[TODO] search: query={}, sources={:?}, days={:?}, limit={}",
-        query, sources, days, limit
-    );
-    if json {
-        println!(r#"{{"error": "RAG commands delegate to Python daemon - not yet implemented"}}"#);
-    } else {
-        println!("RAG search command not yet implemented.");
-        println!("Use Python CLI: python3 gateway/imessage_client.py search \"{}\"", query);
+pub fn search(
+    client: &DaemonClient,
+    query: &str,
+    sources: Option<&str>,
+    days: Option<u32>,
+    limit: u32,
+    output: &OutputControls,
+) -> Result<()> {
+    let result = call(
+        client,
+        "search",
+        params(&[
+            ("query", Some(json!(query))),
+            ("sources", source_list(sources)),
+            ("days", days.map(|d| json!(d))),
+            ("limit", Some(json!(limit))),
+        ]),
+    )?;
+
+    if output.json {
+        output.print(&result)?;
+        return Ok(());
+    }
+
+    let hits = result.as_array().cloned().unwrap_or_default();
+    if hits.is_empty() {
+        println!("No results found for: \"{}\"", query);
+        return Ok(());
+    }
+    println!("Found {} result(s) for: \"{}\"", hits.len(), query);
+    println!("{}", "-".repeat(60));
+    for (i, hit) in hits.iter().enumerate() {
+        let title = match str_field(hit, "title") {
+            "" => str_field(hit, "context_id").chars().take(30).collect::<String>(),
+            t => t.to_string(),
+        };
+        let text = output::preview(str_field(hit, "text"), 200);
+        println!(
+            "\n[{}] [{}] {} | {} | {:.0}% match",
+            i + 1,
+            hit["source"].as_str().unwrap_or("unknown"),
+            title,
+            str_field(hit, "timestamp").chars().take(10).collect::<String>(),
+            hit["score"].as_f64().unwrap_or(0.0) * 100.0
+        );
+        println!("    {}...", text);
     }
     Ok(())
 }
 
 /// Get AI-formatted context from knowledge base (via daemon).
-pub fn ask(question: &str, sources: Option<&str>, days: Option<u32>, limit: u32, json: bool) -> Result<()> {
-    // [*INCOMPLETE*] Implement daemon IPC
-    eprintln!(
-        "[TODO] ask: question={}, sources={:?}, days={:?}, limit={}",
-        question, sources, days, limit
-    );
-    if json {
-        println!(r#"{{"error": "RAG commands delegate to Python daemon - not yet implemented"}}"#);
+///
+/// With `local_fallback`, an unreachable daemon falls back to a keyword
+/// search of chat.db (iMessage only).
+#[allow(clippy::too_many_arguments)]
+pub fn ask(
+    client: &DaemonClient,
+    question: &str,
+    sources: Option<&str>,
+    days: Option<u32>,
+    limit: u32,
+    local_fallback: bool,
+    output: &OutputControls,
+) -> Result<()> {
+    let result = match call(
+        client,
+        "ask",
+        params(&[
+            ("question", Some(json!(question))),
+            ("sources", source_list(sources)),
+            ("days", days.map(|d| json!(d))),
+            ("limit", Some(json!(limit))),
+        ]),
+    ) {
+        Err(e) if local_fallback && is_unavailable(&e) && searches_imessage(sources) => {
+            return ask_local(question, days, limit, output);
+        }
+        result => result?,
+    };
+
+    // The daemon returns the context as a string (or {"context": ...})
+    let context = result
+        .as_str()
+        .or_else(|| result["context"].as_str())
+        .unwrap_or("")
+        .to_string();
+    if output.json {
+        output.print(&AskResult {
+            question: question.to_string(),
+            context,
+            engine: None,
+            keywords: None,
+        })?;
     } else {
-        println!("RAG ask command not yet implemented.");
-        println!("Use Python CLI: python3 gateway/imessage_client.py ask \"{}\"", question);
+        println!("{}", context);
     }
     Ok(())
 }
 
-/// Show knowledge base statistics (via daemon).
-pub fn stats(source: Option<&str>, json: bool) -> Result<()> {
-    // [*INCOMPLETE*] Implement daemon IPC
-    eprintln!("[TODO] stats: source={:?}", source);
-    if json {
-        println!(r#"{{"error": "RAG commands delegate to Python daemon - not yet implemented"}}"#);
+fn is_unavailable(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<CliError>(), Some(CliError::DaemonUnavailable { .. }))
+}
+
+/// Local search only covers iMessage; no --sources means all.
+fn searches_imessage(sources: Option<&str>) -> bool {
+    source_list(sources)
+        .and_then(|v| v.as_array().cloned())
+        .is_none_or(|list| list.iter().any(|s| s.as_str() == Some("imessage")))
+}
+
+/// `ask` without the daemon: keyword search over chat.db.
+fn ask_local(question: &str, days: Option<u32>, limit: u32, output: &OutputControls) -> Result<()> {
+    let conn = connection::open_db().context("Failed to open Messages database")?;
+    let keywords = question_keywords(question);
+    let results = local_results(&conn, &keywords, days, limit)?;
+    let context = format_local_context(question, &keywords, &results);
+    output::warn("daemon unavailable - using local keyword search");
+
+    if output.json {
+        output.print(&AskResult {
+            question: question.to_string(),
+            context,
+            engine: Some(LOCAL_ENGINE.to_string()),
+            keywords: Some(keywords),
+        })?;
     } else {
-        println!("RAG stats command not yet implemented.");
-        println!("Use Python CLI: python3 gateway/imessage_client.py stats");
+        println!("{}", context);
+    }
+    Ok(())
+}
+
+/// Salient search keywords from a question: lowercased, stopwords and
+/// short tokens dropped, first occurrence kept.
+pub fn question_keywords(question: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for token in question.split(|c: char| !(c.is_alphanumeric() || c == '\'')) {
+        let token = token.trim_matches('\'').to_lowercase();
+        let token = token.strip_suffix("'s").unwrap_or(&token).to_string();
+        let long_enough = if token.chars().all(|c| c.is_ascii_digit()) {
+            token.len() >= 2
+        } else {
+            token.chars().count() >= 3
+        };
+        if long_enough && !STOPWORDS.contains(&token.as_str()) && !keywords.contains(&token) {
+            keywords.push(token);
+        }
+    }
+    keywords.truncate(MAX_KEYWORDS);
+    keywords
+}
+
+/// A local hit plus the messages around it.
+#[derive(Debug, Clone)]
+struct LocalResult {
+    hit: SearchHit,
+    context: Vec<ContextMessage>,
+}
+
+/// Top `limit` hits for any keyword, most keywords matched first.
+fn local_results(
+    conn: &Connection,
+    keywords: &[String],
+    days: Option<u32>,
+    limit: u32,
+) -> Result<Vec<LocalResult>> {
+    let cutoff = days.map(queries::days_ago_cocoa);
+    let mut hits: Vec<SearchHit> = helpers::query_text_search(
+        conn,
+        keywords,
+        queries::TermMatch::Any,
+        None,
+        false,
+        limit.saturating_mul(10).max(50),
+    )?
+    .into_iter()
+    .filter(|hit| cutoff.is_none_or(|c| hit.date_cocoa >= c))
+    .collect();
+    // Stable sort: ties stay newest first
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.matched_terms.len()));
+    hits.truncate(limit as usize);
+
+    hits.into_iter()
+        .map(|hit| {
+            let context =
+                helpers::query_message_context(conn, hit.rowid, LOCAL_CONTEXT_WINDOW, LOCAL_CONTEXT_WINDOW)?;
+            Ok(LocalResult { hit, context })
+        })
+        .collect()
+}
+
+fn local_timestamp(date_cocoa: i64) -> String {
+    chrono::Local
+        .timestamp_opt(queries::cocoa_to_unix(date_cocoa), 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Render results like the daemon's `ask` (the Python retriever format), so
+/// prompts built on either engine read the same.
+fn format_local_context(question: &str, keywords: &[String], results: &[LocalResult]) -> String {
+    if results.is_empty() {
+        return format!("No relevant content found for: \"{}\"", question);
+    }
+
+    let mut formatted = vec![format!(
+        "Found {} relevant result(s) for: \"{}\"\n",
+        results.len(),
+        question
+    )];
+    for (i, result) in results.iter().enumerate() {
+        let hit = &result.hit;
+        let relevance = hit.matched_terms.len() * 100 / keywords.len().max(1);
+        let title = hit
+            .cache_roomnames
+            .clone()
+            .filter(|r| !r.is_empty())
+            .or_else(|| hit.handle.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let mut header = vec!["[imessage]".to_string(), title];
+        let timestamp = local_timestamp(hit.date_cocoa);
+        if !timestamp.is_empty() {
+            header.push(timestamp);
+        }
+        header.push(format!("(relevance: {}%)", relevance));
+        formatted.push(format!("\n**Result {}** {}:", i + 1, header.join(" | ")));
+
+        if result.context.is_empty() {
+            formatted.push(hit.text.clone());
+        }
+        for message in &result.context {
+            let sender = if message.is_from_me {
+                "Me"
+            } else {
+                message.handle.as_deref().unwrap_or("unknown")
+            };
+            // Quote the matching message so it stands out from its context
+            let marker = if message.rowid == hit.rowid { "> " } else { "" };
+            formatted.push(format!(
+                "{}{}: {}",
+                marker,
+                sender,
+                message.text.as_deref().unwrap_or("")
+            ));
+        }
+        formatted.push("\n---".to_string());
+    }
+    formatted.join("\n")
+}
+
+/// Show knowledge base statistics (via daemon).
+pub fn stats(client: &DaemonClient, source: Option<&str>, output: &OutputControls) -> Result<()> {
+    let result = call(client, "stats", params(&[("source", source.map(|s| json!(s)))]))?;
+
+    if output.json {
+        output.print(&result)?;
+        return Ok(());
+    }
+
+    let total = result["total_chunks"].as_u64().unwrap_or(0);
+    if total == 0 {
+        println!("Knowledge base is empty.");
+        println!("\nRun 'index --source=<source>' to start indexing:");
+        println!("  index --source=imessage      Index iMessage conversations");
+        println!("  index --source=superwhisper  Index voice transcriptions");
+        println!("  index --source=notes         Index markdown notes");
+        println!("  index --source=local         Index all local sources");
+        return Ok(());
+    }
+
+    println!("Knowledge Base Statistics");
+    println!("{}", "=".repeat(40));
+    println!("Total chunks indexed: {}", total);
+    println!("Unique participants: {}", result["unique_participants"].as_u64().unwrap_or(0));
+    println!("Unique tags: {}", result["unique_tags"].as_u64().unwrap_or(0));
+    if let Some(by_source) = result["by_source"].as_object() {
+        println!("\nBy Source:");
+        let mut entries: Vec<_> = by_source.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (src, info) in entries {
+            let count = info["chunk_count"].as_u64().unwrap_or(0);
+            if count > 0 {
+                println!(
+                    "  {}: {} chunks ({} to {})",
+                    src,
+                    count,
+                    date_prefix(info, "oldest"),
+                    date_prefix(info, "newest")
+                );
+            }
+        }
     }
     Ok(())
 }
 
-/// Clear indexed data (via daemon).
-pub fn clear(source: Option<&str>, force: bool, json: bool) -> Result<()> {
-    // [*INCOMPLETE*] Implement daemon IPC
-    eprintln!("[TODO] clear: source={:?}, force={}", source, force);
-    if json {
-        println!(r#"{{"error": "RAG commands delegate to Python daemon - not yet implemented"}}"#);
+/// Clear indexed data (via daemon). Asks first unless `force`.
+pub fn clear(
+    client: &DaemonClient,
+    source: Option<&str>,
+    force: bool,
+    output: &OutputControls,
+) -> Result<()> {
+    if !force {
+        let stats = call(client, "stats", params(&[("source", source.map(|s| json!(s)))]))?;
+        let total = stats["total_chunks"].as_u64().unwrap_or(0);
+        if total == 0 {
+            if output.json {
+                output.print(&ClearResult {
+                    deleted_chunks: 0,
+                    source: source.unwrap_or("all").to_string(),
+                })?;
+            } else {
+                println!("Nothing to clear - knowledge base is empty.");
+            }
+            return Ok(());
+        }
+        let scope = source.map(|s| format!(" for source '{}'", s)).unwrap_or_default();
+        if !confirm(&format!("About to delete {} chunks{}. Continue?", total, scope))? {
+            bail!("Clear cancelled (use --force to skip confirmation)");
+        }
+    }
+
+    let result = call(
+        client,
+        "clear",
+        params(&[("source", source.map(|s| json!(s))), ("force", Some(json!(true)))]),
+    )?;
+    let deleted = result["deleted_chunks"].as_u64().or_else(|| result.as_u64()).unwrap_or(0);
+
+    if output.json {
+        output.print(&ClearResult {
+            deleted_chunks: deleted,
+            source: source.unwrap_or("all").to_string(),
+        })?;
     } else {
-        println!("RAG clear command not yet implemented.");
-        println!("Use Python CLI: python3 gateway/imessage_client.py clear");
+        println!("✓ Deleted {} chunks", deleted);
     }
     Ok(())
 }
 
+/// y/N prompt on stderr (stdout stays clean for --json). Needs a terminal.
+fn confirm(question: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        bail!("Refusing to clear without confirmation (no terminal); pass --force");
+    }
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 /// List available and indexed sources (via daemon).
-pub fn sources(json: bool) -> Result<()> {
-    // [*INCOMPLETE*] Implement daemon IPC
-    eprintln!("[TODO] sources");
-    if json {
-        println!(r#"{{"error": "RAG commands delegate to Python daemon - not yet implemented"}}"#);
-    } else {
-        println!("RAG sources command not yet implemented.");
-        println!("Use Python CLI: python3 gateway/imessage_client.py sources");
+pub fn sources(client: &DaemonClient, output: &OutputControls) -> Result<()> {
+    let result = call(client, "sources", Map::new())?;
+
+    if output.json {
+        output.print(&result)?;
+        return Ok(());
     }
+
+    let indexed: Vec<&str> = result["indexed"]
+        .as_array()
+        .map(|a| a.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    println!("Available Sources:");
+    println!("{}", "-".repeat(40));
+    for src in result["available"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+        let count = result["details"][src].as_u64().unwrap_or(0);
+        let status = if count > 0 {
+            format!("({} chunks)", count)
+        } else {
+            "(not indexed)".to_string()
+        };
+        let marker = if indexed.contains(&src) { "✓" } else { " " };
+        println!("  {} {} {}", marker, src, status);
+    }
+    println!("\nTo index a source:");
+    println!("  index --source=<source> [--days=30]");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_skip_none() {
+        let p = params(&[
+            ("query", Some(json!("dinner"))),
+            ("sources", source_list(Some("imessage, notes,"))),
+            ("days", None),
+        ]);
+        assert_eq!(Value::Object(p), json!({"query": "dinner", "sources": ["imessage", "notes"]}));
+    }
+
+    #[test]
+    fn test_unreachable_daemon_is_daemon_unavailable() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = DaemonClient::new(dir.path().join("missing.sock"));
+        let err = call(&client, "stats", Map::new()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CliError>(),
+            Some(CliError::DaemonUnavailable { .. })
+        ));
+        assert!(err.to_string().contains("wolfies-imessage-daemon start"), "{}", err);
+    }
+
+    #[test]
+    fn test_question_keywords() {
+        assert_eq!(
+            question_keywords("What did Sarah say about the dinner reservation on Friday?"),
+            vec!["sarah", "dinner", "reservation", "friday"]
+        );
+        assert_eq!(
+            question_keywords("Where's John's flight to NYC? NYC, 2pm, 42"),
+            vec!["john", "flight", "nyc", "2pm", "42"]
+        );
+        assert!(question_keywords("what is it?").is_empty());
+        assert_eq!(question_keywords("a b c d e f g h i j k l m n o p q r s t u v w x y z").len(), 0);
+        let many = "alpha bravo charlie delta echo foxtrot golf hotel india juliet";
+        assert_eq!(question_keywords(many).len(), MAX_KEYWORDS);
+    }
+
+    #[test]
+    fn test_searches_imessage() {
+        assert!(searches_imessage(None));
+        assert!(searches_imessage(Some("notes, imessage")));
+        assert!(!searches_imessage(Some("notes,superwhisper")));
+    }
+
+    #[test]
+    fn test_local_results_rank_and_window() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("want to grab dinner?"), DAY_NS, false, true);
+        let best = insert_message(&conn, chat, alice, Some("dinner at Nopa friday"), 2 * DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("sounds good"), 3 * DAY_NS, true, true);
+        insert_message(&conn, chat, alice, Some("friday works"), 4 * DAY_NS, false, true);
+
+        let keywords = question_keywords("When is dinner on Friday?");
+        let results = local_results(&conn, &keywords, None, 2).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].hit.rowid, best);
+        assert_eq!(results[0].context.len(), 4);
+        // Single-keyword ties stay newest first
+        assert_eq!(results[1].hit.text, "friday works");
+
+        let context = format_local_context("When is dinner on Friday?", &keywords, &results);
+        assert!(context.starts_with("Found 2 relevant result(s) for: \"When is dinner on Friday?\"\n"));
+        assert!(context.contains("\n**Result 1** [imessage] | +14155550001 | "));
+        assert!(context.contains("(relevance: 100%):\n+14155550001: want to grab dinner?\n> +14155550001: dinner at Nopa friday\nMe: sounds good"));
+        assert!(context.contains("(relevance: 50%)"));
+        assert!(context.ends_with("\n---"));
+    }
+
+    #[test]
+    fn test_local_context_no_results() {
+        assert_eq!(
+            format_local_context("anything?", &[], &[]),
+            "No relevant content found for: \"anything?\""
+        );
+    }
+}
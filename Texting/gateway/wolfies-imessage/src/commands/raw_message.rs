@@ -0,0 +1,264 @@
+//! `raw-message`: one message row as Messages stored it, for debugging.
+//!
+//! Dumps every column of the row (BLOBs as base64), what `parse_blob` made
+//! of attributedBody and which of its decoders succeeded, the chat_message_join
+//! and chat rows, the attachments, and the rows associated with the message
+//! (tapbacks, stickers) as pretty JSON, so a parser bug can be filed with the
+//! row that triggers it.
+//!
+//! `--redact-text` overwrites the message text (text, subject, and the text
+//! inside attributedBody) with `x`s of the same byte length, keeping
+//! whitespace, so the redacted blob still decodes the way the original did.
+//! A blob whose text isn't stored verbatim (e.g. UTF-16 in a bplist) is
+//! dropped instead.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial raw-message command
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Connection, ToSql};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::db::blob_parser::{self, BlobStrategy};
+use crate::db::connection::open_db;
+use crate::db::queries;
+use crate::output::OutputControls;
+
+/// Columns holding message text (redacted by `--redact-text`).
+const TEXT_COLUMNS: &[&str] = &["text", "subject"];
+
+/// Column holding the archived attributed string.
+const BLOB_COLUMN: &str = "attributedBody";
+
+/// Which message to dump.
+#[derive(Debug, Clone, Copy)]
+pub enum MessageKey<'a> {
+    Guid(&'a str),
+    Rowid(i64),
+}
+
+/// What `parse_blob` made of attributedBody.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BlobDecode {
+    pub bytes: usize,
+    pub text: Option<String>,
+    /// The decoder that produced `text`
+    pub strategy: Option<BlobStrategy>,
+    /// Why parsing failed, if it did
+    pub error: Option<String>,
+}
+
+/// `raw-message` output. Rows map column names to values; BLOBs are base64.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RawMessage {
+    pub message: Map<String, Value>,
+    pub attributed_body: Option<BlobDecode>,
+    /// chat_message_join columns, then the chat's, one row per chat
+    pub chats: Vec<Map<String, Value>>,
+    pub attachments: Vec<Map<String, Value>>,
+    /// Rows whose associated_message_guid points at this message
+    pub associated: Vec<Map<String, Value>>,
+    pub redacted: bool,
+}
+
+/// A row's columns in order, as stored.
+type RawRow = Vec<(String, SqlValue)>;
+
+fn query_rows(conn: &Connection, sql: &str, param: &dyn ToSql) -> Result<Vec<RawRow>> {
+    let mut stmt = conn.prepare(sql)?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let rows = stmt.query_map([param], |row| {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Ok((name.clone(), row.get::<_, SqlValue>(i)?)))
+            .collect::<rusqlite::Result<RawRow>>()
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+fn to_json(value: SqlValue) -> Value {
+    match value {
+        SqlValue::Null => Value::Null,
+        SqlValue::Integer(n) => Value::from(n),
+        SqlValue::Real(x) => serde_json::Number::from_f64(x).map_or(Value::Null, Value::Number),
+        SqlValue::Text(s) => Value::String(s),
+        SqlValue::Blob(bytes) => Value::String(STANDARD.encode(bytes)),
+    }
+}
+
+/// Every non-whitespace character as `x`s of the same UTF-8 length.
+fn redact(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_whitespace() { c.to_string() } else { "x".repeat(c.len_utf8()) })
+        .collect()
+}
+
+/// `blob` with each verbatim copy of `text` redacted in place; None when
+/// the text isn't in it verbatim.
+fn redact_blob(blob: &[u8], text: &str) -> Option<Vec<u8>> {
+    let needle = text.as_bytes();
+    if needle.is_empty() {
+        return Some(blob.to_vec());
+    }
+    let replacement = redact(text).into_bytes();
+    let mut out = Vec::with_capacity(blob.len());
+    let mut rest = blob;
+    let mut found = false;
+    while let Some(at) = rest.windows(needle.len()).position(|w| w == needle) {
+        out.extend_from_slice(&rest[..at]);
+        out.extend_from_slice(&replacement);
+        rest = &rest[at + needle.len()..];
+        found = true;
+    }
+    out.extend_from_slice(rest);
+    found.then_some(out)
+}
+
+/// A message-table row as JSON (text redacted when asked) and its
+/// attributedBody decode.
+fn message_row(row: RawRow, redact_text: bool) -> (Map<String, Value>, Option<BlobDecode>) {
+    let mut decode = None;
+    let mut map = Map::new();
+    for (name, value) in row {
+        let value = match value {
+            SqlValue::Text(text) if redact_text && TEXT_COLUMNS.contains(&name.as_str()) => {
+                SqlValue::Text(redact(&text))
+            }
+            SqlValue::Blob(blob) if name == BLOB_COLUMN => {
+                let (text, strategy, error) = match blob_parser::parse_blob(&blob) {
+                    Ok(parsed) => {
+                        let strategy = parsed.as_ref().map(|p| p.strategy);
+                        (parsed.map(|p| p.text), strategy, None)
+                    }
+                    Err(e) => (None, None, Some(e.to_string())),
+                };
+                let value = match (&text, redact_text) {
+                    (_, false) => SqlValue::Blob(blob.clone()),
+                    (Some(text), true) => redact_blob(&blob, text).map_or(SqlValue::Null, SqlValue::Blob),
+                    (None, true) => SqlValue::Null,
+                };
+                let text = if redact_text { text.as_deref().map(redact) } else { text };
+                decode = Some(BlobDecode { bytes: blob.len(), text, strategy, error });
+                value
+            }
+            value => value,
+        };
+        map.insert(name, to_json(value));
+    }
+    (map, decode)
+}
+
+fn plain_rows(rows: Vec<RawRow>) -> Vec<Map<String, Value>> {
+    rows.into_iter()
+        .map(|row| row.into_iter().map(|(name, value)| (name, to_json(value))).collect())
+        .collect()
+}
+
+/// Dump the message `key` names.
+pub fn build_dump(conn: &Connection, key: MessageKey, redact_text: bool) -> Result<RawMessage> {
+    let rows = match key {
+        MessageKey::Guid(guid) => query_rows(conn, queries::RAW_MESSAGE_BY_GUID, &guid)?,
+        MessageKey::Rowid(rowid) => query_rows(conn, queries::RAW_MESSAGE_BY_ROWID, &rowid)?,
+    };
+    let row = rows.into_iter().next().ok_or_else(|| match key {
+        MessageKey::Guid(guid) => anyhow!("No message with GUID {}", guid),
+        MessageKey::Rowid(rowid) => anyhow!("No message with ROWID {}", rowid),
+    })?;
+    let (message, attributed_body) = message_row(row, redact_text);
+    let rowid = message.get("ROWID").and_then(Value::as_i64).unwrap_or_default();
+    let guid = message.get("guid").and_then(Value::as_str).unwrap_or_default().to_string();
+
+    let associated = query_rows(conn, queries::RAW_MESSAGE_REACTIONS, &guid)?
+        .into_iter()
+        .map(|row| message_row(row, redact_text).0)
+        .collect();
+    Ok(RawMessage {
+        message,
+        attributed_body,
+        chats: plain_rows(query_rows(conn, queries::RAW_MESSAGE_CHATS, &rowid)?),
+        attachments: plain_rows(query_rows(conn, queries::RAW_MESSAGE_ATTACHMENTS, &rowid)?),
+        associated,
+        redacted: redact_text,
+    })
+}
+
+/// Print one message's raw rows as pretty JSON.
+pub fn raw_message(key: MessageKey, redact_text: bool, output: &OutputControls) -> Result<()> {
+    let conn = open_db()?;
+    let dump = build_dump(&conn, key, redact_text)?;
+    output.print_json(&dump, true)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_attachment, insert_chat, insert_handle, insert_message, insert_reaction, DAY_NS};
+
+    const MENTION: &[u8] = include_bytes!("../db/testdata/attributed_body/mention.bin");
+
+    fn fixture() -> (Connection, i64) {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let rowid = insert_message(&conn, chat, alice, None, 700 * DAY_NS, false, true);
+        conn.execute("UPDATE message SET attributedBody = ?1 WHERE ROWID = ?2", rusqlite::params![MENTION, rowid])
+            .unwrap();
+        insert_attachment(&conn, rowid, Some("image/jpeg"), "IMG_0001.jpg");
+        insert_reaction(&conn, chat, 0, &format!("msg-{}", rowid), 2000, 701 * DAY_NS, true);
+        insert_message(&conn, chat, alice, Some("unrelated"), 702 * DAY_NS, false, true);
+        (conn, rowid)
+    }
+
+    #[test]
+    fn test_dump_by_rowid_and_guid() {
+        let (conn, rowid) = fixture();
+        let dump = build_dump(&conn, MessageKey::Rowid(rowid), false).unwrap();
+        assert_eq!(dump.message["guid"], "msg-1");
+        assert_eq!(dump.message["attributedBody"], STANDARD.encode(MENTION));
+        let decode = dump.attributed_body.unwrap();
+        assert_eq!(decode.text.as_deref(), Some("Hey @Sarah are you coming?"));
+        assert_eq!(decode.strategy, Some(BlobStrategy::Typedstream));
+        assert_eq!(dump.chats.len(), 1);
+        assert_eq!(dump.chats[0]["chat_identifier"], "+14155550001");
+        assert_eq!(dump.attachments[0]["transfer_name"], "IMG_0001.jpg");
+        assert_eq!(dump.associated.len(), 1);
+        assert_eq!(dump.associated[0]["associated_message_guid"], "p:0/msg-1");
+
+        let by_guid = build_dump(&conn, MessageKey::Guid("msg-1"), false).unwrap();
+        assert_eq!(by_guid.message, dump.message);
+        assert!(build_dump(&conn, MessageKey::Rowid(99), false).is_err());
+        assert!(build_dump(&conn, MessageKey::Guid("nope"), false).is_err());
+    }
+
+    #[test]
+    fn test_redacted_blob_still_decodes() {
+        let (conn, rowid) = fixture();
+        conn.execute("UPDATE message SET text = 'Hey @Sarah' WHERE ROWID = ?1", [rowid]).unwrap();
+        let dump = build_dump(&conn, MessageKey::Rowid(rowid), true).unwrap();
+        assert!(dump.redacted);
+        assert_eq!(dump.message["text"], "xxx xxxxxx");
+        let redacted_text = "xxx xxxxxx xxx xxx xxxxxxx";
+        assert_eq!(dump.attributed_body.unwrap().text.as_deref(), Some(redacted_text));
+
+        let blob = STANDARD.decode(dump.message["attributedBody"].as_str().unwrap()).unwrap();
+        assert_eq!(blob.len(), MENTION.len());
+        assert!(!blob.windows(5).any(|w| w == b"Sarah"));
+        let parsed = blob_parser::parse_blob(&blob).unwrap().unwrap();
+        assert_eq!(parsed.text, redacted_text);
+    }
+
+    #[test]
+    fn test_redact_keeps_byte_length() {
+        assert_eq!(redact("hi there"), "xx xxxxx");
+        assert_eq!(redact("café 👍").len(), "café 👍".len());
+        assert_eq!(redact_blob(b"..hi..hi", "hi"), Some(b"..xx..xx".to_vec()));
+        assert_eq!(redact_blob(b"nope", "hi"), None);
+    }
+}
@@ -1,490 +1,794 @@
 //! Reading commands: find, messages, recent, unread, text-search, bundle, etc.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Moved summary to commands::summary
+//! - 10/16/2026 - recent --per-conversation muted_count counts the muted conversations the listing left out (api::muted_conversation_count), not every muted chat
+//! - 10/16/2026 - bundle reads every section in one snapshot (connection::read_snapshot, one connection); meta.snapshot and meta.max_rowid
+//! - 10/16/2026 - bundle unread_count and unread_messages follow the unread command (api::unread_count, helpers::unread_conditions: read position, muted conversations left out)
+//! - 10/16/2026 - summary_range renamed date_range (shared with analytics --start/--end)
+//! - 10/16/2026 - messages and summary --with-reactions: standing tapbacks per message (reactions: emoji, from, is_from_me), one query per page
+//! - 10/16/2026 - bundle trims to a token budget (budget::trim_to_budget; meta.trimmed)
+//! - 10/16/2026 - Messages carry item_kind; system items and app messages without text get placeholders ("[FaceTime call]", "[renamed the group to ...]")
+//! - 10/16/2026 - Messages carry service (message.service, else the chat's); find/messages take a ServiceFilter
+//! - 10/16/2026 - Message and summary dates decode through queries::decode_cocoa (seconds or nanoseconds); implausible dates are null with date_suspect: true
+//! - 10/16/2026 - unread --by-conversation (UnreadChat rows: count and oldest unread per chat, past the read position)
+//! - 10/16/2026 - summary --detect-language: per-message lang and language_distribution (crate::language)
+//! - 10/16/2026 - bundle warns about unknown --include sections; rows that fail to decode are skipped with a warning (helpers::ok_row)
+//! - 10/16/2026 - Message.is_known_sender (senders::SenderFilter); recent and unread accept --known-only / --unknown-only
+//! - 10/16/2026 - find/messages: no messages with a name-resolved contact reports the resolution (contact, phone, score, other candidates)
+//! - 10/16/2026 - unread --count-only (unread_count: one COUNT query, no contacts)
+//! - 10/16/2026 - --envelope: find/messages record contact_resolved; bundle, reactions, voice JSON through print_json
+//! - 10/16/2026 - Message.display_name (contact > 1:1 chat display name > handle); text output uses it
+//! - 10/16/2026 - text-search --group-id/--group (one chat; group name in the header)
+//! - 10/16/2026 - summary --cursor keyset pagination (meta.next_cursor); OFFSET kept for small values
+//! - 10/16/2026 - Attachment-only messages carry a placeholder ("[photo]", "[file: name]") with empty text; text comes from db::message_body
+//! - 10/16/2026 - unread and recent --per-conversation leave out muted conversations (--include-muted), report muted_count
+//! - 10/16/2026 - recent, find, unread, text-search print results from the api layer; Message carries contact_name
+//! - 10/16/2026 - recent, find, unread, text-search render aligned columns via output::show
+//! - 10/16/2026 - Text-mode previews use output::preview (char-safe, one line)
+//! - 10/16/2026 - Implemented summary (contact transcript window) with --include-deleted
+//! - 10/16/2026 - Message, BundleMessage, and search rows carry rowid and guid
+//! - 10/16/2026 - `messages self` / `find self` read the notes-to-self conversation
+//! - 10/16/2026 - Typed JSON outputs (JsonSchema) for bundle, reactions, voice, thread
+//! - 10/16/2026 - recent, unread, find read blob text through the text cache
+//! - 10/16/2026 - recent, unread, find, messages accept --entities (mentions, links, subject)
+//! - 10/16/2026 - find and bundle search escape LIKE wildcards
+//! - 10/16/2026 - text-search supports --any/--all with matched_terms
+//! - 10/16/2026 - Moved links command to commands::links
+//! - 10/16/2026 - recent and unread accept a relationship handle filter
+//! - 10/16/2026 - thread returns the full reply tree with depth/reply_to_guid
+//! - 10/16/2026 - Enriched voice output with duration, transcript, and sender name
+//! - 10/16/2026 - Moved attachments command to commands::attachments
+//! - 10/16/2026 - Added recent --per-conversation (latest message per chat)
+//! - 10/16/2026 - Reactions use shared reaction_kind (removals, custom emoji)
 //! - 01/10/2026 - Implemented recent command with actual DB queries (Claude)
 //! - 01/10/2026 - Initial stub implementation (Claude)
 
-use crate::db::{blob_parser, connection, queries};
-use crate::output::OutputControls;
-use anyhow::{Context, Result};
-use chrono::{DateTime, TimeZone, Utc};
+use crate::api;
+use crate::budget;
+use crate::commands::attachments;
+use crate::contacts::manager::{ContactsManager, Resolution};
+use crate::db::blob_parser::Entities;
+use crate::db::item_kind::{ItemCodes, ItemKind};
+use crate::db::message_body::{self, display_text};
+use crate::db::{blob_parser, connection, helpers, queries};
+use crate::mutes::MuteFilter;
+use crate::senders::SenderMode;
+use crate::output::{self, MessageLine, OutputControls, TextRenderer, TextStyle, Titled, Tone};
+use anyhow::{anyhow, Context, Result};
+use chrono::{TimeZone, Utc};
+use schemars::JsonSchema;
 use serde::Serialize;
-use serde_json::json;
+use std::collections::HashMap;
 
 /// Message struct for serialization.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct Message {
+    pub rowid: i64,
+    pub guid: String,
     pub text: String,
+    /// Attachment or system item placeholder ("[photo]", "[FaceTime call]", ...); `text` is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    /// Message, or a system item / app message (group_rename, facetime, payment, ...)
+    pub item_kind: ItemKind,
     pub date: Option<String>,
+    /// The stored date is zero, before 2001, or in the future (`date` is null)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub date_suspect: bool,
     pub is_from_me: bool,
     pub phone: String,
+    /// Contact name for `phone`, when it's in contacts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_name: Option<String>,
+    /// Contact name, else the 1:1 chat's display name, else `phone`
+    pub display_name: String,
+    /// Sender is a contact, or in a chat Messages didn't filter (see `senders`)
+    pub is_known_sender: bool,
     pub is_group_chat: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group_id: Option<String>,
+    /// Service the message went over ("iMessage", "SMS"), else its chat's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// Mentions, links and subject (only with --entities).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Entities>,
+    /// Standing tapbacks (only with --with-reactions; absent when there are none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reactions: Vec<helpers::MessageReaction>,
 }
 
-/// Convert Cocoa timestamp (nanoseconds since 2001-01-01) to ISO string.
-fn cocoa_to_iso(cocoa_ns: i64) -> Option<String> {
-    if cocoa_ns == 0 {
-        return None;
+impl TextRenderer for [Message] {
+    fn render(&self, style: &TextStyle) -> String {
+        let texts: Vec<String> = self
+            .iter()
+            .map(|m| format!("{}{}", display_text(&m.text, m.placeholder.as_deref()), reaction_suffix(&m.reactions)))
+            .collect();
+        let lines: Vec<MessageLine> = self
+            .iter()
+            .zip(&texts)
+            .map(|(m, text)| MessageLine {
+                date: m.date.as_deref(),
+                sender: if m.is_from_me { "Me" } else { &m.display_name },
+                is_from_me: m.is_from_me,
+                text,
+            })
+            .collect();
+        output::render_message_lines(&lines, 80, style)
     }
-    let unix_secs = queries::cocoa_to_unix(cocoa_ns);
-    Utc.timestamp_opt(unix_secs, 0)
-        .single()
-        .map(|dt: DateTime<Utc>| dt.to_rfc3339())
-}
-
-/// Check if a chat identifier indicates a group chat.
-fn is_group_chat_identifier(chat_id: Option<&str>) -> bool {
-    match chat_id {
-        None => false,
-        Some(id) => {
-            // Group chats start with 'chat' followed by digits
-            if id.starts_with("chat") && id[4..].chars().all(|c| c.is_ascii_digit()) {
-                return true;
-            }
-            // Or contain comma-separated handles
-            id.contains(',')
-        }
+}
+
+/// Text search result: a message plus the search terms it matched.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchMatch {
+    #[serde(flatten)]
+    pub message: Message,
+    pub matched_terms: Vec<String>,
+}
+
+impl TextRenderer for [SearchMatch] {
+    fn render(&self, style: &TextStyle) -> String {
+        let lines: Vec<MessageLine> = self
+            .iter()
+            .map(|m| MessageLine {
+                date: m.message.date.as_deref(),
+                sender: match m.message.contact_name {
+                    _ if m.message.is_from_me => "Me",
+                    Some(ref name) => name,
+                    None => &m.message.phone,
+                },
+                is_from_me: m.message.is_from_me,
+                text: display_text(&m.message.text, m.message.placeholder.as_deref()),
+            })
+            .collect();
+        output::render_message_lines(&lines, 100, style)
     }
 }
 
-/// Extract message text from text column or attributedBody blob.
-fn get_message_text(text: Option<String>, attributed_body: Option<Vec<u8>>) -> String {
-    if let Some(t) = text {
-        if !t.is_empty() {
-            return t;
+/// Entities for one message: blob attribute runs plus the subject column.
+fn message_entities(attributed_body: Option<&[u8]>, subject: Option<String>) -> Entities {
+    let mut entities = attributed_body
+        .and_then(|blob| blob_parser::parse_blob(blob).ok().flatten())
+        .map(|parsed| parsed.entities)
+        .unwrap_or_default();
+    entities.subject = subject.filter(|s| !s.is_empty());
+    entities
+}
+
+/// One raw row of `queries::MESSAGE_ROWS`.
+pub(crate) struct MessageRow {
+    pub text: Option<String>,
+    pub attributed_body: Option<Vec<u8>>,
+    pub date_cocoa: i64,
+    pub is_from_me: bool,
+    pub handle: Option<String>,
+    pub cache_roomnames: Option<String>,
+    pub subject: Option<String>,
+    pub guid: Option<String>,
+    pub rowid: i64,
+    pub attachment_hint: Option<String>,
+    /// chat_identifier TAB display_name
+    pub chat_hint: Option<String>,
+    pub service: Option<String>,
+    pub item: ItemCodes,
+}
+
+impl MessageRow {
+    /// chat_identifier of the message's chat.
+    pub(crate) fn chat_identifier(&self) -> Option<&str> {
+        self.chat_hint.as_deref()?.split('\t').next().filter(|id| !id.is_empty())
+    }
+
+    /// Display name of the message's chat when it's a 1:1 chat with one set.
+    pub(crate) fn direct_chat_name(&self) -> Option<&str> {
+        let (identifier, name) = self.chat_hint.as_deref()?.split_once('\t')?;
+        (!name.is_empty() && !helpers::is_group_chat_identifier(Some(identifier))).then_some(name)
+    }
+
+    /// Decode text (and entities, if asked) into a `Message` without a contact
+    /// name (`display_name` is the handle until the caller resolves it).
+    pub(crate) fn into_message(self, with_entities: bool) -> Message {
+        let entities = with_entities.then(|| message_entities(self.attributed_body.as_deref(), self.subject));
+        let is_group = helpers::is_group_chat_identifier(self.cache_roomnames.as_deref());
+        let (text, placeholder) = message_body::message_body(
+            self.guid.as_deref(),
+            self.text,
+            self.attributed_body.as_deref(),
+            self.attachment_hint.as_deref(),
+        )
+        .with_item_placeholder(self.item.placeholder())
+        .into_parts();
+        let phone = self.handle.unwrap_or_else(|| "unknown".to_string());
+        let date = queries::decode_cocoa(self.date_cocoa);
+        Message {
+            item_kind: self.item.kind(),
+            rowid: self.rowid,
+            text,
+            placeholder,
+            guid: self.guid.unwrap_or_default(),
+            date: date.iso(),
+            date_suspect: date.suspect,
+            is_from_me: self.is_from_me,
+            display_name: phone.clone(),
+            phone,
+            contact_name: None,
+            is_known_sender: false,
+            is_group_chat: is_group,
+            group_id: if is_group { self.cache_roomnames } else { None },
+            service: self.service.filter(|s| !s.is_empty()),
+            entities,
+            reactions: Vec::new(),
         }
     }
+}
+
+/// Conversation row for per-conversation recent output.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConversationRow {
+    pub chat_identifier: String,
+    pub display_name: Option<String>,
+    pub contact_name: Option<String>,
+    pub last_message_text: String,
+    /// Attachment placeholder for the last message; `last_message_text` is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    pub last_date: String,
+    pub last_is_from_me: bool,
+    pub unread_count: i64,
+    pub is_group: bool,
+}
+
+/// Unread messages in one conversation (`unread --by-conversation`).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnreadChat {
+    pub chat_identifier: String,
+    /// Contact name (1:1), group display name, else the chat identifier
+    pub name: String,
+    pub unread_count: i64,
+    pub oldest_unread_date: String,
+    pub is_group: bool,
+}
+
+/// `bundle` output; each section is present only when included.
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct Bundle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<BundleMeta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent: Option<Vec<BundleMessage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_messages: Option<Vec<BundleMessage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<Vec<BundleMessage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_messages: Option<Vec<BundleMessage>>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BundleMeta {
+    pub version: String,
+    pub timestamp: String,
+    /// Every section was read from one database snapshot
+    pub snapshot: bool,
+    /// Highest message ROWID in that snapshot (the data's horizon)
+    pub max_rowid: i64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BundleMessage {
+    pub rowid: i64,
+    pub guid: String,
+    pub text: String,
+    pub date: Option<String>,
+    pub is_from_me: bool,
+    pub phone: String,
+}
+
+impl BundleMessage {
+    /// Row layout: text, date, is_from_me, handle.id, ROWID, guid.
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(BundleMessage {
+            rowid: row.get(4)?,
+            guid: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            text: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+            date: queries::decode_cocoa(row.get(1)?).iso(),
+            is_from_me: row.get::<_, i32>(2)? != 0,
+            phone: row.get::<_, Option<String>>(3)?.unwrap_or_else(|| "unknown".to_string()),
+        })
+    }
+}
+
+/// A tapback (or tapback removal).
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Reaction {
+    pub reaction_emoji: String,
+    pub reaction_kind: String,
+    pub reaction_type: i64,
+    pub removed: bool,
+    pub associated_guid: Option<String>,
+    pub date: Option<String>,
+    pub is_from_me: bool,
+    pub reactor_handle: Option<String>,
+}
+
+/// An audio attachment with its enrichment.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VoiceMessage {
+    pub attachment_path: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub date: String,
+    pub is_from_me: bool,
+    pub sender_handle: Option<String>,
+    pub sender_name: Option<String>,
+    pub duration_secs: Option<f64>,
+    pub transcript: Option<String>,
+}
 
-    if let Some(blob) = attributed_body {
-        if let Ok(Some(extracted)) = blob_parser::extract_text_from_blob(&blob) {
-            return extracted;
+impl From<attachments::Attachment> for VoiceMessage {
+    fn from(a: attachments::Attachment) -> Self {
+        let audio = a.audio.unwrap_or(attachments::AudioInfo {
+            duration_secs: None,
+            transcript: None,
+            sender_name: None,
+        });
+        VoiceMessage {
+            attachment_path: a.filename,
+            size_bytes: a.total_bytes,
+            date: a.date,
+            is_from_me: a.is_from_me,
+            sender_handle: a.sender_handle,
+            sender_name: audio.sender_name,
+            duration_secs: audio.duration_secs,
+            transcript: audio.transcript,
         }
     }
+}
 
-    "[message content not available]".to_string()
+/// `thread` output: the reply tree around the requested message.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Thread {
+    pub root_guid: String,
+    pub requested_guid: String,
+    pub thread_size: usize,
+    pub messages: Vec<ThreadEntry>,
+}
+
+/// A thread message plus the sender's contact name.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ThreadEntry {
+    #[serde(flatten)]
+    pub message: helpers::ThreadMessage,
+    pub sender_name: Option<String>,
 }
 
 /// Get recent conversations across all contacts.
-pub fn recent(limit: u32, output: &OutputControls) -> Result<()> {
+///
+/// With `per_conversation`, returns the latest message per chat instead of
+/// the latest messages globally (so one chatty group can't fill the list).
+#[allow(clippy::too_many_arguments)]
+pub fn recent(
+    limit: u32,
+    per_conversation: bool,
+    relationship: Option<&str>,
+    with_entities: bool,
+    include_muted: bool,
+    sender: SenderMode,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
     let conn = connection::open_db().context("Failed to open Messages database")?;
 
-    let mut stmt = conn
-        .prepare(
-            r#"
-            SELECT
-                message.text,
-                message.attributedBody,
-                message.date,
-                message.is_from_me,
-                handle.id,
-                message.cache_roomnames
-            FROM message
-            LEFT JOIN handle ON message.handle_id = handle.ROWID
-            ORDER BY message.date DESC
-            LIMIT ?1
-            "#,
-        )
-        .context("Failed to prepare query")?;
+    if per_conversation {
+        let mutes = MuteFilter::load(&conn, include_muted)?;
+        let rows = api::conversations(&conn, contacts, limit, relationship, mutes.as_ref())?;
+        let muted_count = api::muted_conversation_count(&conn, contacts, limit, relationship, mutes.as_ref())?;
+        print_conversations(&rows, muted_count, output)?;
+        return Ok(());
+    }
 
-    let rows = stmt
-        .query_map([limit], |row| {
-            Ok((
-                row.get::<_, Option<String>>(0)?,   // text
-                row.get::<_, Option<Vec<u8>>>(1)?,  // attributedBody
-                row.get::<_, i64>(2)?,              // date
-                row.get::<_, i32>(3)?,              // is_from_me
-                row.get::<_, Option<String>>(4)?,   // handle.id
-                row.get::<_, Option<String>>(5)?,   // cache_roomnames
-            ))
-        })
-        .context("Failed to execute query")?;
-
-    let mut messages: Vec<Message> = Vec::new();
-
-    for row_result in rows {
-        let (text, attributed_body, date_cocoa, is_from_me, handle_id, cache_roomnames) =
-            row_result.context("Failed to read row")?;
-
-        // Extract message text
-        let message_text = if let Some(t) = text {
-            if !t.is_empty() {
-                t
-            } else if let Some(blob) = attributed_body {
-                blob_parser::extract_text_from_blob(&blob)
-                    .ok()
-                    .flatten()
-                    .unwrap_or_else(|| "[message content not available]".to_string())
+    let opts = api::RecentOptions {
+        limit,
+        relationship: relationship.map(str::to_string),
+        with_entities,
+        sender,
+        ..Default::default()
+    };
+    let messages = api::recent(&conn, contacts, &opts)?;
+
+    output.show(&Titled::new(
+        format!("Recent Conversations ({} messages):", messages.len()),
+        "No recent conversations found.",
+        &messages,
+    ))?;
+
+    Ok(())
+}
+
+/// Latest message per conversation, with contact names for direct chats.
+pub fn conversation_rows(
+    conn: &rusqlite::Connection,
+    limit: u32,
+    filter: Option<&helpers::HandleFilter>,
+    mutes: Option<&MuteFilter>,
+    contacts: &ContactsManager,
+) -> Result<Vec<ConversationRow>> {
+    let conversations = helpers::query_recent_conversations(conn, limit, filter, mutes)
+        .context("Failed to query recent conversations")?;
+
+    Ok(conversations
+        .into_iter()
+        .map(|c| {
+            let contact_name = if c.is_group {
+                None
             } else {
-                "[message content not available]".to_string()
+                contacts.find_by_phone(&c.chat_identifier).map(|ct| ct.name.clone())
+            };
+            let (last_message_text, placeholder) = message_body::MessageBody {
+                text: c.last_message_text,
+                placeholder: c.placeholder,
             }
-        } else if let Some(blob) = attributed_body {
-            blob_parser::extract_text_from_blob(&blob)
-                .ok()
-                .flatten()
-                .unwrap_or_else(|| "[message content not available]".to_string())
-        } else {
-            "[message content not available]".to_string()
-        };
-
-        let is_group = is_group_chat_identifier(cache_roomnames.as_deref());
+            .into_parts();
+            ConversationRow {
+                contact_name,
+                last_message_text,
+                placeholder,
+                chat_identifier: c.chat_identifier,
+                display_name: c.display_name,
+                last_date: c.last_date,
+                last_is_from_me: c.last_is_from_me,
+                unread_count: c.unread_count,
+                is_group: c.is_group,
+            }
+        })
+        .collect())
+}
 
-        messages.push(Message {
-            text: message_text,
-            date: cocoa_to_iso(date_cocoa),
-            is_from_me: is_from_me != 0,
-            phone: handle_id.unwrap_or_else(|| "unknown".to_string()),
-            is_group_chat: is_group,
-            group_id: if is_group { cache_roomnames } else { None },
-        });
-    }
+/// Unread counts per conversation with names resolved, muted chats left out.
+pub fn unread_chat_rows(
+    conn: &rusqlite::Connection,
+    mutes: Option<&MuteFilter>,
+    contacts: &ContactsManager,
+) -> Result<Vec<UnreadChat>> {
+    let rows = helpers::query_unread_by_chat(conn, mutes).context("Failed to query unread conversations")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let is_group = helpers::is_group_chat_identifier(Some(&row.chat_identifier));
+            let name = if is_group {
+                row.display_name.unwrap_or_else(|| row.chat_identifier.clone())
+            } else {
+                contacts.display_name(&row.chat_identifier, row.display_name.as_deref())
+            };
+            UnreadChat {
+                chat_identifier: row.chat_identifier,
+                name,
+                unread_count: row.unread_count,
+                oldest_unread_date: helpers::cocoa_to_iso(row.oldest_unread_cocoa),
+                is_group,
+            }
+        })
+        .collect())
+}
 
-    if output.json {
-        output.print(&messages);
+/// Render the latest message per conversation.
+fn print_conversations(rows: &[ConversationRow], muted_count: usize, output: &OutputControls) -> Result<()> {
+    if output.json || output.csv {
+        output.print_muted(&rows, muted_count, "conversations")?;
     } else {
-        if messages.is_empty() {
+        print_muted_note(muted_count, "conversations");
+        if rows.is_empty() {
             println!("No recent conversations found.");
             return Ok(());
         }
 
-        println!("Recent Conversations ({} messages):", messages.len());
+        println!("Recent Conversations ({}):", rows.len());
         println!("{}", "-".repeat(60));
 
-        for msg in &messages {
-            let sender = if msg.is_from_me { "Me" } else { &msg.phone };
-            let text_preview: String = msg.text.chars().take(80).collect();
-            let date = msg.date.as_deref().unwrap_or("");
-            println!("[{}] {}: {}", date, sender, text_preview);
+        for row in rows {
+            let name = row
+                .contact_name
+                .as_deref()
+                .or(row.display_name.as_deref())
+                .unwrap_or(&row.chat_identifier);
+            let unread = if row.unread_count > 0 {
+                format!(" ({} unread)", row.unread_count)
+            } else {
+                String::new()
+            };
+            let prefix = if row.last_is_from_me { "Me: " } else { "" };
+            let text_preview = output::preview(display_text(&row.last_message_text, row.placeholder.as_deref()), 80);
+            println!("[{}] {}{}", row.last_date, name, unread);
+            println!("  {}{}", prefix, text_preview);
         }
     }
-
     Ok(())
 }
 
-/// Find messages with a contact (keyword search).
+/// Find messages with a contact (keyword search), across their SMS and
+/// iMessage conversations unless `service` picks one.
+#[allow(clippy::too_many_arguments)]
 pub fn find(
     contact: &str,
     query: Option<&str>,
     limit: u32,
+    with_entities: bool,
+    service: api::ServiceFilter,
+    include_system: bool,
+    with_reactions: bool,
     output: &OutputControls,
+    contacts: &ContactsManager,
 ) -> Result<()> {
-    use crate::contacts::manager::ContactsManager;
-
     let conn = connection::open_db().context("Failed to open Messages database")?;
-
-    // Load contacts for name resolution
-    let contacts = ContactsManager::load_default().unwrap_or_else(|_| ContactsManager::empty());
-
-    // Resolve contact to phone number
-    let phone = match contacts.resolve_to_phone(contact) {
-        Some(p) => p,
-        None => {
-            // If no contact match, try using input directly as phone pattern
-            contact.to_string()
-        }
-    };
-
-    // Build query - search messages with this contact, optionally filtered by text
-    let sql = match query {
-        Some(_) => r#"
-            SELECT
-                message.text,
-                message.attributedBody,
-                message.date,
-                message.is_from_me,
-                handle.id,
-                message.cache_roomnames
-            FROM message
-            JOIN handle ON message.handle_id = handle.ROWID
-            WHERE handle.id LIKE ?1
-              AND (message.text LIKE ?2 OR message.attributedBody IS NOT NULL)
-            ORDER BY message.date DESC
-            LIMIT ?3
-        "#,
-        None => r#"
-            SELECT
-                message.text,
-                message.attributedBody,
-                message.date,
-                message.is_from_me,
-                handle.id,
-                message.cache_roomnames
-            FROM message
-            JOIN handle ON message.handle_id = handle.ROWID
-            WHERE handle.id LIKE ?1
-            ORDER BY message.date DESC
-            LIMIT ?3
-        "#,
-    };
-
-    let mut stmt = conn.prepare(sql).context("Failed to prepare query")?;
-
-    // Build parameters
-    let phone_pattern = format!("%{}%", phone.chars().filter(|c| c.is_ascii_digit()).collect::<String>());
-    let query_pattern = query.map(|q| format!("%{}%", q)).unwrap_or_default();
-
-    let rows: Vec<_> = if query.is_some() {
-        stmt.query_map(
-            rusqlite::params![phone_pattern, query_pattern, limit],
-            |row| {
-                Ok((
-                    row.get::<_, Option<String>>(0)?,
-                    row.get::<_, Option<Vec<u8>>>(1)?,
-                    row.get::<_, i64>(2)?,
-                    row.get::<_, i32>(3)?,
-                    row.get::<_, Option<String>>(4)?,
-                    row.get::<_, Option<String>>(5)?,
-                ))
-            },
-        )?
-        .collect()
-    } else {
-        stmt.query_map(
-            rusqlite::params![phone_pattern, "", limit],
-            |row| {
-                Ok((
-                    row.get::<_, Option<String>>(0)?,
-                    row.get::<_, Option<Vec<u8>>>(1)?,
-                    row.get::<_, i64>(2)?,
-                    row.get::<_, i32>(3)?,
-                    row.get::<_, Option<String>>(4)?,
-                    row.get::<_, Option<String>>(5)?,
-                ))
-            },
-        )?
-        .collect()
+    if let Some(phone) = contacts.resolve_to_phone(contact) {
+        output::applied("contact_resolved", phone);
+    }
+    let opts = api::FindOptions {
+        query: query.map(str::to_string),
+        limit,
+        with_entities,
+        service,
+        include_system,
+        with_reactions,
+        ..api::FindOptions::contact(contact)
     };
-
-    let mut messages: Vec<Message> = Vec::new();
-
-    for row_result in rows {
-        let (text, attributed_body, date_cocoa, is_from_me, handle_id, cache_roomnames) =
-            row_result.context("Failed to read row")?;
-
-        let message_text = get_message_text(text, attributed_body);
-
-        // Filter by query if provided
-        if let Some(q) = query {
-            if !message_text.to_lowercase().contains(&q.to_lowercase()) {
-                continue;
-            }
+    let messages = api::find(&conn, contacts, &opts)?;
+
+    // Nothing with the contact a name resolved to: say who that was, in case
+    // it was the wrong one
+    if messages.is_empty() && !contact.eq_ignore_ascii_case(helpers::SELF_CONTACT) {
+        if let Some(resolution) = contacts.resolve_with_diagnostics(contact) {
+            output.show(&EmptyResolution {
+                messages: Vec::new(),
+                resolution: ContactResolution { resolution, message_count: 0 },
+            })?;
+            return Ok(());
         }
+    }
 
-        let is_group = is_group_chat_identifier(cache_roomnames.as_deref());
+    let empty = format!(
+        "No messages found for '{}'{}",
+        contact,
+        query.map(|q| format!(" matching '{}'", q)).unwrap_or_default()
+    );
+    output.show(&Titled::new(
+        format!("Messages with '{}' ({} found):", contact, messages.len()),
+        &empty,
+        &messages,
+    ))?;
 
-        messages.push(Message {
-            text: message_text,
-            date: cocoa_to_iso(date_cocoa),
-            is_from_me: is_from_me != 0,
-            phone: handle_id.unwrap_or_else(|| "unknown".to_string()),
-            is_group_chat: is_group,
-            group_id: if is_group { cache_roomnames } else { None },
-        });
-    }
+    Ok(())
+}
 
-    if output.json {
-        output.print(&messages);
-    } else {
-        if messages.is_empty() {
-            println!("No messages found for '{}'{}", contact,
-                query.map(|q| format!(" matching '{}'", q)).unwrap_or_default());
-            return Ok(());
-        }
+/// A name's resolution and how many messages the chosen contact has.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ContactResolution {
+    #[serde(flatten)]
+    pub resolution: Resolution,
+    pub message_count: usize,
+}
 
-        println!("Messages with '{}' ({} found):", contact, messages.len());
-        println!("{}", "-".repeat(60));
+/// find/messages output when the resolved contact has no messages.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EmptyResolution {
+    pub messages: Vec<Message>,
+    pub resolution: ContactResolution,
+}
 
-        for msg in &messages {
-            let sender = if msg.is_from_me { "Me" } else { &msg.phone };
-            let text_preview: String = msg.text.chars().take(80).collect();
-            let date = msg.date.as_deref().unwrap_or("");
-            println!("[{}] {}: {}", date, sender, text_preview);
+impl TextRenderer for EmptyResolution {
+    fn render(&self, style: &TextStyle) -> String {
+        let r = &self.resolution.resolution;
+        let mut lines = vec![
+            format!("No messages found for '{}'", r.query),
+            style.paint(
+                Tone::Dim,
+                &format!(
+                    "Resolved to {} ({}): {} match, score {:.2}, {} messages",
+                    r.contact_name, r.phone, r.method, r.score, self.resolution.message_count
+                ),
+            ),
+        ];
+        if !r.candidates.is_empty() {
+            let others: Vec<String> =
+                r.candidates.iter().map(|c| format!("{} ({}, {:.2})", c.name, c.phone, c.score)).collect();
+            lines.push(style.paint(Tone::Dim, &format!("Other matches: {}", others.join(", "))));
         }
+        lines.join("\n")
     }
-
-    Ok(())
 }
 
 /// Get messages with a specific contact.
-pub fn messages(contact: &str, limit: u32, output: &OutputControls) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub fn messages(
+    contact: &str,
+    limit: u32,
+    with_entities: bool,
+    service: api::ServiceFilter,
+    include_system: bool,
+    with_reactions: bool,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
     // Delegate to find with no query
-    find(contact, None, limit, output)
+    find(contact, None, limit, with_entities, service, include_system, with_reactions, output, contacts)
 }
 
 /// Get unread messages.
-pub fn unread(limit: u32, output: &OutputControls) -> Result<()> {
+pub fn unread(
+    limit: u32,
+    relationship: Option<&str>,
+    with_entities: bool,
+    include_muted: bool,
+    sender: SenderMode,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
     let conn = connection::open_db().context("Failed to open Messages database")?;
+    let opts = api::UnreadOptions {
+        limit,
+        relationship: relationship.map(str::to_string),
+        with_entities,
+        mutes: MuteFilter::load(&conn, include_muted)?,
+        sender,
+    };
+    let messages = api::unread(&conn, contacts, &opts)?;
+    let muted_count = api::muted_unread_count(&conn, contacts, &opts)?;
 
-    let mut stmt = conn
-        .prepare(
-            r#"
-            SELECT
-                message.text,
-                message.attributedBody,
-                message.date,
-                message.is_from_me,
-                handle.id,
-                message.cache_roomnames
-            FROM message
-            LEFT JOIN handle ON message.handle_id = handle.ROWID
-            WHERE message.is_from_me = 0
-              AND message.date_read = 0
-              AND message.is_read = 0
-            ORDER BY message.date DESC
-            LIMIT ?1
-            "#,
-        )
-        .context("Failed to prepare query")?;
-
-    let rows = stmt
-        .query_map([limit], |row| {
-            Ok((
-                row.get::<_, Option<String>>(0)?,
-                row.get::<_, Option<Vec<u8>>>(1)?,
-                row.get::<_, i64>(2)?,
-                row.get::<_, i32>(3)?,
-                row.get::<_, Option<String>>(4)?,
-                row.get::<_, Option<String>>(5)?,
-            ))
-        })
-        .context("Failed to execute query")?;
-
-    let mut messages: Vec<Message> = Vec::new();
-
-    for row_result in rows {
-        let (text, attributed_body, date_cocoa, is_from_me, handle_id, cache_roomnames) =
-            row_result.context("Failed to read row")?;
+    if output.json {
+        output.print_muted(&messages, muted_count, "unread messages")?;
+        return Ok(());
+    }
+    print_muted_note(muted_count, "unread messages");
+    output.show(&Titled::new(
+        format!("Unread Messages ({}):", messages.len()),
+        "No unread messages.",
+        &messages,
+    ))?;
 
-        let message_text = text.filter(|t| !t.is_empty()).unwrap_or_else(|| {
-            attributed_body
-                .as_ref()
-                .and_then(|blob| blob_parser::extract_text_from_blob(blob).ok().flatten())
-                .unwrap_or_else(|| "[message content not available]".to_string())
-        });
+    Ok(())
+}
 
-        let is_group = is_group_chat_identifier(cache_roomnames.as_deref());
+/// Unread count and oldest unread message per conversation (triage view).
+pub fn unread_by_conversation(include_muted: bool, output: &OutputControls, contacts: &ContactsManager) -> Result<()> {
+    let conn = connection::open_db().context("Failed to open Messages database")?;
+    let mutes = MuteFilter::load(&conn, include_muted)?;
+    let chats = api::unread_by_chat(&conn, contacts, mutes.as_ref())?;
+    let muted_count = api::muted_unread_chat_count(&conn, mutes.as_ref())?;
 
-        messages.push(Message {
-            text: message_text,
-            date: cocoa_to_iso(date_cocoa),
-            is_from_me: is_from_me != 0,
-            phone: handle_id.unwrap_or_else(|| "unknown".to_string()),
-            is_group_chat: is_group,
-            group_id: if is_group { cache_roomnames } else { None },
-        });
+    if output.json || output.csv {
+        output.print_muted(&chats, muted_count, "conversations")?;
+        return Ok(());
+    }
+    print_muted_note(muted_count, "conversations");
+    if chats.is_empty() {
+        println!("No unread messages.");
+        return Ok(());
+    }
+    println!("Unread by conversation ({}):", chats.len());
+    println!("{}", "-".repeat(60));
+    for chat in &chats {
+        let kind = if chat.is_group { " [group]" } else { "" };
+        println!(
+            "{}{}: {} unread since {}",
+            chat.name, kind, chat.unread_count, chat.oldest_unread_date
+        );
     }
+    Ok(())
+}
 
+/// Print just the unread count (menu-bar polling): one COUNT query, no contacts.
+pub fn unread_count(include_muted: bool, output: &OutputControls) -> Result<()> {
+    let conn = connection::open_db().context("Failed to open Messages database")?;
+    let mutes = MuteFilter::load(&conn, include_muted)?;
+    let count = api::unread_count(&conn, mutes.as_ref())?;
     if output.json {
-        output.print(&messages);
+        output.print(&serde_json::json!({ "unread_count": count }))?;
     } else {
-        if messages.is_empty() {
-            println!("No unread messages.");
-            return Ok(());
-        }
-
-        println!("Unread Messages ({}):", messages.len());
-        println!("{}", "-".repeat(60));
+        println!("{}", count);
+    }
+    Ok(())
+}
 
-        for msg in &messages {
-            let text_preview: String = msg.text.chars().take(150).collect();
-            println!("{}: {}", msg.phone, text_preview);
-        }
+/// Text-mode note that muted conversations were left out.
+pub(crate) fn print_muted_note(muted_count: usize, what: &str) {
+    if muted_count > 0 {
+        println!("{}", output::muted_note(muted_count, what));
     }
+}
 
-    Ok(())
+/// Search hits as output rows.
+pub fn search_matches(hits: Vec<helpers::SearchHit>) -> Vec<SearchMatch> {
+    hits.into_iter()
+        .map(|hit| {
+            let is_group = helpers::is_group_chat_identifier(hit.cache_roomnames.as_deref());
+            let phone = hit.handle.unwrap_or_else(|| "unknown".to_string());
+            let date = queries::decode_cocoa(hit.date_cocoa);
+            SearchMatch {
+                message: Message {
+                    rowid: hit.rowid,
+                    guid: hit.guid,
+                    text: hit.text,
+                    placeholder: None,
+                    item_kind: hit.item_kind,
+                    date: date.iso(),
+                    date_suspect: date.suspect,
+                    is_from_me: hit.is_from_me,
+                    display_name: phone.clone(),
+                    phone,
+                    contact_name: None,
+                    is_known_sender: false,
+                    is_group_chat: is_group,
+                    group_id: if is_group { hit.cache_roomnames } else { None },
+                    service: None,
+                    entities: None,
+                    reactions: Vec::new(),
+                },
+                matched_terms: hit.matched_terms,
+            }
+        })
+        .collect()
 }
 
 /// Fast text search across all messages.
+#[allow(clippy::too_many_arguments)]
 pub fn text_search(
-    query: &str,
+    query: Option<&str>,
+    any: &[String],
+    all: &[String],
     _contact: Option<&str>,
+    group: Option<&str>,
     limit: u32,
+    include_system: bool,
     _days: Option<u32>,
     _since: Option<&str>,
     output: &OutputControls,
+    contacts: &ContactsManager,
 ) -> Result<()> {
+    // Validate the terms before touching the database
+    let (terms, mode) = helpers::resolve_search_terms(query, any, all)?;
     let conn = connection::open_db().context("Failed to open Messages database")?;
+    let chat = group
+        .map(|g| helpers::resolve_group(&conn, g)?.ok_or_else(|| anyhow::anyhow!("Group '{}' not found", g)))
+        .transpose()?;
+
+    let opts = api::SearchOptions {
+        query: query.map(str::to_string),
+        any: any.to_vec(),
+        all: all.to_vec(),
+        group: chat.as_ref().map(|c| c.chat_identifier.clone()),
+        limit,
+        include_system,
+    };
+    let matches = api::text_search(&conn, contacts, &opts)?;
 
-    let mut stmt = conn
-        .prepare(
-            r#"
-            SELECT
-                message.text,
-                message.attributedBody,
-                message.date,
-                message.is_from_me,
-                handle.id,
-                message.cache_roomnames
-            FROM message
-            LEFT JOIN handle ON message.handle_id = handle.ROWID
-            WHERE message.text LIKE '%' || ?1 || '%'
-            ORDER BY message.date DESC
-            LIMIT ?2
-            "#,
-        )
-        .context("Failed to prepare query")?;
-
-    let rows = stmt
-        .query_map(rusqlite::params![query, limit], |row| {
-            Ok((
-                row.get::<_, Option<String>>(0)?,
-                row.get::<_, Option<Vec<u8>>>(1)?,
-                row.get::<_, i64>(2)?,
-                row.get::<_, i32>(3)?,
-                row.get::<_, Option<String>>(4)?,
-                row.get::<_, Option<String>>(5)?,
-            ))
-        })
-        .context("Failed to execute query")?;
-
-    let mut messages: Vec<Message> = Vec::new();
-
-    for row_result in rows {
-        let (text, attributed_body, date_cocoa, is_from_me, handle_id, cache_roomnames) =
-            row_result.context("Failed to read row")?;
-
-        let message_text = text.filter(|t| !t.is_empty()).unwrap_or_else(|| {
-            attributed_body
-                .as_ref()
-                .and_then(|blob| blob_parser::extract_text_from_blob(blob).ok().flatten())
-                .unwrap_or_else(|| "[message content not available]".to_string())
-        });
-
-        let is_group = is_group_chat_identifier(cache_roomnames.as_deref());
-
-        messages.push(Message {
-            text: message_text,
-            date: cocoa_to_iso(date_cocoa),
-            is_from_me: is_from_me != 0,
-            phone: handle_id.unwrap_or_else(|| "unknown".to_string()),
-            is_group_chat: is_group,
-            group_id: if is_group { cache_roomnames } else { None },
-        });
-    }
-
-    if output.json {
-        output.print(&messages);
-    } else {
-        if messages.is_empty() {
-            println!("No matches found for: \"{}\"", query);
-            return Ok(());
-        }
-
-        println!("Matches ({}) for: \"{}\"", messages.len(), query);
-        println!("{}", "-".repeat(60));
-
-        for msg in &messages {
-            let sender = if msg.is_from_me { "Me" } else { &msg.phone };
-            let text_preview: String = msg.text.chars().take(100).collect();
-            println!("[{}] {}: {}", msg.date.as_deref().unwrap_or(""), sender, text_preview);
+    let mut label = format!(
+        "\"{}\"",
+        match mode {
+            queries::TermMatch::Any => terms.join(" OR "),
+            queries::TermMatch::All => terms.join(" AND "),
         }
+    );
+    if let Some(chat) = &chat {
+        label = format!("{} in {}", label, chat.display_name.as_deref().unwrap_or(&chat.chat_identifier));
     }
 
+    let empty = format!("No matches found for: {}", label);
+    output.show(&Titled::new(
+        format!("Matches ({}) for: {}", matches.len(), label),
+        &empty,
+        &matches,
+    ))?;
+
     Ok(())
 }
 
-/// Run a canonical LLM workload bundle.
+/// Sections `bundle --include` knows.
+const BUNDLE_SECTIONS: &[&str] = &["meta", "unread_count", "recent", "unread_messages", "search", "contact_messages"];
+
+/// Run a canonical LLM workload bundle, trimmed to `budget` (max tokens,
+/// section priority) when given.
 #[allow(clippy::too_many_arguments)]
 pub fn bundle(
     contact: Option<&str>,
@@ -497,255 +801,144 @@ pub fn bundle(
     _messages_limit: u32,
     _search_scoped_to_contact: bool,
     include: Option<&str>,
+    budget: Option<(usize, Vec<String>)>,
     output: &OutputControls,
 ) -> Result<()> {
     // Parse include sections
     let sections: Vec<&str> = include
         .map(|s| s.split(',').map(|p| p.trim()).collect())
         .unwrap_or_else(|| vec!["meta", "unread_count", "unread_messages", "recent"]);
-
-    let mut bundle_result = serde_json::Map::new();
-
-    // Meta section
-    if sections.contains(&"meta") {
-        bundle_result.insert(
-            "meta".to_string(),
-            json!({
-                "version": "1.0",
-                "timestamp": Utc::now().to_rfc3339(),
-            }),
-        );
-    }
-
-    // Unread count
-    if sections.contains(&"unread_count") {
-        let conn = connection::open_db()?;
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM message WHERE is_from_me = 0 AND date_read = 0 AND is_read = 0",
-            [],
-            |row| row.get(0),
-        )?;
-        bundle_result.insert("unread_count".to_string(), json!(count));
-    }
-
-    // Recent messages
-    if sections.contains(&"recent") {
-        let conn = connection::open_db()?;
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT message.text, message.date, message.is_from_me, handle.id, message.cache_roomnames
-            FROM message
-            LEFT JOIN handle ON message.handle_id = handle.ROWID
-            ORDER BY message.date DESC
-            LIMIT ?1
-            "#,
-        )?;
-
-        let rows: Vec<serde_json::Value> = stmt
-            .query_map([recent_limit], |row| {
-                Ok(json!({
-                    "text": row.get::<_, Option<String>>(0)?.unwrap_or_default(),
-                    "date": cocoa_to_iso(row.get::<_, i64>(1)?),
-                    "is_from_me": row.get::<_, i32>(2)? != 0,
-                    "phone": row.get::<_, Option<String>>(3)?.unwrap_or_else(|| "unknown".to_string()),
-                }))
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        bundle_result.insert("recent".to_string(), json!(rows));
+    for section in sections.iter().filter(|s| !BUNDLE_SECTIONS.contains(s)) {
+        output::warn(format!(
+            "unknown bundle section '{}' (expected {})",
+            section,
+            BUNDLE_SECTIONS.join(", ")
+        ));
     }
 
-    // Unread messages
-    if sections.contains(&"unread_messages") {
-        let conn = connection::open_db()?;
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT message.text, message.date, message.is_from_me, handle.id
-            FROM message
-            LEFT JOIN handle ON message.handle_id = handle.ROWID
-            WHERE message.is_from_me = 0 AND message.date_read = 0 AND message.is_read = 0
-            ORDER BY message.date DESC
-            LIMIT ?1
-            "#,
-        )?;
-
-        let rows: Vec<serde_json::Value> = stmt
-            .query_map([unread_limit], |row| {
-                Ok(json!({
-                    "text": row.get::<_, Option<String>>(0)?.unwrap_or_default(),
-                    "date": cocoa_to_iso(row.get::<_, i64>(1)?),
-                    "is_from_me": row.get::<_, i32>(2)? != 0,
-                    "phone": row.get::<_, Option<String>>(3)?.unwrap_or_else(|| "unknown".to_string()),
-                }))
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
+    // One snapshot for every section, so counts and lists agree
+    let conn = connection::open_db()?;
+    let bundle_result = connection::read_snapshot(&conn, |conn| {
+        let mut bundle_result = Bundle::default();
+
+        // Meta section
+        if sections.contains(&"meta") {
+            bundle_result.meta = Some(BundleMeta {
+                version: "1.0".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+                snapshot: true,
+                max_rowid: helpers::query_max_message_rowid(conn)?,
+            });
+        }
 
-        bundle_result.insert("unread_messages".to_string(), json!(rows));
-    }
+        // Unread count
+        if sections.contains(&"unread_count") {
+            let mutes = MuteFilter::load(conn, false)?;
+            bundle_result.unread_count = Some(api::unread_count(conn, mutes.as_ref())? as i64);
+        }
 
-    // Search section
-    if sections.contains(&"search") {
-        if let Some(q) = query {
-            let conn = connection::open_db()?;
+        // Recent messages
+        if sections.contains(&"recent") {
             let mut stmt = conn.prepare(
                 r#"
-                SELECT message.text, message.date, message.is_from_me, handle.id
+                SELECT message.text, message.date, message.is_from_me, handle.id, message.ROWID, message.guid
                 FROM message
                 LEFT JOIN handle ON message.handle_id = handle.ROWID
-                WHERE message.text LIKE '%' || ?1 || '%'
                 ORDER BY message.date DESC
-                LIMIT 20
+                LIMIT ?1
                 "#,
             )?;
 
-            let rows: Vec<serde_json::Value> = stmt
-                .query_map([q], |row| {
-                    Ok(json!({
-                        "text": row.get::<_, Option<String>>(0)?.unwrap_or_default(),
-                        "date": cocoa_to_iso(row.get::<_, i64>(1)?),
-                        "is_from_me": row.get::<_, i32>(2)? != 0,
-                        "phone": row.get::<_, Option<String>>(3)?.unwrap_or_else(|| "unknown".to_string()),
-                    }))
-                })?
-                .filter_map(|r| r.ok())
+            let rows: Vec<BundleMessage> = stmt
+                .query_map([recent_limit], BundleMessage::from_row)?
+                .filter_map(helpers::ok_row)
                 .collect();
 
-            bundle_result.insert("search".to_string(), json!(rows));
+            bundle_result.recent = Some(rows);
         }
-    }
 
-    // Contact-specific messages
-    if sections.contains(&"contact_messages") {
-        if let Some(_c) = contact {
-            // [*INCOMPLETE*] Need contacts manager to resolve name → phone
-            bundle_result.insert("contact_messages".to_string(), json!([]));
-        }
-    }
+        // Unread messages
+        if sections.contains(&"unread_messages") {
+            let mutes = MuteFilter::load(conn, false)?;
+            let sql = format!(
+                r#"
+                SELECT message.text, message.date, message.is_from_me, handle.id, message.ROWID, message.guid
+                FROM message
+                LEFT JOIN handle ON message.handle_id = handle.ROWID
+                WHERE {}
+                ORDER BY message.date DESC
+                LIMIT ?1
+                "#,
+                helpers::unread_conditions(conn, "message", mutes.as_ref()).join(" AND ")
+            );
+            let mut stmt = conn.prepare(&sql)?;
 
-    if output.json {
-        output.print(&bundle_result);
-    } else {
-        println!("{}", serde_json::to_string_pretty(&bundle_result)?);
-    }
+            let rows: Vec<BundleMessage> = stmt
+                .query_map([unread_limit], BundleMessage::from_row)?
+                .filter_map(helpers::ok_row)
+                .collect();
 
-    Ok(())
-}
+            bundle_result.unread_messages = Some(rows);
+        }
 
-/// Get attachments (photos, videos, files).
-pub fn attachments(
-    _contact: Option<&str>,
-    _mime_type: Option<&str>,
-    limit: u32,
-    json_out: bool,
-) -> Result<()> {
-    let conn = connection::open_db()?;
+        // Search section
+        if sections.contains(&"search") {
+            if let Some(q) = query {
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT message.text, message.date, message.is_from_me, handle.id, message.ROWID, message.guid
+                    FROM message
+                    LEFT JOIN handle ON message.handle_id = handle.ROWID
+                    WHERE message.text LIKE ?1 ESCAPE '\'
+                    ORDER BY message.date DESC
+                    LIMIT 20
+                    "#,
+                )?;
+
+                let rows: Vec<BundleMessage> = stmt
+                    .query_map([queries::like_contains(q)], BundleMessage::from_row)?
+                    .filter_map(helpers::ok_row)
+                    .collect();
+
+                bundle_result.search = Some(rows);
+            }
+        }
 
-    let mut stmt = conn.prepare(
-        r#"
-        SELECT
-            attachment.filename,
-            attachment.mime_type,
-            attachment.total_bytes,
-            attachment.transfer_name,
-            message.date
-        FROM attachment
-        JOIN message_attachment_join ON attachment.ROWID = message_attachment_join.attachment_id
-        JOIN message ON message_attachment_join.message_id = message.ROWID
-        ORDER BY message.date DESC
-        LIMIT ?1
-        "#,
-    )?;
+        // Contact-specific messages
+        if sections.contains(&"contact_messages") {
+            if let Some(_c) = contact {
+                // [*INCOMPLETE*] Need contacts manager to resolve name → phone
+                bundle_result.contact_messages = Some(Vec::new());
+            }
+        }
 
-    let attachments: Vec<serde_json::Value> = stmt
-        .query_map([limit], |row| {
-            Ok(json!({
-                "filename": row.get::<_, Option<String>>(0)?,
-                "mime_type": row.get::<_, Option<String>>(1)?,
-                "total_bytes": row.get::<_, Option<i64>>(2)?,
-                "transfer_name": row.get::<_, Option<String>>(3)?,
-                "date": cocoa_to_iso(row.get::<_, i64>(4)?),
-            }))
-        })?
-        .filter_map(|r| r.ok())
-        .collect();
+        Ok(bundle_result)
+    })?;
 
-    if json_out {
-        println!("{}", serde_json::to_string(&attachments)?);
-    } else {
-        if attachments.is_empty() {
-            println!("No attachments found.");
-            return Ok(());
-        }
+    let mut bundle_value = serde_json::to_value(&bundle_result)?;
+    if let Some((max_tokens, priority)) = budget {
+        budget::trim_to_budget(&mut bundle_value, max_tokens, &priority, &budget::CharsPerToken::default());
+    }
 
-        println!("Attachments ({}):", attachments.len());
-        println!("{}", "-".repeat(60));
-        for a in &attachments {
-            let name = a["filename"].as_str().or(a["transfer_name"].as_str()).unwrap_or("Unknown");
-            let mime = a["mime_type"].as_str().unwrap_or("unknown");
-            let size = a["total_bytes"].as_i64().unwrap_or(0);
-            let size_str = if size > 0 {
-                format!("{:.1}KB", size as f64 / 1024.0)
-            } else {
-                "N/A".to_string()
-            };
-            println!("{} ({}, {})", name, mime, size_str);
-        }
+    if output.json {
+        output.print(&bundle_value)?;
+    } else {
+        output.print_json(&bundle_value, true)?;
     }
 
     Ok(())
 }
 
 /// Get reactions (tapbacks) from messages.
-pub fn reactions(_contact: Option<&str>, limit: u32, json_out: bool) -> Result<()> {
+///
+/// Includes removal events (3000-range) marked `removed: true` and iOS 17+
+/// custom emoji tapbacks (read from associated_message_emoji when present).
+pub fn reactions(_contact: Option<&str>, limit: u32, output: &OutputControls) -> Result<()> {
     let conn = connection::open_db()?;
+    let reactions = query_reaction_rows(&conn, limit)?;
 
-    // Reactions have associated_message_guid and associated_message_type > 1999
-    let mut stmt = conn.prepare(
-        r#"
-        SELECT
-            message.text,
-            message.associated_message_guid,
-            message.associated_message_type,
-            message.date,
-            message.is_from_me,
-            handle.id
-        FROM message
-        LEFT JOIN handle ON message.handle_id = handle.ROWID
-        WHERE message.associated_message_type >= 2000
-          AND message.associated_message_type < 3000
-        ORDER BY message.date DESC
-        LIMIT ?1
-        "#,
-    )?;
-
-    let reactions: Vec<serde_json::Value> = stmt
-        .query_map([limit], |row| {
-            let reaction_type = row.get::<_, i32>(2)?;
-            let emoji = match reaction_type {
-                2000 => "❤️",
-                2001 => "👍",
-                2002 => "👎",
-                2003 => "😂",
-                2004 => "‼️",
-                2005 => "❓",
-                _ => "?",
-            };
-            Ok(json!({
-                "reaction_emoji": emoji,
-                "reaction_type": reaction_type,
-                "associated_guid": row.get::<_, Option<String>>(1)?,
-                "date": cocoa_to_iso(row.get::<_, i64>(3)?),
-                "is_from_me": row.get::<_, i32>(4)? != 0,
-                "reactor_handle": row.get::<_, Option<String>>(5)?,
-            }))
-        })?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    if json_out {
-        println!("{}", serde_json::to_string(&reactions)?);
+    if output.json {
+        output.print_json(&reactions, false)?;
     } else {
         if reactions.is_empty() {
             println!("No reactions found.");
@@ -755,123 +948,117 @@ pub fn reactions(_contact: Option<&str>, limit: u32, json_out: bool) -> Result<(
         println!("Reactions ({}):", reactions.len());
         println!("{}", "-".repeat(60));
         for r in &reactions {
-            let emoji = r["reaction_emoji"].as_str().unwrap_or("?");
-            let reactor = if r["is_from_me"].as_bool().unwrap_or(false) {
+            let reactor = if r.is_from_me {
                 "Me"
             } else {
-                r["reactor_handle"].as_str().unwrap_or("Unknown")
+                r.reactor_handle.as_deref().unwrap_or("Unknown")
             };
-            println!("{} by {}", emoji, reactor);
+            if r.removed {
+                println!("{} removed by {}", r.reaction_emoji, reactor);
+            } else {
+                println!("{} by {}", r.reaction_emoji, reactor);
+            }
         }
     }
 
     Ok(())
 }
 
-/// Extract URLs shared in conversations.
-pub fn links(_contact: Option<&str>, _days: Option<u32>, _all_time: bool, limit: u32, json_out: bool) -> Result<()> {
-    let conn = connection::open_db()?;
+/// Newest tapbacks first, classified.
+pub fn query_reaction_rows(conn: &rusqlite::Connection, limit: u32) -> Result<Vec<Reaction>> {
 
-    // Simple URL extraction from message text using LIKE patterns
-    let mut stmt = conn.prepare(
+    // associated_message_emoji only exists on newer macOS schemas
+    let emoji_col = if helpers::has_column(conn, "message", "associated_message_emoji") {
+        "message.associated_message_emoji"
+    } else {
+        "NULL"
+    };
+
+    // Reactions have associated_message_guid and associated_message_type in 2000-3999
+    let sql = format!(
         r#"
         SELECT
             message.text,
+            message.associated_message_guid,
+            message.associated_message_type,
             message.date,
             message.is_from_me,
-            handle.id
+            handle.id,
+            {} AS reaction_emoji
         FROM message
         LEFT JOIN handle ON message.handle_id = handle.ROWID
-        WHERE message.text LIKE '%http%'
+        WHERE message.associated_message_type >= 2000
+          AND message.associated_message_type < 4000
         ORDER BY message.date DESC
         LIMIT ?1
         "#,
-    )?;
+        emoji_col
+    );
+    let mut stmt = conn.prepare(&sql)?;
 
-    let url_regex = regex::Regex::new(r#"https?://[^\s<>"]+"#).ok();
-
-    let mut links: Vec<serde_json::Value> = Vec::new();
-
-    let rows = stmt.query_map([limit], |row| {
-        Ok((
-            row.get::<_, Option<String>>(0)?,
-            row.get::<_, i64>(1)?,
-            row.get::<_, i32>(2)?,
-            row.get::<_, Option<String>>(3)?,
-        ))
-    })?;
+    let reactions = stmt
+        .query_map([limit], |row| {
+            let text = row.get::<_, Option<String>>(0)?;
+            let reaction_type = row.get::<_, i64>(2)?;
+            let emoji_value = row
+                .get::<_, Option<String>>(6)?
+                .or_else(|| text.as_deref().and_then(helpers::emoji_from_reaction_text));
+            let kind = helpers::reaction_kind(reaction_type, emoji_value.as_deref());
+            Ok(Reaction {
+                reaction_emoji: kind.emoji,
+                reaction_kind: kind.kind.to_string(),
+                reaction_type,
+                removed: kind.is_removal,
+                associated_guid: row.get::<_, Option<String>>(1)?,
+                date: queries::decode_cocoa(row.get(3)?).iso(),
+                is_from_me: row.get::<_, i32>(4)? != 0,
+                reactor_handle: row.get::<_, Option<String>>(5)?,
+            })
+        })?
+        .filter_map(helpers::ok_row)
+        .collect();
+    Ok(reactions)
+}
 
-    for row_result in rows {
-        let (text, date, is_from_me, handle_id) = row_result?;
-        if let Some(text) = text {
-            if let Some(ref re) = url_regex {
-                for url_match in re.find_iter(&text) {
-                    links.push(json!({
-                        "url": url_match.as_str(),
-                        "date": cocoa_to_iso(date),
-                        "is_from_me": is_from_me != 0,
-                        "sender_handle": handle_id.clone(),
-                    }));
-                }
-            }
+/// Standing tapbacks per message guid (helpers::query_message_reactions),
+/// with contact names for the people who reacted.
+pub fn named_reactions(
+    conn: &rusqlite::Connection,
+    contacts: &ContactsManager,
+    rowids: &[i64],
+) -> Result<HashMap<String, Vec<helpers::MessageReaction>>> {
+    let mut reactions = helpers::query_message_reactions(conn, rowids)?;
+    for r in reactions.values_mut().flatten().filter(|r| !r.is_from_me) {
+        if let Some(contact) = contacts.find_by_phone(&r.from) {
+            r.from = contact.name.clone();
         }
     }
+    Ok(reactions)
+}
 
-    if json_out {
-        println!("{}", serde_json::to_string(&links)?);
-    } else {
-        if links.is_empty() {
-            println!("No links found.");
-            return Ok(());
-        }
-
-        println!("Shared Links ({}):", links.len());
-        println!("{}", "-".repeat(60));
-        for link in &links {
-            println!("{}", link["url"].as_str().unwrap_or(""));
-        }
+/// " [❤️ Alice, 👍 Me]" after a text-mode message line; empty without tapbacks.
+pub(crate) fn reaction_suffix(reactions: &[helpers::MessageReaction]) -> String {
+    if reactions.is_empty() {
+        return String::new();
     }
-
-    Ok(())
+    let tags: Vec<String> = reactions.iter().map(|r| format!("{} {}", r.emoji, r.from)).collect();
+    format!(" [{}]", tags.join(", "))
 }
 
-/// Get voice messages with file paths.
-pub fn voice(_contact: Option<&str>, limit: u32, json_out: bool) -> Result<()> {
+/// Get voice messages with file paths, duration, and transcripts.
+pub fn voice(
+    contact: Option<&str>,
+    limit: u32,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
     let conn = connection::open_db()?;
+    let rows = attachments::query_attachments(&conn, contact, Some("audio/"), limit, contacts)?;
 
-    let mut stmt = conn.prepare(
-        r#"
-        SELECT
-            attachment.filename,
-            attachment.total_bytes,
-            message.date,
-            message.is_from_me,
-            handle.id
-        FROM attachment
-        JOIN message_attachment_join ON attachment.ROWID = message_attachment_join.attachment_id
-        JOIN message ON message_attachment_join.message_id = message.ROWID
-        LEFT JOIN handle ON message.handle_id = handle.ROWID
-        WHERE attachment.mime_type LIKE 'audio/%'
-        ORDER BY message.date DESC
-        LIMIT ?1
-        "#,
-    )?;
-
-    let voice_msgs: Vec<serde_json::Value> = stmt
-        .query_map([limit], |row| {
-            Ok(json!({
-                "attachment_path": row.get::<_, Option<String>>(0)?,
-                "size_bytes": row.get::<_, Option<i64>>(1)?,
-                "date": cocoa_to_iso(row.get::<_, i64>(2)?),
-                "is_from_me": row.get::<_, i32>(3)? != 0,
-                "sender_handle": row.get::<_, Option<String>>(4)?,
-            }))
-        })?
-        .filter_map(|r| r.ok())
-        .collect();
+    let voice_msgs: Vec<VoiceMessage> = rows.into_iter().map(VoiceMessage::from).collect();
 
-    if json_out {
-        println!("{}", serde_json::to_string(&voice_msgs)?);
+    if output.json {
+        output.print_json(&voice_msgs, false)?;
     } else {
         if voice_msgs.is_empty() {
             println!("No voice messages found.");
@@ -881,88 +1068,175 @@ pub fn voice(_contact: Option<&str>, limit: u32, json_out: bool) -> Result<()> {
         println!("Voice Messages ({}):", voice_msgs.len());
         println!("{}", "-".repeat(60));
         for v in &voice_msgs {
-            let path = v["attachment_path"].as_str().unwrap_or("N/A");
-            println!("{}", path);
+            let path = v.attachment_path.as_deref().unwrap_or("N/A");
+            let who = if v.is_from_me {
+                "Me"
+            } else {
+                v.sender_name
+                    .as_deref()
+                    .or(v.sender_handle.as_deref())
+                    .unwrap_or("Unknown")
+            };
+            let duration = v
+                .duration_secs
+                .map(|d| format!("{:.1}s", d))
+                .unwrap_or_else(|| "?s".to_string());
+            println!("[{}] {} ({}) {}", v.date, who, duration, path);
+            if let Some(transcript) = &v.transcript {
+                println!("    \"{}\"", transcript);
+            }
         }
     }
 
     Ok(())
 }
 
-/// Get messages in a reply thread.
-pub fn thread(guid: &str, limit: u32, json_out: bool) -> Result<()> {
+/// Get the full reply tree containing a message.
+///
+/// Accepts any message in the thread (originator or reply); resolves the
+/// originator first, then returns every message beneath it with `depth` and
+/// `reply_to_guid`. A message with no thread comes back alone (thread_size 1).
+pub fn thread(
+    guid: &str,
+    limit: u32,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
     let conn = connection::open_db()?;
+    let thread = build_thread(&conn, guid, limit, contacts)?;
 
-    let mut stmt = conn.prepare(
-        r#"
-        SELECT
-            message.text,
-            message.date,
-            message.is_from_me,
-            handle.id,
-            message.thread_originator_guid
-        FROM message
-        LEFT JOIN handle ON message.handle_id = handle.ROWID
-        WHERE message.thread_originator_guid = ?1
-           OR message.guid = ?1
-        ORDER BY message.date ASC
-        LIMIT ?2
-        "#,
-    )?;
-
-    let thread_msgs: Vec<serde_json::Value> = stmt
-        .query_map(rusqlite::params![guid, limit], |row| {
-            Ok(json!({
-                "text": row.get::<_, Option<String>>(0)?,
-                "date": cocoa_to_iso(row.get::<_, i64>(1)?),
-                "is_from_me": row.get::<_, i32>(2)? != 0,
-                "sender_handle": row.get::<_, Option<String>>(3)?,
-                "is_thread_originator": row.get::<_, Option<String>>(4)?.is_none(),
-            }))
-        })?
-        .filter_map(|r| r.ok())
-        .collect();
+    if output.json {
+        output.print(&thread)?;
+        return Ok(());
+    }
 
-    if json_out {
-        println!("{}", serde_json::to_string(&thread_msgs)?);
-    } else {
-        if thread_msgs.is_empty() {
-            println!("No thread messages found.");
-            return Ok(());
-        }
+    println!("Thread ({} messages):", thread.thread_size);
+    println!("{}", "-".repeat(60));
+    for entry in &thread.messages {
+        let m = &entry.message;
+        let sender = if m.is_from_me {
+            "Me"
+        } else {
+            entry
+                .sender_name
+                .as_deref()
+                .or(m.sender_handle.as_deref())
+                .unwrap_or("Unknown")
+        };
+        let text = m.text.as_deref().or(m.placeholder.as_deref()).unwrap_or("[media]");
+        let marker = if m.guid == guid { " <" } else { "" };
+        println!("{}{}: {}{}", "  ".repeat(m.depth.max(0) as usize), sender, text, marker);
+    }
 
-        println!("Thread Messages ({}):", thread_msgs.len());
-        println!("{}", "-".repeat(60));
-        for m in &thread_msgs {
-            let sender = if m["is_from_me"].as_bool().unwrap_or(false) {
-                "Me"
+    Ok(())
+}
+
+/// Resolve the thread containing `guid` and name its senders.
+pub fn build_thread(
+    conn: &rusqlite::Connection,
+    guid: &str,
+    limit: u32,
+    contacts: &ContactsManager,
+) -> Result<Thread> {
+    let root_guid = helpers::resolve_thread_root(conn, guid)?
+        .ok_or_else(|| anyhow::anyhow!("Message not found: {}", guid))?;
+    let messages: Vec<ThreadEntry> = helpers::query_thread(conn, &root_guid, limit)?
+        .into_iter()
+        .map(|m| {
+            let sender_name = if m.is_from_me {
+                None
             } else {
-                m["sender_handle"].as_str().unwrap_or("Unknown")
+                m.sender_handle
+                    .as_deref()
+                    .and_then(|h| contacts.find_by_phone(h))
+                    .map(|c| c.name.clone())
             };
-            let text = m["text"].as_str().unwrap_or("[media]");
-            println!("{}: {}", sender, text);
-        }
-    }
+            ThreadEntry { message: m, sender_name }
+        })
+        .collect();
 
-    Ok(())
+    Ok(Thread {
+        root_guid,
+        requested_guid: guid.to_string(),
+        thread_size: messages.len(),
+        messages,
+    })
 }
 
-/// Get conversation formatted for AI summarization.
-#[allow(clippy::too_many_arguments)]
-pub fn summary(
-    _contact: &str,
-    _days: Option<u32>,
-    _start: Option<&str>,
-    _end: Option<&str>,
-    _limit: u32,
-    _offset: u32,
-    _order: &str,
-    json_out: bool,
-) -> Result<()> {
-    // [*INCOMPLETE*] Needs contact resolution
-    eprintln!("[TODO] summary: needs contact resolution");
-    if json_out {
-        println!("{{}}");
+/// Resolve a contact name or raw handle to a filter covering all of its handles.
+pub(crate) fn contact_filter<'a>(
+    conn: &rusqlite::Connection,
+    contact: &str,
+    contacts: &'a ContactsManager,
+) -> Result<(Option<&'a crate::contacts::manager::Contact>, helpers::HandleFilter)> {
+    let known = contacts.find_by_phone(contact).or_else(|| contacts.find_fuzzy(contact));
+    let handles: Vec<String> = match known {
+        Some(c) => c.all_handles().map(String::from).collect(),
+        None => vec![contact.to_string()],
+    };
+    Ok((known, helpers::HandleFilter::resolve(conn, &handles)?))
+}
+
+/// Parse --days/--start/--end (summary, analytics) into a Cocoa [start, end)
+/// range; `end` is `i64::MAX` without --end.
+///
+/// --start/--end are local dates (end inclusive); --days counts back from now.
+pub fn date_range(days: Option<u32>, start: Option<&str>, end: Option<&str>) -> Result<(i64, i64)> {
+    let local_midnight = |s: &str, flag: &str| -> Result<i64> {
+        let date = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|_| anyhow!("Invalid {} '{}' (use YYYY-MM-DD)", flag, s))?;
+        let dt = chrono::Local
+            .from_local_datetime(&date.and_time(chrono::NaiveTime::MIN))
+            .earliest()
+            .ok_or_else(|| anyhow!("Invalid local midnight for {}", date))?;
+        Ok(queries::unix_to_cocoa(dt.timestamp()))
+    };
+
+    let start_cocoa = match (start, days) {
+        (Some(s), _) => local_midnight(s, "--start")?,
+        (None, Some(d)) => queries::days_ago_cocoa(d),
+        (None, None) => 0,
+    };
+    let end_cocoa = match end {
+        Some(e) => local_midnight(e, "--end")? + 86_400 * 1_000_000_000,
+        None => i64::MAX,
+    };
+    if start_cocoa >= end_cocoa {
+        return Err(anyhow!("--start must be on or before --end"));
+    }
+    Ok((start_cocoa, end_cocoa))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_resolution_names_the_chosen_contact() {
+        use crate::contacts::manager::Contact;
+        let contact = |name: &str, phone: &str| Contact {
+            name: name.to_string(),
+            phone: phone.to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        };
+        let contacts =
+            ContactsManager::from_contacts(vec![contact("Alex Smith", "+14155550001"), contact("Alex Smyth", "+14155550002")]);
+        let report = EmptyResolution {
+            messages: Vec::new(),
+            resolution: ContactResolution { resolution: contacts.resolve_with_diagnostics("Alex").unwrap(), message_count: 0 },
+        };
+
+        let text = report.render(&TextStyle::default());
+        assert!(text.starts_with("No messages found for 'Alex'"));
+        assert!(text.contains("Resolved to Alex Smith (+14155550001): partial match, score"));
+        assert!(text.contains("Other matches: Alex Smyth (+14155550002"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["messages"], serde_json::json!([]));
+        assert_eq!(json["resolution"]["message_count"], 0);
+        assert_eq!(json["resolution"]["contact_name"], "Alex Smith");
+        assert_eq!(json["resolution"]["candidates"][0]["name"], "Alex Smyth");
     }
-    Ok(())
 }
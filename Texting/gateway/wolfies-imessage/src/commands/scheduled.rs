@@ -0,0 +1,178 @@
+//! Scheduled sends: queue with `send --at/--in`, list and cancel with `scheduled`.
+//!
+//! Messages.db has no scheduled-send table, so jobs live in the local queue
+//! (see crate::scheduler) and are sent by the daemon's worker thread.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - ScheduleResult.dry_run is serde(default): optional in the schema
+//! - 10/16/2026 - Typed outputs (ScheduleResult, ScheduledList, CancelResult) for `schema`
+//! - 10/16/2026 - Scheduling honours send safety config (dry run, max message length)
+//! - 10/16/2026 - Initial scheduled list/cancel and send scheduling
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::commands::messaging::{check_message_length, dry_run, normalize_phone};
+use crate::contacts::manager::ContactsManager;
+use crate::output::OutputControls;
+use crate::scheduler::{JobStatus, ScheduleStore, ScheduledJob};
+
+/// `send --at/--in` output: the queued job, or what would be queued on a dry run.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScheduleResult {
+    pub scheduled: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job: Option<ScheduledJob>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_at: Option<String>,
+}
+
+/// `scheduled list` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScheduledList {
+    pub scheduled_messages: Vec<ScheduledJob>,
+    pub pending: usize,
+}
+
+/// `scheduled cancel` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CancelResult {
+    pub cancelled: bool,
+    pub job: ScheduledJob,
+}
+
+/// Queue a message to a contact by name.
+pub fn schedule_to_contact(
+    contact: &str,
+    message: &str,
+    send_at: DateTime<Local>,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let phone = contacts
+        .resolve_to_phone(contact)
+        .ok_or_else(|| anyhow!("Contact '{}' not found", contact))?;
+    check_message_length(message)?;
+    if dry_run() {
+        print_dry_run(&phone, message, send_at, output)?;
+        return Ok(());
+    }
+    let job = ScheduleStore::open_default().add(&phone, Some(contact), message, send_at)?;
+    print_scheduled(&job, output)
+}
+
+/// Queue a message to a phone number.
+pub fn schedule_to_phone(
+    phone: &str,
+    message: &str,
+    send_at: DateTime<Local>,
+    output: &OutputControls,
+) -> Result<()> {
+    let phone = normalize_phone(phone);
+    check_message_length(message)?;
+    if dry_run() {
+        print_dry_run(&phone, message, send_at, output)?;
+        return Ok(());
+    }
+    let job = ScheduleStore::open_default().add(&phone, None, message, send_at)?;
+    print_scheduled(&job, output)
+}
+
+/// Dry run: report the job instead of queueing it.
+fn print_dry_run(phone: &str, message: &str, send_at: DateTime<Local>, output: &OutputControls) -> Result<()> {
+    if output.json {
+        output.print(&ScheduleResult {
+            scheduled: false,
+            dry_run: true,
+            job: None,
+            phone: Some(phone.to_string()),
+            message: Some(message.to_string()),
+            send_at: Some(send_at.to_rfc3339()),
+        })?;
+    } else {
+        println!("Dry run: would schedule for {} to {}", send_at.format("%Y-%m-%d %H:%M"), phone);
+    }
+    Ok(())
+}
+
+fn print_scheduled(job: &ScheduledJob, output: &OutputControls) -> Result<()> {
+    if output.json {
+        output.print(&ScheduleResult {
+            scheduled: true,
+            dry_run: false,
+            job: Some(job.clone()),
+            phone: None,
+            message: None,
+            send_at: None,
+        })?;
+    } else {
+        println!(
+            "Scheduled {} for {} (id {})",
+            job.contact.as_deref().unwrap_or(&job.recipient),
+            job.send_at.format("%Y-%m-%d %H:%M"),
+            job.id
+        );
+        println!("Note: the daemon must be running to send it.");
+    }
+    Ok(())
+}
+
+/// List pending, sent, and failed jobs.
+pub fn list(output: &OutputControls) -> Result<()> {
+    let jobs = ScheduleStore::open_default().list()?;
+
+    if output.json {
+        let pending = jobs.iter().filter(|j| j.status == JobStatus::Pending).count();
+        output.print(&ScheduledList {
+            scheduled_messages: jobs,
+            pending,
+        })?;
+        return Ok(());
+    }
+
+    println!("Scheduled Messages:");
+    println!("{:-<60}", "");
+    if jobs.is_empty() {
+        println!("No scheduled messages.");
+        return Ok(());
+    }
+    for job in &jobs {
+        let status = match job.status {
+            JobStatus::Pending => "pending",
+            JobStatus::InProgress => "sending",
+            JobStatus::Sent => "sent",
+            JobStatus::Failed => "failed",
+        };
+        println!(
+            "[{}] {} {:<8} {}: {}",
+            job.id,
+            job.send_at.format("%Y-%m-%d %H:%M"),
+            status,
+            job.contact.as_deref().unwrap_or(&job.recipient),
+            job.message
+        );
+        if let Some(ref error) = job.error {
+            println!("    error: {}", error);
+        }
+    }
+    Ok(())
+}
+
+/// Cancel a pending job.
+pub fn cancel(id: &str, output: &OutputControls) -> Result<()> {
+    let job = ScheduleStore::open_default().cancel(id)?;
+    if output.json {
+        output.print(&CancelResult { cancelled: true, job })?;
+    } else {
+        println!("Cancelled scheduled message {}", job.id);
+    }
+    Ok(())
+}
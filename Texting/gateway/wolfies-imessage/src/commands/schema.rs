@@ -0,0 +1,436 @@
+//! `schema`: JSON Schema for each command's `--json` output.
+//!
+//! Schemas are generated from the output types themselves (`JsonSchema`
+//! derives), so they can't drift from what the commands print. Commands
+//! whose output shape depends on a flag (e.g. `recent --per-conversation`,
+//! `analytics --series`, `attachments --copy-to`) get an `anyOf` over the
+//! possible shapes.
+//! Daemon-backed RAG commands other than `ask` and `clear` pass the
+//! daemon's result through unchanged, so their schema is unconstrained.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - unread and recent drop the muted_count `results` variants (muted_count is envelope meta only)
+//! - 10/16/2026 - analytics --streaks schema
+//! - 10/16/2026 - maintenance schema (status and prune)
+//! - 10/16/2026 - raw-message schema
+//! - 10/16/2026 - analytics schema covers the --compare comparison block
+//! - 10/16/2026 - unread schema covers --by-conversation
+//! - 10/16/2026 - attachments schema covers --dedupe listing and copy-out
+//! - 10/16/2026 - presets schema (list and delete)
+//! - 10/16/2026 - find/messages cover the empty-result resolution object
+//! - 10/16/2026 - analytics covers --heatmap output
+//! - 10/16/2026 - chat-info schema
+//! - 10/16/2026 - analytics covers --gaps output
+//! - 10/16/2026 - analytics covers --emoji output
+//! - 10/16/2026 - quick schema; doctor listed for --all
+//! - 10/16/2026 - doctor schema
+//! - 10/16/2026 - followup covers snooze/unsnooze output
+//! - 10/16/2026 - account schema
+//! - 10/16/2026 - contacts covers dedupe
+//! - 10/16/2026 - send-log schema
+//! - 10/16/2026 - mute/unmute/mutes schemas; unread and recent cover the muted_count envelope
+//! - 10/16/2026 - draft schema
+//! - 10/16/2026 - compare schema
+//! - 10/16/2026 - deleted and summary schemas
+//! - 10/16/2026 - group-history schema
+//! - 10/16/2026 - contacts schema covers history, diff, and restore output
+//! - 10/16/2026 - contacts schema covers --stats output
+//! - 10/16/2026 - Added commitments schema
+//! - 10/16/2026 - analytics schema covers --series and --top-terms output
+//! - 10/16/2026 - Initial schema registry and `schema <command>` / `schema --all`
+
+use anyhow::{anyhow, Result};
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde_json::{Map, Value};
+
+use crate::commands::account::AccountInfo;
+use crate::commands::analytics::{Analytics, EmojiStats, Heatmap, SilenceGaps, Streaks, TopTerms};
+use crate::commands::attachments::{Attachment, CopyResult, DedupedAttachments, DedupedCopy};
+use crate::commands::cache::CacheClear;
+use crate::commands::commitments::Commitment;
+use crate::commands::compare::CompareReport;
+use crate::commands::config::ConfigShow;
+use crate::commands::contacts::{ContactStats, DedupeReport};
+use crate::commands::deleted::DeletedMessage;
+use crate::commands::digest::Digest;
+use crate::commands::doctor::DoctorReport;
+use crate::commands::draft::{DraftDeleteResult, DraftSendReport};
+use crate::commands::discovery::{DiscoveryCandidate, Handle, MergedHandle, UnknownSender};
+use crate::commands::followup::{FollowUpReport, UnsnoozeResult};
+use crate::commands::groups::{GroupAnalytics, GroupChat, GroupHistory, GroupMessage};
+use crate::commands::links::SharedLink;
+use crate::commands::maintenance::{DataStatus, PruneReport};
+use crate::commands::messaging::{MarkReadReport, SendLogSummary, SendResult};
+use crate::commands::mutes::UnmuteResult;
+use crate::commands::presets::PresetDelete;
+use crate::commands::chat_info::ChatInfoReport;
+use crate::commands::quick::QuickReport;
+use crate::commands::rag::{AskResult, ClearResult};
+use crate::commands::raw_message::RawMessage;
+use crate::commands::reading::{
+    Bundle, ConversationRow, EmptyResolution, Message, Reaction, SearchMatch, Thread, UnreadChat,
+    VoiceMessage,
+};
+use crate::commands::scheduled::{CancelResult, ScheduleResult, ScheduledList};
+use crate::commands::summary::Summary;
+use crate::commands::watch::WatchEvent;
+use crate::drafts::{Draft, SentDraft};
+use crate::mutes::Mute;
+use crate::presets::Preset;
+use crate::snoozes::Snooze;
+use crate::contacts::backup::{BackupInfo, ContactsDiff, RestoreResult};
+use crate::contacts::manager::Contact;
+use crate::db::helpers::VolumeBucket;
+
+/// Commands with a JSON output schema, in `--help` order.
+pub const COMMANDS: &[&str] = &[
+    "find",
+    "messages",
+    "recent",
+    "unread",
+    "quick",
+    "digest",
+    "watch",
+    "text-search",
+    "bundle",
+    "send",
+    "send-by-phone",
+    "mark-read",
+    "send-log",
+    "contacts",
+    "analytics",
+    "followup",
+    "commitments",
+    "groups",
+    "group-analytics",
+    "group-history",
+    "group-messages",
+    "chat-info",
+    "attachments",
+    "reactions",
+    "links",
+    "voice",
+    "deleted",
+    "compare",
+    "thread",
+    "handles",
+    "unknown",
+    "discover",
+    "scheduled",
+    "draft",
+    "mute",
+    "unmute",
+    "mutes",
+    "presets",
+    "cache",
+    "maintenance",
+    "account",
+    "doctor",
+    "raw-message",
+    "config",
+    "summary",
+    "index",
+    "search",
+    "ask",
+    "stats",
+    "clear",
+    "sources",
+];
+
+// Schema-only unions for commands whose output shape depends on a flag.
+// They are never constructed; `untagged` makes schemars emit `anyOf`.
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum RecentOutput {
+    Messages(Vec<Message>),
+    Conversations(Vec<ConversationRow>),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum UnreadOutput {
+    Messages(Vec<Message>),
+    ByConversation(Vec<UnreadChat>),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum ContactsOutput {
+    List(Vec<Contact>),
+    Stats(Vec<ContactStats>),
+    History(Vec<BackupInfo>),
+    Diff(ContactsDiff),
+    Restore(RestoreResult),
+    Dedupe(DedupeReport),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum AnalyticsOutput {
+    Totals(Box<Analytics>),
+    Series(Vec<VolumeBucket>),
+    TopTerms(TopTerms),
+    Emoji(EmojiStats),
+    Gaps(SilenceGaps),
+    Heatmap(Box<Heatmap>),
+    Streaks(Streaks),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum FindOutput {
+    Messages(Vec<Message>),
+    Unmatched(EmptyResolution),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum SendOutput {
+    Sent(SendResult),
+    Scheduled(ScheduleResult),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum AttachmentsOutput {
+    Listed(Vec<Attachment>),
+    Copied(Vec<CopyResult>),
+    Deduped(DedupedAttachments),
+    DedupedCopy(DedupedCopy),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum HandlesOutput {
+    Handles(Vec<Handle>),
+    Merged(Vec<MergedHandle>),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum ScheduledOutput {
+    List(ScheduledList),
+    Cancelled(CancelResult),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum FollowupOutput {
+    Report(FollowUpReport),
+    Snoozed(Snooze),
+    Unsnoozed(UnsnoozeResult),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum DraftOutput {
+    Added(Draft),
+    List(Vec<Draft>),
+    Sent(Vec<SentDraft>),
+    SendReport(DraftSendReport),
+    Deleted(DraftDeleteResult),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum PresetsOutput {
+    List(Vec<Preset>),
+    Deleted(PresetDelete),
+}
+
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum MaintenanceOutput {
+    Status(DataStatus),
+    Pruned(PruneReport),
+}
+
+/// JSON Schema for one command's `--json` output, or None if it has none.
+pub fn schema_for_command(command: &str) -> Option<RootSchema> {
+    let schema = match command {
+        "find" | "messages" => schema_for!(FindOutput),
+        "unread" => schema_for!(UnreadOutput),
+        "recent" => schema_for!(RecentOutput),
+        "digest" => schema_for!(Digest),
+        "watch" => schema_for!(WatchEvent),
+        "text-search" => schema_for!(Vec<SearchMatch>),
+        "bundle" => schema_for!(Bundle),
+        "send" | "send-by-phone" => schema_for!(SendOutput),
+        "mark-read" => schema_for!(MarkReadReport),
+        "send-log" => schema_for!(SendLogSummary),
+        "contacts" => schema_for!(ContactsOutput),
+        "analytics" => schema_for!(AnalyticsOutput),
+        "followup" => schema_for!(FollowupOutput),
+        "commitments" => schema_for!(Vec<Commitment>),
+        "groups" => schema_for!(Vec<GroupChat>),
+        "group-analytics" => schema_for!(GroupAnalytics),
+        "group-history" => schema_for!(GroupHistory),
+        "group-messages" => schema_for!(Vec<GroupMessage>),
+        "attachments" => schema_for!(AttachmentsOutput),
+        "reactions" => schema_for!(Vec<Reaction>),
+        "links" => schema_for!(Vec<SharedLink>),
+        "voice" => schema_for!(Vec<VoiceMessage>),
+        "deleted" => schema_for!(Vec<DeletedMessage>),
+        "compare" => schema_for!(CompareReport),
+        "thread" => schema_for!(Thread),
+        "handles" => schema_for!(HandlesOutput),
+        "unknown" => schema_for!(Vec<UnknownSender>),
+        "discover" => schema_for!(Vec<DiscoveryCandidate>),
+        "scheduled" => schema_for!(ScheduledOutput),
+        "draft" => schema_for!(DraftOutput),
+        "mute" => schema_for!(Mute),
+        "unmute" => schema_for!(UnmuteResult),
+        "mutes" => schema_for!(Vec<Mute>),
+        "presets" => schema_for!(PresetsOutput),
+        "cache" => schema_for!(CacheClear),
+        "maintenance" => schema_for!(MaintenanceOutput),
+        "account" => schema_for!(AccountInfo),
+        "doctor" => schema_for!(DoctorReport),
+        "raw-message" => schema_for!(RawMessage),
+        "quick" => schema_for!(QuickReport),
+        "chat-info" => schema_for!(ChatInfoReport),
+        "config" => schema_for!(ConfigShow),
+        "summary" => schema_for!(Summary),
+        "ask" => schema_for!(AskResult),
+        "clear" => schema_for!(ClearResult),
+        // Daemon result passed through as-is
+        "index" | "search" | "stats" | "sources" => schema_for!(Value),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// Print one command's schema, or every schema keyed by command with `all`.
+pub fn print(command: Option<&str>, all: bool) -> Result<()> {
+    let value = if all {
+        let mut schemas = Map::new();
+        for name in COMMANDS {
+            if let Some(schema) = schema_for_command(name) {
+                schemas.insert(name.to_string(), serde_json::to_value(schema)?);
+            }
+        }
+        Value::Object(schemas)
+    } else {
+        let command = command.ok_or_else(|| anyhow!("Specify a command or --all"))?;
+        let schema = schema_for_command(command).ok_or_else(|| {
+            anyhow!(
+                "No JSON schema for '{}'. Commands with schemas: {}",
+                command,
+                COMMANDS.join(", ")
+            )
+        })?;
+        serde_json::to_value(schema)?
+    };
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{attachments, digest, groups, links, reading};
+    use crate::contacts::manager::ContactsManager;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+    use crate::db::{helpers, queries};
+    use crate::api;
+    use crate::senders::{SenderFilter, SenderMode};
+    use rusqlite::Connection;
+    use serde::Serialize;
+
+    /// Validate `output` against the command's schema, failing with every error.
+    fn assert_valid<T: Serialize>(command: &str, output: &T) {
+        let schema = serde_json::to_value(schema_for_command(command).unwrap()).unwrap();
+        let instance = serde_json::to_value(output).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let errors: Vec<String> = validator.iter_errors(&instance).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "{} output violates its schema: {:?}", command, errors);
+    }
+
+    /// A 1:1 conversation and a group chat with recent traffic.
+    fn fixture() -> Connection {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let direct = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let group = insert_chat(&conn, "chat900", Some("Crew"), &[alice, bob]);
+
+        let recent = queries::days_ago_cocoa(1);
+        insert_message(&conn, direct, alice, Some("dinner tonight? https://example.com/menu"), recent, false, false);
+        insert_message(&conn, direct, alice, Some("sure"), recent + 1, true, true);
+        insert_message(&conn, group, bob, Some("who's driving?"), recent + 2, false, true);
+        insert_message(&conn, group, alice, Some("me"), recent + 3, false, true);
+        conn
+    }
+
+    #[test]
+    fn test_every_listed_command_has_a_schema() {
+        for name in COMMANDS {
+            assert!(schema_for_command(name).is_some(), "missing schema for {}", name);
+        }
+        assert!(schema_for_command("setup").is_none());
+        assert!(print(Some("nope"), false).is_err());
+    }
+
+    #[test]
+    fn test_reading_outputs_match_schemas() {
+        let conn = fixture();
+        let contacts = ContactsManager::empty();
+
+        let rows = reading::conversation_rows(&conn, 10, None, None, &contacts).unwrap();
+        assert!(!rows.is_empty());
+        assert_valid("recent", &rows);
+
+        let unread = api::unread(&conn, &contacts, &api::UnreadOptions::default()).unwrap();
+        assert_valid("unread", &unread);
+        let by_chat = api::unread_by_chat(&conn, &contacts, None).unwrap();
+        assert!(!by_chat.is_empty());
+        assert_valid("unread", &by_chat);
+
+        let terms = vec!["dinner".to_string()];
+        let hits = helpers::query_text_search(&conn, &terms, queries::TermMatch::Any, None, false, 10).unwrap();
+        assert_valid("text-search", &reading::search_matches(hits));
+
+        assert_valid("reactions", &reading::query_reaction_rows(&conn, 10).unwrap());
+        assert_valid("thread", &reading::build_thread(&conn, "msg-1", 10, &contacts).unwrap());
+    }
+
+    #[test]
+    fn test_report_outputs_match_schemas() {
+        let conn = fixture();
+        let contacts = ContactsManager::empty();
+        let now = queries::days_ago_cocoa(0);
+
+        let senders = SenderFilter::load(&conn, &contacts, SenderMode::All).unwrap();
+        let digest = digest::build_digest(&conn, &contacts, now - 7 * DAY_NS, now, None, None, &senders).unwrap();
+        assert_valid("digest", &digest);
+
+        let stats = groups::build_group_analytics(&conn, &contacts, "chat900", 30, None).unwrap();
+        assert_valid("group-analytics", &stats);
+
+        let rows = attachments::query_attachments(&conn, None, None, 10, &contacts).unwrap();
+        assert_valid("attachments", &rows);
+
+        let occurrence = |url: &str| links::LinkOccurrence {
+            url: url.to_string(),
+            date_cocoa: now,
+            shared_by: "me".to_string(),
+        };
+        let occurrences = vec![occurrence("https://example.com/menu"), occurrence("https://example.com/menu")];
+        assert_valid("links", &links::dedupe_links(occurrences, links::LinkSort::Recent));
+    }
+}
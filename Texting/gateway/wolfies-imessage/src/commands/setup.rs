@@ -1,16 +1,19 @@
 //! Setup command for configuring database access.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Not-implemented notice goes through output::warn
 //! - 01/10/2026 - Initial stub implementation (Claude)
 
 use anyhow::Result;
 
+use crate::output;
+
 /// Run the setup command to configure database access.
 pub fn run(yes: bool, force: bool, json: bool) -> Result<()> {
     // [*INCOMPLETE*] Implement file picker and bookmark storage
     // Status: Stub only
     // Remaining: Port NSOpenPanel from file_picker.py, security-scoped bookmarks
-    eprintln!("[TODO] setup: yes={}, force={}", yes, force);
+    output::warn(format!("setup is not implemented yet (yes={}, force={})", yes, force));
     if json {
         println!(r#"{{"success": false, "error": "Not implemented yet"}}"#);
     } else {
@@ -0,0 +1,488 @@
+//! Summary command: one contact's conversation in a date window, as a
+//! transcript for AI summarization.
+//!
+//! Pages by keyset cursor (`--cursor`, the previous page's
+//! `meta.next_cursor`, which only continues the order it came from);
+//! `--offset` re-reads every skipped row and can't be combined with it. Recently
+//! deleted messages are left out unless `--include-deleted` keeps them,
+//! marked (see commands::deleted).
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - SummaryMessage.deleted is serde(default) (the schema required a field that is only printed when true)
+//! - 10/16/2026 - A cursor can't be combined with an offset or reused under the other order
+//! - 10/16/2026 - Moved from reading.rs into its own module
+
+use anyhow::{anyhow, bail, Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::commands::reading::{contact_filter, date_range, named_reactions, reaction_suffix};
+use crate::contacts::manager::ContactsManager;
+use crate::db::cursor::{Cursor, PageMeta};
+use crate::db::message_body::{self, display_text};
+use crate::db::{connection, helpers, queries};
+use crate::language;
+use crate::output::OutputControls;
+
+/// `summary` output: one conversation window as a transcript.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Summary {
+    pub contact: String,
+    pub contact_name: Option<String>,
+    /// Window start (None = from the beginning).
+    pub start: Option<String>,
+    /// Window end, exclusive (None = now).
+    pub end: Option<String>,
+    pub offset: u32,
+    pub message_count: usize,
+    /// Recently deleted messages in the transcript (only with --include-deleted).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_count: Option<usize>,
+    /// Share of messages per language code (only with --detect-language).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_distribution: Option<BTreeMap<String, f64>>,
+    pub messages: Vec<SummaryMessage>,
+    /// `next_cursor` continues after this page
+    pub meta: PageMeta,
+}
+
+/// One transcript line.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SummaryMessage {
+    pub rowid: i64,
+    pub guid: String,
+    pub date: Option<String>,
+    /// The stored date is zero, before 2001, or in the future (`date` is null)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub date_suspect: bool,
+    pub is_from_me: bool,
+    /// "Me", the contact's name, or the raw handle.
+    pub sender: String,
+    pub text: String,
+    /// Attachment placeholder ("[photo]", "[file: name.pdf]", ...); `text` is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    /// In Recently Deleted (only present when true).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+    /// ISO 639-1 code, "und" when too short to tell (only with --detect-language;
+    /// attachment-only messages have none).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Standing tapbacks (only with --with-reactions; absent when there are none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reactions: Vec<helpers::MessageReaction>,
+}
+
+/// Which slice of a conversation `summary` returns.
+#[derive(Debug, Clone)]
+pub struct SummaryWindow {
+    pub start_cocoa: i64,
+    /// Exclusive; `i64::MAX` for "now".
+    pub end_cocoa: i64,
+    pub limit: u32,
+    pub offset: u32,
+    /// Continue after this row (keyset pagination; preferred over `offset`).
+    pub cursor: Option<Cursor>,
+    pub ascending: bool,
+    /// Keep recently deleted messages (marked) instead of dropping them.
+    pub include_deleted: bool,
+    /// Tag each message's language (off by default: it costs a pass over every text).
+    pub detect_language: bool,
+    /// Attach each message's tapbacks (one more query per page).
+    pub with_reactions: bool,
+}
+
+/// Build a contact's transcript for one window.
+pub fn build_summary(
+    conn: &rusqlite::Connection,
+    contacts: &ContactsManager,
+    contact: &str,
+    window: &SummaryWindow,
+) -> Result<Summary> {
+    let (known, filter) = contact_filter(conn, contact, contacts)?;
+
+    // Deleted messages keep their message row; only the chat join moves
+    let recoverable = helpers::has_recoverable_messages(conn);
+    let deleted_expr = if recoverable {
+        "m.ROWID IN (SELECT message_id FROM chat_recoverable_message_join)"
+    } else {
+        "0"
+    };
+    let sql = queries::SUMMARY_MESSAGES
+        .replace("{deleted}", deleted_expr)
+        .replace("{order}", if window.ascending { "ASC" } else { "DESC" });
+    let mut sql = queries::with_filter(&sql, &filter.sql_condition("m.handle_id"));
+    if recoverable && !window.include_deleted {
+        sql = queries::with_filter(&sql, &format!("NOT ({})", deleted_expr));
+    }
+    let mut params: Vec<i64> = vec![window.start_cocoa, window.end_cocoa, window.limit.into(), window.offset.into()];
+    if let Some(cursor) = window.cursor {
+        if window.offset > 0 {
+            bail!("--cursor and --offset can't be combined (the cursor already marks the position)");
+        }
+        cursor.check_order(window.ascending)?;
+        sql = queries::with_filter(&sql, &cursor.condition("m", 5));
+        params.extend([cursor.date, cursor.rowid]);
+    }
+
+    let contact_name = known.map(|c| c.name.clone());
+    let mut last = None;
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare query")?;
+    let rows = stmt.query_map(
+        rusqlite::params_from_iter(&params),
+        |row| {
+            let guid: Option<String> = row.get(1)?;
+            let text: Option<String> = row.get(2)?;
+            let blob: Option<Vec<u8>> = row.get(3)?;
+            let is_from_me: bool = row.get(5)?;
+            let handle: Option<String> = row.get(6)?;
+            let hint: Option<String> = row.get(8)?;
+            let (text, placeholder) =
+                message_body::message_body(guid.as_deref(), text, blob.as_deref(), hint.as_deref()).into_parts();
+            let (rowid, date): (i64, i64) = (row.get(0)?, row.get(4)?);
+            let decoded = queries::decode_cocoa(date);
+            Ok((Cursor { date, rowid, ascending: window.ascending }, SummaryMessage {
+                rowid,
+                text,
+                placeholder,
+                guid: guid.unwrap_or_default(),
+                date: decoded.iso(),
+                date_suspect: decoded.suspect,
+                is_from_me,
+                sender: if is_from_me {
+                    "Me".to_string()
+                } else {
+                    contact_name
+                        .clone()
+                        .or(handle)
+                        .unwrap_or_else(|| "Unknown".to_string())
+                },
+                deleted: row.get(7)?,
+                lang: None,
+                reactions: Vec::new(),
+            }))
+        },
+    )?;
+    let mut messages = Vec::new();
+    for row in rows {
+        let (cursor, message) = row?;
+        last = Some(cursor);
+        messages.push(message);
+    }
+    if window.with_reactions {
+        let rowids: Vec<i64> = messages.iter().map(|m| m.rowid).collect();
+        let mut reactions = named_reactions(conn, contacts, &rowids)?;
+        for m in &mut messages {
+            m.reactions = reactions.remove(&m.guid).unwrap_or_default();
+        }
+    }
+    let language_distribution = window.detect_language.then(|| {
+        for m in messages.iter_mut().filter(|m| !m.text.is_empty()) {
+            m.lang = Some(language::detect(&m.text).to_string());
+        }
+        language::distribution(messages.iter().filter_map(|m| m.lang.as_deref()))
+    });
+
+    Ok(Summary {
+        contact: contact.to_string(),
+        start: (window.start_cocoa > 0).then(|| helpers::cocoa_to_iso(window.start_cocoa)),
+        end: (window.end_cocoa < i64::MAX).then(|| helpers::cocoa_to_iso(window.end_cocoa)),
+        offset: window.offset,
+        message_count: messages.len(),
+        deleted_count: window
+            .include_deleted
+            .then(|| messages.iter().filter(|m| m.deleted).count()),
+        language_distribution,
+        meta: PageMeta::after(messages.len(), window.limit, last),
+        contact_name,
+        messages,
+    })
+}
+
+/// Get conversation formatted for AI summarization.
+///
+/// Page with `cursor` (the previous page's `meta.next_cursor`); `offset`
+/// re-reads every skipped row and is kept for small values only.
+#[allow(clippy::too_many_arguments)]
+pub fn summary(
+    contact: &str,
+    days: Option<u32>,
+    start: Option<&str>,
+    end: Option<&str>,
+    limit: u32,
+    offset: u32,
+    cursor: Option<&str>,
+    order: &str,
+    include_deleted: bool,
+    detect_language: bool,
+    with_reactions: bool,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let conn = connection::open_db().context("Failed to open Messages database")?;
+    let (start_cocoa, end_cocoa) = date_range(days, start, end)?;
+    let window = SummaryWindow {
+        start_cocoa,
+        end_cocoa,
+        limit,
+        offset,
+        cursor: cursor.map(Cursor::decode).transpose()?,
+        ascending: match order {
+            "asc" => true,
+            "desc" => false,
+            other => return Err(anyhow!("Invalid order '{}' (expected asc|desc)", other)),
+        },
+        include_deleted,
+        detect_language,
+        with_reactions,
+    };
+    let summary = build_summary(&conn, contacts, contact, &window)?;
+
+    if output.json {
+        output.print(&summary)?;
+        return Ok(());
+    }
+
+    println!(
+        "Conversation with {} ({} messages)",
+        summary.contact_name.as_deref().unwrap_or(&summary.contact),
+        summary.message_count
+    );
+    if let Some(ref shares) = summary.language_distribution {
+        let mix: Vec<String> = shares.iter().map(|(lang, share)| format!("{} {:.0}%", lang, share * 100.0)).collect();
+        println!("Languages: {}", mix.join(", "));
+    }
+    for m in &summary.messages {
+        let marker = if m.deleted { " [deleted]" } else { "" };
+        let lang = m.lang.as_deref().map(|l| format!(" [{}]", l)).unwrap_or_default();
+        let text = display_text(&m.text, m.placeholder.as_deref());
+        println!(
+            "[{}] {}{}{}: {}{}",
+            m.date.as_deref().unwrap_or(""),
+            m.sender,
+            marker,
+            lang,
+            text,
+            reaction_suffix(&m.reactions)
+        );
+    }
+    if let Some(ref next) = summary.meta.next_cursor {
+        println!("More: --cursor {}", next);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{empty_db, insert_attachment, insert_chat, insert_handle, insert_message, DAY_NS};
+
+    #[test]
+    fn test_summary_cursor_pages_cover_thread_once() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        // 11 messages; runs of equal dates straddle page boundaries
+        let dates = [1, 2, 2, 2, 3, 4, 4, 5, 5, 5, 6];
+        let mut rowids = Vec::new();
+        for (i, day) in dates.iter().enumerate() {
+            let text = format!("m{}", i);
+            rowids.push(insert_message(&conn, chat, alice, Some(&text), 700 * DAY_NS + day, i % 2 == 0, true));
+        }
+        let contacts = ContactsManager::empty();
+
+        for ascending in [true, false] {
+            let mut window = SummaryWindow {
+                start_cocoa: 0,
+                end_cocoa: i64::MAX,
+                limit: 3,
+                offset: 0,
+                cursor: None,
+                ascending,
+                include_deleted: false,
+                detect_language: false,
+                with_reactions: false,
+            };
+            let mut seen = Vec::new();
+            let mut pages = 0;
+            loop {
+                let page = build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+                pages += 1;
+                seen.extend(page.messages.iter().map(|m| m.rowid));
+                match page.meta.next_cursor {
+                    Some(next) => window.cursor = Some(Cursor::decode(&next).unwrap()),
+                    None => break,
+                }
+            }
+            let mut expected = rowids.clone();
+            if !ascending {
+                expected.reverse();
+            }
+            assert_eq!(seen, expected, "ascending={}", ascending);
+            assert_eq!(pages, 4);
+        }
+
+        // A full last page yields one more (empty) page with no cursor
+        let window = SummaryWindow {
+            start_cocoa: 0,
+            end_cocoa: i64::MAX,
+            limit: 11,
+            offset: 0,
+            cursor: None,
+            ascending: true,
+            include_deleted: false,
+            detect_language: false,
+            with_reactions: false,
+        };
+        let page = build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        let next = Cursor::decode(page.meta.next_cursor.as_deref().unwrap()).unwrap();
+        assert_eq!(next, Cursor { date: 700 * DAY_NS + 6, rowid: *rowids.last().unwrap(), ascending: true });
+        let rest =
+            build_summary(&conn, &contacts, "+14155550001", &SummaryWindow { cursor: Some(next), ..window.clone() })
+                .unwrap();
+        assert!(rest.messages.is_empty() && rest.meta.next_cursor.is_none());
+
+        // The cursor only continues its own order, and replaces --offset
+        let reversed = SummaryWindow { cursor: Some(next), ascending: false, ..window.clone() };
+        assert!(build_summary(&conn, &contacts, "+14155550001", &reversed).is_err());
+        let offset = SummaryWindow { cursor: Some(next), offset: 2, ..window };
+        assert!(build_summary(&conn, &contacts, "+14155550001", &offset).is_err());
+    }
+
+    #[test]
+    fn test_summary_detect_language() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let texts = [
+            "I think we should leave early to avoid the traffic.",
+            "Ich komme heute etwas später nach Hause, warte nicht auf mich.",
+            "ok",
+            "Thank you so much for the birthday present, I love it!",
+        ];
+        for (i, text) in texts.iter().enumerate() {
+            insert_message(&conn, chat, alice, Some(text), 700 * DAY_NS + i as i64, false, true);
+        }
+        let photo = insert_message(&conn, chat, alice, None, 700 * DAY_NS + 9, true, true);
+        insert_attachment(&conn, photo, Some("image/jpeg"), "IMG_1.jpg");
+        let mut window = SummaryWindow {
+            start_cocoa: 0,
+            end_cocoa: i64::MAX,
+            limit: 10,
+            offset: 0,
+            cursor: None,
+            ascending: true,
+            include_deleted: false,
+            detect_language: false,
+            with_reactions: false,
+        };
+        let contacts = ContactsManager::empty();
+
+        let plain = build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        assert!(plain.language_distribution.is_none() && plain.messages.iter().all(|m| m.lang.is_none()));
+
+        window.detect_language = true;
+        let tagged = build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        let langs: Vec<_> = tagged.messages.iter().map(|m| m.lang.as_deref()).collect();
+        // The photo has no text to tag and stays out of the mix
+        assert_eq!(langs, [Some("en"), Some("de"), Some("und"), Some("en"), None]);
+        let shares = tagged.language_distribution.unwrap();
+        assert_eq!(shares.keys().collect::<Vec<_>>(), ["de", "en", "und"]);
+        assert!((shares["en"] - 0.5).abs() < 1e-9 && (shares["de"] - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_summary_with_reactions() {
+        use crate::contacts::manager::Contact;
+        use crate::db::fixtures::insert_reaction;
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let base = 700 * DAY_NS;
+        let asked = insert_message(&conn, chat, alice, Some("dinner at 7?"), base, false, true);
+        insert_message(&conn, chat, alice, Some("or 8"), base + 10, false, true);
+        // Alice loves it, I emphasize it, then Alice takes her heart back
+        insert_reaction(&conn, chat, alice, &format!("msg-{}", asked), 2000, base + 1, false);
+        insert_reaction(&conn, chat, 0, &format!("msg-{}", asked), 2004, base + 2, true);
+        insert_reaction(&conn, chat, alice, &format!("msg-{}", asked), 3000, base + 3, false);
+        insert_reaction(&conn, chat, alice, &format!("msg-{}", asked), 2003, base + 4, false);
+        let mut window = SummaryWindow {
+            start_cocoa: 0,
+            end_cocoa: i64::MAX,
+            limit: 10,
+            offset: 0,
+            cursor: None,
+            ascending: true,
+            include_deleted: false,
+            detect_language: false,
+            with_reactions: false,
+        };
+        let contacts = ContactsManager::from_contacts(vec![Contact {
+            name: "Alice".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }]);
+
+        let plain = build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        assert!(plain.messages.iter().all(|m| m.reactions.is_empty()));
+
+        window.with_reactions = true;
+        let summary = build_summary(&conn, &contacts, "+14155550001", &window).unwrap();
+        assert_eq!(summary.messages.len(), 2);
+        let json = serde_json::to_value(&summary.messages).unwrap();
+        assert_eq!(
+            json[0]["reactions"],
+            serde_json::json!([
+                {"emoji": "‼️", "from": "Me", "is_from_me": true},
+                {"emoji": "😂", "from": "Alice", "is_from_me": false},
+            ])
+        );
+        assert!(json[1].get("reactions").is_none());
+        assert_eq!(reaction_suffix(&summary.messages[0].reactions), " [‼️ Me, 😂 Alice]");
+    }
+
+    #[test]
+    fn test_summary_flags_suspect_dates() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("zero"), 0, false, true);
+        // Old imports store seconds since 2001
+        insert_message(&conn, chat, alice, Some("seconds"), 400_000_000, false, true);
+        insert_message(&conn, chat, alice, Some("nanoseconds"), 700 * DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("future"), queries::days_ago_cocoa(0) + 400 * DAY_NS, false, true);
+        let window = SummaryWindow {
+            start_cocoa: i64::MIN,
+            end_cocoa: i64::MAX,
+            limit: 10,
+            offset: 0,
+            cursor: None,
+            ascending: true,
+            include_deleted: false,
+            detect_language: false,
+            with_reactions: false,
+        };
+
+        let summary = build_summary(&conn, &ContactsManager::empty(), "+14155550001", &window).unwrap();
+        let dates: Vec<(&str, Option<&str>, bool)> = summary
+            .messages
+            .iter()
+            .map(|m| (m.text.as_str(), m.date.as_deref(), m.date_suspect))
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                ("zero", None, true),
+                ("seconds", Some("2013-09-04T15:06:40+00:00"), false),
+                ("nanoseconds", Some(helpers::cocoa_to_iso(700 * DAY_NS).as_str()), false),
+                ("future", None, true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Watch command: stream new incoming messages as they arrive.
+//!
+//! Polls Messages.db for rows newer than the last seen ROWID. With `--notify`
+//! each new message also posts a desktop notification, rate-limited per
+//! conversation so busy group chats don't cause a storm. Muted
+//! conversations still stream but never notify (unless --include-muted);
+//! the mute list is re-read each poll so mutes and expiries apply live.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - A failed notification is an output::warn too
+//! - 10/16/2026 - An unreadable mutes.json is reported through output::warn (flushed after each poll)
+//! - 10/16/2026 - --json events go through OutputControls::line, so --redact, --fields and --max-text-chars apply
+//! - 10/16/2026 - Events carry is_known_sender; --known-only / --unknown-only skip the other senders
+//! - 10/16/2026 - Events carry the attachment placeholder; text output and notifications show it for attachment-only messages
+//! - 10/16/2026 - --notify skips muted conversations (--include-muted)
+//! - 10/16/2026 - Typed WatchEvent output for `schema`
+//! - 10/16/2026 - Initial watch command with --notify and --filter-contacts
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::applescript;
+use crate::contacts::manager::ContactsManager;
+use crate::db::{connection, helpers};
+use crate::mutes::{Mute, MuteStore};
+use crate::notify::{self, ContactFilter, RateLimiter};
+use crate::output::{self, OutputControls};
+use crate::senders::{SenderFilter, SenderMode};
+
+/// Max rows fetched per poll.
+const BATCH_LIMIT: u32 = 200;
+
+/// Notification settings from CLI flags.
+#[derive(Debug, Clone)]
+pub struct NotifyOptions {
+    /// Minimum seconds between notifications for one conversation
+    pub window_secs: u64,
+    /// Only notify for these contacts (names or handles); empty = everyone
+    pub filter_contacts: Vec<String>,
+    /// Notify for muted conversations too
+    pub include_muted: bool,
+}
+
+/// One NDJSON line emitted per new message with --json (shaped like any
+/// other output: --fields, --max-text-chars and --redact apply).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WatchEvent {
+    pub rowid: i64,
+    pub date: String,
+    pub sender_handle: Option<String>,
+    pub sender_name: Option<String>,
+    pub text: Option<String>,
+    /// Attachment placeholder ("[photo]", ...) when the message has no text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    pub chat_identifier: Option<String>,
+    pub is_group_chat: bool,
+    pub is_known_sender: bool,
+}
+
+/// Watch for new incoming messages until interrupted, keeping `sender`'s senders.
+pub fn watch(
+    interval_secs: u64,
+    notify_opts: Option<&NotifyOptions>,
+    sender: SenderMode,
+    output: &OutputControls,
+    contacts: &ContactsManager,
+) -> Result<()> {
+    let json_out = output.json;
+    let conn = connection::open_db()?;
+    let mut last_rowid = helpers::query_max_message_rowid(&conn)?;
+    let senders = SenderFilter::load(&conn, contacts, sender)?;
+
+    let mut limiter = notify_opts.map(|o| RateLimiter::new(Duration::from_secs(o.window_secs)));
+    let filter = notify_opts.map(|o| ContactFilter::new(&o.filter_contacts, contacts));
+
+    if !json_out {
+        eprintln!("Watching for new messages (Ctrl-C to stop)...");
+    }
+
+    loop {
+        let incoming = helpers::query_incoming_since(&conn, last_rowid, BATCH_LIMIT)
+            .context("Failed to poll for new messages")?;
+        let mutes = match notify_opts {
+            Some(o) if !o.include_muted && !incoming.is_empty() => active_mutes(),
+            _ => Vec::new(),
+        };
+
+        for msg in &incoming {
+            last_rowid = last_rowid.max(msg.rowid);
+            let is_known_sender = senders.is_known(msg.sender_handle.as_deref(), msg.chat_identifier.as_deref());
+            if !senders.keeps(is_known_sender) {
+                continue;
+            }
+
+            let sender_name = msg
+                .sender_handle
+                .as_deref()
+                .and_then(|h| contacts.find_by_phone(h))
+                .map(|c| c.name.clone());
+            let sender = sender_name
+                .clone()
+                .or_else(|| msg.sender_handle.clone())
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            if json_out {
+                let event = WatchEvent {
+                    rowid: msg.rowid,
+                    date: msg.date.clone(),
+                    sender_handle: msg.sender_handle.clone(),
+                    sender_name,
+                    text: msg.text.clone(),
+                    placeholder: msg.placeholder.clone(),
+                    chat_identifier: msg.chat_identifier.clone(),
+                    is_group_chat: msg.is_group_chat,
+                    is_known_sender,
+                };
+                println!("{}", output.line(&event));
+            } else {
+                println!("[{}] {}: {}", msg.date, sender, msg.text.as_deref().or(msg.placeholder.as_deref()).unwrap_or("[media]"));
+            }
+
+            if let (Some(limiter), Some(filter)) = (limiter.as_mut(), filter.as_ref()) {
+                if !filter.matches(msg.sender_handle.as_deref()) {
+                    continue;
+                }
+                let key = msg
+                    .chat_identifier
+                    .as_deref()
+                    .or(msg.sender_handle.as_deref())
+                    .unwrap_or("unknown");
+                if mutes.iter().any(|m| m.matches(key)) {
+                    continue;
+                }
+                if !limiter.allow(key, Instant::now()) {
+                    continue;
+                }
+                let group_name = if msg.is_group_chat {
+                    msg.display_name.as_deref().or(Some("Group chat"))
+                } else {
+                    None
+                };
+                let n = notify::build_notification(
+                    &sender,
+                    group_name,
+                    msg.text.as_deref().or(msg.placeholder.as_deref()),
+                    notify::PREVIEW_CHARS,
+                );
+                if let Err(e) = applescript::display_notification(&n.title, n.subtitle.as_deref(), &n.body) {
+                    output::warn(format!("notification failed: {:#}", e));
+                }
+            }
+        }
+
+        // Watch never finishes, so warnings print as each poll raises them
+        output::flush_warnings();
+        std::thread::sleep(Duration::from_secs(interval_secs.max(1)));
+    }
+}
+
+/// Mutes in effect now; an unreadable mutes.json mutes nothing (with a warning).
+fn active_mutes() -> Vec<Mute> {
+    MuteStore::open_default().active(Local::now()).unwrap_or_else(|e| {
+        output::warn(format!("ignoring mutes.json: {:#}", e));
+        Vec::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Redactor;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_events_redacted() {
+        let output = OutputControls { json: true, redactor: Some(Arc::new(Redactor::new(None))), ..Default::default() };
+        let event = WatchEvent {
+            rowid: 7,
+            date: "2026-10-16T09:00:00".to_string(),
+            sender_handle: Some("+14155551234".to_string()),
+            sender_name: None,
+            text: Some("call me on 415-555-9876".to_string()),
+            placeholder: None,
+            chat_identifier: Some("+14155551234".to_string()),
+            is_group_chat: false,
+            is_known_sender: false,
+        };
+        let line = output.line(&event);
+        assert!(!line.contains('\n') && !line.contains("555"), "{}", line);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["sender_handle"], "Contact-1");
+        assert_eq!(value["chat_identifier"], "Contact-1");
+        assert_eq!(value["text"], "call me on ###-###-####");
+        assert_eq!(value["rowid"], 7);
+    }
+}
@@ -0,0 +1,524 @@
+//! User configuration (~/.wolfies-imessage/config.toml).
+//!
+//! Precedence per value: CLI flag, then environment variable (where one
+//! exists), then config.toml, then the built-in default. The merged result
+//! (`Settings`) records where each value came from for `config show`, and is
+//! installed once at startup so path and send helpers can read it.
+//!
+//! ```toml
+//! db_path = "~/Library/Messages/chat.db"
+//! contacts_path = "~/LIFE-PLANNER/Texting/config/contacts.json"
+//! country_code = "1"
+//! my_handles = ["+14155550000", "me@icloud.com"]
+//! pinned = ["Mom", "Sarah", "Work Group"]  # `quick`, in this order
+//!
+//! [output]
+//! minimal = true
+//! max_text_chars = 160
+//! fields = ["text", "date", "phone"]
+//!
+//! [daemon]
+//! socket = "~/.wolfies-imessage/daemon.sock"
+//! auto_start = true
+//!
+//! [send]
+//! dry_run = false
+//! max_message_chars = 2000     # default 10000; 0 = no limit
+//! max_per_minute = 10            # 0 = no limit
+//! max_per_recipient = 3          # per recipient_window_minutes; 0 = no limit
+//! recipient_window_minutes = 5
+//! ```
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Path comes from the data file registry (data_files::CONFIG)
+//! - 10/16/2026 - send.max_message_chars defaults to 10000 (0 = no limit)
+//! - 10/16/2026 - Added pinned (contacts and groups shown by `quick`)
+//! - 10/16/2026 - Send rate limits ([send] max_per_minute, max_per_recipient, recipient_window_minutes) and --force-send
+//! - 10/16/2026 - db_path accepts a --db-path flag override
+//! - 10/16/2026 - Added my_handles (notes-to-self detection)
+//! - 10/16/2026 - Source derives JsonSchema for `schema`
+//! - 10/16/2026 - Initial config.toml loading, merge with flags, and provenance
+
+use anyhow::{anyhow, bail, Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::commands::rag::SOCKET_ENV;
+use crate::contacts::manager::{builtin_contacts_path, CONTACTS_PATH_ENV};
+use crate::daemon_client;
+use crate::db::connection;
+use crate::data_files;
+
+/// Env var pointing at an alternate config file.
+pub const CONFIG_ENV: &str = "WOLFIES_CONFIG";
+
+/// Default send.max_message_chars.
+pub const DEFAULT_MAX_MESSAGE_CHARS: u32 = 10_000;
+
+/// Config file: $WOLFIES_CONFIG, else config.toml in the data directory.
+pub fn config_path() -> PathBuf {
+    match std::env::var(CONFIG_ENV) {
+        Ok(path) if !path.is_empty() => expand(&path),
+        _ => data_files::CONFIG.path(),
+    }
+}
+
+fn expand(path: &str) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(path).to_string())
+}
+
+/// Values set in config.toml (`None` = not set).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileConfig {
+    pub json: Option<bool>,
+    pub compact: Option<bool>,
+    pub minimal: Option<bool>,
+    pub fields: Option<String>,
+    pub max_text_chars: Option<u32>,
+    pub db_path: Option<PathBuf>,
+    pub contacts_path: Option<PathBuf>,
+    pub country_code: Option<String>,
+    pub my_handles: Option<Vec<String>>,
+    pub pinned: Option<Vec<String>>,
+    pub daemon_socket: Option<PathBuf>,
+    pub auto_start_daemon: Option<bool>,
+    pub send_dry_run: Option<bool>,
+    pub send_max_message_chars: Option<u32>,
+    pub send_max_per_minute: Option<u32>,
+    pub send_max_per_recipient: Option<u32>,
+    pub send_recipient_window_minutes: Option<u32>,
+}
+
+impl FileConfig {
+    /// Parse config.toml text. Unknown keys are returned as warnings;
+    /// malformed TOML or a wrongly typed value is an error.
+    pub fn parse(text: &str) -> Result<(Self, Vec<String>)> {
+        let table: toml::Table = text.parse().context("Invalid TOML")?;
+        let mut config = FileConfig::default();
+        let mut warnings = Vec::new();
+
+        for (key, value) in &table {
+            match key.as_str() {
+                "db_path" => config.db_path = Some(expand(string(key, value)?)),
+                "contacts_path" => config.contacts_path = Some(expand(string(key, value)?)),
+                "country_code" => config.country_code = Some(country_code(key, value)?),
+                "my_handles" => config.my_handles = Some(strings(key, value)?),
+                "pinned" => config.pinned = Some(strings(key, value)?),
+                "output" => {
+                    for (name, value) in section(key, value)? {
+                        let key = &format!("output.{}", name);
+                        match name.as_str() {
+                            "json" => config.json = Some(boolean(key, value)?),
+                            "compact" => config.compact = Some(boolean(key, value)?),
+                            "minimal" => config.minimal = Some(boolean(key, value)?),
+                            "fields" => config.fields = Some(fields(key, value)?),
+                            "max_text_chars" => config.max_text_chars = Some(count(key, value)?),
+                            _ => warnings.push(format!("unknown key '{}'", key)),
+                        }
+                    }
+                }
+                "daemon" => {
+                    for (name, value) in section(key, value)? {
+                        let key = &format!("daemon.{}", name);
+                        match name.as_str() {
+                            "socket" => config.daemon_socket = Some(expand(string(key, value)?)),
+                            "auto_start" => config.auto_start_daemon = Some(boolean(key, value)?),
+                            _ => warnings.push(format!("unknown key '{}'", key)),
+                        }
+                    }
+                }
+                "send" => {
+                    for (name, value) in section(key, value)? {
+                        let key = &format!("send.{}", name);
+                        match name.as_str() {
+                            "dry_run" => config.send_dry_run = Some(boolean(key, value)?),
+                            "max_message_chars" => {
+                                config.send_max_message_chars = Some(count(key, value)?)
+                            }
+                            "max_per_minute" => config.send_max_per_minute = Some(count(key, value)?),
+                            "max_per_recipient" => {
+                                config.send_max_per_recipient = Some(count(key, value)?)
+                            }
+                            "recipient_window_minutes" => {
+                                config.send_recipient_window_minutes = Some(count(key, value)?)
+                            }
+                            _ => warnings.push(format!("unknown key '{}'", key)),
+                        }
+                    }
+                }
+                _ => warnings.push(format!("unknown key '{}'", key)),
+            }
+        }
+        Ok((config, warnings))
+    }
+
+    /// Load a config file; a missing file means nothing is set.
+    pub fn load(path: &Path) -> Result<(Self, Vec<String>)> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("Failed to parse {:?}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok((Self::default(), Vec::new())),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", path)),
+        }
+    }
+}
+
+fn type_error(key: &str, expected: &str, value: &toml::Value) -> anyhow::Error {
+    anyhow!("'{}' must be {}, found {}", key, expected, value.type_str())
+}
+
+fn section<'a>(key: &str, value: &'a toml::Value) -> Result<&'a toml::Table> {
+    value.as_table().ok_or_else(|| type_error(key, "a table", value))
+}
+
+fn string<'a>(key: &str, value: &'a toml::Value) -> Result<&'a str> {
+    value.as_str().ok_or_else(|| type_error(key, "a string", value))
+}
+
+fn boolean(key: &str, value: &toml::Value) -> Result<bool> {
+    value.as_bool().ok_or_else(|| type_error(key, "true or false", value))
+}
+
+fn count(key: &str, value: &toml::Value) -> Result<u32> {
+    value
+        .as_integer()
+        .and_then(|n| u32::try_from(n).ok())
+        .ok_or_else(|| type_error(key, "a non-negative integer", value))
+}
+
+/// `fields` as "a,b" or ["a", "b"]; stored comma-separated like --fields.
+fn fields(key: &str, value: &toml::Value) -> Result<String> {
+    match value.as_array() {
+        Some(items) => items
+            .iter()
+            .map(|item| string(key, item))
+            .collect::<Result<Vec<_>>>()
+            .map(|names| names.join(",")),
+        None => string(key, value).map(str::to_string),
+    }
+}
+
+/// A list of strings; a single string is a one-item list.
+fn strings(key: &str, value: &toml::Value) -> Result<Vec<String>> {
+    match value.as_array() {
+        Some(items) => items.iter().map(|item| string(key, item).map(str::to_string)).collect(),
+        None => string(key, value).map(|s| vec![s.to_string()]),
+    }
+}
+
+/// Calling code without "+", e.g. "1" or "44".
+fn country_code(key: &str, value: &toml::Value) -> Result<String> {
+    let code = match value {
+        toml::Value::Integer(n) => n.to_string(),
+        _ => string(key, value)?.trim_start_matches('+').to_string(),
+    };
+    if code.is_empty() || code.len() > 3 || !code.chars().all(|c| c.is_ascii_digit()) {
+        bail!("'{}' must be a 1-3 digit calling code, found {:?}", key, code);
+    }
+    Ok(code)
+}
+
+/// Values given on the command line (`None` = flag not passed).
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub json: Option<bool>,
+    pub compact: Option<bool>,
+    pub minimal: Option<bool>,
+    pub fields: Option<String>,
+    pub max_text_chars: Option<u32>,
+    pub db_path: Option<PathBuf>,
+    pub daemon_socket: Option<PathBuf>,
+    pub auto_start_daemon: Option<bool>,
+    pub send_dry_run: Option<bool>,
+    pub send_force: Option<bool>,
+}
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Default,
+    Config,
+    Env,
+    Flag,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Default => "default",
+            Source::Config => "config",
+            Source::Env => "env",
+            Source::Flag => "flag",
+        }
+    }
+}
+
+/// An effective value and its provenance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+/// First of flag / config / default that is set.
+fn pick<T>(flag: Option<T>, config: Option<T>, default: T) -> Setting<T> {
+    match (flag, config) {
+        (Some(value), _) => Setting { value, source: Source::Flag },
+        (None, Some(value)) => Setting { value, source: Source::Config },
+        (None, None) => Setting { value: default, source: Source::Default },
+    }
+}
+
+/// Like `pick`, with an environment variable between flag and config.
+fn pick_path(
+    flag: Option<PathBuf>,
+    env_var: Option<&str>,
+    config: Option<PathBuf>,
+    default: impl FnOnce() -> PathBuf,
+) -> Setting<PathBuf> {
+    if let Some(value) = flag {
+        return Setting { value, source: Source::Flag };
+    }
+    if let Some(value) = env_var.and_then(|v| std::env::var(v).ok()).filter(|v| !v.is_empty()) {
+        return Setting { value: expand(&value), source: Source::Env };
+    }
+    match config {
+        Some(value) => Setting { value, source: Source::Config },
+        None => Setting { value: default(), source: Source::Default },
+    }
+}
+
+/// Effective configuration after merging flags, env, config.toml and defaults.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub config_path: PathBuf,
+    pub config_found: bool,
+    pub json: Setting<bool>,
+    pub compact: Setting<bool>,
+    pub minimal: Setting<bool>,
+    pub fields: Setting<Option<String>>,
+    pub max_text_chars: Setting<Option<u32>>,
+    pub db_path: Setting<PathBuf>,
+    pub contacts_path: Setting<PathBuf>,
+    pub country_code: Setting<Option<String>>,
+    /// Empty = detect from the accounts in Messages.db
+    pub my_handles: Setting<Vec<String>>,
+    /// Contacts and groups for `quick`, in display order
+    pub pinned: Setting<Vec<String>>,
+    pub daemon_socket: Setting<PathBuf>,
+    pub auto_start_daemon: Setting<bool>,
+    pub send_dry_run: Setting<bool>,
+    pub send_max_message_chars: Setting<u32>,
+    pub send_max_per_minute: Setting<u32>,
+    pub send_max_per_recipient: Setting<u32>,
+    pub send_recipient_window_minutes: Setting<u32>,
+    /// --force-send: skip the rate limit (flag only)
+    pub send_force: Setting<bool>,
+}
+
+impl Settings {
+    pub fn resolve(config_path: &Path, file: &FileConfig, flags: &Overrides) -> Self {
+        let file = file.clone();
+        let flags = flags.clone();
+        Settings {
+            config_path: config_path.to_path_buf(),
+            config_found: config_path.exists(),
+            json: pick(flags.json, file.json, false),
+            compact: pick(flags.compact, file.compact, false),
+            minimal: pick(flags.minimal, file.minimal, false),
+            fields: pick(flags.fields.map(Some), file.fields.map(Some), None),
+            max_text_chars: pick(flags.max_text_chars.map(Some), file.max_text_chars.map(Some), None),
+            db_path: pick_path(flags.db_path, None, file.db_path, connection::default_db_path),
+            contacts_path: pick_path(
+                None,
+                Some(CONTACTS_PATH_ENV),
+                file.contacts_path,
+                builtin_contacts_path,
+            ),
+            country_code: pick(None, file.country_code.map(Some), None),
+            my_handles: pick(None, file.my_handles, Vec::new()),
+            pinned: pick(None, file.pinned, Vec::new()),
+            daemon_socket: pick_path(
+                flags.daemon_socket,
+                Some(SOCKET_ENV),
+                file.daemon_socket,
+                daemon_client::default_socket_path,
+            ),
+            auto_start_daemon: pick(flags.auto_start_daemon, file.auto_start_daemon, false),
+            send_dry_run: pick(flags.send_dry_run, file.send_dry_run, false),
+            send_max_message_chars: pick(None, file.send_max_message_chars, DEFAULT_MAX_MESSAGE_CHARS),
+            send_max_per_minute: pick(None, file.send_max_per_minute, 10),
+            send_max_per_recipient: pick(None, file.send_max_per_recipient, 3),
+            send_recipient_window_minutes: pick(None, file.send_recipient_window_minutes, 5),
+            send_force: pick(flags.send_force, None, false),
+        }
+    }
+
+    /// (key, value, source) per setting, in config-file order.
+    pub fn entries(&self) -> Vec<(&'static str, Value, Source)> {
+        fn entry<T: Serialize>(key: &'static str, s: &Setting<T>) -> (&'static str, Value, Source) {
+            (key, json!(s.value), s.source)
+        }
+        vec![
+            entry("db_path", &self.db_path),
+            entry("contacts_path", &self.contacts_path),
+            entry("country_code", &self.country_code),
+            entry("my_handles", &self.my_handles),
+            entry("pinned", &self.pinned),
+            entry("output.json", &self.json),
+            entry("output.compact", &self.compact),
+            entry("output.minimal", &self.minimal),
+            entry("output.fields", &self.fields),
+            entry("output.max_text_chars", &self.max_text_chars),
+            entry("daemon.socket", &self.daemon_socket),
+            entry("daemon.auto_start", &self.auto_start_daemon),
+            entry("send.dry_run", &self.send_dry_run),
+            entry("send.max_message_chars", &self.send_max_message_chars),
+            entry("send.max_per_minute", &self.send_max_per_minute),
+            entry("send.max_per_recipient", &self.send_max_per_recipient),
+            entry("send.recipient_window_minutes", &self.send_recipient_window_minutes),
+        ]
+    }
+}
+
+static ACTIVE: OnceLock<Settings> = OnceLock::new();
+
+/// Make `settings` the process-wide configuration (first call wins).
+pub fn install(settings: Settings) {
+    let _ = ACTIVE.set(settings);
+}
+
+/// The installed configuration; built-in defaults (plus env) if none was.
+pub fn active() -> &'static Settings {
+    ACTIVE.get_or_init(|| Settings::resolve(&config_path(), &FileConfig::default(), &Overrides::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: &str = r#"
+db_path = "/data/chat.db"
+contacts_path = "/data/contacts.json"
+country_code = "+44"
+my_handles = ["+447700900000", "me@icloud.com"]
+pinned = ["Mom", "Work Group"]
+
+[output]
+minimal = true
+max_text_chars = 160
+fields = ["text", "date"]
+
+[daemon]
+socket = "/tmp/rag.sock"
+auto_start = true
+
+[send]
+dry_run = true
+max_message_chars = 500
+max_per_minute = 0
+max_per_recipient = 5
+recipient_window_minutes = 10
+"#;
+
+    #[test]
+    fn test_parse_full_config() {
+        let (config, warnings) = FileConfig::parse(FULL).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config.db_path, Some(PathBuf::from("/data/chat.db")));
+        assert_eq!(config.contacts_path, Some(PathBuf::from("/data/contacts.json")));
+        assert_eq!(config.country_code.as_deref(), Some("44"));
+        assert_eq!(
+            config.my_handles,
+            Some(vec!["+447700900000".to_string(), "me@icloud.com".to_string()])
+        );
+        assert_eq!(config.pinned, Some(vec!["Mom".to_string(), "Work Group".to_string()]));
+        assert_eq!(config.minimal, Some(true));
+        assert_eq!(config.json, None);
+        assert_eq!(config.max_text_chars, Some(160));
+        assert_eq!(config.fields.as_deref(), Some("text,date"));
+        assert_eq!(config.daemon_socket, Some(PathBuf::from("/tmp/rag.sock")));
+        assert_eq!(config.auto_start_daemon, Some(true));
+        assert_eq!(config.send_dry_run, Some(true));
+        assert_eq!(config.send_max_message_chars, Some(500));
+        assert_eq!(config.send_max_per_minute, Some(0));
+        assert_eq!(config.send_max_per_recipient, Some(5));
+        assert_eq!(config.send_recipient_window_minutes, Some(10));
+    }
+
+    #[test]
+    fn test_unknown_keys_warn() {
+        let text = "colour = \"red\"\n[output]\njson = true\nmax_chars = 10\n[extras]\nx = 1\n";
+        let (config, warnings) = FileConfig::parse(text).unwrap();
+        assert_eq!(config.json, Some(true));
+        assert_eq!(
+            warnings,
+            vec!["unknown key 'colour'", "unknown key 'extras'", "unknown key 'output.max_chars'"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_error() {
+        let err = FileConfig::parse("[output]\njson = \"yes\"\n").unwrap_err();
+        assert!(err.to_string().contains("'output.json' must be true or false"));
+
+        let err = FileConfig::parse("[output]\nmax_text_chars = -5\n").unwrap_err();
+        assert!(err.to_string().contains("non-negative integer"));
+
+        assert!(FileConfig::parse("output = 3\n").is_err());
+        assert!(FileConfig::parse("country_code = \"uk\"\n").is_err());
+        assert!(FileConfig::parse("my_handles = [1, 2]\n").is_err());
+        assert!(FileConfig::parse("[output\njson = true\n").is_err());
+    }
+
+    #[test]
+    fn test_precedence_flag_config_default() {
+        let (file, _) = FileConfig::parse(FULL).unwrap();
+        let flags = Overrides {
+            max_text_chars: Some(40),
+            db_path: Some(PathBuf::from("/backups/chat.db")),
+            daemon_socket: Some(PathBuf::from("/tmp/flag.sock")),
+            ..Overrides::default()
+        };
+        let settings = Settings::resolve(Path::new("/nonexistent/config.toml"), &file, &flags);
+
+        assert!(!settings.config_found);
+        assert_eq!(settings.max_text_chars, Setting { value: Some(40), source: Source::Flag });
+        assert_eq!(settings.minimal, Setting { value: true, source: Source::Config });
+        assert_eq!(settings.json, Setting { value: false, source: Source::Default });
+        assert_eq!(settings.daemon_socket.value, PathBuf::from("/tmp/flag.sock"));
+        assert_eq!(settings.daemon_socket.source, Source::Flag);
+        assert_eq!(settings.db_path.value, PathBuf::from("/backups/chat.db"));
+        assert_eq!(settings.db_path.source, Source::Flag);
+
+        let defaults = Settings::resolve(Path::new("/x"), &FileConfig::default(), &Overrides::default());
+        assert_eq!(defaults.db_path.value, connection::default_db_path());
+        assert_eq!(defaults.db_path.source, Source::Default);
+        assert_eq!(defaults.auto_start_daemon.source, Source::Default);
+        assert_eq!(defaults.country_code.value, None);
+        assert!(defaults.my_handles.value.is_empty());
+        assert_eq!(defaults.send_max_per_minute, Setting { value: 10, source: Source::Default });
+        assert_eq!(defaults.send_max_message_chars.value, DEFAULT_MAX_MESSAGE_CHARS);
+        assert_eq!(settings.send_max_per_recipient, Setting { value: 5, source: Source::Config });
+        assert_eq!(settings.my_handles.source, Source::Config);
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config, warnings) = FileConfig::load(&dir.path().join("config.toml")).unwrap();
+        assert_eq!(config, FileConfig::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_entries_cover_every_setting() {
+        let settings = Settings::resolve(Path::new("/x"), &FileConfig::default(), &Overrides::default());
+        let keys: Vec<_> = settings.entries().iter().map(|(k, _, _)| *k).collect();
+        assert_eq!(keys.len(), 17);
+        assert!(keys.contains(&"send.max_message_chars"));
+    }
+}
@@ -0,0 +1,331 @@
+//! contacts.json safety net: atomic saves, timestamped backups, and diffs.
+//!
+//! Every mutation goes through `save_contacts`, which copies the current file
+//! into the backup directory (keeping the newest `BACKUP_KEEP`) before
+//! replacing it atomically. Entries are matched by normalized phone
+//! (`handle_key`) when diffing.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Path comes from the data file registry (data_files::CONTACTS_BACKUPS)
+//! - 10/16/2026 - Initial backups, atomic saves, history, diff, and restore
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::manager::{Contact, ContactsManager};
+use crate::db::helpers::handle_key;
+use crate::data_files;
+use crate::storage;
+
+/// Backups kept per directory; older ones are pruned after each backup.
+pub const BACKUP_KEEP: usize = 10;
+
+const BACKUP_PREFIX: &str = "contacts-";
+
+/// Backup directory (~/.wolfies-imessage/contacts-backups).
+pub fn backup_dir() -> PathBuf {
+    data_files::CONTACTS_BACKUPS.path()
+}
+
+/// One backup file.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BackupInfo {
+    /// File name, accepted by `contacts diff` and `contacts restore`.
+    pub name: String,
+    pub path: String,
+    pub created: String,
+    /// None when the file doesn't parse as contacts.json.
+    pub contact_count: Option<usize>,
+}
+
+/// Contacts added, removed, or changed going from a backup to the current file.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ContactsDiff {
+    pub added: Vec<Contact>,
+    pub removed: Vec<Contact>,
+    pub changed: Vec<ContactChange>,
+}
+
+impl ContactsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A contact present in both files with differing fields.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ContactChange {
+    /// Field names that differ ("name", "phone", "relationship_type", "notes", "handles").
+    pub fields: Vec<String>,
+    pub before: Contact,
+    pub after: Contact,
+}
+
+/// Result of `contacts restore`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RestoreResult {
+    pub restored_from: String,
+    /// Backup of the file that was replaced, if there was one.
+    pub previous_backup: Option<String>,
+    pub contact_count: usize,
+}
+
+/// Copy `contacts_path` into `dir` as a timestamped backup and prune old ones.
+///
+/// Returns None when there is no file to back up yet.
+pub fn backup(contacts_path: &Path, dir: &Path, keep: usize) -> Result<Option<PathBuf>> {
+    if !contacts_path.exists() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create directory {:?}", dir))?;
+
+    // Names must sort chronologically, so wait out a same-millisecond collision
+    let dest = loop {
+        let name = format!("{}{}.json", BACKUP_PREFIX, Local::now().format("%Y%m%d-%H%M%S-%3f"));
+        let dest = dir.join(name);
+        if !dest.exists() {
+            break dest;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    };
+    std::fs::copy(contacts_path, &dest)
+        .with_context(|| format!("Failed to back up {:?} to {:?}", contacts_path, dest))?;
+
+    prune(dir, keep)?;
+    Ok(Some(dest))
+}
+
+/// Back up the current file, then atomically replace it with `contacts`.
+pub fn save_contacts(contacts_path: &Path, contacts: &[Contact], dir: &Path, keep: usize) -> Result<()> {
+    backup(contacts_path, dir, keep)?;
+    storage::write_json_atomic(contacts_path, &contacts)
+}
+
+/// Backup files in `dir`, newest first.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>> {
+    let mut paths = backup_paths(dir)?;
+    paths.reverse();
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let created = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(|t| DateTime::<Local>::from(t).to_rfc3339())
+                .unwrap_or_default();
+            BackupInfo {
+                name: file_name(&path),
+                contact_count: ContactsManager::load(&path).ok().map(|m| m.all().len()),
+                path: path.to_string_lossy().into_owned(),
+                created,
+            }
+        })
+        .collect())
+}
+
+/// Resolve a `contacts diff`/`restore` argument: a file name in `dir`, or a path.
+pub fn resolve_backup(dir: &Path, name: &str) -> Result<PathBuf> {
+    let in_dir = dir.join(name);
+    if in_dir.is_file() {
+        return Ok(in_dir);
+    }
+    let as_path = PathBuf::from(name);
+    if as_path.is_file() {
+        return Ok(as_path);
+    }
+    bail!("Backup not found: {} (see `contacts history`)", name)
+}
+
+/// Compare entries by normalized phone: what changed going from `old` to `new`.
+pub fn diff_contacts(old: &[Contact], new: &[Contact]) -> ContactsDiff {
+    let old_by_key: HashMap<String, &Contact> = old.iter().map(|c| (handle_key(&c.phone), c)).collect();
+    let new_keys: HashSet<String> = new.iter().map(|c| handle_key(&c.phone)).collect();
+
+    let mut diff = ContactsDiff::default();
+    for contact in new {
+        match old_by_key.get(&handle_key(&contact.phone)) {
+            None => diff.added.push(contact.clone()),
+            Some(before) => {
+                let fields = changed_fields(before, contact);
+                if !fields.is_empty() {
+                    diff.changed.push(ContactChange {
+                        fields,
+                        before: (*before).clone(),
+                        after: contact.clone(),
+                    });
+                }
+            }
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|c| !new_keys.contains(&handle_key(&c.phone)))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// Restore `backup_path` over `contacts_path`, backing up the current file first.
+pub fn restore(contacts_path: &Path, backup_path: &Path, dir: &Path, keep: usize) -> Result<RestoreResult> {
+    let contents = std::fs::read(backup_path)
+        .with_context(|| format!("Failed to read backup {:?}", backup_path))?;
+    let contact_count = ContactsManager::load(backup_path)
+        .with_context(|| format!("{:?} is not a valid contacts file", backup_path))?
+        .all()
+        .len();
+
+    // Read before backing up: with keep = 1 the new backup can prune the source
+    let previous_backup = backup(contacts_path, dir, keep)?;
+    storage::write_atomic(contacts_path, &contents)?;
+
+    Ok(RestoreResult {
+        restored_from: backup_path.to_string_lossy().into_owned(),
+        previous_backup: previous_backup.map(|p| p.to_string_lossy().into_owned()),
+        contact_count,
+    })
+}
+
+fn changed_fields(a: &Contact, b: &Contact) -> Vec<String> {
+    let mut fields = Vec::new();
+    if a.name != b.name {
+        fields.push("name");
+    }
+    if a.phone != b.phone {
+        fields.push("phone");
+    }
+    if a.relationship_type != b.relationship_type {
+        fields.push("relationship_type");
+    }
+    if a.notes != b.notes {
+        fields.push("notes");
+    }
+    if a.handles != b.handles {
+        fields.push("handles");
+    }
+    fields.into_iter().map(String::from).collect()
+}
+
+/// Backup files in `dir`, oldest first (timestamped names sort chronologically).
+fn backup_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", dir)),
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            let name = file_name(p);
+            name.starts_with(BACKUP_PREFIX) && name.ends_with(".json")
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn prune(dir: &Path, keep: usize) -> Result<()> {
+    let paths = backup_paths(dir)?;
+    for old in &paths[..paths.len().saturating_sub(keep)] {
+        std::fs::remove_file(old).with_context(|| format!("Failed to remove old backup {:?}", old))?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, phone: &str, relationship: &str) -> Contact {
+        Contact {
+            name: name.to_string(),
+            phone: phone.to_string(),
+            relationship_type: relationship.to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_save_backs_up_atomically_and_prunes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("contacts.json");
+        let dir = tmp.path().join("backups");
+
+        // First save: nothing to back up yet
+        save_contacts(&path, &[contact("Ann", "+14155550001", "friend")], &dir, 2).unwrap();
+        assert!(list_backups(&dir).unwrap().is_empty());
+
+        for name in ["Bea", "Cal", "Dee"] {
+            let mut all = ContactsManager::load(&path).unwrap().all().to_vec();
+            all.push(contact(name, "+14155550009", "other"));
+            save_contacts(&path, &all, &dir, 2).unwrap();
+        }
+
+        let backups = list_backups(&dir).unwrap();
+        assert_eq!(backups.len(), 2);
+        // Newest first: the file as it was before the last save
+        assert_eq!(backups[0].contact_count, Some(3));
+        assert_eq!(backups[1].contact_count, Some(2));
+        assert_eq!(ContactsManager::load(&path).unwrap().all().len(), 4);
+
+        // Only the contacts file and backups dir: no temp files left behind
+        let leftovers: Vec<String> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|n| n != "contacts.json" && n != "backups")
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+    }
+
+    #[test]
+    fn test_diff_matches_by_normalized_phone() {
+        let old = vec![
+            contact("Ann", "+14155550001", "friend"),
+            contact("Bob", "+14155550002", "work"),
+            contact("Cy", "+14155550003", "family"),
+        ];
+        let new = vec![
+            // Same number reformatted, relationship changed
+            contact("Ann", "(415) 555-0001", "family"),
+            contact("Bob", "+14155550002", "work"),
+            contact("Dee", "+14155550004", "friend"),
+        ];
+        let diff = diff_contacts(&old, &new);
+        assert_eq!(diff.added.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["Dee"]);
+        assert_eq!(diff.removed.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["Cy"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].fields, ["phone", "relationship_type"]);
+        assert_eq!(diff.changed[0].before.relationship_type, "friend");
+
+        assert!(diff_contacts(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_restore_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("contacts.json");
+        let dir = tmp.path().join("backups");
+
+        save_contacts(&path, &[contact("Ann", "+14155550001", "friend")], &dir, 5).unwrap();
+        save_contacts(&path, &[], &dir, 5).unwrap();
+        let backups = list_backups(&dir).unwrap();
+        let source = resolve_backup(&dir, &backups[0].name).unwrap();
+
+        let result = restore(&path, &source, &dir, 5).unwrap();
+        assert_eq!(result.contact_count, 1);
+        assert!(result.previous_backup.is_some());
+        assert_eq!(ContactsManager::load(&path).unwrap().all()[0].name, "Ann");
+        assert_eq!(list_backups(&dir).unwrap().len(), 2);
+
+        assert!(resolve_backup(&dir, "contacts-missing.json").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Sender classification for unknown handles.
+//!
+//! Separates 2FA codes, delivery bots and marketing senders from people,
+//! using the handle's shape (short codes, sender IDs, toll-free numbers,
+//! no-reply addresses) plus two narrow content checks: opt-out instructions
+//! and one-time-code wording. Ordinary texts full of numbers or repeated
+//! "ok"s stay personal.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Narrowed looks_automated to opt-out and one-time-code wording (dropped digit-ratio and repeated-text checks, which caught people); added NoReply emails
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - Initial classifier (handle kind + looks_automated)
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Recent incoming texts inspected per sender.
+pub const SAMPLE_TEXTS: u32 = 20;
+
+/// NANP toll-free area codes.
+const TOLL_FREE_PREFIXES: &[&str] = &["800", "833", "844", "855", "866", "877", "888"];
+
+/// Phrases that only show up in bulk/automated messages.
+const AUTOMATED_PHRASES: &[&str] = &[
+    "stop to opt out",
+    "stop to unsubscribe",
+    "reply stop",
+    "text stop",
+    "msg&data rates",
+    "msg & data rates",
+    "do not share this code",
+    "don't share this code",
+    "verification code",
+    "security code",
+];
+
+/// Email local parts (separators removed) of addresses nobody reads.
+const NO_REPLY_PREFIXES: &[&str] = &["noreply", "donotreply"];
+
+/// Shape of a sender handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderKind {
+    /// 5-6 digit SMS short code (e.g. 32665)
+    ShortCode,
+    /// Alphanumeric sender ID (e.g. "AMAZON")
+    Alphanumeric,
+    /// NANP toll-free number (800, 888, ...)
+    TollFree,
+    /// No-reply email address (noreply@, no-reply@, donotreply@)
+    NoReply,
+    /// Regular phone number or email
+    Personal,
+}
+
+impl SenderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SenderKind::ShortCode => "short_code",
+            SenderKind::Alphanumeric => "alphanumeric",
+            SenderKind::TollFree => "toll_free",
+            SenderKind::NoReply => "no_reply",
+            SenderKind::Personal => "personal",
+        }
+    }
+}
+
+/// Classification attached to unknown-sender rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Classification {
+    pub kind: SenderKind,
+    pub looks_automated: bool,
+}
+
+impl Classification {
+    /// True when either the handle shape or the content says "bot".
+    pub fn is_automated(&self) -> bool {
+        self.kind != SenderKind::Personal || self.looks_automated
+    }
+}
+
+/// Classify a handle from its shape and its recent texts.
+pub fn classify(handle: &str, texts: &[String]) -> Classification {
+    Classification {
+        kind: classify_handle(handle),
+        looks_automated: looks_automated(texts),
+    }
+}
+
+/// Classify a handle by its shape alone.
+pub fn classify_handle(handle: &str) -> SenderKind {
+    let handle = handle.trim();
+    if let Some((local, _)) = handle.split_once('@') {
+        let local: String = local
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | '.'))
+            .collect::<String>()
+            .to_lowercase();
+        return if NO_REPLY_PREFIXES.iter().any(|p| local.starts_with(p)) {
+            SenderKind::NoReply
+        } else {
+            SenderKind::Personal
+        };
+    }
+
+    let compact: String = handle
+        .chars()
+        .filter(|c| !matches!(c, '+' | '-' | ' ' | '(' | ')' | '.'))
+        .collect();
+    if compact.is_empty() {
+        return SenderKind::Personal;
+    }
+    if compact.chars().any(|c| c.is_alphabetic()) {
+        return SenderKind::Alphanumeric;
+    }
+    if !compact.chars().all(|c| c.is_ascii_digit()) {
+        return SenderKind::Personal;
+    }
+
+    if (5..=6).contains(&compact.len()) {
+        return SenderKind::ShortCode;
+    }
+
+    let national = match compact.len() {
+        11 if compact.starts_with('1') => &compact[1..],
+        10 => compact.as_str(),
+        _ => return SenderKind::Personal,
+    };
+    if TOLL_FREE_PREFIXES.iter().any(|p| national.starts_with(p)) {
+        SenderKind::TollFree
+    } else {
+        SenderKind::Personal
+    }
+}
+
+/// Content heuristic: an opt-out instruction or one-time-code wording in
+/// any text. Kept narrow on purpose, since a person can text anything.
+pub fn looks_automated(texts: &[String]) -> bool {
+    texts.iter().map(|t| t.to_lowercase()).any(|t| {
+        AUTOMATED_PHRASES.iter().any(|p| t.contains(p)) || is_code_message(&t)
+    })
+}
+
+/// "your ... code" alongside a 4-8 digit run, e.g. "Your Uber code is 4821".
+fn is_code_message(lowered: &str) -> bool {
+    if !(lowered.contains("your") && lowered.contains("code")) {
+        return false;
+    }
+    lowered
+        .split(|c: char| !c.is_ascii_digit())
+        .any(|run| (4..=8).contains(&run.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_classify_handle() {
+        let cases = [
+            ("32665", SenderKind::ShortCode),
+            ("889932", SenderKind::ShortCode),
+            ("AMAZON", SenderKind::Alphanumeric),
+            ("Chase-Alerts", SenderKind::Alphanumeric),
+            ("+18005551234", SenderKind::TollFree),
+            ("(888) 555-1234", SenderKind::TollFree),
+            ("8775551234", SenderKind::TollFree),
+            ("+14087771234", SenderKind::Personal),
+            ("4087771234", SenderKind::Personal),
+            ("john@example.com", SenderKind::Personal),
+            ("noreply@example.com", SenderKind::NoReply),
+            ("No-Reply@accounts.example.com", SenderKind::NoReply),
+            ("do_not_reply@bank.example", SenderKind::NoReply),
+            ("noreen@example.com", SenderKind::Personal),
+            ("1234", SenderKind::Personal),
+            ("+447911123456", SenderKind::Personal),
+        ];
+        for (handle, expected) in cases {
+            assert_eq!(classify_handle(handle), expected, "{}", handle);
+        }
+    }
+
+    #[test]
+    fn test_looks_automated() {
+        let cases: [(&[&str], bool); 6] = [
+            (&["Your Uber code is 4821. Never share it."], true),
+            (&["Sale ends tonight! Reply STOP to opt out"], true),
+            (&["Your package has shipped. Msg&data rates may apply"], true),
+            (&["G-482913 is your Google verification code."], true),
+            (&["hey are we still on for dinner?", "running 5 min late"], false),
+            (&[], false),
+        ];
+        for (items, expected) in cases {
+            assert_eq!(looks_automated(&texts(items)), expected, "{:?}", items);
+        }
+    }
+
+    #[test]
+    fn test_ordinary_messages_not_automated() {
+        let cases: &[&[&str]] = &[
+            &["ok", "ok", "ok", "ok"],
+            &["lol", "lol", "lol"],
+            &["415-555-1234", "10:30?", "2/14"],
+            &["the gate code is 4821", "apt 2204"],
+            &["I think I'll opt out of the ski trip", "one-time thing, promise"],
+            &["unsubscribe me from your group chat drama 😂"],
+            &["Flight UA 1523 lands 6:45, gate B12", "$45.50 each for 2"],
+        ];
+        for items in cases {
+            assert!(!looks_automated(&texts(items)), "{:?}", items);
+            assert!(!classify("+14087771234", &texts(items)).is_automated(), "{:?}", items);
+        }
+    }
+
+    #[test]
+    fn test_is_automated() {
+        let personal = classify("+14087771234", &texts(&["see you soon"]));
+        assert!(!personal.is_automated());
+
+        let short_code = classify("32665", &texts(&["hi"]));
+        assert!(short_code.is_automated());
+
+        let spammy = classify("+14087771234", &texts(&["Reply STOP to unsubscribe"]));
+        assert_eq!(spammy.kind, SenderKind::Personal);
+        assert!(spammy.is_automated());
+
+        assert!(classify("noreply@example.com", &texts(&["Your receipt"])).is_automated());
+    }
+}
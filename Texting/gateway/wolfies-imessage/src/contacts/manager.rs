@@ -1,27 +1,70 @@
 //! Contact manager - load and lookup contacts from JSON.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - other_handle_keys (a contact's other handles, for mutes and snoozes)
+//! - 10/16/2026 - Contact warnings reach stderr or the envelope meta only (no `results` wrapper around list output)
+//! - 10/16/2026 - Handle comparison uses helpers::handle_key (dropped the local copy and normalize_phone)
+//! - 10/16/2026 - Added resolve_with_diagnostics (chosen contact, match method and score, other candidates)
+//! - 10/16/2026 - resolve_to_phone passes email-shaped input through as a handle (looks_like_email)
+//! - 10/16/2026 - display_name: contact name > 1:1 chat display name > handle
+//! - 10/16/2026 - Malformed contacts.json: skip bad entries, load_default_or_empty warns instead of silently going empty
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - contacts_path from config.toml (crate::config) sits between the env var and built-in lookup
+//! - 10/16/2026 - Added extra handles per contact and relationship lookups
 //! - 01/10/2026 - Added fuzzy matching with score threshold (Claude)
 //! - 01/10/2026 - Initial stub (Claude)
 
 use super::fuzzy;
-use anyhow::{Context, Result};
+use crate::db::helpers::handle_key;
+use crate::output;
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-/// Default contacts.json path.
+/// How a name resolved to a contact, for explaining surprising matches.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Resolution {
+    /// The name as given
+    pub query: String,
+    pub contact_name: String,
+    pub phone: String,
+    /// exact, partial, or fuzzy
+    pub method: String,
+    /// Fuzzy score of the chosen name against the query (0.0 - 1.0)
+    pub score: f64,
+    /// Other contacts that also matched, best first
+    pub candidates: Vec<Candidate>,
+}
+
+/// A contact that matched a name but wasn't chosen.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Candidate {
+    pub name: String,
+    pub phone: String,
+    pub score: f64,
+}
+
+/// Env var overriding the contacts.json path.
+pub const CONTACTS_PATH_ENV: &str = "IMESSAGE_CONTACTS_PATH";
+
+/// Effective contacts.json path.
 ///
-/// Tries multiple locations in order:
+/// Resolved by crate::config in order:
 /// 1. IMESSAGE_CONTACTS_PATH env var
-/// 2. Texting/config/contacts.json (relative to LIFE-PLANNER)
-/// 3. Compile-time path from CARGO_MANIFEST_DIR
+/// 2. contacts_path in config.toml
+/// 3. Built-in lookup (`builtin_contacts_path`)
 pub fn default_contacts_path() -> PathBuf {
-    // 1. Check env var
-    if let Ok(path) = std::env::var("IMESSAGE_CONTACTS_PATH") {
-        return PathBuf::from(path);
-    }
+    crate::config::active().contacts_path.value.clone()
+}
 
-    // 2. Try Texting/config/contacts.json in LIFE-PLANNER
+/// Built-in contacts.json location.
+///
+/// 1. Texting/config/contacts.json (relative to LIFE-PLANNER)
+/// 2. Compile-time path from CARGO_MANIFEST_DIR
+pub fn builtin_contacts_path() -> PathBuf {
+    // 1. Try Texting/config/contacts.json in LIFE-PLANNER
     if let Some(home) = dirs::home_dir() {
         let life_planner_path = home
             .join("LIFE-PLANNER")
@@ -33,7 +76,7 @@ pub fn default_contacts_path() -> PathBuf {
         }
     }
 
-    // 3. Fallback to compile-time path
+    // 2. Fallback to compile-time path
     let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .parent()
         .and_then(|p| p.parent())
@@ -44,7 +87,7 @@ pub fn default_contacts_path() -> PathBuf {
 }
 
 /// A contact from the contacts.json file.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Contact {
     pub name: String,
     pub phone: String,
@@ -52,12 +95,109 @@ pub struct Contact {
     pub relationship_type: String,
     #[serde(default)]
     pub notes: Option<String>,
+    /// Additional phones/emails for the same person
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handles: Vec<String>,
+}
+
+impl Contact {
+    /// Primary phone followed by any additional handles.
+    pub fn all_handles(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.phone.as_str())
+            .chain(self.handles.iter().map(|h| h.as_str()))
+            .filter(|h| !h.is_empty())
+    }
 }
 
 /// Wrapper for contacts.json format (has "contacts" key).
 #[derive(Debug, Deserialize)]
 struct ContactsFile {
-    contacts: Vec<Contact>,
+    contacts: Vec<Entry>,
+}
+
+/// One element of the contacts array: a contact, or why it was rejected.
+///
+/// Elements are checked one by one so a single bad entry doesn't sink the
+/// whole file. Duplicate keys are rejected (serde_json::Value would keep the
+/// last one silently).
+#[derive(Debug)]
+struct Entry(std::result::Result<Contact, String>);
+
+impl<'de> Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+struct EntryVisitor;
+
+impl EntryVisitor {
+    fn not_an_object<E>(found: &str) -> std::result::Result<Entry, E> {
+        Ok(Entry(Err(format!("expected an object, found {}", found))))
+    }
+}
+
+impl<'de> Visitor<'de> for EntryVisitor {
+    type Value = Entry;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a contact object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Entry, A::Error> {
+        let mut object = serde_json::Map::new();
+        let mut duplicate = None;
+        while let Some((key, value)) = map.next_entry::<String, serde_json::Value>()? {
+            if object.contains_key(&key) && duplicate.is_none() {
+                duplicate = Some(key.clone());
+            }
+            object.insert(key, value);
+        }
+        if let Some(key) = duplicate {
+            return Ok(Entry(Err(format!("duplicate field `{}`", key))));
+        }
+        Ok(Entry(
+            serde_json::from_value(serde_json::Value::Object(object)).map_err(|e| e.to_string()),
+        ))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Entry, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Self::not_an_object("an array")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> std::result::Result<Entry, E> {
+        Self::not_an_object("a boolean")
+    }
+
+    fn visit_i64<E>(self, _: i64) -> std::result::Result<Entry, E> {
+        Self::not_an_object("a number")
+    }
+
+    fn visit_u64<E>(self, _: u64) -> std::result::Result<Entry, E> {
+        Self::not_an_object("a number")
+    }
+
+    fn visit_f64<E>(self, _: f64) -> std::result::Result<Entry, E> {
+        Self::not_an_object("a number")
+    }
+
+    fn visit_str<E>(self, _: &str) -> std::result::Result<Entry, E> {
+        Self::not_an_object("a string")
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Entry, E> {
+        Self::not_an_object("null")
+    }
+}
+
+/// Parse either contacts.json layout, reporting errors for the one the file uses.
+fn parse_entries(content: &str) -> serde_json::Result<Vec<Entry>> {
+    if content.trim_start().starts_with('[') {
+        serde_json::from_str(content)
+    } else {
+        serde_json::from_str::<ContactsFile>(content).map(|file| file.contacts)
+    }
 }
 
 /// Manages contacts loaded from JSON file.
@@ -67,32 +207,85 @@ pub struct ContactsManager {
 }
 
 impl ContactsManager {
-    /// Load contacts from a JSON file.
+    /// Load contacts from a JSON file, skipping malformed entries.
     ///
     /// Supports both formats:
     /// - `{"contacts": [...]}` (Python format)
     /// - `[...]` (flat array)
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = std::fs::read_to_string(path.as_ref())
-            .with_context(|| format!("Failed to read contacts file: {:?}", path.as_ref()))?;
+        Self::load_with_warnings(path).map(|(manager, _)| manager)
+    }
+
+    /// Like `load`, also returning a warning per skipped entry.
+    ///
+    /// Errors if the file can't be read or isn't valid JSON of either format
+    /// (serde's message carries the line and column).
+    pub fn load_with_warnings<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<String>)> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read contacts file: {:?}", path))?;
+        let entries =
+            parse_entries(&content).with_context(|| format!("Failed to parse contacts file {:?}", path))?;
 
-        // Try wrapped format first ({"contacts": [...]})
-        if let Ok(wrapper) = serde_json::from_str::<ContactsFile>(&content) {
-            return Ok(Self {
-                contacts: wrapper.contacts,
-            });
+        let mut contacts = Vec::with_capacity(entries.len());
+        let mut warnings = Vec::new();
+        for (i, Entry(entry)) in entries.into_iter().enumerate() {
+            match entry {
+                Ok(contact) => contacts.push(contact),
+                Err(reason) => warnings.push(format!("skipped contact #{} in {:?}: {}", i + 1, path, reason)),
+            }
         }
+        Ok((Self { contacts }, warnings))
+    }
 
-        // Fallback to flat array format
-        let contacts: Vec<Contact> = serde_json::from_str(&content)
-            .with_context(|| "Failed to parse contacts JSON")?;
+    /// Load from default path, reporting skipped entries via `output::warn`
+    /// (stderr after the output, or `meta.warnings` under `--envelope`).
+    pub fn load_default() -> Result<Self> {
+        let (manager, warnings) = Self::load_with_warnings(default_contacts_path())?;
+        warnings.into_iter().for_each(output::warn);
+        Ok(manager)
+    }
 
-        Ok(Self { contacts })
+    /// Contacts for commands that work without any (names fall back to handles).
+    ///
+    /// No file means no contacts, silently. An unreadable or corrupt file, or
+    /// skipped entries, are reported via `output::warn` so the missing names
+    /// have an explanation; the JSON payload itself keeps its shape.
+    pub fn load_default_or_empty() -> Self {
+        let (manager, warnings) = Self::load_or_empty(default_contacts_path());
+        warnings.into_iter().for_each(output::warn);
+        manager
     }
 
-    /// Load from default path.
-    pub fn load_default() -> Result<Self> {
-        Self::load(default_contacts_path())
+    /// `load_with_warnings` that degrades to no contacts: a missing file
+    /// gives no warnings, a corrupt one a single warning with the cause.
+    pub fn load_or_empty<P: AsRef<Path>>(path: P) -> (Self, Vec<String>) {
+        let path = path.as_ref();
+        if !path.exists() {
+            return (Self::empty(), Vec::new());
+        }
+        match Self::load_with_warnings(path) {
+            Ok(loaded) => loaded,
+            Err(e) => (Self::empty(), vec![format!("{:#}; continuing without contacts", e)]),
+        }
+    }
+
+    /// Normalized handles (`handle_key`) of the contact `handle` belongs to,
+    /// other than `handle` itself; empty for an unknown handle.
+    pub fn other_handle_keys(&self, handle: &str) -> Vec<String> {
+        let key = handle_key(handle);
+        let mut keys: Vec<String> = Vec::new();
+        for other in self.find_by_phone(handle).into_iter().flat_map(Contact::all_handles).map(handle_key) {
+            if other != key && !keys.contains(&other) {
+                keys.push(other);
+            }
+        }
+        keys
+    }
+
+    /// Create a manager from already-loaded contacts.
+    pub fn from_contacts(contacts: Vec<Contact>) -> Self {
+        Self { contacts }
     }
 
     /// Create an empty manager (for when contacts aren't available).
@@ -113,12 +306,63 @@ impl ContactsManager {
             .find(|c| c.name.to_lowercase() == name_lower)
     }
 
-    /// Find a contact by phone number.
+    /// Find a contact by phone number (or any of its additional handles).
     pub fn find_by_phone(&self, phone: &str) -> Option<&Contact> {
-        let normalized = normalize_phone(phone);
+        let key = handle_key(phone);
+        if key.is_empty() {
+            return None;
+        }
         self.contacts
             .iter()
-            .find(|c| normalize_phone(&c.phone) == normalized)
+            .find(|c| c.all_handles().any(|h| handle_key(h) == key))
+    }
+
+    /// Who a handle is, for display: the contact's name, else the custom
+    /// display name of a 1:1 chat with it (business chats often have one),
+    /// else the handle itself. Callers pass None for group chat names.
+    pub fn display_name(&self, handle: &str, chat_display_name: Option<&str>) -> String {
+        if let Some(contact) = self.find_by_phone(handle) {
+            return contact.name.clone();
+        }
+        match chat_display_name.map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => handle.to_string(),
+        }
+    }
+
+    /// Distinct relationship types present in contacts.json (lowercase, sorted).
+    pub fn relationships(&self) -> Vec<String> {
+        let mut known: Vec<String> = self
+            .contacts
+            .iter()
+            .map(|c| c.relationship_type.trim().to_lowercase())
+            .filter(|r| !r.is_empty())
+            .collect();
+        known.sort();
+        known.dedup();
+        known
+    }
+
+    /// All handles of contacts with the given relationship type.
+    ///
+    /// Errors on a relationship no contact has, listing the known set.
+    pub fn handles_for_relationship(&self, relationship: &str) -> Result<Vec<String>> {
+        let wanted = relationship.trim().to_lowercase();
+        let known = self.relationships();
+        if !known.contains(&wanted) {
+            return Err(anyhow!(
+                "Unknown relationship '{}' (known: {})",
+                relationship,
+                if known.is_empty() { "none".to_string() } else { known.join(", ") }
+            ));
+        }
+
+        Ok(self
+            .contacts
+            .iter()
+            .filter(|c| c.relationship_type.trim().to_lowercase() == wanted)
+            .flat_map(|c| c.all_handles().map(|h| h.to_string()))
+            .collect())
     }
 
     /// Find contact with fuzzy matching.
@@ -128,9 +372,14 @@ impl ContactsManager {
     /// 2. Partial name match (name contains query)
     /// 3. Fuzzy match with score >= 0.85
     pub fn find_fuzzy(&self, name: &str) -> Option<&Contact> {
+        self.find_fuzzy_with_method(name).map(|(contact, _)| contact)
+    }
+
+    /// find_fuzzy, plus which step matched: exact, partial, or fuzzy.
+    fn find_fuzzy_with_method(&self, name: &str) -> Option<(&Contact, &'static str)> {
         // First try exact match
         if let Some(contact) = self.find_by_name(name) {
-            return Some(contact);
+            return Some((contact, "exact"));
         }
 
         // Then try partial match
@@ -138,28 +387,63 @@ impl ContactsManager {
         if let Some(contact) = self.contacts.iter().find(|c| {
             c.name.to_lowercase().contains(&name_lower)
         }) {
-            return Some(contact);
+            return Some((contact, "partial"));
         }
 
         // Finally try fuzzy match with threshold
         let mut best_match: Option<(&Contact, f64)> = None;
         for contact in &self.contacts {
             let match_result = fuzzy::multi_match(name, &contact.name);
-            if match_result.score >= fuzzy::DEFAULT_THRESHOLD {
-                if best_match.as_ref().map_or(true, |(_, score)| match_result.score > *score) {
-                    best_match = Some((contact, match_result.score));
-                }
+            if match_result.score >= fuzzy::DEFAULT_THRESHOLD
+                && best_match.as_ref().is_none_or(|(_, score)| match_result.score > *score)
+            {
+                best_match = Some((contact, match_result.score));
             }
         }
 
-        best_match.map(|(c, _)| c)
+        best_match.map(|(c, _)| (c, "fuzzy"))
+    }
+
+    /// Resolve a name like find_fuzzy, reporting the score and the other
+    /// contacts that would also have matched (partially or above the fuzzy
+    /// threshold). None for phone or email input, which isn't name-matched.
+    pub fn resolve_with_diagnostics(&self, name: &str) -> Option<Resolution> {
+        if is_handle_input(name) {
+            return None;
+        }
+        let (chosen, method) = self.find_fuzzy_with_method(name)?;
+        let name_lower = name.to_lowercase();
+        let mut candidates: Vec<Candidate> = self
+            .contacts
+            .iter()
+            .filter(|c| !std::ptr::eq(*c, chosen))
+            .filter_map(|c| {
+                let score = fuzzy::multi_match(name, &c.name).score;
+                (score >= fuzzy::DEFAULT_THRESHOLD || c.name.to_lowercase().contains(&name_lower))
+                    .then(|| Candidate { name: c.name.clone(), phone: c.phone.clone(), score })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        Some(Resolution {
+            query: name.to_string(),
+            contact_name: chosen.name.clone(),
+            phone: chosen.phone.clone(),
+            method: method.to_string(),
+            score: fuzzy::multi_match(name, &chosen.name).score,
+            candidates,
+        })
     }
 
     /// Resolve a name or phone to a phone number.
     ///
+    /// Email-shaped input is already an iMessage handle and comes back as-is.
     /// If input looks like a phone number, returns it normalized.
     /// Otherwise, tries to resolve as a contact name.
     pub fn resolve_to_phone(&self, name_or_phone: &str) -> Option<String> {
+        if looks_like_email(name_or_phone) {
+            return Some(name_or_phone.trim().to_string());
+        }
+
         // Check if it's already a phone number
         let digits: String = name_or_phone.chars().filter(|c| c.is_ascii_digit()).collect();
         if digits.len() >= 10 {
@@ -173,21 +457,169 @@ impl ContactsManager {
     }
 }
 
-/// Normalize phone number for comparison.
-fn normalize_phone(phone: &str) -> String {
-    phone
-        .chars()
-        .filter(|c| c.is_ascii_digit())
-        .collect()
+/// Whether `input` is shaped like an email address: one `@`, no whitespace,
+/// and a dotted domain. Not full RFC 5322, just enough to tell handles from names.
+pub fn looks_like_email(input: &str) -> bool {
+    let input = input.trim();
+    let Some((local, domain)) = input.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !input.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+/// Whether resolve_to_phone takes `input` as a handle rather than a name.
+fn is_handle_input(input: &str) -> bool {
+    looks_like_email(input) || input.chars().filter(|c| c.is_ascii_digit()).count() >= 10
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn contact(name: &str, phone: &str, relationship: &str, handles: &[&str]) -> Contact {
+        Contact {
+            name: name.to_string(),
+            phone: phone.to_string(),
+            relationship_type: relationship.to_string(),
+            notes: None,
+            handles: handles.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_resolution_reports_score_and_other_candidates() {
+        let manager = ContactsManager::from_contacts(vec![
+            contact("Alex Smith", "+14155550001", "friend", &[]),
+            contact("Alex Smyth", "+14155550002", "work", &[]),
+            contact("Sam Jones", "+14155550003", "friend", &[]),
+        ]);
+
+        let r = manager.resolve_with_diagnostics("alex").unwrap();
+        assert_eq!((r.contact_name.as_str(), r.phone.as_str(), r.method.as_str()), ("Alex Smith", "+14155550001", "partial"));
+        let others: Vec<&str> = r.candidates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(others, ["Alex Smyth"]);
+
+        let r = manager.resolve_with_diagnostics("Alex Smithe").unwrap();
+        assert_eq!((r.contact_name.as_str(), r.method.as_str()), ("Alex Smith", "fuzzy"));
+        assert!(r.score >= fuzzy::DEFAULT_THRESHOLD && r.score < 1.0);
+        assert_eq!(r.candidates.len(), 1);
+        assert_eq!(r.candidates[0].phone, "+14155550002");
+        assert!(r.candidates[0].score < r.score);
+
+        let r = manager.resolve_with_diagnostics("Sam Jones").unwrap();
+        assert_eq!((r.method.as_str(), r.score), ("exact", 1.0));
+        assert!(r.candidates.is_empty());
+
+        assert!(manager.resolve_with_diagnostics("+14155550002").is_none());
+        assert!(manager.resolve_with_diagnostics("alex@example.com").is_none());
+        assert!(manager.resolve_with_diagnostics("Zed").is_none());
+    }
+
+    #[test]
+    fn test_email_input_resolves_verbatim() {
+        let manager = ContactsManager::from_contacts(vec![contact("Sarah Jones", "+14155550001", "friend", &[])]);
+        // Digits in an email don't make it a phone number
+        assert_eq!(manager.resolve_to_phone("Sarah.J1415555000@iCloud.com").as_deref(), Some("Sarah.J1415555000@iCloud.com"));
+        assert_eq!(manager.resolve_to_phone(" sarah@icloud.com ").as_deref(), Some("sarah@icloud.com"));
+        assert_eq!(manager.resolve_to_phone("sarah").as_deref(), Some("+14155550001"));
+
+        for not_email in ["sarah@", "@icloud.com", "sarah@icloud", "sarah jones@icloud.com", "a@b@c.com", "sarah@icloud."] {
+            assert!(!looks_like_email(not_email), "{}", not_email);
+        }
+    }
+
+    #[test]
+    fn test_find_by_additional_handle() {
+        let manager = ContactsManager::from_contacts(vec![contact(
+            "Alice",
+            "+14155550001",
+            "friend",
+            &["alice@example.com", "+14155550009"],
+        )]);
+        assert_eq!(manager.find_by_phone("Alice@Example.com").unwrap().name, "Alice");
+        assert_eq!(manager.find_by_phone("(415) 555-0009").map(|c| c.name.as_str()), Some("Alice"));
+        assert!(manager.find_by_phone("bob@example.com").is_none());
+    }
+
+    #[test]
+    fn test_display_name_precedence() {
+        let manager = ContactsManager::from_contacts(vec![contact("Alice", "+14155550001", "friend", &[])]);
+        // Contact name wins over the chat's name
+        assert_eq!(manager.display_name("+14155550001", Some("Ali (work)")), "Alice");
+        // Unknown handle: the chat's display name
+        assert_eq!(manager.display_name("+18005550100", Some("Acme Support")), "Acme Support");
+        // No (or blank) chat name: the handle
+        assert_eq!(manager.display_name("+18005550100", None), "+18005550100");
+        assert_eq!(manager.display_name("+18005550100", Some("  ")), "+18005550100");
+    }
+
+    fn write_contacts(json: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("contacts.json");
+        std::fs::write(&path, json).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_missing_contacts_file_is_quietly_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let (manager, warnings) = ContactsManager::load_or_empty(dir.path().join("contacts.json"));
+        assert!(manager.all().is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_contacts_file_warns_with_position() {
+        let trailing_comma = "{\"contacts\": [\n  {\"name\": \"Ann\", \"phone\": \"+14155550001\"},\n]}";
+        let duplicate_key = "{\"contacts\": [], \"contacts\": []}";
+        for json in [trailing_comma, duplicate_key] {
+            let (_dir, path) = write_contacts(json);
+            assert!(ContactsManager::load(&path).is_err());
+
+            let (manager, warnings) = ContactsManager::load_or_empty(&path);
+            assert!(manager.all().is_empty());
+            assert_eq!(warnings.len(), 1);
+            assert!(warnings[0].contains("line ") && warnings[0].contains("column "), "{}", warnings[0]);
+        }
+    }
+
+    #[test]
+    fn test_bad_entries_skipped_with_warnings() {
+        let (_dir, path) = write_contacts(
+            r#"[
+                {"name": "Ann", "phone": "+14155550001"},
+                {"name": "Bob"},
+                {"name": "Cy", "phone": "+14155550003", "phone": "+14155550004"},
+                "Dee",
+                {"name": "Eve", "phone": "+14155550005", "relationship_type": "family"}
+            ]"#,
+        );
+        let (manager, warnings) = ContactsManager::load_with_warnings(&path).unwrap();
+
+        let names: Vec<&str> = manager.all().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Ann", "Eve"]);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("#2") && warnings[0].contains("missing field `phone`"));
+        assert!(warnings[1].contains("#3") && warnings[1].contains("duplicate field `phone`"));
+        assert!(warnings[2].contains("#4") && warnings[2].contains("expected an object"));
+    }
+
     #[test]
-    fn test_normalize_phone() {
-        assert_eq!(normalize_phone("+1 (415) 555-1234"), "14155551234");
-        assert_eq!(normalize_phone("+14155551234"), "14155551234");
+    fn test_handles_for_relationship() {
+        let manager = ContactsManager::from_contacts(vec![
+            contact("Alice", "+14155550001", "Work", &["alice@work.com"]),
+            contact("Bob", "+14155550002", "family", &[]),
+        ]);
+        assert_eq!(manager.relationships(), vec!["family", "work"]);
+        assert_eq!(
+            manager.handles_for_relationship("work").unwrap(),
+            vec!["+14155550001", "alice@work.com"]
+        );
+        let err = manager.handles_for_relationship("golf").unwrap_err().to_string();
+        assert!(err.contains("known: family, work"));
     }
 }
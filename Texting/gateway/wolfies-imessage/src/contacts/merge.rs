@@ -0,0 +1,289 @@
+//! Duplicate contact detection and merging.
+//!
+//! Two entries are duplicates when they share a normalized phone or email
+//! (`handle_key`) or their names match exactly (case-insensitive) or fuzzily
+//! at `NAME_MATCH_THRESHOLD`. Duplicates make fuzzy resolution pick between
+//! them arbitrarily, so `add-contact` refuses a colliding name (or merges
+//! with --merge) and `contacts dedupe` folds existing duplicates together.
+//! A merge keeps the first entry's name and phone; every other phone and
+//! email moves to its `handles`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial name-collision detection, duplicate groups, and merges
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::fuzzy;
+use super::manager::Contact;
+use crate::db::helpers::handle_key;
+
+/// Fuzzy name score at or above which two names are the same person.
+pub const NAME_MATCH_THRESHOLD: f64 = 0.95;
+
+/// An existing contact whose name collides with a new one.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct NameCollision {
+    /// Index into the contact list
+    #[serde(skip)]
+    pub index: usize,
+    pub name: String,
+    pub phone: String,
+    /// 1.0 for an exact (case-insensitive) match
+    pub score: f64,
+}
+
+/// Name similarity, or None below the threshold.
+fn name_score(a: &str, b: &str) -> Option<f64> {
+    let (a, b) = (a.trim(), b.trim());
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    if a.to_lowercase() == b.to_lowercase() {
+        return Some(1.0);
+    }
+    let score = fuzzy::multi_match(a, b).score;
+    (score >= NAME_MATCH_THRESHOLD).then_some(score)
+}
+
+/// Existing contacts whose name collides with `name`, best match first.
+pub fn name_collisions(contacts: &[Contact], name: &str) -> Vec<NameCollision> {
+    let mut collisions: Vec<NameCollision> = contacts
+        .iter()
+        .enumerate()
+        .filter_map(|(index, c)| {
+            name_score(name, &c.name).map(|score| NameCollision {
+                index,
+                name: c.name.clone(),
+                phone: c.phone.clone(),
+                score,
+            })
+        })
+        .collect();
+    collisions.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+    collisions
+}
+
+/// Add `handle` to `contact` unless it already has it; true if added.
+pub fn add_handle(contact: &mut Contact, handle: &str) -> bool {
+    let key = handle_key(handle);
+    if key.is_empty() || contact.all_handles().any(|h| handle_key(h) == key) {
+        return false;
+    }
+    contact.handles.push(handle.trim().to_string());
+    true
+}
+
+/// Fold `other` into `into`: its phone and handles are added, and its
+/// relationship and notes fill in (or extend) what `into` lacks.
+pub fn merge_into(into: &mut Contact, other: &Contact) {
+    for handle in other.all_handles() {
+        add_handle(into, handle);
+    }
+    if into.relationship_type.trim().is_empty() {
+        into.relationship_type = other.relationship_type.clone();
+    }
+    into.notes = match (into.notes.take(), other.notes.as_deref()) {
+        (Some(mine), Some(theirs)) if !theirs.is_empty() && !mine.contains(theirs) => {
+            Some(format!("{}; {}", mine, theirs))
+        }
+        (None, Some(theirs)) => Some(theirs.to_string()),
+        (mine, _) => mine,
+    };
+}
+
+/// Contacts that should be one entry: `keep` absorbs `duplicates`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DuplicateGroup {
+    pub keep: Contact,
+    pub duplicates: Vec<Contact>,
+    /// Why the entries were grouped, e.g. "same handle 4155550001"
+    pub reasons: Vec<String>,
+    /// `keep` after the merge
+    pub merged: Contact,
+    #[serde(skip)]
+    indices: Vec<usize>,
+}
+
+/// Group duplicate entries (shared handle or near-identical name), in file
+/// order. Grouping is transitive: A~B and B~C put A, B, C together.
+pub fn find_duplicates(contacts: &[Contact]) -> Vec<DuplicateGroup> {
+    let mut parent: Vec<usize> = (0..contacts.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut reasons: HashMap<usize, Vec<String>> = HashMap::new();
+    let mut link = |parent: &mut Vec<usize>, a: usize, b: usize, reason: String| {
+        let (ra, rb) = (root(parent, a), root(parent, b));
+        // The earlier entry stays the root so it is the one kept
+        let (keep, other) = (ra.min(rb), ra.max(rb));
+        parent[other] = keep;
+        let mut moved = if keep != other { reasons.remove(&other).unwrap_or_default() } else { Vec::new() };
+        let list = reasons.entry(keep).or_default();
+        list.append(&mut moved);
+        if !list.contains(&reason) {
+            list.push(reason);
+        }
+    };
+
+    // Shared handles
+    let mut first_with_key: HashMap<String, usize> = HashMap::new();
+    for (i, contact) in contacts.iter().enumerate() {
+        for handle in contact.all_handles() {
+            let key = handle_key(handle);
+            if key.is_empty() {
+                continue;
+            }
+            match first_with_key.get(&key) {
+                Some(&first) if first != i => link(&mut parent, first, i, format!("same handle {}", key)),
+                Some(_) => {}
+                None => {
+                    first_with_key.insert(key, i);
+                }
+            }
+        }
+    }
+
+    // Near-identical names
+    for i in 0..contacts.len() {
+        for j in (i + 1)..contacts.len() {
+            if let Some(score) = name_score(&contacts[i].name, &contacts[j].name) {
+                let reason = if score >= 1.0 {
+                    format!("same name '{}'", contacts[i].name)
+                } else {
+                    format!("similar names '{}' / '{}' ({:.2})", contacts[i].name, contacts[j].name, score)
+                };
+                link(&mut parent, i, j, reason);
+            }
+        }
+    }
+
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); contacts.len()];
+    for i in 0..contacts.len() {
+        let r = root(&mut parent, i);
+        members[r].push(i);
+    }
+    members
+        .into_iter()
+        .enumerate()
+        .filter(|(_, m)| m.len() > 1)
+        .map(|(keep, indices)| {
+            let mut merged = contacts[keep].clone();
+            let duplicates: Vec<Contact> = indices[1..].iter().map(|&i| contacts[i].clone()).collect();
+            for d in &duplicates {
+                merge_into(&mut merged, d);
+            }
+            DuplicateGroup {
+                keep: contacts[keep].clone(),
+                duplicates,
+                reasons: reasons.remove(&keep).unwrap_or_default(),
+                merged,
+                indices,
+            }
+        })
+        .collect()
+}
+
+/// `contacts` with every group collapsed into its merged entry (kept at the
+/// position of its first member).
+pub fn apply_merges(contacts: &[Contact], groups: &[DuplicateGroup]) -> Vec<Contact> {
+    let mut replacement: HashMap<usize, &Contact> = HashMap::new();
+    let mut dropped = vec![false; contacts.len()];
+    for group in groups {
+        replacement.insert(group.indices[0], &group.merged);
+        for &i in &group.indices[1..] {
+            dropped[i] = true;
+        }
+    }
+    contacts
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !dropped[*i])
+        .map(|(i, c)| replacement.get(&i).map_or_else(|| c.clone(), |m| (*m).clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, phone: &str, handles: &[&str]) -> Contact {
+        Contact {
+            name: name.to_string(),
+            phone: phone.to_string(),
+            relationship_type: String::new(),
+            notes: None,
+            handles: handles.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_name_collisions_exact_and_fuzzy() {
+        let contacts = vec![
+            contact("Mom", "+14155550001", &[]),
+            contact("Jonathan Smith", "+14155550002", &[]),
+            contact("Bob", "+14155550003", &[]),
+        ];
+        let exact = name_collisions(&contacts, "mom");
+        assert_eq!((exact.len(), exact[0].index, exact[0].score), (1, 0, 1.0));
+
+        let fuzzy = name_collisions(&contacts, "Jonathan Smithe");
+        assert_eq!(fuzzy.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["Jonathan Smith"]);
+        assert!(fuzzy[0].score >= NAME_MATCH_THRESHOLD && fuzzy[0].score < 1.0);
+
+        // Similar but distinct names don't collide
+        assert!(name_collisions(&contacts, "Rob").is_empty());
+        assert!(name_collisions(&contacts, "Mo").is_empty());
+    }
+
+    #[test]
+    fn test_merge_into_unions_handles() {
+        let mut mom = contact("Mom", "+14155550001", &["mom@example.com"]);
+        mom.notes = Some("home".to_string());
+        let mut other = contact("Mom", "(415) 555-0009", &["+1 415 555 0001", "MOM@example.com"]);
+        other.relationship_type = "family".to_string();
+        other.notes = Some("cell".to_string());
+
+        merge_into(&mut mom, &other);
+        assert_eq!(mom.phone, "+14155550001");
+        assert_eq!(mom.handles, ["mom@example.com", "(415) 555-0009"]);
+        assert_eq!(mom.relationship_type, "family");
+        assert_eq!(mom.notes.as_deref(), Some("home; cell"));
+        assert!(!add_handle(&mut mom, "4155550009"));
+    }
+
+    #[test]
+    fn test_find_and_apply_duplicates() {
+        let contacts = vec![
+            contact("Mom", "+14155550001", &[]),
+            contact("Alice", "+14155550002", &[]),
+            contact("mom", "+14155550003", &[]),
+            contact("Alice W", "4155550002", &[]),
+            contact("Carol", "+14155550004", &[]),
+            // Linked to Mom through the second Mom's number: transitive
+            contact("Mother", "+1 (415) 555-0003", &[]),
+        ];
+        let groups = find_duplicates(&contacts);
+        assert_eq!(groups.len(), 2);
+
+        let mom = &groups[0];
+        assert_eq!(mom.keep.name, "Mom");
+        assert_eq!(mom.duplicates.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["mom", "Mother"]);
+        assert_eq!(mom.merged.handles, ["+14155550003"]);
+        assert!(mom.reasons.iter().any(|r| r.contains("same name")));
+        assert!(mom.reasons.iter().any(|r| r.contains("same handle 4155550003")));
+
+        assert_eq!(groups[1].keep.name, "Alice");
+        assert!(groups[1].merged.handles.is_empty());
+
+        let merged = apply_merges(&contacts, &groups);
+        assert_eq!(merged.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["Mom", "Alice", "Carol"]);
+        assert_eq!(merged[0].handles, ["+14155550003"]);
+        assert!(find_duplicates(&merged).is_empty());
+    }
+}
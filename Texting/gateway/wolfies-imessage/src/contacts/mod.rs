@@ -1,7 +1,13 @@
 //! Contact management module.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Added merge (duplicate detection and merging)
+//! - 10/16/2026 - Added backup (atomic saves, backups, diff, restore)
+//! - 10/16/2026 - Added classify (unknown-sender kinds, automated heuristics)
 //! - 01/10/2026 - Initial module structure (Claude)
 
 pub mod manager;
 pub mod fuzzy;
+pub mod classify;
+pub mod backup;
+pub mod merge;
@@ -0,0 +1,141 @@
+//! Shared-secret auth and bind checks for the daemon's TCP listener.
+//!
+//! The Unix socket is protected by file permissions (0600); TCP clients
+//! instead send `auth` in every request, compared against a token file
+//! (~/.wolfies-imessage/daemon.token) created 0600 on first TCP start.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Path comes from the data file registry (data_files::DAEMON_TOKEN)
+//! - 10/16/2026 - Initial token file, token check, and loopback bind guard
+
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use crate::data_files;
+
+/// Error code returned when a TCP request's `auth` is missing or wrong.
+pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
+
+/// Default token file (~/.wolfies-imessage/daemon.token).
+pub fn default_token_path() -> PathBuf {
+    data_files::DAEMON_TOKEN.path()
+}
+
+/// Read the token at `path`, creating a random one (mode 0600) if missing.
+pub fn load_or_create_token(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            let token = content.trim().to_string();
+            if token.is_empty() {
+                bail!("Token file {:?} is empty", path);
+            }
+            Ok(token)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory {:?}", parent))?;
+            }
+            let token = format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            );
+
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let mut file = options
+                .open(path)
+                .with_context(|| format!("Failed to create token file {:?}", path))?;
+            file.write_all(token.as_bytes())?;
+            file.sync_all()?;
+            Ok(token)
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to read token file {:?}", path)),
+    }
+}
+
+/// Whether a request's `auth` matches the expected token.
+///
+/// Compares every byte so timing doesn't reveal how much of a guess matched.
+pub fn token_matches(expected: &str, given: Option<&str>) -> bool {
+    let Some(given) = given else {
+        return false;
+    };
+    let (a, b) = (expected.as_bytes(), given.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Parse a `--tcp` value: "HOST:PORT", or a bare port (bound on 127.0.0.1).
+pub fn parse_tcp_addr(value: &str) -> Result<SocketAddr> {
+    if let Ok(port) = value.parse::<u16>() {
+        return Ok(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+    }
+    value
+        .parse()
+        .with_context(|| format!("Invalid --tcp address '{}' (expected 127.0.0.1:PORT)", value))
+}
+
+/// Refuse non-loopback binds unless remote access was explicitly allowed.
+pub fn check_bind(addr: &SocketAddr, allow_remote: bool) -> Result<()> {
+    if !addr.ip().is_loopback() && !allow_remote {
+        bail!(
+            "Refusing to listen on non-loopback address {} without --tcp-allow-remote",
+            addr
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_created_once_with_owner_only_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("daemon.token");
+
+        let token = load_or_create_token(&path).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_eq!(load_or_create_token(&path).unwrap(), token);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("secret", Some("secret")));
+        assert!(!token_matches("secret", Some("secreT")));
+        assert!(!token_matches("secret", Some("secret2")));
+        assert!(!token_matches("secret", None));
+    }
+
+    #[test]
+    fn test_bind_guard() {
+        let local = parse_tcp_addr("8765").unwrap();
+        assert_eq!(local.to_string(), "127.0.0.1:8765");
+        assert!(check_bind(&local, false).is_ok());
+        assert!(check_bind(&parse_tcp_addr("[::1]:8765").unwrap(), false).is_ok());
+
+        let lan = parse_tcp_addr("0.0.0.0:8765").unwrap();
+        assert!(check_bind(&lan, false).is_err());
+        assert!(check_bind(&lan, true).is_ok());
+        assert!(parse_tcp_addr("localhost:x").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Per-user LaunchAgent for the daemon (`wolfies-imessage-daemon install`).
+//!
+//! The agent runs `start --foreground` (launchd owns the process, so no
+//! fork or pid file) at login, and restarts it only after a crash: a clean
+//! `--idle-exit` shutdown stays down until a CLI auto-start brings it back.
+//! All `launchctl` calls go through the `Launchctl` trait so tests never
+//! touch the real launchd.
+//!
+//! launchd opens daemon.log once, in append mode, and never reopens it, so
+//! the daemon rotates it itself at start (`rotate_log`): copy, then
+//! truncate in place, which the appending descriptor follows.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - rotate_log: copy-truncate daemon.log to daemon.log.<timestamp> past a size limit
+//! - 10/16/2026 - Initial plist rendering, install/uninstall, and loaded check
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// LaunchAgent label (and plist file stem).
+pub const LABEL: &str = "com.wolfies.imessage.daemon";
+
+/// ~/Library/LaunchAgents/com.wolfies.imessage.daemon.plist
+pub fn default_plist_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", LABEL))
+}
+
+/// The current user's launchd domain (`gui/<uid>`).
+pub fn user_domain() -> String {
+    // SAFETY: getuid has no preconditions and cannot fail
+    format!("gui/{}", unsafe { libc::getuid() })
+}
+
+/// What the agent runs.
+#[derive(Debug, Clone)]
+pub struct AgentSpec {
+    /// Daemon executable
+    pub program: PathBuf,
+    /// Socket path, already expanded (launchd doesn't expand `~`)
+    pub socket: String,
+    pub idle_exit: Option<u64>,
+    /// Where launchd sends the daemon's stderr
+    pub log_path: PathBuf,
+}
+
+/// Escape text for an XML element.
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// LaunchAgent plist for `spec`.
+pub fn render_plist(spec: &AgentSpec) -> String {
+    let mut args = vec![
+        spec.program.to_string_lossy().to_string(),
+        "start".to_string(),
+        "--foreground".to_string(),
+        "--socket".to_string(),
+        spec.socket.clone(),
+    ];
+    if let Some(idle) = spec.idle_exit {
+        args.push("--idle-exit".to_string());
+        args.push(idle.to_string());
+    }
+    let args: String = args
+        .iter()
+        .map(|arg| format!("\t\t<string>{}</string>\n", xml_escape(arg)))
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Label</key>
+	<string>{label}</string>
+	<key>ProgramArguments</key>
+	<array>
+{args}	</array>
+	<key>RunAtLoad</key>
+	<true/>
+	<key>KeepAlive</key>
+	<dict>
+		<key>SuccessfulExit</key>
+		<false/>
+	</dict>
+	<key>ProcessType</key>
+	<string>Background</string>
+	<key>StandardErrorPath</key>
+	<string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = LABEL,
+        args = args,
+        log = xml_escape(&spec.log_path.to_string_lossy()),
+    )
+}
+
+/// Outcome of one `launchctl` run.
+#[derive(Debug, Clone)]
+pub struct LaunchctlOutput {
+    pub success: bool,
+    pub stderr: String,
+}
+
+/// Runs `launchctl` (mocked in tests).
+pub trait Launchctl {
+    fn run(&self, args: &[&str]) -> Result<LaunchctlOutput>;
+}
+
+/// The real `/bin/launchctl`.
+pub struct SystemLaunchctl;
+
+impl Launchctl for SystemLaunchctl {
+    fn run(&self, args: &[&str]) -> Result<LaunchctlOutput> {
+        let output = Command::new("launchctl").args(args).output().context("Failed to run launchctl")?;
+        Ok(LaunchctlOutput {
+            success: output.status.success(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// daemon.log is rotated at daemon start once it is larger than this.
+pub const LOG_ROTATE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Copy `log` to `<log>.<YYYYmmdd-HHMMSS>` and truncate it, if it is larger
+/// than `max_bytes`. Returns the rotated copy's path. The old copies are
+/// left for `maintenance prune --logs`.
+pub fn rotate_log(log: &Path, max_bytes: u64, now: DateTime<Local>) -> Result<Option<PathBuf>> {
+    match std::fs::metadata(log) {
+        Ok(meta) if meta.len() > max_bytes => {}
+        Ok(_) => return Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", log)),
+    }
+    let rotated = PathBuf::from(format!("{}.{}", log.display(), now.format("%Y%m%d-%H%M%S")));
+    std::fs::copy(log, &rotated).with_context(|| format!("Failed to copy {:?} to {:?}", log, rotated))?;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(log)
+        .and_then(|file| file.set_len(0))
+        .with_context(|| format!("Failed to truncate {:?}", log))?;
+    Ok(Some(rotated))
+}
+
+/// Whether the agent is loaded in `domain`.
+pub fn is_loaded(launchctl: &dyn Launchctl, domain: &str) -> Result<bool> {
+    Ok(launchctl.run(&["print", &format!("{}/{}", domain, LABEL)])?.success)
+}
+
+/// Write the plist and load it, replacing an already-loaded agent.
+pub fn install(launchctl: &dyn Launchctl, domain: &str, plist_path: &Path, spec: &AgentSpec) -> Result<()> {
+    if let Some(parent) = plist_path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    std::fs::write(plist_path, render_plist(spec)).with_context(|| format!("Failed to write {:?}", plist_path))?;
+
+    if is_loaded(launchctl, domain)? {
+        launchctl.run(&["bootout", &format!("{}/{}", domain, LABEL)])?;
+    }
+    let plist = plist_path.to_string_lossy();
+    let output = launchctl.run(&["bootstrap", domain, &plist])?;
+    if !output.success {
+        bail!("launchctl bootstrap {} {} failed: {}", domain, plist, output.stderr);
+    }
+    Ok(())
+}
+
+/// Unload the agent and delete its plist. Returns false when neither was there.
+pub fn uninstall(launchctl: &dyn Launchctl, domain: &str, plist_path: &Path) -> Result<bool> {
+    let loaded = is_loaded(launchctl, domain)?;
+    if loaded {
+        let output = launchctl.run(&["bootout", &format!("{}/{}", domain, LABEL)])?;
+        if !output.success {
+            return Err(anyhow!("launchctl bootout {}/{} failed: {}", domain, LABEL, output.stderr));
+        }
+    }
+    let existed = plist_path.exists();
+    if existed {
+        std::fs::remove_file(plist_path).with_context(|| format!("Failed to remove {:?}", plist_path))?;
+    }
+    Ok(loaded || existed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// Records calls; `print` reports `loaded`, bootstrap/bootout flip it.
+    struct FakeLaunchctl {
+        calls: RefCell<Vec<String>>,
+        loaded: Cell<bool>,
+    }
+
+    impl FakeLaunchctl {
+        fn new(loaded: bool) -> Self {
+            FakeLaunchctl { calls: RefCell::new(Vec::new()), loaded: Cell::new(loaded) }
+        }
+    }
+
+    impl Launchctl for FakeLaunchctl {
+        fn run(&self, args: &[&str]) -> Result<LaunchctlOutput> {
+            self.calls.borrow_mut().push(args.join(" "));
+            let success = match args[0] {
+                "print" => self.loaded.get(),
+                "bootstrap" => !self.loaded.replace(true),
+                "bootout" => self.loaded.replace(false),
+                _ => false,
+            };
+            Ok(LaunchctlOutput { success, stderr: String::new() })
+        }
+    }
+
+    fn spec() -> AgentSpec {
+        AgentSpec {
+            program: PathBuf::from("/Users/sam/Apps & Tools/<bin>/wolfies-imessage-daemon"),
+            socket: "/Users/sam/.wolfies-imessage/daemon \"main\".sock".to_string(),
+            idle_exit: Some(3600),
+            log_path: PathBuf::from("/Users/sam/.wolfies-imessage/daemon.log"),
+        }
+    }
+
+    #[test]
+    fn test_plist_matches_golden_file() {
+        assert_eq!(render_plist(&spec()), include_str!("testdata/launchd.plist"));
+        // Parses as a plist, with the escaped path round-tripping
+        let parsed: plist::Value = plist::from_bytes(render_plist(&spec()).as_bytes()).unwrap();
+        let args = parsed.as_dictionary().unwrap()["ProgramArguments"].as_array().unwrap();
+        assert_eq!(args[0].as_string(), Some("/Users/sam/Apps & Tools/<bin>/wolfies-imessage-daemon"));
+
+        let no_idle = render_plist(&AgentSpec { idle_exit: None, ..spec() });
+        assert!(!no_idle.contains("--idle-exit"));
+    }
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("LaunchAgents").join(format!("{}.plist", LABEL));
+        let launchctl = FakeLaunchctl::new(false);
+
+        install(&launchctl, "gui/501", &path, &spec()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), render_plist(&spec()));
+        let bootstrap = format!("bootstrap gui/501 {}", path.display());
+        assert_eq!(*launchctl.calls.borrow(), vec![format!("print gui/501/{}", LABEL), bootstrap.clone()]);
+        assert!(is_loaded(&launchctl, "gui/501").unwrap());
+
+        // Reinstalling replaces the loaded agent
+        launchctl.calls.borrow_mut().clear();
+        install(&launchctl, "gui/501", &path, &spec()).unwrap();
+        assert_eq!(
+            *launchctl.calls.borrow(),
+            vec![format!("print gui/501/{}", LABEL), format!("bootout gui/501/{}", LABEL), bootstrap]
+        );
+
+        assert!(uninstall(&launchctl, "gui/501", &path).unwrap());
+        assert!(!path.exists());
+        assert!(!is_loaded(&launchctl, "gui/501").unwrap());
+        assert!(!uninstall(&launchctl, "gui/501", &path).unwrap());
+    }
+
+    #[test]
+    fn test_install_reports_bootstrap_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("{}.plist", LABEL));
+        struct Failing;
+        impl Launchctl for Failing {
+            fn run(&self, _args: &[&str]) -> Result<LaunchctlOutput> {
+                Ok(LaunchctlOutput { success: false, stderr: "Bootstrap failed: 5: Input/output error".to_string() })
+            }
+        }
+        let err = install(&Failing, "gui/501", &path, &spec()).unwrap_err();
+        assert!(err.to_string().contains("Input/output error"), "{}", err);
+    }
+
+    #[test]
+    fn test_rotate_log_copies_and_truncates_past_the_limit() {
+        use chrono::TimeZone;
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("daemon.log");
+        let now = Local.with_ymd_and_hms(2026, 10, 16, 9, 30, 0).unwrap();
+        assert_eq!(rotate_log(&log, 10, now).unwrap(), None);
+
+        // Opened the way launchd opens StandardErrorPath
+        let mut writer = std::fs::OpenOptions::new().create(true).append(true).open(&log).unwrap();
+        writer.write_all(b"0123456789").unwrap();
+        assert_eq!(rotate_log(&log, 10, now).unwrap(), None);
+
+        writer.write_all(b"abc").unwrap();
+        let rotated = rotate_log(&log, 10, now).unwrap().unwrap();
+        assert_eq!(rotated, dir.path().join("daemon.log.20261016-093000"));
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap(), "0123456789abc");
+        writer.write_all(b"next").unwrap();
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "next");
+    }
+}
@@ -0,0 +1,473 @@
+//! MCP (Model Context Protocol) server over stdio.
+//!
+//! Speaks newline-delimited JSON-RPC 2.0: `initialize`, `ping`, `tools/list`,
+//! and `tools/call`. Read tools map onto DaemonService methods, so the server
+//! holds the same hot connection and contacts cache as the daemon; tool input
+//! schemas are derived from the argument structs below. The `send` tool is
+//! only listed (and callable) with `--allow-send`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - send tool: phone may be an email (api::send_to_handle)
+//! - 10/16/2026 - text_search args: group
+//! - 10/16/2026 - followup args: include_groups, min_question_score, exclude_automated
+//! - 10/16/2026 - include_muted on recent, unread, bundle args
+//! - 10/16/2026 - send tool goes through api::send / api::send_to_phone
+//! - 10/16/2026 - Initial stdio server with read tools and opt-in send
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use crate::api;
+use crate::daemon::service::DaemonService;
+
+/// MCP protocol revision this server implements.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// ============================================================================
+// Tool Arguments (same names and defaults as the daemon params)
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RecentArgs {
+    /// Days to look back (default 7)
+    pub days: Option<u32>,
+    /// Max rows (default 20)
+    pub limit: Option<u32>,
+    /// "messages" (default) or "conversations" (latest message per chat)
+    pub mode: Option<String>,
+    /// Only contacts with this relationship type (e.g. "family")
+    pub relationship: Option<String>,
+    /// Keep muted conversations in "conversations" mode (default false)
+    pub include_muted: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UnreadArgs {
+    /// Max messages (default 50)
+    pub limit: Option<u32>,
+    /// Only contacts with this relationship type
+    pub relationship: Option<String>,
+    /// Include muted conversations (default false)
+    pub include_muted: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TextSearchArgs {
+    /// Text to search for (single term)
+    pub query: Option<String>,
+    /// Match messages containing any of these terms
+    pub any: Option<Vec<String>>,
+    /// Match messages containing all of these terms
+    pub all: Option<Vec<String>>,
+    /// Only search this group chat (chat ID or display name)
+    pub group: Option<String>,
+    /// Max results (default 50)
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MessagesArgs {
+    /// Contact name, phone number, or email
+    pub contact: String,
+    /// Max messages (default 20)
+    pub limit: Option<u32>,
+    /// Days to look back (default 365)
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AnalyticsArgs {
+    /// Contact name (omit for all conversations)
+    pub contact: Option<String>,
+    /// Days to look back (default 30)
+    pub days: Option<u32>,
+    /// Only contacts with this relationship type
+    pub relationship: Option<String>,
+    /// Include notes-to-self conversations (default false)
+    pub include_self: Option<bool>,
+    /// Return a volume series instead: "hourly", "daily", or "weekly"
+    pub series: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FollowupArgs {
+    /// Days to look back (default 30)
+    pub days: Option<u32>,
+    /// Days without a reply before a conversation counts as stale (default 3)
+    pub stale: Option<u32>,
+    /// Only contacts with this relationship type
+    pub relationship: Option<String>,
+    /// Include notes-to-self conversations (default false)
+    pub include_self: Option<bool>,
+    /// Include questions asked in group chats (default false)
+    pub include_groups: Option<bool>,
+    /// Minimum question score, 0.0-1.0 (default 0.5)
+    pub min_question_score: Option<f64>,
+    /// Leave out automated senders (default false)
+    pub exclude_automated: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BundleArgs {
+    /// Comma-separated sections: unread_count, recent, analytics, followup_count
+    /// (default "unread_count,recent")
+    pub include: Option<String>,
+    pub recent_limit: Option<u32>,
+    pub recent_days: Option<u32>,
+    pub analytics_days: Option<u32>,
+    pub followup_days: Option<u32>,
+    pub followup_stale: Option<u32>,
+    /// Count unread in muted conversations too (default false)
+    pub include_muted: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SendArgs {
+    /// Contact name (resolved via contacts.json); give this or `phone`
+    pub contact: Option<String>,
+    /// Phone number or email to send to directly
+    pub phone: Option<String>,
+    /// Message text
+    pub message: String,
+}
+
+/// One tool: name, description, and its argument schema.
+struct Tool {
+    name: &'static str,
+    description: &'static str,
+    input_schema: fn() -> Value,
+    sends: bool,
+}
+
+fn schema<T: JsonSchema>() -> Value {
+    serde_json::to_value(schemars::schema_for!(T)).unwrap_or_else(|_| json!({"type": "object"}))
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "recent",
+        description: "Recent messages (or conversations) across all chats",
+        input_schema: schema::<RecentArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "unread",
+        description: "Unread incoming messages",
+        input_schema: schema::<UnreadArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "text_search",
+        description: "Search message text for one or more terms",
+        input_schema: schema::<TextSearchArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "messages",
+        description: "Messages with one contact, newest first",
+        input_schema: schema::<MessagesArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "analytics",
+        description: "Message volume, busiest times, and top contacts",
+        input_schema: schema::<AnalyticsArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "followup",
+        description: "Unanswered questions and stale conversations",
+        input_schema: schema::<FollowupArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "bundle",
+        description: "Several dashboard sections in one call",
+        input_schema: schema::<BundleArgs>,
+        sends: false,
+    },
+    Tool {
+        name: "send",
+        description: "Send an iMessage to a contact or phone number",
+        input_schema: schema::<SendArgs>,
+        sends: true,
+    },
+];
+
+// ============================================================================
+// Server
+// ============================================================================
+
+/// MCP server holding the daemon's hot resources.
+pub struct McpServer {
+    service: DaemonService,
+    allow_send: bool,
+}
+
+impl McpServer {
+    /// Create a server with a fresh hot connection and contacts cache.
+    pub fn new(allow_send: bool) -> Result<Self> {
+        Ok(Self::with_service(DaemonService::new()?, allow_send))
+    }
+
+    pub fn with_service(service: DaemonService, allow_send: bool) -> Self {
+        Self { service, allow_send }
+    }
+
+    /// Serve newline-delimited JSON-RPC from `input` until EOF.
+    pub fn serve<R: BufRead, W: Write>(&self, input: R, mut output: W) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one message; None for notifications (no reply).
+    fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e))),
+        };
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return Some(error_response(id.unwrap_or(Value::Null), INVALID_REQUEST, "Missing method"));
+        };
+        // Notifications (no id) get no reply, whatever the method
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "wolfies-imessage", "version": env!("CARGO_PKG_VERSION")},
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": self.list_tools()})),
+            "tools/call" => self.call(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn list_tools(&self) -> Vec<Value> {
+        TOOLS
+            .iter()
+            .filter(|t| self.allow_send || !t.sends)
+            .map(|t| {
+                json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "inputSchema": (t.input_schema)(),
+                })
+            })
+            .collect()
+    }
+
+    /// `tools/call`: protocol problems are JSON-RPC errors; tool failures are
+    /// results with `isError` so the model can see them.
+    fn call(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let tool = TOOLS
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {}", name)))?;
+        if tool.sends && !self.allow_send {
+            return Err((INVALID_PARAMS, format!("Tool '{}' requires --allow-send", name)));
+        }
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        Ok(match self.run_tool(name, arguments) {
+            Ok(value) => json!({
+                "content": [{"type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default()}],
+                "isError": false,
+            }),
+            Err(e) => json!({
+                "content": [{"type": "text", "text": e.to_string()}],
+                "isError": true,
+            }),
+        })
+    }
+
+    fn run_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        match name {
+            "recent" => self.dispatch::<RecentArgs>("recent", arguments),
+            "unread" => self.dispatch::<UnreadArgs>("unread", arguments),
+            "text_search" => self.dispatch::<TextSearchArgs>("text_search", arguments),
+            "messages" => self.dispatch::<MessagesArgs>("messages", arguments),
+            "analytics" => self.dispatch::<AnalyticsArgs>("analytics", arguments),
+            "followup" => self.dispatch::<FollowupArgs>("followup", arguments),
+            "bundle" => self.dispatch::<BundleArgs>("bundle", arguments),
+            "send" => self.send(serde_json::from_value(arguments)?),
+            _ => Err(anyhow!("Unknown tool: {}", name)),
+        }
+    }
+
+    /// Validate arguments against `T`, then hand them to the daemon method as params.
+    fn dispatch<T: DeserializeOwned + Serialize>(&self, method: &str, arguments: Value) -> Result<Value> {
+        let args: T = serde_json::from_value(arguments)?;
+        let params: HashMap<String, Value> = match serde_json::to_value(args)? {
+            // Unset options fall back to the daemon's defaults
+            Value::Object(map) => map.into_iter().filter(|(_, v)| !v.is_null()).collect(),
+            _ => HashMap::new(),
+        };
+        self.service.dispatch(method, params)
+    }
+
+    fn send(&self, args: SendArgs) -> Result<Value> {
+        let result = match (args.contact.as_deref(), args.phone.as_deref()) {
+            (_, Some(phone)) => api::send_to_handle(phone, &args.message)?,
+            (Some(name), None) => api::send(self.service.contacts(), name, &args.message)?,
+            (None, None) => return Err(anyhow!("Give either contact or phone")),
+        };
+        Ok(serde_json::to_value(result)?)
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contacts::manager::{Contact, ContactsManager};
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+    fn server(allow_send: bool) -> McpServer {
+        let conn = empty_db();
+        let ann = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[ann]);
+        let b_chat = insert_chat(&conn, "+14155550002", None, &[bob]);
+        let now = crate::db::queries::days_ago_cocoa(0);
+        insert_message(&conn, a_chat, ann, Some("lunch tomorrow?"), now - DAY_NS, false, false);
+        insert_message(&conn, b_chat, bob, Some("hi there"), now - 2 * DAY_NS, false, true);
+        let contacts = ContactsManager::from_contacts(vec![Contact {
+            name: "Ann Lee".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }]);
+        McpServer::with_service(DaemonService::from_parts(conn, contacts), allow_send)
+    }
+
+    /// Pipe canned lines through `serve` and parse each reply line.
+    fn run(server: &McpServer, lines: &[Value]) -> Vec<Value> {
+        let input: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    fn tool_text(reply: &Value) -> Value {
+        serde_json::from_str(reply["result"]["content"][0]["text"].as_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_initialize_list_and_call() {
+        let server = server(false);
+        let replies = run(
+            &server,
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+                    "protocolVersion": PROTOCOL_VERSION, "capabilities": {},
+                    "clientInfo": {"name": "test", "version": "0"}}}),
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+                json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                       "params": {"name": "messages", "arguments": {"contact": "Ann"}}}),
+                json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+                       "params": {"name": "unread", "arguments": {}}}),
+            ],
+        );
+
+        // The notification gets no reply
+        assert_eq!(replies.len(), 4);
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(replies[0]["result"]["capabilities"]["tools"].is_object());
+
+        let tools: Vec<&str> = replies[1]["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(tools, ["recent", "unread", "text_search", "messages", "analytics", "followup", "bundle"]);
+        assert_eq!(replies[1]["result"]["tools"][3]["inputSchema"]["title"], "MessagesArgs");
+
+        assert_eq!(replies[2]["result"]["isError"], false);
+        let messages = tool_text(&replies[2]);
+        assert_eq!(messages["contact"], "Ann Lee");
+        assert_eq!(messages["count"], 1);
+        assert_eq!(messages["messages"][0]["text"], "lunch tomorrow?");
+
+        assert_eq!(tool_text(&replies[3])["unread_count"], 1);
+    }
+
+    #[test]
+    fn test_errors_and_send_gate() {
+        let replies = run(
+            &server(false),
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                       "params": {"name": "send", "arguments": {"contact": "Ann", "message": "hi"}}}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                       "params": {"name": "recent", "arguments": {"limit": "ten"}}}),
+                json!({"jsonrpc": "2.0", "id": 3, "method": "resources/list"}),
+            ],
+        );
+        assert_eq!(replies[0]["error"]["code"], INVALID_PARAMS);
+        // Bad arguments are a tool error the model can read, not a protocol error
+        assert_eq!(replies[1]["result"]["isError"], true);
+        assert_eq!(replies[2]["error"]["code"], METHOD_NOT_FOUND);
+
+        let mut bad = Vec::new();
+        server(false).serve("not json\n".as_bytes(), &mut bad).unwrap();
+        let reply: Value = serde_json::from_slice(&bad).unwrap();
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+
+        let with_send = server(true);
+        let listed = run(&with_send, &[json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"})]);
+        assert!(listed[0]["result"]["tools"].as_array().unwrap().iter().any(|t| t["name"] == "send"));
+    }
+}
@@ -1,8 +1,14 @@
 //! Daemon mode implementation: persistent server with hot resources.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Added launchd (LaunchAgent install/uninstall)
+//! - 10/16/2026 - Added mcp (MCP stdio server over DaemonService)
+//! - 10/16/2026 - Added auth (TCP token and bind guard)
 //! - 01/10/2026 - Initial module structure (Phase 4C, Claude)
 
+pub mod auth;
+pub mod launchd;
+pub mod mcp;
 pub mod protocol;
 pub mod server;
 pub mod service;
@@ -1,23 +1,99 @@
 //! Daemon protocol types for NDJSON communication over UNIX socket.
 //!
+//! Versions: v1 is the original method/params protocol. v2 adds
+//! protocol-level options in `params` (`fields`, `max_text_chars`,
+//! `minimal`, `compact`, `timeout_ms`) that the server applies itself;
+//! under v1 those keys are ordinary params. Requests newer than
+//! `PROTOCOL_V` are rejected with `UNSUPPORTED_PROTOCOL` and every response
+//! reports the server's range in `meta`, so clients can downgrade.
+//!
+//! A v2 request with `trace: true` gets the daemon's log lines for that one
+//! call in `meta.trace` (every request is also logged with its id, method,
+//! duration and outcome, so client errors can be matched to daemon logs).
+//! Problems a request worked around (a corrupt contacts.json, a skipped
+//! row, an unknown bundle section) come back in `meta.warnings`.
+//!
+//! Follow-up methods (both versions): `followup` accepts `include_groups`,
+//! `min_question_score`, `exclude_automated` and `include_system` next to
+//! `days`, `stale`, `relationship` and `include_self`, and leaves out snoozed contacts
+//! (`snoozed_count`). `followup_snooze` (`contact`, `until`, default "1d")
+//! returns the stored snooze; `followup_unsnooze` (`contact`) returns
+//! `{unsnoozed, snooze}`. Snoozes live in snoozes.json, shared with the
+//! CLI's `followup snooze`.
+//!
+//! `quick` (`pins`, default the config's `pinned`) returns `{pins,
+//! unread_total}`: per pin its name, `unread_count`, `last` conversation row
+//! and, for pins matching nothing, a `warning`. Cheap enough to poll.
+//!
+//! `unread_by_chat` (`include_muted`) returns `{chats, muted_count}`: per
+//! conversation with unread messages its `chat_identifier`, `name`,
+//! `unread_count`, `oldest_unread_date` and `is_group`, counted past the
+//! chat's read position (chat.last_read_message_timestamp) where it has one.
+//!
+//! `batch` (`requests`: ordered array of `{method, params}`) runs each item
+//! in order on the same service and returns an array of full responses, one
+//! per item (own `ok`/`error` and `meta.server_ms`). A failing item doesn't
+//! stop the rest. Items take their own v2 options; `fields`,
+//! `max_text_chars` and the remaining `timeout_ms` default to the batch's.
+//! Batches don't nest.
+//!
+//! `bundle` takes `max_tokens` (about 4 characters each) and
+//! `section_priority`: an oversized bundle loses its oldest messages, then
+//! text, then whole sections, and says so in `meta.trimmed` (in the result,
+//! like the CLI's `bundle --max-tokens`).
+//!
+//! Messages carry `item_kind` (message, group_rename, member_change,
+//! facetime, payment, sticker, unknown); system items get a `placeholder`.
+//! `text_search` and `followup` leave system items out unless
+//! `include_system: true`.
+//!
 //! CHANGELOG:
+//! - 10/16/2026 - Documented bundle max_tokens / section_priority
+//! - 10/16/2026 - Documented item_kind and include_system (text_search, followup)
+//! - 10/16/2026 - batch method (ordered sub-requests, per-item responses), INVALID_PARAMS
+//! - 10/16/2026 - Documented unread_by_chat
+//! - 10/16/2026 - meta.warnings (non-fatal issues a request worked around)
+//! - 10/16/2026 - `trace` option (per-request log excerpt in meta.trace)
+//! - 10/16/2026 - Documented quick
+//! - 10/16/2026 - Documented followup params and followup_snooze/followup_unsnooze
+//! - 10/16/2026 - Protocol v2 (RequestOptions), meta.min_supported, UNSUPPORTED_PROTOCOL
+//! - 10/16/2026 - Request carries optional `auth` (TCP shared-secret token)
 //! - 01/10/2026 - Initial implementation (Phase 4C, Claude)
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Newest protocol version this daemon speaks.
+pub const PROTOCOL_V: u8 = 2;
+
+/// Oldest protocol version still accepted.
+pub const MIN_SUPPORTED_V: u8 = 1;
+
+/// Error code for requests whose `v` is outside the supported range.
+pub const UNSUPPORTED_PROTOCOL: &str = "UNSUPPORTED_PROTOCOL";
+
+/// Method that runs an ordered list of sub-requests in one exchange.
+pub const BATCH_METHOD: &str = "batch";
+
+/// Error code for a malformed batch or batch item.
+pub const INVALID_PARAMS: &str = "INVALID_PARAMS";
 
 /// NDJSON request from client to daemon.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Request {
     /// Unique request ID (UUID)
     pub id: String,
-    /// Protocol version (currently 1)
+    /// Protocol version the client speaks
     pub v: u8,
     /// Method name (e.g., "health", "analytics", "bundle")
     pub method: String,
     /// Method parameters (flexible key-value map)
     pub params: HashMap<String, serde_json::Value>,
+    /// Shared-secret token (required on TCP, ignored on the Unix socket)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<String>,
 }
 
 /// NDJSON response from daemon to client.
@@ -51,8 +127,44 @@ pub struct ErrorInfo {
 pub struct ResponseMeta {
     /// Server execution time in milliseconds
     pub server_ms: f64,
-    /// Protocol version
+    /// Newest protocol version the server supports
     pub protocol_v: u8,
+    /// Oldest protocol version the server still accepts
+    pub min_supported: u8,
+    /// Daemon log lines for this request (only when it asked for `trace`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<String>>,
+    /// Non-fatal problems the request worked around (omitted when none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Protocol-level options carried in a v2 request's `params`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RequestOptions {
+    /// Comma-separated field allowlist applied to the result
+    pub fields: Option<String>,
+    /// Truncate text fields in the result to this many characters
+    pub max_text_chars: Option<u32>,
+    /// Accepted for parity with the CLI; responses are always compact NDJSON
+    pub minimal: bool,
+    pub compact: bool,
+    /// Abort the request (code TIMEOUT) once it runs this long
+    pub timeout: Option<Duration>,
+    /// Return the request's log lines in `meta.trace`
+    pub trace: bool,
+}
+
+impl RequestOptions {
+    /// These options, with `fields` and `max_text_chars` taken from `batch`
+    /// where unset (a batch item under its batch's options).
+    pub fn inheriting(self, batch: &RequestOptions) -> Self {
+        Self {
+            fields: self.fields.or_else(|| batch.fields.clone()),
+            max_text_chars: self.max_text_chars.or(batch.max_text_chars),
+            ..self
+        }
+    }
 }
 
 impl Request {
@@ -60,6 +172,60 @@ impl Request {
     pub fn from_ndjson_line(line: &str) -> Result<Self> {
         serde_json::from_str(line).context("Failed to parse request JSON")
     }
+
+    /// Whether a server supporting up to `supported` can serve this request.
+    pub fn is_supported(&self, supported: u8) -> bool {
+        (MIN_SUPPORTED_V..=supported).contains(&self.v)
+    }
+
+    /// Split a batch request's `requests` into sub-requests, one per item, with
+    /// ids `<batch id>.<index>`. Fails for a batch without a `requests`
+    /// array; an item that isn't `{method, params}` comes back as its
+    /// `INVALID_PARAMS` response instead, so it fails alone.
+    pub fn batch_items(&mut self) -> std::result::Result<Vec<std::result::Result<Request, Box<Response>>>, String> {
+        let Some(serde_json::Value::Array(items)) = self.params.remove("requests") else {
+            return Err("batch needs a `requests` array of {method, params}".to_string());
+        };
+        let items = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let id = format!("{}.{}", self.id, index);
+                let method = item.get("method").and_then(|m| m.as_str()).map(str::to_string);
+                let params = match item.get("params") {
+                    None | Some(serde_json::Value::Null) => Some(HashMap::new()),
+                    Some(serde_json::Value::Object(params)) => Some(params.clone().into_iter().collect()),
+                    Some(_) => None,
+                };
+                let invalid = |id, message: &str| Err(Box::new(Response::error(id, INVALID_PARAMS, message.to_string(), 0.0)));
+                match (method, params) {
+                    (Some(method), _) if method == BATCH_METHOD => invalid(id, "Batches don't nest"),
+                    (Some(method), Some(params)) => Ok(Request { id, v: self.v, method, params, auth: None }),
+                    _ => invalid(id, "Each batch item needs a string `method` and an object `params`"),
+                }
+            })
+            .collect();
+        Ok(items)
+    }
+
+    /// Take the v2 protocol options out of `params`.
+    ///
+    /// v1 requests get defaults and keep their params untouched, so a v1
+    /// method param that happens to share a name is never reinterpreted.
+    pub fn take_options(&mut self) -> RequestOptions {
+        if self.v < 2 {
+            return RequestOptions::default();
+        }
+        let mut take = |key: &str| self.params.remove(key);
+        RequestOptions {
+            fields: take("fields").and_then(|v| v.as_str().map(str::to_string)),
+            max_text_chars: take("max_text_chars").and_then(|v| v.as_u64()).map(|n| n as u32),
+            minimal: take("minimal").and_then(|v| v.as_bool()).unwrap_or(false),
+            compact: take("compact").and_then(|v| v.as_bool()).unwrap_or(false),
+            timeout: take("timeout_ms").and_then(|v| v.as_u64()).map(Duration::from_millis),
+            trace: take("trace").and_then(|v| v.as_bool()).unwrap_or(false),
+        }
+    }
 }
 
 impl Response {
@@ -72,7 +238,10 @@ impl Response {
             error: None,
             meta: ResponseMeta {
                 server_ms,
-                protocol_v: 1,
+                protocol_v: PROTOCOL_V,
+                min_supported: MIN_SUPPORTED_V,
+                trace: None,
+                warnings: Vec::new(),
             },
         }
     }
@@ -90,11 +259,32 @@ impl Response {
             }),
             meta: ResponseMeta {
                 server_ms,
-                protocol_v: 1,
+                protocol_v: PROTOCOL_V,
+                min_supported: MIN_SUPPORTED_V,
+                trace: None,
+                warnings: Vec::new(),
             },
         }
     }
 
+    /// Report `supported` as the server's newest version (older servers in tests).
+    pub fn with_protocol(mut self, supported: u8) -> Self {
+        self.meta.protocol_v = supported;
+        self
+    }
+
+    /// Attach the request's log excerpt.
+    pub fn with_trace(mut self, lines: Vec<String>) -> Self {
+        self.meta.trace = Some(lines);
+        self
+    }
+
+    /// Attach the warnings reported while handling the request.
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.meta.warnings = warnings;
+        self
+    }
+
     /// Serialize response to NDJSON line.
     pub fn to_ndjson_line(&self) -> Result<String> {
         let json = serde_json::to_string(self)?;
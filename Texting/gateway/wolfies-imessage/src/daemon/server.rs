@@ -1,35 +1,178 @@
 //! UNIX socket server for daemon mode.
 //!
 //! Listens on a UNIX socket, accepts connections, and dispatches requests
-//! to DaemonService.
+//! to DaemonService. An optional TCP listener speaks the same NDJSON
+//! protocol on its own thread; its requests must carry the shared-secret
+//! `auth` token. Requests from both listeners go through one service (and one
+//! hot connection), one at a time.
+//!
+//! Requests are checked against the protocol range in `protocol` before
+//! anything else is interpreted; v2 options are applied here, around dispatch.
+//!
+//! Each request is logged (tracing) with its id, method, duration and
+//! outcome. Dispatch runs in a `request{id, method}` span; for `trace`
+//! requests its events are captured on this thread and returned in
+//! `meta.trace` instead of going to the daemon log. Warnings reported
+//! during dispatch (`output::warn`) are collected per request, under the
+//! service lock, and returned in `meta.warnings`.
+//!
+//! Every connection gets a read timeout, so a client that connects and never
+//! sends its line can't hold the single-threaded accept loop. With an idle
+//! exit set, a watcher thread shuts the server down (removing the socket and
+//! pid file) once no request has arrived for that long and the scheduled-send
+//! worker it hosts has no unsent jobs (nothing else would send them). Clients
+//! that connected before the socket was removed are still answered.
+//!
+//! `batch` requests are unpacked here too: each item runs through the same
+//! path as a top-level request (options, timeout, warnings, log line), in
+//! order, and its response goes into the batch's result array.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - At shutdown, the connection being served and any still queued (after the socket is removed) are answered
+//! - 10/16/2026 - Idle exit waits while the schedule store has unsent jobs; added with_schedule_store
+//! - 10/16/2026 - batch method: items run in order through the single-request path, one response each
+//! - 10/16/2026 - Warnings reported during dispatch (output::warn) are returned in meta.warnings and logged
+//! - 10/16/2026 - Per-request tracing log (id, method, duration, outcome) and `trace` log excerpts in meta
+//! - 10/16/2026 - Idle auto-shutdown (with_idle_exit) and per-connection read timeouts on both listeners
+//! - 10/16/2026 - Error responses carry CliError codes (e.g. AUTOMATION_DENIED) instead of ERROR
+//! - 10/16/2026 - Socket test: followup_snooze suppresses the contact in followup
+//! - 10/16/2026 - Added with_service (serve a pre-built service, used by benches)
+//! - 10/16/2026 - Protocol version check (UNSUPPORTED_PROTOCOL) and v2 output controls / timeout_ms
+//! - 10/16/2026 - Optional token-authenticated TCP listener (with_tcp)
+//! - 10/16/2026 - Start scheduled-send worker thread on serve
 //! - 01/10/2026 - Initial implementation (Phase 4C, Claude)
 
 use anyhow::Result;
-use std::io::{BufRead, BufReader, Write};
-use std::os::unix::net::{UnixListener, UnixStream};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
 use std::path::Path;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::daemon::{auth, protocol, service::DaemonService};
+use crate::error;
+use crate::output::{self, OutputControls};
+use crate::scheduler::{self, ScheduleStore};
+
+/// How long a client may take to send its request line.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
 
-use crate::daemon::{protocol, service::DaemonService};
+/// PID file written next to the socket when the daemon forks.
+pub fn pid_file_path(socket_path: &str) -> String {
+    format!("{}.pid", socket_path)
+}
+
+/// TCP listener settings.
+#[derive(Debug, Clone)]
+pub struct TcpOptions {
+    pub addr: SocketAddr,
+    /// Every TCP request's `auth` must equal this.
+    pub token: String,
+}
 
 /// Daemon server listening on UNIX socket.
 pub struct DaemonServer {
-    service: DaemonService,
+    service: Arc<Mutex<DaemonService>>,
     socket_path: String,
+    tcp: Option<TcpOptions>,
+    idle_exit: Option<Duration>,
+    read_timeout: Duration,
+    /// Store the scheduled-send worker reads (None: no worker)
+    scheduler: Option<ScheduleStore>,
+    activity: Arc<Activity>,
+}
+
+/// Request activity shared by both listeners and the idle watcher.
+struct Activity {
+    last: Mutex<Instant>,
+    /// Connections being served right now (never idle while > 0)
+    busy: AtomicUsize,
+    shutdown: AtomicBool,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Activity { last: Mutex::new(Instant::now()), busy: AtomicUsize::new(0), shutdown: AtomicBool::new(false) }
+    }
+
+    fn touch(&self) {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// Time since the last request, or zero while one is in flight.
+    fn idle_for(&self) -> Duration {
+        if self.busy.load(Ordering::SeqCst) > 0 {
+            return Duration::ZERO;
+        }
+        self.last.lock().unwrap_or_else(|e| e.into_inner()).elapsed()
+    }
+
+    /// Serve one connection. It holds off the idle exit while open, but only
+    /// an answered request resets the idle clock (a bare connect, like
+    /// `daemon status`, doesn't).
+    fn track(&self, serve: impl FnOnce() -> Result<bool>) -> Result<()> {
+        self.busy.fetch_add(1, Ordering::SeqCst);
+        let result = serve();
+        if matches!(result, Ok(true)) {
+            self.touch();
+        }
+        self.busy.fetch_sub(1, Ordering::SeqCst);
+        result.map(|_| ())
+    }
 }
 
 impl DaemonServer {
     /// Create new daemon server.
     pub fn new(socket_path: impl AsRef<Path>) -> Result<Self> {
-        let socket_path = socket_path.as_ref().to_string_lossy().to_string();
-        let service = DaemonService::new()?;
+        Ok(Self::with_service(socket_path, DaemonService::new()?))
+    }
 
-        Ok(Self {
-            service,
-            socket_path,
-        })
+    /// Server around an already-built service (e.g. over a fixture database).
+    pub fn with_service(socket_path: impl AsRef<Path>, service: DaemonService) -> Self {
+        Self {
+            service: Arc::new(Mutex::new(service)),
+            socket_path: socket_path.as_ref().to_string_lossy().to_string(),
+            tcp: None,
+            idle_exit: None,
+            read_timeout: READ_TIMEOUT,
+            scheduler: Some(ScheduleStore::open_default()),
+            activity: Arc::new(Activity::new()),
+        }
+    }
+
+    /// Exit cleanly after `idle` with no requests (socket and pid file
+    /// removed), once no scheduled sends are left.
+    pub fn with_idle_exit(mut self, idle: Duration) -> Self {
+        self.idle_exit = Some(idle);
+        self
+    }
+
+    /// How long each connection may take to send its request line.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Don't start the scheduled-send worker (tests and benches).
+    pub fn without_scheduler(mut self) -> Self {
+        self.scheduler = None;
+        self
+    }
+
+    /// Run the scheduled-send worker over `store` instead of the default one.
+    pub fn with_schedule_store(mut self, store: ScheduleStore) -> Self {
+        self.scheduler = Some(store);
+        self
+    }
+
+    /// Also listen on TCP. Bind checks (loopback unless allowed) are the caller's job.
+    pub fn with_tcp(mut self, tcp: TcpOptions) -> Self {
+        self.tcp = Some(tcp);
+        self
     }
 
     /// Start serving requests (blocking).
@@ -51,63 +194,811 @@ impl DaemonServer {
 
         eprintln!("[daemon] listening on {}", self.socket_path);
 
+        let _tcp = match self.tcp {
+            Some(ref tcp) => Some(self.spawn_tcp(tcp)?),
+            None => None,
+        };
+
+        // Scheduled sends run on their own thread; the accept loop stays single-threaded
+        let _scheduler = self
+            .scheduler
+            .as_ref()
+            .map(|store| scheduler::spawn_worker(ScheduleStore::new(store.path())));
+
+        let _watcher = match self.idle_exit {
+            Some(idle) => Some(self.spawn_idle_watcher(idle)?),
+            None => None,
+        };
+
+        let serve_one = |stream: UnixStream| {
+            let result = stream.set_read_timeout(Some(self.read_timeout)).map_err(anyhow::Error::from);
+            if let Err(e) = result.and_then(|()| self.activity.track(|| handle_connection(&self.service, &stream, None))) {
+                eprintln!("[daemon] connection error: {}", e);
+            }
+        };
+
         // Accept connections sequentially (single-threaded)
         for stream in listener.incoming() {
             match stream {
-                Ok(stream) => {
-                    if let Err(e) = self.handle_connection(stream) {
-                        eprintln!("[daemon] connection error: {}", e);
-                    }
+                Ok(stream) => serve_one(stream),
+                Err(e) => eprintln!("[daemon] connection error: {}", e),
+            }
+            // Checked after serving, so a client accepted just as the idle
+            // watcher fired still gets its response (the watcher's own wake-up
+            // connection sends nothing and is answered with nothing)
+            if self.activity.shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        // Only the idle watcher stops the loop
+        if let Some(idle) = self.idle_exit {
+            eprintln!("[daemon] idle for {}s, exiting", idle.as_secs());
+        }
+        // No new clients once the socket is gone; answer the ones already queued
+        let _ = std::fs::remove_file(&self.socket_path);
+        let _ = std::fs::remove_file(pid_file_path(&self.socket_path));
+        listener.set_nonblocking(true)?;
+        while let Ok((stream, _)) = listener.accept() {
+            // Accepted sockets inherit non-blocking mode on macOS
+            stream.set_nonblocking(false)?;
+            serve_one(stream);
+        }
+        Ok(())
+    }
+
+    /// Watch for `idle` without requests, then stop the accept loop (woken by
+    /// a connection of our own). While the schedule store has unsent jobs the
+    /// daemon stays up, checking again every `idle`.
+    fn spawn_idle_watcher(&self, idle: Duration) -> Result<JoinHandle<()>> {
+        let activity = Arc::clone(&self.activity);
+        let socket_path = self.socket_path.clone();
+        let schedule = self.scheduler.as_ref().map(|store| ScheduleStore::new(store.path()));
+        let handle = std::thread::Builder::new()
+            .name("daemon-idle".to_string())
+            .spawn(move || loop {
+                let idle_for = activity.idle_for();
+                if idle_for < idle {
+                    std::thread::sleep((idle - idle_for).max(Duration::from_millis(10)));
+                } else if schedule.as_ref().is_some_and(ScheduleStore::has_unsent) {
+                    std::thread::sleep(idle);
+                } else {
+                    activity.shutdown.store(true, Ordering::SeqCst);
+                    let _ = UnixStream::connect(&socket_path);
+                    return;
                 }
-                Err(e) => {
-                    eprintln!("[daemon] accept error: {}", e);
+            })?;
+        Ok(handle)
+    }
+
+    /// Bind the TCP listener and accept on a background thread.
+    fn spawn_tcp(&self, tcp: &TcpOptions) -> Result<JoinHandle<()>> {
+        let listener = TcpListener::bind(tcp.addr)?;
+        eprintln!("[daemon] listening on tcp {}", listener.local_addr()?);
+
+        let service = Arc::clone(&self.service);
+        let activity = Arc::clone(&self.activity);
+        let read_timeout = self.read_timeout;
+        let token = tcp.token.clone();
+        let handle = std::thread::Builder::new()
+            .name("daemon-tcp".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let result = stream.map_err(anyhow::Error::from).and_then(|stream| {
+                        stream.set_read_timeout(Some(read_timeout))?;
+                        activity.track(|| handle_connection(&service, &stream, Some(&token)))
+                    });
+                    if let Err(e) = result {
+                        eprintln!("[daemon] tcp connection error: {}", e);
+                    }
                 }
-            }
+            })?;
+        Ok(handle)
+    }
+}
+
+/// Handle a single client connection (one request line, one response line).
+/// Returns whether a request was answered.
+///
+/// `token` is the required `auth` value, or None when the transport is
+/// trusted (the owner-only Unix socket).
+fn handle_connection<S>(service: &Mutex<DaemonService>, stream: S, token: Option<&str>) -> Result<bool>
+where
+    S: Read + Write + Copy,
+{
+    let mut reader = BufReader::new(stream);
+    let mut writer = stream;
+
+    // Read NDJSON request (one line)
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    if line.trim().is_empty() {
+        return Ok(false); // Client disconnected
+    }
+
+    let response = respond(service, &line, token)?;
+
+    // Send NDJSON response
+    let response_line = response.to_ndjson_line()?;
+    writer.write_all(response_line.as_bytes())?;
+    writer.flush()?;
+
+    Ok(true)
+}
+
+/// Authenticate and dispatch one request line.
+fn respond(service: &Mutex<DaemonService>, line: &str, token: Option<&str>) -> Result<protocol::Response> {
+    respond_as(service, line, token, protocol::PROTOCOL_V)
+}
+
+/// `respond` for a server speaking protocol versions up to `supported`.
+fn respond_as(
+    service: &Mutex<DaemonService>,
+    line: &str,
+    token: Option<&str>,
+    supported: u8,
+) -> Result<protocol::Response> {
+    let start = Instant::now();
+
+    // Parse request
+    let request = protocol::Request::from_ndjson_line(line)?;
+    let (id, method) = (request.id.clone(), request.method.clone());
+
+    let response = check_and_dispatch(service, request, token, supported, start).with_protocol(supported);
+    log_request(&id, &method, &response, start.elapsed());
+    Ok(response)
+}
+
+/// Authenticate, check the version, and dispatch (captured when `trace` is set).
+fn check_and_dispatch(
+    service: &Mutex<DaemonService>,
+    mut request: protocol::Request,
+    token: Option<&str>,
+    supported: u8,
+    start: Instant,
+) -> protocol::Response {
+    let elapsed_ms = || start.elapsed().as_secs_f64() * 1000.0;
+
+    if let Some(expected) = token {
+        if !auth::token_matches(expected, request.auth.as_deref()) {
+            return protocol::Response::error(
+                request.id,
+                auth::UNAUTHORIZED,
+                "Missing or invalid auth token".to_string(),
+                elapsed_ms(),
+            );
         }
+    }
+
+    // Refuse rather than guess at params from a protocol we don't know
+    if !request.is_supported(supported) {
+        return protocol::Response::error(
+            request.id,
+            protocol::UNSUPPORTED_PROTOCOL,
+            format!(
+                "Protocol v{} not supported (server supports v{}-v{})",
+                request.v,
+                protocol::MIN_SUPPORTED_V,
+                supported
+            ),
+            elapsed_ms(),
+        );
+    }
+
+    let options = request.take_options();
+    if request.method == protocol::BATCH_METHOD {
+        return run_batch(service, request, options, start);
+    }
+    run(service, request, options, start)
+}
+
+/// Dispatch one request under its options (output controls, timeout, trace).
+fn run(
+    service: &Mutex<DaemonService>,
+    mut request: protocol::Request,
+    options: protocol::RequestOptions,
+    start: Instant,
+) -> protocol::Response {
+    let elapsed_ms = || start.elapsed().as_secs_f64() * 1000.0;
+    let controls = OutputControls {
+        fields: options.fields,
+        max_text_chars: options.max_text_chars,
+        ..Default::default()
+    };
+
+    // Dispatch to service
+    let params = std::mem::take(&mut request.params);
+    let dispatch = || {
+        let _span = tracing::info_span!("request", id = %request.id, method = %request.method).entered();
+        tracing::debug!(params = ?params.keys().collect::<Vec<_>>(), timeout = ?options.timeout, "dispatching");
+        let service = service.lock().unwrap_or_else(|e| e.into_inner());
+        // Leftovers (e.g. the scheduler thread's) belong to no request: log only
+        for warning in output::take_warnings() {
+            tracing::warn!(warning = %warning, "worked around, outside a request");
+        }
+        let result = service.dispatch_within(&request.method, params, options.timeout);
+        let warnings = output::take_warnings();
+        for warning in &warnings {
+            tracing::warn!(warning = %warning, "worked around");
+        }
+        tracing::debug!(ok = result.is_ok(), elapsed_ms = elapsed_ms(), "dispatched");
+        (result, warnings)
+    };
+    let ((result, warnings), trace) = if options.trace {
+        let (result, lines) = capture_log(dispatch);
+        (result, Some(lines))
+    } else {
+        (dispatch(), None)
+    };
+
+    let response = match result {
+        Ok(result) => protocol::Response::success(request.id, controls.shape(result), elapsed_ms()),
+        Err(_) if options.timeout.is_some_and(|t| start.elapsed() >= t) => protocol::Response::error(
+            request.id,
+            "TIMEOUT",
+            format!("Request exceeded timeout_ms ({} ms)", options.timeout.unwrap_or_default().as_millis()),
+            elapsed_ms(),
+        ),
+        Err(e) => protocol::Response::error(
+            request.id,
+            error::error_code(&e).unwrap_or("ERROR"),
+            e.to_string(),
+            elapsed_ms(),
+        ),
+    };
+    let response = response.with_warnings(warnings);
+    match trace {
+        Some(lines) => response.with_trace(lines),
+        None => response,
+    }
+}
+
+/// Run a batch's items in order, each through `run` with its own response;
+/// a failing item doesn't stop the ones after it. The batch's `trace`
+/// covers every item.
+fn run_batch(
+    service: &Mutex<DaemonService>,
+    mut request: protocol::Request,
+    options: protocol::RequestOptions,
+    start: Instant,
+) -> protocol::Response {
+    let elapsed_ms = || start.elapsed().as_secs_f64() * 1000.0;
+    let items = match request.batch_items() {
+        Ok(items) => items,
+        Err(message) => return protocol::Response::error(request.id, protocol::INVALID_PARAMS, message, elapsed_ms()),
+    };
+    let deadline = options.timeout.map(|timeout| start + timeout);
+    let run_items = || {
+        items
+            .into_iter()
+            .map(|item| match item {
+                Ok(item) => run_item(service, item, &options, deadline),
+                Err(invalid) => *invalid,
+            })
+            .collect::<Vec<_>>()
+    };
+    let (responses, trace) = if options.trace {
+        let (responses, lines) = capture_log(run_items);
+        (responses, Some(lines))
+    } else {
+        (run_items(), None)
+    };
 
+    let result = serde_json::to_value(responses).unwrap_or_default();
+    let response = protocol::Response::success(request.id, result, elapsed_ms());
+    match trace {
+        Some(lines) => response.with_trace(lines),
+        None => response,
+    }
+}
+
+/// Run one batch item under the batch's options, with whatever is left of
+/// the batch's timeout unless the item sets its own.
+fn run_item(
+    service: &Mutex<DaemonService>,
+    mut item: protocol::Request,
+    batch: &protocol::RequestOptions,
+    deadline: Option<Instant>,
+) -> protocol::Response {
+    let start = Instant::now();
+    let (id, method) = (item.id.clone(), item.method.clone());
+    let mut options = item.take_options().inheriting(batch);
+    let remaining = deadline.map(|deadline| deadline.saturating_duration_since(start));
+    let response = if options.timeout.is_none() && remaining == Some(Duration::ZERO) {
+        protocol::Response::error(item.id, "TIMEOUT", "Batch timeout_ms ran out before this item".to_string(), 0.0)
+    } else {
+        options.timeout = options.timeout.or(remaining);
+        run(service, item, options, start)
+    };
+    log_request(&id, &method, &response, start.elapsed());
+    response
+}
+
+/// One log line per answered request, so client errors can be matched by id.
+fn log_request(id: &str, method: &str, response: &protocol::Response, elapsed: Duration) {
+    let duration_ms = elapsed.as_secs_f64() * 1000.0;
+    match response.error {
+        None => tracing::info!(id = %id, method = %method, duration_ms, outcome = "ok", "request"),
+        Some(ref error) => tracing::warn!(
+            id = %id,
+            method = %method,
+            duration_ms,
+            outcome = %error.code,
+            error = %error.message,
+            "request failed"
+        ),
+    }
+}
+
+/// Shared buffer the capture subscriber writes formatted events into.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
         Ok(())
     }
+}
 
-    /// Handle a single client connection.
-    fn handle_connection(&self, stream: UnixStream) -> Result<()> {
-        // Clone stream for writer (UNIX sockets support try_clone)
-        let writer_stream = stream.try_clone()?;
-        let mut reader = BufReader::new(&stream);
-        let mut writer = writer_stream;
+/// Run `f` with this thread's tracing events (debug and up) captured rather
+/// than sent to the daemon log; returns them as plain text lines.
+fn capture_log<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_target(false)
+        .without_time()
+        .with_writer(move || writer.clone())
+        .finish();
+    let value = tracing::subscriber::with_default(subscriber, f);
+    let bytes = buffer.0.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let lines = String::from_utf8_lossy(&bytes).lines().map(str::to_string).collect();
+    (value, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contacts::manager::ContactsManager;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+    use crate::db::queries;
+    use crate::snoozes::SnoozeStore;
+    use serde_json::{json, Value};
+    use std::net::TcpStream;
+
+    const TOKEN: &str = "test-token";
 
-        // Read NDJSON request (one line)
+    fn service() -> Mutex<DaemonService> {
+        Mutex::new(DaemonService::from_parts(empty_db(), ContactsManager::empty()))
+    }
+
+    /// Send `request` on `client`, serve it from `server`, and return the response JSON.
+    fn exchange<S: Read + Write + Copy>(
+        service: &Mutex<DaemonService>,
+        client: S,
+        server: S,
+        request: &Value,
+        token: Option<&str>,
+    ) -> Value {
+        let mut writer = client;
+        writer.write_all(format!("{}\n", request).as_bytes()).unwrap();
+        handle_connection(service, server, token).unwrap();
         let mut line = String::new();
-        reader.read_line(&mut line)?;
+        BufReader::new(client).read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn tcp_exchange(service: &Mutex<DaemonService>, request: &Value) -> Value {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        exchange(service, &client, &server, request, Some(TOKEN))
+    }
+
+    fn without_timing(mut response: Value) -> Value {
+        response["meta"]["server_ms"] = json!(0);
+        response
+    }
+
+    #[test]
+    fn test_tcp_requires_matching_token() {
+        let service = service();
 
-        if line.trim().is_empty() {
-            return Ok(()); // Client disconnected
+        let missing = tcp_exchange(&service, &json!({"id": "a", "v": 1, "method": "health", "params": {}}));
+        assert_eq!(missing["ok"], false);
+        assert_eq!(missing["id"], "a");
+        assert_eq!(missing["error"]["code"], "UNAUTHORIZED");
+
+        let wrong = tcp_exchange(
+            &service,
+            &json!({"id": "b", "v": 1, "method": "health", "params": {}, "auth": "nope"}),
+        );
+        assert_eq!(wrong["error"]["code"], "UNAUTHORIZED");
+
+        let ok = tcp_exchange(
+            &service,
+            &json!({"id": "c", "v": 1, "method": "health", "params": {}, "auth": TOKEN}),
+        );
+        assert_eq!(ok["ok"], true, "{}", ok);
+        assert_eq!(ok["result"]["version"], "v1");
+    }
+
+    #[test]
+    fn test_tcp_and_unix_responses_match() {
+        let service = service();
+        let requests = [
+            json!({"id": "1", "v": 1, "method": "unread", "params": {"limit": 5}}),
+            json!({"id": "2", "v": 1, "method": "no_such_method", "params": {}}),
+        ];
+
+        for request in requests {
+            let (client, server) = UnixStream::pair().unwrap();
+            // The Unix socket needs no auth (and ignores one if sent)
+            let unix = exchange(&service, &client, &server, &request, None);
+
+            let mut authed = request.clone();
+            authed["auth"] = json!(TOKEN);
+            let tcp = tcp_exchange(&service, &authed);
+
+            assert_eq!(without_timing(unix), without_timing(tcp));
         }
+    }
+
+    fn respond_json(service: &Mutex<DaemonService>, request: &Value, supported: u8) -> Value {
+        let response = respond_as(service, &request.to_string(), None, supported).unwrap();
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[test]
+    fn test_v1_and_v2_requests_at_v2_server() {
+        let service = service();
+
+        // v1: `fields` is just an unknown method param, so the result is whole
+        let v1 = respond_json(
+            &service,
+            &json!({"id": "1", "v": 1, "method": "health", "params": {"fields": "version"}}),
+            protocol::PROTOCOL_V,
+        );
+        assert_eq!(v1["ok"], true, "{}", v1);
+        assert!(v1["result"]["pid"].is_number());
+        assert_eq!(v1["meta"]["protocol_v"], 2);
+        assert_eq!(v1["meta"]["min_supported"], 1);
+
+        // v2: output controls and timeout_ms are applied by the server
+        let v2 = respond_json(
+            &service,
+            &json!({"id": "2", "v": 2, "method": "health", "params": {"fields": "version", "timeout_ms": 5000}}),
+            protocol::PROTOCOL_V,
+        );
+        assert_eq!(v2["result"], json!({"version": "v1"}));
+        assert_eq!(v2["meta"]["protocol_v"], 2);
+
+        let future = respond_json(
+            &service,
+            &json!({"id": "3", "v": 3, "method": "health", "params": {}}),
+            protocol::PROTOCOL_V,
+        );
+        assert_eq!(future["ok"], false);
+        assert_eq!(future["error"]["code"], "UNSUPPORTED_PROTOCOL");
+    }
+
+    #[test]
+    fn test_v2_request_at_v1_server() {
+        let service = service();
+
+        let rejected = respond_json(
+            &service,
+            &json!({"id": "a", "v": 2, "method": "health", "params": {"fields": "version"}}),
+            1,
+        );
+        assert_eq!(rejected["ok"], false);
+        assert_eq!(rejected["id"], "a");
+        assert_eq!(rejected["error"]["code"], "UNSUPPORTED_PROTOCOL");
+        assert_eq!(rejected["meta"]["protocol_v"], 1);
+
+        // Downgrading to the advertised version works
+        let retried = respond_json(&service, &json!({"id": "b", "v": 1, "method": "health", "params": {}}), 1);
+        assert_eq!(retried["ok"], true);
+    }
+
+    #[test]
+    fn test_error_response_and_daemon_log_carry_request_id() {
+        let service = service();
+        let request = json!({"id": "req-7f3a", "v": 2, "method": "no_such_method", "params": {}});
+        let (response, log) = capture_log(|| respond_json(&service, &request, protocol::PROTOCOL_V));
+
+        assert_eq!(response["ok"], false);
+        assert_eq!(response["id"], "req-7f3a");
+        let line = log.iter().find(|l| l.contains("request failed")).expect("request logged");
+        assert!(line.contains("id=req-7f3a method=no_such_method duration_ms="), "{}", line);
+        assert!(line.contains("outcome=ERROR"), "{}", line);
+    }
+
+    #[test]
+    fn test_warnings_return_in_meta() {
+        let service = service();
+        let request = json!({"id": "w", "v": 2, "method": "bundle", "params": {"include": "unread_count,nope"}});
+        let response = respond_json(&service, &request, protocol::PROTOCOL_V);
+        assert_eq!(response["ok"], true, "{}", response);
+        assert_eq!(response["result"]["unread_count"], 0);
+        let warnings = response["meta"]["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].as_str().unwrap().starts_with("unknown bundle section 'nope'"));
 
+        // Collected per request: the next one starts clean, and omits the key
+        let health = respond_json(&service, &json!({"id": "h", "v": 2, "method": "health", "params": {}}), 2);
+        assert!(health["meta"].get("warnings").is_none(), "{}", health);
+    }
+
+    #[test]
+    fn test_bundle_trims_to_max_tokens() {
+        let service = service();
+        let params = json!({"include": "unread_count,heatmap", "max_tokens": 40});
+        let request = json!({"id": "b", "v": 2, "method": "bundle", "params": params});
+        let response = respond_json(&service, &request, protocol::PROTOCOL_V);
+        assert_eq!(response["ok"], true, "{}", response);
+        let result = &response["result"];
+        assert_eq!(result["unread_count"], 0);
+        assert!(result.get("heatmap").is_none(), "{}", result);
+        assert_eq!(result["meta"]["trimmed"]["heatmap"], "dropped");
+        assert!(result.to_string().len() <= 160, "{}", result);
+    }
+
+    #[test]
+    fn test_trace_param_returns_log_excerpt() {
+        let service = service();
+        let traced = respond_json(
+            &service,
+            &json!({"id": "req-trace", "v": 2, "method": "health", "params": {"trace": true}}),
+            protocol::PROTOCOL_V,
+        );
+        assert_eq!(traced["ok"], true, "{}", traced);
+        let lines = traced["meta"]["trace"].as_array().unwrap();
+        assert!(lines.iter().any(|l| l.as_str().unwrap().contains("dispatching")), "{:?}", lines);
+        assert!(lines.iter().all(|l| l.as_str().unwrap().contains("req-trace")), "{:?}", lines);
+
+        let plain = respond_json(
+            &service,
+            &json!({"id": "req-plain", "v": 2, "method": "health", "params": {}}),
+            protocol::PROTOCOL_V,
+        );
+        assert!(plain["meta"].get("trace").is_none());
+    }
+
+    #[test]
+    fn test_batch_mixes_successes_and_failures() {
+        let service = service();
+        let request = json!({"id": "b", "v": 2, "method": "batch", "params": {
+            "fields": "version",
+            "requests": [
+                {"method": "health", "params": {}},
+                {"method": "no_such_method", "params": {}},
+                {"method": "unread", "params": {"limit": 5, "fields": "unread_count"}},
+                {"method": "health", "params": 5},
+                {"method": "batch", "params": {"requests": []}},
+                {"method": "health"},
+            ],
+        }});
+        let response = respond_json(&service, &request, protocol::PROTOCOL_V);
+        assert_eq!(response["ok"], true, "{}", response);
+
+        let items = response["result"].as_array().unwrap();
+        let ids: Vec<&str> = items.iter().map(|i| i["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["b.0", "b.1", "b.2", "b.3", "b.4", "b.5"]);
+        let ok: Vec<bool> = items.iter().map(|i| i["ok"].as_bool().unwrap()).collect();
+        assert_eq!(ok, [true, false, true, false, false, true]);
+        assert!(items.iter().all(|i| i["meta"]["server_ms"].is_number()));
+
+        // Batch-level fields apply to items without their own
+        assert_eq!(items[0]["result"], json!({"version": "v1"}));
+        assert_eq!(items[2]["result"], json!({"unread_count": 0}));
+        assert_eq!(items[1]["error"]["code"], "ERROR");
+        assert_eq!(items[3]["error"]["code"], "INVALID_PARAMS");
+        assert_eq!(items[4]["error"]["message"], "Batches don't nest");
+
+        let malformed = respond_json(
+            &service,
+            &json!({"id": "m", "v": 2, "method": "batch", "params": {"requests": {"method": "health"}}}),
+            protocol::PROTOCOL_V,
+        );
+        assert_eq!(malformed["ok"], false);
+        assert_eq!(malformed["error"]["code"], "INVALID_PARAMS");
+    }
+
+    #[test]
+    fn test_batch_items_run_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = Mutex::new(
+            DaemonService::from_parts(empty_db(), ContactsManager::empty())
+                .with_snooze_store(SnoozeStore::new(dir.path().join("snoozes.json"))),
+        );
+        let unsnooze = json!({"method": "followup_unsnooze", "params": {"contact": "+14155550001"}});
+        let request = json!({"id": "o", "v": 1, "method": "batch", "params": {"requests": [
+            unsnooze,
+            {"method": "followup_snooze", "params": {"contact": "+14155550001", "until": "2d"}},
+            unsnooze,
+            unsnooze,
+        ]}});
+        let (client, server) = UnixStream::pair().unwrap();
+        let response = exchange(&service, &client, &server, &request, None);
+
+        // Each item sees the effects of the ones before it: only the unsnooze
+        // right after the snooze finds one
+        let items = response["result"].as_array().unwrap();
+        let ok: Vec<bool> = items.iter().map(|i| i["ok"].as_bool().unwrap()).collect();
+        assert_eq!(ok, [false, true, true, false], "{}", response);
+        assert_eq!(items[2]["result"]["unsnoozed"], true);
+    }
+
+    /// Serve `server` on a background thread once its socket is up.
+    fn serve_in_background(server: DaemonServer, socket: &Path) -> JoinHandle<Result<()>> {
+        let handle = std::thread::spawn(move || server.serve());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while UnixStream::connect(socket).is_err() {
+            assert!(Instant::now() < deadline, "daemon socket never came up");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        handle
+    }
+
+    fn health(socket: &Path) -> Value {
+        let client = UnixStream::connect(socket).unwrap();
+        (&client).write_all(b"{\"id\":\"h\",\"v\":1,\"method\":\"health\",\"params\":{}}\n").unwrap();
+        let mut line = String::new();
+        BufReader::new(&client).read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_idle_exit_removes_socket_and_pid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let pid_file = pid_file_path(socket.to_str().unwrap());
+        std::fs::write(&pid_file, "12345\n").unwrap();
+
+        // Margins are wide: the idle clock is the server's, started at construction
+        let idle = Duration::from_secs(2);
+        let server = DaemonServer::with_service(&socket, DaemonService::from_parts(empty_db(), ContactsManager::empty()))
+            .with_idle_exit(idle)
+            .without_scheduler();
+        let handle = serve_in_background(server, &socket);
+
+        // A request resets the idle clock, so the daemon is still up well after it
+        assert_eq!(health(&socket)["ok"], true);
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(socket.exists());
+
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while socket.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        handle.join().unwrap().unwrap();
+        assert!(!socket.exists());
+        assert!(!Path::new(&pid_file).exists());
+    }
+
+    #[test]
+    fn test_connection_accepted_at_shutdown_is_answered() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let server = DaemonServer::with_service(&socket, DaemonService::from_parts(empty_db(), ContactsManager::empty()))
+            .without_scheduler();
+        let activity = Arc::clone(&server.activity);
+        let handle = serve_in_background(server, &socket);
+        // Once answered, serve_in_background's probe connection is behind us
+        assert_eq!(health(&socket)["ok"], true);
+
+        // As if the idle watcher fired while these clients were connecting:
+        // the first is being served when the flag is set, the others are
+        // still queued when the loop stops
+        let request = b"{\"id\":\"h\",\"v\":1,\"method\":\"health\",\"params\":{}}\n";
+        let in_flight = UnixStream::connect(&socket).unwrap();
+        activity.shutdown.store(true, Ordering::SeqCst);
+        let mut clients: Vec<UnixStream> = (0..2)
+            .map(|_| {
+                let client = UnixStream::connect(&socket).unwrap();
+                (&client).write_all(request).unwrap();
+                client
+            })
+            .collect();
+        (&in_flight).write_all(request).unwrap();
+        clients.insert(0, in_flight);
+        for client in &clients {
+            let mut line = String::new();
+            BufReader::new(client).read_line(&mut line).unwrap();
+            assert_eq!(serde_json::from_str::<Value>(&line).unwrap()["ok"], true);
+        }
+        handle.join().unwrap().unwrap();
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_idle_exit_waits_for_scheduled_sends() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let store = ScheduleStore::new(dir.path().join("scheduled.json"));
+        let job = store.add("+14155550001", None, "later", chrono::Local::now() + chrono::Duration::hours(1)).unwrap();
+
+        let server = DaemonServer::with_service(&socket, DaemonService::from_parts(empty_db(), ContactsManager::empty()))
+            .with_idle_exit(Duration::from_millis(200))
+            .with_schedule_store(ScheduleStore::new(store.path()));
+        let handle = serve_in_background(server, &socket);
+
+        // Idle well past the limit, but the job is still waiting
+        std::thread::sleep(Duration::from_millis(800));
+        assert!(socket.exists());
+        assert!(!handle.is_finished());
+
+        store.cancel(&job.id).unwrap();
+        handle.join().unwrap().unwrap();
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_silent_client_times_out_instead_of_blocking() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let server = DaemonServer::with_service(&socket, DaemonService::from_parts(empty_db(), ContactsManager::empty()))
+            .with_read_timeout(Duration::from_millis(100))
+            .with_idle_exit(Duration::from_millis(500))
+            .without_scheduler();
+        let handle = serve_in_background(server, &socket);
+
+        // Connects, never sends a line, and stays connected
+        let _silent = UnixStream::connect(&socket).unwrap();
         let start = Instant::now();
+        assert_eq!(health(&socket)["ok"], true);
+        assert!(start.elapsed() < Duration::from_secs(5));
 
-        // Parse request
-        let request = protocol::Request::from_ndjson_line(&line)?;
+        handle.join().unwrap().unwrap();
+    }
 
-        // Dispatch to service
-        let response = match self.service.dispatch(&request.method, request.params) {
-            Ok(result) => protocol::Response::success(
-                request.id,
-                result,
-                start.elapsed().as_secs_f64() * 1000.0,
-            ),
-            Err(e) => protocol::Response::error(
-                request.id,
-                "ERROR",
-                e.to_string(),
-                start.elapsed().as_secs_f64() * 1000.0,
-            ),
+    #[test]
+    fn test_followup_snooze_over_socket() {
+        let conn = empty_db();
+        let now = queries::unix_to_cocoa(chrono::Local::now().timestamp());
+        let sam = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[sam]);
+        insert_message(&conn, chat, sam, Some("Can you send the lease?"), now - 5 * DAY_NS, false, true);
+        let dir = tempfile::tempdir().unwrap();
+        let service = Mutex::new(
+            DaemonService::from_parts(conn, ContactsManager::empty())
+                .with_snooze_store(SnoozeStore::new(dir.path().join("snoozes.json"))),
+        );
+        let send = |request: Value| {
+            let (client, server) = UnixStream::pair().unwrap();
+            let response = exchange(&service, &client, &server, &request, None);
+            assert_eq!(response["ok"], true, "{}", response);
+            response["result"].clone()
         };
+        let followup = json!({"id": "f", "v": 2, "method": "followup", "params": {"include_self": true}});
 
-        // Send NDJSON response
-        let response_line = response.to_ndjson_line()?;
-        writer.write_all(response_line.as_bytes())?;
-        writer.flush()?;
+        let before = send(followup.clone());
+        assert_eq!(before["unanswered_questions"][0]["phone"], "+14155550001");
+        assert_eq!(before["snoozed_count"], 0);
 
-        Ok(())
+        let snoozed = send(json!({
+            "id": "s", "v": 2, "method": "followup_snooze",
+            "params": {"contact": "(415) 555-0001", "until": "2d"}
+        }));
+        assert_eq!(snoozed["key"], "4155550001");
+
+        let after = send(followup.clone());
+        assert_eq!(after["unanswered_questions"], json!([]));
+        assert!(after["snoozed_count"].as_u64().unwrap() >= 1);
+
+        let unsnoozed = send(json!({
+            "id": "u", "v": 2, "method": "followup_unsnooze", "params": {"contact": "+14155550001"}
+        }));
+        assert_eq!(unsnoozed["unsnoozed"], true);
+        assert_eq!(send(followup)["unanswered_questions"][0]["phone"], "+14155550001");
     }
 }
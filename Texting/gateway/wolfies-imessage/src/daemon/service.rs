@@ -3,6 +3,45 @@
 //! Maintains hot resources (SQLite connection, contact cache) for fast execution.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - analytics series and bundle analytics report analysis_period_days (the key analytics uses), not period_days
+//! - 10/16/2026 - analytics takes streaks/all_time (streaks block)
+//! - 10/16/2026 - bundle reads every section in one snapshot (connection::read_snapshot); meta.snapshot and meta.max_rowid
+//! - 10/16/2026 - unread returns unread_count as a true count (api::unread_total; was the number of messages returned)
+//! - 10/16/2026 - analytics takes start/end (fixed window)
+//! - 10/16/2026 - analytics takes compare (comparison block against the preceding window)
+//! - 10/16/2026 - bundle takes max_tokens and section_priority (budget::trim_to_budget, meta.trimmed)
+//! - 10/16/2026 - text_search and followup take include_system (system items left out by default)
+//! - 10/16/2026 - Added unread_by_chat method (api::unread_by_chat)
+//! - 10/16/2026 - bundle warns about unknown sections (meta.warnings); startup warnings go to the daemon log
+//! - 10/16/2026 - recent (messages), unread, digest accept sender_filter (all | known | unknown)
+//! - 10/16/2026 - bundle heatmap section (weekday x hour counts; heatmap_days, heatmap_contact)
+//! - 10/16/2026 - Added chat_info method (commands::chat_info)
+//! - 10/16/2026 - bundle unread_count uses api::unread_count (one COUNT; was capped at 100 rows)
+//! - 10/16/2026 - Handle enrichment adds display_name (shared ContactsManager::display_name)
+//! - 10/16/2026 - text_search accepts group_id / group (one chat; exclusive with contact), echoes the group
+//! - 10/16/2026 - Added quick method (pinned contacts and groups, commands::quick)
+//! - 10/16/2026 - followup uses commands::followup (include_groups, min_question_score, exclude_automated, snoozes); added followup_snooze and followup_unsnooze
+//! - 10/16/2026 - unread, recent (conversations), digest, bundle leave out muted conversations unless include_muted; muted_count
+//! - 10/16/2026 - Contacts via load_default_or_empty (corrupt contacts.json warns on stderr)
+//! - 10/16/2026 - from_parts is public (benches and integration tests)
+//! - 10/16/2026 - recent, messages, unread, text_search, analytics, bundle call the api layer (typed rows)
+//! - 10/16/2026 - dispatch_within: interrupt SQLite work past a request deadline (v2 timeout_ms)
+//! - 10/16/2026 - Added contacts() accessor
+//! - 10/16/2026 - Added messages handler (one contact's messages, all of their handles)
+//! - 10/16/2026 - Added test-only from_parts constructor
+//! - 10/16/2026 - analytics accepts a series param (hourly/daily/weekly volume buckets)
+//! - 10/16/2026 - recent, unread, text_search rows include message rowid and guid
+//! - 10/16/2026 - Handle, top-contact, and unknown-sender rows include merged phone variants
+//! - 10/16/2026 - analytics/followup/unknown/discover skip notes-to-self unless include_self
+//! - 10/16/2026 - Larger prepared-statement cache on the hot connection
+//! - 10/16/2026 - Installs the blob text cache (disk + 10k-entry LRU) at startup
+//! - 10/16/2026 - unknown/discover return kind + looks_automated, accept exclude_automated
+//! - 10/16/2026 - handles accepts merge_contacts (one row per person)
+//! - 10/16/2026 - Added text_search method (query | any | all)
+//! - 10/16/2026 - Added group_analytics method
+//! - 10/16/2026 - relationship param on recent, unread, analytics, followup, digest
+//! - 10/16/2026 - Added digest method
+//! - 10/16/2026 - recent accepts mode=conversations (latest message per chat)
 //! - 01/11/2026 - Refactored: added param helpers, enrichment methods (review feedback) (Claude)
 //! - 01/11/2026 - Optimized analytics: 6 queries → 3 queries (20ms → ~5ms) (Claude)
 //! - 01/10/2026 - Implemented all command handlers (Phase 5) (Claude)
@@ -11,12 +50,24 @@
 use anyhow::{anyhow, Result};
 use rusqlite::Connection;
 use std::collections::HashMap;
-use std::sync::Arc;
-
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use crate::api;
+use crate::budget;
+use crate::commands::followup::{self, FollowupOptions};
+use crate::commands::{analytics, chat_info, digest, discovery, groups, quick};
+use crate::config;
+use crate::contacts::classify::Classification;
 use crate::contacts::manager::ContactsManager;
-use crate::db::connection::open_db;
+use crate::db::connection::{self, open_db};
 use crate::db::helpers;
 use crate::db::queries;
+use crate::db::text_cache::{self, TextCache};
+use crate::mutes::MuteFilter;
+use crate::output;
+use crate::senders::{SenderFilter, SenderMode};
+use crate::snoozes::SnoozeStore;
 
 // ============================================================================
 // Time Constants (for self-documenting time calculations)
@@ -25,20 +76,37 @@ use crate::db::queries;
 const SECONDS_PER_DAY: i64 = 24 * 3600;
 const NANOS_PER_SECOND: i64 = 1_000_000_000;
 
+/// Extracted-text entries kept in memory in front of the on-disk text cache.
+const TEXT_CACHE_LRU_CAPACITY: usize = 10_000;
+
+/// Compiled statements kept on the hot connection (helpers use prepare_cached).
+/// Room for every constant query plus common filter/limit variants.
+const STATEMENT_CACHE_CAPACITY: usize = 128;
+
 /// Daemon service with hot resources.
 pub struct DaemonService {
     conn: Connection,               // Hot SQLite connection (eliminates 5ms overhead per query)
     contacts: Arc<ContactsManager>, // Cached contacts (eliminates 20-50ms per command)
     started_at: String,             // ISO timestamp
+    snoozes: SnoozeStore,           // Followup snoozes (read per request)
 }
 
 impl DaemonService {
     /// Create new daemon service with hot resources.
     pub fn new() -> Result<Self> {
         let conn = open_db()?;
-        let contacts = Arc::new(
-            ContactsManager::load_default().unwrap_or_else(|_| ContactsManager::empty()),
-        );
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        let contacts = Arc::new(ContactsManager::load_default_or_empty());
+        // Startup warnings (e.g. a corrupt contacts.json) belong in the log, not the first response
+        for warning in output::take_warnings() {
+            tracing::warn!(warning = %warning, "startup");
+        }
+
+        // Blob text cache lives for the process: on-disk cache plus an in-memory LRU
+        text_cache::install(TextCache::new(
+            Some(text_cache::default_path()),
+            TEXT_CACHE_LRU_CAPACITY,
+        ));
 
         let started_at = chrono::Utc::now().to_rfc3339();
 
@@ -46,9 +114,32 @@ impl DaemonService {
             conn,
             contacts,
             started_at,
+            snoozes: SnoozeStore::open_default(),
         })
     }
 
+    /// Service over an existing connection and contacts (no text cache install).
+    pub fn from_parts(conn: Connection, contacts: ContactsManager) -> Self {
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        Self {
+            conn,
+            contacts: Arc::new(contacts),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            snoozes: SnoozeStore::open_default(),
+        }
+    }
+
+    /// Use `store` for followup snoozes instead of ~/.wolfies-imessage/snoozes.json.
+    pub fn with_snooze_store(mut self, store: SnoozeStore) -> Self {
+        self.snoozes = store;
+        self
+    }
+
+    /// The cached contacts.
+    pub fn contacts(&self) -> &ContactsManager {
+        &self.contacts
+    }
+
     // ========================================================================
     // Parameter Parsing Helpers (reduces boilerplate)
     // ========================================================================
@@ -62,11 +153,53 @@ impl DaemonService {
             .unwrap_or(default)
     }
 
+    /// Get optional bool parameter with default value.
+    fn get_param_bool(params: &HashMap<String, serde_json::Value>, key: &str, default: bool) -> bool {
+        params.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
+    }
+
+    /// Get optional f64 parameter with default value.
+    fn get_param_f64(params: &HashMap<String, serde_json::Value>, key: &str, default: f64) -> f64 {
+        params.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+    }
+
     /// Get optional string parameter.
     fn get_param_str<'a>(params: &'a HashMap<String, serde_json::Value>, key: &str) -> Option<&'a str> {
         params.get(key).and_then(|v| v.as_str())
     }
 
+    /// Resolve the optional `relationship` param to a handle filter.
+    fn relationship_filter(
+        &self,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> Result<Option<helpers::HandleFilter>> {
+        Self::get_param_str(params, "relationship")
+            .map(|r| {
+                let handles = self.contacts.handles_for_relationship(r)?;
+                helpers::HandleFilter::resolve(&self.conn, &handles)
+            })
+            .transpose()
+    }
+
+    /// My own handles to leave out, unless the `include_self` param is true.
+    fn self_exclusion(
+        &self,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> Result<Option<helpers::HandleFilter>> {
+        helpers::self_exclusion(&self.conn, Self::get_param_bool(params, "include_self", false))
+    }
+
+    /// Muted conversations to leave out, unless the `include_muted` param is true.
+    /// Read per request so `mute`/`unmute` apply without a daemon restart.
+    fn mutes(&self, params: &HashMap<String, serde_json::Value>) -> Result<Option<MuteFilter>> {
+        MuteFilter::load(&self.conn, Self::get_param_bool(params, "include_muted", false))
+    }
+
+    /// The optional `sender_filter` param ("all" default, "known", "unknown").
+    fn sender_mode(params: &HashMap<String, serde_json::Value>) -> Result<SenderMode> {
+        Self::get_param_str(params, "sender_filter").map_or(Ok(SenderMode::All), SenderMode::parse)
+    }
+
     /// Convert days to stale threshold in nanoseconds.
     fn days_to_stale_ns(days: u32) -> i64 {
         (days as i64) * SECONDS_PER_DAY * NANOS_PER_SECOND
@@ -76,81 +209,33 @@ impl DaemonService {
     // Contact Enrichment Helpers (reduces duplication)
     // ========================================================================
 
-    /// Enrich a recent message with contact name.
-    fn enrich_recent_message(&self, msg: helpers::RecentMessage) -> serde_json::Value {
-        let contact_name = self.contacts.find_by_phone(&msg.phone).map(|c| c.name.clone());
-        serde_json::json!({
-            "text": msg.text,
-            "date": msg.date,
-            "is_from_me": msg.is_from_me,
-            "phone": msg.phone,
-            "contact_name": contact_name,
-        })
-    }
-
-    /// Enrich an unread message with contact name.
-    fn enrich_unread_message(&self, msg: helpers::UnreadMessage) -> serde_json::Value {
-        let contact_name = self.contacts.find_by_phone(&msg.phone).map(|c| c.name.clone());
-        serde_json::json!({
-            "text": msg.text,
-            "date": msg.date,
-            "phone": msg.phone,
-            "contact_name": contact_name,
-        })
-    }
-
     /// Enrich handle info with contact name.
     fn enrich_handle(&self, handle: helpers::HandleInfo) -> serde_json::Value {
         let contact_name = self.contacts.find_by_phone(&handle.handle).map(|c| c.name.clone());
         serde_json::json!({
+            "display_name": self.contacts.display_name(&handle.handle, None),
             "handle": handle.handle,
             "contact_name": contact_name,
             "message_count": handle.message_count,
             "last_date": handle.last_date,
-        })
-    }
-
-    /// Enrich top contact with name.
-    fn enrich_top_contact(&self, tc: helpers::TopContact) -> serde_json::Value {
-        let contact_name = self.contacts.find_by_phone(&tc.phone).map(|c| c.name.clone());
-        serde_json::json!({
-            "phone": tc.phone,
-            "contact_name": contact_name,
-            "message_count": tc.message_count,
+            "variants": handle.variants,
         })
     }
 
     /// Enrich unknown sender with context.
-    fn enrich_unknown_sender(&self, sender: helpers::UnknownSender) -> serde_json::Value {
+    fn enrich_unknown_sender(
+        &self,
+        sender: helpers::UnknownSender,
+        classification: Classification,
+    ) -> serde_json::Value {
         serde_json::json!({
             "handle": sender.handle,
             "message_count": sender.message_count,
             "last_date": sender.last_date,
             "sample_text": sender.sample_text,
-        })
-    }
-
-    /// Enrich unanswered question with contact name.
-    fn enrich_unanswered(&self, q: helpers::UnansweredQuestion) -> serde_json::Value {
-        let contact_name = self.contacts.find_by_phone(&q.phone).map(|c| c.name.clone());
-        serde_json::json!({
-            "text": q.text,
-            "date": q.date,
-            "phone": q.phone,
-            "contact_name": contact_name,
-            "days_ago": q.days_ago,
-        })
-    }
-
-    /// Enrich stale conversation with contact name.
-    fn enrich_stale_conversation(&self, conv: helpers::StaleConversation) -> serde_json::Value {
-        let contact_name = self.contacts.find_by_phone(&conv.phone).map(|c| c.name.clone());
-        serde_json::json!({
-            "phone": conv.phone,
-            "contact_name": contact_name,
-            "last_date": conv.last_date,
-            "days_ago": conv.days_ago,
-            "last_text": conv.last_text,
+            "variants": sender.variants,
+            "kind": classification.kind,
+            "looks_automated": classification.looks_automated,
         })
     }
 
@@ -168,16 +253,53 @@ impl DaemonService {
             "health" => self.health(),
             "analytics" => self.analytics(params),
             "followup" => self.followup(params),
+            "followup_snooze" => self.followup_snooze(params),
+            "followup_unsnooze" => self.followup_unsnooze(params),
             "recent" => self.recent(params),
+            "messages" => self.messages(params),
             "unread" => self.unread(params),
+            "unread_by_chat" => self.unread_by_chat(params),
+            "quick" => self.quick(params),
             "discover" => self.discover(params),
             "unknown" => self.unknown(params),
             "handles" => self.handles(params),
             "bundle" => self.bundle(params),
+            "digest" => self.digest(params),
+            "group_analytics" => self.group_analytics(params),
+            "chat_info" => self.chat_info(params),
+            "text_search" => self.text_search(params),
             _ => Err(anyhow!("Unknown method: {}", method)),
         }
     }
 
+    /// Dispatch with an optional deadline.
+    ///
+    /// A watchdog interrupts the hot connection once `timeout` elapses, so a
+    /// long query fails (SQLITE_INTERRUPT) instead of running to completion.
+    pub fn dispatch_within(
+        &self,
+        method: &str,
+        params: HashMap<String, serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<serde_json::Value> {
+        let Some(timeout) = timeout else {
+            return self.dispatch(method, params);
+        };
+
+        let interrupt = self.conn.get_interrupt_handle();
+        let (done, finished) = mpsc::channel::<()>();
+        let watchdog = std::thread::spawn(move || {
+            if finished.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout) {
+                interrupt.interrupt();
+            }
+        });
+
+        let result = self.dispatch(method, params);
+        drop(done);
+        let _ = watchdog.join();
+        result
+    }
+
     // ========================================================================
     // Health Check
     // ========================================================================
@@ -197,137 +319,323 @@ impl DaemonService {
     // ========================================================================
 
     /// Recent messages handler.
-    /// Params: days (default 7), limit (default 20)
+    /// Params: days (default 7), limit (default 20), mode ("messages" | "conversations"),
+    /// relationship (optional), sender_filter (messages mode: all | known | unknown)
     fn recent(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
         let days = Self::get_param_u32(&params, "days", 7);
         let limit = Self::get_param_u32(&params, "limit", 20);
+        let relationship = Self::get_param_str(&params, "relationship");
+
+        match Self::get_param_str(&params, "mode").unwrap_or("messages") {
+            "messages" => {}
+            "conversations" => {
+                let mutes = self.mutes(&params)?;
+                let conversations =
+                    api::conversations(&self.conn, &self.contacts, limit, relationship, mutes.as_ref())?;
+                let muted_count =
+                    api::muted_conversation_count(&self.conn, &self.contacts, limit, relationship, mutes.as_ref())?;
+                return Ok(serde_json::json!({
+                    "count": conversations.len(),
+                    "conversations": conversations,
+                    "mode": "conversations",
+                    "muted_count": muted_count,
+                }));
+            }
+            other => return Err(anyhow!("Unknown recent mode: {} (expected messages|conversations)", other)),
+        }
 
-        let cutoff_cocoa = queries::days_ago_cocoa(days);
-        let messages = helpers::query_recent_messages(&self.conn, cutoff_cocoa, limit)?;
+        let opts = api::RecentOptions {
+            limit,
+            days: Some(days),
+            relationship: relationship.map(str::to_string),
+            sender: Self::sender_mode(&params)?,
+            ..Default::default()
+        };
+        let messages = api::recent(&self.conn, &self.contacts, &opts)?;
 
-        let enriched: Vec<serde_json::Value> = messages
-            .into_iter()
-            .map(|msg| self.enrich_recent_message(msg))
-            .collect();
+        Ok(serde_json::json!({
+            "count": messages.len(),
+            "messages": messages,
+            "days": days,
+        }))
+    }
+
+    /// Messages with one contact handler.
+    /// Params: contact (required: name, phone, or email), limit (default 20),
+    /// days (default 365)
+    fn messages(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let contact = Self::get_param_str(&params, "contact")
+            .ok_or_else(|| anyhow!("Missing required param: contact"))?;
+        let limit = Self::get_param_u32(&params, "limit", 20);
+        let days = Self::get_param_u32(&params, "days", 365);
+
+        // A known contact covers all of their handles; anything else is taken as a raw handle
+        let known = self
+            .contacts
+            .find_by_phone(contact)
+            .or_else(|| self.contacts.find_fuzzy(contact));
+        let handles: Vec<String> = match known {
+            Some(c) => c.all_handles().map(String::from).collect(),
+            None => vec![contact.to_string()],
+        };
+
+        let opts = api::RecentOptions { limit, days: Some(days), handles, ..Default::default() };
+        let messages = api::recent(&self.conn, &self.contacts, &opts)?;
 
         Ok(serde_json::json!({
-            "messages": enriched,
-            "count": enriched.len(),
+            "contact": known.map(|c| c.name.clone()),
+            "count": messages.len(),
+            "messages": messages,
             "days": days,
         }))
     }
 
+    /// Pinned contacts and groups: latest message and unread count each.
+    /// Params: pins (optional list; default `pinned` from config.toml, re-read
+    /// per request so edits apply without a restart)
+    fn quick(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let pins: Vec<String> = match params.get("pins").and_then(|v| v.as_array()) {
+            Some(pins) => pins.iter().filter_map(|p| p.as_str().map(str::to_string)).collect(),
+            None => config::FileConfig::load(&config::config_path())?.0.pinned.unwrap_or_default(),
+        };
+        let report = quick::build_report(&self.conn, &self.contacts, &pins)?;
+        Ok(serde_json::to_value(report)?)
+    }
+
     /// Unread messages handler.
-    /// Params: limit (default 50)
+    /// Params: limit (default 50), relationship (optional), include_muted (default false),
+    /// sender_filter (all | known | unknown; applied before the limit)
     fn unread(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
-        let limit = Self::get_param_u32(&params, "limit", 50);
-        let messages = helpers::query_unread_messages(&self.conn, limit)?;
+        let opts = api::UnreadOptions {
+            limit: Self::get_param_u32(&params, "limit", 50),
+            relationship: Self::get_param_str(&params, "relationship").map(str::to_string),
+            with_entities: false,
+            mutes: self.mutes(&params)?,
+            sender: Self::sender_mode(&params)?,
+        };
+        let messages = api::unread(&self.conn, &self.contacts, &opts)?;
+        let unread_count = api::unread_total(&self.conn, &self.contacts, &opts)?;
+        let muted_count = api::muted_unread_count(&self.conn, &self.contacts, &opts)?;
 
-        let enriched: Vec<serde_json::Value> = messages
-            .into_iter()
-            .map(|msg| self.enrich_unread_message(msg))
-            .collect();
+        Ok(serde_json::json!({
+            "unread_count": unread_count,
+            "messages": messages,
+            "muted_count": muted_count,
+        }))
+    }
+
+    /// Unread count and oldest unread date per conversation.
+    /// Params: include_muted (default false)
+    fn unread_by_chat(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let mutes = self.mutes(&params)?;
+        let chats = api::unread_by_chat(&self.conn, &self.contacts, mutes.as_ref())?;
+        let muted_count = api::muted_unread_chat_count(&self.conn, mutes.as_ref())?;
 
         Ok(serde_json::json!({
-            "unread_count": enriched.len(),
-            "messages": enriched,
+            "chats": chats,
+            "muted_count": muted_count,
         }))
     }
 
-    /// Analytics command handler (optimized - 2 queries instead of 6).
-    /// Params: contact (optional), days (default 30)
+    /// Analytics command handler (combined query plus top contacts).
+    /// Params: contact (optional), days (default 30), relationship (optional),
+    /// include_self (default false), series (optional: hourly, daily, weekly),
+    /// compare (default false: no comparison block), start/end (optional
+    /// YYYY-MM-DD, end inclusive: a fixed window instead of days), streaks
+    /// (default false; needs contact: a streaks block), all_time (default
+    /// false: streaks over all history instead of days)
     fn analytics(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
         let contact = Self::get_param_str(&params, "contact");
         let days = Self::get_param_u32(&params, "days", 30);
 
-        // Resolve contact to phone if provided
-        let phone = contact.and_then(|name| {
-            self.contacts.find_by_name(name).map(|c| c.phone.clone())
-        });
-
-        let cutoff_cocoa = queries::days_ago_cocoa(days);
-        let phone_ref = phone.as_deref();
-
-        // Query 1: Combined analytics (total, sent, received, reactions, attachments, busiest_hour, busiest_day)
-        let stats = helpers::query_analytics_combined(&self.conn, cutoff_cocoa, phone_ref)?;
+        if let Some(series) = Self::get_param_str(&params, "series") {
+            let phone = contact.and_then(|name| self.contacts.find_by_name(name).map(|c| c.phone.clone()));
+            let bucket = helpers::SeriesBucket::parse(series)?;
+            let cutoff_cocoa = queries::days_ago_cocoa(days);
+            let points = helpers::query_volume_series(&self.conn, cutoff_cocoa, bucket, phone.as_deref())?;
+            return Ok(serde_json::json!({
+                "analysis_period_days": days,
+                "bucket": bucket.name(),
+                "series": points,
+            }));
+        }
 
-        // Query 2: Top contacts (only if no phone filter)
-        let top_contacts = if phone_ref.is_none() {
-            helpers::query_top_contacts(&self.conn, cutoff_cocoa)?
-        } else {
-            Vec::new()
+        let (start_cocoa, end_cocoa) =
+            analytics::date_window(Self::get_param_str(&params, "start"), Self::get_param_str(&params, "end"))?;
+        let opts = api::AnalyticsOptions {
+            contact: contact.map(str::to_string),
+            relationship: Self::get_param_str(&params, "relationship").map(str::to_string),
+            days,
+            start_cocoa,
+            end_cocoa,
+            include_self: Self::get_param_bool(&params, "include_self", false),
+            compare: Self::get_param_bool(&params, "compare", false),
+            streaks: Self::get_param_bool(&params, "streaks", false),
+            streaks_all_time: Self::get_param_bool(&params, "all_time", false),
         };
+        Ok(serde_json::to_value(api::analytics(&self.conn, &self.contacts, &opts)?)?)
+    }
 
-        let busiest_day_name = stats.busiest_day
-            .and_then(|d| helpers::day_number_to_name(d).map(|s| s.to_string()));
+    // ========================================================================
+    // P1 Handlers: followup, handles, unknown, discover, bundle
+    // ========================================================================
 
-        let enriched_top_contacts: Vec<serde_json::Value> = top_contacts
-            .into_iter()
-            .map(|tc| self.enrich_top_contact(tc))
-            .collect();
+    /// Digest handler.
+    /// Params: since ("yesterday" default, "today", or YYYY-MM-DD), relationship (optional),
+    /// include_muted (default false), sender_filter (unread section: all | known | unknown)
+    fn digest(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let start = digest::parse_since(Self::get_param_str(&params, "since"))?;
+        let filter = self.relationship_filter(&params)?;
+        let mutes = self.mutes(&params)?;
+        let senders = SenderFilter::load(&self.conn, &self.contacts, Self::sender_mode(&params)?)?;
+        let start_cocoa = queries::unix_to_cocoa(start.timestamp());
+        let end_cocoa = queries::unix_to_cocoa(chrono::Local::now().timestamp());
+
+        let result = digest::build_digest(
+            &self.conn,
+            &self.contacts,
+            start_cocoa,
+            end_cocoa,
+            filter.as_ref(),
+            mutes.as_ref(),
+            &senders,
+        )?;
+        Ok(serde_json::to_value(result)?)
+    }
 
-        let avg_daily = if days > 0 {
-            (stats.total as f64) / (days as f64)
-        } else {
-            0.0
+    /// Get search terms from a string ("a,b") or array (["a", "b"]) param.
+    fn get_param_terms(params: &HashMap<String, serde_json::Value>, key: &str) -> Vec<String> {
+        match params.get(key) {
+            Some(serde_json::Value::String(s)) => vec![s.clone()],
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                // Array items are whole terms; escape commas so they aren't split
+                .map(|s| s.replace(',', "\\,"))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Text search handler.
+    /// Params: query | any | all (string or array), group_id | group (optional,
+    /// not with contact), limit (default 50), include_system (default false)
+    fn text_search(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let group = Self::get_param_str(&params, "group_id").or_else(|| Self::get_param_str(&params, "group"));
+        if group.is_some() && params.contains_key("contact") {
+            return Err(anyhow!("contact and group_id/group are mutually exclusive"));
+        }
+        let chat = group
+            .map(|g| helpers::resolve_group(&self.conn, g)?.ok_or_else(|| anyhow!("Group '{}' not found", g)))
+            .transpose()?;
+
+        let opts = api::SearchOptions {
+            query: Self::get_param_str(&params, "query").map(str::to_string),
+            any: Self::get_param_terms(&params, "any"),
+            all: Self::get_param_terms(&params, "all"),
+            group: chat.as_ref().map(|c| c.chat_identifier.clone()),
+            limit: Self::get_param_u32(&params, "limit", 50),
+            include_system: Self::get_param_bool(&params, "include_system", false),
         };
+        let results = api::text_search(&self.conn, &self.contacts, &opts)?;
 
-        Ok(serde_json::json!({
-            "period_days": days,
-            "total_messages": stats.total,
-            "sent_count": stats.sent,
-            "received_count": stats.received,
-            "avg_per_day": (avg_daily * 10.0).round() / 10.0,
-            "busiest_hour": stats.busiest_hour,
-            "busiest_day": busiest_day_name,
-            "top_contacts": enriched_top_contacts,
-            "attachment_count": stats.attachments,
-            "reaction_count": stats.reactions,
-        }))
+        let mut response = serde_json::json!({
+            "count": results.len(),
+            "results": results,
+        });
+        if let Some(chat) = chat {
+            response["group"] = serde_json::json!({
+                "group_id": chat.chat_identifier,
+                "display_name": chat.display_name,
+            });
+        }
+        Ok(response)
     }
 
-    // ========================================================================
-    // P1 Handlers: followup, handles, unknown, discover, bundle
-    // ========================================================================
-
-    /// Follow-up command handler.
-    /// Params: days (default 30), stale (default 3)
-    fn followup(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+    /// Group analytics handler.
+    /// Params: group (required, id or name), days (default 30), me (optional)
+    fn group_analytics(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let group = Self::get_param_str(&params, "group")
+            .ok_or_else(|| anyhow!("Missing required param: group"))?;
         let days = Self::get_param_u32(&params, "days", 30);
-        let stale = Self::get_param_u32(&params, "stale", 3);
-
-        let cutoff_cocoa = queries::days_ago_cocoa(days);
-        let stale_threshold_ns = Self::days_to_stale_ns(stale);
+        let me = Self::get_param_str(&params, "me");
 
-        let unanswered = helpers::query_unanswered_questions(&self.conn, cutoff_cocoa, stale_threshold_ns)?;
-        let stale_convos = helpers::query_stale_conversations(&self.conn, cutoff_cocoa, stale_threshold_ns)?;
+        let result = groups::build_group_analytics(&self.conn, &self.contacts, group, days, me)?;
+        Ok(serde_json::to_value(result)?)
+    }
 
-        let enriched_unanswered: Vec<serde_json::Value> = unanswered
-            .into_iter()
-            .map(|q| self.enrich_unanswered(q))
-            .collect();
+    /// Conversation header: participants, service, message span, attachments.
+    /// Params: target (group ID or name, contact name, or phone/email)
+    fn chat_info(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let target = Self::get_param_str(&params, "target")
+            .ok_or_else(|| anyhow!("Missing required param: target"))?;
+        let report = chat_info::build_report(&self.conn, &self.contacts, target)?;
+        Ok(serde_json::to_value(report)?)
+    }
 
-        let enriched_stale: Vec<serde_json::Value> = stale_convos
-            .into_iter()
-            .map(|s| self.enrich_stale_conversation(s))
-            .collect();
+    /// Follow-up command handler.
+    /// Params: days (default 30), stale (default 3), relationship (optional),
+    /// include_self (default false), include_groups (default false),
+    /// min_question_score (default 0.5), exclude_automated (default false),
+    /// include_system (default false). Snoozed contacts are left out (snoozed_count).
+    fn followup(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let opts = FollowupOptions {
+            days: Self::get_param_u32(&params, "days", 30),
+            stale: Self::get_param_u32(&params, "stale", 3),
+            relationship: Self::get_param_str(&params, "relationship").map(str::to_string),
+            include_self: Self::get_param_bool(&params, "include_self", false),
+            include_groups: Self::get_param_bool(&params, "include_groups", false),
+            min_question_score: Self::get_param_f64(
+                &params,
+                "min_question_score",
+                followup::DEFAULT_MIN_QUESTION_SCORE,
+            ),
+            exclude_automated: Self::get_param_bool(&params, "exclude_automated", false),
+            include_system: Self::get_param_bool(&params, "include_system", false),
+        };
+        let snoozes = self.snoozes.active_or_warn(chrono::Local::now());
+        let report = followup::build_report(&self.conn, &self.contacts, &opts, &snoozes)?;
+        Ok(serde_json::to_value(report)?)
+    }
 
-        let total_items = enriched_unanswered.len() + enriched_stale.len();
+    /// Snooze a contact's follow-ups.
+    /// Params: contact (required: name, phone, or email), until (default "1d":
+    /// duration, date, or local time)
+    fn followup_snooze(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let contact = Self::get_param_str(&params, "contact")
+            .ok_or_else(|| anyhow!("Missing required param: contact"))?;
+        let until = Self::get_param_str(&params, "until").unwrap_or("1d");
+        let snooze = followup::snooze_contact(&self.snoozes, contact, until, &self.contacts, chrono::Local::now())?;
+        Ok(serde_json::to_value(snooze)?)
+    }
 
-        Ok(serde_json::json!({
-            "unanswered_questions": enriched_unanswered,
-            "stale_conversations": enriched_stale,
-            "total_items": total_items,
-        }))
+    /// Remove a contact's snooze.
+    /// Params: contact (required: name, phone, or email)
+    fn followup_unsnooze(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
+        let contact = Self::get_param_str(&params, "contact")
+            .ok_or_else(|| anyhow!("Missing required param: contact"))?;
+        let snooze = followup::unsnooze_contact(&self.snoozes, contact, &self.contacts, chrono::Local::now())?;
+        Ok(serde_json::to_value(followup::UnsnoozeResult { unsnoozed: true, snooze })?)
     }
 
     /// Handles list handler.
-    /// Params: days (default 30), limit (default 50)
+    /// Params: days (default 30), limit (default 50), merge_contacts (default false)
     fn handles(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
         let days = Self::get_param_u32(&params, "days", 30);
         let limit = Self::get_param_u32(&params, "limit", 50);
+        let merge_contacts = Self::get_param_bool(&params, "merge_contacts", false);
 
         let cutoff_cocoa = queries::days_ago_cocoa(days);
+        if merge_contacts {
+            let all = helpers::query_handles(&self.conn, cutoff_cocoa, discovery::MERGE_FETCH_LIMIT)?;
+            let mut merged = discovery::merge_handles(all, &self.contacts);
+            merged.truncate(limit as usize);
+            return Ok(serde_json::json!({
+                "handles": merged,
+                "count": merged.len(),
+            }));
+        }
+
         let handles = helpers::query_handles(&self.conn, cutoff_cocoa, limit)?;
 
         let enriched: Vec<serde_json::Value> = handles
@@ -342,20 +650,31 @@ impl DaemonService {
     }
 
     /// Unknown senders handler - handles not in contacts.
-    /// Params: days (default 30), limit (default 20)
+    /// Params: days (default 30), limit (default 20), exclude_automated (default false),
+    /// include_self (default false)
     fn unknown(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
         let days = Self::get_param_u32(&params, "days", 30);
         let limit = Self::get_param_u32(&params, "limit", 20);
+        let exclude_automated = Self::get_param_bool(&params, "exclude_automated", false);
+        let mine = self.self_exclusion(&params)?;
 
         let cutoff_cocoa = queries::days_ago_cocoa(days);
         let all_senders = helpers::query_unknown_senders(&self.conn, cutoff_cocoa)?;
 
-        // Filter to unknown senders (not in contacts)
+        // Filter to unknown senders (not in contacts, not me)
         let unknown: Vec<serde_json::Value> = all_senders
             .into_iter()
-            .filter(|s| self.contacts.find_by_phone(&s.handle).is_none())
+            .filter(|s| {
+                self.contacts.find_by_phone(&s.handle).is_none()
+                    && !helpers::is_mine(mine.as_ref(), &s.handle)
+            })
+            .map(|s| {
+                let classification = discovery::classify_sender(&self.conn, &s.handle);
+                (s, classification)
+            })
+            .filter(|(_, c)| !(exclude_automated && c.is_automated()))
             .take(limit as usize)
-            .map(|s| self.enrich_unknown_sender(s))
+            .map(|(s, c)| self.enrich_unknown_sender(s, c))
             .collect();
 
         Ok(serde_json::json!({
@@ -365,10 +684,13 @@ impl DaemonService {
     }
 
     /// Discovery command handler - find frequent unknown senders for potential contacts.
-    /// Params: days (default 90), min_messages (default 3)
+    /// Params: days (default 90), min_messages (default 3), exclude_automated (default true),
+    /// include_self (default false)
     fn discover(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
         let days = Self::get_param_u32(&params, "days", 90);
         let min_messages = Self::get_param_u32(&params, "min_messages", 3) as i64;
+        let exclude_automated = Self::get_param_bool(&params, "exclude_automated", true);
+        let mine = self.self_exclusion(&params)?;
 
         let cutoff_cocoa = queries::days_ago_cocoa(days);
         let all_senders = helpers::query_unknown_senders(&self.conn, cutoff_cocoa)?;
@@ -378,9 +700,15 @@ impl DaemonService {
             .into_iter()
             .filter(|s| {
                 self.contacts.find_by_phone(&s.handle).is_none()
+                    && !helpers::is_mine(mine.as_ref(), &s.handle)
                     && s.message_count >= min_messages
             })
-            .map(|s| self.enrich_unknown_sender(s))
+            .map(|s| {
+                let classification = discovery::classify_sender(&self.conn, &s.handle);
+                (s, classification)
+            })
+            .filter(|(_, c)| !(exclude_automated && c.is_automated()))
+            .map(|(s, c)| self.enrich_unknown_sender(s, c))
             .collect();
 
         Ok(serde_json::json!({
@@ -389,71 +717,103 @@ impl DaemonService {
             "criteria": {
                 "days": days,
                 "min_messages": min_messages,
+                "exclude_automated": exclude_automated,
             },
         }))
     }
 
     /// Bundle command handler - combines multiple queries for dashboard use.
-    /// Params: include (comma-separated: unread_count,recent,analytics,followup_count)
+    /// Params: include (comma-separated: unread_count,recent,analytics,followup_count,heatmap),
+    /// include_muted (default false; applies to unread_count),
+    /// heatmap_days (default 30), heatmap_contact (optional contact name),
+    /// max_tokens (optional; trims to fit, see `budget`), section_priority
+    /// (comma-separated, most important first). Sections share one database
+    /// snapshot; meta carries `snapshot: true` and its `max_rowid`.
     fn bundle(&self, params: HashMap<String, serde_json::Value>) -> Result<serde_json::Value> {
         let include = Self::get_param_str(&params, "include").unwrap_or("unread_count,recent");
         let sections: Vec<&str> = include.split(',').map(|s| s.trim()).collect();
-        let mut result = serde_json::Map::new();
-
-        for section in sections {
-            match section {
-                "unread_count" => {
-                    let unread = helpers::query_unread_messages(&self.conn, 100)?;
-                    result.insert("unread_count".to_string(), serde_json::json!(unread.len()));
-                }
-                "recent" => {
-                    let limit = Self::get_param_u32(&params, "recent_limit", 10);
-                    let days = Self::get_param_u32(&params, "recent_days", 7);
-                    let cutoff = queries::days_ago_cocoa(days);
-                    let messages = helpers::query_recent_messages(&self.conn, cutoff, limit)?;
-
-                    let enriched: Vec<serde_json::Value> = messages
-                        .into_iter()
-                        .map(|msg| self.enrich_recent_message(msg))
-                        .collect();
-                    result.insert("recent".to_string(), serde_json::json!(enriched));
-                }
-                "analytics" => {
-                    let days = Self::get_param_u32(&params, "analytics_days", 30);
-                    let cutoff = queries::days_ago_cocoa(days);
-                    let (total, sent, received) =
-                        helpers::query_message_counts(&self.conn, cutoff, None)?;
-
-                    result.insert(
-                        "analytics".to_string(),
-                        serde_json::json!({
-                            "total_messages": total,
-                            "sent_count": sent,
-                            "received_count": received,
-                            "period_days": days,
-                        }),
-                    );
-                }
-                "followup_count" => {
-                    let days = Self::get_param_u32(&params, "followup_days", 30);
-                    let stale = Self::get_param_u32(&params, "followup_stale", 3);
-                    let cutoff = queries::days_ago_cocoa(days);
-                    let stale_ns = Self::days_to_stale_ns(stale);
-
-                    let unanswered = helpers::query_unanswered_questions(&self.conn, cutoff, stale_ns)?;
-                    let stale_convos = helpers::query_stale_conversations(&self.conn, cutoff, stale_ns)?;
-
-                    result.insert(
-                        "followup_count".to_string(),
-                        serde_json::json!(unanswered.len() + stale_convos.len()),
-                    );
-                }
-                _ => {
-                    // Unknown section, skip silently
+        // One snapshot for every section (the closure's connection is self.conn)
+        let result = connection::read_snapshot(&self.conn, |_| {
+            let mut result = serde_json::Map::new();
+            let max_rowid = helpers::query_max_message_rowid(&self.conn)?;
+            result.insert("meta".to_string(), serde_json::json!({ "snapshot": true, "max_rowid": max_rowid }));
+
+            for section in sections {
+                match section {
+                    "unread_count" => {
+                        let opts = api::UnreadOptions { mutes: self.mutes(&params)?, ..Default::default() };
+                        let unread_count = api::unread_count(&self.conn, opts.mutes.as_ref())?;
+                        let muted_count = api::muted_unread_count(&self.conn, &self.contacts, &opts)?;
+                        result.insert("unread_count".to_string(), serde_json::json!(unread_count));
+                        result.insert("muted_count".to_string(), serde_json::json!(muted_count));
+                    }
+                    "recent" => {
+                        let opts = api::RecentOptions {
+                            limit: Self::get_param_u32(&params, "recent_limit", 10),
+                            days: Some(Self::get_param_u32(&params, "recent_days", 7)),
+                            ..Default::default()
+                        };
+                        let messages = api::recent(&self.conn, &self.contacts, &opts)?;
+                        result.insert("recent".to_string(), serde_json::json!(messages));
+                    }
+                    "analytics" => {
+                        let days = Self::get_param_u32(&params, "analytics_days", 30);
+                        let cutoff = queries::days_ago_cocoa(days);
+                        let (total, sent, received) =
+                            helpers::query_message_counts(&self.conn, cutoff, None, None)?;
+
+                        result.insert(
+                            "analytics".to_string(),
+                            serde_json::json!({
+                                "total_messages": total,
+                                "sent_count": sent,
+                                "received_count": received,
+                                "analysis_period_days": days,
+                            }),
+                        );
+                    }
+                    "heatmap" => {
+                        let days = Self::get_param_u32(&params, "heatmap_days", 30);
+                        let phone = Self::get_param_str(&params, "heatmap_contact")
+                            .map(|name| {
+                                self.contacts
+                                    .find_by_name(name)
+                                    .map(|c| c.phone.clone())
+                                    .ok_or_else(|| anyhow!("Contact '{}' not found", name))
+                            })
+                            .transpose()?;
+                        let heatmap = analytics::build_heatmap(&self.conn, days, phone)?;
+                        result.insert("heatmap".to_string(), serde_json::to_value(heatmap)?);
+                    }
+                    "followup_count" => {
+                        let days = Self::get_param_u32(&params, "followup_days", 30);
+                        let stale = Self::get_param_u32(&params, "followup_stale", 3);
+                        let cutoff = queries::days_ago_cocoa(days);
+                        let stale_ns = Self::days_to_stale_ns(stale);
+
+                        let unanswered = helpers::query_unanswered_questions(&self.conn, cutoff, stale_ns, helpers::FOLLOWUP_LIMIT, false)?;
+                        let stale_convos = helpers::query_stale_conversations(&self.conn, cutoff, stale_ns, false)?;
+
+                        result.insert(
+                            "followup_count".to_string(),
+                            serde_json::json!(unanswered.len() + stale_convos.len()),
+                        );
+                    }
+                    other => output::warn(format!(
+                        "unknown bundle section '{}' (expected unread_count, recent, analytics, followup_count, heatmap)",
+                        other
+                    )),
                 }
             }
-        }
 
-        Ok(serde_json::Value::Object(result))
+            Ok(result)
+        })?;
+
+        let mut bundle = serde_json::Value::Object(result);
+        if let Some(max_tokens) = params.get("max_tokens").and_then(|v| v.as_u64()) {
+            let priority = budget::parse_priority(Self::get_param_str(&params, "section_priority"));
+            budget::trim_to_budget(&mut bundle, max_tokens as usize, &priority, &budget::CharsPerToken::default());
+        }
+        Ok(bundle)
     }
 }
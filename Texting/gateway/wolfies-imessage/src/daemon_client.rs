@@ -0,0 +1,350 @@
+//! Unix socket client for daemon-backed commands, with opt-in auto-start.
+//!
+//! Speaks the daemons' NDJSON protocol (one request line, one response line).
+//! When the socket is missing and auto-start is enabled, the daemon binary
+//! is spawned (`<bin> start --socket <path>`) and the socket polled for a
+//! bounded time.
+//! A lock file around the spawn keeps concurrent invocations from starting
+//! two daemons: the loser waits on the lock, then finds the socket present.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Path comes from the data file registry (data_files::DAEMON_SOCKET)
+//! - 10/16/2026 - Added call() (NDJSON request/response) and per-client timeout
+//! - 10/16/2026 - Initial client with --auto-start-daemon support
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::data_files;
+use crate::storage::FileLock;
+
+/// Daemon binary installed next to the CLI.
+pub const DAEMON_BIN_NAME: &str = "wolfies-imessage-daemon";
+
+/// Env var overriding the daemon binary used for auto-start.
+pub const DAEMON_BIN_ENV: &str = "WOLFIES_DAEMON_BIN";
+
+/// Default read/write timeout per request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long auto-start waits for the socket to appear.
+pub const DEFAULT_START_WAIT: Duration = Duration::from_secs(10);
+
+const START_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Errors talking to (or starting) the daemon.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Socket not found: {0}")]
+    SocketNotFound(String),
+
+    #[error("Connection failed: {0}")]
+    ConnectFailed(#[source] std::io::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Empty response from daemon")]
+    EmptyResponse,
+
+    /// The daemon answered with ok=false.
+    #[error("{message} ({code})")]
+    Daemon { code: String, message: String },
+
+    #[error("Daemon auto-start failed: {0}")]
+    StartFailed(String),
+}
+
+impl ClientError {
+    /// True when the daemon isn't reachable at all (vs. failing a request).
+    pub fn is_unavailable(&self) -> bool {
+        matches!(
+            self,
+            ClientError::SocketNotFound(_) | ClientError::ConnectFailed(_) | ClientError::StartFailed(_)
+        )
+    }
+}
+
+/// Default daemon socket (~/.wolfies-imessage/daemon.sock).
+pub fn default_socket_path() -> PathBuf {
+    data_files::DAEMON_SOCKET.path()
+}
+
+/// Daemon binary for auto-start: $WOLFIES_DAEMON_BIN, else next to the current exe.
+pub fn daemon_binary() -> Option<PathBuf> {
+    if let Ok(bin) = std::env::var(DAEMON_BIN_ENV) {
+        return Some(PathBuf::from(bin));
+    }
+    let exe = std::env::current_exe().ok()?;
+    let sibling = exe.parent()?.join(DAEMON_BIN_NAME);
+    sibling.exists().then_some(sibling)
+}
+
+/// How to start the daemon when its socket is missing.
+#[derive(Debug, Clone)]
+pub struct AutoStart {
+    pub daemon_bin: PathBuf,
+    pub wait: Duration,
+}
+
+/// Client for one daemon socket.
+#[derive(Debug, Clone)]
+pub struct DaemonClient {
+    socket_path: PathBuf,
+    timeout: Duration,
+    auto_start: Option<AutoStart>,
+}
+
+impl DaemonClient {
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            timeout: DEFAULT_TIMEOUT,
+            auto_start: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Start the daemon on demand instead of failing with SocketNotFound.
+    pub fn with_auto_start(mut self, auto_start: AutoStart) -> Self {
+        self.auto_start = Some(auto_start);
+        self
+    }
+
+    /// Make sure the socket exists, auto-starting the daemon if enabled.
+    pub fn ensure_running(&self) -> Result<(), ClientError> {
+        if self.socket_path.exists() {
+            return Ok(());
+        }
+        match &self.auto_start {
+            Some(auto_start) => start_daemon(&self.socket_path, auto_start),
+            None => Err(ClientError::SocketNotFound(self.socket_path.display().to_string())),
+        }
+    }
+
+    /// Send one request; returns the response's `result` on ok=true.
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, ClientError> {
+        self.ensure_running()?;
+
+        let stream = UnixStream::connect(&self.socket_path).map_err(ClientError::ConnectFailed)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let request = json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "v": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut writer = &stream;
+        writer.write_all(serde_json::to_string(&request)?.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+
+        let mut line = String::new();
+        if BufReader::new(&stream).read_line(&mut line)? == 0 {
+            return Err(ClientError::EmptyResponse);
+        }
+        parse_response(&line)
+    }
+}
+
+/// Unwrap a response line: `result` on ok=true, `ClientError::Daemon` otherwise.
+fn parse_response(line: &str) -> Result<Value, ClientError> {
+    let mut response: Value = serde_json::from_str(line)?;
+    if response["ok"].as_bool().unwrap_or(false) {
+        return Ok(response["result"].take());
+    }
+    let error = &response["error"];
+    Err(ClientError::Daemon {
+        code: error["code"].as_str().unwrap_or("ERROR").to_string(),
+        message: error["message"].as_str().unwrap_or("unknown error").to_string(),
+    })
+}
+
+/// Spawn the daemon (once, under a lock) and wait for its socket.
+fn start_daemon(socket: &Path, auto_start: &AutoStart) -> Result<(), ClientError> {
+    let _lock = FileLock::acquire(socket)
+        .map_err(|e| ClientError::StartFailed(format!("{:#}", e)))?;
+
+    // Another invocation may have started it while we waited for the lock
+    if socket.exists() {
+        return Ok(());
+    }
+
+    let mut child = Command::new(&auto_start.daemon_bin)
+        .arg("start")
+        .arg("--socket")
+        .arg(socket)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            ClientError::StartFailed(format!("{}: {}", auto_start.daemon_bin.display(), e))
+        })?;
+
+    let deadline = Instant::now() + auto_start.wait;
+    loop {
+        if socket.exists() {
+            return Ok(());
+        }
+        // `start` daemonizes, so a clean early exit is normal; a failing one isn't
+        if let Ok(Some(status)) = child.try_wait() {
+            if !status.success() {
+                return Err(ClientError::StartFailed(format!(
+                    "{} exited with {}",
+                    auto_start.daemon_bin.display(),
+                    status
+                )));
+            }
+        }
+        if Instant::now() >= deadline {
+            return Err(ClientError::StartFailed(format!(
+                "socket {} did not appear within {:?}",
+                socket.display(),
+                auto_start.wait
+            )));
+        }
+        std::thread::sleep(START_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Fake daemon: records each start, then creates the socket after a delay.
+    fn fake_daemon(dir: &Path, delay_secs: &str, creates_socket: bool) -> PathBuf {
+        let bin = dir.join("fake-daemon");
+        let starts = dir.join("starts.log");
+        let create = if creates_socket { "touch \"$3\"" } else { "true" };
+        let script = format!(
+            "#!/bin/sh\necho start >> \"{}\"\nsleep {}\n{}\n",
+            starts.display(),
+            delay_secs,
+            create
+        );
+        std::fs::write(&bin, script).unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        bin
+    }
+
+    fn start_count(dir: &Path) -> usize {
+        std::fs::read_to_string(dir.join("starts.log"))
+            .map(|s| s.lines().count())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_missing_socket_without_auto_start() {
+        let dir = tempfile::tempdir().unwrap();
+        fake_daemon(dir.path(), "0", true);
+        let client = DaemonClient::new(dir.path().join("daemon.sock"));
+
+        assert!(matches!(client.ensure_running(), Err(ClientError::SocketNotFound(_))));
+        assert_eq!(start_count(dir.path()), 0);
+    }
+
+    #[test]
+    fn test_auto_start_waits_for_slow_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let client = DaemonClient::new(&socket).with_auto_start(AutoStart {
+            daemon_bin: fake_daemon(dir.path(), "0.3", true),
+            wait: Duration::from_secs(5),
+        });
+
+        client.ensure_running().unwrap();
+        assert!(socket.exists());
+        assert_eq!(start_count(dir.path()), 1);
+
+        // Already running: no second start
+        client.ensure_running().unwrap();
+        assert_eq!(start_count(dir.path()), 1);
+    }
+
+    #[test]
+    fn test_concurrent_auto_start_spawns_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let client = DaemonClient::new(&socket).with_auto_start(AutoStart {
+            daemon_bin: fake_daemon(dir.path(), "0.3", true),
+            wait: Duration::from_secs(5),
+        });
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                std::thread::spawn(move || client.ensure_running())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+        assert_eq!(start_count(dir.path()), 1);
+    }
+
+    #[test]
+    fn test_call_round_trip() {
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let response = json!({
+                "id": request["id"],
+                "ok": true,
+                "result": {"method": request["method"], "days": request["params"]["days"]},
+                "meta": {"server_ms": 1.0, "protocol_v": 1},
+            });
+            (&stream).write_all(format!("{}\n", response).as_bytes()).unwrap();
+        });
+
+        let result = DaemonClient::new(&socket).call("stats", json!({"days": 7})).unwrap();
+        server.join().unwrap();
+        assert_eq!(result, json!({"method": "stats", "days": 7}));
+    }
+
+    #[test]
+    fn test_parse_error_response() {
+        let line = r#"{"id":"1","ok":false,"result":null,"error":{"code":"UNKNOWN_METHOD","message":"Unknown method: x"}}"#;
+        match parse_response(line) {
+            Err(ClientError::Daemon { code, .. }) => assert_eq!(code, "UNKNOWN_METHOD"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(ClientError::SocketNotFound("s".into()).is_unavailable());
+        assert!(!ClientError::EmptyResponse.is_unavailable());
+    }
+
+    #[test]
+    fn test_auto_start_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = DaemonClient::new(dir.path().join("daemon.sock"))
+            .with_auto_start(AutoStart {
+                daemon_bin: fake_daemon(dir.path(), "0", false),
+                wait: Duration::from_millis(300),
+            });
+
+        assert!(matches!(client.ensure_running(), Err(ClientError::StartFailed(_))));
+    }
+}
@@ -0,0 +1,154 @@
+//! Registry of everything kept in the data directory (~/.wolfies-imessage).
+//!
+//! Each store takes its path from its entry here (`MUTES.path()`, ...), so
+//! this list is the one place file names live. `maintenance status` lists
+//! the directory against it and `maintenance prune` deletes by `DataClass`:
+//! a new store shows up in both once it has an entry, and a file matching
+//! no entry is listed as unknown and never pruned.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - ROTATED_LOGS (daemon.log.*) back: the daemon now rotates daemon.log at start
+//! - 10/16/2026 - Initial data file registry
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::storage;
+
+/// What a data file is, which decides whether `maintenance prune` removes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    /// Settings and credentials; never pruned
+    Config,
+    /// User state (mutes, presets, snoozes, the send log); never pruned
+    State,
+    /// Drafts and scheduled sends not yet sent; pruned only with `--force`
+    Pending,
+    /// contacts.json backups; pruned only with `--force`
+    Backup,
+    /// Rebuilt on demand (`--caches`)
+    Cache,
+    /// The daemon log being written; never pruned
+    Log,
+    /// Rotated daemon logs (`--logs`)
+    RotatedLog,
+    /// Daemon sockets and pid files; pruned once the daemon is gone
+    Runtime,
+    /// Lock files and in-progress atomic writes; never pruned
+    Transient,
+    /// Matches no registry entry; never pruned
+    Unknown,
+}
+
+/// One registry entry: a file name, or a pattern with one `*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataFile {
+    pub name: &'static str,
+    pub class: DataClass,
+    pub about: &'static str,
+}
+
+impl DataFile {
+    /// This entry's path in the data directory (for exact names).
+    pub fn path(&self) -> PathBuf {
+        storage::data_file(self.name)
+    }
+
+    pub fn matches(&self, file_name: &str) -> bool {
+        match self.name.split_once('*') {
+            Some((prefix, suffix)) => {
+                file_name.len() >= prefix.len() + suffix.len()
+                    && file_name.starts_with(prefix)
+                    && file_name.ends_with(suffix)
+            }
+            None => file_name == self.name,
+        }
+    }
+}
+
+pub const CONFIG: DataFile = DataFile { name: "config.toml", class: DataClass::Config, about: "Settings" };
+pub const DAEMON_TOKEN: DataFile =
+    DataFile { name: "daemon.token", class: DataClass::Config, about: "Shared secret for the daemon's TCP listener" };
+pub const MUTES: DataFile = DataFile { name: "mutes.json", class: DataClass::State, about: "Muted conversations" };
+pub const PRESETS: DataFile = DataFile { name: "presets.json", class: DataClass::State, about: "Saved presets" };
+pub const SNOOZES: DataFile = DataFile { name: "snoozes.json", class: DataClass::State, about: "Snoozed followups" };
+pub const SEND_LOG: DataFile =
+    DataFile { name: "send_log.ndjson", class: DataClass::State, about: "Sent message log (undo, send limits)" };
+pub const DRAFTS: DataFile = DataFile { name: "drafts.json", class: DataClass::Pending, about: "Queued drafts" };
+pub const SCHEDULED: DataFile =
+    DataFile { name: "scheduled.json", class: DataClass::Pending, about: "Scheduled sends" };
+pub const CONTACTS_BACKUPS: DataFile =
+    DataFile { name: "contacts-backups", class: DataClass::Backup, about: "contacts.json backups" };
+pub const TEXT_CACHE: DataFile =
+    DataFile { name: "textcache.db", class: DataClass::Cache, about: "Extracted-text cache" };
+pub const TEXT_CACHE_SIDECARS: DataFile =
+    DataFile { name: "textcache.db-*", class: DataClass::Cache, about: "Text cache WAL and shared memory" };
+pub const DAEMON_LOG: DataFile = DataFile { name: "daemon.log", class: DataClass::Log, about: "Daemon log" };
+pub const ROTATED_LOGS: DataFile =
+    DataFile { name: "daemon.log.*", class: DataClass::RotatedLog, about: "Rotated daemon log" };
+pub const DAEMON_SOCKET: DataFile = DataFile { name: "daemon.sock", class: DataClass::Runtime, about: "Daemon socket" };
+pub const SOCKETS: DataFile = DataFile { name: "*.sock", class: DataClass::Runtime, about: "Daemon socket" };
+pub const PID_FILES: DataFile = DataFile { name: "*.sock.pid", class: DataClass::Runtime, about: "Daemon pid file" };
+pub const LOCKS: DataFile = DataFile { name: "*.lock", class: DataClass::Transient, about: "Lock file" };
+pub const TEMP_FILES: DataFile =
+    DataFile { name: ".*.tmp", class: DataClass::Transient, about: "Atomic write in progress" };
+
+/// Every entry; a file is described by the first one it matches.
+pub const REGISTRY: &[DataFile] = &[
+    CONFIG,
+    DAEMON_TOKEN,
+    MUTES,
+    PRESETS,
+    SNOOZES,
+    SEND_LOG,
+    DRAFTS,
+    SCHEDULED,
+    CONTACTS_BACKUPS,
+    TEXT_CACHE,
+    TEXT_CACHE_SIDECARS,
+    DAEMON_LOG,
+    ROTATED_LOGS,
+    DAEMON_SOCKET,
+    SOCKETS,
+    PID_FILES,
+    LOCKS,
+    TEMP_FILES,
+];
+
+/// The registry entry describing `file_name`, if any.
+pub fn classify(file_name: &str) -> Option<&'static DataFile> {
+    REGISTRY.iter().find(|entry| entry.matches(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_names_and_patterns() {
+        let cases = [
+            ("mutes.json", Some(DataClass::State)),
+            ("drafts.json", Some(DataClass::Pending)),
+            ("textcache.db-wal", Some(DataClass::Cache)),
+            ("daemon.log", Some(DataClass::Log)),
+            ("daemon.log.1", Some(DataClass::RotatedLog)),
+            ("daemon.sock.pid", Some(DataClass::Runtime)),
+            ("other.sock", Some(DataClass::Runtime)),
+            ("mutes.json.lock", Some(DataClass::Transient)),
+            (".mutes.json.42.tmp", Some(DataClass::Transient)),
+            ("notes.txt", None),
+        ];
+        for (name, class) in cases {
+            assert_eq!(classify(name).map(|e| e.class), class, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_exact_names_are_unique_and_classified_as_themselves() {
+        for entry in REGISTRY.iter().filter(|e| !e.name.contains('*')) {
+            assert_eq!(classify(entry.name), Some(entry), "{}", entry.name);
+        }
+    }
+}
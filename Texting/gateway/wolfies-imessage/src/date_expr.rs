@@ -0,0 +1,348 @@
+//! Recognizer for date/time expressions in casual message text.
+//!
+//! Handles what people actually type: "tomorrow at 3", "next tues",
+//! "10/20", "Oct 31st 7pm", "in 2 hours", "lunch at noon". Everything is
+//! resolved against a reference time (the message's own timestamp, local
+//! time), so "tomorrow" in a week-old message means the day after it was sent.
+//!
+//! Conventions:
+//! - A bare weekday is its next occurrence, today included ("friday" sent
+//!   on a Friday is that day); "next <weekday>" is that day in the
+//!   following Monday-start week.
+//! - Numeric dates are month/day (US). A date without a year is the
+//!   occurrence nearest the reference: "10/1" sent on 10/16 is two weeks
+//!   ago, not next October, while "1/5" sent then is next January.
+//! - An hour with no am/pm is pm for 1-7 (nobody means "at 3" as 3am), or
+//!   whenever the text says tonight/evening.
+//! - A time with no date is today, or tomorrow once that time has passed.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - A date without a year is its nearest occurrence (a recent past date stays in this year)
+//! - 10/16/2026 - Initial recognizer (relative days, weekdays, numeric and
+//!   month-name dates, clock times, "in N units")
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A recognized date, with the time of day when the text gave one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateMatch {
+    pub datetime: NaiveDateTime,
+    pub has_time: bool,
+}
+
+impl DateMatch {
+    pub fn date(&self) -> NaiveDate {
+        self.datetime.date()
+    }
+}
+
+/// Find the first date/time expression in `text`, resolved against `reference`.
+pub fn recognize(text: &str, reference: NaiveDateTime) -> Option<DateMatch> {
+    let lower = text.to_lowercase();
+
+    // "in 2 hours" is a complete instant on its own
+    if let Some(m) = relative_offset(&lower, reference) {
+        return Some(m);
+    }
+
+    let date = day_word(&lower, reference.date())
+        .or_else(|| weekday(&lower, reference.date()))
+        .or_else(|| month_name_date(&lower, reference.date()))
+        .or_else(|| numeric_date(&lower, reference.date()));
+    let time = clock_time(&lower);
+
+    match (date, time) {
+        (Some(date), Some(time)) => Some(DateMatch {
+            datetime: date.and_time(time),
+            has_time: true,
+        }),
+        (Some(date), None) => Some(DateMatch {
+            datetime: date.and_time(NaiveTime::MIN),
+            has_time: false,
+        }),
+        (None, Some(time)) => {
+            let today = reference.date().and_time(time);
+            let datetime = if today > reference { today } else { today + Duration::days(1) };
+            Some(DateMatch { datetime, has_time: true })
+        }
+        (None, None) => None,
+    }
+}
+
+fn re(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("valid date_expr regex"))
+}
+
+/// "in 20 minutes", "in an hour", "in 3 days", "in a week".
+fn relative_offset(text: &str, reference: NaiveDateTime) -> Option<DateMatch> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let caps = re(&RE, r"\bin (a|an|one|\d{1,3}) (minute|min|hour|hr|day|week)s?\b").captures(text)?;
+    let n: i64 = match &caps[1] {
+        "a" | "an" | "one" => 1,
+        digits => digits.parse().ok()?,
+    };
+    let (offset, has_time) = match &caps[2] {
+        "minute" | "min" => (Duration::minutes(n), true),
+        "hour" | "hr" => (Duration::hours(n), true),
+        "day" => (Duration::days(n), false),
+        _ => (Duration::weeks(n), false),
+    };
+    let instant = reference + offset;
+    Some(DateMatch {
+        datetime: if has_time { instant } else { instant.date().and_time(NaiveTime::MIN) },
+        has_time,
+    })
+}
+
+/// today / tonight / tomorrow / day after tomorrow / next week.
+fn day_word(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let caps = re(
+        &RE,
+        r"\b(day after (?:tomorrow|tmrw|tmr)|tomorrow|tmrw|tmr|2morrow|today|tonight|this (?:morning|afternoon|evening)|next week)\b",
+    )
+    .captures(text)?;
+    let days = match &caps[1] {
+        s if s.starts_with("day after") => 2,
+        "tomorrow" | "tmrw" | "tmr" | "2morrow" => 1,
+        "next week" => 7,
+        _ => 0,
+    };
+    Some(today + Duration::days(days))
+}
+
+/// "friday", "on tues", "this sat", "next monday". Abbreviations need a
+/// leading this/next/on, since "sat", "wed", and "sun" are ordinary words.
+fn weekday(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = re(
+        &RE,
+        r"\b(?:(this|next|on) )?(monday|tuesday|wednesday|thursday|friday|saturday|sunday|mon|tues|tue|wed|thurs|thur|thu|fri|sat|sun)\b",
+    );
+    for caps in re.captures_iter(text) {
+        let prefix = caps.get(1).map(|m| m.as_str());
+        let name = &caps[2];
+        let full = name.ends_with("day");
+        if !full && prefix.is_none() {
+            continue;
+        }
+        let target = parse_weekday(name)?;
+        let date = if prefix == Some("next") {
+            let next_monday = today + Duration::days(7 - today.weekday().num_days_from_monday() as i64);
+            next_monday + Duration::days(target.num_days_from_monday() as i64)
+        } else {
+            let ahead = (7 + target.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64) % 7;
+            today + Duration::days(ahead)
+        };
+        return Some(date);
+    }
+    None
+}
+
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    Some(match &name[..3] {
+        "mon" => Weekday::Mon,
+        "tue" => Weekday::Tue,
+        "wed" => Weekday::Wed,
+        "thu" => Weekday::Thu,
+        "fri" => Weekday::Fri,
+        "sat" => Weekday::Sat,
+        "sun" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// "oct 31", "October 31st", "31st of october", "31 oct".
+fn month_name_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    const MONTHS: &str = r"(jan|january|feb|february|mar|march|apr|april|may|jun|june|jul|july|aug|august|sep|sept|september|oct|october|nov|november|dec|december)";
+    static MONTH_DAY: OnceLock<Regex> = OnceLock::new();
+    static DAY_MONTH: OnceLock<Regex> = OnceLock::new();
+
+    let month_day = re(&MONTH_DAY, &format!(r"\b{}\.? (\d{{1,2}})(?:st|nd|rd|th)?\b", MONTHS));
+    let day_month = re(&DAY_MONTH, &format!(r"\b(\d{{1,2}})(?:st|nd|rd|th)? (?:of )?{}\b", MONTHS));
+
+    let (month, day) = if let Some(c) = month_day.captures(text) {
+        (month_number(&c[1])?, c[2].parse().ok()?)
+    } else {
+        let c = day_month.captures(text)?;
+        (month_number(&c[2])?, c[1].parse().ok()?)
+    };
+    nearest(today, month, day, None)
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let idx = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"]
+        .iter()
+        .position(|m| name.starts_with(m))?;
+    Some(idx as u32 + 1)
+}
+
+/// "10/20", "10/20/26", "10/20/2026" (month first).
+fn numeric_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let caps = re(&RE, r"\b(\d{1,2})/(\d{1,2})(?:/(\d{4}|\d{2}))?\b").captures(text)?;
+    let year = caps.get(3).and_then(|y| y.as_str().parse::<i32>().ok()).map(|y| if y < 100 { 2000 + y } else { y });
+    nearest(today, caps[1].parse().ok()?, caps[2].parse().ok()?, year)
+}
+
+/// The month/day in `year`, or else the one nearest `today` (last year's,
+/// this year's, or next year's; the later one on a tie).
+fn nearest(today: NaiveDate, month: u32, day: u32, year: Option<i32>) -> Option<NaiveDate> {
+    if let Some(year) = year {
+        return NaiveDate::from_ymd_opt(year, month, day);
+    }
+    [today.year() + 1, today.year(), today.year() - 1]
+        .into_iter()
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .min_by_key(|date| (*date - today).num_days().abs())
+}
+
+/// "3pm", "3:30 p.m.", "15:00", "at 7", "noon".
+fn clock_time(text: &str) -> Option<NaiveTime> {
+    static NOON: OnceLock<Regex> = OnceLock::new();
+    static MERIDIEM: OnceLock<Regex> = OnceLock::new();
+    static COLON: OnceLock<Regex> = OnceLock::new();
+    static AT_HOUR: OnceLock<Regex> = OnceLock::new();
+    static EVENING: OnceLock<Regex> = OnceLock::new();
+
+    if re(&NOON, r"\b(noon|midday)\b").is_match(text) {
+        return NaiveTime::from_hms_opt(12, 0, 0);
+    }
+    // "3 pm" may take a space; a bare "3p" may not ("1 a day" isn't a time)
+    let meridiem = re(
+        &MERIDIEM,
+        r"\b(\d{1,2})(?::(\d{2}))?(?:\s?(a\.m\.|p\.m\.|am|pm)|(a|p))(?:\s|$|[^a-z])",
+    );
+    if let Some(c) = meridiem.captures(text) {
+        let hour: u32 = c[1].parse().ok()?;
+        let minute: u32 = c.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        let pm = c.get(3).or(c.get(4)).is_some_and(|m| m.as_str().starts_with('p'));
+        let hour = match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (h, true) => h + 12,
+            (h, false) => h,
+        };
+        return NaiveTime::from_hms_opt(hour, minute, 0);
+    }
+
+    let evening = re(&EVENING, r"\b(tonight|this evening|dinner)\b").is_match(text);
+    let (hour, minute) = if let Some(c) = re(&COLON, r"\b(\d{1,2}):(\d{2})\b").captures(text) {
+        (c[1].parse::<u32>().ok()?, c[2].parse::<u32>().ok()?)
+    } else {
+        let c = re(&AT_HOUR, r"\bat (\d{1,2})(?:\s|$|[.,!?])").captures(text)?;
+        (c[1].parse::<u32>().ok()?, 0)
+    };
+    let hour = if (1..=7).contains(&hour) || (evening && (1..12).contains(&hour)) {
+        hour + 12
+    } else {
+        hour
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday, Oct 16 2026, 10:00.
+    fn reference() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(10, 0, 0).unwrap()
+    }
+
+    /// For the dates used here, the nearest Jan-Apr to the reference is next year's.
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> Option<(String, bool)> {
+        let dt = NaiveDate::from_ymd_opt(2026 + (month < 5) as i32, month, day)?.and_hms_opt(hour, minute, 0)?;
+        Some((dt.format("%Y-%m-%d %H:%M").to_string(), true))
+    }
+
+    fn on(month: u32, day: u32) -> Option<(String, bool)> {
+        let date = NaiveDate::from_ymd_opt(2026 + (month < 5) as i32, month, day)?;
+        Some((date.format("%Y-%m-%d 00:00").to_string(), false))
+    }
+
+    #[test]
+    fn test_recognize_table() {
+        let cases: &[(&str, Option<(String, bool)>)] = &[
+            // Relative days
+            ("see you tomorrow", on(10, 17)),
+            ("see you tmrw at 3", at(10, 17, 15, 0)),
+            ("Tomorrow at 9am works", at(10, 17, 9, 0)),
+            ("day after tomorrow then", on(10, 18)),
+            ("today is busy", on(10, 16)),
+            ("tonight at 8", at(10, 16, 20, 0)),
+            ("dinner at 8?", at(10, 16, 20, 0)),
+            ("next week sometime", on(10, 23)),
+            // Weekdays (reference is a Friday)
+            ("friday it is", on(10, 16)),
+            ("how about Monday", on(10, 19)),
+            ("on wed at 2:30", at(10, 21, 14, 30)),
+            ("this sat", on(10, 17)),
+            ("next tuesday at 11am", at(10, 20, 11, 0)),
+            ("next friday", on(10, 23)),
+            ("I sat down", None),
+            ("the sun is out", None),
+            // Explicit dates
+            ("due 10/20", on(10, 20)),
+            ("due 10/20 at 5pm", at(10, 20, 17, 0)),
+            ("due 1/5", on(1, 5)),
+            ("was due 10/1", on(10, 1)),
+            ("back on 5/1", on(5, 1)),
+            ("by 4/10", on(4, 10)),
+            ("on 10/20/2027", Some(("2027-10-20 00:00".to_string(), false))),
+            ("on 3/4/27", Some(("2027-03-04 00:00".to_string(), false))),
+            ("Oct 31st at 7pm", at(10, 31, 19, 0)),
+            ("october 31", on(10, 31)),
+            ("the 2nd of November", on(11, 2)),
+            ("sept 3", on(9, 3)),
+            ("13/45 is not a date", None),
+            ("2/30", None),
+            // Times only
+            ("call you at 3", at(10, 16, 15, 0)),
+            ("call you at 3:30pm", at(10, 16, 15, 30)),
+            ("at 3:30 p.m.", at(10, 16, 15, 30)),
+            ("lunch at noon", at(10, 16, 12, 0)),
+            ("15:00 works", at(10, 16, 15, 0)),
+            ("meet at 11", at(10, 16, 11, 0)),
+            ("at 9", at(10, 17, 9, 0)),
+            ("12am", at(10, 17, 0, 0)),
+            ("12pm", at(10, 16, 12, 0)),
+            ("7p", at(10, 16, 19, 0)),
+            ("5 pm", at(10, 16, 17, 0)),
+            ("take 1 a day", None),
+            // In N units
+            ("in 2 hours", at(10, 16, 12, 0)),
+            ("in an hour", at(10, 16, 11, 0)),
+            ("in 20 mins", at(10, 16, 10, 20)),
+            ("in 3 days", on(10, 19)),
+            ("in a week", on(10, 23)),
+            // Nothing
+            ("sounds good", None),
+            ("I have 3 cats", None),
+            ("at 30 degrees", None),
+            ("", None),
+        ];
+        for (text, expected) in cases {
+            let got = recognize(text, reference()).map(|m| (m.datetime.format("%Y-%m-%d %H:%M").to_string(), m.has_time));
+            assert_eq!(&got, expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_relative_to_message_time() {
+        // "tomorrow" in an old message is relative to when it was sent
+        let sent = NaiveDate::from_ymd_opt(2026, 2, 27).unwrap().and_hms_opt(18, 0, 0).unwrap();
+        let m = recognize("tomorrow", sent).unwrap();
+        assert_eq!(m.date(), NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+        let m = recognize("day after tomorrow", sent).unwrap();
+        assert_eq!(m.date(), NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        // A date without a year near the turn of the year: last December
+        let sent = NaiveDate::from_ymd_opt(2027, 1, 5).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(recognize("since 12/20", sent).unwrap().date(), NaiveDate::from_ymd_opt(2026, 12, 20).unwrap());
+    }
+}
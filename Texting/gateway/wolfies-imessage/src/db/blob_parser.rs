@@ -1,43 +1,102 @@
 //! Parser for attributedBody binary blobs in Messages.db.
 //!
 //! macOS Messages stores text in two formats:
+//! - 10/16/2026 - ParsedBlob.strategy: which decoder produced the text (raw-message)
 //! - `text` column: Plain text (older messages)
 //! - `attributedBody` column: Binary blob (macOS Ventura+)
 //!
 //! The blob is typically NSKeyedArchiver format (bplist) or streamtyped format.
+//! Streamtyped blobs are decoded by `typedstream`; the heuristics below are
+//! the fallback when that decoder can't make sense of a blob.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Entities derives JsonSchema
+//! - 10/16/2026 - parse_blob returns text + entities (mentions, links); replaces extract_text_from_blob
+//! - 10/16/2026 - Try the typedstream decoder first; heuristics are now the fallback
 //! - 01/10/2026 - Implemented full blob parsing (Claude)
 //! - 01/10/2026 - Initial stub (Claude)
 
 use anyhow::Result;
 use plist::Value;
+use schemars::JsonSchema;
+use serde::Serialize;
 
-/// Extract text from an attributedBody blob.
+use super::typedstream;
+
+/// Structured entities attached to a message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, JsonSchema)]
+pub struct Entities {
+    /// Handles from confirmed @-mentions.
+    pub mentions: Vec<String>,
+    /// Inline link URLs.
+    pub links: Vec<String>,
+    /// Subject line (message.subject column; never in the blob).
+    pub subject: Option<String>,
+}
+
+/// Which of `parse_blob`'s decoders produced the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobStrategy {
+    Typedstream,
+    Bplist,
+    Streamtyped,
+    /// Longest readable run (last resort)
+    Readable,
+}
+
+/// Text and entities parsed from an attributedBody blob.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedBlob {
+    pub text: String,
+    pub entities: Entities,
+    pub strategy: BlobStrategy,
+}
+
+impl ParsedBlob {
+    fn text_only(text: String, strategy: BlobStrategy) -> Self {
+        Self { text, entities: Entities::default(), strategy }
+    }
+}
+
+/// Parse an attributedBody blob into text plus entities.
 ///
 /// Handles multiple formats:
-/// 1. NSKeyedArchiver bplist format
-/// 2. Streamtyped format (NSString markers)
-/// 3. Fallback regex extraction
-pub fn extract_text_from_blob(blob: &[u8]) -> Result<Option<String>> {
+/// 1. Typedstream (full NSAttributedString decode, with mentions/links)
+/// 2. NSKeyedArchiver bplist format
+/// 3. Streamtyped heuristics (NSString markers)
+/// 4. Fallback regex extraction
+///
+/// Only the typedstream path yields entities.
+pub fn parse_blob(blob: &[u8]) -> Result<Option<ParsedBlob>> {
     if blob.is_empty() {
         return Ok(None);
     }
 
+    // Checked before bplist: data-detector attributes embed archived bplists
+    if let Some(body) = typedstream::decode_attributed_body(blob) {
+        let entities = Entities {
+            mentions: body.mentions().into_iter().map(String::from).collect(),
+            links: body.links().into_iter().map(String::from).collect(),
+            subject: None,
+        };
+        return Ok(Some(ParsedBlob { text: body.text, entities, strategy: BlobStrategy::Typedstream }));
+    }
+
     // Find bplist header (may not be at start of blob)
     if let Some(bplist_start) = find_subsequence(blob, b"bplist") {
         if let Ok(Some(text)) = parse_bplist(&blob[bplist_start..]) {
-            return Ok(Some(text));
+            return Ok(Some(ParsedBlob::text_only(text, BlobStrategy::Bplist)));
         }
     }
 
     // Try streamtyped format
     if let Some(text) = parse_streamtyped(blob) {
-        return Ok(Some(text));
+        return Ok(Some(ParsedBlob::text_only(text, BlobStrategy::Streamtyped)));
     }
 
     // Fallback: try to extract any readable text
-    Ok(extract_readable_text(blob))
+    Ok(extract_readable_text(blob).map(|text| ParsedBlob::text_only(text, BlobStrategy::Readable)))
 }
 
 /// Find a subsequence in a byte slice.
@@ -62,14 +121,9 @@ fn parse_bplist(blob: &[u8]) -> Result<Option<String>> {
 
             for obj in objects {
                 match obj {
-                    Value::String(s) => {
-                        // Skip class names and metadata
-                        if !s.starts_with("NS")
-                            && !s.starts_with('$')
-                            && !s.is_empty()
-                        {
-                            text_candidates.push(s.clone());
-                        }
+                    // Skip class names and metadata
+                    Value::String(s) if !s.starts_with("NS") && !s.starts_with('$') && !s.is_empty() => {
+                        text_candidates.push(s.clone());
                     }
                     Value::Dictionary(d) => {
                         // Sometimes text is in NS.string key
@@ -227,11 +281,9 @@ fn extract_readable_text(blob: &[u8]) -> Option<String> {
                 let should_skip = skip_patterns.iter().any(|p| current_run.contains(p));
                 if !should_skip {
                     let cleaned = current_run.trim_matches('+').trim();
-                    if cleaned.len() >= 2 {
-                        // Prefer longer runs
-                        if best_candidate.as_ref().map_or(true, |b| cleaned.len() > b.len()) {
-                            best_candidate = Some(cleaned.to_string());
-                        }
+                    // Prefer longer runs
+                    if cleaned.len() >= 2 && best_candidate.as_ref().is_none_or(|b| cleaned.len() > b.len()) {
+                        best_candidate = Some(cleaned.to_string());
                     }
                 }
             }
@@ -244,10 +296,8 @@ fn extract_readable_text(blob: &[u8]) -> Option<String> {
         let should_skip = skip_patterns.iter().any(|p| current_run.contains(p));
         if !should_skip {
             let cleaned = current_run.trim_matches('+').trim();
-            if cleaned.len() >= 2 {
-                if best_candidate.as_ref().map_or(true, |b| cleaned.len() > b.len()) {
-                    best_candidate = Some(cleaned.to_string());
-                }
+            if cleaned.len() >= 2 && best_candidate.as_ref().is_none_or(|b| cleaned.len() > b.len()) {
+                best_candidate = Some(cleaned.to_string());
             }
         }
     }
@@ -259,10 +309,13 @@ fn extract_readable_text(blob: &[u8]) -> Option<String> {
 mod tests {
     use super::*;
 
+    fn text(blob: &[u8]) -> Option<String> {
+        parse_blob(blob).unwrap().map(|p| p.text)
+    }
+
     #[test]
     fn test_empty_blob() {
-        let result = extract_text_from_blob(&[]).unwrap();
-        assert_eq!(result, None);
+        assert_eq!(parse_blob(&[]).unwrap(), None);
     }
 
     #[test]
@@ -274,8 +327,44 @@ mod tests {
         blob.extend_from_slice(b"Hello"); // actual text
         blob.extend_from_slice(&[0x86, 0x84]); // end markers
 
-        let result = extract_text_from_blob(&blob).unwrap();
-        assert_eq!(result, Some("Hello".to_string()));
+        assert_eq!(text(&blob), Some("Hello".to_string()));
+    }
+
+    #[test]
+    fn test_typedstream_preferred_over_embedded_bplist() {
+        let blob = include_bytes!("testdata/attributed_body/data_detected.bin");
+        assert_eq!(text(blob), Some("Call me at (415) 555-0199 tomorrow".to_string()));
+    }
+
+    #[test]
+    fn test_entities_mention() {
+        let parsed = parse_blob(include_bytes!("testdata/attributed_body/mention.bin"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.text, "Hey @Sarah are you coming?");
+        assert_eq!(parsed.strategy, BlobStrategy::Typedstream);
+        assert_eq!(parsed.entities.mentions, vec!["+14155551234"]);
+        assert!(parsed.entities.links.is_empty());
+    }
+
+    #[test]
+    fn test_entities_rich_link() {
+        let parsed = parse_blob(include_bytes!("testdata/attributed_body/link.bin"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.entities.links, vec!["https://example.com/menu"]);
+        assert!(parsed.entities.mentions.is_empty());
+        assert_eq!(parsed.entities.subject, None);
+    }
+
+    #[test]
+    fn test_heuristic_fallback_has_no_entities() {
+        let mut blob: Vec<u8> = b"streamtypedNSString".to_vec();
+        blob.extend_from_slice(&[0x01, 0x94, 0x84, 0x01, b'+', 0x02, b'h', b'i', 0x86]);
+        let parsed = parse_blob(&blob).unwrap().unwrap();
+        assert_eq!(parsed.text, "hi");
+        assert_eq!(parsed.strategy, BlobStrategy::Streamtyped);
+        assert_eq!(parsed.entities, Entities::default());
     }
 
     #[test]
@@ -1,13 +1,48 @@
 //! SQLite connection management for Messages.db.
 //!
+//! With `--explain`, connections from `open_db` record every statement they
+//! run (SQL and time) and the slowest query is explained afterwards.
+//!
 //! CHANGELOG:
+//! - 10/16/2026 - Added read_snapshot (one deferred read transaction across a multi-statement read)
+//! - 10/16/2026 - Statement recording and EXPLAIN QUERY PLAN capture (--explain, doctor --performance)
+//! - 10/16/2026 - Added open_read_only and attach_read_only (compare against a backup)
+//! - 10/16/2026 - open_db uses db_path from config.toml when set
 //! - 01/10/2026 - Initial stub (Claude)
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use rusqlite::Connection;
-use std::path::PathBuf;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Whether `open_db` connections record their statements (`--explain`).
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Statements run on recording connections, with their run time.
+static STATEMENTS: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+/// One row of EXPLAIN QUERY PLAN output.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct PlanRow {
+    pub id: i64,
+    pub parent: i64,
+    /// e.g. "SCAN m", "SEARCH h USING INTEGER PRIMARY KEY (rowid=?)"
+    pub detail: String,
+}
+
+/// A recorded query with its plan (`--explain`).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QueryPlan {
+    pub sql: String,
+    pub elapsed_ms: f64,
+    pub plan: Vec<PlanRow>,
+}
 
-/// Default Messages.db path.
+/// Built-in Messages.db path (config.toml's db_path overrides it).
 pub fn default_db_path() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -16,19 +51,136 @@ pub fn default_db_path() -> PathBuf {
         .join("chat.db")
 }
 
-/// Open a read-only connection to Messages.db.
+/// Open a read-only connection to Messages.db (the configured db_path).
 pub fn open_db() -> Result<Connection> {
-    let db_path = default_db_path();
+    let db_path = &crate::config::active().db_path.value;
 
     // [*INCOMPLETE*] Check for security-scoped bookmark first
-    // Status: Opens default path only
+    // Status: Opens the configured path only
     // Remaining: Integrate with bookmark storage from db_access.py
 
-    Connection::open_with_flags(
-        &db_path,
-        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
-    )
-    .with_context(|| format!("Failed to open Messages database at {:?}", db_path))
+    let mut conn =
+        open_read_only(db_path).with_context(|| format!("Failed to open Messages database at {:?}", db_path))?;
+    if RECORDING.load(Ordering::Relaxed) {
+        record_statements_on(&mut conn);
+    }
+    Ok(conn)
+}
+
+/// Make `open_db` connections record their statements from now on.
+pub fn record_statements() {
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// Record the statements `conn` runs (see `primary_statement`).
+pub fn record_statements_on(conn: &mut Connection) {
+    conn.profile(Some(record_statement));
+}
+
+fn record_statement(sql: &str, elapsed: Duration) {
+    if let Ok(mut statements) = STATEMENTS.lock() {
+        statements.push((sql.to_string(), elapsed));
+    }
+}
+
+/// A command's primary query: the slowest SELECT recorded so far.
+pub fn primary_statement() -> Option<(String, Duration)> {
+    let statements = STATEMENTS.lock().ok()?;
+    statements
+        .iter()
+        .filter(|(sql, _)| {
+            let head = sql.trim_start().to_ascii_uppercase();
+            head.starts_with("SELECT") || head.starts_with("WITH")
+        })
+        .max_by_key(|(_, elapsed)| *elapsed)
+        .cloned()
+}
+
+/// EXPLAIN QUERY PLAN for `sql`. Parameters stay unbound (NULL); the
+/// planner doesn't need their values.
+pub fn explain_query_plan(conn: &Connection, sql: &str) -> Result<Vec<PlanRow>> {
+    let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql))?;
+    let mut rows = stmt.raw_query();
+    let mut plan = Vec::new();
+    while let Some(row) = rows.next()? {
+        plan.push(PlanRow {
+            id: row.get(0)?,
+            parent: row.get(1)?,
+            detail: row.get(3)?,
+        });
+    }
+    Ok(plan)
+}
+
+/// Plan of the primary query recorded so far, explained on a fresh
+/// (non-recording) connection; None when nothing was recorded.
+pub fn primary_query_plan() -> Result<Option<QueryPlan>> {
+    let Some((sql, elapsed)) = primary_statement() else {
+        return Ok(None);
+    };
+    let conn = open_read_only(&crate::config::active().db_path.value)?;
+    let plan = explain_query_plan(&conn, &sql)?;
+    Ok(Some(QueryPlan {
+        sql: sql.trim().to_string(),
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        plan,
+    }))
+}
+
+/// Open `path` read-only. URI filenames are enabled so `attach_read_only`
+/// can pass `mode=ro` for attached databases too.
+pub fn open_read_only(path: &Path) -> Result<Connection> {
+    Ok(Connection::open_with_flags(
+        path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY
+            | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX
+            | rusqlite::OpenFlags::SQLITE_OPEN_URI,
+    )?)
+}
+
+/// Attach another database read-only as `schema` (e.g. a Time Machine copy).
+///
+/// `schema` is spliced into the SQL, so only plain identifiers are accepted.
+pub fn attach_read_only(conn: &Connection, path: &Path, schema: &str) -> Result<()> {
+    if schema.is_empty() || !schema.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("Invalid schema name '{}'", schema);
+    }
+    if !path.is_file() {
+        bail!("Database not found: {:?}", path);
+    }
+    conn.execute(&format!("ATTACH DATABASE ?1 AS {}", schema), [sqlite_uri(path, "ro")])
+        .with_context(|| format!("Failed to attach {:?}", path))?;
+    Ok(())
+}
+
+/// `file:` URI for `path` with `mode`, escaping the characters URIs reserve.
+fn sqlite_uri(path: &Path, mode: &str) -> String {
+    let mut uri = String::from("file:");
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '%' | '?' | '#' => uri.push_str(&format!("%{:02X}", c as u32)),
+            _ => uri.push(c),
+        }
+    }
+    format!("{}?mode={}", uri, mode)
+}
+
+/// Run `f` in one deferred read transaction, so every statement in it sees
+/// the same snapshot of the database while Messages keeps writing (a bundle's
+/// unread_count and unread_messages can't disagree).
+///
+/// The snapshot starts at the first read and ends when `f` returns. Keep `f`
+/// to one request's reads, never a prompt or the connection's lifetime: an
+/// open snapshot holds back WAL checkpoints. Inside an open transaction, `f`
+/// joins it.
+pub fn read_snapshot<T>(conn: &Connection, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    if !conn.is_autocommit() {
+        return f(conn);
+    }
+    let tx = conn.unchecked_transaction()?;
+    let value = f(&tx)?;
+    tx.commit()?;
+    Ok(value)
 }
 
 /// Check if we have access to the Messages database.
@@ -45,4 +197,85 @@ mod tests {
         let path = default_db_path();
         assert!(path.ends_with("Library/Messages/chat.db"));
     }
+
+    #[test]
+    fn test_recorded_primary_statement_and_plan() {
+        let mut conn = crate::db::fixtures::empty_db();
+        record_statements_on(&mut conn);
+        let sql = "SELECT m.ROWID FROM message m JOIN handle h ON m.handle_id = h.ROWID WHERE m.date >= ?1";
+        conn.prepare(sql).unwrap().query_map([0], |r| r.get::<_, i64>(0)).unwrap().for_each(drop);
+        conn.execute_batch("PRAGMA user_version").unwrap();
+
+        let (recorded, _) = primary_statement().unwrap();
+        assert_eq!(recorded, sql);
+
+        let plan = explain_query_plan(&conn, &recorded).unwrap();
+        let details: Vec<&str> = plan.iter().map(|r| r.detail.as_str()).collect();
+        assert!(details.contains(&"SCAN m"), "{:?}", details);
+        assert!(details.iter().any(|d| d.starts_with("SEARCH h USING INTEGER PRIMARY KEY")), "{:?}", details);
+    }
+
+    #[test]
+    fn test_attach_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let primary = dir.path().join("chat.db");
+        let other = dir.path().join("backup 50%?.db");
+        for path in [&primary, &other] {
+            Connection::open(path)
+                .unwrap()
+                .execute_batch("CREATE TABLE message (guid TEXT)")
+                .unwrap();
+        }
+
+        let conn = open_read_only(&primary).unwrap();
+        attach_read_only(&conn, &other, "other").unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM other.message", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(conn.execute("INSERT INTO other.message VALUES ('x')", []).is_err());
+
+        assert!(attach_read_only(&conn, &other, "x; DROP").is_err());
+        assert!(attach_read_only(&conn, &dir.path().join("missing.db"), "gone").is_err());
+    }
+
+    #[test]
+    fn test_read_snapshot_ignores_interleaved_writes() {
+        use crate::db::fixtures::{insert_chat, insert_handle, insert_message, DAY_NS, SCHEMA};
+        use crate::db::helpers;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chat.db");
+        // Messages writes through WAL, so readers and the writer don't block each other
+        let writer = Connection::open(&path).unwrap();
+        writer.execute_batch(SCHEMA).unwrap();
+        writer.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(())).unwrap();
+        let alice = insert_handle(&writer, "+14155550001");
+        let chat = insert_chat(&writer, "+14155550001", None, &[alice]);
+        insert_message(&writer, chat, alice, Some("first"), 700 * DAY_NS, false, false);
+
+        let reader = open_read_only(&path).unwrap();
+        // Count, let a message arrive, then list: both see the count's database
+        let (count, listed, max_rowid) = read_snapshot(&reader, |conn| {
+            let count = helpers::query_unread_count(conn, None, None, None)?;
+            insert_message(&writer, chat, alice, Some("second"), 701 * DAY_NS, false, false);
+            let listed = helpers::query_unread_messages(conn, 10, None, None, None)?.len();
+            Ok((count, listed, helpers::query_max_message_rowid(conn)?))
+        })
+        .unwrap();
+        assert_eq!((count, listed, max_rowid), (1, 1, 1));
+        assert!(reader.is_autocommit());
+
+        // Without a snapshot the same interleaving disagrees
+        let count = helpers::query_unread_count(&reader, None, None, None).unwrap();
+        insert_message(&writer, chat, alice, Some("third"), 702 * DAY_NS, false, false);
+        let listed = helpers::query_unread_messages(&reader, 10, None, None, None).unwrap().len();
+        assert_eq!((count, listed), (2, 3));
+
+        // Errors end the snapshot too, and nested snapshots join the open one
+        assert!(read_snapshot(&reader, |_| -> Result<()> { bail!("boom") }).is_err());
+        assert!(reader.is_autocommit());
+        let nested = read_snapshot(&reader, |conn| read_snapshot(conn, |conn| Ok(conn.is_autocommit()))).unwrap();
+        assert!(!nested);
+    }
 }
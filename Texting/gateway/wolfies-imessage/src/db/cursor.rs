@@ -0,0 +1,112 @@
+//! Keyset pagination cursors.
+//!
+//! A cursor is the (date, ROWID) of the last row a page returned and the
+//! page's sort order, as URL-safe base64 of `{"d":<date>,"r":<rowid>,"a":<asc>}`.
+//! The next page continues with `(m.date, m.ROWID) > (?, ?)` (`<` when
+//! descending), so deep pages cost the same as the first, unlike OFFSET, and
+//! rows sharing a date are neither skipped nor repeated. Callers treat
+//! cursors as opaque.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Cursors record the sort order (`a`); continuing under the other order is an error
+//! - 10/16/2026 - Initial (date, rowid) cursors (summary --cursor)
+
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Position after the last row of a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(rename = "d")]
+    pub date: i64,
+    #[serde(rename = "r")]
+    pub rowid: i64,
+    /// The order of the page the cursor came from
+    #[serde(rename = "a")]
+    pub ascending: bool,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(cursor.trim())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| anyhow!("Invalid cursor '{}' (pass next_cursor from a previous page)", cursor))
+    }
+
+    /// Condition continuing after this cursor, for rows ordered by
+    /// `{alias}.date, {alias}.ROWID` in the cursor's order; binds `?{first}`
+    /// (date) and `?{first + 1}` (rowid).
+    pub fn condition(&self, alias: &str, first: usize) -> String {
+        format!(
+            "({alias}.date, {alias}.ROWID) {op} (?{date}, ?{rowid})",
+            alias = alias,
+            op = if self.ascending { ">" } else { "<" },
+            date = first,
+            rowid = first + 1
+        )
+    }
+
+    /// Error unless the page being read uses the cursor's order: under the
+    /// other order it would skip or repeat rows.
+    pub fn check_order(&self, ascending: bool) -> Result<()> {
+        if self.ascending != ascending {
+            let order = |asc| if asc { "asc" } else { "desc" };
+            bail!(
+                "This cursor continues an --order {} listing; pass --order {} or start over without --cursor",
+                order(self.ascending),
+                order(self.ascending)
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Pagination info returned with a page.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct PageMeta {
+    /// Pass as `--cursor` for the next page; null once the rows run out
+    pub next_cursor: Option<String>,
+}
+
+impl PageMeta {
+    /// Next cursor when the page came back full (`last` is its final row).
+    pub fn after(rows: usize, limit: u32, last: Option<Cursor>) -> Self {
+        let next_cursor = if rows >= limit as usize && limit > 0 { last.map(|c| c.encode()) } else { None };
+        Self { next_cursor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip_and_rejects_garbage() {
+        let cursor = Cursor { date: 700_000_000_123_456_789, rowid: 42, ascending: false };
+        let encoded = cursor.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&encoded).unwrap(), cursor);
+
+        assert!(Cursor::decode("not a cursor").is_err());
+        // Valid base64, wrong payload
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"x\":1}")).is_err());
+
+        // Cursors from before the order was recorded don't decode
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"d\":1,\"r\":2}")).is_err());
+
+        assert_eq!(cursor.condition("m", 5), "(m.date, m.ROWID) < (?5, ?6)");
+        assert!(cursor.check_order(false).is_ok());
+        assert!(cursor.check_order(true).is_err());
+        assert_eq!(PageMeta::after(2, 3, Some(cursor)).next_cursor, None);
+        assert_eq!(PageMeta::after(3, 3, Some(cursor)).next_cursor, Some(encoded));
+    }
+}
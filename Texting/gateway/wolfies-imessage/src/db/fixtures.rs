@@ -0,0 +1,265 @@
+//! In-memory Messages.db fixtures for query unit tests.
+//!
+//! Creates the subset of the chat.db schema the crate queries, with small
+//! insert helpers so tests can plant deterministic conversations.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - insert_reaction (tapbacks on a message guid)
+//! - 10/16/2026 - message.balloon_bundle_id and set_item (system items)
+//! - 10/16/2026 - set_chat_service (SMS history alongside iMessage for the same number)
+//! - 10/16/2026 - chat.last_read_message_timestamp and set_last_read (unread by conversation)
+//! - 10/16/2026 - insert_attachment (attachment-only placeholders)
+//! - 10/16/2026 - chat.last_addressed_handle (account info)
+//! - 10/16/2026 - chat_recoverable_message_join and mark_deleted (recently deleted)
+//! - 10/16/2026 - message.other_handle and message.group_title (group events)
+//! - 10/16/2026 - chat.account_login (my-handles detection)
+//! - 10/16/2026 - Initial fixture schema and insert helpers
+
+use rusqlite::{params, Connection};
+
+/// Schema subset used by the crate's queries.
+pub const SCHEMA: &str = r#"
+CREATE TABLE handle (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    service TEXT DEFAULT 'iMessage',
+    person_centric_id TEXT
+);
+CREATE TABLE chat (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    guid TEXT,
+    chat_identifier TEXT,
+    display_name TEXT,
+    service_name TEXT DEFAULT 'iMessage',
+    account_login TEXT,
+    last_addressed_handle TEXT,
+    last_read_message_timestamp INTEGER DEFAULT 0
+);
+CREATE TABLE message (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    guid TEXT UNIQUE,
+    text TEXT,
+    attributedBody BLOB,
+    handle_id INTEGER DEFAULT 0,
+    date INTEGER DEFAULT 0,
+    date_read INTEGER DEFAULT 0,
+    date_delivered INTEGER DEFAULT 0,
+    is_from_me INTEGER DEFAULT 0,
+    is_read INTEGER DEFAULT 0,
+    cache_roomnames TEXT,
+    cache_has_attachments INTEGER DEFAULT 0,
+    associated_message_guid TEXT,
+    associated_message_type INTEGER DEFAULT 0,
+    thread_originator_guid TEXT,
+    item_type INTEGER DEFAULT 0,
+    group_action_type INTEGER DEFAULT 0,
+    other_handle INTEGER DEFAULT 0,
+    group_title TEXT,
+    balloon_bundle_id TEXT,
+    subject TEXT,
+    service TEXT DEFAULT 'iMessage'
+);
+CREATE TABLE chat_message_join (
+    chat_id INTEGER,
+    message_id INTEGER,
+    PRIMARY KEY (chat_id, message_id)
+);
+CREATE TABLE chat_recoverable_message_join (
+    chat_id INTEGER,
+    message_id INTEGER,
+    delete_date INTEGER,
+    PRIMARY KEY (chat_id, message_id)
+);
+CREATE TABLE chat_handle_join (
+    chat_id INTEGER,
+    handle_id INTEGER,
+    UNIQUE (chat_id, handle_id)
+);
+CREATE TABLE attachment (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    guid TEXT,
+    filename TEXT,
+    mime_type TEXT,
+    total_bytes INTEGER DEFAULT 0,
+    transfer_name TEXT
+);
+CREATE TABLE message_attachment_join (
+    message_id INTEGER,
+    attachment_id INTEGER,
+    UNIQUE (message_id, attachment_id)
+);
+"#;
+
+pub use crate::db::queries::DAY_NS;
+
+/// Open an in-memory database with the fixture schema.
+pub fn empty_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("in-memory db");
+    conn.execute_batch(SCHEMA).expect("fixture schema");
+    conn
+}
+
+/// Insert a handle, returning its ROWID.
+pub fn insert_handle(conn: &Connection, id: &str) -> i64 {
+    conn.execute("INSERT INTO handle (id) VALUES (?1)", params![id])
+        .expect("insert handle");
+    conn.last_insert_rowid()
+}
+
+/// Insert a chat with its participants, returning its ROWID.
+pub fn insert_chat(
+    conn: &Connection,
+    chat_identifier: &str,
+    display_name: Option<&str>,
+    handle_ids: &[i64],
+) -> i64 {
+    conn.execute(
+        "INSERT INTO chat (guid, chat_identifier, display_name) VALUES (?1, ?2, ?3)",
+        params![format!("iMessage;-;{}", chat_identifier), chat_identifier, display_name],
+    )
+    .expect("insert chat");
+    let chat_id = conn.last_insert_rowid();
+    for handle_id in handle_ids {
+        conn.execute(
+            "INSERT INTO chat_handle_join (chat_id, handle_id) VALUES (?1, ?2)",
+            params![chat_id, handle_id],
+        )
+        .expect("insert chat_handle_join");
+    }
+    chat_id
+}
+
+/// Insert a message into a chat, returning its ROWID.
+///
+/// The GUID is `msg-<rowid>` so tests can reference messages predictably.
+pub fn insert_message(
+    conn: &Connection,
+    chat_id: i64,
+    handle_id: i64,
+    text: Option<&str>,
+    date: i64,
+    is_from_me: bool,
+    is_read: bool,
+) -> i64 {
+    conn.execute(
+        "INSERT INTO message (text, handle_id, date, is_from_me, is_read, date_read)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            text,
+            handle_id,
+            date,
+            is_from_me as i32,
+            is_read as i32,
+            if is_read { date } else { 0 }
+        ],
+    )
+    .expect("insert message");
+    let rowid = conn.last_insert_rowid();
+    conn.execute(
+        "UPDATE message SET guid = ?1 WHERE ROWID = ?2",
+        params![format!("msg-{}", rowid), rowid],
+    )
+    .expect("set guid");
+    conn.execute(
+        "INSERT INTO chat_message_join (chat_id, message_id) VALUES (?1, ?2)",
+        params![chat_id, rowid],
+    )
+    .expect("insert chat_message_join");
+    rowid
+}
+
+/// Attach a file to a message (and set its cache_has_attachments flag).
+pub fn insert_attachment(conn: &Connection, message_id: i64, mime_type: Option<&str>, transfer_name: &str) -> i64 {
+    conn.execute(
+        "INSERT INTO attachment (guid, filename, mime_type, transfer_name) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            format!("att-{}", message_id),
+            format!("~/Library/Messages/Attachments/{}", transfer_name),
+            mime_type,
+            transfer_name
+        ],
+    )
+    .expect("insert attachment");
+    let rowid = conn.last_insert_rowid();
+    conn.execute(
+        "INSERT INTO message_attachment_join (message_id, attachment_id) VALUES (?1, ?2)",
+        params![message_id, rowid],
+    )
+    .expect("insert message_attachment_join");
+    conn.execute(
+        "UPDATE message SET cache_has_attachments = 1 WHERE ROWID = ?1",
+        [message_id],
+    )
+    .expect("set cache_has_attachments");
+    rowid
+}
+
+/// Move a chat, its participants' handles, and its messages to `service`
+/// ("SMS", "iMessage"), as Messages stores a number's SMS history.
+pub fn set_chat_service(conn: &Connection, chat_id: i64, service: &str) {
+    conn.execute_batch(&format!(
+        "UPDATE chat SET service_name = '{service}',
+             guid = '{service};-;' || chat_identifier WHERE ROWID = {chat_id};
+         UPDATE handle SET service = '{service}'
+             WHERE ROWID IN (SELECT handle_id FROM chat_handle_join WHERE chat_id = {chat_id});
+         UPDATE message SET service = '{service}'
+             WHERE ROWID IN (SELECT message_id FROM chat_message_join WHERE chat_id = {chat_id});"
+    ))
+    .expect("update chat service");
+}
+
+/// Set a chat's read position (chat.last_read_message_timestamp, Cocoa ns).
+pub fn set_last_read(conn: &Connection, chat_id: i64, timestamp: i64) {
+    conn.execute(
+        "UPDATE chat SET last_read_message_timestamp = ?1 WHERE ROWID = ?2",
+        params![timestamp, chat_id],
+    )
+    .expect("update chat.last_read_message_timestamp");
+}
+
+/// Move a message to "Recently Deleted": out of its chat, into
+/// chat_recoverable_message_join with `delete_date` (Cocoa ns).
+pub fn mark_deleted(conn: &Connection, chat_id: i64, message_id: i64, delete_date: i64) {
+    conn.execute(
+        "DELETE FROM chat_message_join WHERE chat_id = ?1 AND message_id = ?2",
+        params![chat_id, message_id],
+    )
+    .expect("delete chat_message_join");
+    conn.execute(
+        "INSERT INTO chat_recoverable_message_join (chat_id, message_id, delete_date) VALUES (?1, ?2, ?3)",
+        params![chat_id, message_id, delete_date],
+    )
+    .expect("insert chat_recoverable_message_join");
+}
+
+/// Turn a message into a system item (item_type, group_action_type, and
+/// group_title for renames), clearing its text as Messages stores them.
+pub fn set_item(conn: &Connection, message_id: i64, item_type: i64, group_action_type: i64, group_title: Option<&str>) {
+    conn.execute(
+        "UPDATE message SET item_type = ?1, group_action_type = ?2, group_title = ?3, text = NULL
+         WHERE ROWID = ?4",
+        params![item_type, group_action_type, group_title, message_id],
+    )
+    .expect("update message item");
+}
+
+/// Insert a tapback (associated_message_type 2000-2007, removals 3000-3007)
+/// on the message with `target_guid`, stored as `p:0/<guid>` like Messages
+/// does, returning its ROWID.
+pub fn insert_reaction(
+    conn: &Connection,
+    chat_id: i64,
+    handle_id: i64,
+    target_guid: &str,
+    reaction_type: i64,
+    date: i64,
+    is_from_me: bool,
+) -> i64 {
+    let rowid = insert_message(conn, chat_id, handle_id, None, date, is_from_me, true);
+    conn.execute(
+        "UPDATE message SET associated_message_type = ?1, associated_message_guid = ?2 WHERE ROWID = ?3",
+        params![reaction_type, format!("p:0/{}", target_guid), rowid],
+    )
+    .expect("update reaction");
+    rowid
+}
@@ -4,63 +4,320 @@
 //! and daemon mode (hot cached connection).
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Restored query_chunked, HandleFilter::rowids and query_messages_for_handles (bound, chunked handle sets past the parameter limit)
+//! - 10/16/2026 - Added query_chat_by_identifier (chat ROWID and guid)
+//! - 10/16/2026 - variants (skipped when empty) is serde(default): optional in the JSON Schema
+//! - 10/16/2026 - query_chat_participants lists each chat's handles sorted by id
+//! - 10/16/2026 - query_handle_stats counts unread with unread_conditions (chat read position, mutes) like the unread commands
+//! - 10/16/2026 - query_volume_series_at takes a UTC offset lookup and buckets each 15-minute slot with the offset in effect then (DST changes no longer shift buckets)
+//! - 10/16/2026 - DeletedRow carries a MessageBody (shared text/placeholder path) instead of bare text
+//! - 10/16/2026 - One way to restrict by a ROWID set (inlined rowids_condition): dropped query_chunked and the test-only query_messages_for_handles; query_message_reactions takes message ROWIDs
+//! - 10/16/2026 - TimelineMessage drops the unread sender_handle field
+//! - 10/16/2026 - query_text_search pages through candidates until limit hits (a match behind 100+ blob-only messages was dropped)
+//! - 10/16/2026 - Added query_conversation_lifetime (all-history count and first message date)
+//! - 10/16/2026 - Added unread_conditions (the unread WHERE clause shared by unread, unread --count-only, bundle and digest; past the chat read position); query_unread_count takes a HandleFilter and SenderFilter
+//! - 10/16/2026 - query_analytics_combined, query_analytics_for_handles, query_message_counts and query_top_contacts take an optional upper bound (bounded statements; replaces the *_range helpers)
+//! - 10/16/2026 - Added query_analytics_combined_range and query_analytics_for_handles_range (bounded windows; analytics --compare)
+//! - 10/16/2026 - Added query_message_reactions (standing tapbacks per message guid, removals netted out)
+//! - 10/16/2026 - query_text_search and the follow-up queries leave out system items unless include_system; SearchHit carries item_kind
+//! - 10/16/2026 - Added query_direct_chats_for (1:1 chats with any of a set of handles, every service); query_self_chats uses it
+//! - 10/16/2026 - cocoa_to_iso and days_ago_from_cocoa decode through queries::decode_cocoa (seconds or nanoseconds; implausible dates clamped)
+//! - 10/16/2026 - Added query_unread_by_chat (unread count and oldest unread per chat, past chat.last_read_message_timestamp)
+//! - 10/16/2026 - Added ok_row: rows that fail to decode are skipped with an output::warn (was silently)
+//! - 10/16/2026 - UnreadMessage.is_known_sender; query_unread_messages takes an optional SenderFilter
+//! - 10/16/2026 - Added query_chunked (bound IN lists past the parameter limit) and query_messages_for_handles
+//! - 10/16/2026 - Added query_heatmap (local weekday x hour, per-message UTC offset across DST changes)
+//! - 10/16/2026 - ChatStats, query_chats_with_handles, query_chat_stats (chat-info)
+//! - 10/16/2026 - query_unread_count (unread --count-only, daemon bundle unread_count)
+//! - 10/16/2026 - Added query_conversation_timeline (shares the timeline row mapping with query_group_timeline)
+//! - 10/16/2026 - Added query_latest_in_chats (sharing the recent-conversations rows) and query_direct_chats
+//! - 10/16/2026 - query_unanswered_questions takes a limit and reports the chat (FOLLOWUP_LIMIT)
+//! - 10/16/2026 - Recent, conversation, unread, incoming, and thread rows carry an attachment placeholder; unread reads blob text
+//! - 10/16/2026 - Added query_chat_accounts (account_login + last_addressed_handle per service); my-handles detection uses both
+//! - 10/16/2026 - query_unread_messages and query_recent_conversations take a MuteFilter; added query_muted_unread_count
+//! - 10/16/2026 - Added query_chat_participants (all chats' participants in one query)
+//! - 10/16/2026 - Removed per-stat analytics helpers (busiest hour/day, attachments, reactions); analytics uses the combined query
+//! - 10/16/2026 - TopContact.contact_name (filled in by api::analytics)
+//! - 10/16/2026 - Added query_deleted_messages (recently deleted, schema-guarded)
+//! - 10/16/2026 - Added query_group_events (participant and rename history, schema-guarded)
+//! - 10/16/2026 - Added query_handle_stats (per-handle stats map for contacts --stats)
+//! - 10/16/2026 - query_recent_messages reads blob text; RecentMessage keeps the raw date
+//! - 10/16/2026 - Added for_each_conversation_text (streams text incl. blob content)
+//! - 10/16/2026 - Added query_volume_series (dense sent/received series per local bucket)
+//! - 10/16/2026 - RecentMessage, UnreadMessage, SearchHit carry message rowid and guid; query_recent_messages binds its limit
+//! - 10/16/2026 - Top contacts, handles, and unknown senders merge phone format variants
+//! - 10/16/2026 - Added my-handles detection, self-chat lookup, and top-contacts exclusion
+//! - 10/16/2026 - Output structs derive JsonSchema
+//! - 10/16/2026 - Added query_message_context; SearchHit carries the message ROWID
+//! - 10/16/2026 - Statements use prepare_cached (reused on the daemon's hot connection)
+//! - 10/16/2026 - Blob text goes through text_cache (keyed by message guid)
+//! - 10/16/2026 - Added query_handle_chat_names and query_handle_recent_texts
+//! - 10/16/2026 - HandleInfo carries person_centric_id and raw last date
+//! - 10/16/2026 - Phone and group-name LIKE filters escape wildcards
+//! - 10/16/2026 - Added multi-term text search with matched_terms
+//! - 10/16/2026 - Added per-chat group analytics helpers
+//! - 10/16/2026 - Added HandleFilter (relationship filtering) to list/count helpers
+//! - 10/16/2026 - Added bounded-range count helpers (digest)
+//! - 10/16/2026 - Added incoming-since queries for watch
+//! - 10/16/2026 - Added query_unread_chats
+//! - 10/16/2026 - Added thread root resolution and reply tree query
+//! - 10/16/2026 - Added query_recent_conversations (latest message per chat)
+//! - 10/16/2026 - Added reaction_kind tapback classifier and has_column schema guard
 //! - 01/10/2026 - Initial extraction from analytics.rs (Phase 5) (Claude)
 
 use anyhow::Result;
 use rusqlite::{self, Connection};
+use schemars::JsonSchema;
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 
-use super::queries;
+use super::item_kind::{self, ItemCodes, ItemKind};
+use super::message_body::{message_body, MessageBody};
+use super::{queries, text_cache};
+use crate::mutes::MuteFilter;
+use crate::output;
+use crate::senders::SenderFilter;
 
 // ============================================================================
 // Data Structures
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct TopContact {
     pub phone: String,
+    /// Contact name for `phone`, when resolved by the caller.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_name: Option<String>,
     pub message_count: i64,
+    /// Raw handles merged into `phone`, when there was more than one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct RecentMessage {
+    pub rowid: i64,
+    pub guid: String,
     pub text: Option<String>,
     pub date: String,
+    #[serde(skip)]
+    pub date_cocoa: i64,
     pub is_from_me: bool,
     pub phone: String,
+    /// "[photo]", "[file: name.pdf]", ... when the message is only an attachment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConversationSummary {
+    pub chat_identifier: String,
+    pub display_name: Option<String>,
+    pub last_message_text: Option<String>,
+    /// Attachment placeholder for the last message (see `RecentMessage`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    pub last_date: String,
+    pub last_is_from_me: bool,
+    pub last_sender: Option<String>,
+    pub unread_count: i64,
+    pub is_group: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IncomingMessage {
+    pub rowid: i64,
+    pub text: Option<String>,
+    pub date: String,
+    pub sender_handle: Option<String>,
+    pub chat_identifier: Option<String>,
+    pub display_name: Option<String>,
+    pub is_group_chat: bool,
+    /// Attachment placeholder (see `RecentMessage`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ThreadMessage {
+    pub guid: String,
+    pub text: Option<String>,
+    pub date: String,
+    pub is_from_me: bool,
+    pub sender_handle: Option<String>,
+    /// Message this one replies to (None for the thread root)
+    pub reply_to_guid: Option<String>,
+    pub depth: i64,
+    /// Attachment placeholder (see `RecentMessage`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+/// A text search hit annotated with the terms it matched.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub rowid: i64,
+    pub guid: String,
+    pub text: String,
+    pub date_cocoa: i64,
+    pub is_from_me: bool,
+    pub handle: Option<String>,
+    pub cache_roomnames: Option<String>,
+    pub item_kind: ItemKind,
+    pub matched_terms: Vec<String>,
+}
+
+/// One message in a context window around a search hit. Only the hit's
+/// date is shown, so context lines carry no date of their own.
+#[derive(Debug, Clone)]
+pub struct ContextMessage {
+    pub rowid: i64,
+    pub text: Option<String>,
+    pub is_from_me: bool,
+    pub handle: Option<String>,
+}
+
+/// A chat resolved by identifier or display name.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GroupRef {
+    pub rowid: i64,
+    pub chat_identifier: String,
+    pub display_name: Option<String>,
+}
+
+/// Header facts for one chat (`chat-info`); participants are filled in separately.
+#[derive(Debug, Clone)]
+pub struct ChatStats {
+    pub rowid: i64,
+    pub chat_identifier: String,
+    pub display_name: Option<String>,
+    pub service: Option<String>,
+    pub message_count: i64,
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    pub attachment_count: i64,
+    /// None when the schema has no chat.is_pinned
+    pub pinned: Option<bool>,
+}
+
+/// Message count for one sender in a chat (`handle` is None for me).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SenderCount {
+    pub handle: Option<String>,
+    pub is_from_me: bool,
+    pub message_count: i64,
+}
+
+/// The message with the most tapbacks in a chat.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MostReacted {
+    pub guid: String,
+    pub text: Option<String>,
+    pub is_from_me: bool,
+    pub sender_handle: Option<String>,
+    pub date: String,
+    pub reaction_count: i64,
+}
+
+/// One message in a chat timeline (oldest first).
+#[derive(Debug, Clone)]
+pub struct TimelineMessage {
+    pub is_from_me: bool,
+    pub text: Option<String>,
+    pub date_cocoa: i64,
+}
+
+/// Unread messages in one conversation (`query_unread_by_chat`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnreadChatRow {
+    pub chat_identifier: String,
+    pub display_name: Option<String>,
+    pub unread_count: i64,
+    pub oldest_unread_cocoa: i64,
+}
+
+/// A recently deleted message still recoverable from chat_recoverable_message_join.
+#[derive(Debug, Clone)]
+pub struct DeletedRow {
+    pub rowid: i64,
+    pub guid: String,
+    pub body: MessageBody,
+    pub date_cocoa: i64,
+    pub is_from_me: bool,
+    pub handle: Option<String>,
+    pub chat_identifier: Option<String>,
+    pub chat_name: Option<String>,
+    /// Deletion time, normalized to Cocoa nanoseconds.
+    pub delete_cocoa: i64,
+}
+
+/// A participant or name-change system message in a chat (raw handles).
+#[derive(Debug, Clone)]
+pub struct GroupEventRow {
+    pub date_cocoa: i64,
+    pub is_from_me: bool,
+    /// Who made the change (None when it was me).
+    pub actor_handle: Option<String>,
+    pub item_type: i64,
+    pub group_action_type: i64,
+    /// Who was added or removed.
+    pub affected_handle: Option<String>,
+    pub group_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct UnreadMessage {
+    pub rowid: i64,
+    pub guid: String,
     pub text: Option<String>,
     pub date: String,
     pub phone: String,
+    /// Attachment placeholder (see `RecentMessage`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    /// From a contact or an unfiltered chat (see `senders`); false without a SenderFilter
+    pub is_known_sender: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct HandleInfo {
     pub handle: String,
     pub message_count: i64,
     pub last_date: String,
+    #[serde(skip)]
+    pub last_date_cocoa: i64,
+    /// Apple's cross-handle person id, when the schema has it.
+    #[serde(skip)]
+    pub person_centric_id: Option<String>,
+    /// Raw handles merged into `handle`, when there was more than one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct UnknownSender {
     pub handle: String,
     pub message_count: i64,
     pub last_date: String,
+    #[serde(skip)]
+    pub last_date_cocoa: i64,
     pub sample_text: Option<String>,
+    /// Raw handles merged into `handle`, when there was more than one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct UnansweredQuestion {
     pub phone: String,
     pub text: String,
     pub date: String,
     pub days_ago: i64,
+    pub chat_identifier: Option<String>,
+    pub is_group: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct StaleConversation {
     pub phone: String,
     pub last_text: Option<String>,
@@ -68,132 +325,698 @@ pub struct StaleConversation {
     pub days_ago: i64,
 }
 
+// ============================================================================
+// Handle Filtering
+// ============================================================================
+
+/// Max ids per `IN (...)` list; larger sets are split into OR'ed chunks.
+const IN_CHUNK_SIZE: usize = 500;
+
+/// A set of handles (e.g. every contact tagged "work") resolved against the
+/// handle table, usable as a SQL condition or as a post-filter.
+#[derive(Debug, Clone, Default)]
+pub struct HandleFilter {
+    rowids: Vec<i64>,
+    keys: HashSet<String>,
+}
+
+impl HandleFilter {
+    /// Resolve contact handles (phones/emails in any format) to handle ROWIDs.
+    pub fn resolve(conn: &Connection, handles: &[String]) -> Result<Self> {
+        let keys: HashSet<String> = handles
+            .iter()
+            .map(|h| handle_key(h))
+            .filter(|k| !k.is_empty())
+            .collect();
+
+        let mut stmt = conn.prepare_cached("SELECT ROWID, id FROM handle")?;
+        let rowids = stmt
+            .query_map([], |row: &rusqlite::Row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+            })?
+            .filter_map(ok_row)
+            .filter(|(_, id)| id.as_deref().map(|id| keys.contains(&handle_key(id))).unwrap_or(false))
+            .map(|(rowid, _)| rowid)
+            .collect();
+
+        Ok(Self { rowids, keys })
+    }
+
+    /// Whether a handle string (as stored in Messages.db) is in the set.
+    pub fn matches(&self, handle: &str) -> bool {
+        self.keys.contains(&handle_key(handle))
+    }
+
+    /// Handle ROWIDs in the set, for binding (see query_chunked).
+    pub fn rowids(&self) -> &[i64] {
+        &self.rowids
+    }
+
+    /// SQL condition restricting `column` (a handle ROWID) to the set.
+    ///
+    /// ROWIDs come from the database itself, so they are inlined rather than
+    /// bound, which sidesteps SQLite's bound-parameter limit.
+    pub fn sql_condition(&self, column: &str) -> String {
+        rowids_condition(column, &self.rowids)
+    }
+
+    /// SQL condition excluding the set from `column` (non-NULL handle ROWIDs).
+    pub fn sql_exclusion(&self, column: &str) -> String {
+        if self.rowids.is_empty() {
+            return "1".to_string();
+        }
+        format!("NOT ({})", self.sql_condition(column))
+    }
+}
+
+/// Comparison key for a handle: lowercase email, or the last 10 phone digits
+/// (so "+1 415..." and "415..." match).
+pub fn handle_key(handle: &str) -> String {
+    if handle.contains('@') {
+        return handle.trim().to_lowercase();
+    }
+    let digits: String = handle.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits[digits.len().saturating_sub(10)..].to_string()
+}
+
+/// SQL condition restricting `column` to database-sourced ROWIDs (inlined,
+/// chunked by `IN_CHUNK_SIZE`); "0" when there are none.
+pub(crate) fn rowids_condition(column: &str, rowids: &[i64]) -> String {
+    if rowids.is_empty() {
+        return "0".to_string();
+    }
+    let chunks: Vec<String> = rowids
+        .chunks(IN_CHUNK_SIZE)
+        .map(|chunk| {
+            let ids: Vec<String> = chunk.iter().map(|id| id.to_string()).collect();
+            format!("{} IN ({})", column, ids.join(","))
+        })
+        .collect();
+    if chunks.len() == 1 {
+        chunks.into_iter().next().unwrap_or_default()
+    } else {
+        format!("({})", chunks.join(" OR "))
+    }
+}
+
+/// Run `query` once per chunk of `values` that fits in one statement beside
+/// `reserved` other bound parameters, and concatenate the rows. No values, no
+/// queries. Each chunk is ordered and limited on its own, so callers that
+/// order or limit re-sort and truncate the merged rows.
+pub fn query_chunked<T, R>(
+    values: &[T],
+    reserved: usize,
+    mut query: impl FnMut(&[T]) -> Result<Vec<R>>,
+) -> Result<Vec<R>> {
+    let size = queries::MAX_BOUND_PARAMS.saturating_sub(reserved).max(1);
+    let mut rows = Vec::new();
+    for chunk in values.chunks(size) {
+        rows.extend(query(chunk)?);
+    }
+    Ok(rows)
+}
+
+// ============================================================================
+// Handle Format Variants
+// ============================================================================
+
+/// Top contacts reported by analytics.
+pub const TOP_CONTACTS_LIMIT: usize = 10;
+
+/// Grouping key for format variants of one phone number ("+14155551234",
+/// "4155551234", "(415) 555-1234"): its last 10 digits. Emails and
+/// alphanumeric sender IDs are their own key.
+pub fn variant_key(handle: &str) -> String {
+    let is_phone = handle.chars().any(|c| c.is_ascii_digit())
+        && handle.chars().all(|c| c.is_ascii_digit() || "+-(). ".contains(c));
+    if is_phone {
+        handle_key(handle)
+    } else {
+        handle.to_string()
+    }
+}
+
+/// Canonical form of merged phone variants: E.164 when any variant carries a
+/// country code, else the last 10 digits.
+pub fn canonical_phone(variants: &[String]) -> String {
+    let digits = |v: &String| v.chars().filter(|c| c.is_ascii_digit()).collect::<String>();
+    let longest = variants.iter().map(digits).max_by_key(|d| d.len()).unwrap_or_default();
+    if longest.len() > 10 {
+        format!("+{}", longest)
+    } else {
+        longest
+    }
+}
+
+/// A per-handle aggregate row that can absorb another variant's row.
+pub trait HandleAggregate {
+    fn handle(&self) -> &str;
+    fn absorb(&mut self, other: Self);
+    fn set_variants(&mut self, canonical: String, variants: Vec<String>);
+}
+
+impl HandleAggregate for TopContact {
+    fn handle(&self) -> &str {
+        &self.phone
+    }
+    fn absorb(&mut self, other: Self) {
+        self.message_count += other.message_count;
+    }
+    fn set_variants(&mut self, canonical: String, variants: Vec<String>) {
+        self.phone = canonical;
+        self.variants = variants;
+    }
+}
+
+impl HandleAggregate for HandleInfo {
+    fn handle(&self) -> &str {
+        &self.handle
+    }
+    fn absorb(&mut self, other: Self) {
+        self.message_count += other.message_count;
+        if other.last_date_cocoa > self.last_date_cocoa {
+            self.last_date_cocoa = other.last_date_cocoa;
+            self.last_date = other.last_date;
+        }
+        if self.person_centric_id.is_none() {
+            self.person_centric_id = other.person_centric_id;
+        }
+    }
+    fn set_variants(&mut self, canonical: String, variants: Vec<String>) {
+        self.handle = canonical;
+        self.variants = variants;
+    }
+}
+
+impl HandleAggregate for UnknownSender {
+    fn handle(&self) -> &str {
+        &self.handle
+    }
+    fn absorb(&mut self, other: Self) {
+        self.message_count += other.message_count;
+        let newer = other.last_date_cocoa > self.last_date_cocoa;
+        if newer {
+            self.last_date_cocoa = other.last_date_cocoa;
+            self.last_date = other.last_date;
+        }
+        // Sample from the most recent variant that has one
+        if other.sample_text.is_some() && (newer || self.sample_text.is_none()) {
+            self.sample_text = other.sample_text;
+        }
+    }
+    fn set_variants(&mut self, canonical: String, variants: Vec<String>) {
+        self.handle = canonical;
+        self.variants = variants;
+    }
+}
+
+/// Merge rows whose handles are format variants of the same phone number.
+/// Merged rows report the canonical form plus the raw variants; everything
+/// else passes through untouched, in input order.
+pub fn merge_variants<T: HandleAggregate>(rows: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut groups: Vec<(T, Vec<String>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for row in rows {
+        let raw = row.handle().to_string();
+        match index.get(&variant_key(&raw)) {
+            Some(&i) => {
+                let (merged, variants) = &mut groups[i];
+                if !variants.contains(&raw) {
+                    variants.push(raw);
+                }
+                merged.absorb(row);
+            }
+            None => {
+                index.insert(variant_key(&raw), groups.len());
+                groups.push((row, vec![raw]));
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(mut merged, mut variants)| {
+            if variants.len() > 1 {
+                variants.sort();
+                merged.set_variants(canonical_phone(&variants), variants);
+            }
+            merged
+        })
+        .collect()
+}
+
+// ============================================================================
+// Self (Notes to Self)
+// ============================================================================
+
+/// Pseudo-contact for the user's own notes-to-self conversation.
+pub const SELF_CONTACT: &str = "self";
+
+/// The user's own handles: `configured` (config.toml `my_handles`) when
+/// non-empty, otherwise the accounts recorded in Messages.db.
+pub fn resolve_my_handles(conn: &Connection, configured: &[String]) -> Result<HandleFilter> {
+    let handles = if configured.is_empty() {
+        query_account_handles(conn)?
+    } else {
+        configured.to_vec()
+    };
+    HandleFilter::resolve(conn, &handles)
+}
+
+/// My handles to exclude from per-person reports, or None with `include_self`.
+pub fn self_exclusion(conn: &Connection, include_self: bool) -> Result<Option<HandleFilter>> {
+    if include_self {
+        return Ok(None);
+    }
+    resolve_my_handles(conn, &crate::config::active().my_handles.value).map(Some)
+}
+
+/// Whether `handle` is in the `self_exclusion` set (false when there is none).
+pub fn is_mine(mine: Option<&HandleFilter>, handle: &str) -> bool {
+    mine.is_some_and(|m| m.matches(handle))
+}
+
+/// One of my handles as recorded on chat rows (`account_login` or
+/// `last_addressed_handle`), per service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatAccount {
+    pub handle: String,
+    /// chat.service_name ("iMessage", "SMS", ...)
+    pub service: Option<String>,
+    pub chat_count: i64,
+}
+
+/// My handles from chat.account_login and chat.last_addressed_handle,
+/// without the "E:"/"P:" prefix. Columns the schema lacks are skipped.
+pub fn query_chat_accounts(conn: &Connection) -> Result<Vec<ChatAccount>> {
+    let service = if has_column(conn, "chat", "service_name") { "service_name" } else { "NULL" };
+    let mut accounts: Vec<ChatAccount> = Vec::new();
+    for column in ["account_login", "last_addressed_handle"] {
+        if !has_column(conn, "chat", column) {
+            continue;
+        }
+        let sql = format!(
+            "SELECT {col}, {service}, COUNT(*) FROM chat WHERE {col} IS NOT NULL AND {col} != '' GROUP BY {col}, {service}",
+            col = column,
+            service = service
+        );
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let rows = stmt.query_map([], |row: &rusqlite::Row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for (login, service, count) in rows.filter_map(ok_row) {
+            let handle = match login.split_once(':') {
+                Some((prefix, handle)) if prefix.len() == 1 => handle.to_string(),
+                _ => login,
+            };
+            if handle.is_empty() {
+                continue;
+            }
+            // The same handle often appears in both columns: count it once per chat set
+            match accounts
+                .iter_mut()
+                .find(|a| handle_key(&a.handle) == handle_key(&handle) && a.service == service)
+            {
+                Some(existing) => existing.chat_count = existing.chat_count.max(count),
+                None => accounts.push(ChatAccount { handle, service, chat_count: count }),
+            }
+        }
+    }
+    Ok(accounts)
+}
+
+/// Distinct account handles from chat.db (`query_chat_accounts`).
+pub fn query_account_handles(conn: &Connection) -> Result<Vec<String>> {
+    let mut handles: Vec<String> = Vec::new();
+    for account in query_chat_accounts(conn)? {
+        if !handles.iter().any(|h| handle_key(h) == handle_key(&account.handle)) {
+            handles.push(account.handle);
+        }
+    }
+    Ok(handles)
+}
+
+/// One-to-one chats as (ROWID, handle): the only participant, or (no
+/// participant rows) the chat identifier.
+pub fn query_direct_chats(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare_cached(queries::SELF_CHAT_CANDIDATES)?;
+    let rows = stmt.query_map([], |row: &rusqlite::Row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+    Ok(rows
+        .filter_map(ok_row)
+        .filter_map(|(rowid, identifier, participants, participant)| {
+            let handle = if participants == 0 { identifier } else { participant };
+            handle.map(|h| (rowid, h))
+        })
+        .collect())
+}
+
+/// ROWIDs of one-to-one chats (`query_direct_chats`) whose handle is in
+/// `handles`: a number's SMS and iMessage chats alike.
+pub fn query_direct_chats_for(conn: &Connection, handles: &HandleFilter) -> Result<Vec<i64>> {
+    Ok(query_direct_chats(conn)?
+        .into_iter()
+        .filter(|(_, handle)| handles.matches(handle))
+        .map(|(rowid, _)| rowid)
+        .collect())
+}
+
+/// ROWIDs of notes-to-self chats: one-to-one chats whose handle is one of `mine`.
+pub fn query_self_chats(conn: &Connection, mine: &HandleFilter) -> Result<Vec<i64>> {
+    query_direct_chats_for(conn, mine)
+}
+
 // ============================================================================
 // Analytics Query Helpers
 // ============================================================================
 
-/// Query message counts (total, sent, received).
+/// Query message counts (total, sent, received) since the cutoff, and
+/// before `end_cocoa` when given.
 pub fn query_message_counts(
     conn: &Connection,
     cutoff_cocoa: i64,
+    end_cocoa: Option<i64>,
     phone: Option<&str>,
 ) -> Result<(i64, i64, i64)> {
-    if let Some(p) = phone {
-        let mut stmt = conn.prepare(queries::ANALYTICS_MESSAGE_COUNTS_PHONE)?;
-        let params: &[&dyn rusqlite::ToSql] = &[&cutoff_cocoa, &p];
-        let row = stmt
-            .query_row(params, |row: &rusqlite::Row| {
-                Ok((
-                    row.get::<_, i64>(0).unwrap_or(0),
-                    row.get::<_, i64>(1).unwrap_or(0),
-                    row.get::<_, i64>(2).unwrap_or(0),
-                ))
-            })
-            .unwrap_or((0, 0, 0));
-        Ok(row)
-    } else {
-        let mut stmt = conn.prepare(queries::ANALYTICS_MESSAGE_COUNTS)?;
-        let row = stmt
-            .query_row(&[&cutoff_cocoa], |row: &rusqlite::Row| {
-                Ok((
-                    row.get::<_, i64>(0).unwrap_or(0),
-                    row.get::<_, i64>(1).unwrap_or(0),
-                    row.get::<_, i64>(2).unwrap_or(0),
-                ))
-            })
-            .unwrap_or((0, 0, 0));
-        Ok(row)
-    }
+    let pattern = phone.map(queries::like_contains);
+    let sql = match (end_cocoa.is_some(), phone.is_some()) {
+        (false, false) => queries::ANALYTICS_MESSAGE_COUNTS,
+        (false, true) => queries::ANALYTICS_MESSAGE_COUNTS_PHONE,
+        (true, false) => queries::ANALYTICS_MESSAGE_COUNTS_RANGE,
+        (true, true) => queries::ANALYTICS_MESSAGE_COUNTS_PHONE_RANGE,
+    };
+    let mut stmt = conn.prepare_cached(sql)?;
+    let params = window_params(&cutoff_cocoa, &end_cocoa, pattern.as_ref().map(|p| p as &dyn rusqlite::ToSql));
+    let row = stmt
+        .query_row(params.as_slice(), |row: &rusqlite::Row| {
+            Ok((
+                row.get::<_, i64>(0).unwrap_or(0),
+                row.get::<_, i64>(1).unwrap_or(0),
+                row.get::<_, i64>(2).unwrap_or(0),
+            ))
+        })
+        .unwrap_or((0, 0, 0));
+    Ok(row)
 }
 
-/// Query busiest hour of day.
-pub fn query_busiest_hour(
+/// Query top contacts by message volume (before `end_cocoa` when given),
+/// optionally excluding handles (e.g. my own).
+pub fn query_top_contacts(
     conn: &Connection,
     cutoff_cocoa: i64,
-    phone: Option<&str>,
-) -> Result<Option<i64>> {
-    if let Some(p) = phone {
-        let mut stmt = conn.prepare(queries::ANALYTICS_BUSIEST_HOUR_PHONE)?;
-        let params: &[&dyn rusqlite::ToSql] = &[&cutoff_cocoa, &p];
-        Ok(stmt
-            .query_row(params, |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .ok())
+    end_cocoa: Option<i64>,
+    filter: Option<&HandleFilter>,
+    exclude: Option<&HandleFilter>,
+) -> Result<Vec<TopContact>> {
+    let base = match end_cocoa {
+        Some(_) => queries::ANALYTICS_TOP_CONTACTS_RANGE,
+        None => queries::ANALYTICS_TOP_CONTACTS,
+    };
+    let conditions: Vec<String> = filter
+        .map(|f| f.sql_condition("m.handle_id"))
+        .into_iter()
+        .chain(exclude.map(|e| e.sql_exclusion("m.handle_id")))
+        .collect();
+    let sql = if conditions.is_empty() {
+        base.to_string()
     } else {
-        let mut stmt = conn.prepare(queries::ANALYTICS_BUSIEST_HOUR)?;
-        Ok(stmt
-            .query_row(&[&cutoff_cocoa], |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .ok())
+        queries::with_filter(base, &conditions.join(" AND "))
+    };
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map(window_params(&cutoff_cocoa, &end_cocoa, None).as_slice(), |row: &rusqlite::Row| {
+        Ok(TopContact {
+            phone: row.get(0)?,
+            contact_name: None,
+            message_count: row.get(1)?,
+            variants: Vec::new(),
+        })
+    })?;
+    let mut contacts = merge_variants(rows.filter_map(|r: rusqlite::Result<TopContact>| r.ok()));
+    contacts.sort_by_key(|c| std::cmp::Reverse(c.message_count));
+    contacts.truncate(TOP_CONTACTS_LIMIT);
+    Ok(contacts)
+}
+
+/// Message stats for one person (or one handle) over a window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandleStats {
+    pub message_count: i64,
+    pub sent: i64,
+    pub received: i64,
+    pub last_date_cocoa: Option<i64>,
+    pub unread: i64,
+}
+
+impl HandleStats {
+    /// Fold another handle's stats in (multi-handle contacts, format variants).
+    pub fn absorb(&mut self, other: &HandleStats) {
+        self.message_count += other.message_count;
+        self.sent += other.sent;
+        self.received += other.received;
+        self.last_date_cocoa = self.last_date_cocoa.max(other.last_date_cocoa);
+        self.unread += other.unread;
     }
 }
 
-/// Query busiest day of week (returns 0-6 for Sunday-Saturday).
-pub fn query_busiest_day(
+/// Stats for every handle with messages since the cutoff, keyed by `handle_key`
+/// so format variants of one number land on the same entry.
+pub fn query_handle_stats(
     conn: &Connection,
     cutoff_cocoa: i64,
-    phone: Option<&str>,
-) -> Result<Option<i64>> {
-    if let Some(p) = phone {
-        let mut stmt = conn.prepare(queries::ANALYTICS_BUSIEST_DAY_PHONE)?;
-        let params: &[&dyn rusqlite::ToSql] = &[&cutoff_cocoa, &p];
-        Ok(stmt
-            .query_row(params, |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .ok())
-    } else {
-        let mut stmt = conn.prepare(queries::ANALYTICS_BUSIEST_DAY)?;
-        Ok(stmt
-            .query_row(&[&cutoff_cocoa], |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .ok())
+    mutes: Option<&MuteFilter>,
+) -> Result<HashMap<String, HandleStats>> {
+    let unread = unread_conditions(conn, "m", mutes).join(" AND ");
+    let sql = queries::HANDLE_STATS.replace("{unread}", &format!("({})", unread));
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map([cutoff_cocoa], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            HandleStats {
+                message_count: row.get(1)?,
+                sent: row.get(2)?,
+                received: row.get(3)?,
+                last_date_cocoa: row.get(4)?,
+                unread: row.get(5)?,
+            },
+        ))
+    })?;
+
+    let mut by_key: HashMap<String, HandleStats> = HashMap::new();
+    for (handle, stats) in rows.filter_map(ok_row) {
+        by_key.entry(handle_key(&handle)).or_default().absorb(&stats);
     }
+    Ok(by_key)
 }
 
-/// Query top contacts by message volume.
-pub fn query_top_contacts(conn: &Connection, cutoff_cocoa: i64) -> Result<Vec<TopContact>> {
-    let mut stmt = conn.prepare(queries::ANALYTICS_TOP_CONTACTS)?;
-    let rows = stmt.query_map(&[&cutoff_cocoa], |row: &rusqlite::Row| {
+/// Query message counts (total, sent, received) in [start, end).
+pub fn query_message_counts_between(
+    conn: &Connection,
+    start_cocoa: i64,
+    end_cocoa: i64,
+    filter: Option<&HandleFilter>,
+) -> Result<(i64, i64, i64)> {
+    let sql = match filter {
+        Some(f) => queries::with_filter(queries::MESSAGE_COUNTS_BETWEEN, &f.sql_condition("m.handle_id")),
+        None => queries::MESSAGE_COUNTS_BETWEEN.to_string(),
+    };
+    let mut stmt = conn.prepare_cached(&sql)?;
+    Ok(stmt
+        .query_row([start_cocoa, end_cocoa], |row: &rusqlite::Row| {
+            Ok((
+                row.get::<_, i64>(0).unwrap_or(0),
+                row.get::<_, i64>(1).unwrap_or(0),
+                row.get::<_, i64>(2).unwrap_or(0),
+            ))
+        })
+        .unwrap_or((0, 0, 0)))
+}
+
+/// Query per-handle message counts in [start, end), busiest first.
+pub fn query_contact_counts_between(
+    conn: &Connection,
+    start_cocoa: i64,
+    end_cocoa: i64,
+    filter: Option<&HandleFilter>,
+) -> Result<Vec<TopContact>> {
+    let sql = match filter {
+        Some(f) => queries::with_filter(queries::CONTACT_COUNTS_BETWEEN, &f.sql_condition("m.handle_id")),
+        None => queries::CONTACT_COUNTS_BETWEEN.to_string(),
+    };
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map([start_cocoa, end_cocoa], |row: &rusqlite::Row| {
         Ok(TopContact {
             phone: row.get(0)?,
+            contact_name: None,
             message_count: row.get(1)?,
+            variants: Vec::new(),
         })
     })?;
-    Ok(rows
-        .filter_map(|r: rusqlite::Result<TopContact>| r.ok())
-        .collect())
+    Ok(rows.filter_map(ok_row).collect())
 }
 
-/// Query attachment count (optimized - skips attachment table join).
-pub fn query_attachments(
-    conn: &Connection,
-    cutoff_cocoa: i64,
-    phone: Option<&str>,
-) -> Result<i64> {
-    if let Some(p) = phone {
-        let mut stmt = conn.prepare(queries::ANALYTICS_ATTACHMENTS_FAST_PHONE)?;
-        let params: &[&dyn rusqlite::ToSql] = &[&cutoff_cocoa, &p];
-        Ok(stmt
-            .query_row(params, |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .unwrap_or(0))
-    } else {
-        let mut stmt = conn.prepare(queries::ANALYTICS_ATTACHMENTS_FAST)?;
-        Ok(stmt
-            .query_row(&[&cutoff_cocoa], |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .unwrap_or(0))
+// ============================================================================
+// Reaction (Tapback) Classification
+// ============================================================================
+
+/// Classified tapback reaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct ReactionKind {
+    /// Emoji to display (custom emoji for iOS 17+ reactions, "?" if unknown)
+    pub emoji: String,
+    /// Stable kind name (love, like, dislike, laugh, emphasize, question, emoji, sticker, unknown)
+    pub kind: &'static str,
+    /// True for 3000-range events that remove a previously sent tapback
+    pub is_removal: bool,
+}
+
+/// Classify an associated_message_type value as a tapback.
+///
+/// - 2000-2005: classic tapbacks, 2006: custom emoji (iOS 17+), 2007: sticker
+/// - 3000-3007: removal of the corresponding 2000-range tapback
+///
+/// `emoji_col` is message.associated_message_emoji when the schema has it
+/// (callers may fall back to `emoji_from_reaction_text` on the message body).
+pub fn reaction_kind(reaction_type: i64, emoji_col: Option<&str>) -> ReactionKind {
+    let is_removal = (3000..4000).contains(&reaction_type);
+    let base_type = if is_removal { reaction_type - 1000 } else { reaction_type };
+
+    let (emoji, kind) = match base_type {
+        2000 => ("❤️", "love"),
+        2001 => ("👍", "like"),
+        2002 => ("👎", "dislike"),
+        2003 => ("😂", "laugh"),
+        2004 => ("‼️", "emphasize"),
+        2005 => ("❓", "question"),
+        2006 => ("?", "emoji"),
+        2007 => ("?", "sticker"),
+        _ => ("?", "unknown"),
+    };
+
+    // Custom emoji/sticker tapbacks carry their glyph in a separate column
+    let emoji = match (kind, emoji_col) {
+        ("emoji", Some(e)) | ("sticker", Some(e)) if !e.trim().is_empty() => e.trim().to_string(),
+        _ => emoji.to_string(),
+    };
+
+    ReactionKind {
+        emoji,
+        kind,
+        is_removal,
     }
 }
 
-/// Query reaction count.
-pub fn query_reactions(conn: &Connection, cutoff_cocoa: i64, phone: Option<&str>) -> Result<i64> {
-    if let Some(p) = phone {
-        let mut stmt = conn.prepare(queries::ANALYTICS_REACTIONS_PHONE)?;
-        let params: &[&dyn rusqlite::ToSql] = &[&cutoff_cocoa, &p];
-        Ok(stmt
-            .query_row(params, |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .unwrap_or(0))
+/// Extract the emoji from a custom reaction body ("Reacted 🔥 to “...”").
+///
+/// Older schemas lack associated_message_emoji; the glyph is only in the text.
+pub fn emoji_from_reaction_text(text: &str) -> Option<String> {
+    let rest = text
+        .strip_prefix("Reacted ")
+        .or_else(|| text.strip_prefix("Removed "))?;
+    let emoji = rest.split_whitespace().next()?;
+    if emoji.chars().any(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    Some(emoji.to_string())
+}
+
+/// A standing tapback on a message (`--with-reactions`).
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct MessageReaction {
+    pub emoji: String,
+    /// Who reacted: "Me", else their handle (callers may put a contact name here)
+    pub from: String,
+    pub is_from_me: bool,
+}
+
+/// Tapbacks standing on each of the messages `rowids`, oldest first, keyed
+/// by message guid.
+///
+/// One query; the ROWIDs are inlined (`rowids_condition`). A removal (3000-range) cancels the same person's earlier tapback of the
+/// same kind and is not listed; messages without tapbacks have no entry.
+pub fn query_message_reactions(conn: &Connection, rowids: &[i64]) -> Result<HashMap<String, Vec<MessageReaction>>> {
+    let emoji_col = if has_column(conn, "message", "associated_message_emoji") {
+        "r.associated_message_emoji"
     } else {
-        let mut stmt = conn.prepare(queries::ANALYTICS_REACTIONS)?;
-        Ok(stmt
-            .query_row(&[&cutoff_cocoa], |row: &rusqlite::Row| row.get::<_, i64>(0))
-            .unwrap_or(0))
+        "NULL"
+    };
+    if rowids.is_empty() {
+        return Ok(HashMap::new());
     }
+    let targets = format!("target_guid IN (SELECT guid FROM message WHERE {})", rowids_condition("ROWID", rowids));
+    let sql = queries::with_filter(&queries::MESSAGE_REACTIONS.replace("{emoji}", emoji_col), &targets);
+    let mut stmt = conn.prepare(&sql)?;
+    let rows: Vec<_> = stmt
+        .query_map([], |row| {
+            let text: Option<String> = row.get(2)?;
+            let emoji = row
+                .get::<_, Option<String>>(3)?
+                .or_else(|| text.as_deref().and_then(emoji_from_reaction_text));
+            Ok((
+                row.get::<_, String>(0)?,
+                reaction_kind(row.get(1)?, emoji.as_deref()),
+                row.get::<_, bool>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })?
+        .filter_map(ok_row)
+        .collect();
+
+    // Per target: (kind, reaction) in the order they were sent
+    let mut standing: HashMap<String, Vec<(&'static str, MessageReaction)>> = HashMap::new();
+    for (target, kind, is_from_me, handle) in rows {
+        let from = if is_from_me {
+            "Me".to_string()
+        } else {
+            handle.unwrap_or_else(|| "Unknown".to_string())
+        };
+        let reactions = standing.entry(target).or_default();
+        if kind.is_removal {
+            let same = |(k, r): &(&str, MessageReaction)| {
+                *k == kind.kind && r.from == from && (kind.emoji == "?" || r.emoji == kind.emoji)
+            };
+            if let Some(i) = reactions.iter().rposition(same) {
+                reactions.remove(i);
+            }
+        } else {
+            reactions.push((kind.kind, MessageReaction { emoji: kind.emoji, from, is_from_me }));
+        }
+    }
+    Ok(standing
+        .into_iter()
+        .filter(|(_, reactions)| !reactions.is_empty())
+        .map(|(guid, reactions)| (guid, reactions.into_iter().map(|(_, r)| r).collect()))
+        .collect())
+}
+
+// ============================================================================
+// Schema Guards
+// ============================================================================
+
+/// A decoded row, or None (with a warning) for one that doesn't decode, so
+/// one malformed row skips instead of failing the listing:
+/// `rows.filter_map(ok_row)`.
+pub fn ok_row<T>(row: rusqlite::Result<T>) -> Option<T> {
+    row.map_err(|e| output::warn(format!("skipped a malformed row: {}", e))).ok()
+}
+
+/// Check whether a table has a column (chat.db schema varies across macOS versions).
+pub fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+    let mut stmt = match conn.prepare_cached(&format!("PRAGMA table_info({})", table)) {
+        Ok(stmt) => stmt,
+        Err(_) => return false,
+    };
+    let found = match stmt.query_map([], |row: &rusqlite::Row| row.get::<_, String>(1)) {
+        Ok(rows) => rows.filter_map(|r| r.ok()).any(|name| name == column),
+        Err(_) => false,
+    };
+    found
 }
 
 /// Convert day number (0-6) to day name.
@@ -207,7 +1030,7 @@ pub fn day_number_to_name(day: i64) -> Option<&'static str> {
         "Friday",
         "Saturday",
     ];
-    if day >= 0 && day < 7 {
+    if (0..7).contains(&day) {
         Some(DAYS[day as usize])
     } else {
         None
@@ -234,112 +1057,775 @@ pub struct CombinedAnalytics {
 /// Query all analytics stats in a single optimized query.
 /// Combines: message_counts, reactions, attachments, busiest_hour, busiest_day.
 /// Reduces from 3 queries to 1 for faster performance.
+/// Counts messages since `cutoff_cocoa`, and before `end_cocoa` when given.
 pub fn query_analytics_combined(
     conn: &Connection,
     cutoff_cocoa: i64,
+    end_cocoa: Option<i64>,
     phone: Option<&str>,
 ) -> Result<CombinedAnalytics> {
-    if let Some(p) = phone {
-        let mut stmt = conn.prepare(queries::ANALYTICS_COMBINED_PHONE)?;
-        let params: &[&dyn rusqlite::ToSql] = &[&cutoff_cocoa, &p];
-        stmt.query_row(params, |row| {
-            Ok(CombinedAnalytics {
-                total: row.get::<_, Option<i64>>(0)?.unwrap_or(0),
-                sent: row.get::<_, Option<i64>>(1)?.unwrap_or(0),
-                received: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
-                reactions: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
-                attachments: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
-                busiest_hour: row.get(5)?,
-                busiest_day: row.get(6)?,
-            })
-        })
-        .map_err(|e| anyhow::anyhow!("Combined analytics query failed: {}", e))
-    } else {
-        let mut stmt = conn.prepare(queries::ANALYTICS_COMBINED)?;
-        stmt.query_row([&cutoff_cocoa], |row| {
-            Ok(CombinedAnalytics {
-                total: row.get::<_, Option<i64>>(0)?.unwrap_or(0),
-                sent: row.get::<_, Option<i64>>(1)?.unwrap_or(0),
-                received: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
-                reactions: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
-                attachments: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
-                busiest_hour: row.get(5)?,
-                busiest_day: row.get(6)?,
-            })
-        })
+    let pattern = phone.map(queries::like_contains);
+    let sql = match (end_cocoa.is_some(), phone.is_some()) {
+        (false, false) => queries::ANALYTICS_COMBINED,
+        (false, true) => queries::ANALYTICS_COMBINED_PHONE,
+        (true, false) => queries::ANALYTICS_COMBINED_RANGE,
+        (true, true) => queries::ANALYTICS_COMBINED_PHONE_RANGE,
+    };
+    let mut stmt = conn.prepare_cached(sql)?;
+    let params = window_params(&cutoff_cocoa, &end_cocoa, pattern.as_ref().map(|p| p as &dyn rusqlite::ToSql));
+    stmt.query_row(params.as_slice(), combined_row)
         .map_err(|e| anyhow::anyhow!("Combined analytics query failed: {}", e))
-    }
 }
 
-// ============================================================================
-// Reading Query Helpers
-// ============================================================================
+fn combined_row(row: &rusqlite::Row) -> rusqlite::Result<CombinedAnalytics> {
+    Ok(CombinedAnalytics {
+        total: row.get::<_, Option<i64>>(0)?.unwrap_or(0),
+        sent: row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+        received: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+        reactions: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+        attachments: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+        busiest_hour: row.get(5)?,
+        busiest_day: row.get(6)?,
+    })
+}
 
-/// Query recent messages.
-pub fn query_recent_messages(
+/// Parameters of a date-window statement: ?1 = start, ?2 = end (bounded
+/// variants only), then `extra` (a phone pattern).
+fn window_params<'a>(
+    start_cocoa: &'a i64,
+    end_cocoa: &'a Option<i64>,
+    extra: Option<&'a dyn rusqlite::ToSql>,
+) -> Vec<&'a dyn rusqlite::ToSql> {
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![start_cocoa];
+    if let Some(end) = end_cocoa {
+        params.push(end);
+    }
+    params.extend(extra);
+    params
+}
+
+/// Combined analytics restricted to a handle set (e.g. a relationship),
+/// before `end_cocoa` when given.
+pub fn query_analytics_for_handles(
     conn: &Connection,
     cutoff_cocoa: i64,
-    limit: u32,
-) -> Result<Vec<RecentMessage>> {
-    let mut stmt = conn.prepare(queries::RECENT_MESSAGES)?;
+    end_cocoa: Option<i64>,
+    filter: &HandleFilter,
+) -> Result<CombinedAnalytics> {
+    let sql = match end_cocoa {
+        Some(_) => queries::ANALYTICS_COMBINED_HANDLES_RANGE,
+        None => queries::ANALYTICS_COMBINED_HANDLES,
+    };
+    let sql = sql
+        .replace("__HANDLES_M2__", &filter.sql_condition("m2.handle_id"))
+        .replace("__HANDLES__", &filter.sql_condition("m.handle_id"));
+    let mut stmt = conn.prepare_cached(&sql)?;
+    stmt.query_row(window_params(&cutoff_cocoa, &end_cocoa, None).as_slice(), combined_row)
+        .map_err(|e| anyhow::anyhow!("Combined analytics query failed: {}", e))
+}
 
-    let rows = stmt.query_map([&cutoff_cocoa, &(limit as i64)], |row: &rusqlite::Row| {
-        let date_cocoa: i64 = row.get(1)?;
-        Ok(RecentMessage {
-            text: row.get(0)?,
-            date: cocoa_to_iso(date_cocoa),
-            is_from_me: row.get::<_, i32>(2)? == 1,
-            phone: row.get::<_, Option<String>>(3)?.unwrap_or_else(|| "Unknown".to_string()),
-        })
-    })?;
+// ============================================================================
+// Volume Series (charting)
+// ============================================================================
 
-    Ok(rows.filter_map(|r| r.ok()).collect())
+/// Bucket width for a message volume series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesBucket {
+    Hourly,
+    Daily,
+    /// Weeks start on Monday.
+    Weekly,
 }
 
-/// Query unread messages.
-pub fn query_unread_messages(conn: &Connection, limit: u32) -> Result<Vec<UnreadMessage>> {
-    let mut stmt = conn.prepare(queries::UNREAD_MESSAGES)?;
+impl SeriesBucket {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "hourly" => Ok(Self::Hourly),
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            other => Err(anyhow::anyhow!(
+                "Unknown series '{}' (expected hourly, daily, or weekly)",
+                other
+            )),
+        }
+    }
 
-    let rows = stmt.query_map([&(limit as i64)], |row: &rusqlite::Row| {
-        let date_cocoa: i64 = row.get(5)?;
-        Ok(UnreadMessage {
-            text: row.get(2)?,
-            date: cocoa_to_iso(date_cocoa),
-            phone: row.get::<_, Option<String>>(6)?.unwrap_or_else(|| "Unknown".to_string()),
-        })
-    })?;
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    fn width_secs(self) -> i64 {
+        match self {
+            Self::Hourly => 3600,
+            Self::Daily => 86_400,
+            Self::Weekly => 7 * 86_400,
+        }
+    }
 
-    Ok(rows.filter_map(|r| r.ok()).collect())
+    /// Extra shift so week buckets start on Monday (1970-01-01 was a Thursday).
+    fn epoch_shift(self) -> i64 {
+        match self {
+            Self::Weekly => 3 * 86_400,
+            _ => 0,
+        }
+    }
+}
+
+/// One point of a volume series.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct VolumeBucket {
+    /// Local start of the bucket (RFC 3339 with offset).
+    pub bucket_start: String,
+    pub sent: i64,
+    pub received: i64,
+}
+
+/// Sent/received counts per local-time bucket from `cutoff_cocoa` to now,
+/// oldest first, with empty buckets filled in.
+pub fn query_volume_series(
+    conn: &Connection,
+    cutoff_cocoa: i64,
+    bucket: SeriesBucket,
+    phone: Option<&str>,
+) -> Result<Vec<VolumeBucket>> {
+    let now_cocoa = queries::unix_to_cocoa(chrono::Utc::now().timestamp());
+    query_volume_series_at(conn, cutoff_cocoa, now_cocoa, bucket, phone, &local_offset_at)
+}
+
+/// Width of the UTC slots VOLUME_SERIES counts in (every UTC offset is a multiple).
+const VOLUME_SLOT_SECS: i64 = 900;
+
+/// query_volume_series over [start, end] with an injected UTC offset lookup.
+///
+/// Each 15-minute slot is bucketed with the offset in effect at its start, so
+/// buckets stay on local midnights (or hours) across DST changes.
+pub fn query_volume_series_at(
+    conn: &Connection,
+    start_cocoa: i64,
+    end_cocoa: i64,
+    bucket: SeriesBucket,
+    phone: Option<&str>,
+    offset_at: &dyn Fn(i64) -> i32,
+) -> Result<Vec<VolumeBucket>> {
+    let width = bucket.width_secs();
+    let shift = bucket.epoch_shift();
+    // Local bucket index of a unix time
+    let index_of = |unix: i64| (unix + offset_at(unix) as i64 + shift).div_euclid(width);
+
+    let sql = match phone {
+        Some(_) => queries::with_filter(queries::VOLUME_SERIES, r"h.id LIKE ?2 ESCAPE '\'"),
+        None => queries::VOLUME_SERIES.to_string(),
+    };
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(start_cocoa)];
+    if let Some(p) = phone {
+        params.push(Box::new(queries::like_contains(p)));
+    }
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row: &rusqlite::Row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+            row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+        ))
+    })?;
+    let mut counts: HashMap<i64, (i64, i64)> = HashMap::new();
+    for (slot, sent, received) in rows.filter_map(ok_row) {
+        let entry = counts.entry(index_of(slot * VOLUME_SLOT_SECS)).or_default();
+        entry.0 += sent;
+        entry.1 += received;
+    }
+
+    let first = index_of(queries::cocoa_to_unix(start_cocoa));
+    let last = index_of(queries::cocoa_to_unix(end_cocoa));
+
+    Ok((first..=last)
+        .map(|index| {
+            let (sent, received) = counts.get(&index).copied().unwrap_or((0, 0));
+            // Local wall-clock start, placed with the offset in effect at that instant
+            let local = index * width - shift;
+            let offset = offset_at(local - offset_at(local) as i64);
+            let start = chrono::FixedOffset::east_opt(offset)
+                .and_then(|tz| chrono::TimeZone::timestamp_opt(&tz, local - offset as i64, 0).single())
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default();
+            VolumeBucket {
+                bucket_start: start,
+                sent,
+                received,
+            }
+        })
+        .collect())
+}
+
+/// Sent and received counts by local weekday (rows, 0 = Sunday) and hour (columns).
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct HeatmapCounts {
+    pub sent: [[i64; 24]; 7],
+    pub received: [[i64; 24]; 7],
+}
+
+/// The local timezone's UTC offset (seconds) at a unix time.
+pub fn local_offset_at(unix: i64) -> i32 {
+    chrono::TimeZone::timestamp_opt(&chrono::Local, unix, 0)
+        .single()
+        .map(|dt| dt.offset().local_minus_utc())
+        .unwrap_or(0)
+}
+
+/// Split [start, end] (unix seconds) into spans of constant UTC offset, as
+/// `(span start, offset)` pairs. Offsets are sampled daily and each change is
+/// narrowed to the second, so transitions less than a day apart are missed.
+pub(crate) fn offset_spans(start: i64, end: i64, offset_at: &dyn Fn(i64) -> i32) -> Vec<(i64, i32)> {
+    const DAY: i64 = 86_400;
+    let mut spans = vec![(start, offset_at(start))];
+    let mut t = start;
+    while t < end {
+        let next = (t + DAY).min(end);
+        let current = spans[spans.len() - 1].1;
+        if offset_at(next) != current {
+            // First second with the new offset
+            let (mut lo, mut hi) = (t, next);
+            while hi - lo > 1 {
+                let mid = lo + (hi - lo) / 2;
+                if offset_at(mid) == current {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            spans.push((hi, offset_at(hi)));
+        }
+        t = next;
+    }
+    spans
+}
+
+/// SQL expression for the UTC offset in effect at `m.date`, one CASE branch per span.
+fn offset_expr(spans: &[(i64, i32)]) -> String {
+    let Some(&(_, last)) = spans.last() else {
+        return "0".to_string();
+    };
+    if spans.len() == 1 {
+        return last.to_string();
+    }
+    let branches: Vec<String> = spans
+        .windows(2)
+        .map(|pair| format!("WHEN m.date < {} THEN {}", queries::unix_to_cocoa(pair[1].0), pair[0].1))
+        .collect();
+    format!("(CASE {} ELSE {} END)", branches.join(" "), last)
+}
+
+/// Weekday-by-hour message counts in local time from `cutoff_cocoa` to now.
+pub fn query_heatmap(conn: &Connection, cutoff_cocoa: i64, phone: Option<&str>) -> Result<HeatmapCounts> {
+    let now_cocoa = queries::unix_to_cocoa(chrono::Utc::now().timestamp());
+    query_heatmap_at(conn, cutoff_cocoa, now_cocoa, phone, &local_offset_at)
+}
+
+/// query_heatmap over [start, end] with an injected UTC offset lookup.
+///
+/// Each message gets the offset in effect when it was sent, so hours stay on
+/// the wall clock across DST changes. Messages after `end` use the last offset.
+pub fn query_heatmap_at(
+    conn: &Connection,
+    start_cocoa: i64,
+    end_cocoa: i64,
+    phone: Option<&str>,
+    offset_at: &dyn Fn(i64) -> i32,
+) -> Result<HeatmapCounts> {
+    let spans = offset_spans(queries::cocoa_to_unix(start_cocoa), queries::cocoa_to_unix(end_cocoa), offset_at);
+    let sql = queries::HEATMAP.replace("{shift}", &offset_expr(&spans));
+    let sql = match phone {
+        Some(_) => queries::with_filter(&sql, r"h.id LIKE ?2 ESCAPE '\'"),
+        None => sql,
+    };
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(start_cocoa)];
+    if let Some(p) = phone {
+        params.push(Box::new(queries::like_contains(p)));
+    }
+
+    let mut counts = HeatmapCounts { sent: [[0; 24]; 7], received: [[0; 24]; 7] };
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row: &rusqlite::Row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+            row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+        ))
+    })?;
+    for (weekday, hour, sent, received) in rows.filter_map(ok_row) {
+        let (Ok(day), Ok(hour)) = (usize::try_from(weekday), usize::try_from(hour)) else {
+            continue;
+        };
+        if day < 7 && hour < 24 {
+            counts.sent[day][hour] += sent;
+            counts.received[day][hour] += received;
+        }
+    }
+    Ok(counts)
+}
+
+/// Stream `(is_from_me, text)` for each message exchanged with `phone` since
+/// `cutoff_cocoa`, with blob text extracted. Returns the number of messages
+/// that had text.
+pub fn for_each_conversation_text(
+    conn: &Connection,
+    cutoff_cocoa: i64,
+    phone: &str,
+    mut f: impl FnMut(bool, &str),
+) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(queries::CONVERSATION_TEXTS)?;
+    let mut rows = stmt.query(rusqlite::params![cutoff_cocoa, queries::like_contains(phone)])?;
+    let mut seen = 0;
+    while let Some(row) = rows.next()? {
+        let blob: Option<Vec<u8>> = row.get(1)?;
+        let guid: Option<String> = row.get(3)?;
+        if let Some(text) = text_cache::message_text(guid.as_deref(), row.get(0)?, blob.as_deref()) {
+            f(row.get::<_, bool>(2)?, &text);
+            seen += 1;
+        }
+    }
+    Ok(seen)
+}
+
+/// Stream `(is_from_me, associated_message_type)` for each tapback in the
+/// conversation with `phone` since `cutoff_cocoa`. Returns the count.
+pub fn for_each_conversation_tapback(
+    conn: &Connection,
+    cutoff_cocoa: i64,
+    phone: &str,
+    mut f: impl FnMut(bool, i64),
+) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(queries::CONVERSATION_TAPBACKS)?;
+    let mut rows = stmt.query(rusqlite::params![cutoff_cocoa, queries::like_contains(phone)])?;
+    let mut seen = 0;
+    while let Some(row) = rows.next()? {
+        f(row.get::<_, bool>(1)?, row.get(0)?);
+        seen += 1;
+    }
+    Ok(seen)
+}
+
+// ============================================================================
+// Reading Query Helpers
+// ============================================================================
+
+/// Query recent messages.
+pub fn query_recent_messages(
+    conn: &Connection,
+    cutoff_cocoa: i64,
+    limit: u32,
+    filter: Option<&HandleFilter>,
+) -> Result<Vec<RecentMessage>> {
+    let sql = match filter {
+        Some(f) => queries::with_filter(queries::RECENT_MESSAGES, &f.sql_condition("m.handle_id")),
+        None => queries::RECENT_MESSAGES.to_string(),
+    };
+    read_recent_messages(conn, &sql, [cutoff_cocoa, limit as i64])
+}
+
+/// Messages exchanged with any of `handles` (phones/emails in any format)
+/// since `cutoff_cocoa`, newest first, at most `limit`.
+///
+/// Handle ROWIDs are bound rather than inlined, in as many queries as the
+/// parameter limit needs; the merged rows are re-sorted and cut to `limit`.
+pub fn query_messages_for_handles(
+    conn: &Connection,
+    handles: &[String],
+    cutoff_cocoa: i64,
+    limit: u32,
+) -> Result<Vec<RecentMessage>> {
+    let filter = HandleFilter::resolve(conn, handles)?;
+    let mut messages = query_chunked(filter.rowids(), 2, |chunk| {
+        let sql = queries::with_filter(queries::RECENT_MESSAGES, &queries::in_params("m.handle_id", 3, chunk.len()));
+        let limit = limit as i64;
+        let params = [&cutoff_cocoa, &limit].into_iter().chain(chunk);
+        read_recent_messages(conn, &sql, rusqlite::params_from_iter(params))
+    })?;
+    messages.sort_by(|a, b| b.date_cocoa.cmp(&a.date_cocoa).then(b.rowid.cmp(&a.rowid)));
+    messages.truncate(limit as usize);
+    Ok(messages)
+}
+
+/// Run a RECENT_MESSAGES-shaped query.
+fn read_recent_messages(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> Result<Vec<RecentMessage>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+
+    let rows = stmt.query_map(params, |row: &rusqlite::Row| {
+        let date_cocoa: i64 = row.get(1)?;
+        let guid: Option<String> = row.get(5)?;
+        let blob: Option<Vec<u8>> = row.get(6)?;
+        let hint: Option<String> = row.get(7)?;
+        let body = message_body(guid.as_deref(), row.get(0)?, blob.as_deref(), hint.as_deref());
+        Ok(RecentMessage {
+            rowid: row.get(4)?,
+            text: body.text,
+            guid: guid.unwrap_or_default(),
+            date: cocoa_to_iso(date_cocoa),
+            date_cocoa,
+            is_from_me: row.get::<_, i32>(2)? == 1,
+            phone: row.get::<_, Option<String>>(3)?.unwrap_or_else(|| "Unknown".to_string()),
+            placeholder: body.placeholder,
+        })
+    })?;
+
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Query the latest message per conversation (one row per chat).
+///
+/// With a filter, only chats that include one of the handles are returned.
+pub fn query_recent_conversations(
+    conn: &Connection,
+    limit: u32,
+    filter: Option<&HandleFilter>,
+    mutes: Option<&MuteFilter>,
+) -> Result<Vec<ConversationSummary>> {
+    let mut sql = match filter {
+        Some(f) => queries::with_filter(
+            queries::RECENT_CONVERSATIONS,
+            &format!(
+                "EXISTS (SELECT 1 FROM chat_handle_join chj WHERE chj.chat_id = c.ROWID AND {})",
+                f.sql_condition("chj.handle_id")
+            ),
+        ),
+        None => queries::RECENT_CONVERSATIONS.to_string(),
+    };
+    if let Some(m) = mutes {
+        sql = queries::with_filter(&sql, &m.sql_chat_exclusion("c.ROWID"));
+    }
+    Ok(query_conversation_rows(conn, &sql, limit)?.into_iter().map(|(_, c)| c).collect())
+}
+
+/// Latest message and unread count of each chat in `chat_ids`, keyed by
+/// chat ROWID, from one query (the `query_recent_conversations` rows).
+/// Chats without messages are absent from the map.
+pub fn query_latest_in_chats(conn: &Connection, chat_ids: &[i64]) -> Result<HashMap<i64, ConversationSummary>> {
+    if chat_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    // On the partition column, so SQLite ranks only these chats' messages
+    let sql = queries::with_filter(queries::RECENT_CONVERSATIONS, &rowids_condition("r.chat_id", chat_ids));
+    Ok(query_conversation_rows(conn, &sql, chat_ids.len() as u32)?.into_iter().collect())
+}
+
+/// Run a `RECENT_CONVERSATIONS`-shaped query (?1 = limit) as (chat ROWID, summary) rows.
+fn query_conversation_rows(conn: &Connection, sql: &str, limit: u32) -> Result<Vec<(i64, ConversationSummary)>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+
+    let rows = stmt.query_map([&(limit as i64)], |row: &rusqlite::Row| {
+        let chat_identifier: Option<String> = row.get(1)?;
+        let text: Option<String> = row.get(3)?;
+        let blob: Option<Vec<u8>> = row.get(4)?;
+        let guid: Option<String> = row.get(9)?;
+        let date_cocoa: i64 = row.get(5)?;
+        let hint: Option<String> = row.get(10)?;
+
+        // Prefer text column, fall back to attributedBody
+        let body = message_body(guid.as_deref(), text, blob.as_deref(), hint.as_deref());
+
+        let chat_identifier = chat_identifier.unwrap_or_else(|| "unknown".to_string());
+        let is_group = is_group_chat_identifier(Some(&chat_identifier));
+
+        Ok((row.get::<_, i64>(0)?, ConversationSummary {
+            display_name: row.get::<_, Option<String>>(2)?.filter(|n| !n.is_empty()),
+            last_message_text: body.text,
+            placeholder: body.placeholder,
+            last_date: cocoa_to_iso(date_cocoa),
+            last_is_from_me: row.get::<_, i32>(6)? != 0,
+            last_sender: row.get(7)?,
+            unread_count: row.get(8)?,
+            is_group,
+            chat_identifier,
+        }))
+    })?;
+
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Highest message ROWID currently in the database.
+pub fn query_max_message_rowid(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row(queries::MAX_MESSAGE_ROWID, [], |row| row.get(0))?)
+}
+
+/// Query incoming messages with ROWID greater than `after_rowid`, oldest first.
+pub fn query_incoming_since(
+    conn: &Connection,
+    after_rowid: i64,
+    limit: u32,
+) -> Result<Vec<IncomingMessage>> {
+    let mut stmt = conn.prepare_cached(queries::INCOMING_SINCE)?;
+
+    let rows = stmt.query_map(rusqlite::params![after_rowid, limit as i64], |row: &rusqlite::Row| {
+        let text: Option<String> = row.get(1)?;
+        let blob: Option<Vec<u8>> = row.get(2)?;
+        let guid: Option<String> = row.get(7)?;
+        let chat_identifier: Option<String> = row.get(5)?;
+        let hint: Option<String> = row.get(8)?;
+
+        let body = message_body(guid.as_deref(), text, blob.as_deref(), hint.as_deref());
+
+        Ok(IncomingMessage {
+            rowid: row.get(0)?,
+            text: body.text,
+            placeholder: body.placeholder,
+            date: cocoa_to_iso(row.get(3)?),
+            sender_handle: row.get(4)?,
+            is_group_chat: is_group_chat_identifier(chat_identifier.as_deref()),
+            chat_identifier,
+            display_name: row.get::<_, Option<String>>(6)?.filter(|n| !n.is_empty()),
+        })
+    })?;
+
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// (ROWID, guid) of the chat with `chat_identifier` (see `queries::CHAT_BY_IDENTIFIER`).
+pub fn query_chat_by_identifier(conn: &Connection, chat_identifier: &str) -> Result<Option<(i64, String)>> {
+    let mut stmt = conn.prepare_cached(queries::CHAT_BY_IDENTIFIER)?;
+    let mut rows = stmt.query_map([chat_identifier], |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Query (chat_identifier, unread_count) for conversations with unread messages.
+pub fn query_unread_chats(conn: &Connection) -> Result<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare_cached(queries::UNREAD_CHATS)?;
+    let rows = stmt.query_map([], |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Unread counts per conversation, most recently active first, leaving out
+/// muted chats.
+///
+/// A message is unread when it arrived after chat.last_read_message_timestamp;
+/// chats without a read position (and schemas without the column) fall back
+/// to the message's own read flags.
+pub fn query_unread_by_chat(conn: &Connection, mutes: Option<&MuteFilter>) -> Result<Vec<UnreadChatRow>> {
+    let unread = if has_column(conn, "chat", "last_read_message_timestamp") {
+        queries::UNREAD_SINCE_LAST_READ
+    } else {
+        queries::UNREAD_FLAGS
+    };
+    let mut sql = queries::with_filter(queries::UNREAD_BY_CHAT, unread);
+    if let Some(m) = mutes {
+        sql = queries::with_filter(&sql, &m.sql_chat_exclusion("c.ROWID"));
+    }
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map([], |row: &rusqlite::Row| {
+        Ok(UnreadChatRow {
+            chat_identifier: row.get(0)?,
+            display_name: row.get::<_, Option<String>>(1)?.filter(|n| !n.is_empty()),
+            unread_count: row.get(2)?,
+            oldest_unread_cocoa: row.get(3)?,
+        })
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Resolve the root of the thread a message belongs to.
+///
+/// Walks thread_originator_guid upward; returns None if `guid` doesn't exist.
+pub fn resolve_thread_root(conn: &Connection, guid: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare_cached(queries::THREAD_ORIGINATOR)?;
+    let mut current = guid.to_string();
+
+    // Bounded walk guards against malformed cycles
+    for hop in 0..32 {
+        let originator: Option<Option<String>> = stmt
+            .query_row([&current], |row| row.get(0))
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                other => Err(other),
+            })?;
+
+        match originator {
+            // Given guid doesn't exist at all
+            None if hop == 0 => return Ok(None),
+            // Originator missing from this database: treat last known as root
+            None => return Ok(Some(current)),
+            Some(None) => return Ok(Some(current)),
+            Some(Some(parent)) if parent.is_empty() || parent == current => {
+                return Ok(Some(current))
+            }
+            Some(Some(parent)) => current = parent,
+        }
+    }
+
+    Ok(Some(current))
+}
+
+/// Query all messages in the reply tree rooted at `root_guid`, oldest first.
+pub fn query_thread(conn: &Connection, root_guid: &str, limit: u32) -> Result<Vec<ThreadMessage>> {
+    let mut stmt = conn.prepare_cached(queries::THREAD_TREE)?;
+
+    let rows = stmt.query_map(rusqlite::params![root_guid, limit as i64], |row: &rusqlite::Row| {
+        let text: Option<String> = row.get(1)?;
+        let blob: Option<Vec<u8>> = row.get(2)?;
+        let guid: Option<String> = row.get(0)?;
+        let depth: i64 = row.get(7)?;
+        let hint: Option<String> = row.get(8)?;
+
+        let body = message_body(guid.as_deref(), text, blob.as_deref(), hint.as_deref());
+
+        Ok(ThreadMessage {
+            guid: row.get(0)?,
+            text: body.text,
+            placeholder: body.placeholder,
+            date: cocoa_to_iso(row.get(3)?),
+            is_from_me: row.get::<_, i32>(4)? != 0,
+            sender_handle: row.get(5)?,
+            // The root may itself point at a message outside this database
+            reply_to_guid: if depth == 0 { None } else { row.get(6)? },
+            depth,
+        })
+    })?;
+
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Conditions keeping the unread incoming rows of message table `alias`,
+/// less muted conversations: the WHERE clause every unread list and count
+/// shares, so they agree.
+///
+/// A message is unread when it arrived after its chat's
+/// last_read_message_timestamp; chats without a read position (and schemas
+/// without the column) fall back to the message's own read flags, as in
+/// `query_unread_by_chat`.
+pub fn unread_conditions(conn: &Connection, alias: &str, mutes: Option<&MuteFilter>) -> Vec<String> {
+    let unread = if has_column(conn, "chat", "last_read_message_timestamp") {
+        queries::UNREAD_MESSAGE_SINCE_LAST_READ
+    } else {
+        queries::UNREAD_MESSAGE_FLAGS
+    };
+    let mut conditions = vec![unread.replace("{m}", alias)];
+    conditions.extend(mutes.map(|m| m.sql_message_exclusion(&format!("{}.ROWID", alias))));
+    conditions
+}
+
+/// `sql` restricted to unread messages (`unread_conditions` over alias `m`),
+/// then to `filter`'s handles and `senders`' mode.
+fn unread_sql(
+    conn: &Connection,
+    sql: &str,
+    filter: Option<&HandleFilter>,
+    mutes: Option<&MuteFilter>,
+    senders: Option<&SenderFilter>,
+) -> String {
+    let conditions = unread_conditions(conn, "m", mutes)
+        .into_iter()
+        .chain(filter.map(|f| f.sql_condition("m.handle_id")))
+        .chain(senders.and_then(|s| s.sql_condition("m.ROWID", "m.handle_id")));
+    conditions.fold(sql.to_string(), |sql, condition| queries::with_filter(&sql, &condition))
+}
+
+/// Query unread messages, leaving out muted conversations and, with a
+/// SenderFilter, senders outside its mode.
+pub fn query_unread_messages(
+    conn: &Connection,
+    limit: u32,
+    filter: Option<&HandleFilter>,
+    mutes: Option<&MuteFilter>,
+    senders: Option<&SenderFilter>,
+) -> Result<Vec<UnreadMessage>> {
+    let sql = unread_sql(conn, queries::UNREAD_MESSAGES, filter, mutes, senders);
+    let mut stmt = conn.prepare_cached(&sql)?;
+
+    let rows = stmt.query_map([&(limit as i64)], |row: &rusqlite::Row| {
+        let date_cocoa: i64 = row.get(5)?;
+        let guid: Option<String> = row.get(1)?;
+        let blob: Option<Vec<u8>> = row.get(3)?;
+        let hint: Option<String> = row.get(9)?;
+        let body = message_body(guid.as_deref(), row.get(2)?, blob.as_deref(), hint.as_deref());
+        let handle: Option<String> = row.get(6)?;
+        let chat_identifier: Option<String> = row.get(7)?;
+        let is_known_sender = senders.is_some_and(|s| s.is_known(handle.as_deref(), chat_identifier.as_deref()));
+        Ok(UnreadMessage {
+            rowid: row.get(0)?,
+            guid: guid.unwrap_or_default(),
+            text: body.text,
+            date: cocoa_to_iso(date_cocoa),
+            phone: handle.unwrap_or_else(|| "Unknown".to_string()),
+            placeholder: body.placeholder,
+            is_known_sender,
+        })
+    })?;
+
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Count the messages `query_unread_messages` would return without a limit
+/// (one COUNT, no row decoding).
+pub fn query_unread_count(
+    conn: &Connection,
+    filter: Option<&HandleFilter>,
+    mutes: Option<&MuteFilter>,
+    senders: Option<&SenderFilter>,
+) -> Result<usize> {
+    let sql = unread_sql(conn, queries::UNREAD_COUNT, filter, mutes, senders);
+    let count: i64 = conn.query_row(&sql, [], |row| row.get(0))?;
+    Ok(count as usize)
+}
+
+/// Count unread messages in muted conversations (what muting hid).
+pub fn query_muted_unread_count(
+    conn: &Connection,
+    filter: Option<&HandleFilter>,
+    mutes: &MuteFilter,
+) -> Result<usize> {
+    let sql = unread_sql(conn, queries::UNREAD_COUNT, filter, None, None);
+    let sql = queries::with_filter(&sql, &mutes.sql_message_condition("m.ROWID"));
+    let count: i64 = conn.query_row(&sql, [], |row| row.get(0))?;
+    Ok(count as usize)
 }
 
 // ============================================================================
 // Discovery Query Helpers
 // ============================================================================
 
-/// Query handles (all senders).
+/// Query handles (all senders), phone format variants merged, most recent first.
 pub fn query_handles(
     conn: &Connection,
     cutoff_cocoa: i64,
     limit: u32,
 ) -> Result<Vec<HandleInfo>> {
-    let mut stmt = conn.prepare(queries::DISCOVERY_HANDLES)?;
+    let with_person = has_column(conn, "handle", "person_centric_id");
+    let sql = if with_person {
+        queries::DISCOVERY_HANDLES_WITH_PERSON
+    } else {
+        queries::DISCOVERY_HANDLES
+    };
+    let mut stmt = conn.prepare_cached(sql)?;
 
-    let rows = stmt.query_map([&cutoff_cocoa, &(limit as i64)], |row: &rusqlite::Row| {
+    let rows = stmt.query_map([&cutoff_cocoa], |row: &rusqlite::Row| {
         let last_date_cocoa: i64 = row.get(2)?;
+        let person_centric_id: Option<String> = if with_person { row.get(3)? } else { None };
         Ok(HandleInfo {
             handle: row.get(0)?,
             message_count: row.get(1)?,
             last_date: cocoa_to_iso(last_date_cocoa),
+            last_date_cocoa,
+            person_centric_id: person_centric_id.filter(|p| !p.is_empty()),
+            variants: Vec::new(),
         })
     })?;
 
-    Ok(rows.filter_map(|r| r.ok()).collect())
+    let mut handles = merge_variants(rows.filter_map(ok_row));
+    handles.sort_by_key(|h| std::cmp::Reverse(h.last_date_cocoa));
+    handles.truncate(limit as usize);
+    Ok(handles)
 }
 
-/// Query unknown senders (handles not matched to contacts).
+/// Query unknown senders (handles not matched to contacts), phone format
+/// variants merged, most recent first.
 /// Returns all handles; caller should filter against contacts list.
 pub fn query_unknown_senders(conn: &Connection, cutoff_cocoa: i64) -> Result<Vec<UnknownSender>> {
-    let mut stmt = conn.prepare(queries::DISCOVERY_UNKNOWN)?;
+    let mut stmt = conn.prepare_cached(queries::DISCOVERY_UNKNOWN)?;
 
     let rows = stmt.query_map([&cutoff_cocoa], |row: &rusqlite::Row| {
         let last_date_cocoa: i64 = row.get(2)?;
@@ -347,41 +1833,74 @@ pub fn query_unknown_senders(conn: &Connection, cutoff_cocoa: i64) -> Result<Vec
             handle: row.get(0)?,
             message_count: row.get(1)?,
             last_date: cocoa_to_iso(last_date_cocoa),
+            last_date_cocoa,
             sample_text: row.get(3)?,
+            variants: Vec::new(),
         })
     })?;
 
-    Ok(rows.filter_map(|r| r.ok()).collect())
+    let mut senders = merge_variants(rows.filter_map(ok_row));
+    senders.sort_by_key(|s| std::cmp::Reverse(s.last_date_cocoa));
+    Ok(senders)
+}
+
+/// Display names of named chats the handle is part of.
+pub fn query_handle_chat_names(conn: &Connection, handle: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(queries::HANDLE_CHAT_NAMES)?;
+    let rows = stmt.query_map([handle], |row: &rusqlite::Row| row.get::<_, String>(0))?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Text of the handle's most recent incoming messages, newest first.
+pub fn query_handle_recent_texts(conn: &Connection, handle: &str, limit: u32) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(queries::HANDLE_RECENT_TEXTS)?;
+    let rows = stmt.query_map(rusqlite::params![handle, limit as i64], |row: &rusqlite::Row| {
+        let text: Option<String> = row.get(0)?;
+        let blob: Option<Vec<u8>> = row.get(1)?;
+        let guid: Option<String> = row.get(2)?;
+        Ok(text_cache::message_text(guid.as_deref(), text, blob.as_deref()))
+    })?;
+    Ok(rows.filter_map(|r| r.ok().flatten()).collect())
 }
 
 // ============================================================================
 // Follow-Up Query Helpers
 // ============================================================================
 
-/// Query unanswered questions.
+/// Unanswered questions per section of `followup` and `digest`.
+pub const FOLLOWUP_LIMIT: u32 = 50;
+
+/// Query unanswered question candidates, newest first.
 pub fn query_unanswered_questions(
     conn: &Connection,
     cutoff_cocoa: i64,
     stale_threshold_ns: i64,
+    limit: u32,
+    include_system: bool,
 ) -> Result<Vec<UnansweredQuestion>> {
-    let mut stmt = conn.prepare(queries::FOLLOWUP_UNANSWERED_QUESTIONS)?;
+    let mut stmt = conn.prepare_cached(queries::FOLLOWUP_UNANSWERED_QUESTIONS)?;
 
-    let rows =
-        stmt.query_map([cutoff_cocoa, stale_threshold_ns], |row: &rusqlite::Row| {
+    let rows = stmt.query_map(
+        rusqlite::params![cutoff_cocoa, stale_threshold_ns, limit as i64, include_system],
+        |row: &rusqlite::Row| {
             let _rowid: i64 = row.get(0)?;
             let text: Option<String> = row.get(1)?;
             let date_cocoa: i64 = row.get(2)?;
             let phone: Option<String> = row.get(3)?;
+            let chat_identifier: Option<String> = row.get(4)?;
 
             Ok(UnansweredQuestion {
                 phone: phone.unwrap_or_else(|| "Unknown".to_string()),
                 text: text.unwrap_or_else(|| "[no text]".to_string()),
                 date: cocoa_to_iso(date_cocoa),
                 days_ago: days_ago_from_cocoa(date_cocoa),
+                is_group: is_group_chat_identifier(chat_identifier.as_deref()),
+                chat_identifier,
             })
-        })?;
+        },
+    )?;
 
-    Ok(rows.filter_map(|r| r.ok()).collect())
+    Ok(rows.filter_map(ok_row).collect())
 }
 
 /// Query stale conversations.
@@ -389,11 +1908,12 @@ pub fn query_stale_conversations(
     conn: &Connection,
     cutoff_cocoa: i64,
     stale_threshold_ns: i64,
+    include_system: bool,
 ) -> Result<Vec<StaleConversation>> {
-    let mut stmt = conn.prepare(queries::FOLLOWUP_STALE_CONVERSATIONS)?;
+    let mut stmt = conn.prepare_cached(queries::FOLLOWUP_STALE_CONVERSATIONS)?;
 
     let rows =
-        stmt.query_map([cutoff_cocoa, stale_threshold_ns], |row: &rusqlite::Row| {
+        stmt.query_map(rusqlite::params![cutoff_cocoa, stale_threshold_ns, include_system], |row: &rusqlite::Row| {
             let phone: Option<String> = row.get(0)?;
             let last_date_cocoa: i64 = row.get(1)?;
             let last_text: Option<String> = row.get(2)?;
@@ -407,41 +1927,504 @@ pub fn query_stale_conversations(
             })
         })?;
 
-    Ok(rows.filter_map(|r| r.ok()).collect())
+    Ok(rows.filter_map(ok_row).collect())
 }
 
 // ============================================================================
-// Utility Functions
+// Text Search Helpers
 // ============================================================================
 
-/// Convert Cocoa timestamp (nanoseconds since 2001-01-01) to ISO 8601 string.
-pub fn cocoa_to_iso(cocoa_ns: i64) -> String {
-    use std::time::{Duration, UNIX_EPOCH};
+/// Split comma-separated search terms; `\,` is a literal comma.
+///
+/// Each value is one `--any`/`--all` flag occurrence, so repeated flags also
+/// work for terms containing commas. Blank terms are dropped.
+pub fn split_terms<S: AsRef<str>>(values: &[S]) -> Vec<String> {
+    let mut terms = Vec::new();
+    for value in values {
+        let mut current = String::new();
+        let mut chars = value.as_ref().chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if chars.peek() == Some(&',') => {
+                    current.push(',');
+                    chars.next();
+                }
+                ',' => terms.push(std::mem::take(&mut current)),
+                _ => current.push(c),
+            }
+        }
+        terms.push(current);
+    }
+    terms
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Resolve a positional query or `--any`/`--all` values to (terms, mode).
+///
+/// Exactly one of the three may be given, to avoid ambiguity.
+pub fn resolve_search_terms(
+    query: Option<&str>,
+    any: &[String],
+    all: &[String],
+) -> Result<(Vec<String>, queries::TermMatch)> {
+    let given = [query.is_some(), !any.is_empty(), !all.is_empty()]
+        .iter()
+        .filter(|g| **g)
+        .count();
+    if given != 1 {
+        return Err(anyhow::anyhow!(
+            "Provide exactly one of: a query, --any, or --all"
+        ));
+    }
 
-    let unix_ts = queries::cocoa_to_unix(cocoa_ns);
-    if unix_ts < 0 {
-        return "1970-01-01T00:00:00Z".to_string();
+    let (terms, mode) = match query {
+        Some(q) => (vec![q.to_string()], queries::TermMatch::Any),
+        None if !any.is_empty() => (split_terms(any), queries::TermMatch::Any),
+        None => (split_terms(all), queries::TermMatch::All),
+    };
+    if terms.is_empty() {
+        return Err(anyhow::anyhow!("No search terms given"));
     }
+    Ok((terms, mode))
+}
 
-    let system_time = UNIX_EPOCH + Duration::from_secs(unix_ts as u64);
-    let datetime: chrono::DateTime<chrono::Utc> = system_time.into();
-    datetime.to_rfc3339()
+/// Terms found in `text` (case-insensitive, like SQLite's LIKE).
+pub fn matched_terms(text: &str, terms: &[String]) -> Vec<String> {
+    let haystack = text.to_lowercase();
+    terms
+        .iter()
+        .filter(|t| haystack.contains(&t.to_lowercase()))
+        .cloned()
+        .collect()
 }
 
-/// Calculate days ago from Cocoa timestamp.
-/// Handles clock adjustments gracefully instead of panicking.
-pub fn days_ago_from_cocoa(cocoa_ns: i64) -> i64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
+/// Search message text for any/all of `terms`, newest first.
+///
+/// SQL narrows candidates; matching is confirmed on the extracted text so
+/// attributedBody-only messages are covered. Candidates are read a page at a
+/// time until `limit` hits are found or they run out, so a match behind many
+/// non-matching blob-only messages is still found. With `chat_id` (chat
+/// ROWID), only messages in that chat are searched.
+pub fn query_text_search(
+    conn: &Connection,
+    terms: &[String],
+    mode: queries::TermMatch,
+    chat_id: Option<i64>,
+    include_system: bool,
+    limit: u32,
+) -> Result<Vec<SearchHit>> {
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Blob-only candidates may not match, so read several per wanted hit
+    let page_size = (limit as i64).saturating_mul(4).max(100);
+    let mut sql = match chat_id {
+        Some(_) => queries::group_text_search_terms_sql(terms.len(), mode),
+        None => queries::text_search_terms_sql(terms.len(), mode),
+    };
+    if !include_system {
+        sql = queries::with_filter(&sql, &item_kind::sql_exclude_system("m"));
+    }
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = terms
+        .iter()
+        .map(|t| Box::new(queries::like_contains(t)) as Box<dyn rusqlite::ToSql>)
+        .collect();
+    if let Some(chat_id) = chat_id {
+        params.push(Box::new(chat_id));
+    }
+    params.push(Box::new(page_size));
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let mut hits = Vec::new();
+    let mut offset: i64 = 0;
+    loop {
+        let page_params = params.iter().map(|p| p.as_ref()).chain([&offset as &dyn rusqlite::ToSql]);
+        let rows = stmt.query_map(rusqlite::params_from_iter(page_params), search_candidate)?;
+        let mut fetched = 0;
+        for row in rows {
+            fetched += 1;
+            let Some((rowid, guid, text, date_cocoa, is_from_me, handle, cache_roomnames, item_kind)) = ok_row(row)
+            else {
+                continue;
+            };
+            let Some(text) = text else { continue };
+            let matched = matched_terms(&text, terms);
+            let keep = match mode {
+                queries::TermMatch::Any => !matched.is_empty(),
+                queries::TermMatch::All => matched.len() == terms.len(),
+            };
+            if keep {
+                hits.push(SearchHit {
+                    rowid,
+                    guid,
+                    text,
+                    date_cocoa,
+                    is_from_me,
+                    handle,
+                    cache_roomnames,
+                    item_kind,
+                    matched_terms: matched,
+                });
+                if hits.len() >= limit as usize {
+                    return Ok(hits);
+                }
+            }
+        }
+        if fetched < page_size {
+            return Ok(hits);
+        }
+        offset += page_size;
+    }
+}
+
+/// A search candidate row: (rowid, guid, text, date, is_from_me, handle,
+/// cache_roomnames, item kind).
+type SearchCandidate = (i64, String, Option<String>, i64, bool, Option<String>, Option<String>, ItemKind);
+
+fn search_candidate(row: &rusqlite::Row) -> rusqlite::Result<SearchCandidate> {
+        let text: Option<String> = row.get(0)?;
+        let blob: Option<Vec<u8>> = row.get(1)?;
+        let guid: Option<String> = row.get(6)?;
+        let text = text_cache::message_text(guid.as_deref(), text, blob.as_deref());
+        let item = ItemCodes {
+            item_type: row.get(8)?,
+            group_action_type: row.get(9)?,
+            balloon_bundle_id: row.get(10)?,
+            ..Default::default()
+        };
+    Ok((
+        row.get::<_, i64>(7)?,
+        guid.unwrap_or_default(),
+        text,
+        row.get::<_, i64>(2)?,
+        row.get::<_, bool>(3)?,
+        row.get::<_, Option<String>>(4)?,
+        row.get::<_, Option<String>>(5)?,
+        item.kind(),
+    ))
+}
+
+/// Up to `before`/`after` messages around `rowid` in its chat, oldest first.
+///
+/// Includes the message itself; empty if it isn't in any chat.
+pub fn query_message_context(
+    conn: &Connection,
+    rowid: i64,
+    before: u32,
+    after: u32,
+) -> Result<Vec<ContextMessage>> {
+    let mut stmt = conn.prepare_cached(queries::MESSAGE_CONTEXT)?;
+    // Column 3 (date) is selected only so the UNION can be ordered by it
+    let rows = stmt.query_map(rusqlite::params![rowid, before, after], |row: &rusqlite::Row| {
+        let text: Option<String> = row.get(1)?;
+        let blob: Option<Vec<u8>> = row.get(2)?;
+        let guid: Option<String> = row.get(6)?;
+        Ok(ContextMessage {
+            rowid: row.get(0)?,
+            text: text_cache::message_text(guid.as_deref(), text, blob.as_deref()),
+            is_from_me: row.get(4)?,
+            handle: row.get(5)?,
+        })
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+// ============================================================================
+// Group Analytics Query Helpers (keyed on chat ROWID)
+// ============================================================================
+
+/// Resolve a chat by identifier, exact display name, or partial display name.
+pub fn resolve_group(conn: &Connection, id_or_name: &str) -> Result<Option<GroupRef>> {
+    let mut stmt = conn.prepare_cached(queries::GROUP_RESOLVE)?;
+    let pattern = queries::like_contains(id_or_name);
+    let mut rows = stmt.query_map([id_or_name, pattern.as_str()], |row: &rusqlite::Row| {
+        Ok(GroupRef {
+            rowid: row.get(0)?,
+            chat_identifier: row.get(1)?,
+            display_name: row.get::<_, Option<String>>(2)?.filter(|n| !n.is_empty()),
+        })
+    })?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Participant handles of each chat in `chat_ids`, from one query, sorted
+/// by handle id.
+///
+/// Chats without participants are absent from the map.
+pub fn query_chat_participants(conn: &Connection, chat_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+    let mut participants: HashMap<i64, Vec<String>> = HashMap::new();
+    if chat_ids.is_empty() {
+        return Ok(participants);
+    }
+    let sql = queries::with_filter(queries::CHAT_PARTICIPANTS, &rowids_condition("chj.chat_id", chat_ids));
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row: &rusqlite::Row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for (chat_id, handle) in rows.filter_map(ok_row) {
+        participants.entry(chat_id).or_default().push(handle);
+    }
+    Ok(participants)
+}
+
+/// Chats (ROWIDs, ascending) that any handle in `filter` takes part in,
+/// including one-to-one chats recorded only by their identifier.
+pub fn query_chats_with_handles(conn: &Connection, filter: &HandleFilter) -> Result<Vec<i64>> {
+    let sql = queries::with_filter(queries::CHATS_WITH_HANDLES, &filter.sql_condition("chj.handle_id"));
+    let mut stmt = conn.prepare(&sql)?;
+    let mut chats: Vec<i64> = stmt
+        .query_map([], |row: &rusqlite::Row| row.get::<_, i64>(0))?
+        .filter_map(ok_row)
+        .collect();
+    chats.extend(
+        query_direct_chats(conn)?
+            .into_iter()
+            .filter(|(_, handle)| filter.matches(handle))
+            .map(|(rowid, _)| rowid),
+    );
+    chats.sort_unstable();
+    chats.dedup();
+    Ok(chats)
+}
+
+/// Header facts for each chat in `chat_ids`, most recently active first.
+pub fn query_chat_stats(conn: &Connection, chat_ids: &[i64]) -> Result<Vec<ChatStats>> {
+    if chat_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pinned = if has_column(conn, "chat", "is_pinned") { "c.is_pinned" } else { "NULL" };
+    let sql = queries::with_filter(&queries::CHAT_INFO.replace("{pinned}", pinned), &rowids_condition("c.ROWID", chat_ids));
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row: &rusqlite::Row| {
+        Ok(ChatStats {
+            rowid: row.get(0)?,
+            chat_identifier: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            display_name: row.get::<_, Option<String>>(2)?.filter(|n| !n.is_empty()),
+            service: row.get(3)?,
+            message_count: row.get(4)?,
+            first_date: row.get::<_, Option<i64>>(5)?.map(cocoa_to_iso),
+            last_date: row.get::<_, Option<i64>>(6)?.map(cocoa_to_iso),
+            attachment_count: row.get(7)?,
+            pinned: row.get(8)?,
+        })
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Query message counts per sender in a chat, busiest first.
+pub fn query_group_sender_counts(
+    conn: &Connection,
+    chat_rowid: i64,
+    cutoff_cocoa: i64,
+) -> Result<Vec<SenderCount>> {
+    let mut stmt = conn.prepare_cached(queries::GROUP_SENDER_COUNTS)?;
+    let rows = stmt.query_map([chat_rowid, cutoff_cocoa], |row: &rusqlite::Row| {
+        let is_from_me: bool = row.get(0)?;
+        Ok(SenderCount {
+            handle: if is_from_me { None } else { row.get(1)? },
+            is_from_me,
+            message_count: row.get(2)?,
+        })
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Query the busiest (hour, message_count) pairs in a chat.
+pub fn query_group_busiest_hours(
+    conn: &Connection,
+    chat_rowid: i64,
+    cutoff_cocoa: i64,
+    limit: u32,
+) -> Result<Vec<(i64, i64)>> {
+    let mut stmt = conn.prepare_cached(queries::GROUP_BUSIEST_HOURS)?;
+    let rows = stmt.query_map([chat_rowid, cutoff_cocoa, limit as i64], |row: &rusqlite::Row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Query the message with the most tapbacks in a chat.
+pub fn query_group_most_reacted(
+    conn: &Connection,
+    chat_rowid: i64,
+    cutoff_cocoa: i64,
+) -> Result<Option<MostReacted>> {
+    let mut stmt = conn.prepare_cached(queries::GROUP_MOST_REACTED)?;
+    let mut rows = stmt.query_map([chat_rowid, cutoff_cocoa], |row: &rusqlite::Row| {
+        let text: Option<String> = row.get(1)?;
+        let blob: Option<Vec<u8>> = row.get(2)?;
+        let guid: Option<String> = row.get(0)?;
+        let text = text_cache::message_text(guid.as_deref(), text, blob.as_deref());
+        Ok(MostReacted {
+            guid: row.get(0)?,
+            text,
+            is_from_me: row.get(3)?,
+            sender_handle: row.get(4)?,
+            date: cocoa_to_iso(row.get(5)?),
+            reaction_count: row.get(6)?,
+        })
+    })?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Whether the database tracks recently deleted messages (macOS 13+).
+pub fn has_recoverable_messages(conn: &Connection) -> bool {
+    has_column(conn, "chat_recoverable_message_join", "message_id")
+}
+
+/// Query recently deleted messages deleted since `cutoff_cocoa`, newest deletion first.
+///
+/// Empty on schemas without chat_recoverable_message_join.
+pub fn query_deleted_messages(
+    conn: &Connection,
+    cutoff_cocoa: i64,
+    filter: Option<&HandleFilter>,
+) -> Result<Vec<DeletedRow>> {
+    if !has_column(conn, "chat_recoverable_message_join", "delete_date") {
+        return Ok(Vec::new());
+    }
+    let sql = match filter {
+        Some(f) => queries::with_filter(queries::DELETED_MESSAGES, &f.sql_condition("m.handle_id")),
+        None => queries::DELETED_MESSAGES.to_string(),
+    };
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map([cutoff_cocoa], |row: &rusqlite::Row| {
+        let guid: Option<String> = row.get(1)?;
+        let text: Option<String> = row.get(2)?;
+        let blob: Option<Vec<u8>> = row.get(3)?;
+        let hint: Option<String> = row.get(10)?;
+        Ok(DeletedRow {
+            rowid: row.get(0)?,
+            body: message_body(guid.as_deref(), text, blob.as_deref(), hint.as_deref()),
+            guid: guid.unwrap_or_default(),
+            date_cocoa: row.get(4)?,
+            is_from_me: row.get(5)?,
+            handle: row.get(6)?,
+            chat_identifier: row.get(7)?,
+            chat_name: row.get::<_, Option<String>>(8)?.filter(|n| !n.is_empty()),
+            delete_cocoa: row.get(9)?,
+        })
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Query a chat's participant and name-change events in chronological order.
+///
+/// Empty when the schema has no item_type/group_action_type; other_handle and
+/// group_title read as NULL on schemas without them.
+pub fn query_group_events(conn: &Connection, chat_rowid: i64) -> Result<Vec<GroupEventRow>> {
+    if !has_column(conn, "message", "item_type") || !has_column(conn, "message", "group_action_type") {
+        return Ok(Vec::new());
+    }
+    let optional = |column: &str| {
+        if has_column(conn, "message", column) {
+            format!("m.{}", column)
+        } else {
+            "NULL".to_string()
+        }
+    };
+    let sql = queries::GROUP_EVENTS
+        .replace("{other_handle}", &optional("other_handle"))
+        .replace("{group_title}", &optional("group_title"));
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map([chat_rowid], |row: &rusqlite::Row| {
+        let is_from_me: bool = row.get(1)?;
+        Ok(GroupEventRow {
+            date_cocoa: row.get(0)?,
+            is_from_me,
+            actor_handle: if is_from_me { None } else { row.get(2)? },
+            item_type: row.get(3)?,
+            group_action_type: row.get(4)?,
+            affected_handle: row.get(5)?,
+            group_title: row.get::<_, Option<String>>(6)?.filter(|t| !t.is_empty()),
+        })
+    })?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Map a GROUP_TIMELINE / CONVERSATION_TIMELINE row.
+fn timeline_message(row: &rusqlite::Row) -> rusqlite::Result<TimelineMessage> {
+    let text: Option<String> = row.get(2)?;
+    let blob: Option<Vec<u8>> = row.get(3)?;
+    let guid: Option<String> = row.get(5)?;
+    let text = text_cache::message_text(guid.as_deref(), text, blob.as_deref());
+    Ok(TimelineMessage {
+        is_from_me: row.get(0)?,
+        text,
+        date_cocoa: row.get(4)?,
+    })
+}
+
+/// Query a chat's messages in chronological order.
+pub fn query_group_timeline(
+    conn: &Connection,
+    chat_rowid: i64,
+    cutoff_cocoa: i64,
+) -> Result<Vec<TimelineMessage>> {
+    let mut stmt = conn.prepare_cached(queries::GROUP_TIMELINE)?;
+    let rows = stmt.query_map([chat_rowid, cutoff_cocoa], timeline_message)?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Query the conversation with `phone` since `cutoff_cocoa` in chronological order.
+pub fn query_conversation_timeline(
+    conn: &Connection,
+    cutoff_cocoa: i64,
+    phone: &str,
+) -> Result<Vec<TimelineMessage>> {
+    let mut stmt = conn.prepare_cached(queries::CONVERSATION_TIMELINE)?;
+    let rows = stmt.query_map(
+        rusqlite::params![cutoff_cocoa, queries::like_contains(phone)],
+        timeline_message,
+    )?;
+    Ok(rows.filter_map(ok_row).collect())
+}
+
+/// Message count and first message date (Cocoa ns) with `phone` over all
+/// history; no date bound, unlike query_message_counts.
+pub fn query_conversation_lifetime(conn: &Connection, phone: &str) -> Result<(i64, Option<i64>)> {
+    let mut stmt = conn.prepare_cached(queries::CONVERSATION_LIFETIME)?;
+    Ok(stmt.query_row([queries::like_contains(phone)], |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?)))?)
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// Check if a chat identifier indicates a group chat.
+pub fn is_group_chat_identifier(chat_id: Option<&str>) -> bool {
+    match chat_id {
+        None => false,
+        Some(id) => {
+            // Group chats start with 'chat' followed by digits
+            if let Some(rest) = id.strip_prefix("chat") {
+                if !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()) {
+                    return true;
+                }
+            }
+            // Or contain comma-separated handles
+            id.contains(',')
+        }
+    }
+}
 
-    // Handle potential clock adjustment gracefully
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs() as i64;
+/// Convert a Cocoa timestamp to an ISO 8601 string (see `queries::decode_cocoa`;
+/// zero, pre-2001 and far-future dates come out clamped).
+pub fn cocoa_to_iso(cocoa: i64) -> String {
+    queries::decode_cocoa(cocoa).to_rfc3339()
+}
 
-    let msg_unix = queries::cocoa_to_unix(cocoa_ns);
-    // Ensure non-negative result even if clock is adjusted
-    ((now - msg_unix) / 86400).max(0)
+/// Calculate days ago from Cocoa timestamp.
+/// Handles clock adjustments gracefully instead of panicking.
+pub fn days_ago_from_cocoa(cocoa: i64) -> i64 {
+    let now = chrono::Utc::now().timestamp();
+    // Future dates are clamped to at most a day ahead; never negative
+    ((now - queries::decode_cocoa_at(cocoa, now).unix_secs) / 86400).max(0)
 }
 
 #[cfg(test)]
@@ -456,6 +2439,484 @@ mod tests {
         assert_eq!(day_number_to_name(-1), None);
     }
 
+    #[test]
+    fn test_reaction_kind_full_range() {
+        let cases: &[(i64, &str, &str, bool)] = &[
+            (2000, "❤️", "love", false),
+            (2001, "👍", "like", false),
+            (2002, "👎", "dislike", false),
+            (2003, "😂", "laugh", false),
+            (2004, "‼️", "emphasize", false),
+            (2005, "❓", "question", false),
+            (2006, "?", "emoji", false),
+            (2007, "?", "sticker", false),
+            (3000, "❤️", "love", true),
+            (3001, "👍", "like", true),
+            (3002, "👎", "dislike", true),
+            (3003, "😂", "laugh", true),
+            (3004, "‼️", "emphasize", true),
+            (3005, "❓", "question", true),
+            (3006, "?", "emoji", true),
+            (3007, "?", "sticker", true),
+            (2999, "?", "unknown", false),
+            (3999, "?", "unknown", true),
+            (0, "?", "unknown", false),
+            (1000, "?", "unknown", false),
+        ];
+        for (reaction_type, emoji, kind, is_removal) in cases {
+            let result = reaction_kind(*reaction_type, None);
+            assert_eq!(result.emoji, *emoji, "emoji for {}", reaction_type);
+            assert_eq!(result.kind, *kind, "kind for {}", reaction_type);
+            assert_eq!(result.is_removal, *is_removal, "removal for {}", reaction_type);
+        }
+    }
+
+    #[test]
+    fn test_reaction_kind_custom_emoji_column() {
+        assert_eq!(reaction_kind(2006, Some("🔥")).emoji, "🔥");
+        assert_eq!(reaction_kind(3006, Some("🔥")).emoji, "🔥");
+        assert_eq!(reaction_kind(2006, Some("")).emoji, "?");
+        // Classic tapbacks ignore the column
+        assert_eq!(reaction_kind(2000, Some("🔥")).emoji, "❤️");
+    }
+
+    #[test]
+    fn test_emoji_from_reaction_text() {
+        assert_eq!(emoji_from_reaction_text("Reacted 🔥 to “hello”"), Some("🔥".to_string()));
+        assert_eq!(emoji_from_reaction_text("Removed 🎉 from “hello”"), Some("🎉".to_string()));
+        assert_eq!(emoji_from_reaction_text("Loved “hello”"), None);
+        assert_eq!(emoji_from_reaction_text("Reacted with a sticker"), None);
+    }
+
+    #[test]
+    fn test_recent_conversations_one_row_per_chat() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let direct = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let group = insert_chat(&conn, "chat123456", Some("Family"), &[alice, bob]);
+
+        // Chatty group: many messages, newest overall
+        for i in 0..8 {
+            insert_message(&conn, group, bob, Some("group msg"), 700 * DAY_NS + i, false, true);
+        }
+        insert_message(&conn, direct, alice, Some("older"), 600 * DAY_NS, false, true);
+        insert_message(&conn, direct, alice, Some("latest direct"), 650 * DAY_NS, false, false);
+
+        let conversations = query_recent_conversations(&conn, 10, None, None).unwrap();
+        assert_eq!(conversations.len(), 2);
+
+        assert_eq!(conversations[0].chat_identifier, "chat123456");
+        assert!(conversations[0].is_group);
+        assert_eq!(conversations[0].display_name.as_deref(), Some("Family"));
+        assert_eq!(conversations[0].unread_count, 0);
+
+        assert_eq!(conversations[1].chat_identifier, "+14155550001");
+        assert!(!conversations[1].is_group);
+        assert_eq!(conversations[1].last_message_text.as_deref(), Some("latest direct"));
+        assert_eq!(conversations[1].unread_count, 1);
+
+        // Limit applies to conversations, not messages
+        assert_eq!(query_recent_conversations(&conn, 1, None, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_latest_in_chats_ranks_only_requested_chats() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let a = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let b = insert_chat(&conn, "+14155550002", None, &[bob]);
+        insert_message(&conn, a, alice, Some("hi"), DAY_NS, false, false);
+        insert_message(&conn, b, bob, Some("newer, not asked for"), 2 * DAY_NS, false, false);
+
+        let latest = query_latest_in_chats(&conn, &[a, 999]).unwrap();
+        assert_eq!(latest.keys().collect::<Vec<_>>(), [&a]);
+        assert_eq!(latest[&a].last_message_text.as_deref(), Some("hi"));
+        assert_eq!(latest[&a].unread_count, 1);
+        assert!(query_latest_in_chats(&conn, &[]).unwrap().is_empty());
+
+        // The chat condition reaches the ranking, which searches instead of scanning
+        let sql = queries::with_filter(queries::RECENT_CONVERSATIONS, &rowids_condition("r.chat_id", &[a]));
+        let plan = crate::db::connection::explain_query_plan(&conn, &sql).unwrap();
+        assert!(plan.iter().any(|row| row.detail.starts_with("SEARCH cmj ")), "{:?}", plan);
+    }
+
+    #[test]
+    fn test_thread_tree_from_any_member() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+
+        let root = insert_message(&conn, chat, alice, Some("root"), 700 * DAY_NS, false, true);
+        let reply = insert_message(&conn, chat, alice, Some("reply"), 701 * DAY_NS, true, true);
+        let sibling = insert_message(&conn, chat, alice, Some("sibling"), 702 * DAY_NS, false, true);
+        let nested = insert_message(&conn, chat, alice, Some("nested"), 703 * DAY_NS, true, true);
+        let lonely = insert_message(&conn, chat, alice, Some("lonely"), 704 * DAY_NS, false, true);
+        let set_parent = |child: i64, parent: i64| {
+            conn.execute(
+                "UPDATE message SET thread_originator_guid = ?1 WHERE ROWID = ?2",
+                rusqlite::params![format!("msg-{}", parent), child],
+            )
+            .unwrap();
+        };
+        set_parent(reply, root);
+        set_parent(sibling, root);
+        set_parent(nested, reply);
+
+        // Starting from a nested reply still resolves the originator
+        let root_guid = resolve_thread_root(&conn, &format!("msg-{}", nested)).unwrap();
+        assert_eq!(root_guid, Some(format!("msg-{}", root)));
+
+        let thread = query_thread(&conn, root_guid.as_deref().unwrap(), 50).unwrap();
+        let depths: Vec<(Option<&str>, i64)> =
+            thread.iter().map(|m| (m.text.as_deref(), m.depth)).collect();
+        assert_eq!(
+            depths,
+            vec![(Some("root"), 0), (Some("reply"), 1), (Some("sibling"), 1), (Some("nested"), 2)]
+        );
+        assert_eq!(thread[0].reply_to_guid, None);
+        assert_eq!(thread[3].reply_to_guid, Some(format!("msg-{}", reply)));
+
+        // A message with no thread is a thread of one
+        let lonely_guid = format!("msg-{}", lonely);
+        assert_eq!(resolve_thread_root(&conn, &lonely_guid).unwrap(), Some(lonely_guid.clone()));
+        assert_eq!(query_thread(&conn, &lonely_guid, 50).unwrap().len(), 1);
+
+        assert_eq!(resolve_thread_root(&conn, "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_unread_chats_grouped() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let b_chat = insert_chat(&conn, "+14155550002", None, &[bob]);
+
+        insert_message(&conn, a_chat, alice, Some("one"), 700 * DAY_NS, false, false);
+        insert_message(&conn, a_chat, alice, Some("two"), 701 * DAY_NS, false, false);
+        insert_message(&conn, b_chat, bob, Some("read"), 702 * DAY_NS, false, true);
+        insert_message(&conn, b_chat, bob, Some("mine"), 703 * DAY_NS, true, false);
+
+        let chats = query_unread_chats(&conn).unwrap();
+        assert_eq!(chats, vec![("+14155550001".to_string(), 2)]);
+        assert_eq!(query_unread_count(&conn, None, None, None).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_unread_by_chat_from_read_position() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, set_last_read, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let carol = insert_handle(&conn, "+14155550003");
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let b_chat = insert_chat(&conn, "+14155550002", Some(""), &[bob]);
+        let group = insert_chat(&conn, "chat42", Some("Book Club"), &[alice, carol]);
+
+        // Alice: read up to day 701; the per-message flags disagree and lose
+        insert_message(&conn, a_chat, alice, Some("seen"), 700 * DAY_NS, false, false);
+        insert_message(&conn, a_chat, alice, Some("at the mark"), 701 * DAY_NS, false, false);
+        insert_message(&conn, a_chat, alice, Some("after one"), 702 * DAY_NS, false, true);
+        insert_message(&conn, a_chat, alice, Some("mine"), 703 * DAY_NS, true, false);
+        insert_message(&conn, a_chat, alice, Some("after two"), 704 * DAY_NS, false, false);
+        set_last_read(&conn, a_chat, 701 * DAY_NS);
+        // Bob: read past everything
+        insert_message(&conn, b_chat, bob, Some("old"), 705 * DAY_NS, false, false);
+        set_last_read(&conn, b_chat, 706 * DAY_NS);
+        // Group: no read position, so the flags decide
+        insert_message(&conn, group, carol, Some("read"), 706 * DAY_NS, false, true);
+        insert_message(&conn, group, carol, Some("unread"), 707 * DAY_NS, false, false);
+
+        let rows = query_unread_by_chat(&conn, None).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                UnreadChatRow {
+                    chat_identifier: "chat42".to_string(),
+                    display_name: Some("Book Club".to_string()),
+                    unread_count: 1,
+                    oldest_unread_cocoa: 707 * DAY_NS,
+                },
+                UnreadChatRow {
+                    chat_identifier: "+14155550001".to_string(),
+                    display_name: None,
+                    unread_count: 2,
+                    oldest_unread_cocoa: 702 * DAY_NS,
+                },
+            ]
+        );
+
+        // Older schemas have no read position: flags everywhere
+        conn.execute_batch("ALTER TABLE chat DROP COLUMN last_read_message_timestamp").unwrap();
+        let rows = query_unread_by_chat(&conn, None).unwrap();
+        let counts: Vec<(&str, i64)> = rows.iter().map(|r| (r.chat_identifier.as_str(), r.unread_count)).collect();
+        assert_eq!(counts, [("chat42", 1), ("+14155550002", 1), ("+14155550001", 3)]);
+    }
+
+    #[test]
+    fn test_unread_list_and_count_follow_read_position() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, set_last_read, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let carol = insert_handle(&conn, "+14155550003");
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let group = insert_chat(&conn, "chat42", Some("Book Club"), &[alice, carol]);
+        insert_message(&conn, a_chat, alice, Some("seen"), 700 * DAY_NS, false, false);
+        insert_message(&conn, a_chat, alice, Some("after"), 702 * DAY_NS, false, true);
+        set_last_read(&conn, a_chat, 701 * DAY_NS);
+        insert_message(&conn, group, carol, Some("unread"), 703 * DAY_NS, false, false);
+
+        let texts = |conn: &Connection| -> Vec<String> {
+            let unread = query_unread_messages(conn, 10, None, None, None).unwrap();
+            unread.into_iter().filter_map(|m| m.text).collect()
+        };
+        // Same rows as query_unread_by_chat, in the list and the count
+        assert_eq!(texts(&conn), ["unread", "after"]);
+        assert_eq!(query_unread_count(&conn, None, None, None).unwrap(), 2);
+        let by_chat: i64 = query_unread_by_chat(&conn, None).unwrap().iter().map(|r| r.unread_count).sum();
+        assert_eq!(by_chat, 2);
+
+        conn.execute_batch("ALTER TABLE chat DROP COLUMN last_read_message_timestamp").unwrap();
+        assert_eq!(texts(&conn), ["unread", "seen"]);
+        assert_eq!(query_unread_count(&conn, None, None, None).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_incoming_since_skips_own_and_seen() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+
+        let seen = insert_message(&conn, chat, alice, Some("old"), 700 * DAY_NS, false, true);
+        assert_eq!(query_max_message_rowid(&conn).unwrap(), seen);
+
+        insert_message(&conn, chat, alice, Some("mine"), 701 * DAY_NS, true, true);
+        let new = insert_message(&conn, chat, alice, Some("new"), 702 * DAY_NS, false, false);
+
+        let incoming = query_incoming_since(&conn, seen, 50).unwrap();
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].rowid, new);
+        assert_eq!(incoming[0].text.as_deref(), Some("new"));
+        assert_eq!(incoming[0].sender_handle.as_deref(), Some("+14155550001"));
+        assert!(!incoming[0].is_group_chat);
+    }
+
+    #[test]
+    fn test_handle_key() {
+        assert_eq!(handle_key("+1 (415) 555-1234"), "4155551234");
+        assert_eq!(handle_key("4155551234"), "4155551234");
+        assert_eq!(handle_key(" Sam@Example.com "), "sam@example.com");
+        assert_eq!(handle_key("32665"), "32665");
+    }
+
+    #[test]
+    fn test_handle_filter_sql_and_matching() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "bob@example.com");
+        let carol = insert_handle(&conn, "+14155550003");
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let b_chat = insert_chat(&conn, "bob@example.com", None, &[bob]);
+        let c_chat = insert_chat(&conn, "+14155550003", None, &[carol]);
+        insert_message(&conn, a_chat, alice, Some("a"), 700 * DAY_NS, false, false);
+        insert_message(&conn, b_chat, bob, Some("b"), 701 * DAY_NS, false, false);
+        insert_message(&conn, c_chat, carol, Some("c"), 702 * DAY_NS, false, false);
+
+        // Contact formats differ from Messages.db formats
+        let filter = HandleFilter::resolve(
+            &conn,
+            &["(415) 555-0001".to_string(), "Bob@Example.com".to_string()],
+        )
+        .unwrap();
+        assert!(filter.matches("+14155550001"));
+        assert!(!filter.matches("+14155550003"));
+
+        let unread = query_unread_messages(&conn, 10, Some(&filter), None, None).unwrap();
+        let phones: Vec<&str> = unread.iter().map(|m| m.phone.as_str()).collect();
+        assert_eq!(phones, vec!["bob@example.com", "+14155550001"]);
+
+        let chats = query_recent_conversations(&conn, 10, Some(&filter), None).unwrap();
+        assert_eq!(chats.len(), 2);
+
+        let counts = query_message_counts_between(&conn, 0, 800 * DAY_NS, Some(&filter)).unwrap();
+        assert_eq!(counts, (2, 0, 2));
+
+        // Empty set matches nothing rather than everything
+        let none = HandleFilter::resolve(&conn, &[]).unwrap();
+        assert!(query_unread_messages(&conn, 10, Some(&none), None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_handle_filter_chunks_large_sets() {
+        let filter = HandleFilter {
+            rowids: (1..=1001).collect(),
+            keys: HashSet::new(),
+        };
+        let sql = filter.sql_condition("m.handle_id");
+        assert_eq!(sql.matches(" IN (").count(), 3);
+        assert!(sql.starts_with("(m.handle_id IN (1,"));
+    }
+
+    /// Me (phone and email) with a notes-to-self chat, plus Alice.
+    fn self_fixture() -> Connection {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let me = insert_handle(&conn, "+14155550000");
+        let alice = insert_handle(&conn, "+14155550001");
+        let notes = insert_chat(&conn, "+14155550000", None, &[me]);
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_chat(&conn, "me@icloud.com", None, &[]);
+        conn.execute("UPDATE chat SET account_login = 'E:me@icloud.com'", []).unwrap();
+        for i in 0..3 {
+            insert_message(&conn, notes, me, Some("note"), 700 * DAY_NS + i, true, true);
+        }
+        insert_message(&conn, a_chat, alice, Some("hi"), 701 * DAY_NS, false, true);
+        conn
+    }
+
+    #[test]
+    fn test_my_handles_from_accounts() {
+        let conn = self_fixture();
+        assert_eq!(query_account_handles(&conn).unwrap(), vec!["me@icloud.com"]);
+
+        let mine = resolve_my_handles(&conn, &[]).unwrap();
+        assert!(mine.matches("ME@icloud.com"));
+        assert!(!mine.matches("+14155550000"));
+        // Only the participant-less chat named by the account counts
+        assert_eq!(query_self_chats(&conn, &mine).unwrap(), vec![3]);
+    }
+
+    #[test]
+    fn test_chat_accounts_per_service() {
+        use crate::db::fixtures::{insert_chat, insert_handle};
+
+        let conn = self_fixture();
+        let bob = insert_handle(&conn, "+14155550002");
+        insert_chat(&conn, "+14155550002", None, &[bob]);
+        conn.execute(
+            "UPDATE chat SET service_name = 'SMS', account_login = 'P:+14155550000', last_addressed_handle = '4155550000' WHERE ROWID = 4",
+            [],
+        )
+        .unwrap();
+        conn.execute("UPDATE chat SET last_addressed_handle = 'me@icloud.com' WHERE ROWID = 1", []).unwrap();
+
+        let accounts = query_chat_accounts(&conn).unwrap();
+        let summary: Vec<(&str, Option<&str>, i64)> = accounts
+            .iter()
+            .map(|a| (a.handle.as_str(), a.service.as_deref(), a.chat_count))
+            .collect();
+        // The SMS phone is recorded in both columns but counted once
+        assert_eq!(summary, [("me@icloud.com", Some("iMessage"), 3), ("+14155550000", Some("SMS"), 1)]);
+        assert_eq!(query_account_handles(&conn).unwrap(), ["me@icloud.com", "+14155550000"]);
+    }
+
+    #[test]
+    fn test_configured_my_handles_override_accounts() {
+        let conn = self_fixture();
+        let mine = resolve_my_handles(&conn, &["(415) 555-0000".to_string()]).unwrap();
+        assert!(mine.matches("+14155550000"));
+        assert!(!mine.matches("me@icloud.com"));
+        assert!(!mine.matches("+14155550001"));
+        assert_eq!(query_self_chats(&conn, &mine).unwrap(), vec![1]);
+
+        // Older schemas without the account columns find nothing to detect
+        let bare = Connection::open_in_memory().unwrap();
+        bare.execute_batch("CREATE TABLE chat (ROWID INTEGER PRIMARY KEY, chat_identifier TEXT);")
+            .unwrap();
+        assert!(query_account_handles(&bare).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_top_contacts_exclude_self() {
+        let conn = self_fixture();
+        let all = query_top_contacts(&conn, 0, None, None, None).unwrap();
+        assert_eq!(all[0].phone, "+14155550000");
+
+        let mine = resolve_my_handles(&conn, &["+14155550000".to_string()]).unwrap();
+        let others = query_top_contacts(&conn, 0, None, None, Some(&mine)).unwrap();
+        let phones: Vec<&str> = others.iter().map(|c| c.phone.as_str()).collect();
+        assert_eq!(phones, vec!["+14155550001"]);
+
+        // Nothing to exclude leaves the list unchanged
+        let none = HandleFilter::default();
+        assert_eq!(query_top_contacts(&conn, 0, None, None, Some(&none)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_analytics_upper_bound_excludes_later_messages() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let a_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let b_chat = insert_chat(&conn, "+14155550002", None, &[bob]);
+        insert_message(&conn, a_chat, alice, Some("before"), 99 * DAY_NS, false, false);
+        insert_message(&conn, a_chat, alice, Some("in"), 100 * DAY_NS, false, false);
+        insert_message(&conn, a_chat, alice, Some("in, sent"), 105 * DAY_NS, true, false);
+        // The end bound is exclusive
+        insert_message(&conn, b_chat, bob, Some("at end"), 110 * DAY_NS, false, false);
+        insert_message(&conn, b_chat, bob, Some("after"), 111 * DAY_NS, false, false);
+        insert_message(&conn, b_chat, bob, Some("after"), 112 * DAY_NS, false, false);
+        let (start, end) = (100 * DAY_NS, Some(110 * DAY_NS));
+
+        assert_eq!(query_message_counts(&conn, start, None, None).unwrap(), (5, 1, 4));
+        assert_eq!(query_message_counts(&conn, start, end, None).unwrap(), (2, 1, 1));
+        assert_eq!(query_message_counts(&conn, start, end, Some("4155550002")).unwrap(), (0, 0, 0));
+
+        let combined = query_analytics_combined(&conn, start, end, None).unwrap();
+        assert_eq!((combined.total, combined.sent, combined.received), (2, 1, 1));
+        let combined = query_analytics_combined(&conn, start, end, Some("4155550001")).unwrap();
+        assert_eq!(combined.total, 2);
+
+        let filter = HandleFilter::resolve(&conn, &["+14155550002".to_string()]).unwrap();
+        assert_eq!(query_analytics_for_handles(&conn, start, None, &filter).unwrap().total, 3);
+        assert_eq!(query_analytics_for_handles(&conn, start, end, &filter).unwrap().total, 0);
+
+        // Bob leads overall but has nothing inside the window
+        let top = query_top_contacts(&conn, start, None, None, None).unwrap();
+        assert_eq!(top[0].phone, "+14155550002");
+        let top = query_top_contacts(&conn, start, end, None, None).unwrap();
+        let phones: Vec<&str> = top.iter().map(|c| c.phone.as_str()).collect();
+        assert_eq!(phones, vec!["+14155550001"]);
+    }
+
+    #[test]
+    fn test_is_group_chat_identifier() {
+        assert!(is_group_chat_identifier(Some("chat123456")));
+        assert!(is_group_chat_identifier(Some("+1415,+1416")));
+        assert!(!is_group_chat_identifier(Some("+14155551234")));
+        assert!(!is_group_chat_identifier(Some("chat")));
+        assert!(!is_group_chat_identifier(None));
+    }
+
+    #[test]
+    fn test_has_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE message (ROWID INTEGER PRIMARY KEY, text TEXT);")
+            .unwrap();
+        assert!(has_column(&conn, "message", "text"));
+        assert!(!has_column(&conn, "message", "associated_message_emoji"));
+        assert!(!has_column(&conn, "missing_table", "text"));
+    }
+
     #[test]
     fn test_cocoa_to_iso() {
         // Known timestamp: 2025-01-01 00:00:00 UTC
@@ -463,4 +2924,563 @@ mod tests {
         let iso = cocoa_to_iso(cocoa);
         assert!(iso.starts_with("2025-01-01"));
     }
+
+    #[test]
+    fn test_message_reactions_net_out_removals() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, insert_reaction};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let group = insert_chat(&conn, "chat42", Some("Ski Trip"), &[alice, bob]);
+        let target = insert_message(&conn, group, alice, Some("who's driving?"), 100, false, true);
+        let quiet = insert_message(&conn, group, bob, Some("me"), 200, false, true);
+        let laughed = insert_message(&conn, group, bob, Some("lol"), 300, false, true);
+        let guid = format!("msg-{}", target);
+        insert_reaction(&conn, group, bob, &guid, 2000, 110, false);
+        insert_reaction(&conn, group, 0, &guid, 2001, 120, true);
+        insert_reaction(&conn, group, bob, &guid, 3000, 130, false);
+        // Older rows use the bp: prefix
+        let r = insert_reaction(&conn, group, alice, "", 2003, 310, false);
+        conn.execute(
+            "UPDATE message SET associated_message_guid = ?1 WHERE ROWID = ?2",
+            rusqlite::params![format!("bp:msg-{}", laughed), r],
+        )
+        .unwrap();
+
+        let guids: Vec<String> = [target, quiet, laughed].iter().map(|id| format!("msg-{}", id)).collect();
+        let reactions = query_message_reactions(&conn, &[target, quiet, laughed]).unwrap();
+        let mine = MessageReaction { emoji: "👍".to_string(), from: "Me".to_string(), is_from_me: true };
+        assert_eq!(reactions[&guid], vec![mine]);
+        assert!(!reactions.contains_key(&guids[1]));
+        assert_eq!(reactions[&guids[2]][0].emoji, "😂");
+        assert_eq!(reactions[&guids[2]][0].from, "+14155550001");
+        assert!(query_message_reactions(&conn, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_group_analytics_helpers() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let group = insert_chat(&conn, "chat42", Some("Ski Trip"), &[alice, bob]);
+        let other = insert_chat(&conn, "chat43", Some("Work"), &[alice]);
+
+        let base = 700 * DAY_NS;
+        let popular = insert_message(&conn, group, alice, Some("who's driving?"), base, false, true);
+        insert_message(&conn, group, alice, Some("hello"), base + 1, false, true);
+        insert_message(&conn, group, bob, Some("me"), base + 2, false, true);
+        insert_message(&conn, group, 0, Some("I can"), base + 3, true, true);
+        insert_message(&conn, other, alice, Some("unrelated"), base, false, true);
+        for (i, guid) in [format!("p:0/msg-{}", popular), format!("bp:msg-{}", popular)].iter().enumerate() {
+            let r = insert_message(&conn, group, bob, Some("Loved"), base + 10 + i as i64, false, true);
+            conn.execute(
+                "UPDATE message SET associated_message_type = 2000, associated_message_guid = ?1 WHERE ROWID = ?2",
+                rusqlite::params![guid, r],
+            )
+            .unwrap();
+        }
+
+        let resolved = resolve_group(&conn, "ski").unwrap().unwrap();
+        assert_eq!(resolved.rowid, group);
+        assert_eq!(resolve_group(&conn, "chat43").unwrap().unwrap().rowid, other);
+        assert!(resolve_group(&conn, "nope").unwrap().is_none());
+
+        let counts = query_group_sender_counts(&conn, group, 0).unwrap();
+        assert_eq!(counts[0].handle.as_deref(), Some("+14155550001"));
+        assert_eq!(counts[0].message_count, 2);
+        assert_eq!(counts.iter().map(|c| c.message_count).sum::<i64>(), 4);
+        assert!(counts.iter().any(|c| c.is_from_me && c.handle.is_none()));
+
+        let most = query_group_most_reacted(&conn, group, 0).unwrap().unwrap();
+        assert_eq!(most.guid, format!("msg-{}", popular));
+        assert_eq!(most.reaction_count, 2);
+
+        let timeline = query_group_timeline(&conn, group, 0).unwrap();
+        assert_eq!(timeline.len(), 4);
+        assert!(timeline[3].is_from_me);
+
+        assert_eq!(query_group_busiest_hours(&conn, group, 0, 3).unwrap()[0].1, 4);
+    }
+
+    #[test]
+    fn test_split_terms() {
+        assert_eq!(split_terms(&["invoice,receipt, payment"]), vec!["invoice", "receipt", "payment"]);
+        assert_eq!(split_terms(&[r"1\,000,fee"]), vec!["1,000", "fee"]);
+        assert_eq!(split_terms(&["a,b", "c"]), vec!["a", "b", "c"]);
+        assert!(split_terms(&[" , "]).is_empty());
+    }
+
+    #[test]
+    fn test_text_search_any_all_and_wildcards() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+        use crate::db::queries::TermMatch;
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("Invoice attached"), DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("receipt and payment"), 2 * DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("100% done"), 3 * DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("1000 things"), 4 * DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("my_file"), 5 * DAY_NS, false, true);
+        insert_message(&conn, chat, alice, Some("myXfile"), 6 * DAY_NS, false, true);
+
+        let terms = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let any = query_text_search(&conn, &terms(&["invoice", "receipt", "payment"]), TermMatch::Any, None, false, 10).unwrap();
+        assert_eq!(any.len(), 2);
+        assert_eq!(any[0].matched_terms, vec!["receipt", "payment"]);
+        assert_eq!(any[1].matched_terms, vec!["invoice"]);
+
+        let all = query_text_search(&conn, &terms(&["receipt", "payment"]), TermMatch::All, None, false, 10).unwrap();
+        assert_eq!(all.len(), 1);
+
+        let pct = query_text_search(&conn, &terms(&["100%"]), TermMatch::Any, None, false, 10).unwrap();
+        assert_eq!(pct.len(), 1);
+        assert_eq!(pct[0].text, "100% done");
+
+        let underscore = query_text_search(&conn, &terms(&["my_file"]), TermMatch::Any, None, false, 10).unwrap();
+        assert_eq!(underscore.len(), 1);
+        assert_eq!(underscore[0].text, "my_file");
+    }
+
+    #[test]
+    fn test_text_search_finds_match_older_than_first_page() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+        use crate::db::queries::TermMatch;
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("the quokka photo"), DAY_NS, false, true);
+        // 250 newer blob-only messages: candidates that don't match
+        let mut blob: Vec<u8> = b"streamtypedNSString".to_vec();
+        blob.extend_from_slice(&[0x01, 0x94, 0x84, 0x01, b'+', 0x05]);
+        blob.extend_from_slice(b"hello");
+        blob.extend_from_slice(&[0x86, 0x84]);
+        for i in 0..250 {
+            let rowid = insert_message(&conn, chat, alice, None, 2 * DAY_NS + i, false, true);
+            conn.execute("UPDATE message SET attributedBody = ?1 WHERE ROWID = ?2", rusqlite::params![blob, rowid])
+                .unwrap();
+        }
+
+        let terms = vec!["quokka".to_string()];
+        let hits = query_text_search(&conn, &terms, TermMatch::Any, None, false, 5).unwrap();
+        assert_eq!(hits.iter().map(|h| h.text.as_str()).collect::<Vec<_>>(), ["the quokka photo"]);
+        let hits = query_text_search(&conn, &["hello".to_string()], TermMatch::Any, Some(chat), false, 120).unwrap();
+        assert_eq!(hits.len(), 120);
+    }
+
+    #[test]
+    fn test_text_search_leaves_out_system_items_by_default() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+        use crate::db::queries::TermMatch;
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("sharing my location"), DAY_NS, false, true);
+        let update = insert_message(&conn, chat, alice, Some("started sharing location"), 2 * DAY_NS, false, true);
+        conn.execute("UPDATE message SET item_type = 4 WHERE ROWID = ?1", [update]).unwrap();
+        let terms = vec!["location".to_string()];
+
+        let hits = query_text_search(&conn, &terms, TermMatch::Any, None, false, 10).unwrap();
+        assert_eq!(hits.iter().map(|h| h.item_kind).collect::<Vec<_>>(), [ItemKind::Message]);
+        let hits = query_text_search(&conn, &terms, TermMatch::Any, None, true, 10).unwrap();
+        assert_eq!(hits.iter().map(|h| h.item_kind).collect::<Vec<_>>(), [ItemKind::Unknown, ItemKind::Message]);
+    }
+
+    #[test]
+    fn test_message_context_window() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let other = insert_chat(&conn, "chat999", Some("Other"), &[alice]);
+        let ids: Vec<i64> = (0..6)
+            .map(|i| insert_message(&conn, chat, alice, Some(&format!("m{}", i)), (i + 1) * DAY_NS, false, true))
+            .collect();
+        insert_message(&conn, other, alice, Some("elsewhere"), 3 * DAY_NS, false, true);
+
+        let window = query_message_context(&conn, ids[2], 2, 1).unwrap();
+        let texts: Vec<_> = window.iter().map(|m| m.text.as_deref().unwrap()).collect();
+        assert_eq!(texts, vec!["m0", "m1", "m2", "m3"]);
+
+        // Clipped at the start of the chat
+        let first = query_message_context(&conn, ids[0], 2, 0).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].rowid, ids[0]);
+    }
+
+    /// Statement-cache timing on the daemon hot path: `cargo test -- --ignored prepare_cached`.
+    #[test]
+    #[ignore]
+    fn test_prepare_cached_timing() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+        use std::time::Instant;
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        for i in 0..200 {
+            insert_message(&conn, chat, alice, Some("hello"), 700 * DAY_NS + i, i % 2 == 0, i % 3 == 0);
+        }
+
+        let run = |conn: &Connection| {
+            let start = Instant::now();
+            for _ in 0..2000 {
+                query_unread_messages(conn, 20, None, None, None).unwrap();
+                query_recent_messages(conn, 0, 20, None).unwrap();
+                query_message_counts(conn, 0, None, None).unwrap();
+            }
+            start.elapsed()
+        };
+
+        // Capacity 0 means every call re-prepares (the pre-cache behavior)
+        conn.set_prepared_statement_cache_capacity(0);
+        let uncached = run(&conn);
+        conn.set_prepared_statement_cache_capacity(16);
+        let cached = run(&conn);
+
+        eprintln!("uncached {:?}, cached {:?}", uncached, cached);
+        assert!(cached < uncached);
+    }
+
+    #[test]
+    fn test_resolve_search_terms_rejects_ambiguity() {
+        use crate::db::queries::TermMatch;
+
+        let any = vec!["a,b".to_string()];
+        assert!(resolve_search_terms(Some("q"), &any, &[]).is_err());
+        assert!(resolve_search_terms(None, &[], &[]).is_err());
+        assert!(resolve_search_terms(None, &any, &any).is_err());
+
+        let (terms, mode) = resolve_search_terms(None, &[], &any).unwrap();
+        assert_eq!(terms, vec!["a", "b"]);
+        assert_eq!(mode, TermMatch::All);
+    }
+
+    #[test]
+    fn test_phone_format_variants_merge() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        // One person over iMessage (+1...) and SMS (bare 10 digits), plus Bob
+        let conn = empty_db();
+        let imessage = insert_handle(&conn, "+14155551234");
+        let sms = insert_handle(&conn, "4155551234");
+        let bob = insert_handle(&conn, "bob@example.com");
+        let chat = insert_chat(&conn, "+14155551234", None, &[imessage, sms]);
+        let b_chat = insert_chat(&conn, "bob@example.com", None, &[bob]);
+        for i in 0..2 {
+            insert_message(&conn, chat, imessage, Some("old"), 700 * DAY_NS + i, false, true);
+        }
+        for i in 0..2 {
+            insert_message(&conn, chat, sms, Some("new"), 702 * DAY_NS + i, false, true);
+        }
+        for i in 0..3 {
+            insert_message(&conn, b_chat, bob, Some("hey"), 701 * DAY_NS + i, false, true);
+        }
+
+        let top = query_top_contacts(&conn, 0, None, None, None).unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!((top[0].phone.as_str(), top[0].message_count), ("+14155551234", 4));
+        assert_eq!(top[0].variants, vec!["+14155551234", "4155551234"]);
+        assert_eq!((top[1].phone.as_str(), top[1].message_count), ("bob@example.com", 3));
+        assert!(top[1].variants.is_empty());
+
+        let handles = query_handles(&conn, 0, 1).unwrap();
+        assert_eq!(handles.len(), 1);
+        assert_eq!((handles[0].handle.as_str(), handles[0].message_count), ("+14155551234", 4));
+        assert_eq!(handles[0].last_date_cocoa, 702 * DAY_NS + 1);
+
+        let senders = query_unknown_senders(&conn, 0).unwrap();
+        assert_eq!(senders.len(), 2);
+        assert_eq!(senders[0].message_count, 4);
+        assert_eq!(senders[0].sample_text.as_deref(), Some("new"));
+        assert_eq!(senders[0].variants.len(), 2);
+    }
+
+    #[test]
+    fn test_variant_key_leaves_emails_and_sender_ids() {
+        assert_eq!(variant_key("+1 (415) 555-1234"), "4155551234");
+        assert_eq!(variant_key("Bob@Example.com"), "Bob@Example.com");
+        assert_eq!(variant_key("AMAZON2FA"), "AMAZON2FA");
+        assert_eq!(canonical_phone(&["4155551234".into(), "14155551234".into()]), "+14155551234");
+        assert_eq!(canonical_phone(&["(415) 555-1234".into(), "4155551234".into()]), "4155551234");
+    }
+
+    #[test]
+    fn test_recent_messages_cutoff_limit_and_filter() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let alice_chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let bob_chat = insert_chat(&conn, "+14155550002", None, &[bob]);
+        insert_message(&conn, alice_chat, alice, Some("too old"), 690 * DAY_NS, false, true);
+        for day in 700..705 {
+            insert_message(&conn, alice_chat, alice, Some("alice"), day * DAY_NS, false, true);
+        }
+        insert_message(&conn, bob_chat, bob, Some("bob"), 706 * DAY_NS, false, true);
+
+        let recent = query_recent_messages(&conn, 695 * DAY_NS, 3, None).unwrap();
+        let dates: Vec<i64> = recent.iter().map(|m| m.date_cocoa / DAY_NS).collect();
+        assert_eq!(dates, [706, 704, 703]);
+
+        let filter = HandleFilter::resolve(&conn, &["+14155550001".to_string()]).unwrap();
+        let alice_only = query_recent_messages(&conn, 695 * DAY_NS, 10, Some(&filter)).unwrap();
+        assert_eq!(alice_only.len(), 5);
+        assert!(alice_only.iter().all(|m| m.text.as_deref() == Some("alice")));
+    }
+
+    #[test]
+    fn test_recent_and_unread_carry_message_ids() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("read"), 700 * DAY_NS, false, true);
+        let unread = insert_message(&conn, chat, alice, Some("unread"), 701 * DAY_NS, false, false);
+
+        let recent = query_recent_messages(&conn, 0, 10, None).unwrap();
+        let ids: Vec<(i64, &str)> = recent.iter().map(|m| (m.rowid, m.guid.as_str())).collect();
+        assert_eq!(ids, vec![(2, "msg-2"), (1, "msg-1")]);
+
+        let pending = query_unread_messages(&conn, 10, None, None, None).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!((pending[0].rowid, pending[0].guid.clone()), (unread, format!("msg-{}", unread)));
+    }
+
+    #[test]
+    fn test_attachment_only_messages_get_placeholders() {
+        use crate::db::fixtures::{empty_db, insert_attachment, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let captioned = insert_message(&conn, chat, alice, Some("look!"), 700 * DAY_NS, false, true);
+        insert_attachment(&conn, captioned, Some("image/jpeg"), "IMG_0001.JPG");
+        let photo = insert_message(&conn, chat, alice, Some("\u{FFFC}"), 701 * DAY_NS, false, true);
+        insert_attachment(&conn, photo, Some("image/heic"), "IMG_0002.HEIC");
+        let pdf = insert_message(&conn, chat, alice, None, 702 * DAY_NS, false, false);
+        insert_attachment(&conn, pdf, Some("application/pdf"), "lease.pdf");
+
+        let recent = query_recent_messages(&conn, 0, 10, None).unwrap();
+        let bodies: Vec<(Option<&str>, Option<&str>)> =
+            recent.iter().map(|m| (m.text.as_deref(), m.placeholder.as_deref())).collect();
+        assert_eq!(
+            bodies,
+            [(None, Some("[file: lease.pdf]")), (None, Some("[photo]")), (Some("look!"), None)]
+        );
+
+        let conversations = query_recent_conversations(&conn, 10, None, None).unwrap();
+        assert_eq!(conversations[0].last_message_text, None);
+        assert_eq!(conversations[0].placeholder.as_deref(), Some("[file: lease.pdf]"));
+
+        let pending = query_unread_messages(&conn, 10, None, None, None).unwrap();
+        assert_eq!((pending[0].text.as_deref(), pending[0].placeholder.as_deref()), (None, Some("[file: lease.pdf]")));
+    }
+
+    #[test]
+    fn test_heatmap_buckets_by_wall_clock_across_dst() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+        // US spring-forward, Sunday 2026-03-08: 02:00 PST (10:00 UTC) becomes 03:00 PDT
+        let change = 1_772_964_000;
+        let offset_at = |unix: i64| if unix < change { -8 * 3600 } else { -7 * 3600 };
+        assert_eq!(offset_spans(change - 3 * 86_400, change + 3 * 86_400, &offset_at), [
+            (change - 3 * 86_400, -8 * 3600),
+            (change, -7 * 3600),
+        ]);
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let other = insert_chat(&conn, "+14155550002", None, &[bob]);
+        let at = |unix: i64| queries::unix_to_cocoa(unix);
+        // 01:30 PST and 03:30 PDT: one wall-clock hour before and after the change
+        insert_message(&conn, chat, alice, Some("before"), at(change - 1800), true, true);
+        insert_message(&conn, chat, alice, Some("after"), at(change + 1800), false, true);
+        // Saturday 23:00 PST
+        insert_message(&conn, other, bob, Some("sat"), at(change - 3 * 3600), false, true);
+
+        let start = at(change - 3 * 86_400);
+        let end = at(change + 3 * 86_400);
+        let all = query_heatmap_at(&conn, start, end, None, &offset_at).unwrap();
+        assert_eq!(all.sent[0][1], 1);
+        assert_eq!(all.received[0][3], 1);
+        assert_eq!(all.received[6][23], 1);
+        let total: i64 = all.sent.iter().chain(all.received.iter()).flatten().sum();
+        assert_eq!(total, 3);
+
+        let alice_only = query_heatmap_at(&conn, start, end, Some("+14155550001"), &offset_at).unwrap();
+        assert_eq!(alice_only.received[6][23], 0);
+        assert_eq!((alice_only.sent[0][1], alice_only.received[0][3]), (1, 1));
+
+        // A single offset puts the PST message an hour late
+        let fixed = query_heatmap_at(&conn, start, end, None, &|_| -7 * 3600).unwrap();
+        assert_eq!((fixed.sent[0][1], fixed.sent[0][2]), (0, 1));
+    }
+
+    #[test]
+    fn test_messages_for_handles_past_the_parameter_limit() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+        // One message per handle; handle i's is the i-th oldest
+        let conn = empty_db();
+        let chat = insert_chat(&conn, "chat-many", None, &[]);
+        let phones: Vec<String> = (0..2500).map(|i| format!("+1415{:07}", i)).collect();
+        for (i, phone) in phones.iter().enumerate() {
+            let handle = insert_handle(&conn, phone);
+            insert_message(&conn, chat, handle, Some(phone), 1_000 + i as i64, false, true);
+        }
+
+        assert_eq!(queries::in_params("m.handle_id", 3, 2), "m.handle_id IN (?3, ?4)");
+        assert_eq!(queries::in_params("m.handle_id", 3, 0), "0");
+
+        for count in [0, 1, 999, 2500] {
+            let wanted = &phones[..count];
+            // Formatting differences don't matter
+            let wanted_loose: Vec<String> = wanted.iter().map(|p| p.trim_start_matches("+1").to_string()).collect();
+            for handles in [wanted.to_vec(), wanted_loose] {
+                let messages = query_messages_for_handles(&conn, &handles, 0, 50).unwrap();
+                let expected: Vec<&str> = wanted.iter().rev().take(50).map(String::as_str).collect();
+                let got: Vec<&str> = messages.iter().map(|m| m.phone.as_str()).collect();
+                assert_eq!(got, expected, "{} handles", count);
+            }
+        }
+
+        // A limit past one chunk's worth still comes back merged, newest first
+        let all = query_messages_for_handles(&conn, &phones, 0, 2000).unwrap();
+        assert_eq!(all.len(), 2000);
+        assert!(all.windows(2).all(|w| w[0].date_cocoa > w[1].date_cocoa));
+        assert_eq!(all[1999].phone, phones[500]);
+
+        let mut calls = 0;
+        let rows = query_chunked(&phones, 2, |chunk| {
+            calls += 1;
+            assert!(chunk.len() + 2 <= queries::MAX_BOUND_PARAMS);
+            Ok(chunk.to_vec())
+        })
+        .unwrap();
+        assert_eq!((calls, rows.len()), (3, 2500));
+    }
+
+    #[test]
+    fn test_volume_series_buckets_at_local_midnight() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+        use chrono::TimeZone;
+
+        // UTC-7: local midnight Oct 15 is 07:00 UTC
+        let offset = -7 * 3600;
+        let tz = chrono::FixedOffset::east_opt(offset).unwrap();
+        let at = |d: u32, h: u32, m: u32, s: u32| {
+            queries::unix_to_cocoa(tz.with_ymd_and_hms(2026, 10, d, h, m, s).unwrap().timestamp())
+        };
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        insert_message(&conn, chat, alice, Some("late"), at(14, 23, 59, 59), true, true);
+        insert_message(&conn, chat, alice, Some("early"), at(15, 0, 0, 0), false, true);
+        insert_message(&conn, chat, alice, Some("later"), at(15, 6, 59, 0), false, true);
+        insert_message(&conn, chat, alice, Some("fri"), at(17, 8, 0, 0), true, true);
+
+        let series = query_volume_series_at(&conn, at(14, 12, 0, 0), at(17, 12, 0, 0), SeriesBucket::Daily, None, &|_| offset)
+            .unwrap();
+        let points: Vec<(&str, i64, i64)> =
+            series.iter().map(|b| (b.bucket_start.as_str(), b.sent, b.received)).collect();
+        assert_eq!(
+            points,
+            vec![
+                ("2026-10-14T00:00:00-07:00", 1, 0),
+                ("2026-10-15T00:00:00-07:00", 0, 2),
+                ("2026-10-16T00:00:00-07:00", 0, 0),
+                ("2026-10-17T00:00:00-07:00", 1, 0),
+            ]
+        );
+
+        // The same instants in UTC split differently: 06:59 and 07:00 UTC both fall on the 15th
+        let utc = query_volume_series_at(&conn, at(14, 12, 0, 0), at(15, 12, 0, 0), SeriesBucket::Daily, None, &|_| 0)
+            .unwrap();
+        assert_eq!(utc[0].bucket_start, "2026-10-14T00:00:00+00:00");
+        assert_eq!((utc[1].sent, utc[1].received), (1, 2));
+
+        let hourly = query_volume_series_at(&conn, at(14, 23, 0, 0), at(15, 0, 30, 0), SeriesBucket::Hourly, None, &|_| offset)
+            .unwrap();
+        assert_eq!(hourly.len(), 2);
+        assert_eq!((hourly[0].sent, hourly[1].received), (1, 1));
+
+        // Oct 12, 2026 is a Monday
+        let weekly = query_volume_series_at(&conn, at(14, 12, 0, 0), at(17, 12, 0, 0), SeriesBucket::Weekly, Some("4155550001"), &|_| offset)
+            .unwrap();
+        assert_eq!(weekly, vec![VolumeBucket {
+            bucket_start: "2026-10-12T00:00:00-07:00".to_string(),
+            sent: 2,
+            received: 2,
+        }]);
+        let nobody = query_volume_series_at(&conn, at(14, 12, 0, 0), at(17, 12, 0, 0), SeriesBucket::Daily, Some("999"), &|_| offset)
+            .unwrap();
+        assert_eq!(nobody.len(), 4);
+        assert!(nobody.iter().all(|b| b.sent + b.received == 0));
+    }
+
+    #[test]
+    fn test_volume_series_follows_dst_changes() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+        // US Pacific: PDT (UTC-7) until 2026-11-01 09:00 UTC, then PST (UTC-8)
+        let transition = chrono::NaiveDate::from_ymd_opt(2026, 11, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let transition = transition.and_utc().timestamp();
+        let pacific = move |unix: i64| if unix < transition { -7 * 3600 } else { -8 * 3600 };
+        let utc = |d: u32, h: u32, m: u32| {
+            let at = chrono::NaiveDate::from_ymd_opt(2026, 11, d).unwrap().and_hms_opt(h, m, 0).unwrap();
+            queries::unix_to_cocoa(at.and_utc().timestamp())
+        };
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        // 23:30 PST on Nov 1 (07:30 UTC Nov 2): 00:30 the next day at the PDT offset
+        insert_message(&conn, chat, alice, Some("late"), utc(2, 7, 30), true, true);
+        // 00:30 PST on Nov 2
+        insert_message(&conn, chat, alice, Some("early"), utc(2, 8, 30), false, true);
+
+        let series = query_volume_series_at(&conn, utc(1, 12, 0), utc(2, 12, 0), SeriesBucket::Daily, None, &pacific)
+            .unwrap();
+        let points: Vec<(&str, i64, i64)> =
+            series.iter().map(|b| (b.bucket_start.as_str(), b.sent, b.received)).collect();
+        assert_eq!(
+            points,
+            vec![("2026-11-01T00:00:00-07:00", 1, 0), ("2026-11-02T00:00:00-08:00", 0, 1)]
+        );
+
+        // A single offset for the range puts both on Nov 2
+        let fixed = query_volume_series_at(&conn, utc(1, 12, 0), utc(2, 12, 0), SeriesBucket::Daily, None, &|_| -7 * 3600)
+            .unwrap();
+        assert_eq!((fixed[1].sent, fixed[1].received), (1, 1));
+    }
+
+    #[test]
+    fn test_chat_participants_sorted_by_handle() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle};
+
+        let conn = empty_db();
+        let carol = insert_handle(&conn, "+14155550003");
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let group = insert_chat(&conn, "chat1", Some("Group"), &[carol, bob, alice]);
+        let other = insert_chat(&conn, "+14155550002", None, &[bob]);
+
+        let participants = query_chat_participants(&conn, &[group, other]).unwrap();
+        assert_eq!(participants[&group], ["+14155550001", "+14155550002", "+14155550003"]);
+        assert_eq!(participants[&other], ["+14155550002"]);
+    }
 }
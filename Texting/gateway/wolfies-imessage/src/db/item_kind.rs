@@ -0,0 +1,183 @@
+//! What a message row is: a message, or a system item.
+//!
+//! Besides messages, the message table holds system items (item_type != 0):
+//! group renames and membership changes, FaceTime calls, group photo and
+//! location-sharing events. Some item_type 0 rows are app messages
+//! (balloon_bundle_id): Apple Cash payments, stickers, handwriting. None of
+//! them usually has text, so they rendered as "[message content not
+//! available]". `ItemCodes::kind` classifies a row and `placeholder`
+//! describes it ("[renamed the group to Ski Trip]", "[FaceTime call]"); like
+//! attachment placeholders, it's only shown when the row has no text.
+//! chat.db doesn't record call lengths or payment amounts.
+//!
+//! "System" means item_type != 0 (`sql_exclude_system`): payments and
+//! stickers were sent by someone, so filters leaving out system items keep
+//! them.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial ItemKind classification and placeholders
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// balloon_bundle_id suffix of Apple Cash messages.
+const PAYMENT_EXTENSION: &str = "PeerPaymentMessagesExtension";
+
+/// balloon_bundle_id of handwritten messages.
+const HANDWRITING: &str = "com.apple.Handwriting.HandwritingProvider";
+
+/// associated_message_type of a sticker placed on another message.
+const STICKER_ASSOCIATION: i64 = 1000;
+
+/// Kind of a message row (`item_kind` in outputs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    #[default]
+    Message,
+    GroupRename,
+    MemberChange,
+    Facetime,
+    Payment,
+    Sticker,
+    /// A system item we don't describe in detail (group photo, location sharing, ...)
+    Unknown,
+}
+
+/// From the CLI's `--include-system` / `--exclude-system` (clap keeps them
+/// exclusive), else the command's `default`.
+pub fn include_system_from_flags(include: bool, exclude: bool, default: bool) -> bool {
+    match (include, exclude) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => default,
+    }
+}
+
+/// Condition keeping only the non-system rows of message table `alias`.
+pub fn sql_exclude_system(alias: &str) -> String {
+    format!("COALESCE({}.item_type, 0) = 0", alias)
+}
+
+/// The columns that decide a row's kind and placeholder.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemCodes {
+    pub item_type: i64,
+    pub group_action_type: i64,
+    pub associated_message_type: i64,
+    pub balloon_bundle_id: Option<String>,
+    /// New name, for renames
+    pub group_title: Option<String>,
+    /// Handle added or removed, for membership changes
+    pub other_handle: Option<String>,
+}
+
+impl ItemCodes {
+    pub fn kind(&self) -> ItemKind {
+        let balloon = self.balloon_bundle_id.as_deref().unwrap_or_default();
+        match (self.item_type, self.group_action_type) {
+            (0, _) if balloon.ends_with(PAYMENT_EXTENSION) => ItemKind::Payment,
+            (0, _) if self.associated_message_type == STICKER_ASSOCIATION => ItemKind::Sticker,
+            (0, _) if balloon.to_ascii_lowercase().contains("sticker") => ItemKind::Sticker,
+            (0, _) => ItemKind::Message,
+            (1, 0 | 1) | (3, 0) => ItemKind::MemberChange,
+            (2, _) => ItemKind::GroupRename,
+            (6, _) => ItemKind::Facetime,
+            _ => ItemKind::Unknown,
+        }
+    }
+
+    /// What the row was, for rows without text; None for plain messages.
+    pub fn placeholder(&self) -> Option<String> {
+        let title = self.group_title.as_deref().filter(|t| !t.is_empty());
+        let who = self.other_handle.as_deref().filter(|h| !h.is_empty()).unwrap_or("someone");
+        let text = match self.kind() {
+            ItemKind::Message if self.balloon_bundle_id.as_deref() == Some(HANDWRITING) => {
+                "[handwritten message]".to_string()
+            }
+            ItemKind::Message => return None,
+            ItemKind::GroupRename => match title {
+                Some(title) => format!("[renamed the group to {}]", title),
+                None => "[renamed the group]".to_string(),
+            },
+            ItemKind::MemberChange => match (self.item_type, self.group_action_type) {
+                (1, 0) => format!("[added {} to the group]", who),
+                (1, _) => format!("[removed {} from the group]", who),
+                _ => "[left the group]".to_string(),
+            },
+            ItemKind::Facetime => "[FaceTime call]".to_string(),
+            ItemKind::Payment => "[Apple Cash payment]".to_string(),
+            ItemKind::Sticker => "[sticker]".to_string(),
+            ItemKind::Unknown => match (self.item_type, self.group_action_type) {
+                (3, 1) => "[changed the group photo]".to_string(),
+                (3, 2) => "[removed the group photo]".to_string(),
+                (4, _) => "[location sharing update]".to_string(),
+                _ => "[system event]".to_string(),
+            },
+        };
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYMENT: &str =
+        "com.apple.messages.MSMessageExtensionBalloonPlugin:0000000000:com.apple.PassbookUIService.PeerPaymentMessagesExtension";
+
+    /// (item_type, group_action_type, associated_message_type, balloon_bundle_id,
+    /// expected kind, expected placeholder)
+    type Case = (i64, i64, i64, Option<&'static str>, ItemKind, Option<&'static str>);
+
+    #[test]
+    fn test_classification_table() {
+        let cases: &[Case] = &[
+            (0, 0, 0, None, ItemKind::Message, None),
+            (0, 0, 2000, None, ItemKind::Message, None),
+            (0, 0, 0, Some("com.apple.messages.URLBalloonProvider"), ItemKind::Message, None),
+            (0, 0, 0, Some(HANDWRITING), ItemKind::Message, Some("[handwritten message]")),
+            (0, 0, 0, Some(PAYMENT), ItemKind::Payment, Some("[Apple Cash payment]")),
+            (0, 0, 1000, None, ItemKind::Sticker, Some("[sticker]")),
+            (
+                0,
+                0,
+                0,
+                Some("com.apple.messages.MSMessageExtensionBalloonPlugin:0000000000:com.apple.Stickers.UserGenerated.MessagesExtension"),
+                ItemKind::Sticker,
+                Some("[sticker]"),
+            ),
+            (1, 0, 0, None, ItemKind::MemberChange, Some("[added +14155550002 to the group]")),
+            (1, 1, 0, None, ItemKind::MemberChange, Some("[removed +14155550002 from the group]")),
+            (2, 0, 0, None, ItemKind::GroupRename, Some("[renamed the group to Ski Trip]")),
+            (3, 0, 0, None, ItemKind::MemberChange, Some("[left the group]")),
+            (3, 1, 0, None, ItemKind::Unknown, Some("[changed the group photo]")),
+            (3, 2, 0, None, ItemKind::Unknown, Some("[removed the group photo]")),
+            (4, 0, 0, None, ItemKind::Unknown, Some("[location sharing update]")),
+            (6, 0, 0, None, ItemKind::Facetime, Some("[FaceTime call]")),
+            (1, 5, 0, None, ItemKind::Unknown, Some("[system event]")),
+            (9, 0, 0, None, ItemKind::Unknown, Some("[system event]")),
+        ];
+        for &(item_type, group_action_type, associated_message_type, balloon, kind, placeholder) in cases {
+            let codes = ItemCodes {
+                item_type,
+                group_action_type,
+                associated_message_type,
+                balloon_bundle_id: balloon.map(str::to_string),
+                group_title: Some("Ski Trip".to_string()),
+                other_handle: Some("+14155550002".to_string()),
+            };
+            let case = (item_type, group_action_type, associated_message_type, balloon);
+            assert_eq!(codes.kind(), kind, "{:?}", case);
+            assert_eq!(codes.placeholder().as_deref(), placeholder, "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn test_placeholders_without_details() {
+        let rename = ItemCodes { item_type: 2, ..Default::default() };
+        assert_eq!(rename.placeholder().as_deref(), Some("[renamed the group]"));
+        let added = ItemCodes { item_type: 1, other_handle: Some(String::new()), ..Default::default() };
+        assert_eq!(added.placeholder().as_deref(), Some("[added someone to the group]"));
+    }
+}
@@ -0,0 +1,141 @@
+//! Message text with attachment placeholders.
+//!
+//! A photo sent on its own has no text (or just U+FFFC, the object
+//! replacement character), so it used to render like a message whose blob
+//! failed to parse. Queries that list messages select an attachment hint
+//! column, "<mime_type>\t<transfer_name>" of the first attachment ('' when
+//! only cache_has_attachments is set, NULL without attachments), and
+//! `message_body` turns an empty message with a hint into a placeholder such
+//! as "[photo]" or "[file: lease.pdf]". The text stays empty; outputs carry
+//! the placeholder in their own `placeholder` field.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - with_item_placeholder (system items and app messages without text)
+//! - 10/16/2026 - Initial attachment-only placeholders
+
+use crate::db::text_cache;
+
+/// Shown when a message has neither text nor attachments we can describe.
+pub const UNAVAILABLE: &str = "[message content not available]";
+
+/// Extracted text, or a placeholder for an attachment-only message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageBody {
+    pub text: Option<String>,
+    pub placeholder: Option<String>,
+}
+
+impl MessageBody {
+    /// Text for outputs with a required string: empty alongside a
+    /// placeholder, `UNAVAILABLE` when there is neither.
+    pub fn into_parts(self) -> (String, Option<String>) {
+        match (self.text, self.placeholder) {
+            (Some(text), placeholder) => (text, placeholder),
+            (None, Some(placeholder)) => (String::new(), Some(placeholder)),
+            (None, None) => (UNAVAILABLE.to_string(), None),
+        }
+    }
+
+    /// Use `item_placeholder` (a system item or app message, see `item_kind`)
+    /// when there's no text; it describes a sticker better than "[photo]".
+    pub fn with_item_placeholder(self, item_placeholder: Option<String>) -> Self {
+        match (&self.text, item_placeholder) {
+            (None, Some(placeholder)) => MessageBody { text: None, placeholder: Some(placeholder) },
+            _ => self,
+        }
+    }
+}
+
+/// Text of a message row (text column, else attributedBody through the text
+/// cache), with a placeholder when it has none but `attachment_hint` is set.
+pub fn message_body(
+    guid: Option<&str>,
+    text_col: Option<String>,
+    blob: Option<&[u8]>,
+    attachment_hint: Option<&str>,
+) -> MessageBody {
+    let text = text_cache::message_text(guid, text_col, blob).filter(|t| !is_blank(t));
+    let placeholder = match (&text, attachment_hint) {
+        (None, Some(hint)) => Some(attachment_placeholder(hint)),
+        _ => None,
+    };
+    MessageBody { text, placeholder }
+}
+
+/// `text`, or the placeholder when the text is empty (for previews).
+pub fn display_text<'a>(text: &'a str, placeholder: Option<&'a str>) -> &'a str {
+    match placeholder {
+        Some(p) if text.is_empty() => p,
+        _ => text,
+    }
+}
+
+/// Whitespace and object replacement characters only.
+fn is_blank(text: &str) -> bool {
+    text.chars().all(|c| c.is_whitespace() || c == '\u{FFFC}')
+}
+
+/// Placeholder for an attachment hint ("<mime_type>\t<transfer_name>").
+///
+/// The mime type decides the kind; without one, the file extension does.
+pub fn attachment_placeholder(hint: &str) -> String {
+    let (mime, name) = hint.split_once('\t').unwrap_or((hint, ""));
+    let (mime, name) = (mime.trim().to_ascii_lowercase(), name.trim());
+    let kind = match mime.split_once('/') {
+        Some((major, _)) => major.to_string(),
+        None => kind_from_extension(name).to_string(),
+    };
+    match kind.as_str() {
+        "image" => "[photo]".to_string(),
+        "video" => "[video]".to_string(),
+        "audio" => "[audio]".to_string(),
+        _ if !name.is_empty() => format!("[file: {}]", name),
+        _ => "[attachment]".to_string(),
+    }
+}
+
+fn kind_from_extension(name: &str) -> &'static str {
+    let ext = name.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "heic" | "heif" | "webp" | "tiff" => "image",
+        "mov" | "mp4" | "m4v" => "video",
+        "caf" | "m4a" | "mp3" | "wav" | "aac" | "amr" => "audio",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_placeholders() {
+        assert_eq!(attachment_placeholder("image/heic\tIMG_0001.HEIC"), "[photo]");
+        assert_eq!(attachment_placeholder("video/quicktime\tclip.mov"), "[video]");
+        assert_eq!(attachment_placeholder("audio/x-caf\tAudio Message.caf"), "[audio]");
+        assert_eq!(attachment_placeholder("application/pdf\tlease.pdf"), "[file: lease.pdf]");
+        // No mime type: fall back to the extension
+        assert_eq!(attachment_placeholder("\tIMG_0002.JPG"), "[photo]");
+        assert_eq!(attachment_placeholder("\tnotes.txt"), "[file: notes.txt]");
+        // Only cache_has_attachments was set
+        assert_eq!(attachment_placeholder(""), "[attachment]");
+    }
+
+    #[test]
+    fn test_message_body() {
+        // Text wins; the hint is ignored
+        let body = message_body(None, Some("look".to_string()), None, Some("image/png\ta.png"));
+        assert_eq!(body.into_parts(), ("look".to_string(), None));
+
+        // U+FFFC alone is attachment-only
+        let body = message_body(None, Some("\u{FFFC}".to_string()), None, Some("image/png\ta.png"));
+        assert_eq!(body.text, None);
+        assert_eq!(body.into_parts(), (String::new(), Some("[photo]".to_string())));
+
+        // Nothing to show and no attachment
+        assert_eq!(message_body(None, None, None, None).into_parts(), (UNAVAILABLE.to_string(), None));
+
+        assert_eq!(display_text("", Some("[photo]")), "[photo]");
+        assert_eq!(display_text("hi", None), "hi");
+    }
+}
@@ -1,10 +1,23 @@
 //! Database module for SQLite access to Messages.db.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Added item_kind (system items: renames, membership changes, FaceTime, payments, stickers)
+//! - 10/16/2026 - Added cursor (keyset pagination)
+//! - 10/16/2026 - Added message_body (attachment-only placeholders)
+//! - 10/16/2026 - Added text_cache (cached blob text by message guid)
+//! - 10/16/2026 - Added typedstream decoder (attributedBody runs)
+//! - 10/16/2026 - Added test-only fixtures module
 //! - 01/10/2026 - Added helpers module for shared query functions (Phase 5) (Claude)
 //! - 01/10/2026 - Initial module structure (Claude)
 
 pub mod blob_parser;
 pub mod connection;
+pub mod cursor;
+#[cfg(test)]
+pub mod fixtures;
 pub mod helpers;
+pub mod item_kind;
+pub mod message_body;
 pub mod queries;
+pub mod text_cache;
+pub mod typedstream;
@@ -1,8 +1,163 @@
 //! SQL queries for Messages.db.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - SQL date arithmetic (busiest hour/day, VOLUME_SERIES, HEATMAP) goes through date_expr!, which also reads second-precision dates; DAY_NS moved here
+//! - 10/16/2026 - Restored MAX_BOUND_PARAMS and in_params (bound IN lists for handle sets, chunked by helpers::query_chunked)
+//! - 10/16/2026 - Added CHAT_BY_IDENTIFIER (ROWID and guid, for mark-read)
+//! - 10/16/2026 - MESSAGE_ROWS reads its rows from a message_page CTE of ROWIDs, so per-row subqueries run after the LIMIT; MESSAGE_SERVICE replaced by message_service_is (uncorrelated chat fallback)
+//! - 10/16/2026 - CHAT_PARTICIPANTS orders by chat and handle id (stable participant lists)
+//! - 10/16/2026 - HANDLE_STATS takes its unread condition from helpers::unread_conditions ({unread})
+//! - 10/16/2026 - VOLUME_SERIES counts per 15-minute UTC slot (local buckets are assigned per slot, with the offset in effect then)
+//! - 10/16/2026 - The attachment_hint column comes from one attachment_hint! fragment
+//! - 10/16/2026 - COMPARE_MESSAGES selects attachment_hint (from the source schema)
+//! - 10/16/2026 - DELETED_MESSAGES selects attachment_hint (placeholders for attachment-only deleted messages)
+//! - 10/16/2026 - Dropped MAX_BOUND_PARAMS and in_params (ROWID sets are inlined, helpers::rowids_condition)
+//! - 10/16/2026 - Text search SQL takes an OFFSET (callers page through candidates) and breaks date ties by ROWID
+//! - 10/16/2026 - Added CONVERSATION_LIFETIME (unbounded count and first message date; analytics --streaks)
+//! - 10/16/2026 - Added RAW_MESSAGE_BY_ROWID, RAW_MESSAGE_BY_GUID, RAW_MESSAGE_CHATS, RAW_MESSAGE_ATTACHMENTS, RAW_MESSAGE_REACTIONS (raw-message)
+//! - 10/16/2026 - UNREAD_MESSAGES and UNREAD_COUNT leave the unread condition to helpers::unread_conditions; added UNREAD_MESSAGE_SINCE_LAST_READ, UNREAD_MESSAGE_FLAGS
+//! - 10/16/2026 - Added ANALYTICS_MESSAGE_COUNTS_RANGE, ANALYTICS_MESSAGE_COUNTS_PHONE_RANGE, ANALYTICS_TOP_CONTACTS_RANGE (date < upper bound)
+//! - 10/16/2026 - Added ANALYTICS_COMBINED_RANGE, ANALYTICS_COMBINED_PHONE_RANGE, ANALYTICS_COMBINED_HANDLES_RANGE (upper-bounded windows for analytics --compare)
+//! - 10/16/2026 - Added MESSAGE_REACTIONS (tapbacks on a set of message guids)
+//! - 10/16/2026 - Text search selects item_type, group_action_type and balloon_bundle_id (item_kind); follow-up queries take an include-system parameter
+//! - 10/16/2026 - MESSAGE_ROWS selects the item columns (item_type, group_action_type, associated_message_type, balloon_bundle_id, group_title, other handle)
+//! - 10/16/2026 - MESSAGE_ROWS selects the service (message.service, else the chat's service_name); added MESSAGE_SERVICE
+//! - 10/16/2026 - cocoa_to_unix detects seconds vs nanoseconds; added CocoaDate / decode_cocoa (flags zero, pre-2001 and future dates); days_ago_cocoa no longer panics on a pre-1970 clock
+//! - 10/16/2026 - Added UNREAD_BY_CHAT, UNREAD_SINCE_LAST_READ, UNREAD_FLAGS (unread counts per conversation)
+//! - 10/16/2026 - Added MAX_BOUND_PARAMS and in_params (bound IN lists)
+//! - 10/16/2026 - Added HEATMAP (sent/received per local weekday and hour)
+//! - 10/16/2026 - Added CHATS_WITH_HANDLES and CHAT_INFO (chat-info)
+//! - 10/16/2026 - SELF_CHAT_CANDIDATES also lists one-to-one chats for quick (helpers::query_direct_chats)
+//! - 10/16/2026 - SUMMARY_MESSAGES orders by (date, ROWID) for keyset cursors
+//! - 10/16/2026 - Added DATE_BOUNDED_QUERIES, message_scans, rowid_range_applies (doctor --performance)
+//! - 10/16/2026 - FOLLOWUP_UNANSWERED_QUESTIONS returns the chat, takes a limit, and counts replies in the same chat
+//! - 10/16/2026 - Message listings select an attachment hint (first attachment mime/transfer_name); RECENT_MESSAGES keeps attachment-only rows
+//! - 10/16/2026 - Removed ACCOUNT_LOGINS (helpers::query_chat_accounts builds its query per schema)
+//! - 10/16/2026 - Added UNREAD_COUNT (muted unread count)
+//! - 10/16/2026 - GROUP_PARTICIPANTS (per chat) replaced by CHAT_PARTICIPANTS (filtered by chat id set)
+//! - 10/16/2026 - Removed per-stat ANALYTICS_BUSIEST_*, ANALYTICS_ATTACHMENTS*, ANALYTICS_REACTIONS* queries
+//! - 10/16/2026 - Added MESSAGE_ROWS (api message listings)
+//! - 10/16/2026 - Added COMPARE_CONVERSATIONS and COMPARE_MESSAGES (guid set difference)
+//! - 10/16/2026 - Added DELETED_MESSAGES and SUMMARY_MESSAGES
+//! - 10/16/2026 - Added GROUP_EVENTS (participant and rename system messages)
+//! - 10/16/2026 - Added HANDLE_STATS (grouped per-handle counts for contacts --stats)
+//! - 10/16/2026 - RECENT_MESSAGES includes blob-only messages (selects m.attributedBody)
+//! - 10/16/2026 - Added group_text_search_terms_sql (text-search --group)
+//! - 10/16/2026 - MESSAGE_ROWS selects a chat hint (identifier and display name)
+//! - 10/16/2026 - Added CONVERSATION_TIMELINE (silence gaps)
+//! - 10/16/2026 - Added CONVERSATION_TAPBACKS (emoji stats)
+//! - 10/16/2026 - Added CONVERSATION_TEXTS (top terms)
+//! - 10/16/2026 - Added VOLUME_SERIES (sent/received per time bucket)
+//! - 10/16/2026 - RECENT_MESSAGES selects m.ROWID and m.guid
+//! - 10/16/2026 - Top-contact and handle queries drop LIMIT (applied after variant merging)
+//! - 10/16/2026 - Added ACCOUNT_LOGINS and SELF_CHAT_CANDIDATES (notes to self)
+//! - 10/16/2026 - Added MESSAGE_CONTEXT; text search selects m.ROWID (ask local fallback)
+//! - 10/16/2026 - Blob-reading queries also select m.guid (text cache key)
+//! - 10/16/2026 - Added HANDLE_CHAT_NAMES and HANDLE_RECENT_TEXTS (discover name hints)
+//! - 10/16/2026 - Added DISCOVERY_HANDLES_WITH_PERSON (person_centric_id)
+//! - 10/16/2026 - User-supplied LIKE parameters are pre-escaped (LIKE ? ESCAPE '\')
+//! - 10/16/2026 - Added escape_like and the multi-term text search builder
+//! - 10/16/2026 - Added per-chat GROUP_* analytics queries
+//! - 10/16/2026 - Added with_filter clause injection and ANALYTICS_COMBINED_HANDLES
+//! - 10/16/2026 - Added bounded-range count queries and unix_to_cocoa (digest)
+//! - 10/16/2026 - Added MAX_MESSAGE_ROWID and INCOMING_SINCE (watch)
+//! - 10/16/2026 - Added UNREAD_CHATS (unread counts per conversation)
+//! - 10/16/2026 - Added THREAD_ORIGINATOR and THREAD_TREE (full reply tree)
+//! - 10/16/2026 - RECENT_CONVERSATIONS groups by chat (latest message per conversation)
+//! - 10/16/2026 - Reaction counts grouped by type; removal events (3000+) excluded
 //! - 01/10/2026 - Initial stub with query constants (Claude)
 
+use crate::db::connection::PlanRow;
+
+/// `attachment_hint` column: the first attachment of message `$m` as
+/// "mime_type<TAB>transfer_name", '' when only cache_has_attachments is set,
+/// NULL without attachments (decoded by db::message_body). The optional
+/// second argument prefixes the attachment tables with a schema ("{a}.").
+macro_rules! attachment_hint {
+    ($m:literal) => {
+        attachment_hint!($m, "")
+    };
+    ($m:literal, $schema:literal) => {
+        concat!(
+            "COALESCE(
+        (SELECT COALESCE(a.mime_type, '') || char(9) || COALESCE(a.transfer_name, '')
+         FROM ", $schema, "message_attachment_join maj JOIN ", $schema, "attachment a ON a.ROWID = maj.attachment_id
+         WHERE maj.message_id = ", $m, ".ROWID ORDER BY a.ROWID LIMIT 1),
+        CASE WHEN ", $m, ".cache_has_attachments = 1 THEN '' END
+    ) AS attachment_hint"
+        )
+    };
+}
+
+/// Unix seconds of the Cocoa date column `$col`: the SQL form of
+/// `cocoa_to_unix` (seconds below `COCOA_SECONDS_BELOW`, else nanoseconds,
+/// plus `COCOA_EPOCH_OFFSET`). Date arithmetic in SQL goes through here;
+/// comparisons and ordering still use the raw column.
+macro_rules! date_expr {
+    ($col:literal) => {
+        concat!(
+            "(CASE WHEN abs(", $col, ") < 1000000000000 THEN ", $col, " ELSE ", $col, " / 1000000000 END + 978307200)"
+        )
+    };
+}
+
+/// Hour of day (UTC, 0-23) of the Cocoa date column `$col`.
+macro_rules! utc_hour {
+    ($col:literal) => {
+        concat!("CAST((", date_expr!($col), " / 3600) % 24 AS INTEGER)")
+    };
+}
+
+/// Day of week (UTC, 0 = Sunday) of the Cocoa date column `$col`; 1970-01-01 was a Thursday.
+macro_rules! utc_weekday {
+    ($col:literal) => {
+        concat!("CAST((", date_expr!($col), " / 86400 + 4) % 7 AS INTEGER)")
+    };
+}
+
+/// Look up a message's thread originator (NULL when it isn't a reply).
+/// Parameters: ?1 = message guid
+pub const THREAD_ORIGINATOR: &str = r#"
+SELECT thread_originator_guid
+FROM message
+WHERE guid = ?1
+"#;
+
+/// Query a whole reply tree rooted at a message, with each reply's depth.
+///
+/// Walks thread_originator_guid downward recursively so nested chains are
+/// included, not just direct replies.
+/// Parameters: ?1 = root guid, ?2 = limit
+pub const THREAD_TREE: &str = concat!(
+    r#"
+WITH RECURSIVE tree(guid, depth) AS (
+    SELECT guid, 0 FROM message WHERE guid = ?1
+    UNION
+    SELECT m.guid, t.depth + 1
+    FROM message m
+    JOIN tree t ON m.thread_originator_guid = t.guid
+    WHERE t.depth < 32
+)
+SELECT
+    m.guid,
+    m.text,
+    m.attributedBody,
+    m.date,
+    m.is_from_me,
+    h.id,
+    m.thread_originator_guid,
+    t.depth,
+    "#,
+    attachment_hint!("m"),
+    r#"
+FROM tree t
+JOIN message m ON m.guid = t.guid
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+ORDER BY m.date ASC
+LIMIT ?2
+"#
+);
+
 /// Query to get recent messages from a specific phone number.
 pub const MESSAGES_BY_PHONE: &str = r#"
 SELECT
@@ -25,27 +180,56 @@ ORDER BY m.date DESC
 LIMIT ?2
 "#;
 
-/// Query to get recent conversations.
-pub const RECENT_CONVERSATIONS: &str = r#"
+/// Query to get recent conversations (latest message per chat).
+/// Ranks messages within each chat via chat_message_join; the limit applies to
+/// conversations, not messages. Reactions are skipped so a tapback doesn't
+/// become a chat's "last message".
+/// Parameters: ?1 = limit
+pub const RECENT_CONVERSATIONS: &str = concat!(
+    r#"
+WITH ranked AS (
+    SELECT
+        cmj.chat_id,
+        m.ROWID AS message_rowid,
+        ROW_NUMBER() OVER (
+            PARTITION BY cmj.chat_id ORDER BY m.date DESC, m.ROWID DESC
+        ) AS rn
+    FROM chat_message_join cmj
+    JOIN message m ON m.ROWID = cmj.message_id
+    WHERE (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+)
 SELECT
-    h.id as handle_id,
-    MAX(m.date) as last_date,
+    c.ROWID,
+    c.chat_identifier,
+    c.display_name,
     m.text,
     m.attributedBody,
+    m.date AS last_date,
     m.is_from_me,
-    c.chat_identifier,
-    c.display_name
-FROM message m
+    h.id AS handle_id,
+    (SELECT COUNT(*) FROM message um
+     JOIN chat_message_join ucmj ON um.ROWID = ucmj.message_id
+     WHERE ucmj.chat_id = c.ROWID
+       AND um.is_from_me = 0
+       AND um.date_read = 0
+       AND um.is_read = 0) AS unread_count,
+    m.guid,
+    "#,
+    attachment_hint!("m"),
+    r#"
+FROM ranked r
+JOIN chat c ON c.ROWID = r.chat_id
+JOIN message m ON m.ROWID = r.message_rowid
 LEFT JOIN handle h ON m.handle_id = h.ROWID
-LEFT JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
-LEFT JOIN chat c ON cmj.chat_id = c.ROWID
-GROUP BY h.id
-ORDER BY last_date DESC
+WHERE r.rn = 1
+ORDER BY m.date DESC
 LIMIT ?1
-"#;
+"#
+);
 
-/// Query to get unread messages.
-pub const UNREAD_MESSAGES: &str = r#"
+/// Query to get unread messages (`helpers::unread_conditions` adds the WHERE clause).
+pub const UNREAD_MESSAGES: &str = concat!(
+    r#"
 SELECT
     m.ROWID,
     m.guid,
@@ -55,36 +239,205 @@ SELECT
     m.date,
     h.id as handle_id,
     c.chat_identifier,
-    c.display_name
+    c.display_name,
+    "#,
+    attachment_hint!("m"),
+    r#"
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 LEFT JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
 LEFT JOIN chat c ON cmj.chat_id = c.ROWID
+ORDER BY m.date DESC
+LIMIT ?1
+"#
+);
+
+/// Count messages (`helpers::unread_conditions` adds the WHERE clause).
+/// Parameters: none
+pub const UNREAD_COUNT: &str = r#"
+SELECT COUNT(*)
+FROM message m
+"#;
+
+/// Query conversations with unread messages, most recently active first.
+/// Parameters: none
+pub const UNREAD_CHATS: &str = r#"
+SELECT
+    c.chat_identifier,
+    COUNT(*) AS unread_count
+FROM message m
+JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+JOIN chat c ON cmj.chat_id = c.ROWID
 WHERE m.is_from_me = 0
   AND m.date_read = 0
   AND m.is_read = 0
-ORDER BY m.date DESC
-LIMIT ?1
+  AND c.chat_identifier IS NOT NULL
+GROUP BY c.chat_identifier
+ORDER BY MAX(m.date) DESC
 "#;
 
+/// A conversation's ROWID and guid (Messages' chat id); the newest chat when
+/// several share the identifier (e.g. an SMS and an iMessage chat).
+/// Parameters: ?1 = chat_identifier
+pub const CHAT_BY_IDENTIFIER: &str = r#"
+SELECT ROWID, guid
+FROM chat
+WHERE chat_identifier = ?1
+ORDER BY ROWID DESC
+LIMIT 1
+"#;
+
+/// Incoming messages per conversation with the oldest one's date, most
+/// recently active first; `helpers::query_unread_by_chat` adds the unread
+/// condition for the schema (`UNREAD_SINCE_LAST_READ` or `UNREAD_FLAGS`).
+/// Parameters: none
+pub const UNREAD_BY_CHAT: &str = r#"
+SELECT
+    c.chat_identifier,
+    c.display_name,
+    COUNT(*) AS unread_count,
+    MIN(m.date) AS oldest_unread
+FROM message m
+JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+JOIN chat c ON cmj.chat_id = c.ROWID
+WHERE m.is_from_me = 0
+  AND c.chat_identifier IS NOT NULL
+GROUP BY c.ROWID
+ORDER BY MAX(m.date) DESC
+"#;
+
+/// Unread: after the chat's read position, or by the message's own flags in
+/// chats that never recorded one.
+pub const UNREAD_SINCE_LAST_READ: &str = "CASE WHEN COALESCE(c.last_read_message_timestamp, 0) > 0 \
+THEN m.date > c.last_read_message_timestamp ELSE m.date_read = 0 AND m.is_read = 0 END";
+
+/// Unread by the message's own flags (schemas without a chat read position).
+pub const UNREAD_FLAGS: &str = "m.date_read = 0 AND m.is_read = 0";
+
+/// `UNREAD_SINCE_LAST_READ` for one message row, without joining its chat:
+/// incoming and past its chat's read position, or unread by its own flags
+/// when the chat never recorded one. `{m}` is the message table alias.
+pub const UNREAD_MESSAGE_SINCE_LAST_READ: &str = "{m}.is_from_me = 0 AND COALESCE(\
+(SELECT {m}.date > lr.last_read_message_timestamp FROM chat_message_join lrj \
+JOIN chat lr ON lr.ROWID = lrj.chat_id \
+WHERE lrj.message_id = {m}.ROWID AND lr.last_read_message_timestamp > 0 LIMIT 1), \
+{m}.date_read = 0 AND {m}.is_read = 0)";
+
+/// Incoming and unread by its own flags (schemas without a chat read
+/// position). `{m}` is the message table alias.
+pub const UNREAD_MESSAGE_FLAGS: &str = "{m}.is_from_me = 0 AND {m}.date_read = 0 AND {m}.is_read = 0";
+
+/// Highest message ROWID (watch starting point).
+pub const MAX_MESSAGE_ROWID: &str = "SELECT COALESCE(MAX(ROWID), 0) FROM message";
+
+/// Incoming (non-reaction) messages newer than a ROWID, oldest first.
+/// Parameters: ?1 = last seen ROWID, ?2 = limit
+pub const INCOMING_SINCE: &str = concat!(
+    r#"
+SELECT
+    m.ROWID,
+    m.text,
+    m.attributedBody,
+    m.date,
+    h.id AS handle_id,
+    c.chat_identifier,
+    c.display_name,
+    m.guid,
+    "#,
+    attachment_hint!("m"),
+    r#"
+FROM message m
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+LEFT JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+LEFT JOIN chat c ON cmj.chat_id = c.ROWID
+WHERE m.ROWID > ?1
+  AND m.is_from_me = 0
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+ORDER BY m.ROWID ASC
+LIMIT ?2
+"#
+);
+
+/// Message listing columns for the ROWIDs in `message_page`, a CTE `api`
+/// builds with the WHERE / ORDER BY / LIMIT. The per-row subqueries (hints,
+/// service, other handle) so only run for the rows that make the page.
+/// Columns: text, attributedBody, date, is_from_me, handle id, cache_roomnames,
+/// subject, guid, ROWID, attachment hint, chat hint (chat_identifier TAB
+/// display_name of the message's chat), service, then the `ItemCodes`
+/// columns: item_type, group_action_type, associated_message_type,
+/// balloon_bundle_id, group_title, other handle id
+pub const MESSAGE_ROWS: &str = concat!(
+    r#"
+SELECT
+    message.text,
+    message.attributedBody,
+    message.date,
+    message.is_from_me,
+    handle.id,
+    message.cache_roomnames,
+    message.subject,
+    message.guid,
+    message.ROWID,
+    "#,
+    attachment_hint!("message"),
+    r#",
+    (SELECT COALESCE(c.chat_identifier, '') || char(9) || COALESCE(c.display_name, '')
+     FROM chat_message_join cmj JOIN chat c ON c.ROWID = cmj.chat_id
+     WHERE cmj.message_id = message.ROWID ORDER BY c.ROWID LIMIT 1) AS chat_hint,
+    COALESCE(NULLIF(message.service, ''),
+        (SELECT c.service_name FROM chat_message_join cmj JOIN chat c ON c.ROWID = cmj.chat_id
+         WHERE cmj.message_id = message.ROWID ORDER BY c.ROWID LIMIT 1)) AS service,
+    COALESCE(message.item_type, 0),
+    COALESCE(message.group_action_type, 0),
+    COALESCE(message.associated_message_type, 0),
+    message.balloon_bundle_id,
+    message.group_title,
+    (SELECT h2.id FROM handle h2 WHERE h2.ROWID = message.other_handle) AS other_handle
+FROM message_page
+JOIN message ON message.ROWID = message_page.rowid
+LEFT JOIN handle ON message.handle_id = handle.ROWID"#
+);
+
+/// Condition: a message's service (as in `MESSAGE_ROWS`' `service` column)
+/// is `service`, compared case-insensitively. The chat fallback is one
+/// uncorrelated lookup, only consulted for messages without a service of
+/// their own, and matches any of the message's chats.
+pub fn message_service_is(service: &str) -> String {
+    let service = service.replace('\'', "''");
+    format!(
+        "(LOWER(message.service) = LOWER('{service}') OR (COALESCE(message.service, '') = '' AND message.ROWID IN (
+    SELECT cmj.message_id FROM chat_message_join cmj JOIN chat c ON c.ROWID = cmj.chat_id
+    WHERE LOWER(c.service_name) = LOWER('{service}'))))"
+    )
+}
+
 /// Query to get recent messages.
 /// Parameters: ?1 = cutoff_cocoa, ?2 = limit
-pub const RECENT_MESSAGES: &str = r#"
+pub const RECENT_MESSAGES: &str = concat!(
+    r#"
 SELECT
     m.text,
     m.date,
     m.is_from_me,
-    h.id as handle
+    h.id as handle,
+    m.ROWID,
+    m.guid,
+    m.attributedBody,
+    "#,
+    attachment_hint!("m"),
+    r#"
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
   AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
-  AND m.text IS NOT NULL
+  AND (m.text IS NOT NULL OR m.attributedBody IS NOT NULL OR m.cache_has_attachments = 1)
 ORDER BY m.date DESC
 LIMIT ?2
-"#;
+"#
+);
 
 /// Query to search messages by text.
+/// Parameters: ?1 = like_contains(query), ?2 = limit
 pub const TEXT_SEARCH: &str = r#"
 SELECT
     m.ROWID,
@@ -99,7 +452,7 @@ FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 LEFT JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
 LEFT JOIN chat c ON cmj.chat_id = c.ROWID
-WHERE m.text LIKE '%' || ?1 || '%'
+WHERE m.text LIKE ?1 ESCAPE '\'
 ORDER BY m.date DESC
 LIMIT ?2
 "#;
@@ -122,16 +475,19 @@ ORDER BY last_date DESC
 LIMIT ?1
 "#;
 
-/// Query to get participants for a specific chat.
-pub const GROUP_PARTICIPANTS: &str = r#"
-SELECT h.id
-FROM handle h
-JOIN chat_handle_join chj ON h.ROWID = chj.handle_id
-WHERE chj.chat_id = ?1
+/// Participants of many chats at once: (chat ROWID, handle id), each chat's
+/// handles sorted by id. Callers add a `chj.chat_id IN (...)` condition with
+/// `with_filter`.
+pub const CHAT_PARTICIPANTS: &str = r#"
+SELECT chj.chat_id, h.id
+FROM chat_handle_join chj
+JOIN handle h ON h.ROWID = chj.handle_id
+ORDER BY chj.chat_id, h.id
 "#;
 
 /// Query to get messages from a group chat by chat_identifier.
-pub const GROUP_MESSAGES: &str = r#"
+pub const GROUP_MESSAGES: &str = concat!(
+    r#"
 SELECT
     m.ROWID,
     m.guid,
@@ -140,7 +496,10 @@ SELECT
     m.is_from_me,
     m.date,
     h.id as sender_handle,
-    c.display_name as group_name
+    c.display_name as group_name,
+    "#,
+    attachment_hint!("m"),
+    r#"
 FROM message m
 JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
 JOIN chat c ON cmj.chat_id = c.ROWID
@@ -148,10 +507,13 @@ LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE c.chat_identifier = ?1
 ORDER BY m.date DESC
 LIMIT ?2
-"#;
+"#
+);
 
 /// Query to get group messages filtered by participant.
-pub const GROUP_MESSAGES_BY_PARTICIPANT: &str = r#"
+/// Parameters: ?1 = like_contains(participant), ?2 = limit
+pub const GROUP_MESSAGES_BY_PARTICIPANT: &str = concat!(
+    r#"
 SELECT
     m.ROWID,
     m.guid,
@@ -161,15 +523,279 @@ SELECT
     m.date,
     h.id as sender_handle,
     c.display_name as group_name,
-    c.chat_identifier
+    c.chat_identifier,
+    "#,
+    attachment_hint!("m"),
+    r#"
 FROM message m
 JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
 JOIN chat c ON cmj.chat_id = c.ROWID
 LEFT JOIN handle h ON m.handle_id = h.ROWID
-WHERE h.id LIKE '%' || ?1 || '%'
+WHERE h.id LIKE ?1 ESCAPE '\'
   AND (c.chat_identifier LIKE 'chat%' OR c.display_name IS NOT NULL)
 ORDER BY m.date DESC
 LIMIT ?2
+"#
+);
+
+/// Resolve a group chat by identifier or display name (exact, then partial).
+/// Parameters: ?1 = chat_identifier or display name, ?2 = like_contains(?1)
+pub const GROUP_RESOLVE: &str = r#"
+SELECT c.ROWID, c.chat_identifier, c.display_name
+FROM chat c
+WHERE c.chat_identifier = ?1
+   OR c.display_name = ?1 COLLATE NOCASE
+   OR c.display_name LIKE ?2 ESCAPE '\'
+ORDER BY
+    CASE
+        WHEN c.chat_identifier = ?1 THEN 0
+        WHEN c.display_name = ?1 COLLATE NOCASE THEN 1
+        ELSE 2
+    END,
+    c.ROWID DESC
+LIMIT 1
+"#;
+
+/// Chats any of a set of handles takes part in.
+/// Callers add a `chj.handle_id IN (...)` condition with `with_filter`.
+pub const CHATS_WITH_HANDLES: &str = r#"
+SELECT DISTINCT chj.chat_id
+FROM chat_handle_join chj
+"#;
+
+/// Header facts for a set of chats, most recently active first: identifier,
+/// name, service, message span and count, attachment count. `{pinned}` is
+/// replaced with c.is_pinned (or NULL on schemas without it) before preparing.
+/// Callers add a `c.ROWID IN (...)` condition with `with_filter`.
+pub const CHAT_INFO: &str = r#"
+SELECT
+    c.ROWID,
+    c.chat_identifier,
+    c.display_name,
+    c.service_name,
+    COUNT(m.ROWID) AS message_count,
+    MIN(m.date) AS first_date,
+    MAX(m.date) AS last_date,
+    (SELECT COUNT(*) FROM chat_message_join acmj
+     JOIN message_attachment_join maj ON maj.message_id = acmj.message_id
+     WHERE acmj.chat_id = c.ROWID) AS attachment_count,
+    {pinned} AS is_pinned
+FROM chat c
+LEFT JOIN chat_message_join cmj ON cmj.chat_id = c.ROWID
+LEFT JOIN message m ON m.ROWID = cmj.message_id
+GROUP BY c.ROWID
+ORDER BY last_date IS NULL, last_date DESC
+"#;
+
+/// Message counts per sender in one chat (reactions excluded).
+/// Parameters: ?1 = chat ROWID, ?2 = cutoff_cocoa
+pub const GROUP_SENDER_COUNTS: &str = r#"
+SELECT m.is_from_me, h.id, COUNT(*) as message_count
+FROM message m
+JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+WHERE cmj.chat_id = ?1
+  AND m.date >= ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY m.is_from_me, h.id
+ORDER BY message_count DESC
+"#;
+
+/// Busiest hours (UTC) in one chat.
+/// Parameters: ?1 = chat ROWID, ?2 = cutoff_cocoa, ?3 = limit
+pub const GROUP_BUSIEST_HOURS: &str = concat!(
+    r#"
+SELECT "#,
+    utc_hour!("m.date"),
+    r#" as hour, COUNT(*) as message_count
+FROM message m
+JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+WHERE cmj.chat_id = ?1
+  AND m.date >= ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY hour
+ORDER BY message_count DESC, hour
+LIMIT ?3
+"#
+);
+
+/// Message with the most tapbacks in one chat.
+///
+/// associated_message_guid is `p:N/<guid>` or `bp:<guid>`; the target guid is
+/// the part after the slash (or prefix). Removal events (3000+) are excluded.
+/// Parameters: ?1 = chat ROWID, ?2 = cutoff_cocoa
+pub const GROUP_MOST_REACTED: &str = r#"
+SELECT t.guid, t.text, t.attributedBody, t.is_from_me, h.id, t.date, COUNT(*) as reaction_count
+FROM message r
+JOIN chat_message_join cmj ON r.ROWID = cmj.message_id
+JOIN message t ON t.guid = CASE
+        WHEN instr(r.associated_message_guid, '/') > 0
+            THEN substr(r.associated_message_guid, instr(r.associated_message_guid, '/') + 1)
+        WHEN r.associated_message_guid LIKE 'bp:%'
+            THEN substr(r.associated_message_guid, 4)
+        ELSE r.associated_message_guid
+    END
+LEFT JOIN handle h ON t.handle_id = h.ROWID
+WHERE cmj.chat_id = ?1
+  AND r.date >= ?2
+  AND r.associated_message_type BETWEEN 2000 AND 2999
+GROUP BY t.ROWID
+ORDER BY reaction_count DESC, t.date DESC
+LIMIT 1
+"#;
+
+/// Chronological messages in one chat (reactions excluded).
+/// Parameters: ?1 = chat ROWID, ?2 = cutoff_cocoa
+pub const GROUP_TIMELINE: &str = r#"
+SELECT m.is_from_me, h.id, m.text, m.attributedBody, m.date, m.guid
+FROM message m
+JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+WHERE cmj.chat_id = ?1
+  AND m.date >= ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+ORDER BY m.date ASC
+"#;
+
+/// Participant and name-change system messages in one chat, oldest first.
+/// item_type 1 = membership change (group_action_type 0 added, 1 removed),
+/// 2 = rename, 3 = left (action 0). `{other_handle}` and `{group_title}` are
+/// replaced with the column (or NULL on schemas without it) before preparing.
+/// Parameters: ?1 = chat ROWID
+pub const GROUP_EVENTS: &str = r#"
+SELECT m.date, m.is_from_me, actor.id, m.item_type, m.group_action_type,
+       affected.id, {group_title}
+FROM message m
+JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+LEFT JOIN handle actor ON m.handle_id = actor.ROWID
+LEFT JOIN handle affected ON {other_handle} = affected.ROWID
+WHERE cmj.chat_id = ?1
+  AND ((m.item_type = 1 AND m.group_action_type IN (0, 1))
+       OR m.item_type = 2
+       OR (m.item_type = 3 AND m.group_action_type = 0))
+ORDER BY m.date ASC
+"#;
+
+/// Recently deleted messages (chat_recoverable_message_join, newer macOS only).
+///
+/// delete_date has been seen in both seconds and nanoseconds since 2001, so
+/// it is normalized to nanoseconds as `delete_ns`.
+/// Parameters: ?1 = cutoff_cocoa (on the delete date)
+pub const DELETED_MESSAGES: &str = concat!(
+    r#"
+SELECT m.ROWID, m.guid, m.text, m.attributedBody, m.date, m.is_from_me, h.id,
+       c.chat_identifier, c.display_name, d.delete_ns,
+    "#,
+    attachment_hint!("m"),
+    r#"
+FROM (
+    SELECT chat_id, message_id,
+           CASE WHEN delete_date < 100000000000 THEN delete_date * 1000000000
+                ELSE delete_date END AS delete_ns
+    FROM chat_recoverable_message_join
+) d
+JOIN message m ON m.ROWID = d.message_id
+LEFT JOIN chat c ON c.ROWID = d.chat_id
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+WHERE d.delete_ns >= ?1
+ORDER BY d.delete_ns DESC
+"#
+);
+
+/// A contact's messages in a date range, for `summary`.
+/// `{deleted}` is replaced with a 0/1 "recently deleted" expression and
+/// `{order}` with ASC or DESC before preparing. Rows are ordered by
+/// (date, ROWID) so a `cursor::Cursor` condition can continue after a page.
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive), ?3 = limit, ?4 = offset
+pub const SUMMARY_MESSAGES: &str = concat!(
+    r#"
+SELECT m.ROWID, m.guid, m.text, m.attributedBody, m.date, m.is_from_me, h.id, {deleted},
+    "#,
+    attachment_hint!("m"),
+    r#"
+FROM message m
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+WHERE m.date >= ?1
+  AND m.date < ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+ORDER BY m.date {order}, m.ROWID {order}
+LIMIT ?3 OFFSET ?4
+"#
+);
+
+/// Messages in schema `{a}` whose guid is missing from schema `{b}`, per conversation.
+/// `{a}`/`{b}` are attached schema names ("main"/"other"), replaced before preparing.
+/// Parameters: ?1 = cutoff_cocoa
+pub const COMPARE_CONVERSATIONS: &str = r#"
+SELECT c.chat_identifier, c.display_name, COUNT(*), MIN(m.date), MAX(m.date)
+FROM {a}.message m
+LEFT JOIN {a}.chat c ON c.ROWID = (
+    SELECT MIN(cmj.chat_id) FROM {a}.chat_message_join cmj WHERE cmj.message_id = m.ROWID
+)
+WHERE m.date >= ?1
+  AND m.guid IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM {b}.message o WHERE o.guid = m.guid)
+GROUP BY c.ROWID
+ORDER BY COUNT(*) DESC, MAX(m.date) DESC
+"#;
+
+/// The rows behind COMPARE_CONVERSATIONS, oldest first (same placeholders).
+/// Parameters: ?1 = cutoff_cocoa
+pub const COMPARE_MESSAGES: &str = concat!(
+    r#"
+SELECT m.ROWID, m.guid, m.text, m.attributedBody, m.date, m.is_from_me, h.id, c.chat_identifier,
+    "#,
+    attachment_hint!("m", "{a}."),
+    r#"
+FROM {a}.message m
+LEFT JOIN {a}.handle h ON h.ROWID = m.handle_id
+LEFT JOIN {a}.chat c ON c.ROWID = (
+    SELECT MIN(cmj.chat_id) FROM {a}.chat_message_join cmj WHERE cmj.message_id = m.ROWID
+)
+WHERE m.date >= ?1
+  AND m.guid IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM {b}.message o WHERE o.guid = m.guid)
+ORDER BY m.date ASC
+"#
+);
+
+/// Messages around one message in its chat, oldest first.
+/// Parameters: ?1 = message ROWID, ?2 = messages before, ?3 = messages after
+pub const MESSAGE_CONTEXT: &str = r#"
+WITH target AS (
+    SELECT cmj.chat_id, m.date
+    FROM message m
+    JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+    WHERE m.ROWID = ?1
+    LIMIT 1
+),
+chat_messages AS (
+    SELECT m.ROWID AS rowid, m.text, m.attributedBody, m.date, m.is_from_me, h.id AS handle_id, m.guid
+    FROM message m
+    JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+    LEFT JOIN handle h ON m.handle_id = h.ROWID
+    WHERE cmj.chat_id = (SELECT chat_id FROM target)
+      AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+)
+SELECT rowid, text, attributedBody, date, is_from_me, handle_id, guid FROM (
+    SELECT * FROM chat_messages
+    WHERE date < (SELECT date FROM target)
+       OR (date = (SELECT date FROM target) AND rowid < ?1)
+    ORDER BY date DESC, rowid DESC
+    LIMIT ?2
+)
+UNION ALL
+SELECT rowid, text, attributedBody, date, is_from_me, handle_id, guid
+FROM chat_messages WHERE rowid = ?1
+UNION ALL
+SELECT rowid, text, attributedBody, date, is_from_me, handle_id, guid FROM (
+    SELECT * FROM chat_messages
+    WHERE date > (SELECT date FROM target)
+       OR (date = (SELECT date FROM target) AND rowid > ?1)
+    ORDER BY date ASC, rowid ASC
+    LIMIT ?3
+)
+ORDER BY date ASC, rowid ASC
 "#;
 
 // ============================================================================
@@ -198,65 +824,68 @@ SELECT
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-  AND h.id LIKE '%' || ?2 || '%'
+  AND h.id LIKE ?2 ESCAPE '\'
   AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 "#;
 
-/// Get busiest hour of day.
-pub const ANALYTICS_BUSIEST_HOUR: &str = r#"
+/// ANALYTICS_MESSAGE_COUNTS over a bounded window.
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive)
+pub const ANALYTICS_MESSAGE_COUNTS_RANGE: &str = r#"
 SELECT
-    CAST((m.date / 1000000000 / 3600) % 24 AS INTEGER) as hour,
-    COUNT(*) as count
+    COUNT(*) as total,
+    SUM(CASE WHEN m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN m.is_from_me = 0 THEN 1 ELSE 0 END) as received
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-GROUP BY hour
-ORDER BY count DESC
-LIMIT 1
+  AND m.date < ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 "#;
 
-/// Get busiest hour with phone filter.
-pub const ANALYTICS_BUSIEST_HOUR_PHONE: &str = r#"
+/// ANALYTICS_MESSAGE_COUNTS_PHONE over a bounded window.
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive), ?3 = like_contains(phone)
+pub const ANALYTICS_MESSAGE_COUNTS_PHONE_RANGE: &str = r#"
 SELECT
-    CAST((m.date / 1000000000 / 3600) % 24 AS INTEGER) as hour,
-    COUNT(*) as count
+    COUNT(*) as total,
+    SUM(CASE WHEN m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN m.is_from_me = 0 THEN 1 ELSE 0 END) as received
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-  AND h.id LIKE '%' || ?2 || '%'
-GROUP BY hour
-ORDER BY count DESC
-LIMIT 1
+  AND m.date < ?2
+  AND h.id LIKE ?3 ESCAPE '\'
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 "#;
 
-/// Get busiest day of week.
-pub const ANALYTICS_BUSIEST_DAY: &str = r#"
+/// Message counts within a time range.
+/// Parameters: ?1 = start cocoa (inclusive), ?2 = end cocoa (exclusive)
+pub const MESSAGE_COUNTS_BETWEEN: &str = r#"
 SELECT
-    CAST((m.date / 1000000000 / 86400 + 1) % 7 AS INTEGER) as dow,
-    COUNT(*) as count
+    COUNT(*) as total,
+    SUM(CASE WHEN m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN m.is_from_me = 0 THEN 1 ELSE 0 END) as received
 FROM message m
-LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-GROUP BY dow
-ORDER BY count DESC
-LIMIT 1
+  AND m.date < ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 "#;
 
-/// Get busiest day with phone filter.
-pub const ANALYTICS_BUSIEST_DAY_PHONE: &str = r#"
+/// Per-handle message counts within a time range (no limit).
+/// Parameters: ?1 = start cocoa (inclusive), ?2 = end cocoa (exclusive)
+pub const CONTACT_COUNTS_BETWEEN: &str = r#"
 SELECT
-    CAST((m.date / 1000000000 / 86400 + 1) % 7 AS INTEGER) as dow,
-    COUNT(*) as count
+    h.id,
+    COUNT(*) as msg_count
 FROM message m
-LEFT JOIN handle h ON m.handle_id = h.ROWID
+JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-  AND h.id LIKE '%' || ?2 || '%'
-GROUP BY dow
-ORDER BY count DESC
-LIMIT 1
+  AND m.date < ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY h.id
+ORDER BY msg_count DESC
 "#;
 
-/// Get top 10 contacts by message volume.
+/// Contacts by message volume, busiest first (top 10 taken after variant merging).
 pub const ANALYTICS_TOP_CONTACTS: &str = r#"
 SELECT
     h.id,
@@ -267,47 +896,39 @@ WHERE m.date >= ?1
   AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 GROUP BY h.id
 ORDER BY msg_count DESC
-LIMIT 10
-"#;
-
-/// Get attachment count.
-pub const ANALYTICS_ATTACHMENTS: &str = r#"
-SELECT COUNT(DISTINCT a.ROWID)
-FROM attachment a
-JOIN message_attachment_join maj ON a.ROWID = maj.attachment_id
-JOIN message m ON maj.message_id = m.ROWID
-LEFT JOIN handle h ON m.handle_id = h.ROWID
-WHERE m.date >= ?1
 "#;
 
-/// Get attachment count with phone filter.
-pub const ANALYTICS_ATTACHMENTS_PHONE: &str = r#"
-SELECT COUNT(DISTINCT a.ROWID)
-FROM attachment a
-JOIN message_attachment_join maj ON a.ROWID = maj.attachment_id
-JOIN message m ON maj.message_id = m.ROWID
-LEFT JOIN handle h ON m.handle_id = h.ROWID
-WHERE m.date >= ?1
-  AND h.id LIKE '%' || ?2 || '%'
-"#;
-
-/// Get reaction count.
-pub const ANALYTICS_REACTIONS: &str = r#"
-SELECT COUNT(*)
+/// ANALYTICS_TOP_CONTACTS over a bounded window.
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive)
+pub const ANALYTICS_TOP_CONTACTS_RANGE: &str = r#"
+SELECT
+    h.id,
+    COUNT(*) as msg_count
 FROM message m
-LEFT JOIN handle h ON m.handle_id = h.ROWID
+JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-  AND m.associated_message_type BETWEEN 2000 AND 3005
+  AND m.date < ?2
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY h.id
+ORDER BY msg_count DESC
 "#;
 
-/// Get reaction count with phone filter.
-pub const ANALYTICS_REACTIONS_PHONE: &str = r#"
-SELECT COUNT(*)
+/// Per-handle message stats for `contacts --stats`, one row per raw handle.
+/// `{unread}` is the unread condition over `m` (helpers::unread_conditions).
+/// Parameters: ?1 = cutoff (cocoa ns)
+pub const HANDLE_STATS: &str = r#"
+SELECT
+    h.id,
+    COUNT(*) as msg_count,
+    SUM(CASE WHEN m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN m.is_from_me = 0 THEN 1 ELSE 0 END) as received,
+    MAX(m.date) as last_date,
+    SUM(CASE WHEN {unread} THEN 1 ELSE 0 END) as unread
 FROM message m
-LEFT JOIN handle h ON m.handle_id = h.ROWID
+JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
-  AND h.id LIKE '%' || ?2 || '%'
-  AND m.associated_message_type BETWEEN 2000 AND 3005
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY h.id
 "#;
 
 /// Query all reactions with details.
@@ -323,12 +944,13 @@ SELECT
     m.is_from_me
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
-WHERE m.associated_message_type IN (2000, 2001, 2002, 2003, 2004, 2005, 3000, 3001, 3002, 3003, 3004, 3005)
+WHERE m.associated_message_type IN (2000, 2001, 2002, 2003, 2004, 2005, 2006, 2007, 3000, 3001, 3002, 3003, 3004, 3005, 3006, 3007)
 ORDER BY m.date DESC
 LIMIT ?1
 "#;
 
 /// Query reactions with phone filter.
+/// Parameters: ?1 = like_contains(phone), ?2 = limit
 pub const QUERY_REACTIONS_PHONE: &str = r#"
 SELECT
     m.ROWID,
@@ -341,12 +963,37 @@ SELECT
     m.is_from_me
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
-WHERE m.associated_message_type IN (2000, 2001, 2002, 2003, 2004, 2005, 3000, 3001, 3002, 3003, 3004, 3005)
-  AND h.id LIKE '%' || ?1 || '%'
+WHERE m.associated_message_type IN (2000, 2001, 2002, 2003, 2004, 2005, 2006, 2007, 3000, 3001, 3002, 3003, 3004, 3005, 3006, 3007)
+  AND h.id LIKE ?1 ESCAPE '\'
 ORDER BY m.date DESC
 LIMIT ?2
 "#;
 
+/// Tapbacks and removals on a set of messages, oldest first, with the guid
+/// of the message each one is on (`p:N/` and `bp:` prefixes stripped, as in
+/// GROUP_MOST_REACTED). Callers replace `{emoji}` (associated_message_emoji,
+/// or NULL on older schemas) and add the target set with with_filter on
+/// `target_guid` (see helpers::query_message_reactions).
+pub const MESSAGE_REACTIONS: &str = r#"
+SELECT
+    CASE
+        WHEN instr(r.associated_message_guid, '/') > 0
+            THEN substr(r.associated_message_guid, instr(r.associated_message_guid, '/') + 1)
+        WHEN r.associated_message_guid LIKE 'bp:%'
+            THEN substr(r.associated_message_guid, 4)
+        ELSE r.associated_message_guid
+    END AS target_guid,
+    r.associated_message_type,
+    r.text,
+    {emoji},
+    r.is_from_me,
+    h.id
+FROM message r
+LEFT JOIN handle h ON r.handle_id = h.ROWID
+WHERE r.associated_message_type BETWEEN 2000 AND 3999
+ORDER BY r.date, r.ROWID
+"#;
+
 // ============================================================================
 // OPTIMIZED ANALYTICS (Combined queries for daemon performance)
 // ============================================================================
@@ -356,59 +1003,254 @@ LIMIT ?2
 /// Includes attachment count using cache_has_attachments column (no join needed).
 /// Returns: total, sent, received, reactions, attachments, busiest_hour, busiest_day
 /// Parameters: ?1 = cutoff_cocoa
-pub const ANALYTICS_COMBINED: &str = r#"
+pub const ANALYTICS_COMBINED: &str = concat!(
+    r#"
 SELECT
     SUM(CASE WHEN associated_message_type IS NULL OR associated_message_type = 0 THEN 1 ELSE 0 END) as total,
     SUM(CASE WHEN (associated_message_type IS NULL OR associated_message_type = 0) AND is_from_me = 1 THEN 1 ELSE 0 END) as sent,
     SUM(CASE WHEN (associated_message_type IS NULL OR associated_message_type = 0) AND is_from_me = 0 THEN 1 ELSE 0 END) as received,
-    SUM(CASE WHEN associated_message_type BETWEEN 2000 AND 3005 THEN 1 ELSE 0 END) as reactions,
+    SUM(CASE WHEN associated_message_type BETWEEN 2000 AND 2007 THEN 1 ELSE 0 END) as reactions,
     SUM(cache_has_attachments) as attachments,
-    (SELECT CAST((date / 1000000000 / 3600) % 24 AS INTEGER) FROM message WHERE date >= ?1 GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
-    (SELECT CAST((date / 1000000000 / 86400 + 1) % 7 AS INTEGER) FROM message WHERE date >= ?1 GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
+    (SELECT "#,
+    utc_hour!("date"),
+    r#" FROM message WHERE date >= ?1 GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
+    (SELECT "#,
+    utc_weekday!("date"),
+    r#" FROM message WHERE date >= ?1 GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
 FROM message
 WHERE date >= ?1
-"#;
+"#
+);
 
 /// Combined analytics with phone filter.
 /// Includes attachment count using cache_has_attachments column.
-/// Parameters: ?1 = cutoff_cocoa, ?2 = phone pattern
-pub const ANALYTICS_COMBINED_PHONE: &str = r#"
+/// Parameters: ?1 = cutoff_cocoa, ?2 = like_contains(phone)
+pub const ANALYTICS_COMBINED_PHONE: &str = concat!(
+    r#"
 SELECT
     SUM(CASE WHEN m.associated_message_type IS NULL OR m.associated_message_type = 0 THEN 1 ELSE 0 END) as total,
     SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
     SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 0 THEN 1 ELSE 0 END) as received,
-    SUM(CASE WHEN m.associated_message_type BETWEEN 2000 AND 3005 THEN 1 ELSE 0 END) as reactions,
+    SUM(CASE WHEN m.associated_message_type BETWEEN 2000 AND 2007 THEN 1 ELSE 0 END) as reactions,
     SUM(m.cache_has_attachments) as attachments,
-    (SELECT CAST((m2.date / 1000000000 / 3600) % 24 AS INTEGER)
+    (SELECT "#,
+    utc_hour!("m2.date"),
+    r#"
      FROM message m2 JOIN handle h2 ON m2.handle_id = h2.ROWID
-     WHERE m2.date >= ?1 AND h2.id LIKE '%' || ?2 || '%'
+     WHERE m2.date >= ?1 AND h2.id LIKE ?2 ESCAPE '\'
      GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
-    (SELECT CAST((m2.date / 1000000000 / 86400 + 1) % 7 AS INTEGER)
+    (SELECT "#,
+    utc_weekday!("m2.date"),
+    r#"
      FROM message m2 JOIN handle h2 ON m2.handle_id = h2.ROWID
-     WHERE m2.date >= ?1 AND h2.id LIKE '%' || ?2 || '%'
+     WHERE m2.date >= ?1 AND h2.id LIKE ?2 ESCAPE '\'
      GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
 FROM message m
 JOIN handle h ON m.handle_id = h.ROWID
-WHERE m.date >= ?1 AND h.id LIKE '%' || ?2 || '%'
-"#;
+WHERE m.date >= ?1 AND h.id LIKE ?2 ESCAPE '\'
+"#
+);
 
-/// Optimized attachment count - uses message_attachment_join directly.
+/// Combined analytics restricted to a set of handles.
+/// `__HANDLES__` / `__HANDLES_M2__` are replaced with HandleFilter clauses on
+/// `m.handle_id` / `m2.handle_id`.
 /// Parameters: ?1 = cutoff_cocoa
-pub const ANALYTICS_ATTACHMENTS_FAST: &str = r#"
-SELECT COUNT(*)
-FROM message_attachment_join maj
-JOIN message m ON maj.message_id = m.ROWID
+pub const ANALYTICS_COMBINED_HANDLES: &str = concat!(
+    r#"
+SELECT
+    SUM(CASE WHEN m.associated_message_type IS NULL OR m.associated_message_type = 0 THEN 1 ELSE 0 END) as total,
+    SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 0 THEN 1 ELSE 0 END) as received,
+    SUM(CASE WHEN m.associated_message_type BETWEEN 2000 AND 2007 THEN 1 ELSE 0 END) as reactions,
+    SUM(m.cache_has_attachments) as attachments,
+    (SELECT "#,
+    utc_hour!("m2.date"),
+    r#"
+     FROM message m2
+     WHERE m2.date >= ?1 AND __HANDLES_M2__
+     GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
+    (SELECT "#,
+    utc_weekday!("m2.date"),
+    r#"
+     FROM message m2
+     WHERE m2.date >= ?1 AND __HANDLES_M2__
+     GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
+FROM message m
+WHERE m.date >= ?1 AND __HANDLES__
+"#
+);
+
+/// ANALYTICS_COMBINED over a bounded window (analytics --start/--end, --compare).
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive)
+pub const ANALYTICS_COMBINED_RANGE: &str = concat!(
+    r#"
+SELECT
+    SUM(CASE WHEN associated_message_type IS NULL OR associated_message_type = 0 THEN 1 ELSE 0 END) as total,
+    SUM(CASE WHEN (associated_message_type IS NULL OR associated_message_type = 0) AND is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN (associated_message_type IS NULL OR associated_message_type = 0) AND is_from_me = 0 THEN 1 ELSE 0 END) as received,
+    SUM(CASE WHEN associated_message_type BETWEEN 2000 AND 2007 THEN 1 ELSE 0 END) as reactions,
+    SUM(cache_has_attachments) as attachments,
+    (SELECT "#,
+    utc_hour!("date"),
+    r#" FROM message WHERE date >= ?1 AND date < ?2 GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
+    (SELECT "#,
+    utc_weekday!("date"),
+    r#" FROM message WHERE date >= ?1 AND date < ?2 GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
+FROM message
+WHERE date >= ?1 AND date < ?2
+"#
+);
+
+/// ANALYTICS_COMBINED_PHONE over a bounded window.
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive), ?3 = like_contains(phone)
+pub const ANALYTICS_COMBINED_PHONE_RANGE: &str = concat!(
+    r#"
+SELECT
+    SUM(CASE WHEN m.associated_message_type IS NULL OR m.associated_message_type = 0 THEN 1 ELSE 0 END) as total,
+    SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 0 THEN 1 ELSE 0 END) as received,
+    SUM(CASE WHEN m.associated_message_type BETWEEN 2000 AND 2007 THEN 1 ELSE 0 END) as reactions,
+    SUM(m.cache_has_attachments) as attachments,
+    (SELECT "#,
+    utc_hour!("m2.date"),
+    r#"
+     FROM message m2 JOIN handle h2 ON m2.handle_id = h2.ROWID
+     WHERE m2.date >= ?1 AND m2.date < ?2 AND h2.id LIKE ?3 ESCAPE '\'
+     GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
+    (SELECT "#,
+    utc_weekday!("m2.date"),
+    r#"
+     FROM message m2 JOIN handle h2 ON m2.handle_id = h2.ROWID
+     WHERE m2.date >= ?1 AND m2.date < ?2 AND h2.id LIKE ?3 ESCAPE '\'
+     GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
+FROM message m
+JOIN handle h ON m.handle_id = h.ROWID
+WHERE m.date >= ?1 AND m.date < ?2 AND h.id LIKE ?3 ESCAPE '\'
+"#
+);
+
+/// ANALYTICS_COMBINED_HANDLES over a bounded window (same placeholders).
+/// Parameters: ?1 = start_cocoa, ?2 = end_cocoa (exclusive)
+pub const ANALYTICS_COMBINED_HANDLES_RANGE: &str = concat!(
+    r#"
+SELECT
+    SUM(CASE WHEN m.associated_message_type IS NULL OR m.associated_message_type = 0 THEN 1 ELSE 0 END) as total,
+    SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN (m.associated_message_type IS NULL OR m.associated_message_type = 0) AND m.is_from_me = 0 THEN 1 ELSE 0 END) as received,
+    SUM(CASE WHEN m.associated_message_type BETWEEN 2000 AND 2007 THEN 1 ELSE 0 END) as reactions,
+    SUM(m.cache_has_attachments) as attachments,
+    (SELECT "#,
+    utc_hour!("m2.date"),
+    r#"
+     FROM message m2
+     WHERE m2.date >= ?1 AND m2.date < ?2 AND __HANDLES_M2__
+     GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_hour,
+    (SELECT "#,
+    utc_weekday!("m2.date"),
+    r#"
+     FROM message m2
+     WHERE m2.date >= ?1 AND m2.date < ?2 AND __HANDLES_M2__
+     GROUP BY 1 ORDER BY COUNT(*) DESC LIMIT 1) as busiest_day
+FROM message m
+WHERE m.date >= ?1 AND m.date < ?2 AND __HANDLES__
+"#
+);
+
+/// Sent/received counts per 15-minute UTC slot (`unix_secs / 900`), oldest
+/// first. Every UTC offset is a multiple of 15 minutes, so each slot falls in
+/// one local bucket; helpers::query_volume_series_at does the mapping.
+/// Parameters: ?1 = cutoff_cocoa (a phone filter injected via `with_filter` binds ?2)
+pub const VOLUME_SERIES: &str = concat!(
+    r#"
+SELECT
+    "#,
+    date_expr!("m.date"),
+    r#" / 900 as slot,
+    SUM(CASE WHEN m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN m.is_from_me = 0 THEN 1 ELSE 0 END) as received
+FROM message m
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+WHERE m.date >= ?1
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY slot
+ORDER BY slot
+"#
+);
+
+/// Sent/received counts per local weekday (0 = Sunday) and hour. `{shift}` is
+/// the UTC offset in seconds as a SQL expression over `m.date`, so each
+/// message is bucketed with the offset in effect when it was sent.
+/// Parameters: ?1 = cutoff_cocoa (a phone filter injected via `with_filter` binds ?2)
+pub const HEATMAP: &str = concat!(
+    r#"
+SELECT
+    (("#,
+    date_expr!("m.date"),
+    r#" + {shift}) / 86400 + 4) % 7 as weekday,
+    (("#,
+    date_expr!("m.date"),
+    r#" + {shift}) % 86400) / 3600 as hour,
+    SUM(CASE WHEN m.is_from_me = 1 THEN 1 ELSE 0 END) as sent,
+    SUM(CASE WHEN m.is_from_me = 0 THEN 1 ELSE 0 END) as received
+FROM message m
+LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+GROUP BY weekday, hour
+"#
+);
+
+/// Text of every message exchanged with a handle, for term counting.
+/// Parameters: ?1 = cutoff_cocoa, ?2 = like_contains(phone)
+pub const CONVERSATION_TEXTS: &str = r#"
+SELECT
+    m.text,
+    m.attributedBody,
+    m.is_from_me,
+    m.guid
+FROM message m
+JOIN handle h ON m.handle_id = h.ROWID
+WHERE m.date >= ?1
+  AND h.id LIKE ?2 ESCAPE '\'
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 "#;
 
-/// Optimized attachment count with phone filter.
-/// Parameters: ?1 = cutoff_cocoa, ?2 = phone pattern
-pub const ANALYTICS_ATTACHMENTS_FAST_PHONE: &str = r#"
-SELECT COUNT(*)
-FROM message_attachment_join maj
-JOIN message m ON maj.message_id = m.ROWID
+/// Tapbacks sent in a conversation with a handle (removals excluded).
+/// Parameters: ?1 = cutoff_cocoa, ?2 = like_contains(phone)
+pub const CONVERSATION_TAPBACKS: &str = r#"
+SELECT
+    m.associated_message_type,
+    m.is_from_me
+FROM message m
 JOIN handle h ON m.handle_id = h.ROWID
-WHERE m.date >= ?1 AND h.id LIKE '%' || ?2 || '%'
+WHERE m.date >= ?1
+  AND h.id LIKE ?2 ESCAPE '\'
+  AND m.associated_message_type BETWEEN 2000 AND 2007
+"#;
+
+/// Chronological messages in a conversation with a handle (reactions excluded).
+/// Parameters: ?1 = cutoff_cocoa, ?2 = like_contains(phone)
+pub const CONVERSATION_TIMELINE: &str = r#"
+SELECT m.is_from_me, h.id, m.text, m.attributedBody, m.date, m.guid
+FROM message m
+JOIN handle h ON m.handle_id = h.ROWID
+WHERE m.date >= ?1
+  AND h.id LIKE ?2 ESCAPE '\'
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
+ORDER BY m.date ASC, m.ROWID ASC
+"#;
+
+/// Lifetime message count and first message date (Cocoa ns) in a
+/// conversation with a handle, over all history (reactions excluded).
+/// Parameters: ?1 = like_contains(phone)
+pub const CONVERSATION_LIFETIME: &str = r#"
+SELECT
+    COUNT(*) as total,
+    MIN(CASE WHEN m.date > 0 THEN m.date END) as first_date
+FROM message m
+JOIN handle h ON m.handle_id = h.ROWID
+WHERE h.id LIKE ?1 ESCAPE '\'
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0)
 "#;
 
 // ============================================================================
@@ -416,46 +1258,61 @@ WHERE m.date >= ?1 AND h.id LIKE '%' || ?2 || '%'
 // ============================================================================
 
 /// Find unanswered questions from received messages.
-/// Parameters: ?1 = cutoff_cocoa (days ago), ?2 = stale_threshold_ns (nanoseconds)
+///
+/// The LIKE keywords only narrow candidates; callers score them
+/// (`followup::question_score`). A question counts as answered by any
+/// message of mine to the sender or in the same chat within the window;
+/// system items (renames, FaceTime calls, ...) only count with ?4.
+/// Parameters: ?1 = cutoff_cocoa (days ago), ?2 = stale_threshold_ns (nanoseconds), ?3 = limit,
+/// ?4 = include system items (0/1)
 pub const FOLLOWUP_UNANSWERED_QUESTIONS: &str = r#"
 SELECT
     m.ROWID,
     m.text,
     m.date,
-    h.id as phone
+    h.id as phone,
+    c.chat_identifier
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
+LEFT JOIN chat_message_join cmj ON m.ROWID = cmj.message_id
+LEFT JOIN chat c ON cmj.chat_id = c.ROWID
 WHERE m.is_from_me = 0
   AND m.date >= ?1
+  AND (?4 OR COALESCE(m.item_type, 0) = 0)
   AND (m.text LIKE '%?%' OR m.text LIKE '%when%' OR m.text LIKE '%what%'
        OR m.text LIKE '%where%' OR m.text LIKE '%how%' OR m.text LIKE '%why%'
        OR m.text LIKE '%can you%' OR m.text LIKE '%could you%')
   AND NOT EXISTS (
     SELECT 1 FROM message m2
-    WHERE m2.handle_id = m.handle_id
-      AND m2.is_from_me = 1
+    LEFT JOIN chat_message_join cmj2 ON m2.ROWID = cmj2.message_id
+    WHERE m2.is_from_me = 1
       AND m2.date > m.date
       AND m2.date < (m.date + ?2)
+      AND (?4 OR COALESCE(m2.item_type, 0) = 0)
+      AND (m2.handle_id = m.handle_id OR cmj2.chat_id = cmj.chat_id)
   )
 ORDER BY m.date DESC
-LIMIT 50
+LIMIT ?3
 "#;
 
-/// Find stale conversations (no reply after N days).
-/// Parameters: ?1 = cutoff_cocoa (days ago), ?2 = stale_threshold_ns (nanoseconds)
+/// Find stale conversations (no reply after N days); system items only
+/// count as the last message with ?3.
+/// Parameters: ?1 = cutoff_cocoa (days ago), ?2 = stale_threshold_ns (nanoseconds),
+/// ?3 = include system items (0/1)
 pub const FOLLOWUP_STALE_CONVERSATIONS: &str = r#"
 SELECT
     h.id as phone,
     MAX(m.date) as last_date,
     (SELECT m2.text FROM message m2
-     WHERE m2.handle_id = h.ROWID
+     WHERE m2.handle_id = h.ROWID AND (?3 OR COALESCE(m2.item_type, 0) = 0)
      ORDER BY m2.date DESC LIMIT 1) as last_text,
     (SELECT m2.is_from_me FROM message m2
-     WHERE m2.handle_id = h.ROWID
+     WHERE m2.handle_id = h.ROWID AND (?3 OR COALESCE(m2.item_type, 0) = 0)
      ORDER BY m2.date DESC LIMIT 1) as last_from_me
 FROM message m
 LEFT JOIN handle h ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
+  AND (?3 OR COALESCE(m.item_type, 0) = 0)
   AND m.is_from_me = 0
 GROUP BY h.id
 HAVING MAX(m.date) < (strftime('%s', 'now') - 978307200) * 1000000000 - ?2
@@ -479,9 +1336,61 @@ JOIN message m ON m.handle_id = h.ROWID
 WHERE m.date >= ?1
 GROUP BY h.id
 ORDER BY last_message_date DESC
+"#;
+
+/// DISCOVERY_HANDLES plus person_centric_id (newer chat.db schemas only).
+pub const DISCOVERY_HANDLES_WITH_PERSON: &str = r#"
+SELECT
+    h.id as handle,
+    COUNT(m.ROWID) as message_count,
+    MAX(m.date) as last_message_date,
+    MAX(h.person_centric_id) as person_centric_id
+FROM handle h
+JOIN message m ON m.handle_id = h.ROWID
+WHERE m.date >= ?1
+GROUP BY h.id
+ORDER BY last_message_date DESC
+"#;
+
+/// Display names of named chats a handle participates in.
+/// Parameters: ?1 = handle id
+pub const HANDLE_CHAT_NAMES: &str = r#"
+SELECT DISTINCT c.display_name
+FROM chat c
+JOIN chat_handle_join chj ON chj.chat_id = c.ROWID
+JOIN handle h ON h.ROWID = chj.handle_id
+WHERE h.id = ?1
+  AND c.display_name IS NOT NULL
+  AND c.display_name != ''
+"#;
+
+/// Most recent incoming messages from a handle.
+/// Parameters: ?1 = handle id, ?2 = limit
+pub const HANDLE_RECENT_TEXTS: &str = r#"
+SELECT m.text, m.attributedBody, m.guid
+FROM message m
+JOIN handle h ON h.ROWID = m.handle_id
+WHERE h.id = ?1
+  AND m.is_from_me = 0
+ORDER BY m.date DESC
 LIMIT ?2
 "#;
 
+/// Chats with at most one participant, with that participant's handle.
+/// One-to-one chats (helpers::query_direct_chats), notes-to-self among them.
+pub const SELF_CHAT_CANDIDATES: &str = r#"
+SELECT
+    c.ROWID,
+    c.chat_identifier,
+    COUNT(chj.handle_id) as participants,
+    MIN(h.id) as participant
+FROM chat c
+LEFT JOIN chat_handle_join chj ON chj.chat_id = c.ROWID
+LEFT JOIN handle h ON h.ROWID = chj.handle_id
+GROUP BY c.ROWID
+HAVING COUNT(chj.handle_id) <= 1
+"#;
+
 /// Find messages from unknown senders (not in contacts).
 /// Returns all handles with message counts and sample text.
 pub const DISCOVERY_UNKNOWN: &str = r#"
@@ -500,12 +1409,174 @@ GROUP BY h.id
 ORDER BY last_message_date DESC
 "#;
 
+// ============================================================================
+// RAW ROWS (raw-message)
+// ============================================================================
+
+/// Every column of one message row.
+/// Parameters: ?1 = ROWID
+pub const RAW_MESSAGE_BY_ROWID: &str = "SELECT * FROM message WHERE ROWID = ?1";
+
+/// Every column of one message row.
+/// Parameters: ?1 = guid
+pub const RAW_MESSAGE_BY_GUID: &str = "SELECT * FROM message WHERE guid = ?1";
+
+/// The message's chat_message_join rows, then the chat's columns.
+/// Parameters: ?1 = message ROWID
+pub const RAW_MESSAGE_CHATS: &str = r#"
+SELECT cmj.*, c.*
+FROM chat_message_join cmj
+LEFT JOIN chat c ON c.ROWID = cmj.chat_id
+WHERE cmj.message_id = ?1
+ORDER BY cmj.chat_id
+"#;
+
+/// Every column of the message's attachments.
+/// Parameters: ?1 = message ROWID
+pub const RAW_MESSAGE_ATTACHMENTS: &str = r#"
+SELECT a.*
+FROM message_attachment_join maj
+JOIN attachment a ON a.ROWID = maj.attachment_id
+WHERE maj.message_id = ?1
+ORDER BY a.ROWID
+"#;
+
+/// Every column of the rows associated with a message (tapbacks and their
+/// removals, stickers), whatever the `p:N/` or `bp:` prefix.
+/// Parameters: ?1 = guid
+pub const RAW_MESSAGE_REACTIONS: &str = r#"
+SELECT *
+FROM message
+WHERE associated_message_guid = ?1
+   OR associated_message_guid = 'bp:' || ?1
+   OR associated_message_guid LIKE 'p:%/' || ?1
+ORDER BY date, ROWID
+"#;
+
 /// Cocoa epoch offset (2001-01-01 in Unix time).
 pub const COCOA_EPOCH_OFFSET: i64 = 978_307_200;
 
-/// Convert Cocoa nanoseconds timestamp to Unix timestamp.
-pub fn cocoa_to_unix(cocoa_ns: i64) -> i64 {
-    (cocoa_ns / 1_000_000_000) + COCOA_EPOCH_OFFSET
+/// Nanoseconds per day in Cocoa timestamps.
+pub const DAY_NS: i64 = 86_400 * 1_000_000_000;
+
+/// Cocoa dates smaller than this (in magnitude) are seconds since 2001, as
+/// some old imported messages store them; larger ones are nanoseconds.
+pub const COCOA_SECONDS_BELOW: i64 = 1_000_000_000_000;
+
+/// How far past now a message date may be before it's suspect (clock skew).
+const FUTURE_SLACK_SECS: i64 = 86_400;
+
+/// Convert a Cocoa timestamp (nanoseconds, or seconds for small values; see
+/// `COCOA_SECONDS_BELOW`) to a Unix timestamp.
+///
+/// Every Cocoa-to-Unix conversion goes through here; use `decode_cocoa` for
+/// message dates, which also checks they're plausible.
+pub fn cocoa_to_unix(cocoa: i64) -> i64 {
+    let secs = if cocoa.unsigned_abs() < COCOA_SECONDS_BELOW as u64 {
+        cocoa
+    } else {
+        cocoa / 1_000_000_000
+    };
+    secs.saturating_add(COCOA_EPOCH_OFFSET)
+}
+
+/// A message date from chat.db (see `decode_cocoa`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CocoaDate {
+    /// Unix seconds, clamped to 2001-01-01 ..= now + 1 day
+    pub unix_secs: i64,
+    /// Zero, before 2001, or more than a day ahead: `unix_secs` is the clamp,
+    /// not when the message was sent.
+    pub suspect: bool,
+}
+
+impl CocoaDate {
+    /// RFC 3339 (UTC) of `unix_secs`; suspect dates give the clamp.
+    pub fn to_rfc3339(self) -> String {
+        chrono::DateTime::from_timestamp(self.unix_secs, 0)
+            .unwrap_or_default()
+            .to_rfc3339()
+    }
+
+    /// RFC 3339 (UTC), or None for suspect dates.
+    pub fn iso(self) -> Option<String> {
+        (!self.suspect).then(|| self.to_rfc3339())
+    }
+}
+
+/// Decode a message date (`date`, `date_read`, ...) relative to the clock.
+pub fn decode_cocoa(cocoa: i64) -> CocoaDate {
+    decode_cocoa_at(cocoa, chrono::Utc::now().timestamp())
+}
+
+/// `decode_cocoa` relative to `now_unix`.
+pub fn decode_cocoa_at(cocoa: i64, now_unix: i64) -> CocoaDate {
+    let unix_secs = cocoa_to_unix(cocoa);
+    let latest = now_unix.saturating_add(FUTURE_SLACK_SECS);
+    if cocoa <= 0 {
+        CocoaDate { unix_secs: COCOA_EPOCH_OFFSET, suspect: true }
+    } else if unix_secs > latest {
+        CocoaDate { unix_secs: latest, suspect: true }
+    } else {
+        CocoaDate { unix_secs, suspect: false }
+    }
+}
+
+/// Bound parameters one statement may use: SQLite's SQLITE_MAX_VARIABLE_NUMBER
+/// default before 3.32 (newer builds allow more, older ones don't).
+pub const MAX_BOUND_PARAMS: usize = 999;
+
+/// `column IN (?first, ?first+1, ...)` for `count` bound parameters; "0" when
+/// `count` is 0. Callers keep `first + count - 1` within MAX_BOUND_PARAMS
+/// (see helpers::query_chunked).
+pub fn in_params(column: &str, first: usize, count: usize) -> String {
+    if count == 0 {
+        return "0".to_string();
+    }
+    let params: Vec<String> = (first..first + count).map(|i| format!("?{}", i)).collect();
+    format!("{} IN ({})", column, params.join(", "))
+}
+
+/// Add a condition to a query's top-level WHERE clause.
+///
+/// Top-level clauses in this module start at column 0 (subqueries are
+/// indented), so the condition is inserted before the first top-level
+/// GROUP BY / HAVING / ORDER BY / LIMIT, adding a WHERE if there is none.
+pub fn with_filter(sql: &str, condition: &str) -> String {
+    let mut out = String::with_capacity(sql.len() + condition.len() + 16);
+    let mut has_where = false;
+    let mut inserted = false;
+
+    for line in sql.lines() {
+        if !inserted {
+            if line.starts_with("WHERE") {
+                has_where = true;
+            }
+            let is_tail = ["GROUP BY", "HAVING", "ORDER BY", "LIMIT"]
+                .iter()
+                .any(|kw| line.starts_with(kw));
+            if is_tail {
+                out.push_str(if has_where { "  AND " } else { "WHERE " });
+                out.push_str(condition);
+                out.push('\n');
+                inserted = true;
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    if !inserted {
+        out.push_str(if has_where { "  AND " } else { "WHERE " });
+        out.push_str(condition);
+        out.push('\n');
+    }
+    out
+}
+
+/// Convert Unix seconds to a Cocoa timestamp (nanoseconds since 2001-01-01).
+pub fn unix_to_cocoa(unix_secs: i64) -> i64 {
+    (unix_secs - COCOA_EPOCH_OFFSET) * 1_000_000_000
 }
 
 /// Calculate Cocoa timestamp for N days ago.
@@ -513,9 +1584,8 @@ pub fn cocoa_to_unix(cocoa_ns: i64) -> i64 {
 pub fn days_ago_cocoa(days: u32) -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards");
+    // A clock set before 1970 counts from the Unix epoch rather than panicking
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
 
     let seconds_ago = days as u64 * 86400;
     let cutoff_unix = now.as_secs().saturating_sub(seconds_ago) as i64;
@@ -524,10 +1594,229 @@ pub fn days_ago_cocoa(days: u32) -> i64 {
     cutoff_cocoa * 1_000_000_000
 }
 
+/// Escape LIKE wildcards (`%`, `_`) and the escape char itself (`\`).
+///
+/// Use with `LIKE ? ESCAPE '\'`.
+pub fn escape_like(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// `%term%` LIKE parameter with wildcards escaped.
+pub fn like_contains(term: &str) -> String {
+    format!("%{}%", escape_like(term))
+}
+
+/// How multiple search terms combine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermMatch {
+    Any,
+    All,
+}
+
+/// Build a text search over `term_count` terms joined with OR (Any) / AND (All).
+///
+/// Parameters: ?1..?N = `like_contains(term)`, ?N+1 = page size,
+/// ?N+2 = offset. Blob-only messages (empty text, attributedBody set) are
+/// always candidates; callers match terms after extracting their text and
+/// page on until they have enough.
+pub fn text_search_terms_sql(term_count: usize, mode: TermMatch) -> String {
+    terms_search_sql(term_count, mode, false)
+}
+
+/// `text_search_terms_sql` restricted to one chat via chat_message_join.
+///
+/// Parameters: ?1..?N = `like_contains(term)`, ?N+1 = chat ROWID,
+/// ?N+2 = page size, ?N+3 = offset.
+pub fn group_text_search_terms_sql(term_count: usize, mode: TermMatch) -> String {
+    terms_search_sql(term_count, mode, true)
+}
+
+fn terms_search_sql(term_count: usize, mode: TermMatch, in_chat: bool) -> String {
+    let joiner = match mode {
+        TermMatch::Any => " OR ",
+        TermMatch::All => " AND ",
+    };
+    let group = (1..=term_count)
+        .map(|i| format!("m.text LIKE ?{} ESCAPE '\\'", i))
+        .collect::<Vec<_>>()
+        .join(joiner);
+    let (chat_join, chat_filter, limit) = if in_chat {
+        (
+            "\nJOIN chat_message_join cmj ON cmj.message_id = m.ROWID",
+            format!("\n  AND cmj.chat_id = ?{}", term_count + 1),
+            term_count + 2,
+        )
+    } else {
+        ("", String::new(), term_count + 1)
+    };
+    format!(
+        r#"
+SELECT
+    m.text,
+    m.attributedBody,
+    m.date,
+    m.is_from_me,
+    h.id as handle_id,
+    m.cache_roomnames,
+    m.guid,
+    m.ROWID,
+    COALESCE(m.item_type, 0),
+    COALESCE(m.group_action_type, 0),
+    m.balloon_bundle_id
+FROM message m{chat_join}
+LEFT JOIN handle h ON m.handle_id = h.ROWID
+WHERE (({group})
+    OR ((m.text IS NULL OR m.text = '') AND m.attributedBody IS NOT NULL))
+  AND (m.associated_message_type IS NULL OR m.associated_message_type = 0){chat_filter}
+ORDER BY m.date DESC, m.ROWID DESC
+LIMIT ?{limit} OFFSET ?{offset}
+"#,
+        chat_join = chat_join,
+        group = group,
+        chat_filter = chat_filter,
+        limit = limit,
+        offset = limit + 1
+    )
+}
+
+// ============================================================================
+// QUERY PLAN CHECKS (doctor --performance)
+// ============================================================================
+
+/// Main date-bounded queries, checked by `doctor --performance`.
+pub const DATE_BOUNDED_QUERIES: &[(&str, &str)] = &[
+    ("RECENT_MESSAGES", RECENT_MESSAGES),
+    ("RECENT_CONVERSATIONS", RECENT_CONVERSATIONS),
+    ("ANALYTICS_COMBINED", ANALYTICS_COMBINED),
+    ("ANALYTICS_COMBINED_RANGE", ANALYTICS_COMBINED_RANGE),
+    ("ANALYTICS_TOP_CONTACTS", ANALYTICS_TOP_CONTACTS),
+    ("ANALYTICS_TOP_CONTACTS_RANGE", ANALYTICS_TOP_CONTACTS_RANGE),
+    ("MESSAGE_COUNTS_BETWEEN", MESSAGE_COUNTS_BETWEEN),
+    ("VOLUME_SERIES", VOLUME_SERIES),
+    ("FOLLOWUP_UNANSWERED_QUESTIONS", FOLLOWUP_UNANSWERED_QUESTIONS),
+    ("FOLLOWUP_STALE_CONVERSATIONS", FOLLOWUP_STALE_CONVERSATIONS),
+    ("DISCOVERY_HANDLES", DISCOVERY_HANDLES),
+    ("DISCOVERY_UNKNOWN", DISCOVERY_UNKNOWN),
+];
+
+/// Names the message table goes by in `sql`: "message" and its aliases.
+pub fn message_aliases(sql: &str) -> Vec<String> {
+    const NOT_ALIASES: &[&str] = &[
+        "where", "join", "left", "inner", "cross", "on", "group", "order", "limit", "union", "using", "as",
+    ];
+    let tokens: Vec<&str> = sql
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut aliases = vec!["message".to_string()];
+    for (i, window) in tokens.windows(2).enumerate() {
+        let is_table = matches!(window[0].to_ascii_lowercase().as_str(), "from" | "join")
+            && window[1].eq_ignore_ascii_case("message");
+        if !is_table {
+            continue;
+        }
+        let mut next = tokens.get(i + 2).copied();
+        if next.is_some_and(|t| t.eq_ignore_ascii_case("as")) {
+            next = tokens.get(i + 3).copied();
+        }
+        if let Some(alias) = next.filter(|t| !NOT_ALIASES.contains(&t.to_ascii_lowercase().as_str())) {
+            if !aliases.iter().any(|a| a == alias) {
+                aliases.push(alias.to_string());
+            }
+        }
+    }
+    aliases
+}
+
+/// Table (or alias) a plan row scans in full, if it is a SCAN.
+pub fn scanned_table(detail: &str) -> Option<&str> {
+    let rest = detail.strip_prefix("SCAN ")?;
+    let rest = rest.strip_prefix("TABLE ").unwrap_or(rest);
+    let mut words = rest.split_whitespace();
+    let table = words.next()?;
+    // Older SQLite: "SCAN TABLE message AS m"
+    match (words.next(), words.next()) {
+        (Some("AS"), Some(alias)) => Some(alias),
+        _ => Some(table),
+    }
+}
+
+/// Plan rows of `sql` that scan the message table in full (by name or alias).
+pub fn message_scans<'a>(sql: &str, plan: &'a [PlanRow]) -> Vec<&'a PlanRow> {
+    let aliases = message_aliases(sql);
+    plan.iter()
+        .filter(|row| scanned_table(&row.detail).is_some_and(|t| aliases.iter().any(|a| a == t)))
+        .collect()
+}
+
+/// Whether a scan of `alias` could be bounded by a ROWID range instead: the
+/// query bounds `alias.date` from below. chat.db assigns ROWIDs in arrival
+/// order, so the first ROWID at the cutoff stands in for the date bound.
+pub fn rowid_range_applies(sql: &str, alias: &str) -> bool {
+    let compact: String = sql.split_whitespace().collect::<Vec<_>>().join(" ").to_ascii_lowercase();
+    let column = if alias == "message" { "date".to_string() } else { format!("{}.date", alias.to_ascii_lowercase()) };
+    [">=", ">", "between"].iter().any(|op| {
+        compact.match_indices(&format!("{} {}", column, op)).any(|(i, _)| {
+            // "m.date" must not match the tail of "m2.date" or "date_read"
+            let before = if i == 0 { b' ' } else { compact.as_bytes()[i - 1] };
+            !(before.is_ascii_alphanumeric() || before == b'.' || before == b'_')
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("_test_"), "\\_test\\_");
+        assert_eq!(escape_like(r"a\b"), r"a\\b");
+        assert_eq!(like_contains("50%_off"), "%50\\%\\_off%");
+    }
+
+    #[test]
+    fn test_text_search_terms_sql_one_term() {
+        let sql = text_search_terms_sql(1, TermMatch::Any);
+        assert!(sql.contains("WHERE ((m.text LIKE ?1 ESCAPE '\\')\n"));
+        assert!(sql.contains("LIMIT ?2 OFFSET ?3"));
+    }
+
+    #[test]
+    fn test_text_search_terms_sql_two_terms() {
+        let any = text_search_terms_sql(2, TermMatch::Any);
+        assert!(any.contains("m.text LIKE ?1 ESCAPE '\\' OR m.text LIKE ?2 ESCAPE '\\'"));
+        let all = text_search_terms_sql(2, TermMatch::All);
+        assert!(all.contains("m.text LIKE ?1 ESCAPE '\\' AND m.text LIKE ?2 ESCAPE '\\'"));
+        assert!(all.contains("LIMIT ?3"));
+    }
+
+    #[test]
+    fn test_text_search_terms_sql_ten_terms() {
+        let sql = text_search_terms_sql(10, TermMatch::Any);
+        assert_eq!(sql.matches(" LIKE ?").count(), 10);
+        assert!(sql.contains("m.text LIKE ?10 ESCAPE"));
+        assert!(sql.contains("LIMIT ?11"));
+    }
+
+    #[test]
+    fn test_group_text_search_terms_sql() {
+        let sql = group_text_search_terms_sql(2, TermMatch::All);
+        assert!(sql.contains("JOIN chat_message_join cmj ON cmj.message_id = m.ROWID"));
+        assert!(sql.contains("m.text LIKE ?1 ESCAPE '\\' AND m.text LIKE ?2 ESCAPE '\\'"));
+        assert!(sql.contains("AND cmj.chat_id = ?3\n"));
+        assert!(sql.contains("LIMIT ?4"));
+        assert!(!text_search_terms_sql(2, TermMatch::All).contains("chat_message_join"));
+    }
+
     #[test]
     fn test_cocoa_to_unix() {
         // 2025-01-01 00:00:00 UTC in Cocoa time
@@ -536,4 +1825,121 @@ mod tests {
         // Should be around 1735689600 (2025-01-01)
         assert!(unix > 1735689500 && unix < 1735689700);
     }
+
+    #[test]
+    fn test_with_filter_inserts_before_tail() {
+        let sql = with_filter(RECENT_MESSAGES, "m.handle_id IN (1)");
+        assert!(sql.contains("OR m.cache_has_attachments = 1)\n  AND m.handle_id IN (1)\nORDER BY"));
+
+        // Subquery WHERE/ORDER BY (indented) is left alone
+        let sql = with_filter(FOLLOWUP_STALE_CONVERSATIONS, "m.handle_id IN (1)");
+        assert!(sql.contains("AND m.is_from_me = 0\n  AND m.handle_id IN (1)\nGROUP BY"));
+
+        // No top-level WHERE: one is added
+        let sql = with_filter("SELECT 1 FROM message m\nORDER BY 1", "x = 1");
+        assert_eq!(sql, "SELECT 1 FROM message m\nWHERE x = 1\nORDER BY 1\n");
+
+        // No tail clause: appended
+        assert!(with_filter(ANALYTICS_MESSAGE_COUNTS, "x = 1").ends_with("  AND x = 1\n"));
+    }
+
+    #[test]
+    fn test_unix_to_cocoa_roundtrip() {
+        assert_eq!(cocoa_to_unix(unix_to_cocoa(1735689600)), 1735689600);
+    }
+
+    #[test]
+    fn test_sql_date_expressions_match_cocoa_to_unix() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let sql = concat!("SELECT ", date_expr!("?1"), ", ", utc_hour!("?1"), ", ", utc_weekday!("?1"));
+        // 2025-01-01 13:30 UTC (a Wednesday), in nanoseconds and in seconds
+        for cocoa in [757_431_000_000_000_000, 757_431_000] {
+            let (unix, hour, weekday): (i64, i64, i64) =
+                conn.query_row(sql, [cocoa], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))).unwrap();
+            assert_eq!(unix, cocoa_to_unix(cocoa));
+            assert_eq!((hour, weekday), (13, 3));
+        }
+        assert_eq!(COCOA_SECONDS_BELOW, 1_000_000_000_000);
+        assert_eq!(COCOA_EPOCH_OFFSET, 978_307_200);
+    }
+
+    #[test]
+    fn test_decode_cocoa_units_and_plausibility() {
+        // 2025-01-01 00:00:00 UTC
+        let now = 1_735_689_600;
+        let cases: &[(&str, i64, i64, bool)] = &[
+            ("nanoseconds", 757_382_400_000_000_000, now, false),
+            ("seconds", 757_382_400, now, false),
+            ("seconds, 2001", 3_600, COCOA_EPOCH_OFFSET + 3_600, false),
+            ("zero", 0, COCOA_EPOCH_OFFSET, true),
+            ("negative seconds", -86_400, COCOA_EPOCH_OFFSET, true),
+            ("pre-2001 nanoseconds", -5 * 86_400 * 1_000_000_000, COCOA_EPOCH_OFFSET, true),
+            ("an hour ahead", 757_386_000_000_000_000, now + 3_600, false),
+            ("a year ahead", 788_918_400_000_000_000, now + 86_400, true),
+            ("garbage", i64::MAX, now + 86_400, true),
+        ];
+        for &(name, raw, unix_secs, suspect) in cases {
+            assert_eq!(decode_cocoa_at(raw, now), CocoaDate { unix_secs, suspect }, "{}", name);
+        }
+
+        let ok = decode_cocoa_at(757_382_400, now);
+        assert_eq!(ok.iso().as_deref(), Some("2025-01-01T00:00:00+00:00"));
+        let future = decode_cocoa_at(i64::MAX, now);
+        assert_eq!(future.iso(), None);
+        assert_eq!(future.to_rfc3339(), "2025-01-02T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_text_search_matches_wildcards_literally() {
+        use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message, DAY_NS};
+
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let chat = insert_chat(&conn, "+14155550001", None, &[alice]);
+        for (i, text) in ["100% sure", "1000 sure", "run _test_ now", "run xtestx now", r"C:\temp\a", "C:tempa"]
+            .iter()
+            .enumerate()
+        {
+            insert_message(&conn, chat, alice, Some(*text), (i as i64 + 1) * DAY_NS, false, true);
+        }
+
+        let search = |q: &str| -> Vec<String> {
+            let mut stmt = conn.prepare(TEXT_SEARCH).unwrap();
+            stmt.query_map(rusqlite::params![like_contains(q), 10], |row| row.get(2))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect()
+        };
+
+        assert_eq!(search("100%"), vec!["100% sure"]);
+        assert_eq!(search("_test_"), vec!["run _test_ now"]);
+        assert_eq!(search(r"C:\temp"), vec![r"C:\temp\a"]);
+    }
+
+    #[test]
+    fn test_date_bounded_query_plans() {
+        use crate::db::connection::explain_query_plan;
+        use crate::db::fixtures::empty_db;
+
+        assert_eq!(message_aliases(FOLLOWUP_UNANSWERED_QUESTIONS), ["message", "m", "m2"]);
+        assert!(rowid_range_applies(RECENT_MESSAGES, "m"));
+        assert!(!rowid_range_applies(RECENT_MESSAGES, "m2"));
+
+        // Every checked query plans against the fixture schema. It has no
+        // date index, so date bounds on message scan it
+        let conn = empty_db();
+        let mut flagged = Vec::new();
+        for (name, sql) in DATE_BOUNDED_QUERIES {
+            let plan = explain_query_plan(&conn, sql).unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+            if !message_scans(sql, &plan).is_empty() {
+                flagged.push(*name);
+            }
+        }
+        assert!(flagged.contains(&"RECENT_MESSAGES") && flagged.contains(&"MESSAGE_COUNTS_BETWEEN"), "{:?}", flagged);
+
+        // With a date index the date bound becomes a SEARCH
+        conn.execute_batch("CREATE INDEX message_idx_date ON message(date)").unwrap();
+        let plan = explain_query_plan(&conn, MESSAGE_COUNTS_BETWEEN).unwrap();
+        assert!(message_scans(MESSAGE_COUNTS_BETWEEN, &plan).is_empty(), "{:?}", plan);
+    }
 }
@@ -0,0 +1,410 @@
+//! Cache of text extracted from attributedBody blobs.
+//!
+//! Blob parsing dominates read latency when the text column is empty, and
+//! the same blobs get re-parsed on every invocation. Extracted text is keyed
+//! by message guid in ~/.wolfies-imessage/textcache.db; the daemon also keeps
+//! an in-memory LRU in front of it. Each entry records a hash of the blob it
+//! came from, and only counts as a hit for the same blob: editing a message
+//! keeps its guid but rewrites attributedBody.
+//!
+//! The cache is best-effort: if it can't be opened or queried, text is
+//! parsed directly, so a broken cache never breaks reads (`cache clear`
+//! removes it).
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Entries carry blob_hash and only hit for the same blob (edited messages re-parse); schema version 2
+//! - 10/16/2026 - Path comes from the data file registry (data_files::TEXT_CACHE)
+//! - 10/16/2026 - Initial on-disk text cache, daemon LRU, and process-wide install
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::db::{blob_parser, helpers};
+use crate::data_files;
+
+/// Cache file name in the data directory.
+pub const CACHE_FILE: &str = data_files::TEXT_CACHE.name;
+
+/// Bump when extraction changes; rows from other versions count as misses.
+pub const SCHEMA_VERSION: i64 = 2;
+
+// WAL + synchronous=NORMAL: cache writes don't fsync per insert (the cache is disposable)
+const CREATE_SCHEMA: &str = r#"
+PRAGMA journal_mode = WAL;
+PRAGMA synchronous = NORMAL;
+CREATE TABLE IF NOT EXISTS text_cache (
+    guid TEXT PRIMARY KEY,
+    extracted_text TEXT,
+    schema_version INTEGER NOT NULL,
+    blob_hash INTEGER NOT NULL
+);
+"#;
+
+/// Default cache path (~/.wolfies-imessage/textcache.db).
+pub fn default_path() -> PathBuf {
+    data_files::TEXT_CACHE.path()
+}
+
+/// Open (or create) a cache database, dropping rows from older schema versions.
+pub fn open_cache(path: &Path) -> Result<Connection> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+    let conn = Connection::open(path)
+        .with_context(|| format!("Failed to open text cache at {:?}", path))?;
+    conn.execute_batch(CREATE_SCHEMA)
+        .with_context(|| format!("Failed to initialize text cache at {:?}", path))?;
+    if !helpers::has_column(&conn, "text_cache", "blob_hash") {
+        // Version 1 layout: its rows can't be validated, so start over
+        conn.execute_batch("DROP TABLE text_cache;")?;
+        conn.execute_batch(CREATE_SCHEMA)?;
+    }
+    conn.execute(
+        "DELETE FROM text_cache WHERE schema_version != ?1",
+        [SCHEMA_VERSION],
+    )?;
+    Ok(conn)
+}
+
+/// Delete the cache file (and SQLite sidecars). Returns whether it existed.
+pub fn clear(path: &Path) -> Result<bool> {
+    let mut existed = false;
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let file = PathBuf::from(format!("{}{}", path.display(), suffix));
+        match std::fs::remove_file(&file) {
+            Ok(()) => existed |= suffix.is_empty(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to remove {:?}", file)),
+        }
+    }
+    Ok(existed)
+}
+
+/// Message text: the text column when present, otherwise the blob's text,
+/// looked up in (and on a miss, added to) the cache by guid and blob hash.
+pub fn get_message_text_cached(
+    conn_cache: &Connection,
+    guid: &str,
+    text_col: Option<String>,
+    blob: Option<&[u8]>,
+) -> Option<String> {
+    if let Some(text) = text_col.filter(|t| !t.is_empty()) {
+        return Some(text);
+    }
+    let blob = blob?;
+    let hash = blob_hash(blob);
+    if let Ok(Some(cached)) = lookup(conn_cache, guid, hash) {
+        return cached;
+    }
+    let text = parse_text(blob);
+    let _ = store(conn_cache, guid, hash, text.as_deref());
+    text
+}
+
+/// FNV-1a hash of an attributedBody blob (stable across builds, unlike
+/// `DefaultHasher`), stored as SQLite's signed INTEGER.
+fn blob_hash(blob: &[u8]) -> i64 {
+    let hash = blob.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    hash as i64
+}
+
+/// Cached entry for `guid` parsed from a blob with `hash`: `Some(None)`
+/// means "parsed, no text". An entry from another blob is a miss.
+fn lookup(conn: &Connection, guid: &str, hash: i64) -> Result<Option<Option<String>>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT extracted_text FROM text_cache WHERE guid = ?1 AND schema_version = ?2 AND blob_hash = ?3",
+    )?;
+    Ok(stmt
+        .query_row(rusqlite::params![guid, SCHEMA_VERSION, hash], |row| row.get(0))
+        .optional()?)
+}
+
+fn store(conn: &Connection, guid: &str, hash: i64, text: Option<&str>) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO text_cache (guid, extracted_text, schema_version, blob_hash) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    stmt.execute(rusqlite::params![guid, text, SCHEMA_VERSION, hash])?;
+    Ok(())
+}
+
+fn parse_text(blob: &[u8]) -> Option<String> {
+    blob_parser::parse_blob(blob).ok().flatten().map(|p| p.text)
+}
+
+/// Fixed-capacity least-recently-used map of guid → (blob hash, extracted text).
+struct Lru {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (i64, Option<String>, u64)>,
+    order: BTreeMap<u64, String>,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Text for `guid` if it was parsed from the blob with `hash`.
+    fn get(&mut self, guid: &str, hash: i64) -> Option<Option<String>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (stored_hash, text, last_used) = self.entries.get_mut(guid)?;
+        if *stored_hash != hash {
+            return None;
+        }
+        self.order.remove(last_used);
+        *last_used = tick;
+        self.order.insert(tick, guid.to_string());
+        Some(text.clone())
+    }
+
+    fn put(&mut self, guid: &str, hash: i64, text: Option<String>) {
+        self.tick += 1;
+        if let Some((_, _, last_used)) = self.entries.get(guid) {
+            self.order.remove(last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(guid.to_string(), (hash, text, self.tick));
+        self.order.insert(self.tick, guid.to_string());
+    }
+}
+
+/// On-disk cache (opened on first use) with an optional in-memory LRU.
+pub struct TextCache {
+    path: Option<PathBuf>,
+    conn: Option<Connection>,
+    open_failed: bool,
+    lru: Option<Lru>,
+}
+
+impl TextCache {
+    /// Cache backed by `path`; `lru_capacity` 0 disables the in-memory layer.
+    pub fn new(path: Option<PathBuf>, lru_capacity: usize) -> Self {
+        Self {
+            path,
+            conn: None,
+            open_failed: false,
+            lru: (lru_capacity > 0).then(|| Lru::new(lru_capacity)),
+        }
+    }
+
+    /// Message text via LRU → disk cache → blob parse.
+    pub fn message_text(
+        &mut self,
+        guid: &str,
+        text_col: Option<String>,
+        blob: Option<&[u8]>,
+    ) -> Option<String> {
+        if let Some(text) = text_col.filter(|t| !t.is_empty()) {
+            return Some(text);
+        }
+        let blob = blob?;
+        let hash = blob_hash(blob);
+        if let Some(cached) = self.lru.as_mut().and_then(|lru| lru.get(guid, hash)) {
+            return cached;
+        }
+
+        let text = match self.connection() {
+            Some(conn) => get_message_text_cached(conn, guid, None, Some(blob)),
+            None => parse_text(blob),
+        };
+        if let Some(lru) = self.lru.as_mut() {
+            lru.put(guid, hash, text.clone());
+        }
+        text
+    }
+
+    fn connection(&mut self) -> Option<&Connection> {
+        if self.conn.is_none() && !self.open_failed {
+            match self.path.as_deref().map(open_cache) {
+                Some(Ok(conn)) => self.conn = Some(conn),
+                Some(Err(e)) => {
+                    tracing::warn!("text cache disabled: {:#}", e);
+                    self.open_failed = true;
+                }
+                None => self.open_failed = true,
+            }
+        }
+        self.conn.as_ref()
+    }
+}
+
+/// Process-wide cache used by the query helpers (None = disabled).
+static ACTIVE: Mutex<Option<TextCache>> = Mutex::new(None);
+
+/// Route helper text extraction through `cache` for the rest of the process.
+pub fn install(cache: TextCache) {
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = Some(cache);
+    }
+}
+
+/// Message text for query helpers: uses the installed cache when there is
+/// one (and a guid to key on), otherwise parses the blob directly.
+pub fn message_text(guid: Option<&str>, text_col: Option<String>, blob: Option<&[u8]>) -> Option<String> {
+    if let Some(text) = text_col.filter(|t| !t.is_empty()) {
+        return Some(text);
+    }
+    let blob = blob?;
+    if let Some(guid) = guid {
+        if let Ok(mut active) = ACTIVE.lock() {
+            if let Some(cache) = active.as_mut() {
+                return cache.message_text(guid, None, Some(blob));
+            }
+        }
+    }
+    parse_text(blob)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    const PLAIN: &[u8] = include_bytes!("testdata/attributed_body/plain.bin");
+    const LONG: &[u8] = include_bytes!("testdata/attributed_body/long.bin");
+
+    #[test]
+    fn test_miss_inserts_and_hit_skips_parsing() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_cache(&dir.path().join(CACHE_FILE)).unwrap();
+
+        let first = get_message_text_cached(&conn, "g1", None, Some(PLAIN)).unwrap();
+        assert!(!first.is_empty());
+
+        // A hit skips parsing: a cached row with planted text comes back as is
+        conn.execute("UPDATE text_cache SET extracted_text = 'cached' WHERE guid = 'g1'", []).unwrap();
+        let hit = get_message_text_cached(&conn, "g1", None, Some(PLAIN));
+        assert_eq!(hit.as_deref(), Some("cached"));
+
+        // The text column always wins and isn't cached
+        let col = get_message_text_cached(&conn, "g2", Some("plain".to_string()), Some(PLAIN));
+        assert_eq!(col.as_deref(), Some("plain"));
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM text_cache", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn test_edited_message_misses_and_reparses() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_cache(&dir.path().join(CACHE_FILE)).unwrap();
+        let before = get_message_text_cached(&conn, "g1", None, Some(PLAIN)).unwrap();
+
+        // Same guid, new attributedBody (an edit): the old text is not reused
+        let after = get_message_text_cached(&conn, "g1", None, Some(LONG)).unwrap();
+        assert_ne!(after, before);
+        assert_eq!(after, parse_text(LONG).unwrap());
+        assert_eq!(lookup(&conn, "g1", blob_hash(PLAIN)).unwrap(), None);
+        assert_eq!(lookup(&conn, "g1", blob_hash(LONG)).unwrap(), Some(Some(after.clone())));
+
+        // The daemon's LRU checks the blob the same way
+        let mut cache = TextCache::new(None, 4);
+        assert_eq!(cache.message_text("g1", None, Some(PLAIN)), Some(before));
+        assert_eq!(cache.message_text("g1", None, Some(LONG)), Some(after));
+    }
+
+    #[test]
+    fn test_stale_schema_rows_dropped_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_FILE);
+        {
+            // A version 1 cache (no blob_hash column) is replaced
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE text_cache (guid TEXT PRIMARY KEY, extracted_text TEXT, schema_version INTEGER NOT NULL);
+                 INSERT INTO text_cache VALUES ('v1', 'stale', 1);",
+            )
+            .unwrap();
+        }
+        {
+            let conn = open_cache(&path).unwrap();
+            assert_eq!(lookup(&conn, "v1", 0).unwrap(), None);
+            conn.execute(
+                "INSERT INTO text_cache VALUES ('old', 'stale', ?1, 0)",
+                [SCHEMA_VERSION - 1],
+            )
+            .unwrap();
+        }
+        let conn = open_cache(&path).unwrap();
+        assert_eq!(lookup(&conn, "old", 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_clear_removes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_FILE);
+        drop(open_cache(&path).unwrap());
+        assert!(clear(&path).unwrap());
+        assert!(!path.exists());
+        assert!(!clear(&path).unwrap());
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let text = || Some("text".to_string());
+        let mut lru = Lru::new(2);
+        lru.put("a", 1, text());
+        lru.put("b", 1, text());
+        // Touch "a" so "b" is the eviction candidate
+        assert!(lru.get("a", 1).is_some());
+        lru.put("c", 1, text());
+
+        assert!(lru.get("a", 1).is_some());
+        assert!(lru.get("c", 1).is_some());
+        assert!(lru.get("b", 1).is_none());
+        // Another blob under the same guid is a miss
+        assert!(lru.get("a", 2).is_none());
+    }
+
+    /// Timing check on a blob-heavy workload: `cargo test -- --ignored text_cache`.
+    #[test]
+    #[ignore]
+    fn test_second_run_is_faster() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_FILE);
+        let guids: Vec<String> = (0..5000).map(|i| format!("guid-{}", i)).collect();
+
+        let run = |cache: &mut TextCache| {
+            let start = Instant::now();
+            for (i, guid) in guids.iter().enumerate() {
+                let blob = if i % 2 == 0 { LONG } else { PLAIN };
+                assert!(cache.message_text(guid, None, Some(blob)).is_some());
+            }
+            start.elapsed()
+        };
+
+        let parse_only = run(&mut TextCache::new(None, 0));
+
+        let mut cli = TextCache::new(Some(path.clone()), 0);
+        let cold = run(&mut cli);
+        let mut cli = TextCache::new(Some(path.clone()), 0);
+        let warm_disk = run(&mut cli);
+
+        let mut daemon = TextCache::new(Some(path), 10_000);
+        run(&mut daemon);
+        let warm_memory = run(&mut daemon);
+
+        eprintln!(
+            "parse only {:?}, cold {:?}, warm disk {:?}, warm memory {:?}",
+            parse_only, cold, warm_disk, warm_memory
+        );
+        assert!(warm_disk < cold);
+        assert!(warm_memory < parse_only);
+    }
+}
@@ -0,0 +1,525 @@
+//! Decoder for NeXT/Apple typedstream archives (`attributedBody` blobs).
+//!
+//! Messages stores rich text as an archived NSAttributedString: a header
+//! (`04 0B "streamtyped" 81 E8 03`), then a flat stream of typed groups.
+//! Each group is a type-encoding string (e.g. `@`, `+`, `iI`) followed by
+//! one value per type char. Objects (`@`) open with a class chain and their
+//! contents are the groups that follow, up to a matching END byte.
+//!
+//! Type strings and class names share one reference table; objects and
+//! classes share another. A byte >= 0x92 in either position is a reference
+//! (`index = byte - 0x92`) to an earlier entry.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial decoder (class chains, 0x81/0x82 lengths, attribute runs)
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+
+const I_16: u8 = 0x81;
+const I_32: u8 = 0x82;
+const DECIMAL: u8 = 0x83;
+const START: u8 = 0x84;
+const EMPTY: u8 = 0x85;
+const END: u8 = 0x86;
+const REFERENCE_TAG: u64 = 0x92;
+
+/// Attribute key for @-mentions (value: the mentioned handle).
+const MENTION_KEY: &str = "__kIMMentionConfirmedMention";
+
+/// Attribute key for inline links (value: NSURL).
+const LINK_KEY: &str = "__kIMLinkAttributeName";
+
+/// How deep string lookups follow nested objects (NSURL -> NSString).
+const MAX_STRING_DEPTH: usize = 4;
+
+/// A run of text sharing one attribute dictionary.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttributeRun {
+    /// Offset into the text in UTF-16 code units, as NSAttributedString counts.
+    pub start: usize,
+    /// Length in UTF-16 code units.
+    pub length: usize,
+    /// The text covered by this run.
+    pub text: String,
+    /// Handle of a confirmed @-mention.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mention: Option<String>,
+    /// URL of an inline link.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+/// Plain text plus attribute runs decoded from an attributedBody blob.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedBody {
+    pub text: String,
+    pub runs: Vec<AttributeRun>,
+}
+
+impl DecodedBody {
+    /// Handles mentioned in the message, in order.
+    pub fn mentions(&self) -> Vec<&str> {
+        self.runs.iter().filter_map(|r| r.mention.as_deref()).collect()
+    }
+
+    /// Inline link URLs, in order.
+    pub fn links(&self) -> Vec<&str> {
+        self.runs.iter().filter_map(|r| r.link.as_deref()).collect()
+    }
+}
+
+/// A decoded value inside a group.
+#[derive(Debug, Clone)]
+enum Field {
+    Int(i64),
+    Str(String),
+    /// Index into the object table; None for nil.
+    Object(Option<usize>),
+    /// Values that are read past but not kept (floats).
+    Other,
+}
+
+/// Entry in the shared object table.
+#[derive(Debug, Clone)]
+enum Archived {
+    Class(String),
+    Object { class: String, values: Vec<Field> },
+    /// Embedded type encodings take a slot too.
+    Type,
+    /// Reserved before the object's class chain is read.
+    Placeholder,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    idx: usize,
+    types: Vec<Vec<u8>>,
+    objects: Vec<Archived>,
+    seen_embedded: HashSet<usize>,
+    /// Objects whose contents are still being read, innermost last.
+    open: Vec<usize>,
+    /// Values read outside any object (normally just the root).
+    top_level: Vec<Field>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            idx: 0,
+            types: Vec::new(),
+            objects: Vec::new(),
+            seen_embedded: HashSet::new(),
+            open: Vec::new(),
+            top_level: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Result<u8> {
+        self.data
+            .get(self.idx)
+            .copied()
+            .ok_or_else(|| anyhow!("typedstream: unexpected end at {}", self.idx))
+    }
+
+    fn read_byte(&mut self) -> Result<u8> {
+        let b = self.peek()?;
+        self.idx += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self
+            .idx
+            .checked_add(n)
+            .filter(|&e| e <= self.data.len())
+            .ok_or_else(|| anyhow!("typedstream: {} bytes past end at {}", n, self.idx))?;
+        let bytes = &self.data[self.idx..end];
+        self.idx = end;
+        Ok(bytes)
+    }
+
+    fn read_signed(&mut self) -> Result<i64> {
+        Ok(match self.read_byte()? {
+            I_16 => i16::from_le_bytes(self.take(2)?.try_into()?) as i64,
+            I_32 => i32::from_le_bytes(self.take(4)?.try_into()?) as i64,
+            b => b as i8 as i64,
+        })
+    }
+
+    fn read_unsigned(&mut self) -> Result<u64> {
+        Ok(match self.read_byte()? {
+            I_16 => u16::from_le_bytes(self.take(2)?.try_into()?) as u64,
+            I_32 => u32::from_le_bytes(self.take(4)?.try_into()?) as u64,
+            b => b as u64,
+        })
+    }
+
+    fn read_float(&mut self, double: bool) -> Result<f64> {
+        if self.peek()? != DECIMAL {
+            return Ok(self.read_signed()? as f64);
+        }
+        self.idx += 1;
+        Ok(if double {
+            f64::from_le_bytes(self.take(8)?.try_into()?)
+        } else {
+            f32::from_le_bytes(self.take(4)?.try_into()?) as f64
+        })
+    }
+
+    fn read_pointer(&mut self) -> Result<usize> {
+        let raw = self.read_unsigned()?;
+        raw.checked_sub(REFERENCE_TAG)
+            .map(|i| i as usize)
+            .ok_or_else(|| anyhow!("typedstream: bad reference {:#x} at {}", raw, self.idx))
+    }
+
+    fn read_header(&mut self) -> Result<()> {
+        let version = self.read_byte()?;
+        let signature_len = self.read_unsigned()? as usize;
+        let signature = self.take(signature_len)?;
+        if version != 4 || (signature != b"streamtyped" && signature != b"typedstream") {
+            bail!("typedstream: bad header");
+        }
+        // System version (1000 on macOS)
+        self.read_signed()?;
+        Ok(())
+    }
+
+    /// Read a type-encoding string: new (START + length + bytes) or a reference.
+    fn read_type(&mut self, embedded: bool) -> Result<Vec<u8>> {
+        if self.peek()? == START {
+            self.idx += 1;
+            let length = self.read_unsigned()? as usize;
+            let encoding = self.take(length)?.to_vec();
+            if embedded {
+                self.seen_embedded.insert(self.types.len());
+                self.objects.push(Archived::Type);
+            }
+            self.types.push(encoding.clone());
+            return Ok(encoding);
+        }
+
+        // Repeated reference bytes (seen in embedded NSNumber values) collapse to one
+        let current = self.peek()?;
+        while self.data.get(self.idx + 1) == Some(&current) {
+            self.idx += 1;
+        }
+        let index = self.read_pointer()?;
+        let encoding = self
+            .types
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow!("typedstream: unknown type reference {}", index))?;
+        if embedded && self.seen_embedded.insert(index) {
+            self.objects.push(Archived::Type);
+        }
+        Ok(encoding)
+    }
+
+    fn class_at(&self, index: usize) -> Result<String> {
+        match self.objects.get(index) {
+            Some(Archived::Class(name)) => Ok(name.clone()),
+            _ => bail!("typedstream: reference {} is not a class", index),
+        }
+    }
+
+    /// Read a class chain, returning the most-derived class name.
+    fn read_class(&mut self) -> Result<String> {
+        match self.peek()? {
+            START => {
+                while self.peek()? == START {
+                    self.idx += 1;
+                }
+                let length = self.read_unsigned()?;
+                if length >= REFERENCE_TAG {
+                    return self.class_at((length - REFERENCE_TAG) as usize);
+                }
+                let name = String::from_utf8_lossy(self.take(length as usize)?).into_owned();
+                let _version = self.read_unsigned()?;
+                self.types.push(name.as_bytes().to_vec());
+                self.objects.push(Archived::Class(name.clone()));
+
+                // Superclass chain ends in EMPTY or a reference to a known class
+                self.read_class()?;
+                Ok(name)
+            }
+            EMPTY => {
+                self.idx += 1;
+                Ok(String::new())
+            }
+            _ => {
+                let index = self.read_pointer()?;
+                self.class_at(index)
+            }
+        }
+    }
+
+    /// Read an object value. New objects stay open until their END byte.
+    fn read_object(&mut self) -> Result<Option<usize>> {
+        match self.peek()? {
+            START => {
+                let index = self.objects.len();
+                self.objects.push(Archived::Placeholder);
+                let class = self.read_class()?;
+                self.objects[index] = Archived::Object { class, values: Vec::new() };
+                self.open.push(index);
+                Ok(Some(index))
+            }
+            EMPTY => {
+                self.idx += 1;
+                Ok(None)
+            }
+            _ => Ok(Some(self.read_pointer()?)),
+        }
+    }
+
+    /// Read one value per type char in `encoding`.
+    fn read_values(&mut self, encoding: &[u8]) -> Result<Vec<Field>> {
+        let mut fields = Vec::new();
+        let mut i = 0;
+        while i < encoding.len() {
+            match encoding[i] {
+                b'@' => fields.push(Field::Object(self.read_object()?)),
+                b'+' => {
+                    let length = self.read_unsigned()? as usize;
+                    let bytes = self.take(length)?;
+                    fields.push(Field::Str(String::from_utf8_lossy(bytes).into_owned()));
+                }
+                b'*' => {
+                    // Embedded data: START, then its own type and values
+                    self.idx += 1;
+                    let inner = self.read_type(true)?;
+                    fields.extend(self.read_values(&inner)?);
+                }
+                b'c' | b's' | b'i' | b'l' | b'q' => fields.push(Field::Int(self.read_signed()?)),
+                b'C' | b'S' | b'I' | b'L' | b'Q' => {
+                    fields.push(Field::Int(self.read_unsigned()? as i64))
+                }
+                b'f' | b'd' => {
+                    self.read_float(encoding[i] == b'd')?;
+                    fields.push(Field::Other);
+                }
+                b'[' => {
+                    // Byte arrays: "[<count>c]" (NSData payloads); skipped
+                    let close = encoding[i..]
+                        .iter()
+                        .position(|&b| b == b']')
+                        .map(|p| i + p)
+                        .ok_or_else(|| anyhow!("typedstream: unterminated array type"))?;
+                    let spec = &encoding[i + 1..close];
+                    let (count, element) = spec.split_at(spec.len().saturating_sub(1));
+                    if !matches!(element, b"c" | b"C") {
+                        bail!("typedstream: unsupported array type");
+                    }
+                    let count: usize = std::str::from_utf8(count)?.parse()?;
+                    self.take(count)?;
+                    i = close;
+                }
+                other => bail!("typedstream: unsupported type {:?}", other as char),
+            }
+            i += 1;
+        }
+        Ok(fields)
+    }
+
+    /// Read groups until the stream ends.
+    fn parse(&mut self) -> Result<()> {
+        self.read_header()?;
+        while self.idx < self.data.len() {
+            if self.peek()? == END {
+                self.idx += 1;
+                self.open.pop();
+                continue;
+            }
+
+            // Values belong to the innermost object open *before* this group
+            let parent = self.open.last().copied();
+            let encoding = self.read_type(false)?;
+            let fields = self.read_values(&encoding)?;
+            match parent.and_then(|p| self.objects.get_mut(p)) {
+                Some(Archived::Object { values, .. }) => values.extend(fields),
+                _ => self.top_level.extend(fields),
+            }
+        }
+        Ok(())
+    }
+
+    fn object(&self, index: usize) -> Option<(&str, &[Field])> {
+        match self.objects.get(index)? {
+            Archived::Object { class, values } => Some((class.as_str(), values.as_slice())),
+            _ => None,
+        }
+    }
+
+    /// First string inside an object, following nested objects (NSURL wraps NSString).
+    fn string_at(&self, index: usize, depth: usize) -> Option<String> {
+        if depth > MAX_STRING_DEPTH {
+            return None;
+        }
+        let (_, values) = self.object(index)?;
+        values.iter().find_map(|field| match field {
+            Field::Str(s) => Some(s.clone()),
+            Field::Object(Some(i)) => self.string_at(*i, depth + 1),
+            _ => None,
+        })
+    }
+
+    /// Key/value object pairs of an NSDictionary (after its entry count).
+    fn dictionary(&self, index: usize) -> Vec<(String, usize)> {
+        let Some((_, values)) = self.object(index) else {
+            return Vec::new();
+        };
+        let objects: Vec<usize> = values
+            .iter()
+            .filter_map(|f| match f {
+                Field::Object(Some(i)) => Some(*i),
+                _ => None,
+            })
+            .collect();
+        objects
+            .chunks_exact(2)
+            .filter_map(|pair| Some((self.string_at(pair[0], 0)?, pair[1])))
+            .collect()
+    }
+
+    fn body(&self) -> Option<DecodedBody> {
+        let root = self.top_level.iter().find_map(|f| match f {
+            Field::Object(Some(i)) => Some(*i),
+            _ => None,
+        })?;
+        let (class, values) = self.object(root)?;
+
+        // A bare NSString archive has no runs
+        if !class.contains("AttributedString") {
+            let text = self.string_at(root, 0)?;
+            return Some(DecodedBody { text, runs: Vec::new() });
+        }
+
+        let mut fields = values.iter();
+        let text = fields.by_ref().find_map(|f| match f {
+            Field::Object(Some(i)) => self.string_at(*i, 0),
+            _ => None,
+        })?;
+
+        // Remaining fields: (run index, run length) ints, then the run's dictionary
+        let utf16: Vec<u16> = text.encode_utf16().collect();
+        let mut runs = Vec::new();
+        let mut start = 0usize;
+        let mut ints: Vec<i64> = Vec::new();
+        for field in fields {
+            match field {
+                Field::Int(n) => ints.push(*n),
+                Field::Object(dict) => {
+                    let length = ints.last().copied().unwrap_or(0).max(0) as usize;
+                    ints.clear();
+
+                    let mut run = AttributeRun {
+                        start,
+                        length,
+                        text: slice_utf16(&utf16, start, length),
+                        mention: None,
+                        link: None,
+                    };
+                    for (key, value) in dict.map(|d| self.dictionary(d)).unwrap_or_default() {
+                        match key.as_str() {
+                            MENTION_KEY => run.mention = self.string_at(value, 0),
+                            LINK_KEY => run.link = self.string_at(value, 0),
+                            _ => {}
+                        }
+                    }
+                    runs.push(run);
+                    start += length;
+                }
+                _ => {}
+            }
+        }
+
+        Some(DecodedBody { text, runs })
+    }
+}
+
+fn slice_utf16(utf16: &[u16], start: usize, length: usize) -> String {
+    let start = start.min(utf16.len());
+    let end = start.saturating_add(length).min(utf16.len());
+    String::from_utf16_lossy(&utf16[start..end])
+}
+
+/// Decode an attributedBody typedstream into text and attribute runs.
+///
+/// Returns None when the blob isn't a typedstream or no text object was
+/// reached. A parse error after the text has been read still returns what
+/// was decoded, so one unsupported attribute doesn't lose the message.
+pub fn decode_attributed_body(blob: &[u8]) -> Option<DecodedBody> {
+    // Header is "04 0B streamtyped"; locate it in case of leading bytes
+    let signature = blob.windows(11).position(|w| w == b"streamtyped")?;
+    let start = signature.checked_sub(2)?;
+
+    let mut reader = Reader::new(&blob[start..]);
+    let _ = reader.parse();
+    reader.body().filter(|b| !b.text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Corpus of attributedBody blobs; expected outputs in expected.json.
+    const CORPUS: &[(&str, &[u8])] = &[
+        ("plain", include_bytes!("testdata/attributed_body/plain.bin")),
+        ("long", include_bytes!("testdata/attributed_body/long.bin")),
+        ("i32_length", include_bytes!("testdata/attributed_body/i32_length.bin")),
+        ("mention", include_bytes!("testdata/attributed_body/mention.bin")),
+        ("link", include_bytes!("testdata/attributed_body/link.bin")),
+        ("emoji_mention", include_bytes!("testdata/attributed_body/emoji_mention.bin")),
+        ("data_detected", include_bytes!("testdata/attributed_body/data_detected.bin")),
+        ("reused_attributes", include_bytes!("testdata/attributed_body/reused_attributes.bin")),
+    ];
+
+    #[test]
+    fn test_corpus() {
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("testdata/attributed_body/expected.json")).unwrap();
+        for (name, blob) in CORPUS {
+            let body = decode_attributed_body(blob).unwrap_or_else(|| panic!("{} did not decode", name));
+            assert_eq!(serde_json::to_value(&body).unwrap(), expected[*name], "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_long_text_uses_multibyte_length() {
+        let (_, blob) = CORPUS.iter().find(|(n, _)| *n == "long").unwrap();
+        let body = decode_attributed_body(blob).unwrap();
+        assert!(body.text.len() > 127);
+        assert!(body.text.ends_with("END"));
+    }
+
+    #[test]
+    fn test_mentions_and_links() {
+        let (_, blob) = CORPUS.iter().find(|(n, _)| *n == "mention").unwrap();
+        assert_eq!(decode_attributed_body(blob).unwrap().mentions(), vec!["+14155551234"]);
+
+        let (_, blob) = CORPUS.iter().find(|(n, _)| *n == "link").unwrap();
+        assert_eq!(
+            decode_attributed_body(blob).unwrap().links(),
+            vec!["https://example.com/menu"]
+        );
+    }
+
+    #[test]
+    fn test_truncated_blob_keeps_text() {
+        let (_, blob) = CORPUS.iter().find(|(n, _)| *n == "mention").unwrap();
+        // Cut inside the attribute dictionaries
+        let cut = &blob[..blob.len() - 12];
+        let body = decode_attributed_body(cut).unwrap();
+        assert_eq!(body.text, "Hey @Sarah are you coming?");
+    }
+
+    #[test]
+    fn test_not_typedstream() {
+        assert_eq!(decode_attributed_body(b"bplist00 garbage"), None);
+        assert_eq!(decode_attributed_body(&[]), None);
+    }
+}
@@ -0,0 +1,214 @@
+//! Draft queue (~/.wolfies-imessage/drafts.json) for review-first sending.
+//!
+//! Automation composes with `draft add`; a person reviews with `draft list`
+//! and releases with `draft send`. Sent drafts move to the file's `sent` log
+//! in the same atomic write. A draft is claimed (removed from the queue)
+//! before it is sent and put back only if the send fails, so it is
+//! attempted at most once even if two `draft send` runs overlap.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Path comes from the data file registry (data_files::DRAFTS)
+//! - 10/16/2026 - Initial draft store (add, list, delete, claim/restore, sent log)
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::data_files;
+use crate::storage::{self, FileLock};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Draft {
+    pub id: String,
+    /// Contact as given to `draft add` (name or phone); re-resolved at send time
+    pub contact: String,
+    /// Phone the contact resolved to when drafted
+    pub phone: String,
+    /// Contact name the phone belongs to, if it's in contacts
+    pub contact_name: Option<String>,
+    pub message: String,
+    pub created_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SentDraft {
+    #[serde(flatten)]
+    pub draft: Draft,
+    /// Phone actually sent to (differs from `phone` if resolution changed)
+    pub sent_to: String,
+    pub sent_at: DateTime<Local>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DraftsFile {
+    drafts: Vec<Draft>,
+    #[serde(default)]
+    sent: Vec<SentDraft>,
+}
+
+/// Draft store backed by a JSON file.
+pub struct DraftStore {
+    path: PathBuf,
+}
+
+impl DraftStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Store at the default location.
+    pub fn open_default() -> Self {
+        Self::new(data_files::DRAFTS.path())
+    }
+
+    /// Run a read-modify-write cycle under the file lock.
+    fn update<R>(&self, f: impl FnOnce(&mut DraftsFile) -> R) -> Result<R> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let mut file: DraftsFile = storage::read_json(&self.path)?;
+        let result = f(&mut file);
+        storage::write_json_atomic(&self.path, &file)?;
+        Ok(result)
+    }
+
+    fn read(&self) -> Result<DraftsFile> {
+        let _lock = FileLock::acquire(&self.path)?;
+        storage::read_json(&self.path)
+    }
+
+    /// Pending drafts, oldest first.
+    pub fn list(&self) -> Result<Vec<Draft>> {
+        let mut drafts = self.read()?.drafts;
+        drafts.sort_by_key(|d| d.created_at);
+        Ok(drafts)
+    }
+
+    /// Sent log, oldest first.
+    pub fn sent(&self) -> Result<Vec<SentDraft>> {
+        Ok(self.read()?.sent)
+    }
+
+    /// Queue a draft.
+    pub fn add(&self, contact: &str, phone: &str, contact_name: Option<&str>, message: &str) -> Result<Draft> {
+        let draft = Draft {
+            id: uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+            contact: contact.to_string(),
+            phone: phone.to_string(),
+            contact_name: contact_name.map(str::to_string),
+            message: message.to_string(),
+            created_at: Local::now(),
+        };
+        let stored = draft.clone();
+        self.update(move |file| file.drafts.push(stored))?;
+        Ok(draft)
+    }
+
+    /// Discard a pending draft.
+    pub fn delete(&self, id: &str) -> Result<Draft> {
+        self.update(|file| {
+            let idx = position(&file.drafts, id)?;
+            Ok(file.drafts.remove(idx))
+        })?
+    }
+
+    /// Take drafts out of the queue for sending: the given ids, or every
+    /// draft when `ids` is None. Unknown ids fail before anything is taken.
+    pub fn claim(&self, ids: Option<&[String]>) -> Result<Vec<Draft>> {
+        self.update(|file| match ids {
+            None => {
+                let mut claimed = std::mem::take(&mut file.drafts);
+                claimed.sort_by_key(|d| d.created_at);
+                Ok(claimed)
+            }
+            Some(ids) => {
+                for id in ids {
+                    position(&file.drafts, id)?;
+                }
+                let mut claimed = Vec::with_capacity(ids.len());
+                for id in ids {
+                    // Repeated ids were already taken on their first occurrence
+                    if let Ok(idx) = position(&file.drafts, id) {
+                        claimed.push(file.drafts.remove(idx));
+                    }
+                }
+                Ok(claimed)
+            }
+        })?
+    }
+
+    /// Put a claimed draft back after a failed send (same id and created_at).
+    pub fn restore(&self, draft: Draft) -> Result<()> {
+        self.update(|file| file.drafts.push(draft))
+    }
+
+    /// Log a claimed draft as sent.
+    pub fn record_sent(&self, draft: Draft, sent_to: &str, sent_at: DateTime<Local>) -> Result<()> {
+        let entry = SentDraft {
+            draft,
+            sent_to: sent_to.to_string(),
+            sent_at,
+        };
+        self.update(|file| file.sent.push(entry))
+    }
+}
+
+fn position(drafts: &[Draft], id: &str) -> Result<usize> {
+    drafts
+        .iter()
+        .position(|d| d.id == id)
+        .ok_or_else(|| anyhow!("No draft with id '{}'", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> (tempfile::TempDir, DraftStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DraftStore::new(dir.path().join("drafts.json"));
+        (dir, store)
+    }
+
+    #[test]
+    fn test_ids_stable_across_list_and_delete() {
+        let (_dir, store) = store();
+        let a = store.add("Alice", "+14155550001", Some("Alice"), "first").unwrap();
+        let b = store.add("+14155550002", "+14155550002", None, "second").unwrap();
+
+        let listed = store.list().unwrap();
+        assert_eq!(listed, vec![a.clone(), b.clone()]);
+        assert_eq!(store.list().unwrap()[1].id, b.id);
+
+        assert_eq!(store.delete(&a.id).unwrap().message, "first");
+        assert!(store.delete(&a.id).is_err());
+        assert_eq!(store.list().unwrap(), vec![b]);
+    }
+
+    #[test]
+    fn test_claim_restore_and_sent_log() {
+        let (_dir, store) = store();
+        let a = store.add("Alice", "+1", None, "a").unwrap();
+        let b = store.add("Bob", "+2", None, "b").unwrap();
+
+        // Unknown id: nothing is taken
+        assert!(store.claim(Some(&[a.id.clone(), "nope".to_string()])).is_err());
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        let claimed = store.claim(Some(std::slice::from_ref(&b.id))).unwrap();
+        assert_eq!(claimed, vec![b.clone()]);
+        assert_eq!(store.list().unwrap(), vec![a.clone()]);
+        store.restore(claimed.into_iter().next().unwrap()).unwrap();
+
+        let all = store.claim(None).unwrap();
+        assert_eq!(all.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(), [a.id.as_str(), b.id.as_str()]);
+        assert!(store.list().unwrap().is_empty());
+
+        store.record_sent(all[0].clone(), "+9", Local::now()).unwrap();
+        let sent = store.sent().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!((sent[0].draft.id.as_str(), sent[0].sent_to.as_str()), (a.id.as_str(), "+9"));
+    }
+}
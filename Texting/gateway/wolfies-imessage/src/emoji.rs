@@ -0,0 +1,181 @@
+//! Emoji counting for `analytics --emoji`: segmentation, emoji check, counter.
+//!
+//! Text is split into extended grapheme clusters so a skin-toned thumbs-up,
+//! a ZWJ family, or a flag counts as one emoji rather than several code
+//! points. No database access here; the command streams text in via
+//! `EmojiCounter::add`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial grapheme-based emoji detection and counter
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use unicode_properties::{EmojiStatus, UnicodeEmoji};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Emoji reported per side of the conversation.
+pub const TOP_EMOJI: usize = 20;
+
+/// An emoji and how often it appeared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct EmojiCount {
+    pub emoji: String,
+    pub count: usize,
+}
+
+fn is_skin_tone_modifier(c: char) -> bool {
+    matches!(c, '\u{1F3FB}'..='\u{1F3FF}')
+}
+
+/// Whether a grapheme cluster renders as an emoji.
+///
+/// Emoji-presentation characters (😂, 🇺🇸) always count. Text-default ones
+/// (❤, ☝, digits) only count when followed by U+FE0F, a keycap, a skin tone,
+/// or a ZWJ; U+FE0E forces text presentation.
+pub fn is_emoji(cluster: &str) -> bool {
+    let mut chars = cluster.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if cluster.contains('\u{FE0E}') {
+        return false;
+    }
+    match first.emoji_status() {
+        EmojiStatus::EmojiPresentation
+        | EmojiStatus::EmojiPresentationAndModifierBase
+        | EmojiStatus::EmojiPresentationAndEmojiComponent
+        | EmojiStatus::EmojiPresentationAndModifierAndEmojiComponent => true,
+        _ if first.is_emoji_char() => {
+            chars.any(|c| matches!(c, '\u{FE0F}' | '\u{20E3}' | '\u{200D}') || is_skin_tone_modifier(c))
+        }
+        _ => false,
+    }
+}
+
+/// Emoji in `text`, one entry per grapheme cluster.
+pub fn emoji_in(text: &str) -> Vec<&str> {
+    text.graphemes(true).filter(|g| is_emoji(g)).collect()
+}
+
+/// Counts emoji across messages.
+#[derive(Debug, Default)]
+pub struct EmojiCounter {
+    counts: HashMap<String, usize>,
+    total: usize,
+    messages: usize,
+}
+
+impl EmojiCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one message's emoji.
+    pub fn add(&mut self, text: &str) {
+        self.messages += 1;
+        for emoji in emoji_in(text) {
+            *self.counts.entry(emoji.to_string()).or_insert(0) += 1;
+            self.total += 1;
+        }
+    }
+
+    /// Emoji seen across all messages added.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Messages added (with or without emoji).
+    pub fn messages(&self) -> usize {
+        self.messages
+    }
+
+    /// Emoji per 100 messages, rounded to 1 decimal.
+    pub fn per_100_messages(&self) -> f64 {
+        if self.messages == 0 {
+            return 0.0;
+        }
+        (self.total as f64 * 100.0 / self.messages as f64 * 10.0).round() / 10.0
+    }
+
+    /// The `n` most frequent emoji, ties broken by the emoji string.
+    pub fn top(&self, n: usize) -> Vec<EmojiCount> {
+        let mut emoji: Vec<EmojiCount> = self
+            .counts
+            .iter()
+            .map(|(e, &count)| EmojiCount { emoji: e.clone(), count })
+            .collect();
+        emoji.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.emoji.cmp(&b.emoji)));
+        emoji.truncate(n);
+        emoji
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_has_no_emoji() {
+        assert!(emoji_in("Hello, world! 123 #1 *ok* © ™").is_empty());
+        assert!(emoji_in("").is_empty());
+    }
+
+    #[test]
+    fn test_simple_emoji() {
+        assert_eq!(emoji_in("lol 😂😂 ok 🎉"), vec!["😂", "😂", "🎉"]);
+    }
+
+    #[test]
+    fn test_skin_tone_modifier_is_one_emoji() {
+        assert_eq!(emoji_in("👍🏽 nice"), vec!["👍🏽"]);
+        // Text-default base made emoji by its modifier
+        assert_eq!(emoji_in("☝🏿"), vec!["☝🏿"]);
+    }
+
+    #[test]
+    fn test_zwj_sequences_are_one_emoji() {
+        let family = "👨\u{200D}👩\u{200D}👧\u{200D}👦";
+        assert_eq!(emoji_in(&format!("the {} is here", family)), vec![family]);
+        let technologist = "👩🏻\u{200D}💻";
+        assert_eq!(emoji_in(technologist), vec![technologist]);
+        let rainbow_flag = "🏳\u{FE0F}\u{200D}🌈";
+        assert_eq!(emoji_in(rainbow_flag), vec![rainbow_flag]);
+    }
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(emoji_in("🇺🇸🇯🇵"), vec!["🇺🇸", "🇯🇵"]);
+        let england = "🏴\u{E0067}\u{E0062}\u{E0065}\u{E006E}\u{E0067}\u{E007F}";
+        assert_eq!(emoji_in(england), vec![england]);
+    }
+
+    #[test]
+    fn test_variation_selectors_and_keycaps() {
+        // ❤ and ‼ are text-default; FE0F makes them emoji, FE0E keeps them text
+        assert_eq!(emoji_in("❤\u{FE0F} ‼\u{FE0F}"), vec!["❤\u{FE0F}", "‼\u{FE0F}"]);
+        assert!(emoji_in("❤ ❤\u{FE0E}").is_empty());
+        assert_eq!(emoji_in("1\u{FE0F}\u{20E3} then 2"), vec!["1\u{FE0F}\u{20E3}"]);
+    }
+
+    #[test]
+    fn test_counter_ranks_and_rates() {
+        let mut counter = EmojiCounter::new();
+        counter.add("😂😂");
+        counter.add("👍🏽 👍🏽 😂");
+        counter.add("no emoji here");
+        counter.add("🎉");
+
+        assert_eq!(counter.messages(), 4);
+        assert_eq!(counter.total(), 6);
+        assert_eq!(counter.per_100_messages(), 150.0);
+        assert_eq!(
+            counter.top(2),
+            vec![
+                EmojiCount { emoji: "😂".into(), count: 3 },
+                EmojiCount { emoji: "👍🏽".into(), count: 2 },
+            ]
+        );
+        assert_eq!(EmojiCounter::new().per_100_messages(), 0.0);
+    }
+}
@@ -0,0 +1,113 @@
+//! CLI error types that map to distinct process exit codes.
+//!
+//! Most failures surface as plain `anyhow::Error` (exit code 1). Errors that
+//! callers need to tell apart in scripts are wrapped in `CliError`, which
+//! main() downcasts to pick the exit code.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Added AutomationDenied (exit code 6) and error codes for JSON output
+//! - 10/16/2026 - Added RateLimited (exit code 5)
+//! - 10/16/2026 - Added DaemonUnavailable (exit code 4)
+//! - 10/16/2026 - Initial CliError with partial-failure exit code
+
+use thiserror::Error;
+
+/// Exit code for generic failures.
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit code when some, but not necessarily all, items of a batch failed.
+/// (2 is reserved by clap for usage errors.)
+pub const EXIT_PARTIAL_FAILURE: u8 = 3;
+/// Exit code when a daemon-backed command can't reach the daemon.
+pub const EXIT_DAEMON_UNAVAILABLE: u8 = 4;
+/// Exit code when a send was refused by the send rate limit.
+pub const EXIT_RATE_LIMITED: u8 = 5;
+/// Exit code when macOS denies Automation (Apple events) access to Messages.
+pub const EXIT_AUTOMATION_DENIED: u8 = 6;
+
+/// Where to grant Automation access, shown with AUTOMATION_DENIED.
+pub const AUTOMATION_GUIDANCE: &str = "open System Settings → Privacy & Security → Automation and enable \
+     Messages for your terminal app, then retry";
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    /// A batch operation where at least one item failed.
+    #[error("{failed} of {total} operations failed")]
+    PartialFailure { failed: usize, total: usize },
+
+    /// DAEMON_UNAVAILABLE: socket missing, connection refused, or auto-start failed.
+    #[error(
+        "Daemon unavailable ({reason}). Start it with `wolfies-imessage-daemon start` or pass --auto-start-daemon"
+    )]
+    DaemonUnavailable { reason: String },
+
+    /// RATE_LIMITED: a send would exceed the `[send]` rate limit.
+    #[error("RATE_LIMITED: {reason}; retry after {retry_after_secs}s (or pass --force-send)")]
+    RateLimited { retry_after_secs: u64, reason: String },
+
+    /// AUTOMATION_DENIED: osascript was refused Apple events to Messages (-1743).
+    #[error("AUTOMATION_DENIED: macOS blocked this app from controlling Messages ({detail}); {guidance}", guidance = AUTOMATION_GUIDANCE)]
+    AutomationDenied { detail: String },
+}
+
+impl CliError {
+    /// Process exit code for this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::PartialFailure { .. } => EXIT_PARTIAL_FAILURE,
+            CliError::DaemonUnavailable { .. } => EXIT_DAEMON_UNAVAILABLE,
+            CliError::RateLimited { .. } => EXIT_RATE_LIMITED,
+            CliError::AutomationDenied { .. } => EXIT_AUTOMATION_DENIED,
+        }
+    }
+
+    /// Stable code for JSON output (`code` fields, daemon error responses).
+    pub fn code(&self) -> &'static str {
+        match self {
+            CliError::PartialFailure { .. } => "PARTIAL_FAILURE",
+            CliError::DaemonUnavailable { .. } => "DAEMON_UNAVAILABLE",
+            CliError::RateLimited { .. } => "RATE_LIMITED",
+            CliError::AutomationDenied { .. } => "AUTOMATION_DENIED",
+        }
+    }
+}
+
+/// The `CliError` code for any command error, if it carries one.
+pub fn error_code(err: &anyhow::Error) -> Option<&'static str> {
+    err.downcast_ref::<CliError>().map(CliError::code)
+}
+
+/// Resolve the exit code for any command error.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    err.downcast_ref::<CliError>()
+        .map(CliError::exit_code)
+        .unwrap_or(EXIT_FAILURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let partial = anyhow::Error::new(CliError::PartialFailure { failed: 1, total: 3 });
+        assert_eq!(exit_code(&partial), EXIT_PARTIAL_FAILURE);
+        let unavailable = anyhow::Error::new(CliError::DaemonUnavailable {
+            reason: "Socket not found: /tmp/x.sock".into(),
+        });
+        assert_eq!(exit_code(&unavailable), EXIT_DAEMON_UNAVAILABLE);
+        let limited = anyhow::Error::new(CliError::RateLimited {
+            retry_after_secs: 30,
+            reason: "3 sends to +14155550001 in the last 5 minutes".into(),
+        })
+        .context("Failed to send message");
+        assert_eq!(exit_code(&limited), EXIT_RATE_LIMITED);
+        let denied = anyhow::Error::new(CliError::AutomationDenied {
+            detail: "Not authorized to send Apple events to Messages. (-1743)".into(),
+        })
+        .context("Failed to send message");
+        assert_eq!(exit_code(&denied), EXIT_AUTOMATION_DENIED);
+        assert_eq!(error_code(&denied), Some("AUTOMATION_DENIED"));
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), EXIT_FAILURE);
+        assert_eq!(error_code(&anyhow::anyhow!("boom")), None);
+    }
+}
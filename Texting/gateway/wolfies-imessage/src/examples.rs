@@ -0,0 +1,675 @@
+//! Usage examples: each subcommand's `--help` epilogue and the `examples`
+//! cookbook.
+//!
+//! Every block is a constant attached to its subcommand with
+//! `#[command(after_help = ...)]` and listed in `EXAMPLES` under a topic;
+//! `examples [topic]` prints from the same list, so help and cookbook can't
+//! drift. tests/examples.rs checks every subcommand has a block.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - send examples with --message-file and --stdin
+//! - 10/16/2026 - analytics example with --streaks
+//! - 10/16/2026 - maintenance examples
+//! - 10/16/2026 - raw-message examples
+//! - 10/16/2026 - analytics example with --start/--end
+//! - 10/16/2026 - analytics example with --compare
+//! - 10/16/2026 - messages and group-messages examples with --with-reactions
+//! - 10/16/2026 - bundle example with --max-tokens
+//! - 10/16/2026 - Initial example blocks, topics, and cookbook rendering
+
+use anyhow::{anyhow, Result};
+
+/// Cookbook section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    Reading,
+    Sending,
+    Analytics,
+    Llm,
+    Daemon,
+    Setup,
+}
+
+impl Topic {
+    /// In cookbook order.
+    pub const ALL: [Topic; 6] =
+        [Topic::Reading, Topic::Sending, Topic::Analytics, Topic::Llm, Topic::Daemon, Topic::Setup];
+
+    /// Name accepted by `examples <topic>`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Topic::Reading => "reading",
+            Topic::Sending => "sending",
+            Topic::Analytics => "analytics",
+            Topic::Llm => "llm",
+            Topic::Daemon => "daemon",
+            Topic::Setup => "setup",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Topic::Reading => "Reading messages",
+            Topic::Sending => "Sending",
+            Topic::Analytics => "Analytics and follow-ups",
+            Topic::Llm => "LLM workloads",
+            Topic::Daemon => "Daemon and semantic search",
+            Topic::Setup => "Setup and maintenance",
+        }
+    }
+}
+
+/// One subcommand's examples (or, for the daemon binary, a program's).
+#[derive(Debug, Clone, Copy)]
+pub struct Examples {
+    /// Subcommand name as typed, or another binary's name.
+    pub command: &'static str,
+    pub topic: Topic,
+    /// "Examples:" heading, then `# comment` / invocation pairs.
+    pub text: &'static str,
+}
+
+impl Examples {
+    /// The block without its heading.
+    pub fn body(&self) -> &'static str {
+        self.text.strip_prefix(HEADING).unwrap_or(self.text)
+    }
+}
+
+const HEADING: &str = "Examples:\n";
+
+// Reading
+
+pub const FIND: &str = "Examples:
+  # Messages with Alice that mention dinner
+  wolfies-imessage find Alice --query dinner
+  # Only the SMS side of a contact's history
+  wolfies-imessage find \"+14155551234\" --service sms --limit 50
+  # Dates and texts only, via jq
+  wolfies-imessage find Alice --json | jq -r '.[] | \"\\(.date) \\(.text)\"'
+";
+
+pub const MESSAGES: &str = "Examples:
+  # Latest 20 messages with Alice
+  wolfies-imessage messages Alice
+  # Notes to self
+  wolfies-imessage messages self --limit 100
+  # Compact JSON with just the fields an agent needs
+  wolfies-imessage messages Alice --json --compact --fields date,is_from_me,text
+  # Each message with its tapbacks
+  wolfies-imessage messages Alice --with-reactions --json
+";
+
+pub const RECENT: &str = "Examples:
+  # The ten most recent messages anywhere
+  wolfies-imessage recent
+  # Latest message per conversation, family only
+  wolfies-imessage recent --per-conversation --relationship family
+  # Senders Messages would file under Unknown Senders
+  wolfies-imessage recent --unknown-only --json | jq -r '.[].phone' | sort -u
+";
+
+pub const UNREAD: &str = "Examples:
+  # Unread messages, newest first
+  wolfies-imessage unread
+  # Just the number, for a status bar
+  wolfies-imessage unread --count-only --json | jq .unread_count
+  # Unread count per conversation
+  wolfies-imessage unread --by-conversation
+";
+
+pub const QUICK: &str = "Examples:
+  # Pinned contacts and groups (config.toml `pinned`)
+  wolfies-imessage quick
+  # Pins with unread messages
+  wolfies-imessage quick --json | jq -r '.pins[] | select(.unread_count > 0) | .name // .pin'
+";
+
+pub const WATCH: &str = "Examples:
+  # Print new messages as they arrive
+  wolfies-imessage watch
+  # Desktop notifications for two contacts
+  wolfies-imessage watch --notify --filter-contacts Alice,Bob
+  # One JSON event per line, for a script
+  wolfies-imessage watch --json --known-only | jq -c '{sender: (.sender_name // .sender_handle), text}'
+";
+
+pub const TEXT_SEARCH: &str = "Examples:
+  # Every message mentioning a flight, last 90 days
+  wolfies-imessage text-search flight --days 90
+  # Messages with all of the terms, in one group
+  wolfies-imessage text-search --all passport,visa --group \"Weekend Trip\"
+  # Who mentioned it, via jq
+  wolfies-imessage text-search invoice --json | jq -r '.[].display_name' | sort | uniq -c
+";
+
+pub const THREAD: &str = "Examples:
+  # The reply tree around a message (guid from --json output)
+  wolfies-imessage thread --guid 5C1D1E7A-0F2B-4B8E-9A63-2C4D0E9F1A11
+  # First 50 messages of the tree
+  wolfies-imessage thread --guid 5C1D1E7A-0F2B-4B8E-9A63-2C4D0E9F1A11 --limit 50
+";
+
+pub const GROUPS: &str = "Examples:
+  # Group chats, most recently active first
+  wolfies-imessage groups
+  # Group ids, for group-messages and mute
+  wolfies-imessage groups --json | jq -r '.[] | \"\\(.group_id)\\t\\(.display_name)\"'
+";
+
+pub const GROUP_MESSAGES: &str = "Examples:
+  # Latest messages in one group
+  wolfies-imessage group-messages --group-id chat123456789
+  # Only what Bob said there
+  wolfies-imessage group-messages --group-id chat123456789 --participant \"+14155550102\"
+  # Who reacted to what
+  wolfies-imessage group-messages --group-id chat123456789 --with-reactions
+";
+
+pub const CHAT_INFO: &str = "Examples:
+  # Participants, service, and message span of a group
+  wolfies-imessage chat-info \"Weekend Trip\"
+  # Every chat a contact is in
+  wolfies-imessage chat-info Alice --json
+";
+
+pub const ATTACHMENTS: &str = "Examples:
+  # Recent photos from Alice
+  wolfies-imessage attachments Alice --type image/
+  # Copy them out with readable names, skipping duplicates
+  wolfies-imessage attachments Alice --type image/ --copy-to ~/Pictures/alice --dedupe
+  # Total size of attachments, via jq
+  wolfies-imessage attachments --limit 500 --json | jq '[.[].total_bytes // 0] | add'
+";
+
+pub const REACTIONS: &str = "Examples:
+  # Tapbacks in the conversation with Alice
+  wolfies-imessage reactions Alice
+  # Count by kind
+  wolfies-imessage reactions --limit 500 --json | jq -r '.[].reaction_type' | sort | uniq -c
+";
+
+pub const LINKS: &str = "Examples:
+  # Links shared in the last 30 days
+  wolfies-imessage links
+  # Most-shared links with Bob, ever, with page titles
+  wolfies-imessage links Bob --all-time --sort frequency --fetch-titles
+  # Just the URLs
+  wolfies-imessage links --json | jq -r '.[].url'
+";
+
+pub const VOICE: &str = "Examples:
+  # Voice messages with file paths
+  wolfies-imessage voice Alice
+  # Paths only, e.g. for a transcriber
+  wolfies-imessage voice --json | jq -r '.[].attachment_path'
+";
+
+pub const DELETED: &str = "Examples:
+  # Messages still recoverable from Recently Deleted
+  wolfies-imessage deleted
+  # Only the conversation with Alice, last week
+  wolfies-imessage deleted --contact Alice --days 7
+";
+
+pub const COMPARE: &str = "Examples:
+  # What changed since a Time Machine copy
+  wolfies-imessage compare --other ~/Backups/chat.db
+  # The messages themselves, last 30 days, into a file
+  wolfies-imessage compare --other ~/Backups/chat.db --days 30 --show-messages > diff.txt
+";
+
+// Sending
+
+pub const SEND: &str = "Examples:
+  # Send to a contact by name
+  wolfies-imessage send Alice \"Running 10 minutes late\"
+  # Check who it would go to without sending
+  wolfies-imessage send Alice \"See you at 7\" --dry-run
+  # Schedule it for a local time instead of sending now
+  wolfies-imessage send Bob \"Happy birthday!\" --at 2026-01-12T09:00
+  # Send a multi-line message exactly as written
+  wolfies-imessage send Alice --message-file note.txt
+";
+
+pub const SEND_BY_PHONE: &str = "Examples:
+  # Send to a number not in contacts
+  wolfies-imessage send-by-phone \"+14155551234\" \"Hi, it's Sam from the meetup\"
+  # Send to an email handle in two hours
+  wolfies-imessage send-by-phone sam@example.com \"Reminder: slides due\" --in 2h
+  # Pipe the message in
+  printf 'Line one\\nLine two' | wolfies-imessage send-by-phone \"+14155551234\" --stdin
+";
+
+pub const MARK_READ: &str = "Examples:
+  # Mark the conversation with Alice read
+  wolfies-imessage mark-read Alice
+  # Mark a group read
+  wolfies-imessage mark-read --group-id chat123456789
+  # Everything
+  wolfies-imessage mark-read --all
+";
+
+pub const SEND_LOG: &str = "Examples:
+  # Sends per recipient, last 7 days
+  wolfies-imessage send-log --days 7
+  # Busiest recipients
+  wolfies-imessage send-log --json | jq '.recipients | sort_by(-.sent) | .[:5]'
+";
+
+pub const SCHEDULED: &str = "Examples:
+  # Pending scheduled sends
+  wolfies-imessage scheduled
+  # Cancel one by id
+  wolfies-imessage scheduled cancel 3f9a1c2e
+";
+
+pub const DRAFT: &str = "Examples:
+  # Queue a message for review
+  wolfies-imessage draft add Alice \"Can we move dinner to 8?\"
+  # Review, then send one or discard it
+  wolfies-imessage draft list
+  wolfies-imessage draft send 8c1e4b7d
+  wolfies-imessage draft delete 2a6f09e3
+";
+
+// Analytics
+
+pub const ANALYTICS: &str = "Examples:
+  # Totals, busiest times, and top contacts over 30 days
+  wolfies-imessage analytics
+  # Weekly volume with Alice this year
+  wolfies-imessage analytics Alice --days 365 --series weekly
+  # This week against last week
+  wolfies-imessage analytics --days 7 --compare
+  # How much we talked in 2023
+  wolfies-imessage analytics Alice --start 2023-01-01 --end 2023-12-31
+  # Days in a row we've texted, and since when
+  wolfies-imessage analytics Alice --streaks --all-time
+";
+
+pub const DIGEST: &str = "Examples:
+  # Yesterday's briefing
+  wolfies-imessage digest
+  # Since Monday, work contacts only
+  wolfies-imessage digest --since 2026-01-05 --relationship work
+  # Unanswered questions only
+  wolfies-imessage digest --json | jq '.unanswered_questions'
+";
+
+pub const FOLLOWUP: &str = "Examples:
+  # Questions without a reply and conversations gone stale
+  wolfies-imessage followup
+  # Look back two weeks, skip automated senders
+  wolfies-imessage followup --days 14 --exclude-automated
+  # Stop flagging a contact for a week
+  wolfies-imessage followup snooze Bob --until 7d
+";
+
+pub const COMMITMENTS: &str = "Examples:
+  # Plans, deadlines, and calls from the last week
+  wolfies-imessage commitments
+  # Into a calendar file
+  wolfies-imessage commitments --days 14 --emit-ics ~/Desktop/commitments.ics
+";
+
+pub const GROUP_ANALYTICS: &str = "Examples:
+  # Who talks most in a group, last 30 days
+  wolfies-imessage group-analytics \"Weekend Trip\"
+  # The last year, by group id
+  wolfies-imessage group-analytics chat123456789 --days 365 --json
+";
+
+pub const GROUP_HISTORY: &str = "Examples:
+  # Joins, leaves, and renames
+  wolfies-imessage group-history \"Weekend Trip\"
+  # As JSON
+  wolfies-imessage group-history chat123456789 --json
+";
+
+pub const HANDLES: &str = "Examples:
+  # Handles that messaged in the last 30 days
+  wolfies-imessage handles
+  # One row per contact, their handles merged
+  wolfies-imessage handles --merge-contacts --days 90
+";
+
+pub const UNKNOWN: &str = "Examples:
+  # Messages from senders not in contacts
+  wolfies-imessage unknown
+  # Without shortcodes and no-reply senders
+  wolfies-imessage unknown --days 60 --exclude-automated
+";
+
+pub const DISCOVER: &str = "Examples:
+  # Frequent texters missing from contacts
+  wolfies-imessage discover
+  # Walk through them and add the ones you know
+  wolfies-imessage discover --min-messages 10 --interactive
+";
+
+// LLM workloads
+
+pub const BUNDLE: &str = "Examples:
+  # Unread count, recent messages, and unread messages in one call
+  wolfies-imessage bundle --json
+  # Just what an agent needs about one contact
+  wolfies-imessage bundle --contact Alice --include meta,contact_messages --json --compact
+  # Unread count only, via jq
+  wolfies-imessage bundle --include unread_count --json | jq .unread_count
+  # Fit a 2,000-token context, keeping unread messages longest
+  wolfies-imessage bundle --json --max-tokens 2000 --section-priority unread_messages,unread_count
+";
+
+pub const SUMMARY: &str = "Examples:
+  # The last week with Alice, formatted for summarizing
+  wolfies-imessage summary Alice --days 7
+  # One calendar month, oldest first, with languages tagged
+  wolfies-imessage summary Alice --start 2026-01-01 --end 2026-01-31 --detect-language --json
+  # Page through a long conversation (pass meta.next_cursor back as --cursor)
+  wolfies-imessage summary Bob --limit 200 --json | jq -r .meta.next_cursor
+";
+
+pub const SCHEMA: &str = "Examples:
+  # JSON Schema for unread --json
+  wolfies-imessage schema unread
+  # Every schema, keyed by command
+  wolfies-imessage schema --all > schemas.json
+";
+
+pub const RUN: &str = "Examples:
+  # Save an invocation, then replay it
+  wolfies-imessage digest --relationship work --save-as morning
+  wolfies-imessage run morning
+  # Replay with an override
+  wolfies-imessage run morning --since today --json
+";
+
+pub const PRESETS: &str = "Examples:
+  # Saved presets
+  wolfies-imessage presets list
+  # Remove one
+  wolfies-imessage presets delete morning
+";
+
+// Daemon and semantic search
+
+pub const INDEX: &str = "Examples:
+  # Index the last 30 days of iMessage
+  wolfies-imessage index --source imessage
+  # Rebuild one contact's history from scratch
+  wolfies-imessage index --source imessage --contact Alice --days 365 --full
+";
+
+pub const SEARCH: &str = "Examples:
+  # Semantic search across indexed sources
+  wolfies-imessage search \"where did we book the cabin\"
+  # iMessage only, top 5 as JSON
+  wolfies-imessage search \"flight confirmation\" --sources imessage --limit 5 --json
+";
+
+pub const ASK: &str = "Examples:
+  # Context for a question, ready to paste into a prompt
+  wolfies-imessage ask \"What did Alice say about the lease?\"
+  # Keyword context from chat.db when the daemon is down
+  wolfies-imessage ask \"dinner plans\" --local-fallback --json | jq -r .context
+";
+
+pub const STATS: &str = "Examples:
+  # What's indexed
+  wolfies-imessage stats
+  # One source
+  wolfies-imessage stats --source imessage --json
+";
+
+pub const CLEAR: &str = "Examples:
+  # Drop the iMessage index (asks first)
+  wolfies-imessage clear --source imessage
+  # Everything, without asking
+  wolfies-imessage clear --force
+";
+
+pub const SOURCES: &str = "Examples:
+  # Sources the daemon can index, and which are indexed
+  wolfies-imessage sources
+";
+
+pub const DAEMON: &str = "Examples:
+  # Start the daemon (unix socket at ~/.wolfies-imessage/daemon.sock)
+  wolfies-imessage-daemon start
+  # Check on it, or start it at login
+  wolfies-imessage-daemon status
+  wolfies-imessage-daemon install
+  # Call a method directly
+  wolfies-imessage-client unread --params '{\"limit\": 5}' | jq '.messages[].text'
+";
+
+// Setup and maintenance
+
+pub const SETUP: &str = "Examples:
+  # One-time Full Disk Access setup
+  wolfies-imessage setup
+  # Non-interactive, redoing existing setup
+  wolfies-imessage setup --yes --force
+";
+
+pub const DOCTOR: &str = "Examples:
+  # Check database access and contacts
+  wolfies-imessage doctor
+  # Include query timings
+  wolfies-imessage doctor --performance --json
+";
+
+pub const RAW_MESSAGE: &str = "Examples:
+  # A message whose text came out wrong
+  wolfies-imessage raw-message --rowid 183502
+  # Safe to attach to a bug report
+  wolfies-imessage raw-message --guid 5F0C2E1A-7B1D-4E0A-9C3E-2D8F6A1B4C7E --redact-text
+";
+
+pub const CONFIG: &str = "Examples:
+  # Effective settings and where each came from
+  wolfies-imessage config show
+  # Against a different config file
+  WOLFIES_CONFIG=~/work.toml wolfies-imessage config show --json
+";
+
+pub const CONTACTS: &str = "Examples:
+  # Everyone in contacts.json
+  wolfies-imessage contacts
+  # Message counts per contact, last 90 days
+  wolfies-imessage contacts --stats --days 90 --sort count
+  # Likely duplicates, and contacts.json backups
+  wolfies-imessage contacts dedupe
+  wolfies-imessage contacts history
+";
+
+pub const ADD_CONTACT: &str = "Examples:
+  # Add a friend
+  wolfies-imessage add-contact \"Alice Archer\" \"+14155550101\" --relationship friend
+  # Add a second number to an existing contact
+  wolfies-imessage add-contact \"Alice Archer\" \"+14155550199\" --merge
+";
+
+pub const MUTE: &str = "Examples:
+  # Hide a noisy group for a day
+  wolfies-imessage mute chat123456789 --until 1d
+  # Mute a contact until Monday morning
+  wolfies-imessage mute Bob --until 2026-01-12T09:00
+";
+
+pub const UNMUTE: &str = "Examples:
+  # Bring a muted group back
+  wolfies-imessage unmute chat123456789
+";
+
+pub const MUTES: &str = "Examples:
+  # Mutes still in effect
+  wolfies-imessage mutes
+  # Muted targets only
+  wolfies-imessage mutes list --json | jq -r '.[].target'
+";
+
+pub const CACHE: &str = "Examples:
+  # Delete the extracted-text cache (rebuilt on demand)
+  wolfies-imessage cache clear
+";
+
+pub const MAINTENANCE: &str = "Examples:
+  # Everything in ~/.wolfies-imessage, with sizes and ages
+  wolfies-imessage maintenance status
+  # Delete the text cache (once the daemon is stopped)
+  wolfies-imessage maintenance prune --caches
+  # Delete rotated daemon logs older than 30 days
+  wolfies-imessage maintenance prune --logs --older-than 30d
+  # Caches, rotated logs, and files left by daemons that are gone
+  wolfies-imessage maintenance prune --all
+";
+
+pub const ACCOUNT: &str = "Examples:
+  # The Apple ID and numbers Messages sends from
+  wolfies-imessage account
+  # As JSON
+  wolfies-imessage account --json
+";
+
+pub const EXAMPLES_CMD: &str = "Examples:
+  # The whole cookbook
+  wolfies-imessage examples
+  # One topic, or one command's examples
+  wolfies-imessage examples llm
+  wolfies-imessage examples attachments
+";
+
+/// Every block, in cookbook order within each topic.
+pub const EXAMPLES: &[Examples] = &[
+    Examples { command: "find", topic: Topic::Reading, text: FIND },
+    Examples { command: "messages", topic: Topic::Reading, text: MESSAGES },
+    Examples { command: "recent", topic: Topic::Reading, text: RECENT },
+    Examples { command: "unread", topic: Topic::Reading, text: UNREAD },
+    Examples { command: "quick", topic: Topic::Reading, text: QUICK },
+    Examples { command: "watch", topic: Topic::Reading, text: WATCH },
+    Examples { command: "text-search", topic: Topic::Reading, text: TEXT_SEARCH },
+    Examples { command: "thread", topic: Topic::Reading, text: THREAD },
+    Examples { command: "groups", topic: Topic::Reading, text: GROUPS },
+    Examples { command: "group-messages", topic: Topic::Reading, text: GROUP_MESSAGES },
+    Examples { command: "chat-info", topic: Topic::Reading, text: CHAT_INFO },
+    Examples { command: "attachments", topic: Topic::Reading, text: ATTACHMENTS },
+    Examples { command: "reactions", topic: Topic::Reading, text: REACTIONS },
+    Examples { command: "links", topic: Topic::Reading, text: LINKS },
+    Examples { command: "voice", topic: Topic::Reading, text: VOICE },
+    Examples { command: "deleted", topic: Topic::Reading, text: DELETED },
+    Examples { command: "compare", topic: Topic::Reading, text: COMPARE },
+    Examples { command: "send", topic: Topic::Sending, text: SEND },
+    Examples { command: "send-by-phone", topic: Topic::Sending, text: SEND_BY_PHONE },
+    Examples { command: "mark-read", topic: Topic::Sending, text: MARK_READ },
+    Examples { command: "send-log", topic: Topic::Sending, text: SEND_LOG },
+    Examples { command: "scheduled", topic: Topic::Sending, text: SCHEDULED },
+    Examples { command: "draft", topic: Topic::Sending, text: DRAFT },
+    Examples { command: "analytics", topic: Topic::Analytics, text: ANALYTICS },
+    Examples { command: "digest", topic: Topic::Analytics, text: DIGEST },
+    Examples { command: "followup", topic: Topic::Analytics, text: FOLLOWUP },
+    Examples { command: "commitments", topic: Topic::Analytics, text: COMMITMENTS },
+    Examples { command: "group-analytics", topic: Topic::Analytics, text: GROUP_ANALYTICS },
+    Examples { command: "group-history", topic: Topic::Analytics, text: GROUP_HISTORY },
+    Examples { command: "handles", topic: Topic::Analytics, text: HANDLES },
+    Examples { command: "unknown", topic: Topic::Analytics, text: UNKNOWN },
+    Examples { command: "discover", topic: Topic::Analytics, text: DISCOVER },
+    Examples { command: "bundle", topic: Topic::Llm, text: BUNDLE },
+    Examples { command: "summary", topic: Topic::Llm, text: SUMMARY },
+    Examples { command: "schema", topic: Topic::Llm, text: SCHEMA },
+    Examples { command: "run", topic: Topic::Llm, text: RUN },
+    Examples { command: "presets", topic: Topic::Llm, text: PRESETS },
+    Examples { command: "wolfies-imessage-daemon", topic: Topic::Daemon, text: DAEMON },
+    Examples { command: "index", topic: Topic::Daemon, text: INDEX },
+    Examples { command: "search", topic: Topic::Daemon, text: SEARCH },
+    Examples { command: "ask", topic: Topic::Daemon, text: ASK },
+    Examples { command: "stats", topic: Topic::Daemon, text: STATS },
+    Examples { command: "clear", topic: Topic::Daemon, text: CLEAR },
+    Examples { command: "sources", topic: Topic::Daemon, text: SOURCES },
+    Examples { command: "setup", topic: Topic::Setup, text: SETUP },
+    Examples { command: "doctor", topic: Topic::Setup, text: DOCTOR },
+    Examples { command: "raw-message", topic: Topic::Setup, text: RAW_MESSAGE },
+    Examples { command: "config", topic: Topic::Setup, text: CONFIG },
+    Examples { command: "contacts", topic: Topic::Setup, text: CONTACTS },
+    Examples { command: "add-contact", topic: Topic::Setup, text: ADD_CONTACT },
+    Examples { command: "mute", topic: Topic::Setup, text: MUTE },
+    Examples { command: "unmute", topic: Topic::Setup, text: UNMUTE },
+    Examples { command: "mutes", topic: Topic::Setup, text: MUTES },
+    Examples { command: "cache", topic: Topic::Setup, text: CACHE },
+    Examples { command: "maintenance", topic: Topic::Setup, text: MAINTENANCE },
+    Examples { command: "account", topic: Topic::Setup, text: ACCOUNT },
+    Examples { command: "examples", topic: Topic::Setup, text: EXAMPLES_CMD },
+];
+
+/// The cookbook: every topic, one topic, or one command's block.
+pub fn cookbook(topic: Option<&str>) -> Result<String> {
+    let Some(wanted) = topic.map(|t| t.trim().to_lowercase()) else {
+        return Ok(Topic::ALL.iter().map(|&t| section(t)).collect::<Vec<_>>().join("\n"));
+    };
+    if let Some(&t) = Topic::ALL.iter().find(|t| t.name() == wanted) {
+        return Ok(section(t));
+    }
+    if let Some(block) = EXAMPLES.iter().find(|e| e.command == wanted) {
+        return Ok(format!("{}:\n{}", block.command, block.body()));
+    }
+    let topics: Vec<&str> = Topic::ALL.iter().map(|t| t.name()).collect();
+    Err(anyhow!("Unknown topic or command: {} (topics: {})", wanted, topics.join(", ")))
+}
+
+/// One topic's heading and its commands' blocks.
+fn section(topic: Topic) -> String {
+    let mut out = format!("{}\n{}\n", topic.title(), "=".repeat(topic.title().len()));
+    for block in EXAMPLES.iter().filter(|e| e.topic == topic) {
+        out.push_str(&format!("\n{}:\n{}", block.command, block.body()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_are_well_formed() {
+        for block in EXAMPLES {
+            assert!(block.text.starts_with(HEADING), "{} has no heading", block.command);
+            let lines: Vec<&str> = block.body().lines().collect();
+            let invocations = lines.iter().filter(|l| !l.trim_start().starts_with('#')).count();
+            assert!((1..=5).contains(&invocations), "{}: {} invocations", block.command, invocations);
+            for line in &lines {
+                assert!(line.starts_with("  "), "{}: unindented line {:?}", block.command, line);
+                let line = line.trim_start();
+                assert!(
+                    line.starts_with('#') || line.contains("wolfies-imessage"),
+                    "{}: {:?} is neither a comment nor an invocation",
+                    block.command,
+                    line
+                );
+            }
+        }
+        let mut names: Vec<&str> = EXAMPLES.iter().map(|e| e.command).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), EXAMPLES.len(), "a command is listed twice");
+    }
+
+    #[test]
+    fn test_cookbook_by_topic_and_command() {
+        let all = cookbook(None).unwrap();
+        for topic in Topic::ALL {
+            assert!(all.contains(topic.title()));
+            assert!(EXAMPLES.iter().any(|e| e.topic == topic), "{} is empty", topic.name());
+        }
+        // Same text as the help epilogues, minus the heading
+        assert!(all.contains(FIND.strip_prefix(HEADING).unwrap()));
+
+        let llm = cookbook(Some("LLM")).unwrap();
+        assert!(llm.starts_with("LLM workloads\n"));
+        assert!(llm.contains("bundle:\n") && !llm.contains("find:\n"));
+
+        let attachments = format!("attachments:\n{}", ATTACHMENTS.strip_prefix(HEADING).unwrap());
+        assert_eq!(cookbook(Some("attachments")).unwrap(), attachments);
+        assert!(cookbook(Some("nope")).unwrap_err().to_string().contains("topics: reading"));
+    }
+}
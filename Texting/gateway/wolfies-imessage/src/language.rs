@@ -0,0 +1,162 @@
+//! Message language tagging for `summary --detect-language`.
+//!
+//! Detection is whatlang's trigram model, mapped to ISO 639-1 codes ("en",
+//! "de"; languages without a two-letter code keep whatlang's three-letter
+//! one). Texts under `MIN_CHARS` characters are tagged `UNDETERMINED`:
+//! "ok", "lol", or a name says nothing about the conversation's language.
+//! Longer texts take the model's best guess even when whatlang wouldn't call
+//! it reliable (its threshold rejects most chat-length lines), so single tags
+//! can be wrong; the distribution over a conversation is what to trust.
+//! No database access here.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial detect / distribution (whatlang, ISO 639-1 codes)
+
+use std::collections::BTreeMap;
+
+/// Tag for texts too short or too ambiguous to call (ISO 639-2 "und").
+pub const UNDETERMINED: &str = "und";
+
+/// Shorter texts are tagged `UNDETERMINED` rather than guessed.
+pub const MIN_CHARS: usize = 15;
+
+/// ISO 639-1 code for a whatlang (ISO 639-3) code, where one exists.
+fn two_letter(code: &'static str) -> &'static str {
+    match code {
+        "afr" => "af",
+        "aka" => "ak",
+        "amh" => "am",
+        "ara" => "ar",
+        "aze" => "az",
+        "bel" => "be",
+        "ben" => "bn",
+        "bul" => "bg",
+        "cat" => "ca",
+        "ces" => "cs",
+        "cmn" => "zh",
+        "dan" => "da",
+        "deu" => "de",
+        "ell" => "el",
+        "eng" => "en",
+        "epo" => "eo",
+        "est" => "et",
+        "fin" => "fi",
+        "fra" => "fr",
+        "guj" => "gu",
+        "heb" => "he",
+        "hin" => "hi",
+        "hrv" => "hr",
+        "hun" => "hu",
+        "hye" => "hy",
+        "ind" => "id",
+        "ita" => "it",
+        "jav" => "jv",
+        "jpn" => "ja",
+        "kan" => "kn",
+        "kat" => "ka",
+        "khm" => "km",
+        "kor" => "ko",
+        "lat" => "la",
+        "lav" => "lv",
+        "lit" => "lt",
+        "mal" => "ml",
+        "mar" => "mr",
+        "mkd" => "mk",
+        "mya" => "my",
+        "nep" => "ne",
+        "nld" => "nl",
+        "nob" => "nb",
+        "ori" => "or",
+        "pan" => "pa",
+        "pes" => "fa",
+        "pol" => "pl",
+        "por" => "pt",
+        "ron" => "ro",
+        "rus" => "ru",
+        "sin" => "si",
+        "slk" => "sk",
+        "slv" => "sl",
+        "sna" => "sn",
+        "spa" => "es",
+        "srp" => "sr",
+        "swe" => "sv",
+        "tam" => "ta",
+        "tel" => "te",
+        "tgl" => "tl",
+        "tha" => "th",
+        "tuk" => "tk",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "urd" => "ur",
+        "uzb" => "uz",
+        "vie" => "vi",
+        "yid" => "yi",
+        "zul" => "zu",
+        other => other,
+    }
+}
+
+/// Language code for one message's text (`UNDETERMINED` when short).
+pub fn detect(text: &str) -> &'static str {
+    let text = text.trim();
+    if text.chars().count() < MIN_CHARS {
+        return UNDETERMINED;
+    }
+    whatlang::detect_lang(text).map_or(UNDETERMINED, |lang| two_letter(lang.code()))
+}
+
+/// Share of each language among the tags (undetermined included), summing to 1.
+pub fn distribution<'a>(tags: impl IntoIterator<Item = &'a str>) -> BTreeMap<String, f64> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut total = 0;
+    for tag in tags {
+        *counts.entry(tag.to_string()).or_default() += 1;
+        total += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(tag, count)| (tag, count as f64 / total as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_sentences_per_language() {
+        let samples = [
+            ("en", "I think we should leave early to avoid the traffic."),
+            ("de", "Ich komme heute etwas später nach Hause, warte nicht auf mich."),
+            ("fr", "Est-ce que tu veux aller au cinéma avec nous ce soir ?"),
+            ("es", "Mañana vamos a la playa con mis hermanos y sus amigos."),
+            ("it", "Ci vediamo domani mattina davanti alla stazione centrale."),
+            ("ru", "Я сегодня вечером буду дома, позвони мне после работы."),
+            ("ja", "明日の朝、駅の前で待ち合わせしましょう。よろしくお願いします。"),
+        ];
+        for (expected, text) in samples {
+            assert_eq!(detect(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_short_texts_are_undetermined() {
+        for text in ["ok", "lol", "Danke schön!", "   see you soon   ", ""] {
+            assert_eq!(detect(text), UNDETERMINED, "{:?}", text);
+        }
+        // 15 characters is enough to try
+        assert_eq!("Wie geht es dir".chars().count(), MIN_CHARS);
+        assert_ne!(detect("Wie war dein Wochenende?"), UNDETERMINED);
+    }
+
+    #[test]
+    fn test_distribution_math() {
+        let shares = distribution(["en", "de", "en", "und", "en", "de", "en", "en", "de", "en"]);
+        assert_eq!(shares.len(), 3);
+        assert!((shares["en"] - 0.6).abs() < 1e-9);
+        assert!((shares["de"] - 0.3).abs() < 1e-9);
+        assert!((shares["und"] - 0.1).abs() < 1e-9);
+        assert!((shares.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(distribution(std::iter::empty()).is_empty());
+    }
+}
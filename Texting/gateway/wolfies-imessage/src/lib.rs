@@ -1,15 +1,57 @@
 //! wolfies-imessage library
 //!
-//! Exposes modules for use by daemon and client binaries.
+//! Exposes modules for use by daemon and client binaries. Other Rust tools
+//! should start from [`api`], which returns typed results instead of printing.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Added data_files module (registry of the data directory)
+//! - 10/16/2026 - Added budget module (bundle --max-tokens trimming)
+//! - 10/16/2026 - Added examples module (help examples and cookbook)
+//! - 10/16/2026 - Added progress module (progress lines for long-running commands)
+//! - 10/16/2026 - Added presets module (saved invocations)
+//! - 10/16/2026 - Added language module (message language detection)
+//! - 10/16/2026 - Added senders module (known/unknown sender filter)
+//! - 10/16/2026 - Added emoji module (emoji analytics)
+//! - 10/16/2026 - Added snoozes module (snoozed follow-ups)
+//! - 10/16/2026 - Added send_log module (send audit log, rate limit)
+//! - 10/16/2026 - Added mutes module (muted conversations)
+//! - 10/16/2026 - Added drafts module (draft queue store)
+//! - 10/16/2026 - Added api module (typed library layer)
+//! - 10/16/2026 - Added date_expr module (date/time expression recognizer)
+//! - 10/16/2026 - Added terms module (top-terms analytics)
+//! - 10/16/2026 - Added config module (config.toml)
+//! - 10/16/2026 - Added daemon_client module (socket client, auto-start)
+//! - 10/16/2026 - Added notify module
+//! - 10/16/2026 - Added storage and scheduler modules (scheduled sends)
+//! - 10/16/2026 - Added error module (CLI exit codes)
 //! - 01/10/2026 - Added db::helpers for shared query functions (Phase 5) (Claude)
 //! - 01/10/2026 - Initial library structure (Phase 4C, Claude)
 
 // Core modules
+pub mod api;
 pub mod applescript;
+pub mod budget;
 pub mod commands;
+pub mod config;
 pub mod contacts;
+pub mod data_files;
+pub mod date_expr;
 pub mod daemon;
+pub mod daemon_client;
 pub mod db;
+pub mod drafts;
+pub mod emoji;
+pub mod error;
+pub mod examples;
+pub mod language;
+pub mod mutes;
+pub mod notify;
 pub mod output;
+pub mod presets;
+pub mod progress;
+pub mod scheduler;
+pub mod senders;
+pub mod send_log;
+pub mod snoozes;
+pub mod storage;
+pub mod terms;
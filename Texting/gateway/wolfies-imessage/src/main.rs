@@ -4,17 +4,87 @@
 //! RAG commands delegate to Python daemon via Unix socket.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - Error envelope carries error.retry_after_secs for RATE_LIMITED (send failures no longer print a SendResult first)
+//! - 10/16/2026 - --redact is refused for text output (which is never redacted)
+//! - 10/16/2026 - --explain with JSON output: plan in the envelope meta (meta.query_plan), not on stderr
+//! - 10/16/2026 - Uses the library crate's modules instead of compiling its own copy (no dead code warnings for items only the daemon uses)
+//! - 10/16/2026 - send / send-by-phone --message-file and --stdin (exact message text); option-like message words are refused
+//! - 10/16/2026 - analytics --streaks [--all-time] (daily streaks with a contact)
+//! - 10/16/2026 - maintenance status / maintenance prune (data directory inventory and cleanup)
+//! - 10/16/2026 - raw-message command (--guid/--rowid, --redact-text)
+//! - 10/16/2026 - analytics --start/--end (fixed window, like summary)
+//! - 10/16/2026 - analytics --compare (deltas against the preceding window)
+//! - 10/16/2026 - --with-reactions on messages, summary and group-messages (tapbacks inline per message)
+//! - 10/16/2026 - bundle --max-tokens / --section-priority (budget trimming)
+//! - 10/16/2026 - --include-system / --exclude-system on find and messages (included by default), text-search and followup (excluded by default)
+//! - 10/16/2026 - Per-subcommand --help examples (after_help blocks from examples.rs); added examples [topic] cookbook
+//! - 10/16/2026 - Global --quiet (no progress lines); attachments --copy-to/--dedupe and compare report progress on stderr
+//! - 10/16/2026 - find/messages --service imessage|sms|all (contact history merges SMS and iMessage chats)
+//! - 10/16/2026 - unread --by-conversation
+//! - 10/16/2026 - attachments --dedupe / --max-hash-bytes
+//! - 10/16/2026 - Global --save-as; added run (replay a preset with overrides) and presets list/delete
+//! - 10/16/2026 - summary --detect-language
+//! - 10/16/2026 - config.toml warnings go through output::warn; unreported warnings print once, after the command's output
+//! - 10/16/2026 - recent, unread, digest, and watch take --known-only / --unknown-only
+//! - 10/16/2026 - send-by-phone accepts email handles (send_by_handle)
+//! - 10/16/2026 - analytics --heatmap (weekday x hour, local time)
+//! - 10/16/2026 - Added chat-info
+//! - 10/16/2026 - unread --count-only (one COUNT query); contacts load on first use, not up front
+//! - 10/16/2026 - Global --envelope ({ok, data, meta} JSON; failures as {ok: false, error})
+//! - 10/16/2026 - Global --csv for list output (errors up front on other commands)
+//! - 10/16/2026 - text-search --group-id/--group
+//! - 10/16/2026 - analytics --gaps [--min-gap-days] (longest silences)
+//! - 10/16/2026 - analytics --emoji (per-sender emoji and tapback tallies)
+//! - 10/16/2026 - attachments --image-meta
+//! - 10/16/2026 - Added quick (pinned contacts and groups from config `pinned`)
+//! - 10/16/2026 - summary --cursor (keyset pagination)
+//! - 10/16/2026 - Global --explain (primary query plan on stderr); added doctor [--performance]
+//! - 10/16/2026 - followup --include-groups/--min-question-score/--exclude-automated; followup snooze/unsnooze
+//! - 10/16/2026 - Added account command (handles and services Messages sends from)
+//! - 10/16/2026 - add-contact --merge; added contacts dedupe [--apply]
+//! - 10/16/2026 - Send rate limit with --force-send on send, send-by-phone, draft send; added send-log
+//! - 10/16/2026 - mute/unmute/mutes; --include-muted on unread, recent --per-conversation, digest, watch
+//! - 10/16/2026 - draft add/list/send/delete (review-first send queue)
+//! - 10/16/2026 - Contacts via load_default_or_empty (warns when contacts.json is corrupt)
+//! - 10/16/2026 - find, messages, text-search get the loaded contacts (api layer)
+//! - 10/16/2026 - Added global --no-color
+//! - 10/16/2026 - Added global --db-path and compare command
+//! - 10/16/2026 - Added deleted command; summary --include-deleted
+//! - 10/16/2026 - Added group-history command
+//! - 10/16/2026 - Added contacts history/diff/restore
+//! - 10/16/2026 - contacts gained --stats (with --days and --sort)
+//! - 10/16/2026 - Added commitments command (--emit-ics)
+//! - 10/16/2026 - analytics --top-terms (per-sender term counts)
+//! - 10/16/2026 - analytics --series (volume buckets for charting)
+//! - 10/16/2026 - --include-self on analytics, followup, unknown, discover
+//! - 10/16/2026 - Added schema subcommand (JSON Schema for --json outputs)
+//! - 10/16/2026 - Load config.toml defaults (flags win); added config show and send --dry-run
+//! - 10/16/2026 - RAG commands call the daemon; added global --socket
+//! - 10/16/2026 - Added global --auto-start-daemon (RAG commands)
+//! - 10/16/2026 - Added global --no-cache and cache clear (extracted-text cache)
+//! - 10/16/2026 - find, messages, recent, unread gained --entities
+//! - 10/16/2026 - unknown/discover gained --exclude-automated
+//! - 10/16/2026 - discover gained --interactive
+//! - 10/16/2026 - handles gained --merge-contacts
+//! - 10/16/2026 - text-search gained --any/--all
+//! - 10/16/2026 - links gained --sort and --fetch-titles
+//! - 10/16/2026 - Added group-analytics command
+//! - 10/16/2026 - Added global --redact / --redact-salt
+//! - 10/16/2026 - Added digest command
+//! - 10/16/2026 - Added watch command with --notify
+//! - 10/16/2026 - Added send --at/--in and scheduled list/cancel
+//! - 10/16/2026 - Added mark-read command and per-error exit codes
+//! - 10/16/2026 - Added attachments --copy-to
 //! - 01/10/2026 - Initial scaffold with CLI skeleton (Claude)
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::cell::LazyCell;
+use std::path::PathBuf;
 use std::process::ExitCode;
 use std::sync::Arc;
 
-mod applescript;
-mod commands;
-mod contacts;
-mod db;
-mod output;
+use wolfies_imessage::{api, budget, commands, config, contacts, db, error, examples, notify, output, presets, scheduler, senders};
 
 /// Fast Rust CLI for iMessage - direct SQLite queries and AppleScript sending.
 #[derive(Parser, Debug)]
@@ -37,22 +107,76 @@ struct Cli {
     #[arg(long, global = true)]
     fields: Option<String>,
 
+    /// CSV output for list results (recent, messages, text-search, attachments, links,
+    /// handles, analytics --top-terms/--series; an error elsewhere); --fields sets the column order
+    #[arg(long, global = true, conflicts_with_all = ["json", "compact", "minimal"])]
+    csv: bool,
+
+    /// Wrap JSON output as {ok, data, meta} (count, elapsed_ms, applied parameters),
+    /// like daemon responses; implies --json
+    #[arg(long, global = true, conflicts_with = "csv")]
+    envelope: bool,
+
     /// Truncate text fields to this length
     #[arg(long, global = true)]
     max_text_chars: Option<u32>,
 
+    /// Replace handles with pseudonyms, strip attachment paths, mask numbers in text
+    /// (JSON and CSV output; text output is refused)
+    #[arg(long, global = true)]
+    redact: bool,
+
+    /// Salt file for pseudonyms stable across runs (created if missing)
+    #[arg(long, global = true, requires = "redact")]
+    redact_salt: Option<PathBuf>,
+
+    /// Plain text output (also when NO_COLOR is set or stdout isn't a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Parse attributedBody blobs directly instead of using the text cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Messages database to read (default: config.toml's db_path or ~/Library/Messages/chat.db)
+    #[arg(long, global = true)]
+    db_path: Option<String>,
+
+    /// Print the primary query's EXPLAIN QUERY PLAN on stderr after the output
+    /// (with JSON output: in the envelope as meta.query_plan; implies --envelope)
+    #[arg(long, global = true, conflicts_with = "csv")]
+    explain: bool,
+
+    /// Start the daemon if its socket is missing (daemon-backed commands)
+    #[arg(long, global = true)]
+    auto_start_daemon: bool,
+
+    /// Daemon socket for RAG commands (default: $WOLFIES_RAG_SOCKET, config.toml, or ~/.wolfies-imessage/daemon.sock)
+    #[arg(long, global = true)]
+    socket: Option<String>,
+
+    /// No progress lines on stderr for long-running commands (also off with --json)
+    #[arg(long, global = true)]
+    quiet: bool,
+
+    /// Save this command and its parameters as a preset (replay with `run <name>`)
+    #[arg(long, global = true, value_name = "NAME")]
+    save_as: Option<String>,
+
     #[command(subcommand)]
     command: Command,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
 enum Command {
     // =========================================================================
     // CORE READING COMMANDS
     // =========================================================================
     /// Find messages with a contact (keyword search)
+    #[command(after_help = examples::FIND)]
     Find {
-        /// Contact name (fuzzy matched)
+        /// Contact name (fuzzy matched), or "self" for notes to self
         contact: String,
 
         /// Text to search for in messages
@@ -62,41 +186,211 @@ enum Command {
         /// Max messages to return (1-500)
         #[arg(short, long, default_value_t = 30)]
         limit: u32,
+
+        /// Include entities (mentions, links, subject) per message
+        #[arg(long)]
+        entities: bool,
+
+        /// Only messages sent over this service (SMS and iMessage chats are merged by default)
+        #[arg(long, default_value = "all", value_parser = ["imessage", "sms", "all"])]
+        service: String,
+
+        /// Keep system items: renames, member changes, FaceTime calls (the default here)
+        #[arg(long)]
+        include_system: bool,
+
+        /// Leave out system items
+        #[arg(long, conflicts_with = "include_system")]
+        exclude_system: bool,
     },
 
     /// Get messages with a specific contact
+    #[command(after_help = examples::MESSAGES)]
     Messages {
-        /// Contact name
+        /// Contact name, or "self" for notes to self
         contact: String,
 
         /// Max messages (1-500)
         #[arg(short, long, default_value_t = 20)]
         limit: u32,
+
+        /// Include entities (mentions, links, subject) per message
+        #[arg(long)]
+        entities: bool,
+
+        /// Only messages sent over this service (SMS and iMessage chats are merged by default)
+        #[arg(long, default_value = "all", value_parser = ["imessage", "sms", "all"])]
+        service: String,
+
+        /// Keep system items: renames, member changes, FaceTime calls (the default here)
+        #[arg(long)]
+        include_system: bool,
+
+        /// Leave out system items
+        #[arg(long, conflicts_with = "include_system")]
+        exclude_system: bool,
+
+        /// Attach each message's tapbacks (reactions: emoji, from, is_from_me)
+        #[arg(long)]
+        with_reactions: bool,
     },
 
     /// Get recent conversations across all contacts
+    #[command(after_help = examples::RECENT)]
     Recent {
         /// Max conversations (1-500)
         #[arg(short, long, default_value_t = 10)]
         limit: u32,
+
+        /// Return the latest message per conversation instead of globally
+        #[arg(long)]
+        per_conversation: bool,
+
+        /// Only contacts with this relationship (family, friend, work, other)
+        #[arg(long)]
+        relationship: Option<String>,
+
+        /// Include entities (mentions, links, subject) per message
+        #[arg(long)]
+        entities: bool,
+
+        /// Keep muted conversations (see `mute`)
+        #[arg(long, requires = "per_conversation")]
+        include_muted: bool,
+
+        /// Only senders in contacts or chats Messages didn't filter
+        #[arg(long, conflicts_with_all = ["unknown_only", "per_conversation"])]
+        known_only: bool,
+
+        /// Only senders Messages would list under Unknown Senders
+        #[arg(long, conflicts_with = "per_conversation")]
+        unknown_only: bool,
     },
 
     /// Get unread messages
+    #[command(after_help = examples::UNREAD)]
     Unread {
         /// Max messages (1-500)
         #[arg(short, long, default_value_t = 20)]
         limit: u32,
+
+        /// Only contacts with this relationship (family, friend, work, other)
+        #[arg(long)]
+        relationship: Option<String>,
+
+        /// Include entities (mentions, links, subject) per message
+        #[arg(long)]
+        entities: bool,
+
+        /// Keep muted conversations (see `mute`)
+        #[arg(long)]
+        include_muted: bool,
+
+        /// Print only the number of unread messages (one COUNT query, no contacts;
+        /// {"unread_count": N} with --json)
+        #[arg(long, conflicts_with_all = ["relationship", "entities", "known_only", "unknown_only"])]
+        count_only: bool,
+
+        /// Only senders in contacts or chats Messages didn't filter
+        #[arg(long, conflicts_with = "unknown_only")]
+        known_only: bool,
+
+        /// Only senders Messages would list under Unknown Senders
+        #[arg(long)]
+        unknown_only: bool,
+
+        /// Unread count and oldest unread date per conversation, past each chat's read position
+        #[arg(long, conflicts_with_all = ["count_only", "relationship", "entities", "known_only", "unknown_only"])]
+        by_conversation: bool,
+    },
+
+    /// Latest message and unread count for each pinned contact or group (config `pinned`)
+    #[command(after_help = examples::QUICK)]
+    Quick,
+
+    /// Daily briefing: unread by contact, unanswered questions, who's gone quiet, volume deltas
+    #[command(after_help = examples::DIGEST)]
+    Digest {
+        /// Period start: yesterday (default), today, or YYYY-MM-DD
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only contacts with this relationship (family, friend, work, other)
+        #[arg(long)]
+        relationship: Option<String>,
+
+        /// Keep muted conversations in the unread section (see `mute`)
+        #[arg(long)]
+        include_muted: bool,
+
+        /// Unread section: only senders in contacts or chats Messages didn't filter
+        #[arg(long, conflicts_with = "unknown_only")]
+        known_only: bool,
+
+        /// Unread section: only senders Messages would list under Unknown Senders
+        #[arg(long)]
+        unknown_only: bool,
+    },
+
+    /// Stream new incoming messages as they arrive
+    #[command(after_help = examples::WATCH)]
+    Watch {
+        /// Poll interval in seconds
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+
+        /// Post a desktop notification for each new message
+        #[arg(long)]
+        notify: bool,
+
+        /// Min seconds between notifications per conversation
+        #[arg(long, default_value_t = notify::DEFAULT_WINDOW_SECS, requires = "notify")]
+        notify_window: u64,
+
+        /// Only notify for these contacts (comma-separated names or handles)
+        #[arg(long, value_delimiter = ',', requires = "notify")]
+        filter_contacts: Vec<String>,
+
+        /// Notify for muted conversations too (see `mute`)
+        #[arg(long, requires = "notify")]
+        include_muted: bool,
+
+        /// Only senders in contacts or chats Messages didn't filter
+        #[arg(long, conflicts_with = "unknown_only")]
+        known_only: bool,
+
+        /// Only senders Messages would list under Unknown Senders
+        #[arg(long)]
+        unknown_only: bool,
     },
 
     /// Fast text search across all messages (no embeddings)
+    #[command(after_help = examples::TEXT_SEARCH)]
     TextSearch {
         /// Search query (keyword or phrase)
-        query: String,
+        #[arg(required_unless_present_any = ["any", "all"], conflicts_with_all = ["any", "all"])]
+        query: Option<String>,
+
+        /// Match any of these terms (comma-separated, `\,` for a literal comma; repeatable)
+        #[arg(long, conflicts_with = "all")]
+        any: Vec<String>,
+
+        /// Match all of these terms (comma-separated, `\,` for a literal comma; repeatable)
+        #[arg(long)]
+        all: Vec<String>,
 
         /// Optional contact name to filter results
         #[arg(long)]
         contact: Option<String>,
 
+        /// Only search this group chat (chat identifier)
+        #[arg(long, conflicts_with_all = ["contact", "group"])]
+        group_id: Option<String>,
+
+        /// Only search this group chat (display name)
+        #[arg(long, conflicts_with = "contact")]
+        group: Option<String>,
+
         /// Max results (1-500)
         #[arg(short, long, default_value_t = 50)]
         limit: u32,
@@ -108,9 +402,18 @@ enum Command {
         /// Only search messages on/after YYYY-MM-DD
         #[arg(long)]
         since: Option<String>,
+
+        /// Also match system items: renames, member changes, FaceTime calls
+        #[arg(long)]
+        include_system: bool,
+
+        /// Leave out system items (the default here)
+        #[arg(long, conflicts_with = "include_system")]
+        exclude_system: bool,
     },
 
     /// Run a canonical LLM workload bundle in one call
+    #[command(after_help = examples::BUNDLE)]
     Bundle {
         /// Optional contact name to include contact-specific data
         #[arg(long)]
@@ -151,36 +454,136 @@ enum Command {
         /// Comma-separated bundle sections to include
         #[arg(long)]
         include: Option<String>,
+
+        /// Trim the bundle to about this many tokens (4 characters each); meta.trimmed says what went
+        #[arg(long)]
+        max_tokens: Option<usize>,
+
+        /// Sections to keep longest when trimming, most important first (comma-separated)
+        #[arg(long, requires = "max_tokens")]
+        section_priority: Option<String>,
     },
 
     // =========================================================================
     // MESSAGING COMMANDS
     // =========================================================================
     /// Send a message to a contact
+    #[command(after_help = examples::SEND)]
     Send {
         /// Contact name
         contact: String,
 
-        /// Message to send
+        /// Message to send (words are joined with spaces)
+        #[arg(required_unless_present_any = ["message_file", "stdin"])]
         message: Vec<String>,
+
+        /// Send the contents of this file exactly (newlines kept; one trailing newline dropped)
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["message", "stdin"])]
+        message_file: Option<PathBuf>,
+
+        /// Send what's piped to stdin exactly (newlines kept; one trailing newline dropped)
+        #[arg(long, conflicts_with = "message")]
+        stdin: bool,
+
+        /// Schedule for a local time instead of sending now (e.g. 2026-01-12T09:00)
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Schedule after a delay instead of sending now (e.g. 2h, 30m, 1h30m)
+        #[arg(long = "in", conflicts_with = "at")]
+        delay: Option<String>,
+
+        /// Report what would be sent without sending (or queueing) it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Send even if it exceeds the send rate limit
+        #[arg(long)]
+        force_send: bool,
     },
 
-    /// Send message directly to phone number
+    /// Send message directly to phone number or email
+    #[command(after_help = examples::SEND_BY_PHONE)]
     SendByPhone {
-        /// Phone number (e.g., +14155551234)
+        /// Phone number (e.g., +14155551234) or email handle
         phone: String,
 
-        /// Message to send
+        /// Message to send (words are joined with spaces)
+        #[arg(required_unless_present_any = ["message_file", "stdin"])]
         message: Vec<String>,
+
+        /// Send the contents of this file exactly (newlines kept; one trailing newline dropped)
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["message", "stdin"])]
+        message_file: Option<PathBuf>,
+
+        /// Send what's piped to stdin exactly (newlines kept; one trailing newline dropped)
+        #[arg(long, conflicts_with = "message")]
+        stdin: bool,
+
+        /// Schedule for a local time instead of sending now (e.g. 2026-01-12T09:00)
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Schedule after a delay instead of sending now (e.g. 2h, 30m, 1h30m)
+        #[arg(long = "in", conflicts_with = "at")]
+        delay: Option<String>,
+
+        /// Report what would be sent without sending (or queueing) it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Send even if it exceeds the send rate limit
+        #[arg(long)]
+        force_send: bool,
+    },
+
+    /// Mark a conversation as read in Messages.app (via AppleScript)
+    #[command(after_help = examples::MARK_READ)]
+    MarkRead {
+        /// Contact name
+        contact: Option<String>,
+
+        /// Group chat identifier (e.g. chat123456789)
+        #[arg(long, conflicts_with = "contact")]
+        group_id: Option<String>,
+
+        /// Mark every conversation with unread messages
+        #[arg(long, conflicts_with_all = ["contact", "group_id"])]
+        all: bool,
+    },
+
+    /// Summarize outbound sends per recipient (from the send log)
+    #[command(after_help = examples::SEND_LOG)]
+    SendLog {
+        /// Days of history to summarize
+        #[arg(long, default_value = "7")]
+        days: u32,
     },
 
     // =========================================================================
     // CONTACT COMMANDS
     // =========================================================================
     /// List all contacts
-    Contacts,
+    #[command(after_help = examples::CONTACTS)]
+    Contacts {
+        #[command(subcommand)]
+        action: Option<ContactsAction>,
+
+        /// Add message count, sent/received, last message, and unread per contact
+        #[arg(long)]
+        stats: bool,
+
+        /// Days to look back for --stats
+        #[arg(short, long, default_value_t = 30, requires = "stats")]
+        days: u32,
+
+        /// Sort order for --stats: last_message, count, or name
+        #[arg(long, default_value = "last_message", requires = "stats", value_parser = ["last_message", "count", "name"])]
+        sort: String,
+    },
 
     /// Add a new contact
+    #[command(after_help = examples::ADD_CONTACT)]
     AddContact {
         /// Contact name
         name: String,
@@ -195,12 +598,17 @@ enum Command {
         /// Notes about the contact
         #[arg(short, long)]
         notes: Option<String>,
+
+        /// If the name matches an existing contact, add the phone to it instead
+        #[arg(long)]
+        merge: bool,
     },
 
     // =========================================================================
     // ANALYTICS COMMANDS
     // =========================================================================
     /// Get conversation analytics
+    #[command(after_help = examples::ANALYTICS)]
     Analytics {
         /// Contact name (optional)
         contact: Option<String>,
@@ -208,9 +616,66 @@ enum Command {
         /// Days to analyze (1-365)
         #[arg(short, long, default_value_t = 30)]
         days: u32,
+
+        /// Only contacts with this relationship (family, friend, work, other)
+        #[arg(long, conflicts_with = "contact")]
+        relationship: Option<String>,
+
+        /// Keep my own notes-to-self conversation (excluded by default)
+        #[arg(long)]
+        include_self: bool,
+
+        /// Volume series instead of totals: hourly, daily, or weekly
+        #[arg(long, conflicts_with = "relationship")]
+        series: Option<String>,
+
+        /// Top terms in the conversation with the contact, split by sender
+        #[arg(long, requires = "contact", conflicts_with = "series")]
+        top_terms: bool,
+
+        /// My name, excluded from --top-terms
+        #[arg(long, requires = "top_terms")]
+        me: Option<String>,
+
+        /// Emoji and tapback tallies in the conversation with the contact, split by sender
+        #[arg(long, requires = "contact", conflicts_with_all = ["series", "top_terms"])]
+        emoji: bool,
+
+        /// Longest silences in the conversation with the contact, and who broke them
+        #[arg(long, requires = "contact", conflicts_with_all = ["series", "top_terms", "emoji"])]
+        gaps: bool,
+
+        /// Message counts by weekday and hour (local time), sent and received
+        #[arg(long, conflicts_with_all = ["relationship", "series", "top_terms", "emoji", "gaps"])]
+        heatmap: bool,
+
+        /// Shortest silence --gaps reports, in days
+        #[arg(long, requires = "gaps", default_value_t = commands::analytics::DEFAULT_MIN_GAP_DAYS)]
+        min_gap_days: u32,
+
+        /// Compare totals with the preceding window of the same length (comparison block)
+        #[arg(long, conflicts_with_all = ["series", "top_terms", "emoji", "gaps", "heatmap"])]
+        compare: bool,
+
+        /// Start date (YYYY-MM-DD), instead of --days back from now
+        #[arg(long, conflicts_with_all = ["days", "series", "top_terms", "emoji", "gaps", "heatmap"])]
+        start: Option<String>,
+
+        /// End date (YYYY-MM-DD, inclusive)
+        #[arg(long, requires = "start")]
+        end: Option<String>,
+
+        /// Consecutive days texting with the contact, first message, and lifetime count
+        #[arg(long, requires = "contact", conflicts_with_all = ["series", "top_terms", "emoji", "gaps", "heatmap", "compare", "start"])]
+        streaks: bool,
+
+        /// Look for --streaks in all history instead of --days
+        #[arg(long, requires = "streaks", conflicts_with = "days")]
+        all_time: bool,
     },
 
     /// Detect messages needing follow-up
+    #[command(after_help = examples::FOLLOWUP)]
     Followup {
         /// Days to look back (1-365)
         #[arg(short, long, default_value_t = 7)]
@@ -219,19 +684,86 @@ enum Command {
         /// Min stale days (1-365)
         #[arg(short, long, default_value_t = 2)]
         stale: u32,
+
+        /// Only contacts with this relationship (family, friend, work, other)
+        #[arg(long)]
+        relationship: Option<String>,
+
+        /// Keep my own notes-to-self conversation (excluded by default)
+        #[arg(long)]
+        include_self: bool,
+
+        /// Include questions asked in group chats
+        #[arg(long)]
+        include_groups: bool,
+
+        /// Minimum question score (0.0-1.0) for an unanswered question
+        #[arg(long, default_value_t = commands::followup::DEFAULT_MIN_QUESTION_SCORE)]
+        min_question_score: f64,
+
+        /// Leave out automated senders (short codes, sender IDs, no-reply, 2FA, marketing)
+        #[arg(long)]
+        exclude_automated: bool,
+
+        /// Count system items as messages and replies: renames, member changes, FaceTime calls
+        #[arg(long)]
+        include_system: bool,
+
+        /// Leave out system items (the default here)
+        #[arg(long, conflicts_with = "include_system")]
+        exclude_system: bool,
+
+        #[command(subcommand)]
+        action: Option<FollowupAction>,
+    },
+
+    /// Find commitments (plans, deadlines, calls) in recent messages
+    #[command(after_help = examples::COMMITMENTS)]
+    Commitments {
+        /// Days to look back (1-365)
+        #[arg(short, long, default_value_t = 7)]
+        days: u32,
+
+        /// Write high-confidence items to this .ics file
+        #[arg(long)]
+        emit_ics: Option<PathBuf>,
     },
 
     // =========================================================================
     // GROUP COMMANDS
     // =========================================================================
     /// List all group chats
+    #[command(after_help = examples::GROUPS)]
     Groups {
         /// Max groups (1-500)
         #[arg(short, long, default_value_t = 50)]
         limit: u32,
     },
 
+    /// Message statistics for one group chat
+    #[command(after_help = examples::GROUP_ANALYTICS)]
+    GroupAnalytics {
+        /// Group chat ID or display name
+        group: String,
+
+        /// Days to analyze (1-365)
+        #[arg(short, long, default_value_t = 30)]
+        days: u32,
+
+        /// My name, for @-mention detection (besides "you")
+        #[arg(long)]
+        me: Option<String>,
+    },
+
+    /// Participant and name-change history for one group chat
+    #[command(after_help = examples::GROUP_HISTORY)]
+    GroupHistory {
+        /// Group chat ID or display name
+        group: String,
+    },
+
     /// Get messages from a group chat
+    #[command(after_help = examples::GROUP_MESSAGES)]
     GroupMessages {
         /// Group chat ID
         #[arg(short, long)]
@@ -244,12 +776,25 @@ enum Command {
         /// Max messages (1-500)
         #[arg(short, long, default_value_t = 50)]
         limit: u32,
+
+        /// Attach each message's tapbacks (reactions: emoji, from, is_from_me)
+        #[arg(long)]
+        with_reactions: bool,
+    },
+
+    /// Participants, service, message span, and attachment count for a group,
+    /// or for every chat a contact is in
+    #[command(after_help = examples::CHAT_INFO)]
+    ChatInfo {
+        /// Group ID or name, contact name, or phone/email
+        target: String,
     },
 
     // =========================================================================
     // T1 COMMANDS - Advanced Features
     // =========================================================================
     /// Get attachments (photos, videos, files)
+    #[command(after_help = examples::ATTACHMENTS)]
     Attachments {
         /// Contact name (optional)
         contact: Option<String>,
@@ -261,9 +806,38 @@ enum Command {
         /// Max attachments (1-500)
         #[arg(short, long, default_value_t = 50)]
         limit: u32,
+
+        /// Copy matched attachments into this directory (YYYYMMDD_<sender>_<name>)
+        #[arg(long)]
+        copy_to: Option<PathBuf>,
+
+        /// Copy newest attachments first (default: oldest first)
+        #[arg(long, requires = "copy_to")]
+        newest_first: bool,
+
+        /// Stop copying after this many bytes
+        #[arg(long, requires = "copy_to")]
+        max_bytes: Option<u64>,
+
+        /// Convert copied HEIC images to JPEG (uses sips)
+        #[arg(long, requires = "copy_to")]
+        convert_heic: bool,
+
+        /// Add width, height, EXIF capture date, and camera model for images
+        #[arg(long, conflicts_with = "copy_to")]
+        image_meta: bool,
+
+        /// Collapse identical files (content hash; size and name when the file is gone)
+        #[arg(long)]
+        dedupe: bool,
+
+        /// Hash only each file's first N bytes (e.g. 1048576); default: whole file
+        #[arg(long, requires = "dedupe")]
+        max_hash_bytes: Option<u64>,
     },
 
     /// Get reactions (tapbacks) from messages
+    #[command(after_help = examples::REACTIONS)]
     Reactions {
         /// Contact name (optional)
         contact: Option<String>,
@@ -274,6 +848,7 @@ enum Command {
     },
 
     /// Extract URLs shared in conversations
+    #[command(after_help = examples::LINKS)]
     Links {
         /// Contact name (optional)
         contact: Option<String>,
@@ -289,9 +864,18 @@ enum Command {
         /// Max links (1-500)
         #[arg(short, long, default_value_t = 100)]
         limit: u32,
+
+        /// Sort order
+        #[arg(long, value_enum, default_value_t = commands::links::LinkSort::Recent)]
+        sort: commands::links::LinkSort,
+
+        /// Fetch page titles over the network (max 20 fetches)
+        #[arg(long)]
+        fetch_titles: bool,
     },
 
     /// Get voice messages with file paths
+    #[command(after_help = examples::VOICE)]
     Voice {
         /// Contact name (optional)
         contact: Option<String>,
@@ -301,7 +885,36 @@ enum Command {
         limit: u32,
     },
 
-    /// Get messages in a reply thread
+    /// Recently deleted messages that can still be recovered (macOS 13+)
+    #[command(after_help = examples::DELETED)]
+    Deleted {
+        /// Days of deletions to include (1-30)
+        #[arg(short, long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=30))]
+        days: u32,
+
+        /// Only messages with this contact (name or handle)
+        #[arg(short, long)]
+        contact: Option<String>,
+    },
+
+    /// Compare with another chat.db (e.g. a backup) by message GUID
+    #[command(after_help = examples::COMPARE)]
+    Compare {
+        /// The other Messages database (attached read-only)
+        #[arg(long)]
+        other: PathBuf,
+
+        /// Only messages from the last N days
+        #[arg(short, long)]
+        days: Option<u32>,
+
+        /// List each differing message, not just per-conversation counts
+        #[arg(long)]
+        show_messages: bool,
+    },
+
+    /// Get the full reply tree containing a message
+    #[command(after_help = examples::THREAD)]
     Thread {
         /// Message GUID to get thread for
         #[arg(short, long)]
@@ -316,6 +929,7 @@ enum Command {
     // T2 COMMANDS - Discovery Features
     // =========================================================================
     /// List all phone/email handles from recent messages
+    #[command(after_help = examples::HANDLES)]
     Handles {
         /// Days to look back (1-365)
         #[arg(short, long, default_value_t = 30)]
@@ -324,9 +938,14 @@ enum Command {
         /// Max handles (1-500)
         #[arg(short, long, default_value_t = 100)]
         limit: u32,
+
+        /// Group handles of the same person (contact, then person_centric_id)
+        #[arg(long)]
+        merge_contacts: bool,
     },
 
     /// Find messages from senders not in contacts
+    #[command(after_help = examples::UNKNOWN)]
     Unknown {
         /// Days to look back (1-365)
         #[arg(short, long, default_value_t = 30)]
@@ -335,9 +954,19 @@ enum Command {
         /// Max unknown senders (1-500)
         #[arg(short, long, default_value_t = 100)]
         limit: u32,
+
+        /// Hide short codes, sender IDs, toll-free and no-reply senders, and
+        /// senders whose texts carry opt-out or one-time-code wording
+        #[arg(long)]
+        exclude_automated: bool,
+
+        /// Keep my own notes-to-self conversation (excluded by default)
+        #[arg(long)]
+        include_self: bool,
     },
 
     /// Discover frequent texters not in contacts
+    #[command(after_help = examples::DISCOVER)]
     Discover {
         /// Days to look back (1-365)
         #[arg(short, long, default_value_t = 90)]
@@ -350,12 +979,149 @@ enum Command {
         /// Minimum message count to include
         #[arg(short, long, default_value_t = 5)]
         min_messages: u32,
+
+        /// Hide short codes, sender IDs, toll-free and no-reply senders, and
+        /// senders whose texts carry opt-out or one-time-code wording
+        /// (on by default; pass --exclude-automated=false to keep them)
+        #[arg(long, default_value_t = true, num_args = 0..=1, default_missing_value = "true", action = clap::ArgAction::Set)]
+        exclude_automated: bool,
+
+        /// Walk candidates and add confirmed ones to contacts
+        #[arg(short, long)]
+        interactive: bool,
+
+        /// Keep my own notes-to-self conversation (excluded by default)
+        #[arg(long)]
+        include_self: bool,
+    },
+
+    /// List scheduled sends (queued with send --at/--in), or cancel one
+    #[command(after_help = examples::SCHEDULED)]
+    Scheduled {
+        #[command(subcommand)]
+        action: Option<ScheduledAction>,
+    },
+
+    /// Mute a conversation: hidden from unread, recent --per-conversation, digest, watch --notify
+    #[command(after_help = examples::MUTE)]
+    Mute {
+        /// Group id (chat identifier, from `groups`) or contact name/phone/email
+        target: String,
+
+        /// Mute until a local time (2026-01-12T09:00, 2026-01-12) or for a duration (8h, 7d)
+        #[arg(long)]
+        until: Option<String>,
+    },
+
+    /// Unmute a conversation
+    #[command(after_help = examples::UNMUTE)]
+    Unmute {
+        /// Group id or contact, as given to `mute`
+        target: String,
+    },
+
+    /// List muted conversations (~/.wolfies-imessage/mutes.json)
+    #[command(after_help = examples::MUTES)]
+    Mutes {
+        #[command(subcommand)]
+        action: Option<MutesAction>,
+    },
+
+    /// Replay a preset saved with --save-as; flags after the name override its values
+    #[command(after_help = examples::RUN)]
+    Run {
+        /// Preset name
+        name: String,
+
+        /// Flags of the preset's command (e.g. --days 2)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        overrides: Vec<String>,
+    },
+
+    /// List or delete presets (~/.wolfies-imessage/presets.json)
+    #[command(after_help = examples::PRESETS)]
+    Presets {
+        #[command(subcommand)]
+        action: Option<PresetsAction>,
+    },
+
+    /// Queue messages for review before sending (~/.wolfies-imessage/drafts.json)
+    #[command(after_help = examples::DRAFT)]
+    Draft {
+        #[command(subcommand)]
+        action: DraftAction,
     },
 
-    /// Get scheduled messages (pending sends)
-    Scheduled,
+    /// Maintain the extracted-text cache (~/.wolfies-imessage/textcache.db)
+    #[command(after_help = examples::CACHE)]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Inventory and prune the data directory (~/.wolfies-imessage)
+    #[command(after_help = examples::MAINTENANCE)]
+    Maintenance {
+        #[command(subcommand)]
+        action: MaintenanceAction,
+    },
+
+    /// Show the Apple ID and phone numbers Messages sends from (iMessage/SMS)
+    #[command(after_help = examples::ACCOUNT)]
+    Account,
+
+    /// Check database access and contacts
+    #[command(after_help = examples::DOCTOR)]
+    Doctor {
+        /// Also EXPLAIN the main date-bounded queries and flag full scans of message
+        #[arg(long)]
+        performance: bool,
+    },
+
+    /// Dump one message row as stored (all columns, blob decode, chats,
+    /// attachments, tapbacks) as JSON, for parser bug reports
+    #[command(after_help = examples::RAW_MESSAGE)]
+    RawMessage {
+        /// Message GUID
+        #[arg(long, required_unless_present = "rowid", conflicts_with = "rowid")]
+        guid: Option<String>,
+
+        /// Message ROWID
+        #[arg(long)]
+        rowid: Option<i64>,
+
+        /// Replace message text (and the text in attributedBody) with x's, so the dump can be shared
+        #[arg(long)]
+        redact_text: bool,
+    },
+
+    /// Inspect configuration (~/.wolfies-imessage/config.toml or $WOLFIES_CONFIG)
+    #[command(after_help = examples::CONFIG)]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Print the JSON Schema of a command's --json output
+    #[command(after_help = examples::SCHEMA)]
+    Schema {
+        /// Command name (e.g. recent, text-search, digest)
+        command: Option<String>,
+
+        /// Print every schema, keyed by command
+        #[arg(long, conflicts_with = "command")]
+        all: bool,
+    },
+
+    /// Usage recipes by topic (reading, sending, analytics, llm, daemon, setup) or command
+    #[command(after_help = examples::EXAMPLES_CMD)]
+    Examples {
+        /// Topic or command name (omit for every topic)
+        topic: Option<String>,
+    },
 
     /// Get conversation formatted for AI summarization
+    #[command(after_help = examples::SUMMARY)]
     Summary {
         /// Contact name
         contact: String,
@@ -376,19 +1142,36 @@ enum Command {
         #[arg(short, long, default_value_t = 200)]
         limit: u32,
 
-        /// Skip this many messages (pagination)
+        /// Skip this many messages (small values; prefer --cursor for paging)
         #[arg(long, default_value_t = 0)]
         offset: u32,
 
+        /// Continue after a previous page (its meta.next_cursor)
+        #[arg(long, conflicts_with = "offset")]
+        cursor: Option<String>,
+
         /// Sort order by date
-        #[arg(long, default_value = "asc")]
+        #[arg(long, default_value = "asc", value_parser = ["asc", "desc"])]
         order: String,
+
+        /// Keep recently deleted messages in the transcript, marked as deleted
+        #[arg(long)]
+        include_deleted: bool,
+
+        /// Tag each message's language and report the conversation's language mix
+        #[arg(long)]
+        detect_language: bool,
+
+        /// Attach each message's tapbacks (reactions: emoji, from, is_from_me)
+        #[arg(long)]
+        with_reactions: bool,
     },
 
     // =========================================================================
     // SETUP COMMAND
     // =========================================================================
     /// Configure Messages database access (one-time setup)
+    #[command(after_help = examples::SETUP)]
     Setup {
         /// Skip confirmation prompts
         #[arg(short, long)]
@@ -403,6 +1186,7 @@ enum Command {
     // RAG COMMANDS - Delegate to Python daemon
     // =========================================================================
     /// Index content for semantic search (via daemon)
+    #[command(after_help = examples::INDEX)]
     Index {
         /// Source to index
         #[arg(short, long)]
@@ -426,6 +1210,7 @@ enum Command {
     },
 
     /// Semantic search across indexed content (via daemon)
+    #[command(after_help = examples::SEARCH)]
     Search {
         /// Search query
         query: String,
@@ -444,6 +1229,7 @@ enum Command {
     },
 
     /// Get AI-formatted context from knowledge base (via daemon)
+    #[command(after_help = examples::ASK)]
     Ask {
         /// Question to answer
         question: String,
@@ -459,9 +1245,14 @@ enum Command {
         /// Max results to include
         #[arg(short, long, default_value_t = 5)]
         limit: u32,
+
+        /// Fall back to a local keyword search of iMessages if the daemon is unavailable
+        #[arg(long)]
+        local_fallback: bool,
     },
 
     /// Show knowledge base statistics (via daemon)
+    #[command(after_help = examples::STATS)]
     Stats {
         /// Show stats for specific source
         #[arg(short, long)]
@@ -469,6 +1260,7 @@ enum Command {
     },
 
     /// Clear indexed data (via daemon)
+    #[command(after_help = examples::CLEAR)]
     Clear {
         /// Clear only this source
         #[arg(short, long)]
@@ -480,9 +1272,186 @@ enum Command {
     },
 
     /// List available and indexed sources (via daemon)
+    #[command(after_help = examples::SOURCES)]
     Sources,
 }
 
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum ScheduledAction {
+    /// Cancel a pending scheduled send
+    Cancel {
+        /// Job id (from `scheduled`)
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum MutesAction {
+    /// List mutes still in effect
+    List,
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum PresetsAction {
+    /// List saved presets
+    List,
+    /// Delete a preset
+    Delete {
+        /// Preset name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum FollowupAction {
+    /// Leave a contact out of followup for a while
+    Snooze {
+        /// Contact name, phone, or email
+        contact: String,
+        /// Duration (8h, 7d), date, or local time
+        #[arg(long, default_value = "1d")]
+        until: String,
+    },
+    /// Bring a snoozed contact back into followup
+    Unsnooze {
+        /// Contact name, phone, or email
+        contact: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum DraftAction {
+    /// Draft a message to a contact (name or phone) without sending it
+    Add {
+        /// Contact name or phone
+        contact: String,
+        /// Message text
+        #[arg(required = true)]
+        message: Vec<String>,
+    },
+    /// List pending drafts
+    List {
+        /// Show the sent log instead
+        #[arg(long)]
+        sent: bool,
+    },
+    /// Send a draft (contact is re-resolved; failed drafts stay queued)
+    Send {
+        /// Draft id (from `draft list`)
+        #[arg(required_unless_present = "all")]
+        id: Option<String>,
+        /// Send every pending draft
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+        /// Send even if it exceeds the send rate limit
+        #[arg(long)]
+        force_send: bool,
+    },
+    /// Discard a pending draft
+    Delete {
+        /// Draft id (from `draft list`)
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum ContactsAction {
+    /// List contacts.json backups (~/.wolfies-imessage/contacts-backups)
+    History,
+    /// Show added/removed/changed contacts versus a backup
+    Diff {
+        /// Backup file name (from `contacts history`) or path
+        backup: String,
+    },
+    /// Replace contacts.json with a backup (current file is backed up first)
+    Restore {
+        /// Backup file name (from `contacts history`) or path
+        backup: String,
+    },
+    /// Find duplicate contacts (shared phone or near-identical name) and propose merges
+    Dedupe {
+        /// Merge them into contacts.json (the current file is backed up first)
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum ConfigAction {
+    /// Print the effective configuration with each value's source
+    Show,
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum CacheAction {
+    /// Delete the cache (it is rebuilt on demand)
+    Clear,
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+enum MaintenanceAction {
+    /// List every file in the data directory with its size, age, and kind
+    Status,
+    /// Delete caches, rotated logs, or leftover daemon files
+    Prune {
+        /// The text cache and its sidecars (kept while a daemon is running)
+        #[arg(long)]
+        caches: bool,
+        /// Rotated daemon logs (daemon.log.*)
+        #[arg(long)]
+        logs: bool,
+        /// Only files last modified longer ago than this (e.g. 30d, 12h)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Caches, rotated logs, and sockets/pid files of daemons that are gone
+        #[arg(long)]
+        all: bool,
+        /// With --all, also delete contacts backups and pending drafts/scheduled sends
+        #[arg(long, requires = "all")]
+        force: bool,
+    },
+}
+
+/// Commands whose output is a list of records (`--csv`).
+fn csv_supported(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Recent { .. }
+            | Command::Messages { .. }
+            | Command::TextSearch { .. }
+            | Command::Attachments { .. }
+            | Command::Links { .. }
+            | Command::Handles { .. }
+            | Command::Analytics { top_terms: true, .. }
+            | Command::Analytics { series: Some(_), .. }
+    )
+}
+
+/// `run <name> [overrides]`: the preset's command and global flags with the
+/// flags after the name (`matches`, `overrides`) applied. Returns the global
+/// flags now in effect, for `--save-as`.
+fn replay_preset(
+    cli: &mut Cli,
+    matches: &clap::ArgMatches,
+    name: &str,
+    overrides: &[String],
+) -> anyhow::Result<clap::ArgMatches> {
+    let preset = presets::PresetStore::open_default().get(name)?;
+    let (command, globals) = preset.replay(Cli::command(), matches, overrides)?;
+    cli.update_from_arg_matches(&globals)?;
+    cli.command = command;
+    Ok(globals)
+}
+
 fn main() -> ExitCode {
     // Initialize tracing/logging
     tracing_subscriber::fmt()
@@ -492,145 +1461,561 @@ fn main() -> ExitCode {
         )
         .init();
 
-    let cli = Cli::parse();
+    let mut matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    output::start_clock();
+
+    // `run` becomes the preset's command before anything looks at it
+    if let Command::Run { name, overrides } = &cli.command {
+        let (name, overrides) = (name.clone(), overrides.clone());
+        match replay_preset(&mut cli, &matches, &name, &overrides) {
+            Ok(globals) => matches = globals,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                return ExitCode::from(1);
+            }
+        }
+    }
+    if let Some(ref name) = cli.save_as {
+        let saved = match cli.command {
+            Command::Run { .. } | Command::Presets { .. } => {
+                Err(anyhow::anyhow!("--save-as can't save `run` or `presets`"))
+            }
+            ref command => presets::Preset::capture(name, command, presets::saved_globals(&Cli::command(), &matches))
+                .and_then(|preset| presets::PresetStore::open_default().save(preset)),
+        };
+        if let Err(e) = saved {
+            eprintln!("Error: failed to save preset '{}': {:#}", name, e);
+            return ExitCode::from(1);
+        }
+        eprintln!("Saved preset '{}'", name);
+    }
 
-    // Build output controls from global flags
-    let output_controls = output::OutputControls {
-        json: cli.json,
-        compact: cli.compact,
-        minimal: cli.minimal,
+    if cli.csv && !csv_supported(&cli.command) {
+        eprintln!(
+            "Error: --csv needs list output (recent, messages, text-search, attachments, links, handles, \
+             analytics --top-terms/--series); use --json for this command"
+        );
+        return ExitCode::from(1);
+    }
+
+    let redactor = match (cli.redact, cli.redact_salt.as_deref()) {
+        (false, _) => None,
+        (true, None) => Some(Arc::new(output::Redactor::new(None))),
+        (true, Some(path)) => match output::Redactor::with_salt_file(path) {
+            Ok(r) => Some(Arc::new(r)),
+            Err(e) => {
+                eprintln!("Error: failed to read redact salt {}: {}", path.display(), e);
+                return ExitCode::from(1);
+            }
+        },
+    };
+
+    // Merge flags over config.toml over built-in defaults
+    let config_path = config::config_path();
+    let file_config = match config::FileConfig::load(&config_path) {
+        Ok((file_config, warnings)) => {
+            for warning in warnings {
+                output::warn(format!("{} in {}", warning, config_path.display()));
+            }
+            file_config
+        }
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            return ExitCode::from(1);
+        }
+    };
+    let dry_run = matches!(
+        cli.command,
+        Command::Send { dry_run: true, .. } | Command::SendByPhone { dry_run: true, .. }
+    );
+    let force_send = matches!(
+        cli.command,
+        Command::Send { force_send: true, .. }
+            | Command::SendByPhone { force_send: true, .. }
+            | Command::Draft { action: DraftAction::Send { force_send: true, .. } }
+    );
+    let flags = config::Overrides {
+        json: (cli.json || cli.envelope).then_some(true),
+        compact: cli.compact.then_some(true),
+        minimal: cli.minimal.then_some(true),
         fields: cli.fields.clone(),
         max_text_chars: cli.max_text_chars,
+        db_path: cli.db_path.as_deref().map(|s| PathBuf::from(shellexpand::tilde(s).to_string())),
+        daemon_socket: cli.socket.as_deref().map(|s| PathBuf::from(shellexpand::tilde(s).to_string())),
+        auto_start_daemon: cli.auto_start_daemon.then_some(true),
+        send_dry_run: dry_run.then_some(true),
+        send_force: force_send.then_some(true),
     };
+    config::install(config::Settings::resolve(&config_path, &file_config, &flags));
+    let settings = config::active();
 
-    // Load contacts once (shared across commands)
-    let contacts = Arc::new(
-        contacts::manager::ContactsManager::load_default()
-            .unwrap_or_else(|_| contacts::manager::ContactsManager::empty())
-    );
+    // Build output controls from the merged settings
+    let output_controls = output::OutputControls {
+        json: settings.json.value,
+        compact: settings.compact.value,
+        minimal: settings.minimal.value,
+        fields: settings.fields.value.clone(),
+        max_text_chars: settings.max_text_chars.value,
+        redactor,
+        csv: cli.csv,
+        envelope: cli.envelope || (cli.explain && settings.json.value),
+        style: output::TextStyle::detect(cli.no_color),
+        quiet: cli.quiet,
+        explain: cli.explain,
+    };
+
+    // Text output is printed as it's rendered, without the redaction pass
+    let json_only = matches!(cli.command, Command::RawMessage { .. });
+    if output_controls.redactor.is_some() && !(output_controls.json || output_controls.csv || json_only) {
+        eprintln!("Error: --redact applies to JSON and CSV output; add --json or --csv");
+        return ExitCode::from(1);
+    }
+
+    if !cli.no_cache {
+        db::text_cache::install(db::text_cache::TextCache::new(
+            Some(db::text_cache::default_path()),
+            0,
+        ));
+    }
+
+    let rag_client =
+        || commands::rag::client(&settings.daemon_socket.value, settings.auto_start_daemon.value);
+
+    // Load contacts once, on first use (shared across commands; unread --count-only never reads them)
+    let contacts = LazyCell::new(|| Arc::new(contacts::manager::ContactsManager::load_default_or_empty()));
+
+    if cli.explain {
+        db::connection::record_statements();
+    }
 
     let result = match cli.command {
         // Core reading commands
-        Command::Find { contact, query, limit } => {
-            commands::reading::find(&contact, query.as_deref(), limit, &output_controls)
+        Command::Find { contact, query, limit, entities, service, include_system, exclude_system } => {
+            let include_system = db::item_kind::include_system_from_flags(include_system, exclude_system, true);
+            api::ServiceFilter::parse(&service).and_then(|service| {
+                commands::reading::find(
+                    &contact, query.as_deref(), limit, entities, service, include_system, false, &output_controls, &contacts,
+                )
+            })
         }
-        Command::Messages { contact, limit } => {
-            commands::reading::messages(&contact, limit, &output_controls)
+        Command::Messages { contact, limit, entities, service, include_system, exclude_system, with_reactions } => {
+            let include_system = db::item_kind::include_system_from_flags(include_system, exclude_system, true);
+            api::ServiceFilter::parse(&service).and_then(|service| {
+                commands::reading::messages(
+                    &contact, limit, entities, service, include_system, with_reactions, &output_controls, &contacts,
+                )
+            })
         }
-        Command::Recent { limit } => {
-            commands::reading::recent(limit, &output_controls)
+        Command::Recent { limit, per_conversation, relationship, entities, include_muted, known_only, unknown_only } => {
+            commands::reading::recent(
+                limit,
+                per_conversation,
+                relationship.as_deref(),
+                entities,
+                include_muted,
+                senders::SenderMode::from_flags(known_only, unknown_only),
+                &output_controls,
+                &contacts,
+            )
+        }
+        Command::Unread { by_conversation: true, include_muted, .. } => {
+            commands::reading::unread_by_conversation(include_muted, &output_controls, &contacts)
         }
-        Command::Unread { limit } => {
-            commands::reading::unread(limit, &output_controls)
+        Command::Unread { count_only: true, include_muted, .. } => {
+            commands::reading::unread_count(include_muted, &output_controls)
         }
-        Command::TextSearch { query, contact, limit, days, since } => {
-            commands::reading::text_search(&query, contact.as_deref(), limit, days, since.as_deref(), &output_controls)
+        Command::Unread { limit, relationship, entities, include_muted, known_only, unknown_only, .. } => {
+            commands::reading::unread(
+                limit,
+                relationship.as_deref(),
+                entities,
+                include_muted,
+                senders::SenderMode::from_flags(known_only, unknown_only),
+                &output_controls,
+                &contacts,
+            )
         }
-        Command::Bundle { contact, query, days, since, unread_limit, recent_limit, search_limit, messages_limit, search_scoped_to_contact, include } => {
+        Command::Quick => commands::quick::quick(&output_controls, &contacts),
+        Command::Digest { since, relationship, include_muted, known_only, unknown_only } => commands::digest::digest(
+            since.as_deref(),
+            relationship.as_deref(),
+            include_muted,
+            senders::SenderMode::from_flags(known_only, unknown_only),
+            &output_controls,
+            &contacts,
+        ),
+        Command::Watch { interval, notify, notify_window, filter_contacts, include_muted, known_only, unknown_only } => {
+            let notify_opts = notify.then_some(commands::watch::NotifyOptions {
+                window_secs: notify_window,
+                filter_contacts,
+                include_muted,
+            });
+            commands::watch::watch(
+                interval,
+                notify_opts.as_ref(),
+                senders::SenderMode::from_flags(known_only, unknown_only),
+                &output_controls,
+                &contacts,
+            )
+        }
+        Command::TextSearch { query, any, all, contact, group_id, group, limit, days, since, include_system, exclude_system } => {
+            commands::reading::text_search(
+                query.as_deref(), &any, &all, contact.as_deref(), group_id.or(group).as_deref(), limit,
+                db::item_kind::include_system_from_flags(include_system, exclude_system, false), days,
+                since.as_deref(), &output_controls, &contacts,
+            )
+        }
+        Command::Bundle {
+            contact,
+            query,
+            days,
+            since,
+            unread_limit,
+            recent_limit,
+            search_limit,
+            messages_limit,
+            search_scoped_to_contact,
+            include,
+            max_tokens,
+            section_priority,
+        } => {
+            let budget = max_tokens.map(|max| (max, budget::parse_priority(section_priority.as_deref())));
             commands::reading::bundle(
                 contact.as_deref(), query.as_deref(), days, since.as_deref(),
                 unread_limit, recent_limit, search_limit, messages_limit,
-                search_scoped_to_contact, include.as_deref(), &output_controls
+                search_scoped_to_contact, include.as_deref(), budget, &output_controls
             )
         }
 
         // Messaging commands
-        Command::Send { contact, message } => {
-            commands::messaging::send(&contact, &message.join(" "), &output_controls)
+        Command::Send { contact, message, message_file, stdin, at, delay, .. } => {
+            let body = commands::messaging::message_body(&message, message_file.as_deref(), stdin);
+            match (body, scheduler::resolve_send_time(at.as_deref(), delay.as_deref(), chrono::Local::now())) {
+                (Ok(body), Ok(Some(send_at))) => commands::scheduled::schedule_to_contact(
+                    &contact, &body, send_at, &output_controls, &contacts,
+                ),
+                (Ok(body), Ok(None)) => commands::messaging::send(&contact, &body, &output_controls),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
+        }
+        Command::SendByPhone { phone, message, message_file, stdin, at, delay, .. } => {
+            let body = commands::messaging::message_body(&message, message_file.as_deref(), stdin);
+            match (body, scheduler::resolve_send_time(at.as_deref(), delay.as_deref(), chrono::Local::now())) {
+                (Ok(body), Ok(Some(send_at))) => commands::scheduled::schedule_to_phone(
+                    &phone, &body, send_at, &output_controls,
+                ),
+                (Ok(body), Ok(None)) => commands::messaging::send_by_handle(&phone, &body, &output_controls),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
         }
-        Command::SendByPhone { phone, message } => {
-            commands::messaging::send_by_phone(&phone, &message.join(" "), &output_controls)
+        Command::MarkRead { contact, group_id, all } => {
+            commands::messaging::mark_read(
+                contact.as_deref(),
+                group_id.as_deref(),
+                all,
+                &output_controls,
+                &contacts,
+            )
         }
+        Command::SendLog { days } => commands::messaging::send_log(days, &output_controls, &contacts),
 
         // Contact commands
-        Command::Contacts => {
+        Command::Contacts { action: Some(action), .. } => match action {
+            ContactsAction::History => commands::contacts::history(&output_controls),
+            ContactsAction::Diff { backup } => commands::contacts::diff(&backup, &output_controls),
+            ContactsAction::Restore { backup } => commands::contacts::restore(&backup, &output_controls),
+            ContactsAction::Dedupe { apply } => commands::contacts::dedupe(apply, &output_controls),
+        },
+        Command::Contacts { stats: true, days, sort, .. } => {
+            commands::contacts::ContactSort::parse(&sort)
+                .and_then(|sort| commands::contacts::stats(days, sort, &output_controls, &contacts))
+        }
+        Command::Contacts { .. } => {
             commands::contacts::list(&output_controls)
         }
-        Command::AddContact { name, phone, relationship, notes } => {
-            commands::contacts::add(&name, &phone, &relationship, notes.as_deref())
+        Command::AddContact { name, phone, relationship, notes, merge } => {
+            commands::contacts::add(&name, &phone, &relationship, notes.as_deref(), merge)
         }
 
         // Analytics commands
-        Command::Analytics { contact, days } => {
-            commands::analytics::analytics(contact.as_deref(), days, cli.json, &contacts)
+        Command::Analytics { contact: Some(contact), days, top_terms: true, me, .. } => {
+            commands::analytics::top_terms(&contact, days, me.as_deref(), &output_controls, &contacts)
+        }
+        Command::Analytics { contact: Some(contact), days, emoji: true, .. } => {
+            commands::analytics::emoji_stats(&contact, days, &output_controls, &contacts)
+        }
+        Command::Analytics { contact: Some(contact), days, gaps: true, min_gap_days, .. } => {
+            commands::analytics::gaps(&contact, days, min_gap_days, &output_controls, &contacts)
+        }
+        Command::Analytics { contact: Some(contact), days, streaks: true, all_time, .. } => {
+            commands::analytics::streaks(&contact, days, all_time, &output_controls, &contacts)
+        }
+        Command::Analytics { contact, days, heatmap: true, .. } => {
+            commands::analytics::heatmap(contact.as_deref(), days, &output_controls, &contacts)
         }
-        Command::Followup { days, stale } => {
-            commands::analytics::followup(days, stale, cli.json, &contacts)
+        Command::Analytics { contact, days, series: Some(series), .. } => {
+            db::helpers::SeriesBucket::parse(&series).and_then(|bucket| {
+                commands::analytics::series(contact.as_deref(), bucket, days, &output_controls, &contacts)
+            })
+        }
+        Command::Analytics { contact, days, relationship, include_self, compare, start, end, series: None, .. } => {
+            commands::analytics::analytics(
+                contact.as_deref(),
+                relationship.as_deref(),
+                days,
+                start.as_deref(),
+                end.as_deref(),
+                include_self,
+                compare,
+                &output_controls,
+                &contacts,
+            )
+        }
+        Command::Followup { action: Some(FollowupAction::Snooze { contact, until }), .. } => {
+            commands::followup::snooze(&contact, &until, &output_controls, &contacts)
+        }
+        Command::Followup { action: Some(FollowupAction::Unsnooze { contact }), .. } => {
+            commands::followup::unsnooze(&contact, &output_controls, &contacts)
+        }
+        Command::Followup {
+            days,
+            stale,
+            relationship,
+            include_self,
+            include_groups,
+            min_question_score,
+            exclude_automated,
+            include_system,
+            exclude_system,
+            action: None,
+        } => {
+            let opts = commands::followup::FollowupOptions {
+                days,
+                stale,
+                relationship,
+                include_self,
+                include_groups,
+                min_question_score,
+                exclude_automated,
+                include_system: db::item_kind::include_system_from_flags(include_system, exclude_system, false),
+            };
+            commands::followup::followup(&opts, &output_controls, &contacts)
+        }
+        Command::Commitments { days, emit_ics } => {
+            commands::commitments::commitments(days, emit_ics.as_deref(), &output_controls, &contacts)
         }
 
         // Group commands
         Command::Groups { limit } => {
-            commands::groups::list(limit, cli.json)
+            commands::groups::list(limit, &output_controls)
         }
-        Command::GroupMessages { group_id, participant, limit } => {
-            commands::groups::messages(group_id.as_deref(), participant.as_deref(), limit, cli.json)
+        Command::GroupAnalytics { group, days, me } => {
+            commands::groups::group_analytics(&group, days, me.as_deref(), &output_controls, &contacts)
+        }
+        Command::GroupHistory { group } => {
+            commands::groups::group_history(&group, &output_controls, &contacts)
+        }
+        Command::GroupMessages { group_id, participant, limit, with_reactions } => {
+            commands::groups::messages(group_id.as_deref(), participant.as_deref(), limit, with_reactions, &output_controls)
+        }
+        Command::ChatInfo { target } => {
+            commands::chat_info::chat_info(&target, &output_controls, &contacts)
         }
 
         // T1 commands
-        Command::Attachments { contact, mime_type, limit } => {
-            commands::reading::attachments(contact.as_deref(), mime_type.as_deref(), limit, cli.json)
+        Command::Attachments {
+            contact,
+            mime_type,
+            limit,
+            copy_to,
+            newest_first,
+            max_bytes,
+            convert_heic,
+            image_meta,
+            dedupe,
+            max_hash_bytes,
+        } => {
+            let copy = copy_to.map(|dest_dir| commands::attachments::CopyOptions {
+                dest_dir,
+                newest_first,
+                max_bytes,
+                convert_heic,
+            });
+            let dedupe = dedupe.then_some(commands::attachments::DedupeOptions { max_hash_bytes });
+            commands::attachments::attachments(
+                contact.as_deref(),
+                mime_type.as_deref(),
+                limit,
+                copy.as_ref(),
+                image_meta,
+                dedupe,
+                &output_controls,
+                &contacts,
+            )
         }
         Command::Reactions { contact, limit } => {
-            commands::reading::reactions(contact.as_deref(), limit, cli.json)
+            commands::reading::reactions(contact.as_deref(), limit, &output_controls)
         }
-        Command::Links { contact, days, all_time, limit } => {
-            commands::reading::links(contact.as_deref(), days, all_time, limit, cli.json)
+        Command::Links { contact, days, all_time, limit, sort, fetch_titles } => {
+            commands::links::links(
+                contact.as_deref(), days, all_time, limit, sort, fetch_titles, &output_controls, &contacts,
+            )
         }
         Command::Voice { contact, limit } => {
-            commands::reading::voice(contact.as_deref(), limit, cli.json)
+            commands::reading::voice(contact.as_deref(), limit, &output_controls, &contacts)
+        }
+        Command::Deleted { days, contact } => {
+            commands::deleted::deleted(days, contact.as_deref(), &output_controls, &contacts)
+        }
+        Command::Compare { other, days, show_messages } => {
+            let other = PathBuf::from(shellexpand::tilde(&other.to_string_lossy()).to_string());
+            commands::compare::compare(&other, days, show_messages, &output_controls, &contacts)
         }
         Command::Thread { guid, limit } => {
-            commands::reading::thread(&guid, limit, cli.json)
+            commands::reading::thread(&guid, limit, &output_controls, &contacts)
         }
 
         // T2 commands
-        Command::Handles { days, limit } => {
-            commands::discovery::handles(days, limit, cli.json)
+        Command::Handles { days, limit, merge_contacts } => {
+            commands::discovery::handles(days, limit, merge_contacts, &output_controls, &contacts)
+        }
+        Command::Unknown { days, limit, exclude_automated, include_self } => {
+            commands::discovery::unknown(days, limit, exclude_automated, include_self, &output_controls, &contacts)
+        }
+        Command::Discover { days, limit, min_messages, exclude_automated, interactive, include_self } => {
+            commands::discovery::discover(
+                days,
+                limit,
+                min_messages,
+                exclude_automated,
+                interactive,
+                include_self,
+                &output_controls,
+                &contacts,
+            )
+        }
+        Command::Scheduled { action } => match action {
+            None => commands::scheduled::list(&output_controls),
+            Some(ScheduledAction::Cancel { id }) => commands::scheduled::cancel(&id, &output_controls),
+        },
+        Command::Mute { target, until } => {
+            commands::mutes::mute(&target, until.as_deref(), &output_controls, &contacts)
         }
-        Command::Unknown { days, limit } => {
-            commands::discovery::unknown(days, limit, cli.json, &contacts)
+        Command::Unmute { target } => commands::mutes::unmute(&target, &output_controls, &contacts),
+        Command::Mutes { action: None | Some(MutesAction::List) } => commands::mutes::list(&output_controls),
+        Command::Run { name, .. } => Err(anyhow::anyhow!("Preset '{}' was not replayed", name)),
+        Command::Presets { action: None | Some(PresetsAction::List) } => commands::presets::list(&output_controls),
+        Command::Presets { action: Some(PresetsAction::Delete { name }) } => {
+            commands::presets::delete(&name, &output_controls)
         }
-        Command::Discover { days, limit, min_messages } => {
-            commands::discovery::discover(days, limit, min_messages, cli.json, &contacts)
+        Command::Draft { action } => match action {
+            DraftAction::Add { contact, message } => {
+                commands::draft::add(&contact, &message.join(" "), &output_controls, &contacts)
+            }
+            DraftAction::List { sent } => commands::draft::list(sent, &output_controls),
+            DraftAction::Send { id, all, .. } => commands::draft::send(id.as_deref(), all, &output_controls, &contacts),
+            DraftAction::Delete { id } => commands::draft::delete(&id, &output_controls),
+        },
+        Command::Cache { action: CacheAction::Clear } => commands::cache::clear(&output_controls),
+        Command::Maintenance { action: MaintenanceAction::Status } => commands::maintenance::status(&output_controls),
+        Command::Maintenance { action: MaintenanceAction::Prune { caches, logs, older_than, all, force } } => {
+            older_than.as_deref().map(scheduler::parse_delay).transpose().and_then(|older_than| {
+                let options = commands::maintenance::PruneOptions { caches, logs, all, force, older_than };
+                commands::maintenance::prune(&options, &output_controls)
+            })
         }
-        Command::Scheduled => {
-            commands::discovery::scheduled(cli.json)
+        Command::Account => commands::account::account(&output_controls),
+        Command::Doctor { performance } => commands::doctor::doctor(performance, &output_controls, &contacts),
+        Command::RawMessage { guid, rowid, redact_text } => {
+            let key = match (guid.as_deref(), rowid) {
+                (Some(guid), _) => commands::raw_message::MessageKey::Guid(guid),
+                (None, rowid) => commands::raw_message::MessageKey::Rowid(rowid.unwrap_or_default()),
+            };
+            commands::raw_message::raw_message(key, redact_text, &output_controls)
         }
-        Command::Summary { contact, days, start, end, limit, offset, order } => {
-            commands::reading::summary(&contact, days, start.as_deref(), end.as_deref(), limit, offset, &order, cli.json)
+        Command::Config { action: ConfigAction::Show } => commands::config::show(settings, &output_controls),
+        Command::Schema { command, all } => commands::schema::print(command.as_deref(), all),
+        Command::Examples { topic } => examples::cookbook(topic.as_deref()).map(|text| print!("{}", text)),
+        Command::Summary {
+            contact,
+            days,
+            start,
+            end,
+            limit,
+            offset,
+            cursor,
+            order,
+            include_deleted,
+            detect_language,
+            with_reactions,
+        } => {
+            commands::summary::summary(
+                &contact,
+                days,
+                start.as_deref(),
+                end.as_deref(),
+                limit,
+                offset,
+                cursor.as_deref(),
+                &order,
+                include_deleted,
+                detect_language,
+                with_reactions,
+                &output_controls,
+                &contacts,
+            )
         }
 
         // Setup command
         Command::Setup { yes, force } => {
-            commands::setup::run(yes, force, cli.json)
+            commands::setup::run(yes, force, output_controls.json)
         }
 
         // RAG commands (delegate to daemon)
         Command::Index { source, days, limit, contact, full } => {
-            commands::rag::index(&source, days, limit, contact.as_deref(), full, cli.json)
+            rag_client().and_then(|c| commands::rag::index(&c, &source, days, limit, contact.as_deref(), full, &output_controls))
         }
         Command::Search { query, sources, days, limit } => {
-            commands::rag::search(&query, sources.as_deref(), days, limit, cli.json)
+            rag_client().and_then(|c| commands::rag::search(&c, &query, sources.as_deref(), days, limit, &output_controls))
         }
-        Command::Ask { question, sources, days, limit } => {
-            commands::rag::ask(&question, sources.as_deref(), days, limit, cli.json)
+        Command::Ask { question, sources, days, limit, local_fallback } => {
+            rag_client().and_then(|c| {
+                commands::rag::ask(&c, &question, sources.as_deref(), days, limit, local_fallback, &output_controls)
+            })
         }
         Command::Stats { source } => {
-            commands::rag::stats(source.as_deref(), cli.json)
+            rag_client().and_then(|c| commands::rag::stats(&c, source.as_deref(), &output_controls))
         }
         Command::Clear { source, force } => {
-            commands::rag::clear(source.as_deref(), force, cli.json)
+            rag_client().and_then(|c| commands::rag::clear(&c, source.as_deref(), force, &output_controls))
         }
         Command::Sources => {
-            commands::rag::sources(cli.json)
+            rag_client().and_then(|c| commands::rag::sources(&c, &output_controls))
         }
     };
 
-    match result {
+    if cli.explain && !output_controls.json {
+        commands::doctor::print_explain();
+    }
+
+    let exit = match result {
         Ok(()) => ExitCode::from(0),
+        Err(e) if output_controls.envelope => {
+            let code = error::error_code(&e).unwrap_or("ERROR");
+            let mut envelope = output::error_envelope(code, &e.to_string(), output_controls.envelope_meta());
+            if let Some(error::CliError::RateLimited { retry_after_secs, .. }) = e.downcast_ref() {
+                envelope["error"]["retry_after_secs"] = (*retry_after_secs).into();
+            }
+            println!("{}", output_controls.format(&envelope));
+            ExitCode::from(error::exit_code(&e))
+        }
         Err(e) => {
             eprintln!("Error: {}", e);
-            ExitCode::from(1)
+            ExitCode::from(error::exit_code(&e))
         }
-    }
+    };
+    // Whatever JSON output didn't carry (text mode: all of them)
+    output::flush_warnings();
+    exit
 }
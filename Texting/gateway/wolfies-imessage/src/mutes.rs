@@ -0,0 +1,284 @@
+//! Muted conversations (~/.wolfies-imessage/mutes.json).
+//!
+//! Muted conversations are left out of unread, recent --per-conversation,
+//! digest, watch --notify, and the daemon's unread/recent/digest/bundle
+//! handlers unless they pass --include-muted (`include_muted` over the
+//! daemon). Groups match by chat_identifier and contacts by normalized
+//! handle (`handle_key`, every handle the contact had when muted) against
+//! the direct chat's identifier, so muting a contact hides their 1:1
+//! conversations but not their messages in groups.
+//! A mute with `until` stops applying once that time passes; expired
+//! entries are pruned on the next write.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - MuteStore is a storage::ExpiringStore (shared with snoozes)
+//! - 10/16/2026 - Contact mutes cover every handle of the contact (Mute.handles), not only the primary one
+//! - 10/16/2026 - Path comes from the data file registry (data_files::MUTES)
+//! - 10/16/2026 - Chat rows that fail to decode are skipped with a warning (helpers::ok_row)
+//! - 10/16/2026 - Initial mute store and MuteFilter (SQL exclusion by chat ROWID)
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::db::helpers::{handle_key, is_group_chat_identifier, ok_row, rowids_condition};
+use crate::output;
+use crate::data_files;
+use crate::storage::{Expiring, ExpiringStore};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteKind {
+    Group,
+    Contact,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Mute {
+    pub kind: MuteKind,
+    /// Group chat_identifier, or the contact's normalized handle
+    pub key: String,
+    /// The contact's other handles, normalized (their other 1:1 chats)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handles: Vec<String>,
+    /// Target as given to `mute`
+    pub target: String,
+    /// Group display name or contact name, when known
+    pub name: Option<String>,
+    /// Muted until this time; indefinitely when None
+    pub until: Option<DateTime<Local>>,
+    pub created_at: DateTime<Local>,
+}
+
+impl Mute {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.until.is_none_or(|until| until > now)
+    }
+
+    /// Whether a conversation (by chat_identifier: a group id, or a direct
+    /// chat's handle) is covered by this mute.
+    pub fn matches(&self, chat_identifier: &str) -> bool {
+        let is_group = is_group_chat_identifier(Some(chat_identifier));
+        match self.kind {
+            MuteKind::Group => is_group && chat_identifier == self.key,
+            MuteKind::Contact => {
+                let key = handle_key(chat_identifier);
+                !is_group && (key == self.key || self.handles.contains(&key))
+            }
+        }
+    }
+}
+
+impl Expiring for Mute {
+    const LIST_KEY: &'static str = "mutes";
+
+    fn expires_at(&self) -> Option<DateTime<Local>> {
+        self.until
+    }
+
+    fn same_target(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.key == other.key
+    }
+}
+
+/// Mute list backed by mutes.json.
+pub type MuteStore = ExpiringStore<Mute>;
+
+impl MuteStore {
+    /// Store at the default location.
+    pub fn open_default() -> Self {
+        Self::new(data_files::MUTES.path())
+    }
+
+    /// Remove the mute for a conversation.
+    pub fn remove_mute(&self, kind: MuteKind, key: &str, now: DateTime<Local>) -> Result<Mute> {
+        self.remove(now, |m| m.kind == kind && m.key == key)?
+            .ok_or_else(|| anyhow!("'{}' is not muted", key))
+    }
+}
+
+/// Muted conversations resolved to chat ROWIDs, usable as SQL conditions.
+#[derive(Debug, Clone, Default)]
+pub struct MuteFilter {
+    chat_rowids: Vec<i64>,
+}
+
+impl MuteFilter {
+    /// Resolve mutes against the chat table.
+    pub fn resolve(conn: &Connection, mutes: &[Mute]) -> Result<Self> {
+        let mut stmt = conn.prepare_cached("SELECT ROWID, chat_identifier FROM chat")?;
+        let chat_rowids = stmt
+            .query_map([], |row: &rusqlite::Row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+            })?
+            .filter_map(ok_row)
+            .filter(|(_, id)| id.as_deref().is_some_and(|id| mutes.iter().any(|m| m.matches(id))))
+            .map(|(rowid, _)| rowid)
+            .collect();
+        Ok(Self { chat_rowids })
+    }
+
+    /// Active mutes from the default store, or None with `include_muted` or
+    /// when nothing is muted. An unreadable mutes.json warns and mutes nothing.
+    pub fn load(conn: &Connection, include_muted: bool) -> Result<Option<Self>> {
+        if include_muted {
+            return Ok(None);
+        }
+        let mutes = match MuteStore::open_default().active(Local::now()) {
+            Ok(mutes) => mutes,
+            Err(e) => {
+                output::warn(format!("ignoring mutes.json: {:#}", e));
+                return Ok(None);
+            }
+        };
+        if mutes.is_empty() {
+            return Ok(None);
+        }
+        Self::resolve(conn, &mutes).map(Some)
+    }
+
+    /// Number of muted conversations.
+    pub fn chat_count(&self) -> usize {
+        self.chat_rowids.len()
+    }
+
+    /// SQL condition excluding muted chats from `column` (a chat ROWID).
+    pub fn sql_chat_exclusion(&self, column: &str) -> String {
+        if self.chat_rowids.is_empty() {
+            return "1".to_string();
+        }
+        format!("NOT ({})", rowids_condition(column, &self.chat_rowids))
+    }
+
+    /// SQL condition: the message `column` (a message ROWID) is in a muted chat.
+    pub fn sql_message_condition(&self, column: &str) -> String {
+        format!(
+            "EXISTS (SELECT 1 FROM chat_message_join mute_cmj WHERE mute_cmj.message_id = {} AND {})",
+            column,
+            rowids_condition("mute_cmj.chat_id", &self.chat_rowids)
+        )
+    }
+
+    /// SQL condition: the message `column` (a message ROWID) is not in a muted chat.
+    pub fn sql_message_exclusion(&self, column: &str) -> String {
+        if self.chat_rowids.is_empty() {
+            return "1".to_string();
+        }
+        format!("NOT {}", self.sql_message_condition(column))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{self, UnreadOptions};
+    use crate::contacts::manager::ContactsManager;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+    use crate::db::{helpers, queries};
+    use chrono::Duration;
+
+    fn mute(kind: MuteKind, key: &str, until: Option<DateTime<Local>>) -> Mute {
+        Mute {
+            kind,
+            key: key.to_string(),
+            handles: Vec::new(),
+            target: key.to_string(),
+            name: None,
+            until,
+            created_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_expired_mutes_stop_applying_and_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MuteStore::new(dir.path().join("mutes.json"));
+        let now = Local::now();
+
+        store.add(mute(MuteKind::Group, "chat1", Some(now + Duration::hours(1))), now).unwrap();
+        store.add(mute(MuteKind::Contact, "4155550001", None), now).unwrap();
+        assert_eq!(store.active(now).unwrap().len(), 2);
+
+        // Past `until`: inactive, then dropped from the file on the next write
+        let later = now + Duration::hours(2);
+        let active = store.active(later).unwrap();
+        assert_eq!(active.iter().map(|m| m.key.as_str()).collect::<Vec<_>>(), ["4155550001"]);
+        assert!(store.remove_mute(MuteKind::Group, "chat1", later).is_err());
+        let raw = std::fs::read_to_string(dir.path().join("mutes.json")).unwrap();
+        assert!(!raw.contains("chat1"));
+
+        // Muting again replaces the entry (new until), not a duplicate
+        store.add(mute(MuteKind::Contact, "4155550001", Some(later + Duration::days(1))), later).unwrap();
+        let active = store.active(later).unwrap();
+        assert_eq!(active.len(), 1);
+        assert!(active[0].until.is_some());
+        store.remove_mute(MuteKind::Contact, "4155550001", later).unwrap();
+        assert!(store.active(later).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_matching_by_chat_identifier_and_handle() {
+        let group = mute(MuteKind::Group, "chat123", None);
+        assert!(group.matches("chat123"));
+        assert!(!group.matches("chat1234"));
+
+        let contact = mute(MuteKind::Contact, &handle_key("+1 (415) 555-0001"), None);
+        assert!(contact.matches("+14155550001"));
+        assert!(contact.matches("4155550001"));
+        // A group never matches a contact mute
+        assert!(!contact.matches("chat123"));
+        assert!(!contact.matches("+14155550002"));
+
+        // A contact's other handles are muted with the primary one
+        let contact = Mute { handles: vec![handle_key("alice@example.com")], ..contact };
+        assert!(contact.matches("Alice@Example.com"));
+        assert!(contact.matches("+14155550001"));
+    }
+
+    #[test]
+    fn test_filter_excludes_muted_conversations() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let bob = insert_handle(&conn, "+14155550002");
+        let direct_alice = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let direct_bob = insert_chat(&conn, "+14155550002", None, &[bob]);
+        let group = insert_chat(&conn, "chat900", Some("Noisy"), &[alice, bob]);
+        let now = queries::days_ago_cocoa(0);
+        insert_message(&conn, direct_alice, alice, Some("alice direct"), now - 5, false, false);
+        insert_message(&conn, direct_bob, bob, Some("bob direct"), now - 4, false, false);
+        insert_message(&conn, group, alice, Some("alice in group"), now - 3, false, false);
+        insert_message(&conn, group, bob, Some("bob in group"), now - 2, false, false);
+
+        let mutes = [
+            mute(MuteKind::Group, "chat900", None),
+            mute(MuteKind::Contact, &handle_key("+14155550001"), None),
+        ];
+        let filter = MuteFilter::resolve(&conn, &mutes).unwrap();
+        assert_eq!(filter.chat_count(), 2);
+
+        let contacts = ContactsManager::empty();
+        let opts = UnreadOptions { mutes: Some(filter.clone()), ..Default::default() };
+        let unread = api::unread(&conn, &contacts, &opts).unwrap();
+        assert_eq!(unread.iter().map(|m| m.text.as_str()).collect::<Vec<_>>(), ["bob direct"]);
+        assert_eq!(api::muted_unread_count(&conn, &contacts, &opts).unwrap(), 3);
+        assert_eq!(api::unread_count(&conn, Some(&filter)).unwrap(), 1);
+
+        let rows = api::conversations(&conn, &contacts, 10, None, Some(&filter)).unwrap();
+        assert_eq!(rows.iter().map(|r| r.chat_identifier.as_str()).collect::<Vec<_>>(), ["+14155550002"]);
+        assert_eq!(api::muted_conversation_count(&conn, &contacts, 10, None, Some(&filter)).unwrap(), 2);
+        // Only the muted chats the listing would have shown count
+        assert_eq!(api::muted_conversation_count(&conn, &contacts, 1, None, Some(&filter)).unwrap(), 1);
+
+        let digest_unread = helpers::query_unread_messages(&conn, 10, None, Some(&filter), None).unwrap();
+        assert_eq!(digest_unread.len(), 1);
+
+        // Nothing muted: every conversation comes back
+        let none = MuteFilter::resolve(&conn, &[]).unwrap();
+        let opts = UnreadOptions { mutes: Some(none), ..Default::default() };
+        assert_eq!(api::unread(&conn, &contacts, &opts).unwrap().len(), 4);
+        assert_eq!(api::muted_unread_count(&conn, &contacts, &opts).unwrap(), 0);
+        assert_eq!(api::unread_count(&conn, opts.mutes.as_ref()).unwrap(), 4);
+    }
+}
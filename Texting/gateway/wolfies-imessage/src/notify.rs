@@ -0,0 +1,161 @@
+//! Desktop notification policy: text construction, rate limiting, filtering.
+//!
+//! Kept separate from the osascript call (applescript::display_notification)
+//! so the policy is unit-testable.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - ContactFilter compares with helpers::handle_key instead of its own normalizer
+//! - 10/16/2026 - Initial notification text, per-conversation rate limiter, contact filter
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::helpers::handle_key;
+
+/// Default minimum gap between notifications for one conversation.
+pub const DEFAULT_WINDOW_SECS: u64 = 30;
+
+/// Max characters of message preview in a notification body.
+pub const PREVIEW_CHARS: usize = 120;
+
+/// A notification ready to post.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub body: String,
+}
+
+/// Build a notification for an incoming message.
+///
+/// Title is the sender (contact name when known); group chats get the group
+/// name as subtitle. The preview is truncated on a char boundary; the
+/// "[attachment]" placeholder is never truncated.
+pub fn build_notification(
+    sender: &str,
+    group_name: Option<&str>,
+    text: Option<&str>,
+    max_chars: usize,
+) -> Notification {
+    let body = match text.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(text) if text.chars().count() > max_chars => {
+            let truncated: String = text.chars().take(max_chars).collect();
+            format!("{}…", truncated.trim_end())
+        }
+        Some(text) => text.to_string(),
+        None => "[attachment]".to_string(),
+    };
+
+    Notification {
+        title: sender.to_string(),
+        subtitle: group_name.map(|g| g.to_string()),
+        body,
+    }
+}
+
+/// At most one notification per key (conversation) per window.
+pub struct RateLimiter {
+    window: Duration,
+    last_sent: HashMap<String, Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Returns true (and records the send) if `key` is outside its window.
+    pub fn allow(&mut self, key: &str, now: Instant) -> bool {
+        match self.last_sent.get(key) {
+            Some(last) if now.duration_since(*last) < self.window => false,
+            _ => {
+                self.last_sent.insert(key.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+/// Restricts notifications to a set of contacts (empty = everyone).
+pub struct ContactFilter {
+    /// Normalized handles: last 10 digits for phones, lowercase for emails
+    handles: Vec<String>,
+}
+
+impl ContactFilter {
+    /// Resolve names (or raw handles) to normalized handles.
+    pub fn new(names: &[String], contacts: &ContactsManager) -> Self {
+        let handles = names
+            .iter()
+            .map(|n| n.trim())
+            .filter(|n| !n.is_empty())
+            .map(|n| {
+                let handle = contacts.resolve_to_phone(n).unwrap_or_else(|| n.to_string());
+                handle_key(&handle)
+            })
+            .collect();
+        Self { handles }
+    }
+
+    /// Whether a sender handle passes the filter.
+    pub fn matches(&self, sender_handle: Option<&str>) -> bool {
+        if self.handles.is_empty() {
+            return true;
+        }
+        match sender_handle {
+            Some(h) => {
+                let normalized = handle_key(h);
+                self.handles.contains(&normalized)
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_notification_truncates_on_char_boundary() {
+        let n = build_notification("Alice", None, Some("héllo wörld"), 5);
+        assert_eq!(n.title, "Alice");
+        assert_eq!(n.body, "héllo…");
+        assert_eq!(n.subtitle, None);
+
+        let n = build_notification("Bob", Some("Family"), Some("  "), 10);
+        assert_eq!(n.body, "[attachment]");
+        assert_eq!(n.subtitle.as_deref(), Some("Family"));
+    }
+
+    #[test]
+    fn test_rate_limiter_per_conversation() {
+        let mut limiter = RateLimiter::new(Duration::from_secs(30));
+        let t0 = Instant::now();
+
+        assert!(limiter.allow("chat1", t0));
+        assert!(!limiter.allow("chat1", t0 + Duration::from_secs(10)));
+        assert!(limiter.allow("chat2", t0 + Duration::from_secs(10)));
+        assert!(limiter.allow("chat1", t0 + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_contact_filter() {
+        let contacts = ContactsManager::empty();
+        let filter = ContactFilter::new(
+            &["+1 (415) 555-0001".to_string(), "Friend@Example.com".to_string()],
+            &contacts,
+        );
+        assert!(filter.matches(Some("+14155550001")));
+        assert!(filter.matches(Some("4155550001")));
+        assert!(filter.matches(Some("friend@example.com")));
+        assert!(!filter.matches(Some("+14155550002")));
+        assert!(!filter.matches(None));
+
+        assert!(ContactFilter::new(&[], &contacts).matches(None));
+    }
+}
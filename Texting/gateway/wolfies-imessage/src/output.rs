@@ -1,10 +1,37 @@
 //! Output formatting and control utilities.
 //!
 //! CHANGELOG:
+//! - 10/16/2026 - print_json shapes its output (--fields, --max-text-chars, --redact) like print
+//! - 10/16/2026 - Redactor pseudonymizes display_name/name when they fell back to a raw handle
+//! - 10/16/2026 - print_json applies --redact (it printed raw handles outside --envelope), as does meta.applied; sample_text is masked; looks_like_email comes from contacts::manager
+//! - 10/16/2026 - print, print_muted, show, and print_csv return Result: --csv on output that is not a list is an error (non-zero exit), not a stderr line
+//! - 10/16/2026 - OutputControls.explain: the primary query plan goes in meta.query_plan
+//! - 10/16/2026 - Plain JSON is always the bare payload: warnings go to stderr and muted counts to a stderr note (muted_note), both in meta under --envelope; dropped with_warnings/with_muted_count and their `results` wrapper
+//! - 10/16/2026 - OutputControls::line (shaped compact NDJSON line; watch events)
+//! - 10/16/2026 - TEXT_FIELDS is public (bundle trimming)
+//! - 10/16/2026 - OutputControls.quiet (--quiet: no progress lines on stderr)
+//! - 10/16/2026 - Warnings are collected per thread, not printed as they happen: JSON output carries them, flush_warnings prints the rest to stderr at exit, take_warnings drains them per daemon request
+//! - 10/16/2026 - --envelope: {ok, data, meta} wrapping (envelope, applied, start_clock) and print_json for self-formatting commands
+//! - 10/16/2026 - --csv output for list-shaped results (to_csv: flatten, RFC 4180 quoting)
+//! - 10/16/2026 - muted_count in printed JSON (print_muted, with_muted_count)
+//! - 10/16/2026 - Process warnings (warn): stderr once, `warnings` in printed JSON
+//! - 10/16/2026 - OutputControls::shape (value-level filtering, shared with the daemon)
+//! - 10/16/2026 - Text rendering layer: TextRenderer, TextStyle (--no-color/NO_COLOR/TTY), aligned message lists, Table
+//! - 10/16/2026 - Char-safe truncate_chars and preview (no panics on multi-byte text)
+//! - 10/16/2026 - Added --redact pass (Redactor: handle pseudonyms, path basenames, number masking)
 //! - 01/10/2026 - Initial implementation (Claude)
 
+use regex::Regex;
 use serde::Serialize;
 use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::contacts::manager::looks_like_email;
+use crate::db::helpers::handle_key;
 
 /// Output control settings from CLI flags.
 #[derive(Debug, Clone, Default)]
@@ -14,13 +41,42 @@ pub struct OutputControls {
     pub minimal: bool,
     pub fields: Option<String>,
     pub max_text_chars: Option<u32>,
+    pub redactor: Option<Arc<Redactor>>,
+    /// CSV instead of JSON or text (list-shaped output only; see `to_csv`).
+    pub csv: bool,
+    /// Wrap JSON output as `{ok, data, meta}` (see `envelope`).
+    pub envelope: bool,
+    /// Styling for text (non-JSON) output.
+    pub style: TextStyle,
+    /// No progress lines on stderr (see `progress::for_output`).
+    pub quiet: bool,
+    /// `--explain`: the primary query's plan as `meta.query_plan` (see `envelope_meta`).
+    pub explain: bool,
 }
 
 impl OutputControls {
     /// Emit data according to output controls.
     pub fn emit<T: Serialize>(&self, data: &T) -> String {
-        let value = serde_json::to_value(data).unwrap_or(json!(null));
+        self.format(&self.shape(serde_json::to_value(data).unwrap_or(json!(null))))
+    }
+
+    /// One shaped compact line, for commands that stream NDJSON.
+    pub fn line<T: Serialize>(&self, data: &T) -> String {
+        let shaped = self.shape(serde_json::to_value(data).unwrap_or(json!(null)));
+        serde_json::to_string(&shaped).unwrap_or_else(|_| "{}".to_string())
+    }
 
+    /// Serialize an already-shaped value (compact or pretty).
+    pub fn format(&self, value: &Value) -> String {
+        if self.compact || self.minimal {
+            serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string())
+        }
+    }
+
+    /// Apply field filtering, text truncation, and redaction to a JSON value.
+    pub fn shape(&self, value: Value) -> Value {
         // Apply field filtering if specified
         let filtered = if let Some(ref fields) = self.fields {
             filter_fields(&value, fields)
@@ -38,17 +94,533 @@ impl OutputControls {
             filtered
         };
 
-        // Format output
-        if self.compact || self.minimal {
-            serde_json::to_string(&truncated).unwrap_or_else(|_| "{}".to_string())
+        // Redact last so filtering/truncation can't reintroduce raw values
+        match self.redactor {
+            Some(ref redactor) => redactor.redact(&truncated),
+            None => truncated,
+        }
+    }
+
+    /// Print data to stdout according to output controls (under
+    /// `--envelope`, with process warnings in meta; see `warn`). Fails only
+    /// for `--csv` on output that isn't a list.
+    pub fn print<T: Serialize>(&self, data: &T) -> anyhow::Result<()> {
+        if self.csv {
+            return self.print_csv(data);
+        }
+        let shaped = self.shape(serde_json::to_value(data).unwrap_or(json!(null)));
+        println!("{}", self.format(&self.finish(shaped, 0)));
+        Ok(())
+    }
+
+    /// `print` for results that left out muted conversations: `muted_count`
+    /// in the envelope's meta, otherwise a `muted_note` about the hidden
+    /// `what` on stderr, so the payload keeps its shape.
+    pub fn print_muted<T: Serialize>(&self, data: &T, muted_count: usize, what: &str) -> anyhow::Result<()> {
+        if !self.envelope && muted_count > 0 {
+            eprintln!("{}", muted_note(muted_count, what));
+        }
+        if self.csv {
+            return self.print_csv(data);
+        }
+        let shaped = self.shape(serde_json::to_value(data).unwrap_or(json!(null)));
+        println!("{}", self.format(&self.finish(shaped, muted_count)));
+        Ok(())
+    }
+
+    /// JSON for commands that pick their own layout (pretty or compact),
+    /// shaped like `print`: `print` under `--envelope`.
+    pub fn print_json<T: Serialize + ?Sized>(&self, data: &T, pretty: bool) -> anyhow::Result<()> {
+        if self.envelope {
+            return self.print(&data);
+        }
+        let value = self.shape(serde_json::to_value(data)?);
+        if pretty {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        } else {
+            println!("{}", serde_json::to_string(&value)?);
+        }
+        Ok(())
+    }
+
+    /// Wrap in the envelope (warnings and muted_count in meta); without
+    /// `--envelope` the payload is printed as is and warnings go to stderr.
+    fn finish(&self, shaped: Value, muted_count: usize) -> Value {
+        if !self.envelope {
+            return shaped;
+        }
+        let mut meta = self.envelope_meta();
+        meta["count"] = json!(match &shaped {
+            Value::Array(items) => items.len(),
+            Value::Null => 0,
+            _ => 1,
+        });
+        if muted_count > 0 {
+            meta["muted_count"] = json!(muted_count);
+        }
+        envelope(shaped, meta)
+    }
+
+    /// Envelope meta without `count`: timing, applied parameters, warnings,
+    /// and the query plan under `--explain`.
+    pub fn envelope_meta(&self) -> Value {
+        let mut applied = json!({
+            "fields": self.fields,
+            "max_text_chars": self.max_text_chars,
+            "days": null,
+            "contact_resolved": null,
+        });
+        for (key, value) in APPLIED.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            applied[key] = value.clone();
+        }
+        if let Some(ref redactor) = self.redactor {
+            applied = redactor.redact(&applied);
+        }
+        let mut meta = json!({
+            "elapsed_ms": (elapsed_ms() * 10.0).round() / 10.0,
+            "applied": applied,
+        });
+        if self.explain {
+            // null when no query ran locally (daemon-backed commands)
+            match crate::db::connection::primary_query_plan() {
+                Ok(plan) => meta["query_plan"] = json!(plan),
+                Err(e) => warn(format!("--explain: {:#}", e)),
+            }
+        }
+        let warnings = deliver_warnings();
+        if !warnings.is_empty() {
+            meta["warnings"] = json!(warnings);
+        }
+        meta
+    }
+
+    /// Print records as JSON, or through their `TextRenderer` in text mode.
+    pub fn show<T: Serialize + TextRenderer + ?Sized>(&self, data: &T) -> anyhow::Result<()> {
+        if self.json || self.csv {
+            self.print(&data)?;
         } else {
-            serde_json::to_string_pretty(&truncated).unwrap_or_else(|_| "{}".to_string())
+            println!("{}", data.render(&self.style));
+        }
+        Ok(())
+    }
+
+    /// Print a list of records as CSV (shaped first, so `--fields`,
+    /// `--max-text-chars`, and `--redact` apply). Warnings go to stderr
+    /// (`flush_warnings`). Output that isn't a list is an error.
+    pub fn print_csv<T: Serialize + ?Sized>(&self, records: &T) -> anyhow::Result<()> {
+        let shaped = self.shape(serde_json::to_value(records).unwrap_or(json!(null)));
+        let csv = to_csv(&shaped, self.fields.as_deref()).ok_or_else(|| anyhow::anyhow!("--csv needs list output"))?;
+        print!("{}", csv);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Envelope
+// ============================================================================
+
+/// When the command started (`start_clock`), for `meta.elapsed_ms`.
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// Parameters commands resolved (see `applied`).
+static APPLIED: Mutex<BTreeMap<String, Value>> = Mutex::new(BTreeMap::new());
+
+/// Start the clock `meta.elapsed_ms` measures from (first call wins).
+pub fn start_clock() {
+    STARTED.get_or_init(Instant::now);
+}
+
+fn elapsed_ms() -> f64 {
+    STARTED.get().map(|t| t.elapsed().as_secs_f64() * 1000.0).unwrap_or(0.0)
+}
+
+/// Record a parameter a command resolved (the days window, the phone a
+/// contact name resolved to, ...) for `meta.applied` under `--envelope`.
+pub fn applied(key: &str, value: impl Serialize) {
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    APPLIED.lock().unwrap_or_else(|e| e.into_inner()).insert(key.to_string(), value);
+}
+
+/// `{ok: true, data, meta}`, the daemon's response shape with the payload under `data`.
+pub fn envelope(data: Value, meta: Value) -> Value {
+    json!({"ok": true, "data": data, "meta": meta})
+}
+
+/// `{ok: false, error: {code, message}, meta}` for a failed command.
+pub fn error_envelope(code: &str, message: &str, meta: Value) -> Value {
+    json!({"ok": false, "error": {"code": code, "message": message}, "meta": meta})
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/// Flatten one record to `(column, cell)` pairs: nested objects become
+/// `parent.child` columns (one level; anything deeper is dropped), arrays of
+/// scalars are joined with "; ", and arrays of objects are dropped. A
+/// non-object record is a single `value` column.
+fn flatten_record(record: &Value) -> Vec<(String, Value)> {
+    let Value::Object(map) = record else {
+        return vec![("value".to_string(), record.clone())];
+    };
+    let mut columns = Vec::new();
+    for (key, value) in map {
+        match value {
+            Value::Object(inner) => {
+                for (inner_key, inner_value) in inner {
+                    if let Some(cell) = scalar_cell(inner_value) {
+                        columns.push((format!("{}.{}", key, inner_key), cell));
+                    }
+                }
+            }
+            _ => {
+                if let Some(cell) = scalar_cell(value) {
+                    columns.push((key.clone(), cell));
+                }
+            }
+        }
+    }
+    columns
+}
+
+/// A value that fits in one cell, or None (objects, arrays of objects).
+fn scalar_cell(value: &Value) -> Option<Value> {
+    match value {
+        Value::Object(_) => None,
+        Value::Array(items) => {
+            let parts: Option<Vec<String>> = items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Object(_) | Value::Array(_) => None,
+                    other => Some(other.to_string()),
+                })
+                .collect();
+            parts.map(|parts| Value::String(parts.join("; ")))
+        }
+        _ => Some(value.clone()),
+    }
+}
+
+/// One CSV cell: text is always quoted (RFC 4180, quotes doubled) with
+/// line breaks escaped as `\n` so each record stays on one line; numbers
+/// and booleans are bare; null is empty.
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => {
+            let escaped = s.replace("\r\n", "\\n").replace(['\n', '\r'], "\\n");
+            format!("\"{}\"", escaped.replace('"', "\"\""))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Header cells are quoted only when they need it.
+fn csv_header(name: &str) -> String {
+    if name.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    } else {
+        name.to_string()
+    }
+}
+
+/// Render a JSON array of records as CSV, header first. Columns follow the
+/// `fields` selection when given (a nested object's `parent.child` columns
+/// stay together at the parent's position), otherwise they're alphabetical.
+/// Returns None when `records` isn't an array.
+pub fn to_csv(records: &Value, fields: Option<&str>) -> Option<String> {
+    let Value::Array(items) = records else {
+        return None;
+    };
+    let rows: Vec<Vec<(String, Value)>> = items.iter().map(flatten_record).collect();
+
+    let mut columns: Vec<String> = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+    columns.sort();
+    if let Some(fields) = fields {
+        let rank = |column: &str| {
+            let parent = column.split('.').next().unwrap_or(column);
+            fields
+                .split(',')
+                .map(str::trim)
+                .position(|f| f == column || f == parent)
+                .unwrap_or(usize::MAX)
+        };
+        columns.sort_by_key(|c| rank(c));
+    }
+
+    let mut out = columns.iter().map(|c| csv_header(c)).collect::<Vec<_>>().join(",");
+    out.push('\n');
+    for row in &rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| {
+                row.iter()
+                    .find(|(name, _)| name == column)
+                    .map(|(_, value)| csv_cell(value))
+                    .unwrap_or_default()
+            })
+            .collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    Some(out)
+}
+
+// ============================================================================
+// Warnings
+// ============================================================================
+
+/// Warnings reported so far, and how many of them an envelope has carried.
+#[derive(Default)]
+struct Warnings {
+    messages: Vec<String>,
+    delivered: usize,
+}
+
+thread_local! {
+    /// Per thread: a CLI command runs on the main thread and the daemon
+    /// handles each request on one thread, so concurrent requests (and
+    /// tests) don't see each other's warnings.
+    static WARNINGS: RefCell<Warnings> = RefCell::default();
+}
+
+/// Report a problem the command worked around (e.g. a corrupt contacts.json).
+///
+/// Nothing prints here, so stdout and stderr don't interleave: the
+/// envelope's meta carries the warnings, `flush_warnings` prints the rest to stderr once the command is done, and
+/// the daemon returns each request's in `meta.warnings`. A message is kept once.
+pub fn warn(message: impl Into<String>) {
+    let message = message.into();
+    WARNINGS.with_borrow_mut(|w| {
+        if !w.messages.contains(&message) {
+            w.messages.push(message);
         }
+    });
+}
+
+/// Warnings for the envelope's meta, marked as delivered so `flush_warnings` skips them.
+fn deliver_warnings() -> Vec<String> {
+    WARNINGS.with_borrow_mut(|w| {
+        w.delivered = w.messages.len();
+        w.messages.clone()
+    })
+}
+
+/// Remove and return this thread's warnings (the daemon's per-request collection).
+pub fn take_warnings() -> Vec<String> {
+    WARNINGS.take().messages
+}
+
+/// Print warnings no JSON output carried to stderr, once, at the end of a command.
+pub fn flush_warnings() {
+    WARNINGS.with_borrow_mut(|w| {
+        for message in &w.messages[w.delivered..] {
+            eprintln!("Warning: {}", message);
+        }
+        w.delivered = w.messages.len();
+    });
+}
+
+/// Note that `muted_count` muted `what` (e.g. "conversations") were left out.
+pub fn muted_note(muted_count: usize, what: &str) -> String {
+    format!("({} muted {} hidden; --include-muted to show)", muted_count, what)
+}
+
+// ============================================================================
+// Text rendering
+// ============================================================================
+
+/// Text-mode rendering for a command's records (JSON mode serializes them instead).
+pub trait TextRenderer {
+    /// Rendered lines, without a trailing newline.
+    fn render(&self, style: &TextStyle) -> String;
+}
+
+/// What a piece of text output is, for styling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    /// Unstyled.
+    Plain,
+    /// Dates and secondary details.
+    Dim,
+    /// Contact names and headings.
+    Bold,
+    /// My own messages.
+    Me,
+    /// Other people's messages.
+    Other,
+    /// Table column headers.
+    Header,
+}
+
+/// ANSI styling for text output; plain text when color is off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub color: bool,
+}
+
+impl TextStyle {
+    /// Color unless --no-color, NO_COLOR is set, or stdout isn't a terminal.
+    pub fn detect(no_color: bool) -> Self {
+        use std::io::IsTerminal;
+
+        let env_off = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        TextStyle { color: !no_color && !env_off && std::io::stdout().is_terminal() }
+    }
+
+    pub fn paint(&self, tone: Tone, text: &str) -> String {
+        let code = match tone {
+            _ if !self.color || text.is_empty() => return text.to_string(),
+            Tone::Plain => return text.to_string(),
+            Tone::Dim => "2",
+            Tone::Bold => "1",
+            Tone::Me => "32",
+            Tone::Other => "1;36",
+            Tone::Header => "1;4",
+        };
+        format!("\x1b[{}m{}\x1b[0m", code, text)
     }
 
-    /// Print data to stdout according to output controls.
-    pub fn print<T: Serialize>(&self, data: &T) {
-        println!("{}", self.emit(data));
+    /// Pad to `width` characters, then paint (escape codes don't count toward width).
+    pub fn pad(&self, tone: Tone, text: &str, width: usize) -> String {
+        let padding = width.saturating_sub(text.chars().count());
+        format!("{}{}", self.paint(tone, text), " ".repeat(padding))
+    }
+}
+
+/// Local "YYYY-MM-DD HH:MM" for an RFC 3339 date; other input is returned as is.
+pub fn short_time(date: &str) -> String {
+    match chrono::DateTime::parse_from_rfc3339(date) {
+        Ok(dt) => dt.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+        Err(_) => date.to_string(),
+    }
+}
+
+/// One row of a message list.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageLine<'a> {
+    pub date: Option<&'a str>,
+    pub sender: &'a str,
+    pub is_from_me: bool,
+    pub text: &'a str,
+}
+
+/// Message list as aligned columns: time, padded sender, one-line preview.
+pub fn render_message_lines(lines: &[MessageLine], preview_chars: usize, style: &TextStyle) -> String {
+    let times: Vec<String> = lines.iter().map(|l| l.date.map(short_time).unwrap_or_default()).collect();
+    let time_width = times.iter().map(|t| t.chars().count()).max().unwrap_or(0);
+    let sender_width = lines.iter().map(|l| l.sender.chars().count()).max().unwrap_or(0);
+
+    lines
+        .iter()
+        .zip(&times)
+        .map(|(line, time)| {
+            let tone = if line.is_from_me { Tone::Me } else { Tone::Other };
+            format!(
+                "{}  {}  {}",
+                style.pad(Tone::Dim, time, time_width),
+                style.pad(tone, line.sender, sender_width),
+                preview(line.text, preview_chars)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Records with a heading for text mode; serializes as just the records.
+pub struct Titled<'a, R> {
+    pub title: String,
+    /// Printed instead of the heading when there are no records.
+    pub empty: &'a str,
+    pub records: &'a [R],
+}
+
+impl<'a, R> Titled<'a, R> {
+    pub fn new(title: impl Into<String>, empty: &'a str, records: &'a [R]) -> Self {
+        Titled { title: title.into(), empty, records }
+    }
+}
+
+impl<R: Serialize> Serialize for Titled<'_, R> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.records.serialize(serializer)
+    }
+}
+
+impl<R> TextRenderer for Titled<'_, R>
+where
+    [R]: TextRenderer,
+{
+    fn render(&self, style: &TextStyle) -> String {
+        if self.records.is_empty() {
+            return self.empty.to_string();
+        }
+        format!(
+            "{}\n{}\n{}",
+            style.paint(Tone::Bold, &self.title),
+            style.paint(Tone::Dim, &"-".repeat(60)),
+            self.records.render(style)
+        )
+    }
+}
+
+/// Compact table: a header row, then cells padded to each column's widest entry.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Table { headers: headers.iter().map(|h| h.to_string()).collect(), rows: Vec::new() }
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) -> &mut Self {
+        self.rows.push(cells);
+        self
+    }
+
+    pub fn render(&self, style: &TextStyle) -> String {
+        let columns = self.headers.len();
+        let widths: Vec<usize> = (0..columns)
+            .map(|i| {
+                std::iter::once(&self.headers[i])
+                    .chain(self.rows.iter().filter_map(|r| r.get(i)))
+                    .map(|c| c.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        // Header row underlined; body rows bold in the first (label) column only
+        let line = |cells: &[String], header: bool| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, (cell, &width))| {
+                    let tone = match (header, i) {
+                        (true, _) => Tone::Header,
+                        (false, 0) => Tone::Bold,
+                        _ => Tone::Plain,
+                    };
+                    // Don't pad the last column into trailing spaces
+                    style.pad(tone, cell, if i + 1 == columns { 0 } else { width })
+                })
+                .collect();
+            padded.join("  ")
+        };
+
+        std::iter::once(line(&self.headers, true))
+            .chain(self.rows.iter().map(|r| line(r, false)))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
@@ -73,12 +645,35 @@ fn filter_fields(value: &Value, fields: &str) -> Value {
     }
 }
 
+/// Cut `text` to at most `max_chars` characters (never mid-character),
+/// appending "..." when anything was dropped.
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// One-line preview for text output: line breaks (and the whitespace around
+/// them) collapse to a single space, then `truncate_chars`.
+pub fn preview(text: &str, max_chars: usize) -> String {
+    const BREAKS: [char; 3] = ['\n', '\r', '\u{2028}'];
+    if !text.contains(BREAKS) {
+        return truncate_chars(text, max_chars);
+    }
+    let one_line = text
+        .split(BREAKS)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    truncate_chars(&one_line, max_chars)
+}
+
 /// Truncate string fields in JSON value.
 fn truncate_text_fields(value: &Value, max_chars: usize) -> Value {
     match value {
-        Value::String(s) if s.len() > max_chars => {
-            Value::String(format!("{}...", &s[..max_chars]))
-        }
+        Value::String(s) => Value::String(truncate_chars(s, max_chars)),
         Value::Array(arr) => {
             Value::Array(arr.iter().map(|v| truncate_text_fields(v, max_chars)).collect())
         }
@@ -93,6 +688,172 @@ fn truncate_text_fields(value: &Value, max_chars: usize) -> Value {
     }
 }
 
+/// Fields holding a phone number or email handle.
+const HANDLE_FIELDS: &[&str] = &[
+    "phone",
+    "handle",
+    "handle_id",
+    "sender",
+    "sender_handle",
+    "chat_identifier",
+    "contact_resolved",
+    "participants",
+    "email",
+];
+
+/// Fields holding a display name that falls back to the raw handle
+/// (`display_name`, `UnreadChat.name`); pseudonymized only in that case.
+const NAME_FIELDS: &[&str] = &["display_name", "name"];
+
+/// Whether `s` is a bare phone number or email rather than a name.
+fn is_bare_handle(s: &str) -> bool {
+    looks_like_email(s)
+        || (s.chars().filter(|c| c.is_ascii_digit()).count() >= 10
+            && s.chars().all(|c| c.is_ascii_digit() || "+-(). ".contains(c)))
+}
+
+/// Fields holding an attachment file path.
+const PATH_FIELDS: &[&str] = &["filename", "path", "source", "dest", "attachment_path"];
+
+/// Fields holding free-form message text.
+pub const TEXT_FIELDS: &[&str] = &[
+    "text",
+    "last_text",
+    "last_message_text",
+    "sample_text",
+    "message",
+    "preview",
+    "transcript",
+    "body",
+];
+
+/// Replaces handles with stable pseudonyms and masks sensitive numbers.
+///
+/// Without a salt, pseudonyms are numbered in first-seen order (`Contact-1`,
+/// `Contact-2`, ...) and only stable within one invocation. With a salt they
+/// are derived from a hash of salt + handle, so they match across invocations
+/// that share the salt file.
+#[derive(Debug)]
+pub struct Redactor {
+    salt: Option<String>,
+    pseudonyms: Mutex<HashMap<String, String>>,
+    ssn_re: Regex,
+    phone_re: Regex,
+}
+
+impl Redactor {
+    pub fn new(salt: Option<String>) -> Self {
+        Self {
+            salt,
+            pseudonyms: Mutex::new(HashMap::new()),
+            ssn_re: Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").expect("valid SSN regex"),
+            phone_re: Regex::new(concat!(
+                r"\(\d{3}\)[\s.-]?\d{3}[\s.-]?\d{4}\b",
+                r"|\+?\b(?:\d{1,2}[\s.-]?)?\d{3}[\s.-]?\d{3}[\s.-]?\d{4}\b",
+            ))
+            .expect("valid phone regex"),
+        }
+    }
+
+    /// Build a redactor whose salt is read from `path`, creating it if missing.
+    pub fn with_salt_file(path: &Path) -> std::io::Result<Self> {
+        let salt = match std::fs::read_to_string(path) {
+            Ok(s) => s.trim().to_string(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let salt = uuid::Uuid::new_v4().to_string();
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, &salt)?;
+                salt
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(Self::new(Some(salt)))
+    }
+
+    /// Pseudonym for a phone number or email handle.
+    pub fn pseudonym(&self, handle: &str) -> String {
+        let key = handle_key(handle);
+        if key.is_empty() {
+            return handle.to_string();
+        }
+        let mut map = self.pseudonyms.lock().unwrap_or_else(|e| e.into_inner());
+        let next = map.len() + 1;
+        map.entry(key.clone())
+            .or_insert_with(|| match self.salt {
+                Some(ref salt) => format!("Contact-{:08x}", fnv1a(salt, &key) as u32),
+                None => format!("Contact-{}", next),
+            })
+            .clone()
+    }
+
+    /// Mask digits in anything in `text` that looks like an SSN or phone number.
+    pub fn mask_text(&self, text: &str) -> String {
+        let mask = |caps: &regex::Captures| {
+            caps[0]
+                .chars()
+                .map(|c| if c.is_ascii_digit() { '#' } else { c })
+                .collect::<String>()
+        };
+        let masked = self.ssn_re.replace_all(text, mask);
+        self.phone_re.replace_all(&masked, mask).into_owned()
+    }
+
+    /// Redact a JSON value (handles, attachment paths, and message text).
+    pub fn redact(&self, value: &Value) -> Value {
+        self.redact_field(None, value)
+    }
+
+    fn redact_field(&self, key: Option<&str>, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact_string(key, s)),
+            Value::Array(arr) => {
+                Value::Array(arr.iter().map(|v| self.redact_field(key, v)).collect())
+            }
+            Value::Object(map) => {
+                let mut redacted = serde_json::Map::new();
+                for (k, v) in map {
+                    redacted.insert(k.clone(), self.redact_field(Some(k), v));
+                }
+                Value::Object(redacted)
+            }
+            _ => value.clone(),
+        }
+    }
+
+    fn redact_string(&self, key: Option<&str>, s: &str) -> String {
+        let key = key.unwrap_or("");
+        let handle_field = HANDLE_FIELDS.contains(&key) || (NAME_FIELDS.contains(&key) && is_bare_handle(s));
+        if handle_field || looks_like_email(s) {
+            // Group ids (chatNNN) aren't personal handles
+            if s.starts_with("chat") && !s.contains('@') {
+                return s.to_string();
+            }
+            self.pseudonym(s)
+        } else if PATH_FIELDS.contains(&key) {
+            Path::new(s)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| s.to_string())
+        } else if TEXT_FIELDS.contains(&key) {
+            self.mask_text(s)
+        } else {
+            s.to_string()
+        }
+    }
+}
+
+/// FNV-1a over salt + key: stable across runs and Rust versions.
+fn fnv1a(salt: &str, key: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in salt.bytes().chain(std::iter::once(0)).chain(key.bytes()) {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
 /// Format error as JSON.
 pub fn format_error(error: &str) -> String {
     serde_json::to_string(&json!({
@@ -100,3 +861,238 @@ pub fn format_error(error: &str) -> String {
         "success": false
     })).unwrap_or_else(|_| format!(r#"{{"error":"{}"}}"#, error))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_chars_multibyte_boundary() {
+        // Cut lands inside the emoji / CJK character at byte level
+        assert_eq!(truncate_chars("ab😀cd", 3), "ab😀...");
+        assert_eq!(truncate_chars("ab😀", 3), "ab😀");
+        assert_eq!(truncate_chars("東京で会いましょう", 2), "東京...");
+        assert_eq!(truncate_chars("", 5), "");
+
+        let controls = OutputControls { max_text_chars: Some(2), compact: true, ..Default::default() };
+        assert_eq!(controls.emit(&json!({"text": "é😀ü"})), r#"{"text":"é😀..."}"#);
+    }
+
+    #[test]
+    fn test_preview_collapses_newlines() {
+        assert_eq!(preview("line one\n\n  line two\r\nthree", 80), "line one line two three");
+        assert_eq!(preview("👍🏽\n👍🏽👍🏽", 3), "👍🏽 ...");
+        assert_eq!(preview("keep  inner   spacing", 80), "keep  inner   spacing");
+    }
+
+    #[test]
+    fn test_warnings_collect_per_thread() {
+        take_warnings();
+        warn("contacts.json is corrupt");
+        warn("contacts.json is corrupt");
+
+        // Plain JSON keeps the payload's shape; the warning waits for stderr
+        let controls = OutputControls { json: true, ..Default::default() };
+        assert_eq!(controls.finish(json!([]), 2), json!([]));
+        assert_eq!(controls.finish(json!({"count": 2}), 0), json!({"count": 2}));
+        assert_eq!(WARNINGS.with_borrow(|w| w.delivered), 0);
+
+        let controls = OutputControls { json: true, envelope: true, ..Default::default() };
+        assert_eq!(controls.finish(json!([]), 0)["meta"]["warnings"], json!(["contacts.json is corrupt"]));
+        assert_eq!(WARNINGS.with_borrow(|w| w.delivered), 1);
+
+        std::thread::spawn(|| assert!(take_warnings().is_empty())).join().unwrap();
+        assert_eq!(take_warnings(), ["contacts.json is corrupt"]);
+        assert!(take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_message_lines_plain_rendering() {
+        let date = "2026-10-16T09:05:00+00:00";
+        let long = "é".repeat(30);
+        let lines = [
+            MessageLine { date: Some(date), sender: "+14155550001", is_from_me: false, text: "running late,\nsee you at 7" },
+            MessageLine { date: Some(date), sender: "Me", is_from_me: true, text: "no worries 👍" },
+            MessageLine { date: None, sender: "Ann", is_from_me: false, text: &long },
+        ];
+        let time = short_time(date);
+        let blank = " ".repeat(time.chars().count());
+        let expected = format!(
+            "{t}  +14155550001  running late, see you at 7\n\
+             {t}  Me            no worries 👍\n\
+             {b}  Ann           {e}...",
+            t = time,
+            b = blank,
+            e = "é".repeat(26)
+        );
+        assert_eq!(render_message_lines(&lines, 26, &TextStyle::default()), expected);
+    }
+
+    #[test]
+    fn test_table_and_titled_plain_rendering() {
+        let mut table = Table::new(&["contact", "messages"]);
+        table.row(vec!["Ann".to_string(), "120".to_string()]);
+        table.row(vec!["+14155550001".to_string(), "7".to_string()]);
+        assert_eq!(
+            table.render(&TextStyle::default()),
+            "contact       messages\nAnn           120\n+14155550001  7"
+        );
+
+        struct Row(&'static str);
+        impl TextRenderer for [Row] {
+            fn render(&self, _: &TextStyle) -> String {
+                self.iter().map(|r| r.0).collect::<Vec<_>>().join("\n")
+            }
+        }
+        let plain = TextStyle::default();
+        assert_eq!(Titled::new("Rows (1):", "None.", &[Row("a")]).render(&plain), format!("Rows (1):\n{}\na", "-".repeat(60)));
+        assert_eq!(Titled::<Row>::new("Rows (0):", "None.", &[]).render(&plain), "None.");
+    }
+
+    #[test]
+    fn test_color_pads_before_painting() {
+        let color = TextStyle { color: true };
+        assert_eq!(color.pad(Tone::Me, "Me", 4), "\x1b[32mMe\x1b[0m  ");
+        assert_eq!(color.paint(Tone::Plain, "x"), "x");
+        assert_eq!(TextStyle::default().paint(Tone::Bold, "x"), "x");
+        assert!(!TextStyle::detect(true).color);
+    }
+
+    #[test]
+    fn test_same_handle_same_pseudonym_across_records() {
+        let redactor = Redactor::new(None);
+        let value = json!([
+            {"phone": "+14155551234", "text": "hi"},
+            {"phone": "someone@example.com", "text": "yo"},
+            {"phone": "(415) 555-1234", "text": "again"},
+        ]);
+        let out = redactor.redact(&value);
+        assert_eq!(out[0]["phone"], "Contact-1");
+        assert_eq!(out[1]["phone"], "Contact-2");
+        assert_eq!(out[2]["phone"], "Contact-1");
+    }
+
+    #[test]
+    fn test_salted_pseudonyms_stable_across_redactors() {
+        let a = Redactor::new(Some("salt".to_string()));
+        let b = Redactor::new(Some("salt".to_string()));
+        let c = Redactor::new(Some("other".to_string()));
+        assert_eq!(a.pseudonym("+14155551234"), b.pseudonym("4155551234"));
+        assert_ne!(a.pseudonym("+14155551234"), c.pseudonym("+14155551234"));
+    }
+
+    #[test]
+    fn test_text_masking_formatted_and_unformatted() {
+        let redactor = Redactor::new(None);
+        assert_eq!(redactor.mask_text("call 415-555-1234"), "call ###-###-####");
+        assert_eq!(redactor.mask_text("call (415) 555-1234!"), "call (###) ###-####!");
+        assert_eq!(redactor.mask_text("or 4155551234"), "or ##########");
+        assert_eq!(redactor.mask_text("or 14155551234"), "or ###########");
+        assert_eq!(redactor.mask_text("+1 415.555.1234"), "+# ###.###.####");
+        assert_eq!(redactor.mask_text("ssn 123-45-6789 ok"), "ssn ###-##-#### ok");
+        assert_eq!(redactor.mask_text("see you at 5 on the 12th"), "see you at 5 on the 12th");
+    }
+
+    #[test]
+    fn test_dates_untouched_and_paths_stripped() {
+        let redactor = Redactor::new(None);
+        let value = json!({
+            "date": "2026-10-16T09:30:00+00:00",
+            "filename": "~/Library/Messages/Attachments/ab/12/IMG_0001.HEIC",
+            "chat_identifier": "chat123456",
+            "sender_handle": "+14155551234",
+            "count": 3,
+        });
+        let out = redactor.redact(&value);
+        assert_eq!(out["date"], "2026-10-16T09:30:00+00:00");
+        assert_eq!(out["filename"], "IMG_0001.HEIC");
+        assert_eq!(out["chat_identifier"], "chat123456");
+        assert_eq!(out["sender_handle"], "Contact-1");
+        assert_eq!(out["count"], 3);
+    }
+
+    #[test]
+    fn test_names_redacted_only_when_they_are_handles() {
+        let redactor = Redactor::new(None);
+        let value = json!([
+            {"handle": "+14155551234", "display_name": "+1 (415) 555-1234"},
+            {"chat_identifier": "chat123456", "name": "Book Club"},
+            {"chat_identifier": "+14155559999", "name": "+14155559999"},
+            {"display_name": "Alice Archer"},
+        ]);
+        let out = redactor.redact(&value);
+        assert_eq!(out[0]["display_name"], "Contact-1");
+        assert_eq!(out[1]["name"], "Book Club");
+        assert_eq!(out[2]["name"], out[2]["chat_identifier"]);
+        assert_eq!(out[3]["display_name"], "Alice Archer");
+    }
+
+    #[test]
+    fn test_envelope_wraps_payload_with_meta() {
+        let controls = OutputControls { envelope: true, fields: Some("text".to_string()), ..Default::default() };
+        let shaped = controls.shape(json!([{"text": "hi", "rowid": 1}, {"text": "yo", "rowid": 2}]));
+        let value = controls.finish(shaped, 3);
+        assert_eq!(value["ok"], true);
+        assert_eq!(value["data"], json!([{"text": "hi"}, {"text": "yo"}]));
+        assert_eq!(value["meta"]["count"], 2);
+        assert_eq!(value["meta"]["muted_count"], 3);
+        assert_eq!(value["meta"]["applied"]["fields"], "text");
+        assert!(value["meta"]["applied"]["max_text_chars"].is_null());
+        assert!(value["meta"]["elapsed_ms"].is_number());
+
+        assert_eq!(controls.finish(json!({"total": 1}), 0)["meta"]["count"], 1);
+        let error = error_envelope("ERROR", "boom", controls.envelope_meta());
+        assert_eq!(error["ok"], false);
+        assert_eq!(error["error"], json!({"code": "ERROR", "message": "boom"}));
+
+        // Without the flag nothing is wrapped
+        let bare = OutputControls::default();
+        assert!(bare.finish(json!([1, 2]), 0).get("meta").is_none());
+    }
+
+    #[test]
+    fn test_csv_quotes_commas_and_newlines() {
+        let records = json!([
+            {"text": "Dinner at 7, ok?", "is_from_me": true, "rowid": 1},
+            {"text": "She said \"hi\"\nthen left\r\nfor good", "is_from_me": false, "rowid": 2},
+            {"text": null, "is_from_me": false, "rowid": 3},
+        ]);
+        assert_eq!(
+            to_csv(&records, None).unwrap(),
+            "is_from_me,rowid,text\n\
+             true,1,\"Dinner at 7, ok?\"\n\
+             false,2,\"She said \"\"hi\"\"\\nthen left\\nfor good\"\n\
+             false,3,\n"
+        );
+        assert!(to_csv(&json!({"total": 3}), None).is_none());
+        assert_eq!(to_csv(&json!([]), None).unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_csv_flattens_one_level_and_follows_fields_order() {
+        let records = json!([
+            {
+                "url": "https://example.com",
+                "shared_by": ["Me", "Sam"],
+                "group": {"id": "chat1", "name": "Hikers", "meta": {"deep": 1}},
+                "attachments": [{"path": "a.jpg"}],
+            },
+            {"url": "https://example.org", "extra": 1},
+        ]);
+        assert_eq!(
+            to_csv(&records, None).unwrap(),
+            "extra,group.id,group.name,shared_by,url\n\
+             ,\"chat1\",\"Hikers\",\"Me; Sam\",\"https://example.com\"\n\
+             1,,,,\"https://example.org\"\n"
+        );
+
+        let controls = OutputControls { csv: true, fields: Some("url, group".to_string()), ..Default::default() };
+        let shaped = controls.shape(records);
+        assert_eq!(
+            to_csv(&shaped, controls.fields.as_deref()).unwrap(),
+            "url,group.id,group.name\n\
+             \"https://example.com\",\"chat1\",\"Hikers\"\n\
+             \"https://example.org\",,\n"
+        );
+    }
+}
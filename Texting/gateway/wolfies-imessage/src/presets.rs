@@ -0,0 +1,474 @@
+//! Saved invocations (~/.wolfies-imessage/presets.json).
+//!
+//! `--save-as <name>` stores the subcommand an invocation ran and its fully
+//! resolved parameters as a flat map (field name to value, defaults
+//! included). `run <name>` rebuilds the typed command from that map and
+//! dispatches it like any other; flags after the name are parsed against the
+//! subcommand's own definition and win over the stored values, including
+//! over stored flags they conflict with (`--unknown-only` clears a saved
+//! `--known-only`). Global flags that shape the output (`SAVED_GLOBALS`:
+//! --json, --fields, --max-text-chars, --envelope, ...) are saved too and
+//! replayed the same way; where to read from (--db-path, --socket) isn't.
+//! Saving under an existing name replaces the preset.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Output-shaping global flags are saved and replayed (Preset.globals); Preset::replay parses the overrides itself
+//! - 10/16/2026 - Path comes from the data file registry (data_files::PRESETS)
+//! - 10/16/2026 - Initial preset store and override merge
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use clap::parser::ValueSource;
+use clap::builder::Resettable;
+use clap::{ArgAction, ArgMatches};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::any::Any;
+use std::path::{Path, PathBuf};
+
+use crate::data_files;
+use crate::storage::{self, FileLock};
+
+/// Global flags (clap ids) a preset keeps.
+pub const SAVED_GLOBALS: &[&str] = &["json", "compact", "minimal", "csv", "envelope", "fields", "max_text_chars"];
+
+/// One saved invocation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Preset {
+    pub name: String,
+    /// Subcommand as typed ("bundle", "text-search")
+    pub subcommand: String,
+    /// Parameters by field name
+    pub params: Map<String, Value>,
+    /// `SAVED_GLOBALS` given on the command line, by clap id
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub globals: Map<String, Value>,
+    pub saved_at: DateTime<Local>,
+}
+
+impl Preset {
+    /// Capture a parsed command (serialized as `{"subcommand", "params"}`)
+    /// and its global flags (see `saved_globals`).
+    pub fn capture<C: Serialize>(name: &str, command: &C, globals: Map<String, Value>) -> Result<Self> {
+        let value = serde_json::to_value(command)?;
+        let subcommand = value["subcommand"]
+            .as_str()
+            .ok_or_else(|| anyhow!("command has no subcommand tag"))?
+            .to_string();
+        Ok(Self {
+            name: name.to_string(),
+            subcommand,
+            params: value.get("params").and_then(Value::as_object).cloned().unwrap_or_default(),
+            globals,
+            saved_at: Local::now(),
+        })
+    }
+
+    /// Rebuild the command from `cli` (the top-level parser) with the
+    /// arguments after `run <name>` applied: global flags clap already
+    /// matched for `run` (`given`) and the rest (`overrides`). Also returns
+    /// the global flags to update the parsed CLI with: the saved ones and
+    /// any given, only those set (see `keep_explicit`).
+    pub fn replay<C: DeserializeOwned>(
+        &self,
+        cli: clap::Command,
+        given: &ArgMatches,
+        overrides: &[String],
+    ) -> Result<(C, ArgMatches)> {
+        if matches!(self.subcommand.as_str(), "run" | "presets") {
+            return Err(anyhow!("Preset '{}' can't run `{}`", self.name, self.subcommand));
+        }
+        let parser = override_parser(cli, &self.subcommand)?;
+        let given = as_args(&parser, &explicit_values(&parser, given, |_| true), |_| false);
+        let parse = |saved: &[String]| {
+            let argv = [parser.get_name(), self.subcommand.as_str()]
+                .into_iter()
+                .chain(saved.iter().map(String::as_str))
+                .chain(given.iter().map(String::as_str))
+                .chain(overrides.iter().map(String::as_str));
+            parser.clone().try_get_matches_from(argv).map_err(|e| {
+                let message = e.to_string();
+                anyhow!("run {}: {}", self.name, message.trim().trim_start_matches("error: "))
+            })
+        };
+        let mut matches = parse(&[])?;
+        // Saved flags yield to any given, and to any given they conflict with
+        let saved = {
+            let explicit: Vec<&clap::Arg> = parser
+                .get_arguments()
+                .filter(|a| matches.value_source(a.get_id().as_str()) == Some(ValueSource::CommandLine))
+                .collect();
+            as_args(&parser, &self.globals, |arg| {
+                explicit.iter().any(|other| other.get_id() == arg.get_id() || conflicts(&parser, arg, other))
+            })
+        };
+        if !saved.is_empty() {
+            matches = parse(&saved)?;
+        }
+
+        let (_, sub_matches) = matches.remove_subcommand().expect("override parse keeps the subcommand");
+        let subcommand = parser.find_subcommand(&self.subcommand).expect("checked by override_parser");
+        let command = self.replay_params(subcommand, &sub_matches)?;
+        keep_explicit(&mut matches);
+        Ok((command, matches))
+    }
+
+    /// Stored params with `overrides` (this subcommand's matches) applied.
+    fn replay_params<C: DeserializeOwned>(&self, subcommand: &clap::Command, overrides: &ArgMatches) -> Result<C> {
+        let mut params = self.params.clone();
+        let args: Vec<&clap::Arg> = subcommand.get_arguments().filter(|a| !a.is_global_set()).collect();
+        let explicit = |id: &str| overrides.value_source(id) == Some(ValueSource::CommandLine);
+
+        for arg in &args {
+            let id = arg.get_id().as_str();
+            match overrides.value_source(id) {
+                // Fields added since the preset was saved take their defaults
+                Some(ValueSource::DefaultValue) => {
+                    if let Some(value) = matched_value(arg, overrides) {
+                        params.entry(id).or_insert(value);
+                    }
+                }
+                Some(ValueSource::CommandLine) => {
+                    for other in &args {
+                        let other_id = other.get_id().as_str();
+                        if explicit(other_id) || !conflicts(subcommand, arg, other) {
+                            continue;
+                        }
+                        let reset = match params.get(other_id) {
+                            Some(Value::Array(_)) => Some(json!([])),
+                            _ => matched_value(other, overrides),
+                        };
+                        match reset {
+                            Some(value) => params.insert(other_id.to_string(), value),
+                            None => params.remove(other_id),
+                        };
+                    }
+                    if let Some(value) = matched_value(arg, overrides) {
+                        params.insert(id.to_string(), value);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut command = json!({"subcommand": self.subcommand});
+        if !params.is_empty() {
+            command["params"] = Value::Object(params);
+        }
+        serde_json::from_value(command)
+            .map_err(|e| anyhow!("Preset '{}' no longer fits `{}`: {}", self.name, self.subcommand, e))
+    }
+}
+
+/// `SAVED_GLOBALS` given on the command line, as typed values by clap id.
+pub fn saved_globals(cli: &clap::Command, matches: &ArgMatches) -> Map<String, Value> {
+    explicit_values(cli, matches, |id| SAVED_GLOBALS.contains(&id))
+}
+
+/// Typed values of the `keep` arguments of `cli` that `matches` got from
+/// the command line, by clap id.
+fn explicit_values(cli: &clap::Command, matches: &ArgMatches, keep: impl Fn(&str) -> bool) -> Map<String, Value> {
+    cli.get_arguments()
+        .filter(|arg| keep(arg.get_id().as_str()))
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .filter_map(|arg| Some((arg.get_id().to_string(), matched_value(arg, matches)?)))
+        .collect()
+}
+
+/// `values` (as from `explicit_values`) back as `cli` arguments, leaving
+/// out the ones `skip` picks.
+fn as_args(cli: &clap::Command, values: &Map<String, Value>, skip: impl Fn(&clap::Arg) -> bool) -> Vec<String> {
+    let mut args = Vec::new();
+    for (id, value) in values {
+        let Some(arg) = cli.get_arguments().find(|a| a.get_id() == id.as_str()) else { continue };
+        let Some(long) = arg.get_long().filter(|_| !skip(arg)) else { continue };
+        let flag = format!("--{}", long);
+        match value {
+            Value::Bool(true) => args.push(flag),
+            Value::Bool(false) | Value::Null => {}
+            Value::Array(items) => {
+                for item in items {
+                    args.extend([flag.clone(), item.as_str().map_or_else(|| item.to_string(), str::to_string)]);
+                }
+            }
+            Value::String(s) => args.extend([flag, s.clone()]),
+            other => args.extend([flag, other.to_string()]),
+        }
+    }
+    args
+}
+
+/// Whether either argument of `subcommand` declares a conflict with the other.
+fn conflicts(subcommand: &clap::Command, a: &clap::Arg, b: &clap::Arg) -> bool {
+    let declares = |x: &clap::Arg, y: &clap::Arg| {
+        subcommand.get_arg_conflicts_with(x).iter().any(|c| c.get_id() == y.get_id())
+    };
+    a.get_id() != b.get_id() && (declares(a, b) || declares(b, a))
+}
+
+/// Typed values clap parsed for `arg`, as JSON (an array for repeatable args).
+fn matched_value(arg: &clap::Arg, matches: &ArgMatches) -> Option<Value> {
+    fn values<T: Any + Clone + Send + Sync + Serialize + 'static>(m: &ArgMatches, id: &str) -> Option<Vec<Value>> {
+        m.try_get_many::<T>(id).ok().flatten().map(|vs| vs.map(|v| json!(v)).collect())
+    }
+    let id = arg.get_id().as_str();
+    let parsed = values::<String>(matches, id)
+        .or_else(|| values::<u32>(matches, id))
+        .or_else(|| values::<u64>(matches, id))
+        .or_else(|| values::<f64>(matches, id))
+        .or_else(|| values::<bool>(matches, id))
+        .or_else(|| values::<PathBuf>(matches, id))?;
+    match arg.get_action() {
+        ArgAction::Append => Some(Value::Array(parsed)),
+        _ => parsed.into_iter().next(),
+    }
+}
+
+/// `cli` with `subcommand` relaxed for parsing overrides: nothing required
+/// (the preset has the rest), no `requires`, no required nested subcommand.
+/// Conflicts among the overrides themselves still fail.
+fn override_parser(cli: clap::Command, subcommand: &str) -> Result<clap::Command> {
+    if cli.find_subcommand(subcommand).is_none() {
+        return Err(anyhow!("Unknown subcommand '{}'", subcommand));
+    }
+    Ok(cli.mut_subcommand(subcommand, |sub| {
+        sub.subcommand_required(false).arg_required_else_help(false).mut_args(|arg| {
+            arg.required(false)
+                .requires(Resettable::Reset)
+                .required_unless_present(Resettable::Reset)
+        })
+    }))
+}
+
+/// Drop everything from `matches` that wasn't on the command line, so a
+/// derived `update_from_arg_matches` only touches what was given.
+fn keep_explicit(matches: &mut ArgMatches) {
+    let implicit: Vec<String> = matches
+        .ids()
+        .map(|id| id.as_str().to_string())
+        .filter(|id| matches.value_source(id) != Some(ValueSource::CommandLine))
+        .collect();
+    for id in implicit {
+        let _ = matches.try_clear_id(&id);
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PresetsFile {
+    presets: Vec<Preset>,
+}
+
+/// Presets backed by a JSON file.
+#[derive(Debug, Clone)]
+pub struct PresetStore {
+    path: PathBuf,
+}
+
+impl PresetStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Store at the default location.
+    pub fn open_default() -> Self {
+        Self::new(data_files::PRESETS.path())
+    }
+
+    /// Run a read-modify-write cycle under the file lock.
+    fn update<R>(&self, f: impl FnOnce(&mut Vec<Preset>) -> R) -> Result<R> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let mut file: PresetsFile = storage::read_json(&self.path)?;
+        let result = f(&mut file.presets);
+        file.presets.sort_by(|a, b| a.name.cmp(&b.name));
+        storage::write_json_atomic(&self.path, &file)?;
+        Ok(result)
+    }
+
+    /// Every preset, by name.
+    pub fn list(&self) -> Result<Vec<Preset>> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let file: PresetsFile = storage::read_json(&self.path)?;
+        Ok(file.presets)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Preset> {
+        self.list()?
+            .into_iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow!("No preset named '{}' (see `presets list`)", name))
+    }
+
+    /// Add a preset, replacing any existing one with the same name.
+    pub fn save(&self, preset: Preset) -> Result<()> {
+        self.update(move |presets| {
+            presets.retain(|p| p.name != preset.name);
+            presets.push(preset);
+        })
+    }
+
+    pub fn remove(&self, name: &str) -> Result<Preset> {
+        self.update(|presets| {
+            let idx = presets
+                .iter()
+                .position(|p| p.name == name)
+                .ok_or_else(|| anyhow!("No preset named '{}' (see `presets list`)", name))?;
+            Ok(presets.remove(idx))
+        })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+
+    #[derive(Parser)]
+    struct Cli {
+        #[arg(long, global = true)]
+        json: bool,
+        #[arg(long, global = true, conflicts_with = "json")]
+        csv: bool,
+        #[arg(long, global = true)]
+        fields: Option<String>,
+        #[arg(long, global = true)]
+        db_path: Option<String>,
+        #[command(subcommand)]
+        command: Cmd,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "subcommand", content = "params", rename_all = "kebab-case")]
+    enum Cmd {
+        Search {
+            query: String,
+            #[arg(long, default_value_t = 10)]
+            limit: u32,
+            #[arg(long)]
+            days: Option<u32>,
+            #[arg(long, conflicts_with = "unknown_only")]
+            known_only: bool,
+            #[arg(long)]
+            unknown_only: bool,
+            #[arg(long)]
+            any: Vec<String>,
+        },
+        Quick,
+    }
+
+    fn capture(name: &str, argv: &[&str]) -> Preset {
+        let matches = Cli::command().get_matches_from(argv);
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        Preset::capture(name, &cli.command, saved_globals(&Cli::command(), &matches)).unwrap()
+    }
+
+    fn replay_cli(preset: &Preset, overrides: &[&str]) -> Result<Cli> {
+        let overrides: Vec<String> = overrides.iter().map(|s| s.to_string()).collect();
+        let given = Cli::command().get_matches_from(["cli", "quick"]);
+        let mut cli = Cli::from_arg_matches(&given)?;
+        let (command, globals) = preset.replay(Cli::command(), &given, &overrides)?;
+        cli.update_from_arg_matches(&globals)?;
+        cli.command = command;
+        Ok(cli)
+    }
+
+    fn replay(preset: &Preset, overrides: &[&str]) -> Result<Cmd> {
+        Ok(replay_cli(preset, overrides)?.command)
+    }
+
+    #[test]
+    fn test_capture_and_replay_with_overrides() {
+        let cli = Cli::parse_from(["cli", "search", "dinner", "--known-only", "--any", "a", "--any", "b"]);
+        let preset = capture("dinner", &["cli", "search", "dinner", "--known-only", "--any", "a", "--any", "b"]);
+        assert_eq!(preset.subcommand, "search");
+        assert_eq!(preset.params["limit"], 10);
+        assert_eq!(preset.params["days"], Value::Null);
+
+        assert_eq!(replay(&preset, &[]).unwrap(), cli.command);
+        // The override wins, and clears the stored flag it conflicts with
+        let cmd = replay(&preset, &["--days", "2", "--unknown-only", "--limit", "5"]).unwrap();
+        assert_eq!(
+            cmd,
+            Cmd::Search {
+                query: "dinner".to_string(),
+                limit: 5,
+                days: Some(2),
+                known_only: false,
+                unknown_only: true,
+                any: vec!["a".to_string(), "b".to_string()],
+            }
+        );
+        let cmd = replay(&preset, &["lunch", "--any", "c"]).unwrap();
+        assert!(matches!(cmd, Cmd::Search { ref query, ref any, .. } if query == "lunch" && any == &["c"]));
+
+        // Overrides are still typed and checked
+        assert!(replay(&preset, &["--days", "two"]).is_err());
+        assert!(replay(&preset, &["--nope"]).is_err());
+
+        let quick = Preset::capture("q", &Cmd::Quick, Map::new()).unwrap();
+        assert!(quick.params.is_empty());
+        assert_eq!(replay(&quick, &[]).unwrap(), Cmd::Quick);
+    }
+
+    #[test]
+    fn test_global_flags_saved_and_replayed() {
+        let preset = capture("q", &["cli", "--json", "--db-path", "x.db", "quick", "--fields", "text,date"]);
+        assert_eq!(Value::Object(preset.globals.clone()), json!({"json": true, "fields": "text,date"}));
+
+        let cli = replay_cli(&preset, &[]).unwrap();
+        assert!(cli.json);
+        assert_eq!(cli.fields.as_deref(), Some("text,date"));
+        assert_eq!(cli.db_path, None);
+
+        // Overrides win, including over saved flags they conflict with
+        let cli = replay_cli(&preset, &["--fields", "text", "--csv"]).unwrap();
+        assert!(cli.csv && !cli.json);
+        assert_eq!(cli.fields.as_deref(), Some("text"));
+
+        // Presets saved before globals were kept still load
+        let old: Preset = serde_json::from_value(json!({
+            "name": "old", "subcommand": "quick", "params": {}, "saved_at": "2026-10-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert!(old.globals.is_empty());
+        assert!(!replay_cli(&old, &[]).unwrap().json);
+
+        let given = Cli::command().get_matches_from(["cli", "quick"]);
+        let err = Preset { subcommand: "run".to_string(), ..old }
+            .replay::<Cmd>(Cli::command(), &given, &[])
+            .unwrap_err();
+        assert!(err.to_string().contains("can't run `run`"));
+    }
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let mut preset = capture("old", &["cli", "search", "x"]);
+        preset.params.remove("limit");
+        preset.params.remove("unknown_only");
+        assert!(matches!(replay(&preset, &[]).unwrap(), Cmd::Search { limit: 10, unknown_only: false, .. }));
+        preset.params.remove("query");
+        let err = replay(&preset, &[]).unwrap_err();
+        assert!(err.to_string().contains("no longer fits `search`"));
+    }
+
+    #[test]
+    fn test_store_save_list_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PresetStore::new(dir.path().join("presets.json"));
+        assert!(store.list().unwrap().is_empty());
+
+        store.save(capture("zeta", &["cli", "search", "x"])).unwrap();
+        store.save(Preset::capture("alpha", &Cmd::Quick, Map::new()).unwrap()).unwrap();
+        store.save(Preset::capture("zeta", &Cmd::Quick, Map::new()).unwrap()).unwrap();
+        let names: Vec<_> = store.list().unwrap().into_iter().map(|p| (p.name, p.subcommand)).collect();
+        assert_eq!(names, [("alpha".to_string(), "quick".to_string()), ("zeta".to_string(), "quick".to_string())]);
+
+        assert_eq!(store.remove("alpha").unwrap().name, "alpha");
+        let err = store.get("alpha").unwrap_err();
+        assert!(err.to_string().contains("No preset named 'alpha'"));
+        assert!(store.remove("alpha").is_err());
+    }
+}
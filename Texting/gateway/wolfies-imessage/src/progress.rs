@@ -0,0 +1,233 @@
+//! Progress reporting for long-running commands (attachments --copy-to and
+//! --dedupe, compare).
+//!
+//! Commands report through the `Progress` trait and never decide where it
+//! goes: `for_output` picks a single stderr line redrawn in place when stderr
+//! is a terminal, and `Silent` with --json, --quiet, or redirected stderr.
+//! Phases with a cheap total up front show `processed/total`; the rest show
+//! a running count. Both show the rate.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial Progress trait, Silent, TerminalProgress (in-place stderr line), for_output
+
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::output::OutputControls;
+
+/// Minimum time between redraws.
+const REDRAW_EVERY: Duration = Duration::from_millis(100);
+
+/// Receives progress from a long-running command. Shared across worker
+/// threads (e.g. the hashing pool), so methods take `&self`.
+pub trait Progress: Sync {
+    /// Begin a phase; `total` when it's known up front.
+    fn start(&self, label: &str, total: Option<u64>);
+    /// `n` more items processed in the current phase.
+    fn advance(&self, n: u64);
+    /// End the current phase.
+    fn finish(&self);
+}
+
+/// Reports nothing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Progress for Silent {
+    fn start(&self, _label: &str, _total: Option<u64>) {}
+    fn advance(&self, _n: u64) {}
+    fn finish(&self) {}
+}
+
+/// The current phase as drawn.
+struct Phase {
+    label: String,
+    total: Option<u64>,
+    processed: u64,
+    started: Instant,
+    drawn: Option<Instant>,
+    /// Length of the last line, to blank it out on the next draw.
+    width: usize,
+}
+
+/// One status line on a terminal, rewritten in place with `\r` and cleared
+/// when the phase finishes so it never mixes into the command's output.
+pub struct TerminalProgress<W: Write + Send> {
+    out: Mutex<(W, Option<Phase>)>,
+}
+
+impl TerminalProgress<std::io::Stderr> {
+    pub fn stderr() -> Self {
+        Self::new(std::io::stderr())
+    }
+}
+
+impl<W: Write + Send> TerminalProgress<W> {
+    pub fn new(out: W) -> Self {
+        Self { out: Mutex::new((out, None)) }
+    }
+
+    /// The writer, once done (tests read back what was drawn).
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().unwrap_or_else(|e| e.into_inner()).0
+    }
+
+    fn draw(out: &mut W, phase: &mut Phase, now: Instant) {
+        let line = format_line(&phase.label, phase.processed, phase.total, now - phase.started);
+        let pad = phase.width.saturating_sub(line.chars().count());
+        // A broken terminal only loses the progress line
+        let _ = write!(out, "\r{}{}", line, " ".repeat(pad));
+        let _ = out.flush();
+        phase.width = line.chars().count();
+        phase.drawn = Some(now);
+    }
+}
+
+impl<W: Write + Send> Progress for TerminalProgress<W> {
+    fn start(&self, label: &str, total: Option<u64>) {
+        let mut guard = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        let (out, phase) = &mut *guard;
+        let width = phase.take().map_or(0, |p| p.width);
+        let now = Instant::now();
+        let mut next = Phase { label: label.to_string(), total, processed: 0, started: now, drawn: None, width };
+        Self::draw(out, &mut next, now);
+        *phase = Some(next);
+    }
+
+    fn advance(&self, n: u64) {
+        let mut guard = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        let (out, phase) = &mut *guard;
+        let Some(phase) = phase.as_mut() else {
+            return;
+        };
+        phase.processed += n;
+        let now = Instant::now();
+        if phase.drawn.is_none_or(|at| now - at >= REDRAW_EVERY) {
+            Self::draw(out, phase, now);
+        }
+    }
+
+    fn finish(&self) {
+        let mut guard = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        let (out, phase) = &mut *guard;
+        if let Some(phase) = phase.take() {
+            let _ = write!(out, "\r{}\r", " ".repeat(phase.width));
+            let _ = out.flush();
+        }
+    }
+}
+
+/// "Copying: 120/500 (24%), 35/s" with a total, "Hashing: 120, 35/s" without.
+pub fn format_line(label: &str, processed: u64, total: Option<u64>, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    let rate = if secs > 0.0 { format!(", {:.0}/s", processed as f64 / secs) } else { String::new() };
+    match total {
+        Some(total) if total > 0 => {
+            let percent = processed.min(total) * 100 / total;
+            format!("{}: {}/{} ({}%){}", label, processed, total, percent, rate)
+        }
+        Some(total) => format!("{}: {}/{}{}", label, processed, total, rate),
+        None => format!("{}: {}{}", label, processed, rate),
+    }
+}
+
+/// Progress for a command run with `output`: a terminal line on stderr, or
+/// `Silent` with --json, --quiet, or when stderr isn't a terminal.
+pub fn for_output(output: &OutputControls) -> Box<dyn Progress> {
+    if shows_progress(output, std::io::stderr().is_terminal()) {
+        Box::new(TerminalProgress::stderr())
+    } else {
+        Box::new(Silent)
+    }
+}
+
+fn shows_progress(output: &OutputControls, stderr_is_terminal: bool) -> bool {
+    stderr_is_terminal && !output.json && !output.quiet
+}
+
+/// Records every call, for tests that check what a command reported.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct Recorder {
+    pub events: Mutex<Vec<Event>>,
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Start(String, Option<u64>),
+    Advance(u64),
+    Finish,
+}
+
+#[cfg(test)]
+impl Recorder {
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Items advanced in total over every phase.
+    pub fn processed(&self) -> u64 {
+        self.events()
+            .iter()
+            .map(|e| match e {
+                Event::Advance(n) => *n,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+impl Progress for Recorder {
+    fn start(&self, label: &str, total: Option<u64>) {
+        self.events.lock().unwrap().push(Event::Start(label.to_string(), total));
+    }
+    fn advance(&self, n: u64) {
+        self.events.lock().unwrap().push(Event::Advance(n));
+    }
+    fn finish(&self) {
+        self.events.lock().unwrap().push(Event::Finish);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_line_with_and_without_total() {
+        let two_secs = Duration::from_secs(2);
+        assert_eq!(format_line("Copying", 120, Some(500), two_secs), "Copying: 120/500 (24%), 60/s");
+        assert_eq!(format_line("Hashing", 70, None, two_secs), "Hashing: 70, 35/s");
+        assert_eq!(format_line("Comparing", 0, Some(0), Duration::ZERO), "Comparing: 0/0");
+    }
+
+    #[test]
+    fn test_terminal_line_redraws_in_place_and_clears() {
+        let progress = TerminalProgress::new(Vec::new());
+        progress.start("Copying", Some(3));
+        for _ in 0..3 {
+            progress.advance(1);
+        }
+        progress.finish();
+        let drawn = String::from_utf8(progress.into_inner()).unwrap();
+
+        assert!(drawn.starts_with("\rCopying: 0/3 (0%)"), "{:?}", drawn);
+        // Never a newline: every draw returns to the start of the same line
+        assert!(!drawn.contains('\n'));
+        // The finished line is blanked, leaving the cursor at column 0
+        let blank = drawn.strip_suffix('\r').and_then(|d| d.rsplit('\r').next()).unwrap();
+        assert!(blank.len() >= "Copying: 0/3 (0%)".len() && blank.trim().is_empty(), "{:?}", drawn);
+    }
+
+    #[test]
+    fn test_silent_unless_terminal_without_json_or_quiet() {
+        let text = OutputControls::default();
+        assert!(shows_progress(&text, true));
+        assert!(!shows_progress(&text, false));
+        assert!(!shows_progress(&OutputControls { json: true, ..Default::default() }, true));
+        assert!(!shows_progress(&OutputControls { quiet: true, ..Default::default() }, true));
+    }
+}
@@ -0,0 +1,435 @@
+//! Local scheduled-send queue (~/.wolfies-imessage/scheduled.json).
+//!
+//! Messages.db has no scheduled sends, so `send --at/--in` writes a job here
+//! and the daemon's worker thread sends it when due. Jobs are claimed
+//! (marked `in_progress` and saved) before sending, so a job is attempted at
+//! most once: a job still `in_progress` after a restart was interrupted
+//! mid-send and is marked failed instead of retried. Jobs go out through
+//! `messaging::deliver`, so they count against the send rate limit and land
+//! in the send log like any other send.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Added has_unsent (the daemon stays up while jobs wait)
+//! - 10/16/2026 - Worker sends through messaging::deliver (rate limit, send log, dry run) instead of AppleScript directly
+//! - 10/16/2026 - Path comes from the data file registry (data_files::SCHEDULED)
+//! - 10/16/2026 - JsonSchema derives for `schema`
+//! - 10/16/2026 - Initial scheduling store, time parsing, and daemon worker
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::commands::messaging;
+use crate::data_files;
+use crate::storage::{self, FileLock};
+
+/// How often the daemon worker checks for due jobs.
+pub const POLL_INTERVAL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    InProgress,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScheduledJob {
+    pub id: String,
+    /// Resolved recipient (phone or email)
+    pub recipient: String,
+    /// Contact name as given on the command line, if any
+    pub contact: Option<String>,
+    pub message: String,
+    pub send_at: DateTime<Local>,
+    pub status: JobStatus,
+    pub created_at: DateTime<Local>,
+    pub attempted_at: Option<DateTime<Local>>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleFile {
+    jobs: Vec<ScheduledJob>,
+}
+
+/// Scheduled job store backed by a JSON file.
+pub struct ScheduleStore {
+    path: PathBuf,
+}
+
+impl ScheduleStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Store at the default location.
+    pub fn open_default() -> Self {
+        Self::new(data_files::SCHEDULED.path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run a read-modify-write cycle under the file lock.
+    fn update<R>(&self, f: impl FnOnce(&mut Vec<ScheduledJob>) -> R) -> Result<R> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let mut file: ScheduleFile = storage::read_json(&self.path)?;
+        let result = f(&mut file.jobs);
+        storage::write_json_atomic(&self.path, &file)?;
+        Ok(result)
+    }
+
+    /// All jobs, ordered by send time.
+    pub fn list(&self) -> Result<Vec<ScheduledJob>> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let file: ScheduleFile = storage::read_json(&self.path)?;
+        let mut jobs = file.jobs;
+        jobs.sort_by_key(|j| j.send_at);
+        Ok(jobs)
+    }
+
+    /// Add a pending job.
+    pub fn add(
+        &self,
+        recipient: &str,
+        contact: Option<&str>,
+        message: &str,
+        send_at: DateTime<Local>,
+    ) -> Result<ScheduledJob> {
+        let job = ScheduledJob {
+            id: uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+            recipient: recipient.to_string(),
+            contact: contact.map(|c| c.to_string()),
+            message: message.to_string(),
+            send_at,
+            status: JobStatus::Pending,
+            created_at: Local::now(),
+            attempted_at: None,
+            error: None,
+        };
+        let stored = job.clone();
+        self.update(move |jobs| jobs.push(stored))?;
+        Ok(job)
+    }
+
+    /// Remove a pending job. Jobs already attempted can't be cancelled.
+    pub fn cancel(&self, id: &str) -> Result<ScheduledJob> {
+        self.update(|jobs| {
+            let idx = jobs
+                .iter()
+                .position(|j| j.id == id)
+                .ok_or_else(|| anyhow!("No scheduled job with id '{}'", id))?;
+            if jobs[idx].status != JobStatus::Pending {
+                return Err(anyhow!(
+                    "Job '{}' is {:?} and can no longer be cancelled",
+                    id,
+                    jobs[idx].status
+                ));
+            }
+            Ok(jobs.remove(idx))
+        })?
+    }
+
+    /// Whether any job is waiting to be sent or being sent. An unreadable
+    /// store counts as empty (the worker can't send from it either).
+    pub fn has_unsent(&self) -> bool {
+        self.list()
+            .map(|jobs| jobs.iter().any(|j| matches!(j.status, JobStatus::Pending | JobStatus::InProgress)))
+            .unwrap_or(false)
+    }
+
+    /// Mark due pending jobs in_progress and return them (persisted before return).
+    pub fn claim_due(&self, now: DateTime<Local>) -> Result<Vec<ScheduledJob>> {
+        self.update(|jobs| {
+            let mut claimed = Vec::new();
+            for job in jobs.iter_mut() {
+                if job.status == JobStatus::Pending && job.send_at <= now {
+                    job.status = JobStatus::InProgress;
+                    job.attempted_at = Some(now);
+                    claimed.push(job.clone());
+                }
+            }
+            claimed
+        })
+    }
+
+    /// Record the outcome of a claimed job.
+    pub fn complete(&self, id: &str, outcome: &Result<()>) -> Result<()> {
+        self.update(|jobs| {
+            if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+                match outcome {
+                    Ok(()) => job.status = JobStatus::Sent,
+                    Err(e) => {
+                        job.status = JobStatus::Failed;
+                        job.error = Some(e.to_string());
+                    }
+                }
+            }
+        })
+    }
+
+    /// Fail jobs left in_progress by a previous run (never resend them).
+    pub fn recover_interrupted(&self) -> Result<usize> {
+        self.update(|jobs| {
+            let mut count = 0;
+            for job in jobs.iter_mut().filter(|j| j.status == JobStatus::InProgress) {
+                job.status = JobStatus::Failed;
+                job.error = Some("interrupted during send; not retried to avoid a double send".to_string());
+                count += 1;
+            }
+            count
+        })
+    }
+}
+
+/// Claim and send every due job. Returns the number of jobs attempted.
+pub fn run_due(
+    store: &ScheduleStore,
+    now: DateTime<Local>,
+    send: &dyn Fn(&str, &str) -> Result<()>,
+) -> Result<usize> {
+    let due = store.claim_due(now)?;
+    for job in &due {
+        let outcome = send(&job.recipient, &job.message);
+        if let Err(ref e) = outcome {
+            eprintln!("[scheduler] job {} failed: {}", job.id, e);
+        }
+        store.complete(&job.id, &outcome)?;
+    }
+    Ok(due.len())
+}
+
+/// Spawn the daemon worker that sends due jobs every POLL_INTERVAL_SECS.
+pub fn spawn_worker(store: ScheduleStore) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        match store.recover_interrupted() {
+            Ok(0) => {}
+            Ok(n) => eprintln!("[scheduler] marked {} interrupted job(s) failed", n),
+            Err(e) => eprintln!("[scheduler] recovery error: {}", e),
+        }
+
+        loop {
+            let deliver = |to: &str, message: &str| messaging::deliver(None, to, message).map(drop);
+            if let Err(e) = run_due(&store, Local::now(), &deliver) {
+                eprintln!("[scheduler] error: {}", e);
+            }
+            std::thread::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS));
+        }
+    })
+}
+
+/// Parse a relative delay like "2h", "30m", "1d", "90s", or "1h30m".
+pub fn parse_delay(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    let mut saw_unit = false;
+
+    for ch in input.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        let n: i64 = digits
+            .parse()
+            .map_err(|_| anyhow!("Invalid delay '{}' (expected e.g. 2h, 30m, 1h30m)", input))?;
+        total += match ch.to_ascii_lowercase() {
+            'd' => Duration::days(n),
+            'h' => Duration::hours(n),
+            'm' => Duration::minutes(n),
+            's' => Duration::seconds(n),
+            _ => return Err(anyhow!("Invalid delay unit '{}' in '{}'", ch, input)),
+        };
+        digits.clear();
+        saw_unit = true;
+    }
+
+    if !digits.is_empty() || !saw_unit {
+        return Err(anyhow!("Invalid delay '{}' (expected e.g. 2h, 30m, 1h30m)", input));
+    }
+    Ok(total)
+}
+
+/// Parse an absolute send time in the local timezone.
+///
+/// Accepts RFC 3339, `YYYY-MM-DDTHH:MM[:SS]`, and `YYYY-MM-DD HH:MM[:SS]`.
+pub fn parse_send_at(input: &str) -> Result<DateTime<Local>> {
+    let input = input.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.with_timezone(&Local));
+    }
+
+    const FORMATS: [&str; 4] = [
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%d %H:%M:%S",
+    ];
+    for fmt in FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, fmt) {
+            // DST gaps have no local time; ambiguous times take the earlier one
+            return Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .ok_or_else(|| anyhow!("'{}' does not exist in the local timezone", input));
+        }
+    }
+
+    Err(anyhow!(
+        "Invalid time '{}' (expected e.g. 2026-01-12T09:00)",
+        input
+    ))
+}
+
+/// Resolve --at / --in into a future send time.
+pub fn resolve_send_time(
+    at: Option<&str>,
+    delay: Option<&str>,
+    now: DateTime<Local>,
+) -> Result<Option<DateTime<Local>>> {
+    let send_at = match (at, delay) {
+        (Some(_), Some(_)) => return Err(anyhow!("Use either --at or --in, not both")),
+        (Some(at), None) => parse_send_at(at)?,
+        (None, Some(delay)) => now + parse_delay(delay)?,
+        (None, None) => return Ok(None),
+    };
+    if send_at <= now {
+        return Err(anyhow!("Send time {} is in the past", send_at.to_rfc3339()));
+    }
+    Ok(Some(send_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn store() -> (tempfile::TempDir, ScheduleStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScheduleStore::new(dir.path().join("scheduled.json"));
+        (dir, store)
+    }
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("2h").unwrap(), Duration::hours(2));
+        assert_eq!(parse_delay("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_delay("1d").unwrap(), Duration::days(1));
+        assert!(parse_delay("").is_err());
+        assert!(parse_delay("10").is_err());
+        assert!(parse_delay("3x").is_err());
+    }
+
+    #[test]
+    fn test_parse_send_at_local() {
+        let dt = parse_send_at("2026-01-12T09:00").unwrap();
+        assert_eq!(dt.format("%Y-%m-%d %H:%M").to_string(), "2026-01-12 09:00");
+        assert!(parse_send_at("2026-01-12 09:00:30").is_ok());
+        assert!(parse_send_at("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_resolve_send_time_rejects_past_and_both() {
+        let now = Local::now();
+        assert!(resolve_send_time(Some("2000-01-01T00:00"), None, now).is_err());
+        assert!(resolve_send_time(Some("2999-01-01T00:00"), Some("1h"), now).is_err());
+        assert_eq!(resolve_send_time(None, None, now).unwrap(), None);
+        assert_eq!(
+            resolve_send_time(None, Some("30m"), now).unwrap(),
+            Some(now + Duration::minutes(30))
+        );
+    }
+
+    #[test]
+    fn test_run_due_sends_once() {
+        let (_dir, store) = store();
+        let now = Local::now();
+        store.add("+14155550001", Some("Alice"), "due", now - Duration::minutes(1)).unwrap();
+        store.add("+14155550002", None, "later", now + Duration::hours(1)).unwrap();
+
+        let sent = RefCell::new(Vec::new());
+        let sender = |to: &str, msg: &str| {
+            sent.borrow_mut().push((to.to_string(), msg.to_string()));
+            Ok(())
+        };
+
+        assert_eq!(run_due(&store, now, &sender).unwrap(), 1);
+        // Second pass must not resend
+        assert_eq!(run_due(&store, now, &sender).unwrap(), 0);
+        assert_eq!(sent.borrow().len(), 1);
+
+        let jobs = store.list().unwrap();
+        assert_eq!(jobs[0].status, JobStatus::Sent);
+        assert_eq!(jobs[1].status, JobStatus::Pending);
+    }
+
+    #[test]
+    fn test_failed_send_is_recorded() {
+        let (_dir, store) = store();
+        let now = Local::now();
+        store.add("+14155550001", None, "hi", now).unwrap();
+
+        run_due(&store, now, &|_: &str, _: &str| Err(anyhow!("boom"))).unwrap();
+        let job = &store.list().unwrap()[0];
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_due_jobs_rate_limited_and_logged() {
+        use crate::send_log::{RateLimit, SendLog};
+
+        let (dir, store) = store();
+        let log = SendLog::new(dir.path().join("send_log.ndjson"));
+        let limit = RateLimit { per_recipient: 1, ..RateLimit::default() };
+        let now = Local::now();
+        store.add("+14155550001", None, "first", now - Duration::minutes(2)).unwrap();
+        store.add("+14155550001", None, "second", now - Duration::minutes(1)).unwrap();
+
+        let sender = |to: &str, msg: &str| messaging::send_logged(&log, Some(limit), to, msg, &|_: &str, _: &str| Ok(()));
+        assert_eq!(run_due(&store, now, &sender).unwrap(), 2);
+
+        let jobs = store.list().unwrap();
+        assert_eq!(jobs[0].status, JobStatus::Sent);
+        assert_eq!(jobs[1].status, JobStatus::Failed);
+        assert!(jobs[1].error.as_deref().unwrap().contains("1 sends to +14155550001"), "{:?}", jobs[1].error);
+        let logged = log.read_since(now - Duration::hours(1)).unwrap();
+        assert_eq!(logged.len(), 1);
+        assert_eq!((logged[0].phone.as_str(), logged[0].success), ("+14155550001", true));
+    }
+
+    #[test]
+    fn test_interrupted_jobs_not_retried() {
+        let (_dir, store) = store();
+        let now = Local::now();
+        store.add("+14155550001", None, "hi", now).unwrap();
+
+        // Simulate a crash between claim and completion
+        assert_eq!(store.claim_due(now).unwrap().len(), 1);
+        assert_eq!(store.recover_interrupted().unwrap(), 1);
+        assert_eq!(store.claim_due(now).unwrap().len(), 0);
+        assert_eq!(store.list().unwrap()[0].status, JobStatus::Failed);
+    }
+
+    #[test]
+    fn test_cancel_only_pending() {
+        let (_dir, store) = store();
+        let now = Local::now();
+        let later = store.add("+1", None, "later", now + Duration::hours(1)).unwrap();
+        let due = store.add("+2", None, "due", now).unwrap();
+        store.claim_due(now).unwrap();
+
+        assert!(store.cancel(&due.id).is_err());
+        assert_eq!(store.cancel(&later.id).unwrap().message, "later");
+        assert!(store.cancel("nope").is_err());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+}
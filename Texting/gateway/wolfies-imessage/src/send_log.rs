@@ -0,0 +1,323 @@
+//! Outbound send audit log (~/.wolfies-imessage/send_log.ndjson) and rate limit.
+//!
+//! Every real send attempt through `messaging::deliver` appends one line
+//! (timestamp, phone, chars, success); dry runs are not logged. Before
+//! sending, the recent history is checked against `RateLimit`, so a runaway
+//! script is stopped with RATE_LIMITED instead of texting someone hundreds
+//! of times. Only successful sends count toward the limits. `--force-send`
+//! skips the check (the send is still logged).
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Path comes from the data file registry (data_files::SEND_LOG)
+//! - 10/16/2026 - Initial send log, rate limiter, and per-recipient summary
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::Settings;
+use crate::db::helpers::handle_key;
+use crate::error::CliError;
+use crate::data_files;
+use crate::storage::FileLock;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SendLogEntry {
+    pub timestamp: DateTime<Local>,
+    pub phone: String,
+    /// Message length in characters
+    pub chars: usize,
+    pub success: bool,
+}
+
+impl SendLogEntry {
+    pub fn new(phone: &str, message: &str, success: bool, timestamp: DateTime<Local>) -> Self {
+        Self {
+            timestamp,
+            phone: phone.to_string(),
+            chars: message.chars().count(),
+            success,
+        }
+    }
+}
+
+/// Append-only NDJSON log of send attempts.
+pub struct SendLog {
+    path: PathBuf,
+}
+
+impl SendLog {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Log at the default location.
+    pub fn open_default() -> Self {
+        Self::new(data_files::SEND_LOG.path())
+    }
+
+    /// Exclusive lock on the log. `deliver` holds it across check, send, and
+    /// append so concurrent senders can't both slip under the limit.
+    pub fn lock(&self) -> Result<FileLock> {
+        FileLock::acquire(&self.path)
+    }
+
+    /// Append one entry.
+    pub fn append(&self, entry: &SendLogEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {:?}", self.path))?;
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write {:?}", self.path))
+    }
+
+    /// Entries at or after `since`, oldest first. Malformed lines are skipped.
+    pub fn read_since(&self, since: DateTime<Local>) -> Result<Vec<SendLogEntry>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", self.path)),
+        };
+        let mut entries: Vec<SendLogEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<SendLogEntry>(line).ok())
+            .filter(|e| e.timestamp >= since)
+            .collect();
+        entries.sort_by_key(|e| e.timestamp);
+        Ok(entries)
+    }
+}
+
+/// Send limits; a limit of 0 is disabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Successful sends per rolling minute, across all recipients
+    pub per_minute: u32,
+    /// Successful sends to one recipient per `recipient_window`
+    pub per_recipient: u32,
+    pub recipient_window: Duration,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            per_minute: 10,
+            per_recipient: 3,
+            recipient_window: Duration::minutes(5),
+        }
+    }
+}
+
+impl RateLimit {
+    /// Limits from `[send]` in config.toml.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            per_minute: settings.send_max_per_minute.value,
+            per_recipient: settings.send_max_per_recipient.value,
+            recipient_window: Duration::minutes(settings.send_recipient_window_minutes.value as i64),
+        }
+    }
+
+    /// How far back `check` looks.
+    pub fn lookback(&self) -> Duration {
+        self.recipient_window.max(Duration::minutes(1))
+    }
+
+    /// Whether one more send to `phone` at `now` is allowed by `history`.
+    pub fn check(&self, history: &[SendLogEntry], phone: &str, now: DateTime<Local>) -> Result<(), CliError> {
+        let key = handle_key(phone);
+        let global = window_wait(history.iter().filter(|e| e.success), now, Duration::minutes(1), self.per_minute);
+        let recipient = window_wait(
+            history.iter().filter(|e| e.success && handle_key(&e.phone) == key),
+            now,
+            self.recipient_window,
+            self.per_recipient,
+        );
+
+        // Report the limit that clears last: that's when a retry will succeed
+        let (retry_after_secs, reason) = match (global, recipient) {
+            (None, None) => return Ok(()),
+            (Some(g), Some(r)) if r > g => (r, self.recipient_reason(phone)),
+            (Some(g), _) => (g, format!("{} sends in the last minute", self.per_minute)),
+            (None, Some(r)) => (r, self.recipient_reason(phone)),
+        };
+        Err(CliError::RateLimited { retry_after_secs, reason })
+    }
+
+    fn recipient_reason(&self, phone: &str) -> String {
+        format!(
+            "{} sends to {} in the last {} minutes",
+            self.per_recipient,
+            phone,
+            self.recipient_window.num_minutes()
+        )
+    }
+}
+
+/// Seconds until fewer than `max` of `sends` fall within `window` of `now`,
+/// or None if another send is allowed now.
+fn window_wait<'a>(
+    sends: impl Iterator<Item = &'a SendLogEntry>,
+    now: DateTime<Local>,
+    window: Duration,
+    max: u32,
+) -> Option<u64> {
+    if max == 0 {
+        return None;
+    }
+    let mut recent: Vec<DateTime<Local>> = sends
+        .map(|e| e.timestamp)
+        .filter(|t| *t > now - window && *t <= now)
+        .collect();
+    let max = max as usize;
+    if recent.len() < max {
+        return None;
+    }
+    // Once the (len - max + 1) oldest sends age out, one slot is free
+    recent.sort();
+    let frees_at = recent[recent.len() - max] + window;
+    let millis = (frees_at - now).num_milliseconds().max(0) as u64;
+    Some(millis.div_ceil(1000).max(1))
+}
+
+/// Outbound volume to one recipient (`send-log`).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RecipientVolume {
+    pub phone: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_name: Option<String>,
+    pub sent: usize,
+    pub failed: usize,
+    /// Characters across successful sends
+    pub chars: usize,
+    pub last_sent: Option<DateTime<Local>>,
+}
+
+/// Per-recipient totals (recipients keyed by normalized handle), busiest first.
+pub fn summarize(entries: &[SendLogEntry]) -> Vec<RecipientVolume> {
+    let mut by_key: HashMap<String, RecipientVolume> = HashMap::new();
+    for entry in entries {
+        let volume = by_key.entry(handle_key(&entry.phone)).or_insert_with(|| RecipientVolume {
+            phone: entry.phone.clone(),
+            contact_name: None,
+            sent: 0,
+            failed: 0,
+            chars: 0,
+            last_sent: None,
+        });
+        if entry.success {
+            volume.sent += 1;
+            volume.chars += entry.chars;
+            volume.last_sent = volume.last_sent.max(Some(entry.timestamp));
+        } else {
+            volume.failed += 1;
+        }
+    }
+    let mut volumes: Vec<RecipientVolume> = by_key.into_values().collect();
+    volumes.sort_by(|a, b| {
+        (b.sent, b.failed)
+            .cmp(&(a.sent, a.failed))
+            .then_with(|| a.phone.cmp(&b.phone))
+    });
+    volumes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 10, 12, 0, 0).unwrap()
+    }
+
+    /// Sends `secs_ago` seconds before now().
+    fn history(sends: &[(&str, i64, bool)]) -> Vec<SendLogEntry> {
+        sends
+            .iter()
+            .map(|&(phone, secs_ago, success)| {
+                SendLogEntry::new(phone, "hi", success, now() - Duration::seconds(secs_ago))
+            })
+            .collect()
+    }
+
+    fn retry_after(result: Result<(), CliError>) -> u64 {
+        match result {
+            Err(CliError::RateLimited { retry_after_secs, .. }) => retry_after_secs,
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_per_recipient_limit() {
+        let limit = RateLimit::default();
+        let alice = "+14155550001";
+
+        // Two recent sends: a third is fine
+        let log = history(&[(alice, 200, true), (alice, 100, true)]);
+        assert!(limit.check(&log, alice, now()).is_ok());
+
+        // Three in 5 minutes: blocked until the oldest is 5 minutes old,
+        // matching on the normalized handle
+        let log = history(&[(alice, 200, true), (alice, 100, true), ("4155550001", 10, true)]);
+        assert_eq!(retry_after(limit.check(&log, alice, now())), 100);
+        assert!(limit.check(&log, "+14155550002", now()).is_ok());
+
+        // Failed sends and sends outside the window don't count
+        let log = history(&[(alice, 400, true), (alice, 100, false), (alice, 50, true), (alice, 10, true)]);
+        assert!(limit.check(&log, alice, now()).is_ok());
+    }
+
+    #[test]
+    fn test_global_limit_and_disabled_limits() {
+        let limit = RateLimit::default();
+        let burst: Vec<(String, i64)> = (0..10).map(|i| (format!("+1415555{:04}", i), 50 - i)).collect();
+        let sends: Vec<(&str, i64, bool)> = burst.iter().map(|(p, s)| (p.as_str(), *s, true)).collect();
+        let log = history(&sends);
+
+        // Ten sends in the last minute, oldest 50s ago: free in 10s
+        assert_eq!(retry_after(limit.check(&log, "+14155559999", now())), 10);
+        // A minute later the burst has aged out
+        assert!(limit.check(&log, "+14155559999", now() + Duration::seconds(60)).is_ok());
+
+        let unlimited = RateLimit { per_minute: 0, per_recipient: 0, ..limit };
+        assert!(unlimited.check(&log, "+14155559999", now()).is_ok());
+    }
+
+    #[test]
+    fn test_log_round_trip_and_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = SendLog::new(dir.path().join("send_log.ndjson"));
+        assert!(log.read_since(now() - Duration::days(1)).unwrap().is_empty());
+
+        for entry in history(&[("+14155550001", 7200, true), ("+14155550001", 60, true), ("+14155550002", 30, false)]) {
+            log.append(&entry).unwrap();
+        }
+        let mut file = std::fs::OpenOptions::new().append(true).open(dir.path().join("send_log.ndjson")).unwrap();
+        writeln!(file, "{{not json").unwrap();
+
+        let recent = log.read_since(now() - Duration::hours(1)).unwrap();
+        assert_eq!(recent.len(), 2);
+
+        let all = log.read_since(now() - Duration::days(1)).unwrap();
+        let summary = summarize(&all);
+        assert_eq!(summary.len(), 2);
+        assert_eq!((summary[0].phone.as_str(), summary[0].sent, summary[0].chars), ("+14155550001", 2, 4));
+        assert_eq!(summary[0].last_sent, Some(now() - Duration::seconds(60)));
+        assert_eq!((summary[1].sent, summary[1].failed, summary[1].last_sent), (0, 1, None));
+    }
+}
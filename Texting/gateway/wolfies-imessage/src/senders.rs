@@ -0,0 +1,229 @@
+//! Known and unknown senders (Messages' "Unknown Senders" split).
+//!
+//! A sender is known when their handle belongs to a contact, or when the
+//! conversation is one Messages itself didn't filter: on schemas with
+//! chat.is_filtered, once any chat has a non-zero flag (the filter is in
+//! use), chats with is_filtered = 0 count as known. Without the column, or
+//! with every flag zero, only contacts decide.
+//!
+//! `is_known_sender` on message outputs and the `--known-only` /
+//! `--unknown-only` filters (`sender_filter` over the daemon) share this
+//! test. The filters are SQL conditions, so they apply before the limit.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial SenderMode / SenderFilter (contacts plus chat.is_filtered)
+
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use std::collections::HashSet;
+
+use crate::contacts::manager::ContactsManager;
+use crate::db::helpers::{has_column, rowids_condition, HandleFilter};
+
+/// Which senders a listing keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SenderMode {
+    #[default]
+    All,
+    Known,
+    Unknown,
+}
+
+impl SenderMode {
+    /// From the CLI's `--known-only` / `--unknown-only` (clap keeps them exclusive).
+    pub fn from_flags(known_only: bool, unknown_only: bool) -> Self {
+        match (known_only, unknown_only) {
+            (true, _) => SenderMode::Known,
+            (_, true) => SenderMode::Unknown,
+            _ => SenderMode::All,
+        }
+    }
+
+    /// From the daemon's `sender_filter` param: "all", "known", or "unknown".
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "all" => Ok(SenderMode::All),
+            "known" => Ok(SenderMode::Known),
+            "unknown" => Ok(SenderMode::Unknown),
+            other => Err(anyhow!("Unknown sender_filter: {} (expected all|known|unknown)", other)),
+        }
+    }
+}
+
+/// Chats Messages left unfiltered, while its unknown-sender filter is in use.
+#[derive(Debug, Clone, Default)]
+struct UnfilteredChats {
+    rowids: Vec<i64>,
+    identifiers: HashSet<String>,
+}
+
+/// The known-sender test for one database, plus the mode a listing applies.
+#[derive(Debug, Clone)]
+pub struct SenderFilter {
+    mode: SenderMode,
+    contacts: HandleFilter,
+    chats: Option<UnfilteredChats>,
+}
+
+impl SenderFilter {
+    /// Resolve contacts' handles and Messages' chat flags against the database.
+    pub fn load(conn: &Connection, contacts: &ContactsManager, mode: SenderMode) -> Result<Self> {
+        let handles: Vec<String> = contacts
+            .all()
+            .iter()
+            .flat_map(|c| c.all_handles().map(str::to_string))
+            .collect();
+        Ok(Self {
+            mode,
+            contacts: HandleFilter::resolve(conn, &handles)?,
+            chats: unfiltered_chats(conn)?,
+        })
+    }
+
+    /// Whether a message from `handle` in the chat `chat_identifier` is from a known sender.
+    pub fn is_known(&self, handle: Option<&str>, chat_identifier: Option<&str>) -> bool {
+        handle.is_some_and(|h| self.contacts.matches(h))
+            || self
+                .chats
+                .as_ref()
+                .zip(chat_identifier)
+                .is_some_and(|(chats, id)| chats.identifiers.contains(id))
+    }
+
+    /// Whether the mode keeps a message whose sender is (or isn't) known.
+    pub fn keeps(&self, known: bool) -> bool {
+        match self.mode {
+            SenderMode::All => true,
+            SenderMode::Known => known,
+            SenderMode::Unknown => !known,
+        }
+    }
+
+    /// SQL condition keeping the mode's senders, given a message ROWID column
+    /// and its handle ROWID column; None for `SenderMode::All`.
+    pub fn sql_condition(&self, message_column: &str, handle_column: &str) -> Option<String> {
+        let known = self.sql_known(message_column, handle_column);
+        match self.mode {
+            SenderMode::All => None,
+            SenderMode::Known => Some(known),
+            SenderMode::Unknown => Some(format!("NOT {}", known)),
+        }
+    }
+
+    fn sql_known(&self, message_column: &str, handle_column: &str) -> String {
+        let by_contact = self.contacts.sql_condition(handle_column);
+        match self.chats {
+            Some(ref chats) => format!(
+                "({} OR EXISTS (SELECT 1 FROM chat_message_join sender_cmj WHERE sender_cmj.message_id = {} AND {}))",
+                by_contact,
+                message_column,
+                rowids_condition("sender_cmj.chat_id", &chats.rowids)
+            ),
+            None => format!("({})", by_contact),
+        }
+    }
+}
+
+/// Unfiltered chats, when the schema has chat.is_filtered and some chat is filtered.
+fn unfiltered_chats(conn: &Connection) -> Result<Option<UnfilteredChats>> {
+    if !has_column(conn, "chat", "is_filtered") {
+        return Ok(None);
+    }
+    let filtering: bool = conn.query_row("SELECT EXISTS (SELECT 1 FROM chat WHERE is_filtered != 0)", [], |row| {
+        row.get(0)
+    })?;
+    if !filtering {
+        return Ok(None);
+    }
+    let mut chats = UnfilteredChats::default();
+    let mut stmt = conn.prepare_cached("SELECT ROWID, chat_identifier FROM chat WHERE is_filtered = 0")?;
+    let rows = stmt.query_map([], |row: &rusqlite::Row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+    })?;
+    for row in rows {
+        let (rowid, identifier) = row?;
+        chats.rowids.push(rowid);
+        chats.identifiers.extend(identifier);
+    }
+    Ok(Some(chats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contacts::manager::Contact;
+    use crate::db::fixtures::{empty_db, insert_chat, insert_handle, insert_message};
+
+    fn contacts() -> ContactsManager {
+        ContactsManager::from_contacts(vec![Contact {
+            name: "Alice".to_string(),
+            phone: "+14155550001".to_string(),
+            relationship_type: "friend".to_string(),
+            notes: None,
+            handles: Vec::new(),
+        }])
+    }
+
+    /// Texts of received messages the filter keeps, newest first.
+    fn kept(conn: &Connection, filter: &SenderFilter) -> Vec<String> {
+        let condition = filter.sql_condition("m.ROWID", "m.handle_id").unwrap_or_else(|| "1".to_string());
+        let sql = format!("SELECT m.text FROM message m WHERE m.is_from_me = 0 AND {} ORDER BY m.date DESC", condition);
+        let mut stmt = conn.prepare(&sql).unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_contacts_decide_without_chat_flags() {
+        let conn = empty_db();
+        let alice = insert_handle(&conn, "+14155550001");
+        let stranger = insert_handle(&conn, "+14155550009");
+        let chat_alice = insert_chat(&conn, "+14155550001", None, &[alice]);
+        let chat_stranger = insert_chat(&conn, "+14155550009", None, &[stranger]);
+        insert_message(&conn, chat_alice, alice, Some("from alice"), 2, false, true);
+        insert_message(&conn, chat_stranger, stranger, Some("from stranger"), 1, false, true);
+
+        let known = SenderFilter::load(&conn, &contacts(), SenderMode::Known).unwrap();
+        assert_eq!(kept(&conn, &known), ["from alice"]);
+        assert!(known.is_known(Some("(415) 555-0001"), None));
+        assert!(!known.is_known(Some("+14155550009"), Some("+14155550009")));
+
+        let unknown = SenderFilter::load(&conn, &contacts(), SenderMode::Unknown).unwrap();
+        assert_eq!(kept(&conn, &unknown), ["from stranger"]);
+        let all = SenderFilter::load(&conn, &contacts(), SenderMode::All).unwrap();
+        assert!(all.sql_condition("m.ROWID", "m.handle_id").is_none());
+        assert!(all.keeps(false) && known.keeps(true) && !known.keeps(false) && unknown.keeps(false));
+    }
+
+    #[test]
+    fn test_unfiltered_chats_count_as_known_once_filtering_is_in_use() {
+        let conn = empty_db();
+        conn.execute_batch("ALTER TABLE chat ADD COLUMN is_filtered INTEGER DEFAULT 0").unwrap();
+        let replied = insert_handle(&conn, "+14155550008");
+        let stranger = insert_handle(&conn, "+14155550009");
+        let chat_replied = insert_chat(&conn, "+14155550008", None, &[replied]);
+        let chat_stranger = insert_chat(&conn, "+14155550009", None, &[stranger]);
+        insert_message(&conn, chat_replied, replied, Some("not in contacts"), 2, false, true);
+        insert_message(&conn, chat_stranger, stranger, Some("spam"), 1, false, true);
+
+        // Every flag zero: Messages isn't filtering, so only contacts count
+        let known = SenderFilter::load(&conn, &contacts(), SenderMode::Known).unwrap();
+        assert!(kept(&conn, &known).is_empty());
+
+        conn.execute("UPDATE chat SET is_filtered = 1 WHERE ROWID = ?1", [chat_stranger]).unwrap();
+        let known = SenderFilter::load(&conn, &contacts(), SenderMode::Known).unwrap();
+        assert_eq!(kept(&conn, &known), ["not in contacts"]);
+        assert!(known.is_known(Some("+14155550008"), Some("+14155550008")));
+        let unknown = SenderFilter::load(&conn, &contacts(), SenderMode::Unknown).unwrap();
+        assert_eq!(kept(&conn, &unknown), ["spam"]);
+    }
+
+    #[test]
+    fn test_mode_parsing() {
+        assert_eq!(SenderMode::parse("Known").unwrap(), SenderMode::Known);
+        assert_eq!(SenderMode::parse("unknown").unwrap(), SenderMode::Unknown);
+        assert_eq!(SenderMode::parse("all").unwrap(), SenderMode::All);
+        assert!(SenderMode::parse("friends").is_err());
+        assert_eq!(SenderMode::from_flags(false, true), SenderMode::Unknown);
+        assert_eq!(SenderMode::from_flags(false, false), SenderMode::All);
+    }
+}
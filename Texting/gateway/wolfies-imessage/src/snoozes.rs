@@ -0,0 +1,143 @@
+//! Snoozed follow-ups (~/.wolfies-imessage/snoozes.json).
+//!
+//! A snoozed contact is left out of `followup` (unanswered questions and
+//! stale conversations) until the snooze ends. The CLI and the daemon's
+//! followup handler read the same file, and `followup snooze`/`unsnooze`
+//! (`followup_snooze`/`followup_unsnooze` over the daemon) write it.
+//! Contacts match by normalized handle (`handle_key`), any of the handles
+//! the contact had when snoozed. Every snooze has an end; expired entries
+//! are pruned on the next write.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - SnoozeStore is a storage::ExpiringStore (shared with mutes); snoozes cover every handle of the contact (Snooze.handles)
+//! - 10/16/2026 - Path comes from the data file registry (data_files::SNOOZES)
+//! - 10/16/2026 - Initial snooze store
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::db::helpers::handle_key;
+use crate::output;
+use crate::data_files;
+use crate::storage::{Expiring, ExpiringStore};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Snooze {
+    /// The contact's normalized handle
+    pub key: String,
+    /// The contact's other handles, normalized
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handles: Vec<String>,
+    /// Target as given to `followup snooze`
+    pub target: String,
+    /// Contact name, when known
+    pub name: Option<String>,
+    pub until: DateTime<Local>,
+    pub created_at: DateTime<Local>,
+}
+
+impl Snooze {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.until > now
+    }
+
+    /// Whether `handle` (any spelling of the number or email) is snoozed.
+    pub fn matches(&self, handle: &str) -> bool {
+        let key = handle_key(handle);
+        key == self.key || self.handles.contains(&key)
+    }
+}
+
+impl Expiring for Snooze {
+    const LIST_KEY: &'static str = "snoozes";
+
+    fn expires_at(&self) -> Option<DateTime<Local>> {
+        Some(self.until)
+    }
+
+    fn same_target(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+/// Snooze list backed by snoozes.json.
+pub type SnoozeStore = ExpiringStore<Snooze>;
+
+impl SnoozeStore {
+    /// Store at the default location.
+    pub fn open_default() -> Self {
+        Self::new(data_files::SNOOZES.path())
+    }
+
+    /// Active snoozes for a followup run. An unreadable file warns and
+    /// snoozes nothing, so a bad snoozes.json never hides follow-ups.
+    pub fn active_or_warn(&self, now: DateTime<Local>) -> Vec<Snooze> {
+        self.active(now).unwrap_or_else(|e| {
+            output::warn(format!("ignoring snoozes.json: {:#}", e));
+            Vec::new()
+        })
+    }
+
+    /// Remove the snooze for a contact (by normalized handle).
+    pub fn remove_snooze(&self, key: &str, now: DateTime<Local>) -> Result<Snooze> {
+        self.remove(now, |s| s.key == key)?
+            .ok_or_else(|| anyhow!("'{}' is not snoozed", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn snooze(handle: &str, until: DateTime<Local>) -> Snooze {
+        Snooze {
+            key: handle_key(handle),
+            handles: Vec::new(),
+            target: handle.to_string(),
+            name: None,
+            until,
+            created_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_snoozes_expire_replace_and_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SnoozeStore::new(dir.path().join("snoozes.json"));
+        let now = Local::now();
+
+        store.add(snooze("+14155550001", now + Duration::hours(1)), now).unwrap();
+        store.add(snooze("bob@example.com", now + Duration::days(2)), now).unwrap();
+        let active = store.active(now).unwrap();
+        assert_eq!(active.len(), 2);
+        assert!(active[0].matches("(415) 555-0001"));
+        assert!(active[1].matches("Bob@Example.com"));
+
+        // Snoozing again moves the end instead of adding a second entry
+        store.add(snooze("4155550001", now + Duration::days(3)), now).unwrap();
+        let active = store.active(now).unwrap();
+        assert_eq!(active.iter().map(|s| s.target.as_str()).collect::<Vec<_>>(), ["bob@example.com", "4155550001"]);
+
+        // Past its end a snooze stops applying and is pruned on the next write
+        let later = now + Duration::days(2) + Duration::hours(1);
+        assert_eq!(store.active(later).unwrap().len(), 1);
+        store.remove_snooze(&handle_key("+14155550001"), later).unwrap();
+        assert!(store.active(later).unwrap().is_empty());
+        assert!(store.remove_snooze(&handle_key("+14155550001"), later).is_err());
+        let raw = std::fs::read_to_string(dir.path().join("snoozes.json")).unwrap();
+        assert!(!raw.contains("bob@example.com"));
+        assert!(raw.contains("\"snoozes\""));
+    }
+
+    #[test]
+    fn test_snooze_covers_every_handle_of_the_contact() {
+        let now = Local::now();
+        let snooze = Snooze { handles: vec![handle_key("alice@example.com")], ..snooze("+14155550001", now) };
+        assert!(snooze.matches("Alice@Example.com"));
+        assert!(snooze.matches("(415) 555-0001"));
+        assert!(!snooze.matches("bob@example.com"));
+    }
+}
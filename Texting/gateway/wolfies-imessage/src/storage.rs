@@ -0,0 +1,245 @@
+//! Local state under ~/.wolfies-imessage (scheduled jobs, caches, config).
+//!
+//! Files are written atomically (temp file + rename) so a crash or a
+//! concurrent reader never sees a half-written JSON document, and an advisory
+//! lock serializes read-modify-write cycles between the CLI and the daemon.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - ExpiringStore: the expiring-entry JSON list behind mutes.json and snoozes.json (Expiring trait)
+//! - 10/16/2026 - Added write_atomic (raw bytes); write_json_atomic builds on it
+//! - 10/16/2026 - Removed config.json; settings moved to config.toml (crate::config)
+//! - 10/16/2026 - Added config.json (Config::load)
+//! - 10/16/2026 - Initial data dir, atomic JSON writes, and file locking
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// Data directory (default: ~/.wolfies-imessage).
+///
+/// Override with WOLFIES_IMESSAGE_HOME (used by tests and sandboxed setups).
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("WOLFIES_IMESSAGE_HOME") {
+        return PathBuf::from(dir);
+    }
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".wolfies-imessage")
+}
+
+/// Path to a file in the data directory.
+pub fn data_file(name: &str) -> PathBuf {
+    data_dir().join(name)
+}
+
+/// Read a JSON file, returning `T::default()` if it doesn't exist yet.
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {:?}", path)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    }
+}
+
+/// Write JSON atomically: write a sibling temp file, fsync, then rename.
+pub fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    write_atomic(path, json.as_bytes())
+}
+
+/// Write bytes atomically: write a sibling temp file, fsync, then rename.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "data".to_string());
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    {
+        let mut file = File::create(&tmp)
+            .with_context(|| format!("Failed to create {:?}", tmp))?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
+
+/// Exclusive advisory lock on `<path>.lock`, released on drop.
+pub struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    /// Block until the lock for `path` is acquired.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let lock_path = path.with_file_name(format!(
+            "{}.lock",
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "data".to_string())
+        ));
+        if let Some(parent) = lock_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open lock file {:?}", lock_path))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: fd is valid for the lifetime of `file`
+            let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) };
+            if rc != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .with_context(|| format!("Failed to lock {:?}", lock_path));
+            }
+        }
+
+        Ok(Self { file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: fd is still owned by self.file
+            unsafe {
+                libc::flock(self.file.as_raw_fd(), libc::LOCK_UN);
+            }
+        }
+    }
+}
+
+/// An entry in an `ExpiringStore`.
+pub trait Expiring: Clone + Serialize + DeserializeOwned {
+    /// Key of the entry list in the file (`{"mutes": [...]}`).
+    const LIST_KEY: &'static str;
+
+    /// When the entry stops applying; never when None.
+    fn expires_at(&self) -> Option<DateTime<Local>>;
+
+    /// Whether `other` is for the same target, so adding one replaces the other.
+    fn same_target(&self, other: &Self) -> bool;
+}
+
+fn is_live<T: Expiring>(entry: &T, now: DateTime<Local>) -> bool {
+    entry.expires_at().is_none_or(|at| at > now)
+}
+
+/// A JSON list of entries that expire (mutes.json, snoozes.json), shared by
+/// the CLI and the daemon. Writes are read-modify-write cycles under the
+/// file lock; expired entries are pruned on every write.
+#[derive(Debug, Clone)]
+pub struct ExpiringStore<T> {
+    path: PathBuf,
+    entries: PhantomData<T>,
+}
+
+impl<T: Expiring> ExpiringStore<T> {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            entries: PhantomData,
+        }
+    }
+
+    fn read(&self) -> Result<Vec<T>> {
+        let mut file: BTreeMap<String, Vec<T>> = read_json(&self.path)?;
+        Ok(file.remove(T::LIST_KEY).unwrap_or_default())
+    }
+
+    /// Run a read-modify-write cycle under the file lock, pruning expired entries.
+    fn update<R>(&self, now: DateTime<Local>, f: impl FnOnce(&mut Vec<T>) -> R) -> Result<R> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let mut entries = self.read()?;
+        entries.retain(|e| is_live(e, now));
+        let result = f(&mut entries);
+        write_json_atomic(&self.path, &BTreeMap::from([(T::LIST_KEY, entries)]))?;
+        Ok(result)
+    }
+
+    /// Entries still in effect at `now`, oldest first.
+    pub fn active(&self, now: DateTime<Local>) -> Result<Vec<T>> {
+        let _lock = FileLock::acquire(&self.path)?;
+        Ok(self.read()?.into_iter().filter(|e| is_live(e, now)).collect())
+    }
+
+    /// Add an entry, replacing any existing one for the same target (so
+    /// adding again changes when it ends).
+    pub fn add(&self, entry: T, now: DateTime<Local>) -> Result<T> {
+        let stored = entry.clone();
+        self.update(now, move |entries| {
+            entries.retain(|e| !e.same_target(&stored));
+            entries.push(stored);
+        })?;
+        Ok(entry)
+    }
+
+    /// Remove and return the entry `is_target` picks; None when there's none.
+    pub fn remove(&self, now: DateTime<Local>, is_target: impl Fn(&T) -> bool) -> Result<Option<T>> {
+        self.update(now, |entries| {
+            let idx = entries.iter().position(is_target)?;
+            Some(entries.remove(idx))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_read_missing_is_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let value: HashMap<String, i32> = read_json(&dir.path().join("missing.json")).unwrap();
+        assert!(value.is_empty());
+    }
+
+    #[test]
+    fn test_atomic_write_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.json");
+        let mut value = HashMap::new();
+        value.insert("a".to_string(), 1);
+
+        write_json_atomic(&path, &value).unwrap();
+        let back: HashMap<String, i32> = read_json(&path).unwrap();
+        assert_eq!(back, value);
+
+        // No temp files left behind
+        let leftovers = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_lock_reacquire_after_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        drop(FileLock::acquire(&path).unwrap());
+        let _again = FileLock::acquire(&path).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+//! Term frequency for `analytics --top-terms`: tokenizer, stopwords, counter.
+//!
+//! Kept free of database access so the tokenizer rules are unit-testable;
+//! the command streams message text in via `TermCounter::add`.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Initial tokenizer, English stopword list, and term counter
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Terms reported per side of the conversation.
+pub const TOP_TERMS: usize = 30;
+
+/// Shorter tokens ("ok", "lol" aside) are almost always filler.
+pub const MIN_TERM_LEN: usize = 3;
+
+/// Common English words, written without apostrophes (tokens are stored that way).
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "and", "any", "are", "aren", "arent", "around",
+    "back", "because", "been", "before", "being", "but", "can", "cant", "could", "couldnt",
+    "did", "didnt", "does", "doesnt", "doing", "done", "dont", "down", "each", "even", "for",
+    "from", "get", "got", "gonna", "had", "has", "have", "havent", "having", "her", "here",
+    "hers", "him", "his", "how", "ill", "its", "ive", "just", "know", "like", "lol", "make",
+    "many", "may", "more", "most", "much", "must", "need", "not", "now", "off", "okay", "one",
+    "only", "other", "our", "ours", "out", "over", "really", "said", "same", "see", "she",
+    "should", "shouldnt", "some", "still", "such", "than", "that", "thats", "the", "their",
+    "theirs", "them", "then", "there", "theres", "these", "they", "theyre", "thing", "think",
+    "this", "those", "through", "too", "under", "until", "very", "want", "was", "wasnt", "way",
+    "well", "were", "what", "whats", "when", "where", "which", "while", "who", "whom", "why",
+    "will", "with", "would", "wouldnt", "yeah", "yes", "you", "youll", "your", "youre",
+    "yours", "youve",
+];
+
+/// A term and how often it appeared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct TermCount {
+    pub term: String,
+    pub count: usize,
+}
+
+/// Lowercased word tokens with punctuation stripped ("Don't!" -> "dont").
+///
+/// URLs are skipped whole; no length or stopword filtering happens here.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter(|word| !is_url(word))
+        .flat_map(|word| {
+            // Apostrophes join ("don't"); any other punctuation splits ("dinner/drinks")
+            word.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+                .map(|part| {
+                    part.chars()
+                        .filter(|c| c.is_alphanumeric())
+                        .flat_map(char::to_lowercase)
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+fn is_url(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("www.")
+}
+
+/// Whether a token is worth counting: long enough, not a stopword, not a number.
+pub fn is_term(token: &str) -> bool {
+    token.chars().count() >= MIN_TERM_LEN
+        && !token.chars().all(|c| c.is_ascii_digit())
+        && !STOPWORDS.contains(&token)
+}
+
+/// Counts terms across messages, skipping stopwords and excluded names.
+#[derive(Debug, Default)]
+pub struct TermCounter {
+    counts: HashMap<String, usize>,
+    excluded: HashSet<String>,
+}
+
+impl TermCounter {
+    /// A counter that ignores every token of the given names
+    /// (e.g. "John Smith" excludes "john" and "smith").
+    pub fn new(excluded_names: &[&str]) -> Self {
+        TermCounter {
+            counts: HashMap::new(),
+            excluded: excluded_names.iter().flat_map(|name| tokenize(name)).collect(),
+        }
+    }
+
+    pub fn add(&mut self, text: &str) {
+        for token in tokenize(text) {
+            if is_term(&token) && !self.excluded.contains(&token) {
+                *self.counts.entry(token).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// The `n` most frequent terms, ties broken alphabetically.
+    pub fn top(&self, n: usize) -> Vec<TermCount> {
+        let mut terms: Vec<TermCount> = self
+            .counts
+            .iter()
+            .map(|(term, &count)| TermCount { term: term.clone(), count })
+            .collect();
+        terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+        terms.truncate(n);
+        terms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let cases: &[(&str, &[&str])] = &[
+            ("Hello, World!", &["hello", "world"]),
+            ("Don't  stop", &["dont", "stop"]),
+            ("it’s fine", &["its", "fine"]),
+            ("dinner/drinks @ 8?", &["dinner", "drinks", "8"]),
+            ("see https://example.com/a-b now", &["see", "now"]),
+            ("ÉCOLE café", &["école", "café"]),
+            ("...", &[]),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(tokenize(text), *expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_is_term() {
+        assert!(is_term("pizza"));
+        assert!(is_term("ski"));
+        assert!(!is_term("ok"));
+        assert!(!is_term("the"));
+        assert!(!is_term("dont"));
+        assert!(!is_term("2026"));
+        assert!(is_term("3pm"));
+    }
+
+    #[test]
+    fn test_counter_excludes_names_and_ranks() {
+        let mut counter = TermCounter::new(&["Sarah Connor", "me"]);
+        counter.add("Sarah, pizza tonight?");
+        counter.add("Pizza again! Connor says pizza.");
+        counter.add("Tacos or pizza, Sarah?");
+        counter.add("tacos");
+
+        let top = counter.top(2);
+        assert_eq!(
+            top,
+            vec![
+                TermCount { term: "pizza".into(), count: 4 },
+                TermCount { term: "tacos".into(), count: 2 },
+            ]
+        );
+        let all = counter.top(TOP_TERMS);
+        assert!(all.iter().all(|t| t.term != "sarah" && t.term != "connor"));
+        // Ties sort alphabetically
+        assert_eq!(all[2..].iter().map(|t| t.term.as_str()).collect::<Vec<_>>(), ["says", "tonight"]);
+    }
+}
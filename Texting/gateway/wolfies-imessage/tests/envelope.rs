@@ -0,0 +1,101 @@
+//! `--envelope` wraps command output as `{ok, data, meta}` with the applied parameters.
+
+mod support;
+
+use serde_json::Value;
+use std::process::Command;
+use support::osascript::fake_osascript;
+use support::synthetic_db::{SyntheticConfig, SyntheticDb};
+
+/// Run the CLI against `db` with `--envelope`, a one-contact contacts.json,
+/// and a private data directory; returns stdout as JSON.
+fn run(db: &SyntheticDb, args: &[&str]) -> Value {
+    parse(command(db, args))
+}
+
+/// The command `run` runs.
+fn command(db: &SyntheticDb, args: &[&str]) -> Command {
+    let contacts = db.dir.path().join("contacts.json");
+    let json = format!(r#"{{"contacts": [{{"name": "Alice Example", "phone": "{}"}}]}}"#, db.phone(0));
+    std::fs::write(&contacts, json).unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_wolfies-imessage"));
+    command
+        .args(args)
+        .arg("--envelope")
+        .arg("--no-cache")
+        .arg("--db-path")
+        .arg(&db.path)
+        .env("IMESSAGE_CONTACTS_PATH", &contacts)
+        .env("WOLFIES_IMESSAGE_HOME", db.dir.path().join("home"))
+        .env("WOLFIES_CONFIG", db.dir.path().join("config.toml"));
+    command
+}
+
+/// stdout of `command` as one JSON document.
+fn parse(mut command: Command) -> Value {
+    let output = command.output().expect("run wolfies-imessage");
+    serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|e| panic!("stdout is not JSON ({}): {}", e, String::from_utf8_lossy(&output.stdout)))
+}
+
+#[test]
+fn test_messages_envelope_reports_resolved_contact() {
+    let db = SyntheticDb::create(SyntheticConfig::small());
+    let value = run(&db, &["messages", "Alice", "--limit", "5", "--fields", "text,phone"]);
+
+    assert_eq!(value["ok"], true);
+    let data = value["data"].as_array().unwrap();
+    assert_eq!(data.len(), 5);
+    assert_eq!(value["meta"]["count"], 5);
+    assert!(value["meta"]["elapsed_ms"].is_number());
+    let applied = &value["meta"]["applied"];
+    assert_eq!(applied["contact_resolved"], db.phone(0));
+    assert_eq!(applied["fields"], "text,phone");
+    assert!(applied["days"].is_null());
+}
+
+#[test]
+fn test_analytics_envelope_reports_days_and_errors() {
+    let db = SyntheticDb::create(SyntheticConfig::small());
+    let value = run(&db, &["analytics", "--days", "7"]);
+    assert_eq!(value["ok"], true);
+    assert_eq!(value["meta"]["count"], 1);
+    assert_eq!(value["meta"]["applied"]["days"], 7);
+    assert!(value["data"]["total_messages"].is_number());
+
+    // Failures keep the shape, with the error in place of data
+    let value = run(&db, &["analytics", "Nobody Known", "--days", "7"]);
+    assert_eq!(value["ok"], false);
+    assert_eq!(value["error"]["code"], "ERROR");
+    assert!(value["error"]["message"].as_str().unwrap().contains("not found"));
+}
+
+#[test]
+fn test_explain_plan_goes_in_meta() {
+    let db = SyntheticDb::create(SyntheticConfig::small());
+    let value = run(&db, &["recent", "--limit", "2", "--explain"]);
+    assert_eq!(value["ok"], true);
+    let plan = &value["meta"]["query_plan"];
+    // The slowest SELECT, which under load needn't be the message query
+    assert!(plan["sql"].as_str().unwrap().contains("SELECT"), "{}", plan);
+    assert!(!plan["plan"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_failed_send_and_mark_read_print_one_error_envelope() {
+    // Both fail at the osascript step; `parse` would reject a result
+    // document printed ahead of the error envelope
+    let db = SyntheticDb::create(SyntheticConfig::small());
+    let path = fake_osascript(db.dir.path(), "echo 'execution error: Messages got an error (-1708)' >&2; exit 1");
+    let mut send = command(&db, &["send-by-phone", &db.phone(0), "hello", "--json"]);
+    send.env("PATH", &path);
+    let value = parse(send);
+    assert_eq!(value["ok"], false);
+    assert!(value["error"]["message"].as_str().unwrap().contains("Failed to send message"));
+
+    let value = run(&db, &["mark-read", "--group-id", "chat123456", "--json"]);
+    assert_eq!(value["ok"], false);
+    assert_eq!(value["error"]["code"], "PARTIAL_FAILURE");
+    assert!(value["error"]["message"].as_str().unwrap().contains("chat123456"), "{}", value);
+}
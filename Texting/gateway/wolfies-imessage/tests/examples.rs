@@ -0,0 +1,50 @@
+//! Every subcommand's `--help` ends with examples, and `examples` lists them all.
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_wolfies-imessage"))
+        .args(args)
+        .output()
+        .expect("run wolfies-imessage");
+    assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Subcommand names from the top-level help's "Commands:" list.
+fn subcommands() -> Vec<String> {
+    let help = run(&["--help"]);
+    let list = help.split("Commands:\n").nth(1).expect("Commands: section");
+    list.lines()
+        .take_while(|line| line.starts_with("  "))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| *name != "help")
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_every_subcommand_help_has_examples() {
+    let names = subcommands();
+    assert!(names.len() > 40, "{:?}", names);
+    for name in &names {
+        let help = run(&[name, "--help"]);
+        let examples = help.split("\nExamples:\n").nth(1).unwrap_or_else(|| panic!("{} --help has no examples", name));
+        assert!(examples.contains("  wolfies-imessage "), "{} examples: {}", name, examples);
+    }
+}
+
+#[test]
+fn test_cookbook_covers_every_subcommand() {
+    let cookbook = run(&["examples"]);
+    for name in subcommands() {
+        assert!(cookbook.contains(&format!("\n{}:\n", name)), "{} missing from examples", name);
+    }
+
+    let llm = run(&["examples", "llm"]);
+    assert!(llm.contains("bundle:") && !llm.contains("send:"), "{}", llm);
+    // A command name prints just that command's block
+    let mark_read = run(&["examples", "mark-read"]);
+    assert!(mark_read.starts_with("mark-read:\n"), "{}", mark_read);
+    assert!(mark_read.lines().skip(1).all(|line| line.starts_with("  ")), "{}", mark_read);
+}
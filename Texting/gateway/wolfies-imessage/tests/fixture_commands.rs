@@ -0,0 +1,203 @@
+//! Reading, search, analytics, group, and follow-up commands against the
+//! hand-written fixture chat.db, with exact counts and fields.
+
+mod support;
+
+use serde_json::Value;
+use support::fixture::{FixtureDb, ALICE, BOB, CAROL, GROUP_ID, GROUP_NAME, MESSAGE_COUNT, UNREAD_COUNT};
+
+fn texts(value: &Value) -> Vec<&str> {
+    value.as_array().unwrap().iter().map(|m| m["text"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn test_recent_and_unread() {
+    let db = FixtureDb::create();
+
+    let recent = db.json(&["recent", "--limit", "50"]);
+    let rows = recent.as_array().unwrap();
+    assert_eq!(rows.len(), MESSAGE_COUNT);
+    assert_eq!(texts(&recent)[..3], ["See you all there", "Lunch tomorrow?", "Yes, what a finish"]);
+    assert_eq!(rows[0]["group_id"], GROUP_ID);
+    assert_eq!(rows[0]["is_group_chat"], true);
+    assert_eq!(rows[0]["contact_name"], "Bob Baker");
+    // Blob-only text is decoded; the photo has a placeholder instead of text
+    assert!(texts(&recent).contains(&"Running late, order dinner without me"));
+    let photo = rows.iter().find(|m| m["placeholder"] == "[photo]").unwrap();
+    assert_eq!((photo["text"].as_str(), photo["is_from_me"].as_bool()), (Some(""), Some(true)));
+
+    // Alice and Carol are friends, Bob is work
+    let recent = db.json(&["recent", "--limit", "3", "--relationship", "friend"]);
+    assert_eq!(
+        texts(&recent),
+        ["Bringing dinner supplies", "Can you send me the address?", "Who's driving on Saturday?"]
+    );
+
+    let unread = db.json(&["unread"]);
+    assert_eq!(unread.as_array().unwrap().len(), UNREAD_COUNT);
+    assert_eq!(texts(&unread), ["See you all there", "Lunch tomorrow?", "Yes, what a finish"]);
+    assert!(unread.as_array().unwrap().iter().all(|m| m["phone"] == BOB && m["is_from_me"] == false));
+
+    assert_eq!(db.json(&["unread", "--count-only"]), serde_json::json!({ "unread_count": UNREAD_COUNT }));
+}
+
+#[test]
+fn test_find_and_text_search() {
+    let db = FixtureDb::create();
+
+    // Alice's 1:1 thread (tapback included) plus her message in the group
+    let found = db.json(&["find", "Alice"]);
+    let rows = found.as_array().unwrap();
+    assert_eq!(rows.len(), 7);
+    assert!(rows.iter().all(|m| m["phone"] == ALICE && m["contact_name"] == "Alice Archer"));
+    assert_eq!(rows.iter().filter(|m| m["is_group_chat"] == true).count(), 1);
+    assert_eq!(rows.last().unwrap()["text"], "Are we still on for dinner Friday?");
+
+    let found = db.json(&["find", "Alice", "--query", "ADDRESS"]);
+    assert_eq!(texts(&found), ["Can you send me the address?"]);
+
+    // The blob-only message matches too
+    let hits = db.json(&["text-search", "dinner"]);
+    assert_eq!(
+        texts(&hits),
+        ["Bringing dinner supplies", "Running late, order dinner without me", "Are we still on for dinner Friday?"]
+    );
+    assert!(hits.as_array().unwrap().iter().all(|h| h["matched_terms"] == serde_json::json!(["dinner"])));
+
+    let hits = db.json(&["text-search", "driving", "--group-id", GROUP_ID]);
+    assert_eq!(texts(&hits), ["Who's driving on Saturday?"]);
+    assert_eq!(hits[0]["phone"], CAROL);
+}
+
+#[test]
+fn test_analytics_and_groups() {
+    let db = FixtureDb::create();
+
+    // Totals leave out the tapback, which is counted separately
+    let stats = db.json(&["analytics"]);
+    assert_eq!(stats["total_messages"], 12);
+    assert_eq!((stats["sent_count"].as_i64(), stats["received_count"].as_i64()), (Some(4), Some(8)));
+    assert_eq!((stats["reaction_count"].as_i64(), stats["attachment_count"].as_i64()), (Some(1), Some(1)));
+    assert_eq!(stats["analysis_period_days"], 30);
+    let top: Vec<(&str, i64)> = stats["top_contacts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| (c["phone"].as_str().unwrap(), c["message_count"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(top, [(ALICE, 6), (BOB, 4), (CAROL, 1)]);
+
+    // Her handle in both chats: five 1:1 messages (without the tapback) and one in the group
+    let alice = db.json(&["analytics", "Alice Archer"]);
+    assert_eq!(alice["total_messages"], 6);
+    let heatmap = db.json(&["analytics", "--heatmap", "--days", "7"]);
+    assert_eq!(heatmap["analysis_period_days"], 7);
+
+    let groups = db.json(&["groups"]);
+    let groups = groups.as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["group_id"], GROUP_ID);
+    assert_eq!(groups[0]["display_name"], GROUP_NAME);
+    assert_eq!(groups[0]["message_count"], 4);
+    assert_eq!(groups[0]["participants"], serde_json::json!([ALICE, BOB, CAROL]));
+}
+
+#[test]
+fn test_followup() {
+    let db = FixtureDb::create();
+
+    let report = db.json(&["followup"]);
+    let questions: Vec<(&str, &str)> = report["unanswered_questions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|q| (q["phone"].as_str().unwrap(), q["text"].as_str().unwrap()))
+        .collect();
+    assert_eq!(questions, [(BOB, "Lunch tomorrow?"), (ALICE, "Can you send me the address?")]);
+    assert_eq!(report["unanswered_questions"][1]["question_score"], 1.0);
+    assert_eq!(report["unanswered_questions"][1]["days_ago"], 3);
+
+    let stale = report["stale_conversations"].as_array().unwrap();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0]["phone"], CAROL);
+    assert_eq!(report["total_items"], 3);
+
+    // The group's question got a reply, so including groups adds nothing
+    let report = db.json(&["followup", "--include-groups"]);
+    let texts: Vec<&str> =
+        report["unanswered_questions"].as_array().unwrap().iter().map(|q| q["text"].as_str().unwrap()).collect();
+    assert_eq!(texts, ["Lunch tomorrow?", "Can you send me the address?"]);
+}
+
+#[test]
+fn test_summary_cursor_excludes_offset_and_keeps_order() {
+    let db = FixtureDb::create();
+    let first = db.json(&["summary", "Alice Archer", "--limit", "2"]);
+    let cursor = first["meta"]["next_cursor"].as_str().unwrap().to_string();
+    let next = db.json(&["summary", "Alice Archer", "--limit", "2", "--cursor", &cursor]);
+    assert_ne!(next["messages"][0]["rowid"], first["messages"][0]["rowid"]);
+
+    for extra in [&["--offset", "1"][..], &["--order", "desc"][..]] {
+        let mut args = vec!["summary", "Alice Archer", "--limit", "2", "--cursor", &cursor];
+        args.extend_from_slice(extra);
+        let output = db.command(&args).output().unwrap();
+        assert!(!output.status.success(), "{:?}", extra);
+    }
+}
+
+#[test]
+fn test_deleted_days_must_be_1_to_30() {
+    let db = FixtureDb::create();
+    assert!(db.json(&["deleted", "--days", "30"]).is_array());
+    for days in ["0", "31"] {
+        let output = db.command(&["deleted", "--days", days]).output().unwrap();
+        assert!(!output.status.success(), "--days {}", days);
+    }
+}
+
+#[test]
+fn test_compare_show_messages_lists_each_side() {
+    let db = FixtureDb::create();
+    let other = db.dir.path().join("other.db");
+    std::fs::copy(&db.path, &other).unwrap();
+    let conn = rusqlite::Connection::open(&other).unwrap();
+    conn.execute("DELETE FROM message WHERE text = 'Lunch tomorrow?'", []).unwrap();
+    drop(conn);
+    let other = other.to_str().unwrap();
+
+    let report = db.json(&["compare", "--other", other, "--show-messages"]);
+    assert_eq!(report["only_in_primary"]["message_count"], 1);
+    assert_eq!(texts(&report["only_in_primary"]["messages"]), ["Lunch tomorrow?"]);
+    assert_eq!(report["only_in_other"]["messages"], Value::Array(vec![]));
+
+    let output = db.command(&["compare", "--other", other, "--show-messages"]).output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Lunch tomorrow?"));
+}
+
+#[test]
+fn test_csv_of_non_list_output_fails() {
+    let db = FixtureDb::create();
+    let output = db.command(&["analytics", "--csv"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--csv needs list output"));
+
+    let output = db.command(&["recent", "--csv", "--fields", "text"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_self_formatted_json_honours_output_controls() {
+    // group-messages prints through print_json, not print
+    let db = FixtureDb::create();
+    let messages = db.json(&["group-messages", "--group-id", GROUP_ID, "--fields", "text", "--max-text-chars", "4"]);
+    let rows = messages.as_array().unwrap();
+    assert!(!rows.is_empty());
+    for row in rows {
+        let row = row.as_object().unwrap();
+        assert_eq!(row.keys().collect::<Vec<_>>(), ["text"]);
+        assert!(row["text"].as_str().unwrap().chars().count() <= 7, "{:?}", row);
+    }
+    assert!(texts(&messages).iter().any(|t| t.ends_with("...")));
+}
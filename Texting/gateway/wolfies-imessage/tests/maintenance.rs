@@ -0,0 +1,86 @@
+//! `maintenance status` and `maintenance prune` against a private data
+//! directory: prune removes only the selected safe subsets, and backups and
+//! pending sends need `--all --force`.
+
+mod support;
+
+use serde_json::Value;
+use std::path::Path;
+use support::fixture::FixtureDb;
+
+fn names(entries: &Value) -> Vec<&str> {
+    entries.as_array().unwrap().iter().map(|e| e["name"].as_str().unwrap()).collect()
+}
+
+fn populate(home: &Path) {
+    std::fs::create_dir_all(home.join("contacts-backups")).unwrap();
+    for name in [
+        "config.toml",
+        "mutes.json",
+        "drafts.json",
+        "scheduled.json",
+        "textcache.db",
+        "textcache.db-wal",
+        "daemon.log",
+        "daemon.log.1",
+        "old.sock",
+        "notes.txt",
+        "contacts-backups/contacts-20261001-120000-000.json",
+    ] {
+        std::fs::write(home.join(name), "{}").unwrap();
+    }
+    std::fs::write(home.join("old.sock.pid"), "999999999\n").unwrap();
+}
+
+#[test]
+fn test_status_lists_every_file() {
+    let db = FixtureDb::create();
+    let home = db.dir.path().join("home");
+    populate(&home);
+
+    let status = db.json(&["maintenance", "status"]);
+    assert_eq!(status["exists"], true);
+    let entries = &status["entries"];
+    assert_eq!(names(entries).len(), 12);
+    let class_of = |name: &str| {
+        entries.as_array().unwrap().iter().find(|e| e["name"] == name).unwrap()["class"].clone()
+    };
+    assert_eq!(class_of("contacts-backups"), "backup");
+    assert_eq!(class_of("drafts.json"), "pending");
+    assert_eq!(class_of("textcache.db-wal"), "cache");
+    assert_eq!(class_of("daemon.log"), "log");
+    assert_eq!(class_of("daemon.log.1"), "rotated_log");
+    assert_eq!(class_of("old.sock"), "runtime");
+    assert_eq!(class_of("notes.txt"), "unknown");
+}
+
+#[test]
+fn test_prune_subsets_and_force() {
+    let db = FixtureDb::create();
+    let home = db.dir.path().join("home");
+    populate(&home);
+
+    let caches = db.json(&["maintenance", "prune", "--caches"]);
+    assert_eq!(names(&caches["removed"]), ["textcache.db", "textcache.db-wal"]);
+    assert!(home.join("daemon.log.1").exists());
+
+    let all = db.json(&["maintenance", "prune", "--all"]);
+    assert_eq!(names(&all["removed"]), ["daemon.log.1", "old.sock", "old.sock.pid"]);
+    assert_eq!(names(&all["skipped"]), ["contacts-backups", "drafts.json", "scheduled.json"]);
+    assert_eq!(all["skipped"][0]["reason"], "needs --force");
+    for kept in ["config.toml", "mutes.json", "daemon.log", "notes.txt", "drafts.json"] {
+        assert!(home.join(kept).exists(), "{}", kept);
+    }
+
+    let forced = db.json(&["maintenance", "prune", "--all", "--force"]);
+    assert_eq!(names(&forced["removed"]), ["contacts-backups", "drafts.json", "scheduled.json"]);
+    let left = db.json(&["maintenance", "status"]);
+    assert_eq!(names(&left["entries"]), ["config.toml", "daemon.log", "mutes.json", "notes.txt"]);
+
+    // --force only with --all; a selection is required; bad ages are rejected
+    for args in [&["--force"][..], &[][..], &["--logs", "--older-than", "soon"][..]] {
+        let mut command = db.command(&["maintenance", "prune"]);
+        let output = command.args(args).output().unwrap();
+        assert!(!output.status.success(), "{:?}", args);
+    }
+}
@@ -0,0 +1,46 @@
+//! `mark-read` against the fixture with a fake `osascript`: chats are
+//! addressed by their chat id (group chats included), and each one that
+//! fails, or isn't read afterwards in chat.db, is named in the error.
+
+mod support;
+
+use support::fixture::{FixtureDb, ALICE, BOB, GROUP_ID};
+use support::osascript::{fake_osascript, osascript_calls};
+
+#[test]
+fn test_mark_read_opens_chat_by_id() {
+    let db = FixtureDb::create();
+    let path = fake_osascript(db.dir.path(), "exit 0");
+
+    // Alice's chat has nothing unread, so it's read as soon as it's opened
+    let report = db.command(&["mark-read", "Alice Archer", "--json"]).env("PATH", &path).output().unwrap();
+    assert!(report.status.success(), "{}", String::from_utf8_lossy(&report.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&report.stdout).unwrap();
+    assert_eq!((report["attempted"].as_u64(), report["failed"].as_u64()), (Some(1), Some(0)));
+    assert_eq!(
+        osascript_calls(db.dir.path()),
+        [format!("iMessage;-;{} imessage://open?addresses={}", ALICE, ALICE)]
+    );
+}
+
+#[test]
+fn test_mark_read_all_reports_each_failed_chat() {
+    let db = FixtureDb::create();
+    // Messages "opens" Bob's chat (which stays unread here) but can't find the group
+    let path = fake_osascript(
+        db.dir.path(),
+        "case \"$1\" in *chat*) echo 'execution error: Chat not found (-2700)' >&2; exit 1;; esac",
+    );
+
+    let output = db.command(&["mark-read", "--all", "--json"]).env("PATH", &path).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 of 2 conversations not marked read"), "{}", stderr);
+    assert!(stderr.contains(&format!("{} (AppleScript failed: execution error: Chat not found", GROUP_ID)), "{}", stderr);
+    assert!(stderr.contains(&format!("{} (opened in Messages, but 2 message(s) are still unread)", BOB)), "{}", stderr);
+
+    // The group went by its chat id and its participants
+    let calls = osascript_calls(db.dir.path());
+    assert!(calls.iter().any(|c| c.starts_with(&format!("iMessage;+;{} imessage://open?addresses=", GROUP_ID))), "{:?}", calls);
+}
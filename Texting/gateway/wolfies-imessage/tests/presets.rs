@@ -0,0 +1,96 @@
+//! `--save-as`, `presets`, and `run`: a saved invocation replays with the
+//! same parameters, and flags after the preset name override them.
+
+mod support;
+
+use serde_json::Value;
+use support::fixture::FixtureDb;
+
+fn results(value: &Value) -> &Vec<Value> {
+    value.as_array().unwrap_or_else(|| panic!("expected a list: {}", value))
+}
+
+#[test]
+fn test_save_list_and_run_with_overrides() {
+    let db = FixtureDb::create();
+
+    let saved = db.command(&["text-search", "dinner", "--limit", "5", "--save-as", "dinner", "--json"]).output().unwrap();
+    assert!(saved.status.success(), "{}", String::from_utf8_lossy(&saved.stderr));
+    assert!(String::from_utf8_lossy(&saved.stderr).contains("Saved preset 'dinner'"));
+    let first: Value = serde_json::from_slice(&saved.stdout).unwrap();
+    assert_eq!(results(&first).len(), 3);
+
+    let presets = db.json(&["presets", "list"]);
+    let preset = &results(&presets)[0];
+    assert_eq!(preset["name"], "dinner");
+    assert_eq!(preset["subcommand"], "text-search");
+    assert_eq!(preset["params"]["query"], "dinner");
+    assert_eq!(preset["params"]["limit"], 5);
+
+    // Same parameters, same output; global flags after the name still apply
+    assert_eq!(db.json(&["run", "dinner"]), first);
+    assert_eq!(results(&db.json(&["run", "dinner", "--limit", "1"])).len(), 1);
+    let lunch = db.json(&["run", "dinner", "lunch"]);
+    assert_eq!(results(&lunch).len(), 1);
+    assert_eq!(results(&lunch)[0]["text"], "Lunch tomorrow?");
+
+    // An override beats the stored flag it conflicts with
+    db.json(&["recent", "--limit", "3", "--known-only", "--save-as", "known"]);
+    assert_eq!(results(&db.json(&["run", "known"])).len(), 3);
+    let unknown = db.json(&["recent", "--limit", "3", "--unknown-only"]);
+    assert_ne!(unknown, db.json(&["run", "known"]));
+    assert_eq!(db.json(&["run", "known", "--unknown-only"]), unknown);
+
+    // Re-saving from a run keeps the overrides
+    db.json(&["run", "dinner", "--limit", "2", "--save-as", "dinner2"]);
+    let presets = db.json(&["presets"]);
+    let names: Vec<&str> = results(&presets).iter().map(|p| p["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["dinner", "dinner2", "known"]);
+    assert_eq!(results(&presets)[1]["params"]["limit"], 2);
+
+    let deleted = db.json(&["presets", "delete", "dinner2"]);
+    assert_eq!(deleted["deleted"], true);
+    assert_eq!(results(&db.json(&["presets", "list"])).len(), 2);
+}
+
+#[test]
+fn test_output_flags_saved_with_the_preset() {
+    let db = FixtureDb::create();
+    let args = ["text-search", "dinner", "--json", "--fields", "text", "--max-text-chars", "6"];
+    let saved = db.command(&[&args[..], &["--save-as", "short"]].concat()).output().unwrap();
+    assert!(saved.status.success(), "{}", String::from_utf8_lossy(&saved.stderr));
+    let first: Value = serde_json::from_slice(&saved.stdout).unwrap();
+    assert!(results(&first).iter().all(|m| m.as_object().unwrap().keys().eq(["text"])));
+
+    // No flags after the name: still JSON, same fields and truncation
+    let output = db.command(&["run", "short"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(serde_json::from_slice::<Value>(&output.stdout).unwrap(), first);
+
+    // Re-saving from a run keeps them; flags after the name still win
+    db.json(&["run", "short", "--fields", "date", "--save-as", "dated"]);
+    let preset = results(&db.json(&["presets", "list"]))[0].clone();
+    assert_eq!(preset["name"], "dated");
+    assert_eq!(preset["globals"]["fields"], "date");
+    assert_eq!(preset["globals"]["max_text_chars"], 6);
+    let output = db.command(&["run", "dated", "--csv"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("date\n"));
+}
+
+#[test]
+fn test_missing_preset_and_bad_overrides_fail() {
+    let db = FixtureDb::create();
+
+    let output = db.command(&["run", "nope"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No preset named 'nope'"));
+
+    let output = db.command(&["presets", "delete", "nope"]).output().unwrap();
+    assert!(!output.status.success());
+
+    db.json(&["recent", "--limit", "2", "--save-as", "two"]);
+    let output = db.command(&["run", "two", "--limit", "many"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("run two: invalid value 'many'"));
+}
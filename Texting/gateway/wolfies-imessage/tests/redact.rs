@@ -0,0 +1,84 @@
+//! `--redact` on every output path: `print` (messages, recent, unread), `print_json`
+//! (handles, unknown, digest), and CSV never show a raw handle or an
+//! unmasked number; text output, which has no redaction pass, is refused.
+
+mod support;
+
+use rusqlite::{params, Connection};
+use support::fixture::{FixtureDb, ALICE, BOB, CAROL};
+use wolfies_imessage::db::queries;
+
+/// A sender who isn't in contacts.json.
+const STRANGER: &str = "+14155550003";
+
+/// The fixture plus an unread message from `STRANGER` quoting a phone number.
+fn fixture_with_stranger() -> FixtureDb {
+    let db = FixtureDb::create();
+    let conn = Connection::open(&db.path).unwrap();
+    conn.execute("INSERT INTO handle (id) VALUES (?1)", params![STRANGER]).unwrap();
+    let handle = conn.last_insert_rowid();
+    conn.execute(
+        "INSERT INTO chat (guid, chat_identifier) VALUES (?1, ?2)",
+        params![format!("iMessage;-;{}", STRANGER), STRANGER],
+    )
+    .unwrap();
+    let chat = conn.last_insert_rowid();
+    conn.execute("INSERT INTO chat_handle_join (chat_id, handle_id) VALUES (?1, ?2)", params![chat, handle]).unwrap();
+    conn.execute(
+        "INSERT INTO message (guid, text, handle_id, date, is_from_me, is_read) VALUES ('stranger-1', ?1, ?2, ?3, 0, 0)",
+        params!["Call me back at 415-555-0199", handle, queries::days_ago_cocoa(0) - 60_000_000_000],
+    )
+    .unwrap();
+    let message = conn.last_insert_rowid();
+    conn.execute("INSERT INTO chat_message_join (chat_id, message_id) VALUES (?1, ?2)", params![chat, message])
+        .unwrap();
+    db
+}
+
+/// stdout of a successful run, checked for raw handles and numbers.
+fn assert_redacted(db: &FixtureDb, args: &[&str]) -> String {
+    let output = db.command(args).arg("--redact").output().unwrap();
+    assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for raw in [ALICE, BOB, CAROL, STRANGER, "14155550", "415-555-0199"] {
+        assert!(!stdout.contains(raw), "{:?} printed {}:\n{}", args, raw, stdout);
+    }
+    assert!(stdout.contains("Contact-"), "{:?} has no pseudonyms:\n{}", args, stdout);
+    stdout
+}
+
+#[test]
+fn test_redact_print_path() {
+    let db = fixture_with_stranger();
+    assert_redacted(&db, &["messages", "Alice Archer", "--json"]);
+    assert_redacted(&db, &["messages", "Alice Archer", "--json", "--envelope"]);
+    // display_name and UnreadChat.name fall back to the stranger's raw handle
+    assert_redacted(&db, &["recent", "--json"]);
+    let unread = assert_redacted(&db, &["unread", "--json"]);
+    assert!(unread.contains("Call me back at ###-###-####"), "{}", unread);
+    assert_redacted(&db, &["unread", "--by-conversation", "--json"]);
+}
+
+#[test]
+fn test_redact_print_json_path() {
+    let db = fixture_with_stranger();
+    assert_redacted(&db, &["handles", "--json"]);
+    let unknown = assert_redacted(&db, &["unknown", "--json"]);
+    assert!(unknown.contains("###-###-####"), "{}", unknown);
+    assert_redacted(&db, &["digest", "--json"]);
+}
+
+#[test]
+fn test_redact_csv_path() {
+    let db = fixture_with_stranger();
+    assert_redacted(&db, &["handles", "--csv"]);
+}
+
+#[test]
+fn test_redact_refuses_text_output() {
+    let db = fixture_with_stranger();
+    let output = db.command(&["recent", "--redact"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--redact applies to JSON and CSV output"));
+}
@@ -0,0 +1,113 @@
+//! Every command in the schema registry, run against the fixture chat.db:
+//! its real `--json` output must validate against `schema <command>`.
+//! Commands that can't run here (daemon, Messages.app, endless streams)
+//! are listed with the reason, so a new command can't go unchecked.
+
+mod support;
+
+use serde_json::Value;
+use support::fixture::{FixtureDb, ALICE, GROUP_ID};
+use wolfies_imessage::commands::schema::{schema_for_command, COMMANDS};
+
+/// How `command` is exercised: CLI args, or why it can't run in a test.
+fn sample(command: &str, db: &FixtureDb) -> Result<Vec<String>, &'static str> {
+    let args: &[&str] = match command {
+        "find" => &["find", "Alice Archer", "--query", "dinner"],
+        "messages" => &["messages", "Alice Archer"],
+        "recent" => &["recent"],
+        "unread" => &["unread"],
+        "quick" => &["quick"],
+        "digest" => &["digest"],
+        "text-search" => &["text-search", "dinner"],
+        "bundle" => &["bundle"],
+        "send" => &["send", "Alice Archer", "hello", "--dry-run"],
+        "send-by-phone" => &["send-by-phone", ALICE, "hello", "--dry-run"],
+        "send-log" => &["send-log"],
+        "contacts" => &["contacts"],
+        "analytics" => &["analytics"],
+        "followup" => &["followup"],
+        "commitments" => &["commitments"],
+        "groups" => &["groups"],
+        "group-analytics" => &["group-analytics", GROUP_ID],
+        "group-history" => &["group-history", GROUP_ID],
+        "group-messages" => &["group-messages", "--group-id", GROUP_ID],
+        "chat-info" => &["chat-info", GROUP_ID],
+        "attachments" => &["attachments"],
+        "reactions" => &["reactions"],
+        "links" => &["links"],
+        "voice" => &["voice"],
+        "deleted" => &["deleted"],
+        "compare" => {
+            let other = db.path.to_str().unwrap();
+            return Ok(["compare", "--other", other, "--show-messages"].map(str::to_string).to_vec());
+        }
+        "thread" => &["thread", "--guid", "fixture-1"],
+        "handles" => &["handles"],
+        "unknown" => &["unknown"],
+        "discover" => &["discover"],
+        "scheduled" => &["scheduled"],
+        "draft" => &["draft", "list"],
+        // In COMMANDS order, so unmute finds the mute and mutes lists none
+        "mute" => &["mute", "Alice Archer"],
+        "unmute" => &["unmute", "Alice Archer"],
+        "mutes" => &["mutes"],
+        "presets" => &["presets", "list"],
+        "cache" => &["cache", "clear"],
+        "maintenance" => &["maintenance", "status"],
+        "account" => &["account"],
+        "doctor" => &["doctor"],
+        "raw-message" => &["raw-message", "--guid", "fixture-1"],
+        "config" => &["config", "show"],
+        "summary" => &["summary", "Alice Archer"],
+        "watch" => return Err("streams until killed"),
+        "mark-read" => return Err("drives Messages.app"),
+        "index" | "search" | "ask" | "stats" | "clear" | "sources" => return Err("needs the daemon"),
+        other => panic!("no schema sample for '{}': add one here", other),
+    };
+    Ok(args.iter().map(|a| a.to_string()).collect())
+}
+
+#[test]
+fn test_every_command_output_matches_its_schema() {
+    let db = FixtureDb::create();
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    for &command in COMMANDS {
+        let Ok(args) = sample(command, &db) else { continue };
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let run = db.command(&args).arg("--json").output().unwrap();
+        if !run.status.success() {
+            failures.push(format!("{}: failed: {}", command, String::from_utf8_lossy(&run.stderr)));
+            continue;
+        }
+        let output: Value = match serde_json::from_slice(&run.stdout) {
+            Ok(output) => output,
+            Err(e) => {
+                failures.push(format!("{}: stdout is not JSON ({})", command, e));
+                continue;
+            }
+        };
+
+        let schema = serde_json::to_value(schema_for_command(command).unwrap()).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let errors: Vec<String> = validator.iter_errors(&output).map(|e| e.to_string()).collect();
+        if !errors.is_empty() {
+            failures.push(format!("{}: violates its schema: {:?}", command, errors));
+        }
+        checked += 1;
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    assert!(checked >= COMMANDS.len() - 8, "only {} of {} commands checked", checked, COMMANDS.len());
+}
+
+#[test]
+fn test_schema_cli_matches_registry() {
+    let db = FixtureDb::create();
+    let all = db.json(&["schema", "--all"]);
+    let listed: Vec<&str> = all.as_object().unwrap().keys().map(String::as_str).collect();
+    let mut expected: Vec<&str> = COMMANDS.to_vec();
+    expected.sort_unstable();
+    assert_eq!(listed, expected);
+    assert_eq!(all["summary"], db.json(&["schema", "summary"]));
+    assert!(matches!(all["find"], Value::Object(_)));
+}
@@ -0,0 +1,88 @@
+//! `send` / `send-by-phone` with `--stdin` and `--message-file`: the message
+//! goes out exactly as given (newlines and spacing kept, one trailing newline
+//! dropped), and option-like words in the positional form are refused.
+//! Every send is a dry run.
+
+mod support;
+
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Output, Stdio};
+use support::fixture::{FixtureDb, ALICE};
+
+fn run_with_stdin(db: &FixtureDb, args: &[&str], input: &str) -> Output {
+    let mut child = db
+        .command(args)
+        .args(["--dry-run", "--json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn sent(output: &Output) -> Value {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_stdin_message_sent_exactly() {
+    let db = FixtureDb::create();
+    let message = "Line one\n\n  indented  twice\n-- signed";
+    let result = sent(&run_with_stdin(&db, &["send-by-phone", ALICE, "--stdin"], &format!("{}\n", message)));
+    assert_eq!(result["dry_run"], true);
+    assert_eq!(result["message"], message);
+    assert_eq!(result["message_bytes"], message.len());
+    assert_eq!(result["message_chars"], message.chars().count());
+
+    let result = sent(&run_with_stdin(&db, &["send", "Alice", "--stdin"], "café ☕"));
+    assert_eq!(result["phone"], ALICE);
+    assert_eq!((result["message_bytes"].as_u64(), result["message_chars"].as_u64()), (Some(9), Some(6)));
+
+    // Nothing but whitespace is not a message
+    assert!(!run_with_stdin(&db, &["send", "Alice", "--stdin"], "\n\n").status.success());
+}
+
+#[test]
+fn test_message_file_trailing_newline() {
+    let db = FixtureDb::create();
+    let path = db.dir.path().join("note.txt");
+    let file = path.to_str().unwrap();
+    for (contents, expected) in [
+        ("See you at 7\n", "See you at 7"),
+        ("See you at 7\r\n", "See you at 7"),
+        ("See you at 7\n\n", "See you at 7\n"),
+        ("See you at 7", "See you at 7"),
+    ] {
+        std::fs::write(&path, contents).unwrap();
+        let result = db.json(&["send", "Alice", "--message-file", file, "--dry-run"]);
+        assert_eq!(result["message"], expected, "{:?}", contents);
+    }
+
+    let missing = db.dir.path().join("missing.txt");
+    let output = db.command(&["send", "Alice", "--message-file", missing.to_str().unwrap(), "--dry-run"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.txt"));
+}
+
+#[test]
+fn test_positional_words_and_misplaced_options() {
+    let db = FixtureDb::create();
+    let result = db.json(&["send", "Alice", "running", "late", "--dry-run"]);
+    assert_eq!(result["message"], "running late");
+
+    // After `--`, an option-like word is refused instead of sent
+    let output = db.command(&["send", "Alice", "--dry-run", "--", "running", "--in", "2h"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("'--in'") && stderr.contains("--stdin"), "{}", stderr);
+
+    // A message is required from exactly one source
+    for args in [&["send", "Alice"][..], &["send", "Alice", "hi", "--stdin"][..]] {
+        let output = db.command(args).arg("--dry-run").output().unwrap();
+        assert!(!output.status.success(), "{:?}", args);
+    }
+}
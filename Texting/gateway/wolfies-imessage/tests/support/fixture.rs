@@ -0,0 +1,210 @@
+//! Small hand-written chat.db for command-level integration tests.
+//!
+//! Where `synthetic_db` generates volume, this fixture is a handful of named
+//! conversations whose every row is known, so tests can assert exact counts
+//! and fields: a 1:1 thread with Alice (a photo, a tapback, a blob-only
+//! message, an unanswered question), a 1:1 thread with Bob ending in two
+//! unread messages, and a three-person group chat with one unread message.
+//! Dates are relative to now, so day-window filters see every row.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - FixtureDb::command (the CLI against the fixture, output unchecked)
+//! - 10/16/2026 - Initial fixture (1:1 threads, group chat, unread, tapback, attachment, blob-only text)
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use tempfile::TempDir;
+use wolfies_imessage::db::queries;
+
+use super::synthetic_db::{attributed_body, SCHEMA};
+
+pub const ALICE: &str = "+14155550101";
+pub const BOB: &str = "+14155550102";
+pub const CAROL: &str = "carol@example.com";
+pub const GROUP_ID: &str = "chat900001";
+pub const GROUP_NAME: &str = "Weekend Trip";
+
+/// Every message row (tapback included).
+pub const MESSAGE_COUNT: usize = 13;
+/// Received, unread rows: two from Bob, one in the group.
+pub const UNREAD_COUNT: usize = 3;
+
+const HOUR_NS: i64 = 3_600 * 1_000_000_000;
+
+/// Handle ROWIDs, in insertion order.
+const ALICE_ID: i64 = 1;
+const BOB_ID: i64 = 2;
+const CAROL_ID: i64 = 3;
+
+/// Chat ROWIDs, in insertion order.
+const ALICE_CHAT: i64 = 1;
+const BOB_CHAT: i64 = 2;
+const GROUP_CHAT: i64 = 3;
+
+/// One message row: chat, sender handle (0 for me in a group), from me,
+/// text, hours ago, read, and what kind of row it is.
+struct Row {
+    chat: i64,
+    handle: i64,
+    from_me: bool,
+    text: &'static str,
+    hours_ago: i64,
+    read: bool,
+    kind: Kind,
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    Text,
+    /// NULL `text`; only the attributedBody blob carries it
+    BlobOnly,
+    /// An image attachment (text is the object replacement character)
+    Photo,
+    /// Loved tapback on the previous message
+    Tapback,
+}
+
+const ROWS: &[Row] = &[
+    Row { chat: ALICE_CHAT, handle: ALICE_ID, from_me: false, text: "Are we still on for dinner Friday?", hours_ago: 6 * 24, read: true, kind: Kind::Text },
+    Row { chat: ALICE_CHAT, handle: ALICE_ID, from_me: true, text: "Yes! 7pm at the usual place", hours_ago: 6 * 24 - 1, read: true, kind: Kind::Text },
+    Row { chat: ALICE_CHAT, handle: ALICE_ID, from_me: true, text: "\u{fffc}", hours_ago: 5 * 24, read: true, kind: Kind::Photo },
+    Row { chat: ALICE_CHAT, handle: ALICE_ID, from_me: false, text: "Loved an image", hours_ago: 5 * 24 - 1, read: true, kind: Kind::Tapback },
+    Row { chat: ALICE_CHAT, handle: ALICE_ID, from_me: false, text: "Running late, order dinner without me", hours_ago: 4 * 24, read: true, kind: Kind::BlobOnly },
+    Row { chat: ALICE_CHAT, handle: ALICE_ID, from_me: false, text: "Can you send me the address?", hours_ago: 3 * 24, read: true, kind: Kind::Text },
+    Row { chat: BOB_CHAT, handle: BOB_ID, from_me: true, text: "Did you see the game last night?", hours_ago: 2 * 24, read: true, kind: Kind::Text },
+    Row { chat: BOB_CHAT, handle: BOB_ID, from_me: false, text: "Yes, what a finish", hours_ago: 24, read: false, kind: Kind::Text },
+    Row { chat: BOB_CHAT, handle: BOB_ID, from_me: false, text: "Lunch tomorrow?", hours_ago: 12, read: false, kind: Kind::Text },
+    Row { chat: GROUP_CHAT, handle: CAROL_ID, from_me: false, text: "Who's driving on Saturday?", hours_ago: 3 * 24 + 2, read: true, kind: Kind::Text },
+    Row { chat: GROUP_CHAT, handle: 0, from_me: true, text: "I can drive", hours_ago: 3 * 24 - 1, read: true, kind: Kind::Text },
+    Row { chat: GROUP_CHAT, handle: ALICE_ID, from_me: false, text: "Bringing dinner supplies", hours_ago: 2 * 24, read: true, kind: Kind::Text },
+    Row { chat: GROUP_CHAT, handle: BOB_ID, from_me: false, text: "See you all there", hours_ago: 1, read: false, kind: Kind::Text },
+];
+
+/// The fixture database and a contacts.json naming Alice, Bob, and Carol,
+/// in their own temp directory (removed on drop).
+pub struct FixtureDb {
+    pub dir: TempDir,
+    pub path: PathBuf,
+    pub contacts_path: PathBuf,
+}
+
+impl FixtureDb {
+    pub fn create() -> Self {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("chat.db");
+        generate(&path).expect("generate fixture chat.db");
+        let contacts_path = dir.path().join("contacts.json");
+        let contacts = serde_json::json!({"contacts": [
+            {"name": "Alice Archer", "phone": ALICE, "relationship_type": "friend"},
+            {"name": "Bob Baker", "phone": BOB, "relationship_type": "work"},
+            {"name": "Carol Chen", "phone": CAROL, "relationship_type": "friend"},
+        ]});
+        std::fs::write(&contacts_path, contacts.to_string()).expect("write contacts.json");
+        Self { dir, path, contacts_path }
+    }
+
+    /// The CLI against the fixture with a private data directory and no
+    /// cache, ready for more args.
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_wolfies-imessage"));
+        command
+            .args(args)
+            .arg("--no-cache")
+            .arg("--db-path")
+            .arg(&self.path)
+            .env("IMESSAGE_CONTACTS_PATH", &self.contacts_path)
+            .env("WOLFIES_IMESSAGE_HOME", self.dir.path().join("home"))
+            .env("WOLFIES_CONFIG", self.dir.path().join("config.toml"));
+        command
+    }
+
+    /// Run the CLI against the fixture with `--json`; returns stdout as JSON.
+    pub fn json(&self, args: &[&str]) -> Value {
+        let output = self.command(args).arg("--json").output().expect("run wolfies-imessage");
+        assert!(
+            output.status.success(),
+            "{:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice(&output.stdout)
+            .unwrap_or_else(|e| panic!("stdout is not JSON ({}): {}", e, String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Write the fixture chat.db to `path`.
+pub fn generate(path: &Path) -> rusqlite::Result<()> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    for handle in [ALICE, BOB, CAROL] {
+        conn.execute("INSERT INTO handle (id) VALUES (?1)", params![handle])?;
+    }
+    for (identifier, display_name, members) in [
+        (ALICE, None, vec![ALICE_ID]),
+        (BOB, None, vec![BOB_ID]),
+        (GROUP_ID, Some(GROUP_NAME), vec![ALICE_ID, BOB_ID, CAROL_ID]),
+    ] {
+        let style = if display_name.is_some() { '+' } else { '-' };
+        conn.execute(
+            "INSERT INTO chat (guid, chat_identifier, display_name) VALUES (?1, ?2, ?3)",
+            params![format!("iMessage;{};{}", style, identifier), identifier, display_name],
+        )?;
+        let chat_id = conn.last_insert_rowid();
+        for handle_id in members {
+            conn.execute(
+                "INSERT INTO chat_handle_join (chat_id, handle_id) VALUES (?1, ?2)",
+                params![chat_id, handle_id],
+            )?;
+        }
+    }
+
+    let now = queries::days_ago_cocoa(0);
+    for (i, row) in ROWS.iter().enumerate() {
+        let rowid = i as i64 + 1;
+        let date = now - row.hours_ago * HOUR_NS;
+        let tapback = row.kind == Kind::Tapback;
+        conn.execute(
+            "INSERT INTO message (ROWID, guid, text, attributedBody, handle_id, date, date_read,
+                                  date_delivered, is_from_me, is_read, cache_roomnames,
+                                  cache_has_attachments, associated_message_guid,
+                                  associated_message_type)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                rowid,
+                format!("fixture-{}", rowid),
+                (row.kind != Kind::BlobOnly).then_some(row.text),
+                attributed_body(row.text),
+                row.handle,
+                date,
+                if row.read { date } else { 0 },
+                date,
+                row.from_me,
+                row.read || row.from_me,
+                (row.chat == GROUP_CHAT).then_some(GROUP_ID),
+                row.kind == Kind::Photo,
+                tapback.then(|| format!("p:0/fixture-{}", rowid - 1)),
+                if tapback { 2000 } else { 0 },
+            ],
+        )?;
+        conn.execute(
+            "INSERT INTO chat_message_join (chat_id, message_id) VALUES (?1, ?2)",
+            params![row.chat, rowid],
+        )?;
+        if row.kind == Kind::Photo {
+            conn.execute(
+                "INSERT INTO attachment (guid, filename, mime_type, total_bytes, transfer_name)
+                 VALUES (?1, '~/Library/Messages/Attachments/00/IMG_0001.jpeg', 'image/jpeg', 123456, 'IMG_0001.jpeg')",
+                params![format!("att-{}", rowid)],
+            )?;
+            conn.execute(
+                "INSERT INTO message_attachment_join (message_id, attachment_id) VALUES (?1, ?2)",
+                params![rowid, conn.last_insert_rowid()],
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,15 @@
+//! Shared helpers for integration tests and benches.
+//!
+//! Benches pull this in with `#[path = "../tests/support/mod.rs"]`, so not
+//! every helper is used by every target.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - Added osascript (fake osascript on PATH for Messages.app commands)
+//! - 10/16/2026 - Added fixture (small hand-written chat.db for command tests)
+//! - 10/16/2026 - Initial support module (synthetic chat.db generator)
+
+#![allow(dead_code)]
+
+pub mod fixture;
+pub mod osascript;
+pub mod synthetic_db;
@@ -0,0 +1,32 @@
+//! A stand-in `osascript`, so tests of commands that drive Messages.app
+//! never reach the real one (or depend on it being missing).
+
+use std::path::{Path, PathBuf};
+
+/// Install a fake `osascript` under `dir`: it appends the arguments after its
+/// `-e <script>` to `dir/osascript.log`, then runs `body` (POSIX sh, with
+/// those arguments as `$1`, `$2`, ...). Returns the directory to use as PATH.
+pub fn fake_osascript(dir: &Path, body: &str) -> PathBuf {
+    let bin = dir.join("fake-bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let script = format!(
+        "#!/bin/sh\nshift 2\necho \"$@\" >> '{}'\n{}\n",
+        dir.join("osascript.log").display(),
+        body
+    );
+    let path = bin.join("osascript");
+    std::fs::write(&path, script).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    bin
+}
+
+/// What the fake `osascript` under `dir` was called with, one call per line.
+pub fn osascript_calls(dir: &Path) -> Vec<String> {
+    std::fs::read_to_string(dir.join("osascript.log"))
+        .map(|log| log.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
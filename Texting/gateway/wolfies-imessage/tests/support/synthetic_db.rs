@@ -0,0 +1,391 @@
+//! Synthetic chat.db generator for benches and integration tests.
+//!
+//! Writes an on-disk database with the chat.db tables and indexes the crate
+//! queries, filled with deterministic (seeded) traffic: 1:1 and group chats,
+//! unread messages, tapbacks, attachments, and an `attributedBody` blob on
+//! every message. Like a macOS 13+ database, some messages have only the
+//! blob and a NULL `text` column, so blob decoding is on the measured path.
+//! A few rows are system items with neither: group renames in group chats,
+//! FaceTime calls in 1:1 chats.
+//!
+//! CHANGELOG:
+//! - 10/16/2026 - System items (system_every): group renames and FaceTime calls
+//! - 10/16/2026 - Initial generator (configurable row counts, typedstream blobs)
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+use tempfile::TempDir;
+use wolfies_imessage::db::queries;
+
+/// Word several phrases contain (case-insensitively); a realistic text_search query.
+pub const COMMON_TERM: &str = "dinner";
+
+/// chat.db tables used by the crate, plus the indexes Messages creates on them.
+pub const SCHEMA: &str = r#"
+CREATE TABLE handle (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    service TEXT DEFAULT 'iMessage',
+    person_centric_id TEXT
+);
+CREATE TABLE chat (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    guid TEXT,
+    chat_identifier TEXT,
+    display_name TEXT,
+    service_name TEXT DEFAULT 'iMessage',
+    account_login TEXT
+);
+CREATE TABLE message (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    guid TEXT UNIQUE,
+    text TEXT,
+    attributedBody BLOB,
+    handle_id INTEGER DEFAULT 0,
+    date INTEGER DEFAULT 0,
+    date_read INTEGER DEFAULT 0,
+    date_delivered INTEGER DEFAULT 0,
+    is_from_me INTEGER DEFAULT 0,
+    is_read INTEGER DEFAULT 0,
+    cache_roomnames TEXT,
+    cache_has_attachments INTEGER DEFAULT 0,
+    associated_message_guid TEXT,
+    associated_message_type INTEGER DEFAULT 0,
+    thread_originator_guid TEXT,
+    item_type INTEGER DEFAULT 0,
+    group_action_type INTEGER DEFAULT 0,
+    other_handle INTEGER DEFAULT 0,
+    group_title TEXT,
+    balloon_bundle_id TEXT,
+    subject TEXT,
+    service TEXT DEFAULT 'iMessage'
+);
+CREATE TABLE chat_message_join (
+    chat_id INTEGER,
+    message_id INTEGER,
+    PRIMARY KEY (chat_id, message_id)
+);
+CREATE TABLE chat_recoverable_message_join (
+    chat_id INTEGER,
+    message_id INTEGER,
+    delete_date INTEGER,
+    PRIMARY KEY (chat_id, message_id)
+);
+CREATE TABLE chat_handle_join (
+    chat_id INTEGER,
+    handle_id INTEGER,
+    UNIQUE (chat_id, handle_id)
+);
+CREATE TABLE attachment (
+    ROWID INTEGER PRIMARY KEY AUTOINCREMENT,
+    guid TEXT,
+    filename TEXT,
+    mime_type TEXT,
+    total_bytes INTEGER DEFAULT 0,
+    transfer_name TEXT
+);
+CREATE TABLE message_attachment_join (
+    message_id INTEGER,
+    attachment_id INTEGER,
+    UNIQUE (message_id, attachment_id)
+);
+CREATE INDEX message_idx_date ON message(date);
+CREATE INDEX message_idx_handle ON message(handle_id, date);
+CREATE INDEX message_idx_is_read ON message(is_read, is_from_me, item_type);
+CREATE INDEX message_idx_associated_message ON message(associated_message_guid);
+CREATE INDEX chat_message_join_idx_message_id_only ON chat_message_join(message_id);
+CREATE INDEX chat_handle_join_idx_handle_id ON chat_handle_join(handle_id);
+CREATE INDEX message_attachment_join_idx_message_id ON message_attachment_join(message_id);
+"#;
+
+/// Everyday phrases; a few mention `COMMON_TERM`.
+const PHRASES: &[&str] = &[
+    "On my way, see you in 10",
+    "Are we still on for dinner tonight?",
+    "Can you send me the address?",
+    "Running a few minutes late, sorry!",
+    "Sounds good to me",
+    "Did you see the game last night?",
+    "Let's grab dinner this weekend",
+    "Happy birthday!! Hope it's a great one",
+    "Call me when you get a chance",
+    "Just landed, heading to baggage claim",
+    "Thanks so much, really appreciate it",
+    "What time works for you tomorrow?",
+    "I'll pick up groceries on the way home",
+    "The meeting got moved to 3pm",
+    "Dinner was amazing, we should go back",
+    "lol",
+    "Can you check the shared doc when you have a sec?",
+    "Here's the link: https://example.com/plans",
+    "Good morning!",
+    "Let me know if you need anything",
+];
+
+/// Row counts and mix for a generated database.
+#[derive(Debug, Clone)]
+pub struct SyntheticConfig {
+    /// People with a 1:1 conversation (one handle each).
+    pub contacts: usize,
+    /// Group chats of three to six contacts.
+    pub groups: usize,
+    /// Messages, spread evenly over `days` up to now.
+    pub messages: usize,
+    pub days: u32,
+    /// Every Nth message has a NULL `text` column (blob only).
+    pub blob_only_every: usize,
+    /// Every Nth received message is unread.
+    pub unread_every: usize,
+    /// Every Nth message is a tapback on the previous one.
+    pub reaction_every: usize,
+    /// Every Nth message carries an image attachment.
+    pub attachment_every: usize,
+    /// Every Nth message is a system item: a rename in a group chat, else a
+    /// FaceTime call (no text or blob).
+    pub system_every: usize,
+    pub seed: u64,
+}
+
+impl Default for SyntheticConfig {
+    /// Roughly a year of a moderately busy account.
+    fn default() -> Self {
+        Self {
+            contacts: 200,
+            groups: 20,
+            messages: 50_000,
+            days: 365,
+            blob_only_every: 3,
+            unread_every: 40,
+            reaction_every: 15,
+            attachment_every: 25,
+            system_every: 97,
+            seed: 0x5eed,
+        }
+    }
+}
+
+impl SyntheticConfig {
+    /// A few hundred rows; fast enough for every integration test to build its own.
+    pub fn small() -> Self {
+        Self {
+            contacts: 10,
+            groups: 2,
+            messages: 500,
+            days: 30,
+            ..Default::default()
+        }
+    }
+}
+
+/// A generated database in its own temp directory (removed on drop).
+pub struct SyntheticDb {
+    pub dir: TempDir,
+    pub path: PathBuf,
+    pub config: SyntheticConfig,
+}
+
+impl SyntheticDb {
+    /// Generate `config` into a fresh temp directory.
+    pub fn create(config: SyntheticConfig) -> Self {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("chat.db");
+        generate(&path, &config).expect("generate synthetic chat.db");
+        Self { dir, path, config }
+    }
+
+    /// Handle of the `index`th contact.
+    pub fn phone(&self, index: usize) -> String {
+        contact_phone(index)
+    }
+}
+
+/// Handle of the `index`th generated contact.
+pub fn contact_phone(index: usize) -> String {
+    format!("+1415555{:04}", index)
+}
+
+/// Write a synthetic chat.db to `path`.
+pub fn generate(path: &Path, config: &SyntheticConfig) -> rusqlite::Result<()> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    let mut rng = SplitMix64(config.seed);
+
+    // Handle and 1:1 chat ROWIDs both equal contact index + 1
+    for i in 0..config.contacts {
+        let phone = contact_phone(i);
+        tx.execute("INSERT INTO handle (id) VALUES (?1)", params![phone])?;
+        tx.execute(
+            "INSERT INTO chat (guid, chat_identifier) VALUES (?1, ?2)",
+            params![format!("iMessage;-;{}", phone), phone],
+        )?;
+        tx.execute(
+            "INSERT INTO chat_handle_join (chat_id, handle_id) VALUES (?1, ?1)",
+            params![i as i64 + 1],
+        )?;
+    }
+
+    let mut group_members = Vec::with_capacity(config.groups);
+    for g in 0..config.groups {
+        let identifier = format!("chat{}", 100_000 + g);
+        tx.execute(
+            "INSERT INTO chat (guid, chat_identifier, display_name) VALUES (?1, ?2, ?3)",
+            params![format!("iMessage;+;{}", identifier), identifier, format!("Group {}", g + 1)],
+        )?;
+        let chat_id = tx.last_insert_rowid();
+        let size = 3 + rng.below(4);
+        let members: Vec<i64> = (0..size).map(|_| 1 + rng.below(config.contacts) as i64).collect();
+        for handle_id in &members {
+            tx.execute(
+                "INSERT OR IGNORE INTO chat_handle_join (chat_id, handle_id) VALUES (?1, ?2)",
+                params![chat_id, handle_id],
+            )?;
+        }
+        group_members.push((chat_id, identifier, members));
+    }
+
+    let now = queries::days_ago_cocoa(0);
+    let start = queries::days_ago_cocoa(config.days);
+    let step = (now - start) / config.messages.max(1) as i64;
+
+    {
+        let mut insert_message = tx.prepare(
+            "INSERT INTO message (ROWID, guid, text, attributedBody, handle_id, date, date_read,
+                                  date_delivered, is_from_me, is_read, cache_roomnames,
+                                  cache_has_attachments, associated_message_guid,
+                                  associated_message_type, item_type, group_title)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        )?;
+        let mut insert_join =
+            tx.prepare("INSERT INTO chat_message_join (chat_id, message_id) VALUES (?1, ?2)")?;
+        let mut insert_attachment = tx.prepare(
+            "INSERT INTO attachment (guid, filename, mime_type, total_bytes, transfer_name)
+             VALUES (?1, ?2, 'image/jpeg', ?3, ?4)",
+        )?;
+        let mut insert_attachment_join = tx.prepare(
+            "INSERT INTO message_attachment_join (message_id, attachment_id) VALUES (?1, ?2)",
+        )?;
+
+        for n in 1..=config.messages {
+            let rowid = n as i64;
+            let date = start + step * (n as i64 - 1) + rng.below(step.max(1) as usize) as i64;
+            let is_from_me = rng.below(10) < 4;
+
+            // Four in five messages are 1:1
+            let (chat_id, handle_id, roomname) = if group_members.is_empty() || rng.below(5) > 0 {
+                let handle = 1 + rng.below(config.contacts) as i64;
+                (handle, handle, None)
+            } else {
+                let (chat_id, identifier, members) = &group_members[rng.below(group_members.len())];
+                let sender = if is_from_me { 0 } else { members[rng.below(members.len())] };
+                (*chat_id, sender, Some(identifier.clone()))
+            };
+
+            let is_reaction = n > 1 && n % config.reaction_every == 0;
+            let has_attachment = !is_reaction && n % config.attachment_every == 0;
+            let is_system = !is_reaction && !has_attachment && n % config.system_every == 0;
+            let text = if is_reaction {
+                format!("Loved \u{201c}{}\u{201d}", PHRASES[rng.below(PHRASES.len())])
+            } else if has_attachment {
+                "\u{fffc}".to_string()
+            } else if rng.below(50) == 0 {
+                // Occasional long message (blob lengths past one byte)
+                (0..6).map(|_| PHRASES[rng.below(PHRASES.len())]).collect::<Vec<_>>().join(". ")
+            } else {
+                PHRASES[rng.below(PHRASES.len())].to_string()
+            };
+            let is_read = is_from_me || n % config.unread_every != 0;
+            // Renames in groups, FaceTime calls in 1:1 chats
+            let (item_type, group_title) = match (&roomname, is_system) {
+                (Some(identifier), true) => (2, Some(format!("{} (renamed)", identifier))),
+                (None, true) => (6, None),
+                (_, false) => (0, None),
+            };
+
+            insert_message.execute(params![
+                rowid,
+                format!("msg-{}", rowid),
+                (!is_system && n % config.blob_only_every != 0).then_some(&text),
+                (!is_system).then(|| attributed_body(&text)),
+                handle_id,
+                date,
+                if is_read { date } else { 0 },
+                date,
+                is_from_me,
+                is_read,
+                roomname,
+                has_attachment,
+                is_reaction.then(|| format!("p:0/msg-{}", rowid - 1)),
+                if is_reaction { 2000 } else { 0 },
+                item_type,
+                group_title,
+            ])?;
+            insert_join.execute(params![chat_id, rowid])?;
+
+            if has_attachment {
+                let name = format!("IMG_{:04}.jpeg", n);
+                insert_attachment.execute(params![
+                    format!("att-{}", rowid),
+                    format!("~/Library/Messages/Attachments/{:02x}/{}", n % 256, name),
+                    100_000 + rng.below(4_000_000) as i64,
+                    name,
+                ])?;
+                insert_attachment_join.execute(params![rowid, tx.last_insert_rowid()])?;
+            }
+        }
+    }
+
+    tx.commit()?;
+    conn.execute_batch("ANALYZE")?;
+    Ok(())
+}
+
+/// Archived NSMutableAttributedString for `text`, shaped like the blobs
+/// Messages writes for plain (single-run) text.
+pub fn attributed_body(text: &str) -> Vec<u8> {
+    const HEADER: &[u8] = b"\x04\x0bstreamtyped\x81\xe8\x03\x84\x01@\x84\x84\x84\x19NSMutableAttributedString\x00\x84\x84\x12NSAttributedString\x00\x84\x84\x08NSObject\x00\x85\x92\x84\x84\x84\x08NSString\x01\x95\x84\x01+";
+    const RUN: &[u8] = b"\x86\x84\x02iI\x01";
+    const ATTRIBUTES: &[u8] = b"\x92\x84\x84\x84\x0cNSDictionary\x00\x95\x84\x01i\x01\x92\x84\x97\x97\x1d__kIMMessagePartAttributeName\x86\x92\x84\x84\x84\x08NSNumber\x00\x84\x84\x07NSValue\x00\x95\x84\x01*\x84\x9a\x9a\x00\x86\x86\x86";
+
+    let mut blob = Vec::with_capacity(HEADER.len() + text.len() + 64);
+    blob.extend_from_slice(HEADER);
+    push_length(&mut blob, text.len());
+    blob.extend_from_slice(text.as_bytes());
+    blob.extend_from_slice(RUN);
+    // Run lengths count UTF-16 code units, as NSAttributedString does
+    push_length(&mut blob, text.encode_utf16().count());
+    blob.extend_from_slice(ATTRIBUTES);
+    blob
+}
+
+/// typedstream integer: one byte below 0x80, else a 0x81 (i16) or 0x82 (i32) marker.
+fn push_length(blob: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        blob.push(len as u8);
+    } else if len <= i16::MAX as usize {
+        blob.push(0x81);
+        blob.extend_from_slice(&(len as u16).to_le_bytes());
+    } else {
+        blob.push(0x82);
+        blob.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+}
+
+/// Small seeded PRNG so generated databases are reproducible without a rand dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform-ish value in `0..n` (n > 0).
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
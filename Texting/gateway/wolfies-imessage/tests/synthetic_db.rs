@@ -0,0 +1,51 @@
+//! The synthetic chat.db generator produces data the real query paths read.
+
+mod support;
+
+use support::synthetic_db::{attributed_body, SyntheticConfig, SyntheticDb, COMMON_TERM};
+use wolfies_imessage::api;
+use wolfies_imessage::contacts::manager::ContactsManager;
+use wolfies_imessage::db::item_kind::ItemKind;
+use wolfies_imessage::db::{connection, typedstream};
+
+#[test]
+fn test_blob_matches_messages_archive() {
+    let plain = include_bytes!("../src/db/testdata/attributed_body/plain.bin");
+    assert_eq!(attributed_body("On my way, see you in 10"), plain.to_vec());
+
+    // Two-byte lengths, and UTF-16 run lengths for non-ASCII text
+    let long = format!("{} 🎉", "word ".repeat(60));
+    let decoded = typedstream::decode_attributed_body(&attributed_body(&long)).unwrap();
+    assert_eq!(decoded.text, long);
+    assert_eq!(decoded.runs[0].length, long.encode_utf16().count());
+}
+
+#[test]
+fn test_generated_db_serves_hot_queries() {
+    let db = SyntheticDb::create(SyntheticConfig::small());
+    let conn = connection::open_read_only(&db.path).unwrap();
+    let contacts = ContactsManager::empty();
+
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM message", [], |r| r.get(0)).unwrap();
+    assert_eq!(count, db.config.messages as i64);
+
+    let recent = api::recent(&conn, &contacts, &api::RecentOptions::default()).unwrap();
+    assert_eq!(recent.len(), 20);
+    // Blob-only rows decode to real text; image rows and system items get a placeholder instead
+    assert!(recent.iter().all(|m| !m.text.is_empty()
+        || m.placeholder.as_deref() == Some("[photo]")
+        || m.item_kind != ItemKind::Message));
+
+    let unread = api::unread(&conn, &contacts, &api::UnreadOptions::default()).unwrap();
+    assert!(!unread.is_empty() && unread.iter().all(|m| !m.is_from_me));
+
+    let opts = api::SearchOptions { query: Some(COMMON_TERM.to_string()), ..Default::default() };
+    let hits = api::text_search(&conn, &contacts, &opts).unwrap();
+    assert!(!hits.is_empty());
+
+    let stats = api::analytics(&conn, &contacts, &api::AnalyticsOptions::default()).unwrap();
+    assert!(stats.total_messages > 0);
+
+    let find = api::FindOptions::contact(db.phone(0));
+    assert!(!api::find(&conn, &contacts, &find).unwrap().is_empty());
+}
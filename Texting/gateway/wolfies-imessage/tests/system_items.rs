@@ -0,0 +1,66 @@
+//! System items (group renames, FaceTime calls) carry item_kind and a
+//! placeholder; find keeps them unless --exclude-system.
+
+mod support;
+
+use serde_json::Value;
+use std::process::Command;
+use support::synthetic_db::{SyntheticConfig, SyntheticDb};
+use wolfies_imessage::api;
+use wolfies_imessage::contacts::manager::ContactsManager;
+use wolfies_imessage::db::connection;
+use wolfies_imessage::db::item_kind::ItemKind;
+
+fn db() -> SyntheticDb {
+    SyntheticDb::create(SyntheticConfig { system_every: 7, ..SyntheticConfig::small() })
+}
+
+/// `find <contact 0> --json` plus `args`, as JSON.
+fn find(db: &SyntheticDb, args: &[&str]) -> Vec<Value> {
+    let output = Command::new(env!("CARGO_BIN_EXE_wolfies-imessage"))
+        .args(["find", &db.phone(0), "--limit", "500", "--json", "--no-cache", "--db-path"])
+        .arg(&db.path)
+        .args(args)
+        .env("IMESSAGE_CONTACTS_PATH", db.dir.path().join("contacts.json"))
+        .env("WOLFIES_IMESSAGE_HOME", db.dir.path().join("home"))
+        .env("WOLFIES_CONFIG", db.dir.path().join("config.toml"))
+        .output()
+        .expect("run wolfies-imessage");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_generated_system_items_are_classified() {
+    let db = db();
+    let conn = connection::open_read_only(&db.path).unwrap();
+    let opts = api::RecentOptions { limit: 500, ..Default::default() };
+    let recent = api::recent(&conn, &ContactsManager::empty(), &opts).unwrap();
+
+    let renames: Vec<_> = recent.iter().filter(|m| m.item_kind == ItemKind::GroupRename).collect();
+    let calls: Vec<_> = recent.iter().filter(|m| m.item_kind == ItemKind::Facetime).collect();
+    assert!(!renames.is_empty() && !calls.is_empty());
+    for rename in renames {
+        let placeholder = rename.placeholder.as_deref().unwrap();
+        assert!(rename.text.is_empty() && rename.is_group_chat, "{:?}", rename);
+        assert!(placeholder.starts_with("[renamed the group to chat") && placeholder.ends_with("(renamed)]"));
+    }
+    for call in calls {
+        assert!(call.text.is_empty() && !call.is_group_chat, "{:?}", call);
+        assert_eq!(call.placeholder.as_deref(), Some("[FaceTime call]"));
+    }
+}
+
+#[test]
+fn test_find_keeps_system_items_unless_excluded() {
+    let db = db();
+    let is_system = |m: &Value| m["item_kind"] != "message";
+
+    let all = find(&db, &[]);
+    let included = find(&db, &["--include-system"]);
+    let excluded = find(&db, &["--exclude-system"]);
+    assert!(all.iter().any(is_system));
+    assert_eq!(all, included);
+    assert!(!excluded.is_empty() && !excluded.iter().any(is_system));
+    assert_eq!(excluded.len(), all.iter().filter(|m| !is_system(m)).count());
+}
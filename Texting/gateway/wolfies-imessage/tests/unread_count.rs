@@ -0,0 +1,118 @@
+//! `unread --count-only` answers with one COUNT query and never reads
+//! contacts, and agrees with bundle and the daemon past any row limit.
+
+mod support;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+use support::synthetic_db::{SyntheticConfig, SyntheticDb, SCHEMA};
+use wolfies_imessage::api;
+use wolfies_imessage::contacts::manager::ContactsManager;
+use wolfies_imessage::daemon::service::DaemonService;
+use wolfies_imessage::db::{connection, queries};
+
+/// Run `unread` against `db` with a corrupt contacts.json, which warns on stderr if it is read.
+fn unread(db: &SyntheticDb, args: &[&str]) -> Output {
+    let contacts = db.dir.path().join("contacts.json");
+    std::fs::write(&contacts, "{ not json").unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_wolfies-imessage"))
+        .arg("unread")
+        .args(args)
+        .arg("--no-cache")
+        .arg("--db-path")
+        .arg(&db.path)
+        .env("IMESSAGE_CONTACTS_PATH", &contacts)
+        .env("WOLFIES_IMESSAGE_HOME", db.dir.path().join("home"))
+        .env("WOLFIES_CONFIG", db.dir.path().join("config.toml"))
+        .output()
+        .expect("run wolfies-imessage")
+}
+
+#[test]
+fn test_count_only_skips_contacts() {
+    let db = SyntheticDb::create(SyntheticConfig::small());
+
+    let output = unread(&db, &["--count-only"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "", "count-only must not read contacts.json");
+    let count: usize = String::from_utf8_lossy(&output.stdout).trim().parse().unwrap();
+    assert!(count > 0);
+
+    let output = unread(&db, &["--count-only", "--json"]);
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value, serde_json::json!({ "unread_count": count }));
+
+    // The full command does load contacts (and warns about the corrupt file)
+    let output = unread(&db, &["--limit", "500"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("continuing without contacts"));
+}
+
+/// One 1:1 chat with `unread` unread messages, plus read and sent ones.
+fn write_unread_db(path: &Path, unread: usize) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(SCHEMA).unwrap();
+    conn.execute("INSERT INTO handle (id) VALUES ('+14155550101')", []).unwrap();
+    conn.execute("INSERT INTO chat (guid, chat_identifier) VALUES ('iMessage;-;+14155550101', '+14155550101')", [])
+        .unwrap();
+    let now = queries::days_ago_cocoa(0);
+    for n in 0..unread + 20 {
+        // The first ten are read, the next ten sent
+        let (is_read, is_from_me) = (n < 10, (10..20).contains(&n));
+        conn.execute(
+            "INSERT INTO message (guid, text, handle_id, date, is_from_me, is_read, date_read)
+             VALUES (?1, ?2, 1, ?3, ?4, ?5, ?6)",
+            params![format!("m{}", n), format!("message {}", n), now - n as i64, is_from_me, is_read, is_read as i64],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO chat_message_join (chat_id, message_id) VALUES (1, ?1)", [conn.last_insert_rowid()])
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_unread_counts_agree_past_row_limits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("chat.db");
+    write_unread_db(&path, 150);
+    let cli = |args: &[&str]| -> Value {
+        let output = Command::new(env!("CARGO_BIN_EXE_wolfies-imessage"))
+            .args(args)
+            .args(["--json", "--no-cache", "--db-path"])
+            .arg(&path)
+            .env("IMESSAGE_CONTACTS_PATH", dir.path().join("contacts.json"))
+            .env("WOLFIES_IMESSAGE_HOME", dir.path().join("home"))
+            .env("WOLFIES_CONFIG", dir.path().join("config.toml"))
+            .output()
+            .expect("run wolfies-imessage");
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    assert_eq!(cli(&["unread", "--count-only"]), json!({ "unread_count": 150 }));
+    let bundle = cli(&["bundle", "--include", "unread_count,unread_messages", "--unread-limit", "500"]);
+    assert_eq!(bundle["unread_count"], 150);
+    assert_eq!(bundle["unread_messages"].as_array().unwrap().len(), 150);
+    // A smaller limit trims the list, not the count
+    let bundle = cli(&["bundle", "--include", "meta,unread_count,unread_messages"]);
+    assert_eq!(bundle["unread_count"], 150);
+    assert_eq!(bundle["unread_messages"].as_array().unwrap().len(), 20);
+    // Sections come from one snapshot, whose horizon meta reports
+    assert_eq!(bundle["meta"]["snapshot"], true);
+    assert_eq!(bundle["meta"]["max_rowid"], 170);
+
+    let conn = connection::open_read_only(&path).unwrap();
+    assert_eq!(api::unread_count(&conn, None).unwrap(), 150);
+    let service = DaemonService::from_parts(conn, ContactsManager::empty());
+    let params = |value: Value| -> HashMap<String, Value> { serde_json::from_value(value).unwrap() };
+    let bundle = service.dispatch("bundle", params(json!({"include": "unread_count"}))).unwrap();
+    assert_eq!(bundle["unread_count"], 150);
+    assert_eq!(bundle["meta"], json!({"snapshot": true, "max_rowid": 170}));
+    let unread = service.dispatch("unread", params(json!({"limit": 20}))).unwrap();
+    assert_eq!(unread["unread_count"], 150);
+    assert_eq!(unread["messages"].as_array().unwrap().len(), 20);
+}
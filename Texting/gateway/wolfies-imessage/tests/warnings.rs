@@ -0,0 +1,51 @@
+//! Non-fatal problems reach `--envelope` consumers in `meta.warnings` and
+//! everyone else as stderr lines after the output, never interleaved with it.
+
+mod support;
+
+use serde_json::Value;
+use support::fixture::FixtureDb;
+
+/// The fixture with a contacts.json that doesn't parse.
+fn corrupt_contacts() -> FixtureDb {
+    let db = FixtureDb::create();
+    std::fs::write(&db.contacts_path, "{ not json").unwrap();
+    db
+}
+
+#[test]
+fn test_corrupt_contacts_warning_on_stderr_or_in_meta() {
+    let db = corrupt_contacts();
+
+    // Plain JSON keeps its shape; the warning goes to stderr
+    let output = db.command(&["recent", "--limit", "2", "--json"]).output().unwrap();
+    assert!(output.status.success());
+    let recent: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(recent.as_array().unwrap().len(), 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("continuing without contacts").count(), 1, "{}", stderr);
+
+    // Under --envelope they sit in meta; either way, not on stderr too
+    let output = db.command(&["unread", "--envelope"]).output().unwrap();
+    assert!(output.status.success());
+    let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(value["meta"]["warnings"][0].as_str().unwrap().contains("continuing without contacts"));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+}
+
+#[test]
+fn test_text_mode_warnings_print_once_at_the_end() {
+    let db = corrupt_contacts();
+
+    let output = db.command(&["bundle", "--include", "unread_count,bogus"]).output().unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 1, "{}", stderr);
+    assert!(lines[0].starts_with("Warning: unknown bundle section 'bogus'"), "{}", stderr);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("unread_count"));
+
+    let output = db.command(&["recent", "--limit", "1"]).output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("continuing without contacts").count(), 1, "{}", stderr);
+}